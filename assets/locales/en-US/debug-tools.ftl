@@ -2,10 +2,15 @@ debug-tools = Debug Tools
 kinematic-colliders = Kinematic Colliders
 damage-regions = Damage Regions
 world-inspector = World Inspector
+entity-inspector = Entity Inspector
+entity-inspector-help = Click an entity, or use { $previous } and { $next } to cycle through them.
+entity-inspector-none = No entity selected.
 frame-time-diagnostics = Frame Time Diagnostics
 network-visualizer = Network Visualizer
 profiler = Profiler
 pathfinding-lines = Pathfinding Lines
+spatial-queries = Spatial Queries
+tile-materials = Tile Materials
 network-debug = Network Debug
 networking-disabled-message = Networking not enabled on this platform.
 
@@ -17,6 +22,11 @@ pause = Pause
 resume = Resume
 frame = Frame
 
+stage-profiler = Stage Profiler
+time-last = Last
+time-average = Average
+time-max = Max
+
 last-frame-with-skips = Last Frame With Skips
 last-skipped-frame-count = Last Skipped Frame Count
 no-frame-skips-detected = No frame skips detected.
@@ -30,3 +40,19 @@ player = Player
 snapshot = Snapshot
 take-snapshot = Take Snapshot
 restore-snapshot = Restore Snapshot
+
+desync-detected = Desync detected at frame { $frame } with player { $player }!
+input-violations = Player { $player } sent { $count } invalid inputs{ $flagged ->
+        [true] {" "}and has been flagged!
+       *[other] .
+    }
+
+heatmap = Heatmap
+heatmap-off = Off
+heatmap-deaths = Deaths
+heatmap-kills = Kills
+heatmap-pickups = Pickups
+
+map-warnings = Map Warnings ({ $count })
+map-warnings-none = No problems found in this map.
+metadata-problems = Metadata Problems ({ $count })
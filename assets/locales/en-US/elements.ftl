@@ -0,0 +1,44 @@
+# The names of the map elements, keyed by their name in lowercase with dashes for spaces.
+# Elements without a name here use the one in their metadata.
+element-anemones = Anemones
+element-blunderbass = Blunderbass
+element-buss = Buss
+element-cannon = Cannon
+element-cannonball = Cannonball
+element-coral-spikes = Coral Spikes
+element-crab = Crab
+element-crate = Crate
+element-decoy = Decoy
+element-door = Door
+element-elevator = Elevator
+element-fish-school = Fish School
+element-giant-crab = Giant Crab
+element-grenade = Grenade
+element-jellyfish = Jellyfish
+element-jellyfish-mount = Jellyfish Mount
+element-kick-bomb = Kick Bomb
+element-laser-rifle = Laser Rifle
+element-lava = Lava
+element-machinegun = MachineGun
+element-mine = Mine
+element-musket = Musket
+element-periscope = Periscope
+element-player-spawner = Player Spawner
+element-remote-charge = Remote Charge
+element-rope = Rope
+element-sea-slug = Sea Slug
+element-seaweed = Seaweed
+element-singularity = Singularity
+element-slippery = Slippery
+element-slippery-seaweed = Slippery Seaweed
+element-smoke-grenade = Smoke Grenade
+element-snail = Snail
+element-sniper-rifle = Sniper Rifle
+element-spikes = Spikes
+element-sproinger = Sproinger
+element-stomp-boots = Stomp Boots
+element-switch = Switch
+element-sword = Sword
+element-thief-fish = Thief Fish
+element-urchin = Urchin
+element-water = Water
@@ -0,0 +1,49 @@
+hot-seat-trap-phase = { $player } is setting traps: { $seconds }s
+hot-seat-survive-phase = { $player }, survive! { $seconds }s
+hot-seat-round-winner = { $player } survived the longest!
+hot-seat-round-draw = It's a draw!
+
+infection-time-left = Survive! { $seconds }s
+infection-teams = Survivors: { $survivors }  Infected: { $infected }
+infection-survivors-win = The survivors made it!
+infection-infected-win = Everybody got infected!
+
+kill-feed = { $attackers } eliminated { $victim }
+kill-feed-crushed = { $victim } was crushed
+kill-feed-spikes = { $victim } was impaled
+kill-feed-lava = { $victim } fell into lava
+rivalry-revenge = Revenge! { $killer } got back at { $victim }
+rivalry-domination = { $killer } is dominating { $victim }
+
+announcer-round-start = Round start!
+announcer-countdown = { $seconds }...
+announcer-first-blood = First blood!
+announcer-match-point = Match point!
+announcer-revenge = Revenge!
+announcer-domination = Domination!
+
+afk = AFK
+afk-warning = Still there? { $seconds }s
+
+training-menu = Training
+training-frame-step = { $freeze }: freeze, { $step }: next frame
+training-frozen = Frozen
+training-hit = KO!
+training-hit-distance = KO! ({ $distance } px)
+training-frame-data = { $state } (frame { $age })
+    velocity: { $x }, { $y }
+
+touch-jump = Jump
+touch-shoot = Shoot
+touch-grab = Grab
+touch-slide = Slide
+touch-pause = Pause
+
+map-vote = Vote for the next map: { $seconds }s
+map-vote-count = { $votes ->
+    [one] 1 vote
+   *[other] { $votes } votes
+}
+map-vote-player = P{ $player }
+map-vote-player-locked = P{ $player } (locked)
+map-vote-hint = Move left and right to pick a map, jump to lock in your vote, grab to change it.
@@ -4,4 +4,6 @@ waiting-for-map = Waiting for other player to select map...
 default-maps = Default Maps
 experimental-maps = Experimental Maps
 user-maps = User Maps
-builtin-maps = Builtin Maps
\ No newline at end of file
+builtin-maps = Builtin Maps
+# A map or element from a mod, tagged with the name of the mod
+mod-content = { $name } [{ $mod }]
@@ -1,3 +1,6 @@
+# The name of the language, shown in the language setting
+language-name = English
+
 # Menu Pages
 local-game = Local Game
 network-game = Network Game
@@ -7,6 +10,8 @@ main-menu = Main Menu
 map-editor = Map Editor
 settings = Settings
 paused = Paused
+match-still-running = The match keeps running while the menu is open.
+quit-to-lobby = Quit to Lobby
 credits = Credits
 
 # Actions
@@ -5,6 +5,10 @@ join = Join
 servers = Servers
 players = Players
 no-servers = No Servers
+direct-connect = Direct Connect
+host-address = Host Address
+connect = Connect
+copy = Copy
 server-name = Server Name
 start-server = Start Server
 stop-server = Stop Server
@@ -24,4 +28,10 @@ error = Error
 # Pop-ups
 disconnected = Disconnected
 disconnected-from-all = Disconnected from all other players.
+ping-ms = { $ping }ms
+player-ping = P{ $player }: { $ping }ms
+chat-player = P{ $player }:
+chat-hint = Press T to chat
+chat-mute = Mute P{ $player }
+chat-unmute = Unmute P{ $player }
 exit-match = Exit Match
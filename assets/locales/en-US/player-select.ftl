@@ -7,8 +7,11 @@ pick-a-hat = Pick a Hat
 player-select-ready = Ready!
 player-select-title = Player Select
 player-select-unready = Press { $button } to Unready
+player-select-starting-in = Starting in { $seconds }...
+player-select-waiting-for-ready = Waiting for players to ready up
 
-press-button-to-join = Press { $button } to Join
+press-button-to-join-with = Press { $button } to Join with { $device }
+keyboard-with-keys = Keyboard ({ $keys })
 press-button-to-confirm = Press { $button } to Confirm
 press-button-to-go-back = Press { $button } to Go Back
 press-button-to-lock-in = Press { $button } to Lock In
@@ -17,3 +20,28 @@ press-button-to-remove = Press { $button } to Remove
 add-ai-player = Add AI Player
 remove-ai-player = Remove AI Player
 ai-player = AI Player
+
+game-mode-elimination = Elimination
+game-mode-hot-seat = Hot Seat
+game-mode-bomb-tag = Bomb Tag
+game-mode-infection = Infection
+game-mode-training = Training
+drop-in = Drop-In
+mutator-low-gravity = Low Gravity
+mutator-mirror = Mirror World
+mutator-auto-pickup = Item Magnet
+mutator-explosives-only = Explosives Only
+mutator-one-hit-kill = One Hit Kill
+mutator-double-speed = Double Speed
+mutator-big-heads = Big Heads
+
+downloading-pack = Downloading { $pack }...
+pack-download-failed = Could not download asset pack { $error }
+packs-downloaded = Downloaded { $packs }. Restart the game to play with them.
+
+settings-mismatch = The match can't start until everybody has the same settings:
+settings-mismatch-version = Player { $player } is on version { $version }
+settings-mismatch-physics = Player { $player } has different physics settings
+settings-mismatch-core-assets = Player { $player } has modified game assets
+settings-mismatch-mutators = Player { $player } hasn't received the host's mutators yet
+settings-mismatch-pack = Player { $player } doesn't have the same { $pack } asset pack as you
@@ -12,12 +12,21 @@ clear-binding = Clear Binding
 keyboard-1 = Keyboard 1
 keyboard-2 = Keyboard 2
 gamepad = Gamepad
+all-gamepads = All Gamepads
+gamepad-n = Gamepad { $n }
+use-shared-gamepad-controls = Use the controls shared by all gamepads
+gamepad-rumble = Rumble
+conflicting-bindings = Some actions are bound to the same input.
 
 action = Action
 
 # Networking settings
 networking = Networking
 matchmaking-server = Matchmaking Server
+match-report-endpoint = Match Report URL
+match-report-endpoint-hint = Leave empty to only save reports to disk
+stream-overlay = Stream overlay server (ws://127.0.0.1:{ $port })
+lag-compensation = Lag compensation for hitscan weapons (when picking the map)
 
 # Audio settings
 audio = Audio
@@ -25,8 +34,32 @@ volume = Volume
 volume-main = Main
 volume-music = Music
 volume-effects = Effects
+volume-announcer = Announcer
 
 # Graphics settings
 graphics = Graphics
 fullscreen = Fullscreen
-no-graphics-settings-on-web = There are no graphics settings on web.
+minimap = Show minimap
+damage-numbers = Show damage numbers
+screen-flash = Flash on nearby explosions
+chromatic-aberration = Kill cam color fringes
+vignette = Vignette
+touch-controls = Show touch controls
+touch-controls-swap-sides = Joystick on the right
+touch-controls-opacity = Touch controls opacity
+touch-controls-size = Touch controls size
+
+# Accessibility settings
+accessibility = Accessibility
+player-outlines = Outline players with their color
+player-outline-thickness = Outline thickness
+language = Language
+language-system = System
+subtitles = Show announcer subtitles
+player-palette = Player colors
+player-palette-standard = Standard
+player-palette-red-green = Red-green safe
+player-palette-blue-yellow = Blue-yellow safe
+player-patterns = Mark players with patterns
+speak-focus = Speak focused menu items
+reduced-motion = Reduce motion and flashing
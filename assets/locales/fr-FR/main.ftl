@@ -1,7 +1,23 @@
+# Le nom de la langue, affiché dans le réglage de la langue
+language-name = Français
+
 # Main Menu
 start-game = Démarrer Jeu
 
 # Pause Menu
 paused = En Pause
 continue = Continuez
-main-menu = Menu Principal
\ No newline at end of file
+main-menu = Menu Principal
+
+# Settings
+language = Langue
+language-system = Système
+subtitles = Afficher les sous-titres du présentateur
+player-palette = Couleurs des joueurs
+player-palette-standard = Standard
+player-palette-red-green = Adaptées au rouge-vert
+player-palette-blue-yellow = Adaptées au bleu-jaune
+player-patterns = Marquer les joueurs avec des motifs
+speak-focus = Lire à voix haute les éléments du menu sélectionnés
+reduced-motion = Réduire les mouvements et les flashs
+damage-numbers = Afficher les dégâts
@@ -11,6 +11,8 @@ local KinematicBody = s"KinematicBody"
 local Transform = s"Transform"
 local DropItem = s"DropItem"
 local ItemUsed = s"ItemUsed"
+local ScriptedItem = s"ScriptedItem"
+local ScriptCollider = s"ScriptCollider"
 local IdleAnchor = s"IdleAnchor"
 local FallingAnchor = s"FallingAnchor"
 local DamageRegion = s"DamageRegion"
@@ -34,6 +36,7 @@ local function hydrate()
       components:insert(ent, sprite)
       components:insert(ent, components:get(spawner_ent, Transform))
       components:insert(ent, Item:create())
+      components:insert(ent, ScriptedItem:create())
       local item_grab = ItemGrab:create()
       item_grab.fin_anim = anchor_meta.fin_anim
       item_grab.grab_offset = anchor_meta.grab_offset
@@ -43,13 +46,14 @@ local function hydrate()
       dehydrate_out_of_bounds[0] = spawner_ent
       components:insert(ent, dehydrate_out_of_bounds)
       local body = KinematicBody:create()
-      -- TODO: Set the body shape and size. Doesn't work in lua yet because
-      -- the body shape is an enum.
       body.gravity = assets.root.core.physics.gravity
       body.has_mass = true
       body.has_friction = true
       body.bounciness = 0
       components:insert(ent, body)
+      local collider = ScriptCollider:create()
+      collider.size = anchor_meta.body_size
+      components:insert(ent, collider)
 
       -- Mark spawner as hydrated
       components:insert(spawner_ent, MapElementHydrated:create())
@@ -67,7 +71,6 @@ local function update()
 
     local used = components:get(ent, ItemUsed)
     if used then
-      -- components:remove(ent, ItemUsed)
       components:remove(ent, KinematicBody)
       components:remove(ent, IdleAnchor)
       components:insert(ent, FallingAnchor:create())
@@ -1,16 +1,24 @@
 use crate::prelude::*;
 
+pub mod announcer;
 pub mod music;
+pub mod spatial;
 use kira::sound::static_sound::StaticSoundSettings;
 pub use music::*;
+pub use spatial::{AudioListener, SpatialAudio, SpatialAudioMeta};
 
 pub fn game_plugin(game: &mut Game) {
     game.init_shared_resource::<AudioCenter>();
+    game.init_shared_resource::<SpatialAudio>();
+    game.init_shared_resource::<MusicLowPass>();
 
     let modified_session = game.sessions.modify_and_replace_existing_session(
         SessionNames::AUDIO,
         |session: &mut SessionBuilder| {
-            session.stages().add_system_to_stage(First, music_system);
+            session
+                .stages()
+                .add_system_to_stage(First, music_system)
+                .add_system_to_stage(First, spatial::spatial_audio_system);
         },
     );
 
@@ -0,0 +1,217 @@
+//! Announcer voice lines.
+//!
+//! The announcer calls out the flow of a match: the start of each round, the last seconds of
+//! timed game modes, the first death of a round, rounds where somebody is about to win the match,
+//! and revenge kills and dominations between rivals. Each line has its own cooldown so that the
+//! announcer doesn't talk over itself.
+//!
+//! The last line that was called out is kept in the [`AnnouncerState`], so that the HUD can show a
+//! subtitle for it. Lines are subtitled even when they don't have a sound.
+
+use crate::prelude::*;
+
+/// Install the announcer in a match session.
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session.init_resource::<AnnouncerState>();
+    session.add_system_to_stage(CoreStage::Last, announcer);
+}
+
+/// The voice lines of the announcer.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct AnnouncerMeta {
+    pub round_start: AnnouncerLineMeta,
+    /// The lines for the last seconds of a countdown, starting with one second left.
+    pub countdown: SVec<AnnouncerLineMeta>,
+    pub first_blood: AnnouncerLineMeta,
+    /// Played at the start of a round where a player can win the match.
+    pub match_point: AnnouncerLineMeta,
+    /// Played when a player kills somebody that was dominating them.
+    pub revenge: AnnouncerLineMeta,
+    /// Played when a player starts dominating another player.
+    pub domination: AnnouncerLineMeta,
+}
+
+/// A single announcer voice line.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct AnnouncerLineMeta {
+    /// The sound to play. Lines without a sound are only subtitled.
+    pub sound: Maybe<Handle<AudioSource>>,
+    pub volume: f64,
+    /// The shortest time between two plays of this line.
+    pub cooldown: Duration,
+}
+
+/// A line that the announcer may say.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AnnouncerLine {
+    RoundStart,
+    /// The number of seconds left in a countdown.
+    Countdown(u32),
+    FirstBlood,
+    MatchPoint,
+    Revenge,
+    Domination,
+}
+
+impl AnnouncerLine {
+    /// Get the localization key of the line's subtitle.
+    pub fn localization_key(self) -> &'static str {
+        match self {
+            AnnouncerLine::RoundStart => "announcer-round-start",
+            AnnouncerLine::Countdown(_) => "announcer-countdown",
+            AnnouncerLine::FirstBlood => "announcer-first-blood",
+            AnnouncerLine::MatchPoint => "announcer-match-point",
+            AnnouncerLine::Revenge => "announcer-revenge",
+            AnnouncerLine::Domination => "announcer-domination",
+        }
+    }
+
+    fn meta(self, meta: &AnnouncerMeta) -> Option<&AnnouncerLineMeta> {
+        match self {
+            AnnouncerLine::RoundStart => Some(&meta.round_start),
+            AnnouncerLine::Countdown(seconds) => {
+                meta.countdown.get((seconds as usize).checked_sub(1)?)
+            }
+            AnnouncerLine::FirstBlood => Some(&meta.first_blood),
+            AnnouncerLine::MatchPoint => Some(&meta.match_point),
+            AnnouncerLine::Revenge => Some(&meta.revenge),
+            AnnouncerLine::Domination => Some(&meta.domination),
+        }
+    }
+}
+
+/// Session resource tracking what the announcer has already said this round.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct AnnouncerState {
+    round_started: bool,
+    first_blood: bool,
+    /// The seconds left in the countdown that was called out last.
+    last_countdown: Option<u32>,
+    /// When each line was last played, in game time.
+    last_played: HashMap<AnnouncerLine, Duration>,
+    /// The last line that was called out and when, in game time.
+    last_line: Option<(AnnouncerLine, Duration)>,
+}
+
+impl AnnouncerState {
+    /// Get the last line that was called out and when it was, in game time.
+    pub fn last_line(&self) -> Option<(AnnouncerLine, Duration)> {
+        self.last_line
+    }
+
+    /// Play a line, unless it is still cooling down.
+    fn say(
+        &mut self,
+        line: AnnouncerLine,
+        now: Duration,
+        meta: &AnnouncerMeta,
+        volume_scale: f64,
+        audio_center: &mut AudioCenter,
+    ) {
+        let Some(line_meta) = line.meta(meta) else {
+            return;
+        };
+        if self
+            .last_played
+            .get(&line)
+            .is_some_and(|last| now < *last + line_meta.cooldown)
+        {
+            return;
+        }
+
+        self.last_played.insert(line, now);
+        self.last_line = Some((line, now));
+        if let Some(sound) = line_meta.sound.option() {
+            audio_center.play_sound(sound, line_meta.volume * volume_scale);
+        }
+    }
+}
+
+/// Get the seconds left in the countdown of the current game mode, if it has one running.
+fn countdown_seconds(
+    hot_seat: Option<&HotSeatState>,
+    bomb_tag: Option<&BombTagState>,
+    infection: Option<&InfectionState>,
+) -> Option<f32> {
+    if let Some(state) = hot_seat {
+        return (state.phase != HotSeatPhase::RoundOver).then(|| state.timer.remaining_secs());
+    }
+    if let Some(state) = bomb_tag {
+        return state.holder.map(|_| state.fuse.remaining_secs());
+    }
+    if let Some(state) = infection {
+        return (state.patient_zero.is_some() && state.winners.is_none())
+            .then(|| state.timer.remaining_secs());
+    }
+    None
+}
+
+fn announcer(
+    meta: Root<GameMeta>,
+    time: Res<Time>,
+    storage: Res<Storage>,
+    event_log: Res<MatchEventLog>,
+    rivalry_callouts: Res<RivalryCallouts>,
+    score: Res<MatchScore>,
+    player_inputs: Res<MatchInputs>,
+    hot_seat: Option<Res<HotSeatState>>,
+    bomb_tag: Option<Res<BombTagState>>,
+    infection: Option<Res<InfectionState>>,
+    mut state: ResMut<AnnouncerState>,
+    mut audio_center: ResMut<AudioCenter>,
+) {
+    let announcer = &meta.announcer;
+    let volume_scale = storage
+        .get::<Settings>()
+        .map(|x| x.announcer_volume as f64)
+        .unwrap_or(1.0);
+    let now = time.elapsed();
+    let mut say = |state: &mut AnnouncerState, line| {
+        state.say(line, now, announcer, volume_scale, &mut audio_center);
+    };
+
+    if !state.round_started {
+        state.round_started = true;
+        say(&mut state, AnnouncerLine::RoundStart);
+
+        let match_point = meta.core.config.winning_score_threshold as i32 - 1;
+        let is_match_point = (0..MAX_PLAYERS)
+            .filter(|i| player_inputs.players[*i as usize].active)
+            .any(|i| score.score(PlayerIdx(i)) >= match_point);
+        if is_match_point {
+            say(&mut state, AnnouncerLine::MatchPoint);
+        }
+    }
+
+    if !state.first_blood
+        && event_log
+            .events
+            .iter()
+            .any(|x| x.kind == MatchEventKind::Death)
+    {
+        state.first_blood = true;
+        say(&mut state, AnnouncerLine::FirstBlood);
+    }
+
+    for callout in rivalry_callouts.callouts.iter().filter(|x| x.age == 0) {
+        let line = match callout.kind {
+            RivalryKind::Revenge => AnnouncerLine::Revenge,
+            RivalryKind::Domination => AnnouncerLine::Domination,
+        };
+        say(&mut state, line);
+    }
+
+    let countdown = countdown_seconds(
+        hot_seat.as_deref(),
+        bomb_tag.as_deref(),
+        infection.as_deref(),
+    )
+    .map(|x| x.ceil() as u32)
+    .filter(|x| *x > 0 && *x as usize <= announcer.countdown.len());
+    if let Some(seconds) = countdown.filter(|_| countdown != state.last_countdown) {
+        say(&mut state, AnnouncerLine::Countdown(seconds));
+    }
+    state.last_countdown = countdown;
+}
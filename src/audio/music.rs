@@ -1,5 +1,9 @@
 use bones_framework::prelude::kira::{
     sound::{static_sound::StaticSoundSettings, PlaybackState, Region},
+    track::{
+        effect::filter::{FilterBuilder, FilterHandle},
+        TrackBuilder, TrackHandle,
+    },
     tween::Tween,
 };
 
@@ -35,12 +39,37 @@ pub const MUSIC_FADE_DURATION: Duration = Duration::from_millis(500);
 
 pub const MUSIC_VOLUME: f64 = 0.1;
 
+/// The cutoff frequency of the music's low-pass filter when it isn't muffled, in hertz, which is
+/// above what anyone can hear.
+pub const UNFILTERED_CUTOFF: f64 = 20_000.0;
+
+/// Shared resource with how muffled the music should be, set by the weather of the map being
+/// played.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct MusicLowPass {
+    /// The cutoff frequency of the music's low-pass filter in hertz, or `None` to leave the music
+    /// unfiltered.
+    pub cutoff: Option<f64>,
+}
+
+/// The mixer track that the music plays on, with the low-pass filter for muffling it.
+#[derive(HasSchema, Default)]
+#[schema(no_clone)]
+pub struct MusicTrack {
+    handles: Option<(TrackHandle, FilterHandle)>,
+    /// The cutoff frequency that the filter was last set to.
+    cutoff: f64,
+}
+
 /// System that plays music according to the game mode.
 pub(super) fn music_system(
     meta: Root<GameMeta>,
     mut audio: ResMut<AudioCenter>,
     mut shuffled_fight_music: ResMutInit<ShuffledPlaylist>,
     mut music_state: ResMutInit<MusicState>,
+    mut music_track: ResMutInit<MusicTrack>,
+    mut audio_manager: ResMut<AudioManager>,
+    music_low_pass: Res<MusicLowPass>,
     ctx: Res<EguiCtx>,
     sessions: Res<Sessions>,
 ) {
@@ -55,10 +84,38 @@ pub(super) fn music_system(
         duration: MUSIC_FADE_DURATION,
         easing: kira::tween::Easing::Linear,
     };
-    let play_settings = StaticSoundSettings::default()
+    let mut play_settings = StaticSoundSettings::default()
         .volume(MUSIC_VOLUME)
         .fade_in_tween(tween);
 
+    // Play the music through a track with a low-pass filter, so that it can be muffled
+    let music_track = &mut *music_track;
+    if music_track.handles.is_none() {
+        let mut builder = TrackBuilder::new();
+        let filter = builder.add_effect(FilterBuilder::new().cutoff(UNFILTERED_CUTOFF));
+        match audio_manager.add_sub_track(builder) {
+            Ok(track) => {
+                music_track.handles = Some((track, filter));
+                music_track.cutoff = UNFILTERED_CUTOFF;
+            }
+            Err(e) => warn!("Could not create the music track: {e}"),
+        }
+    }
+    if let Some((track, filter)) = &mut music_track.handles {
+        play_settings = play_settings.output_destination(&*track);
+
+        let cutoff = music_low_pass
+            .cutoff
+            .filter(|_| sessions.get(SessionNames::GAME).is_some())
+            .unwrap_or(UNFILTERED_CUTOFF);
+        if cutoff != music_track.cutoff {
+            if let Err(e) = filter.set_cutoff(cutoff, tween) {
+                warn!("Could not change the music filter: {e}");
+            }
+            music_track.cutoff = cutoff;
+        }
+    }
+
     // If we are in a game
     if sessions.get(SessionNames::GAME).is_some() {
         if let MusicState::Fight { idx } = &mut *music_state {
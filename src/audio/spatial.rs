@@ -0,0 +1,182 @@
+//! Positional audio for sounds that happen somewhere in the game world.
+//!
+//! The bones [`AudioCenter`] plays every sound in the middle of the stereo field, so sounds that
+//! have a position are queued in the [`SpatialAudio`] shared resource instead. The audio session
+//! then pans and attenuates them based on where they are relative to the game camera, which acts
+//! as the listener. Sounds that are behind solid tiles from the point of view of the listener are
+//! muffled.
+
+use kira::sound::static_sound::StaticSoundSettings;
+
+use crate::prelude::*;
+
+/// Install the system that keeps the audio listener on the game camera.
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .add_system_to_stage(CoreStage::Last, update_audio_listener)
+        .add_system_to_stage(CoreStage::Last, occlude_sounds);
+}
+
+/// Settings for how positional sounds are panned and attenuated.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct SpatialAudioMeta {
+    /// Sounds closer than this to the listener play at full volume, in pixels.
+    pub full_volume_distance: f32,
+    /// Sounds further than this from the listener play at the minimum volume, in pixels.
+    pub min_volume_distance: f32,
+    /// What the volume of the furthest sounds is multiplied by.
+    pub min_volume: f64,
+    /// The horizontal distance at which sounds are panned all the way to one side, in pixels.
+    pub pan_distance: f32,
+    /// How far to one side sounds may be panned, from `0.0` for not at all to `1.0` for fully.
+    pub max_pan: f64,
+    /// What the volume of sounds that are behind solid tiles is multiplied by.
+    pub occluded_volume: f64,
+}
+
+/// Where sounds are heard from in the game world.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AudioListener {
+    pub position: Vec2,
+    /// Whether left and right are swapped, like in mirror matches.
+    pub mirrored: bool,
+}
+
+/// A sound waiting to be played at a position.
+#[derive(Clone, Debug)]
+struct SpatialSound {
+    sound_source: Handle<AudioSource>,
+    volume: f64,
+    position: Vec2,
+    /// Whether there are solid tiles between the sound and the listener, once it has been checked.
+    occluded: Option<bool>,
+}
+
+/// Shared resource for playing sounds at a position in the game world.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct SpatialAudio {
+    listener: Option<AudioListener>,
+    queue: Vec<SpatialSound>,
+}
+
+impl SpatialAudio {
+    /// Play a sound that came from the given position in the game world.
+    pub fn play_sound_at(
+        &mut self,
+        sound_source: Handle<AudioSource>,
+        volume: f64,
+        position: Vec2,
+    ) {
+        self.queue.push(SpatialSound {
+            sound_source,
+            volume,
+            position,
+            occluded: None,
+        });
+    }
+
+    /// Get the volume and panning to play a sound at the given position with, where a panning of
+    /// `0.5` is centered.
+    fn volume_and_panning(
+        &self,
+        meta: &SpatialAudioMeta,
+        position: Vec2,
+        occluded: bool,
+    ) -> (f64, f64) {
+        let Some(listener) = self.listener else {
+            return (1.0, 0.5);
+        };
+        let offset = position - listener.position;
+
+        let falloff_range = (meta.min_volume_distance - meta.full_volume_distance).max(1.0);
+        let falloff =
+            ((offset.length() - meta.full_volume_distance) / falloff_range).clamp(0.0, 1.0) as f64;
+        let mut volume = 1.0 - falloff * (1.0 - meta.min_volume);
+        if occluded {
+            volume *= meta.occluded_volume;
+        }
+
+        let mut pan = (offset.x / meta.pan_distance.max(1.0)).clamp(-1.0, 1.0) as f64;
+        if listener.mirrored {
+            pan = -pan;
+        }
+        (volume, 0.5 + pan * meta.max_pan * 0.5)
+    }
+}
+
+/// Play the queued positional sounds.
+pub(super) fn spatial_audio_system(
+    meta: Root<GameMeta>,
+    assets: Res<AssetServer>,
+    storage: Res<Storage>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+    mut audio_manager: ResMut<AudioManager>,
+) {
+    let volume_scale = storage
+        .get::<Settings>()
+        .map(|x| (x.main_volume * x.effects_volume) as f64)
+        .unwrap_or(1.0);
+
+    for sound in std::mem::take(&mut spatial_audio.queue) {
+        let (volume, panning) = spatial_audio.volume_and_panning(
+            &meta.core.spatial_audio,
+            sound.position,
+            sound.occluded.unwrap_or_default(),
+        );
+        let settings = StaticSoundSettings::default()
+            .volume(sound.volume * volume * volume_scale)
+            .panning(panning);
+        let sound_data = assets.get(sound.sound_source).0.with_settings(settings);
+        if let Err(e) = audio_manager.play(sound_data) {
+            warn!("Could not play positional sound: {e}");
+        }
+    }
+}
+
+/// Listen from the game camera.
+fn update_audio_listener(
+    entities: Res<Entities>,
+    cameras: Comp<Camera>,
+    transforms: Comp<Transform>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+) {
+    spatial_audio.listener =
+        entities
+            .iter_with((&cameras, &transforms))
+            .next()
+            .map(|(_, (_, transform))| AudioListener {
+                position: transform.translation.xy(),
+                mirrored: transform.scale.x < 0.0,
+            });
+}
+
+/// Check which of the sounds played this frame can't be seen from the listener, by stepping along
+/// the line between them and looking for solid tiles.
+fn occlude_sounds(
+    map: Res<LoadedMap>,
+    collision_world: CollisionWorld,
+    mut spatial_audio: ResMut<SpatialAudio>,
+) {
+    let Some(listener) = spatial_audio.listener else {
+        return;
+    };
+    let step = map.tile_size.min_element() / 2.0;
+    if step <= 0.0 {
+        return;
+    }
+
+    for sound in spatial_audio
+        .queue
+        .iter_mut()
+        .filter(|x| x.occluded.is_none())
+    {
+        let offset = listener.position - sound.position;
+        let steps = (offset.length() / step).ceil() as u32;
+        let occluded = (1..steps).any(|i| {
+            let point = sound.position + offset * (i as f32 / steps as f32);
+            collision_world.tile_collision_point(point) == TileCollisionKind::Solid
+        });
+        sound.occluded = Some(occluded);
+    }
+}
@@ -0,0 +1,152 @@
+//! Benchmark of the core simulation, run by starting the game with `--bench-sim [frames]`.
+//!
+//! Instead of opening the main menu, the game starts a match between AI players on the first stable
+//! map once the assets are loaded. All of the frames are simulated in one go, without rendering or
+//! waiting for real time in between, and then the time each stage took per frame is printed and the
+//! game exits. This makes it possible to measure performance motivated changes to the element
+//! systems. The stages are timed with the same [`StageTimings`] as the profiler in the debug tools.
+//!
+//! The match is played with the training rules so that rounds never end, but without the debug
+//! drawing that training matches normally have.
+
+use crate::{core::MatchPlugin, prelude::*};
+
+/// How many frames are simulated if `--bench-sim` isn't given a number.
+pub const DEFAULT_BENCH_FRAMES: u32 = 3600;
+
+/// How many AI players fight in the benchmark.
+pub const BENCH_PLAYERS: usize = 4;
+
+/// Get how many frames to benchmark the simulation for, if the game was started with
+/// `--bench-sim`.
+pub fn bench_sim_frames() -> Option<u32> {
+    let mut args = std::env::args().skip_while(|x| x != "--bench-sim");
+    args.next()?;
+    Some(
+        args.next()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(DEFAULT_BENCH_FRAMES),
+    )
+}
+
+/// Install the benchmark, which starts as soon as the game is stepped for the first time.
+pub fn game_plugin(game: &mut Game, frames: u32) {
+    game.insert_shared_resource(BenchSim {
+        frames,
+        started: false,
+    });
+    game.systems.add_before_system(start_bench_sim);
+}
+
+/// Shared resource with the benchmark settings.
+#[derive(HasSchema, Clone, Debug, Default)]
+struct BenchSim {
+    frames: u32,
+    started: bool,
+}
+
+/// Start the benchmark match.
+fn start_bench_sim(game: &mut Game) {
+    let frames = {
+        let Some(mut bench) = game.shared_resource_mut::<BenchSim>() else {
+            return;
+        };
+        if bench.started {
+            return;
+        }
+        bench.started = true;
+        bench.frames
+    };
+
+    let (map, player, plugins) = {
+        let assets = game.shared_resource::<AssetServer>().unwrap();
+        let meta = assets.root::<GameMeta>();
+        let (Some(map), Some(player)) = (
+            meta.core.stable_maps.first().copied(),
+            meta.core.players.first().copied(),
+        ) else {
+            error!("Can't benchmark the simulation without any maps or players");
+            std::process::exit(1);
+        };
+        (map, player, meta.get_plugins(&assets))
+    };
+
+    game.sessions.create_with(SessionNames::GAME, |builder| {
+        builder.install_plugin(MatchPlugin {
+            maps: MapPool::from_single_map(map),
+            player_info: std::array::from_fn(|i| PlayerInput {
+                active: i < BENCH_PLAYERS,
+                selected_player: player,
+                selected_hat: None,
+                control: default(),
+                editor_input: default(),
+                control_source: None,
+                is_ai: true,
+            }),
+            plugins,
+            mode: GameMode::Training,
+            mutators: default(),
+            lag_compensation: default(),
+            drop_in: default(),
+            session_runner: Box::new(BenchSimRunner { frames }),
+            score: default(),
+        });
+        builder.insert_resource(DebugSettings::default());
+        builder.insert_resource(StageTimings {
+            capacity: frames as usize,
+            ..default()
+        });
+    });
+}
+
+/// Session runner that simulates every benchmarked frame the first time it is stepped, prints the
+/// timings, and exits.
+struct BenchSimRunner {
+    frames: u32,
+}
+
+impl SessionRunner for BenchSimRunner {
+    fn step(&mut self, _frame_start: Instant, world: &mut World, stages: &mut SystemStages) {
+        let step = Duration::from_secs_f64(1.0 / FPS as f64);
+        for _ in 0..self.frames {
+            world.resource_mut::<Time>().advance_exact(step);
+            stages.run(world);
+        }
+
+        println!("Simulated {} frames", self.frames);
+        println!(
+            "{:<12} {:>10} {:>10} {:>10} {:>10}",
+            "stage", "p50", "p90", "p99", "max"
+        );
+        let timings = world.resource::<StageTimings>();
+        for (i, stage) in timings.stages.iter().enumerate() {
+            print_percentiles(stage, timings.stage_times(i).collect());
+        }
+        print_percentiles("Frame", timings.frame_times().collect());
+
+        std::process::exit(0);
+    }
+
+    fn restart_session(&mut self) {}
+
+    fn disable_local_input(&mut self, _disable_input: bool) {}
+}
+
+/// Print a row of the timing table.
+fn print_percentiles(label: &str, mut durations: Vec<Duration>) {
+    if durations.is_empty() {
+        return;
+    }
+    durations.sort();
+    let percentile = |p: f64| {
+        let i = ((durations.len() - 1) as f64 * p).round() as usize;
+        format!("{:.3}ms", durations[i].as_secs_f64() * 1000.0)
+    };
+    println!(
+        "{label:<12} {:>10} {:>10} {:>10} {:>10}",
+        percentile(0.5),
+        percentile(0.9),
+        percentile(0.99),
+        percentile(1.0),
+    );
+}
@@ -1,23 +1,42 @@
+pub mod afk;
 pub mod attachment;
 pub mod bullet;
 pub mod camera;
 pub mod damage;
 pub mod debug;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod desync;
+pub mod drop_in;
 pub mod editor;
 pub mod elements;
+pub mod game_mode;
 pub mod globals;
+pub mod heatmap;
 pub mod input;
+pub mod input_validation;
 pub mod item;
+pub mod lag_compensation;
 pub mod lifetime;
 pub mod map;
 pub mod map_constructor;
+pub mod map_events;
+pub mod map_file;
 pub mod map_pool;
+pub mod map_validation;
+pub mod map_vote;
+pub mod match_report;
 pub mod metadata;
+pub mod mutators;
+pub mod particles;
 pub mod physics;
 pub mod player;
 pub mod random;
+pub mod reduced_motion;
 pub mod scoring;
+pub mod scripting;
+pub mod trail;
 pub mod utils;
+pub mod weather;
 pub mod win_indicator;
 
 /// The target fixed frames-per-second that the game sumulation runs at.
@@ -32,10 +51,13 @@ use crate::{prelude::*, settings::PlayerControlMapping};
 
 pub mod prelude {
     pub use super::{
-        attachment::*, bullet::*, camera::*, damage::*, debug::*, editor::*, elements::prelude::*,
-        flappy_jellyfish::*, globals::*, input::*, item::*, lifetime::*, map::*,
-        map_constructor::*, map_pool::*, metadata::*, physics::*, player::*, random::*, scoring::*,
-        utils::*, win_indicator::*, FPS, MAX_PLAYERS,
+        afk::*, attachment::*, bullet::*, camera::*, damage::*, debug::*, drop_in::*, editor::*,
+        elements::prelude::*, flappy_jellyfish::*, game_mode::*, globals::*, heatmap::*, input::*,
+        input_validation::*, item::*, lag_compensation::*, lifetime::*, map::*, map_constructor::*,
+        map_events::*, map_file::*, map_pool::*, map_validation::*, map_vote::*, match_report::*,
+        metadata::*, mutators::*, particles::*, physics::*, player::*, random::*,
+        reduced_motion::*, scoring::*, scripting::*, trail::*, utils::*, weather::*,
+        win_indicator::*, Hitstop, TimeScale, FPS, MAX_PLAYERS,
     };
 }
 
@@ -45,6 +67,7 @@ pub fn game_plugin(game: &mut Game) {
     HatMeta::register_schema();
     MapMeta::register_schema();
     game.install_plugin(elements::game_plugin)
+        .install_plugin(metadata::validation::game_plugin)
         .install_plugin(bullet::game_plugin)
         .install_plugin(win_indicator::game_plugin)
         .init_shared_resource::<AssetServer>();
@@ -55,6 +78,14 @@ pub struct MatchPlugin {
     pub player_info: [PlayerInput; MAX_PLAYERS as usize],
     /// The lua plugins to enable for this match.
     pub plugins: Arc<Vec<Handle<LuaPlugin>>>,
+    /// The game mode to play.
+    pub mode: GameMode,
+    /// The mutators enabled on top of the game mode.
+    pub mutators: Mutators,
+    /// The lag compensation settings picked by the host.
+    pub lag_compensation: LagCompensation,
+    /// Whether new local players may join during the match, and who is waiting to.
+    pub drop_in: DropIn,
 
     /// Tracks score for match. Should be default if installing for
     /// new match, but if restarting MatchPlugin to transition between rounds,
@@ -79,19 +110,45 @@ impl SessionPlugin for MatchPlugin {
 
         physics::install(session);
         input::install(session);
+        input_validation::install(session);
         map::install(session);
+        map_events::install(session);
         player::plugin(session);
+        afk::install(session);
         elements::session_plugin(session);
         damage::install(session);
         camera::install(session);
         lifetime::install(session);
+        particles::install(session);
+        trail::install(session);
+        weather::install(session);
         random::plugin(session);
         debug::plugin(session);
         item::install(session);
         attachment::install(session);
         bullet::session_plugin(session);
         editor::install(session);
-        scoring::session_plugin(session);
+        map_validation::install(session);
+        map_vote::install(session);
+        scripting::install(session);
+        game_mode::install(session, self.mode);
+        // Players that dropped in during the last round join this one
+        let mut drop_in = self.drop_in;
+        let mut player_info = self.player_info;
+        let mut score = self.score;
+        drop_in.admit(&mut player_info, &mut score);
+        drop_in::install(session, drop_in);
+        mutators::install(session, self.mutators);
+        lag_compensation::install(session, self.lag_compensation);
+        #[cfg(not(target_arch = "wasm32"))]
+        desync::install(session);
+        heatmap::install(session);
+        match_report::install(session);
+        crate::audio::announcer::session_plugin(session);
+        crate::audio::spatial::session_plugin(session);
+        crate::input::rumble::session_plugin(session);
+        // Installed last so that it can time everything else
+        debug::install_stage_timers(session);
 
         let current_map = self.maps.current_map;
         session.insert_resource(self.maps);
@@ -106,13 +163,45 @@ impl SessionPlugin for MatchPlugin {
         );
 
         session.insert_resource(MatchInputs {
-            players: self.player_info,
+            players: player_info,
         });
-        session.insert_resource(self.score);
+        session.insert_resource(score);
         session.set_session_runner(self.session_runner);
     }
 }
 
+/// Session resource controlling how fast the simulation runs compared to real time.
+///
+/// A scale of `0.5`, for example, runs the fixed update half as often, which plays the match in
+/// slow motion without changing the simulation itself. Only local matches respect the time scale,
+/// since network matches have to keep up with the remote players.
+#[derive(HasSchema, Clone, Copy, Debug)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Session resource that freezes the simulation for a few frames when a hit lands, to give it some
+/// weight.
+///
+/// Like the [`TimeScale`], only local matches respect the hitstop.
+#[derive(HasSchema, Clone, Copy, Debug, Default)]
+pub struct Hitstop {
+    /// How many more frames the simulation stays frozen for.
+    pub frames: u32,
+}
+
+impl Hitstop {
+    /// Freeze the simulation for the given duration, unless it is already frozen for longer.
+    pub fn trigger(&mut self, duration: Duration) {
+        let frames = (duration.as_secs_f32() * FPS).round() as u32;
+        self.frames = self.frames.max(frames);
+    }
+}
+
 #[derive(Default)]
 pub struct JumpyDefaultMatchRunner {
     /// The jumpy match runner has it's own input collector instead of using the global one, because
@@ -127,8 +216,12 @@ pub struct JumpyDefaultMatchRunner {
 impl SessionRunner for JumpyDefaultMatchRunner {
     fn step(&mut self, frame_start: Instant, world: &mut World, stages: &mut SystemStages) {
         pub const STEP: f64 = 1.0 / FPS as f64;
+        /// The longest time that is simulated in a single step. The runner isn't stepped while the
+        /// session is paused, so this keeps the time spent in the pause menu from being caught up
+        /// on when it is closed.
+        const MAX_DELTA: f64 = STEP * 4.0;
         let last_run = self.last_run.unwrap_or(frame_start);
-        let delta = (frame_start - last_run).as_secs_f64();
+        let delta = (frame_start - last_run).as_secs_f64().min(MAX_DELTA);
 
         {
             let keyboard = world.resource::<KeyboardInputs>();
@@ -140,7 +233,23 @@ impl SessionRunner for JumpyDefaultMatchRunner {
             );
         }
 
+        // Training matches may be frozen, in which case this is whether to advance one frame
+        let frozen_step = world.get_resource::<FrameStep>().is_some().then(|| {
+            let mut frame_step = world.resource_mut::<FrameStep>();
+            frame_step.handle_keyboard(&world.resource::<KeyboardInputs>());
+            frame_step.frozen.then(|| frame_step.take_step())
+        });
+
         let mut run = || {
+            // Hold everything still while a hit lands
+            if world
+                .get_resource::<Hitstop>()
+                .is_some_and(|x| x.frames > 0)
+            {
+                world.resource_mut::<Hitstop>().frames -= 1;
+                return;
+            }
+
             // Advance the world time
             world
                 .resource_mut::<Time>()
@@ -172,7 +281,16 @@ impl SessionRunner for JumpyDefaultMatchRunner {
             stages.run(world);
         };
 
-        self.accumulator += delta;
+        let time_scale = world
+            .resources
+            .get::<TimeScale>()
+            .map(|x| x.0 as f64)
+            .unwrap_or(1.0);
+        self.accumulator += delta * time_scale;
+
+        if let Some(step) = frozen_step.flatten() {
+            self.accumulator = if step { STEP } else { 0.0 };
+        }
 
         let loop_start = Instant::now();
         loop {
@@ -0,0 +1,109 @@
+//! Detection of players that have gone away from their controls.
+//!
+//! Players that don't touch their controls for long enough are flagged as away from keyboard. AFK
+//! players can't be hurt, so that they don't hand out free kills, but they can't win rounds either.
+//! In network matches they may also be taken out of the round after a while, so that everybody
+//! else doesn't have to hunt them down.
+//!
+//! The idle times are counted from the match inputs, so every client in a network match agrees on
+//! who is AFK.
+
+use crate::prelude::*;
+
+/// How long AFK players are made invincible for at a time. They are made invincible again every
+/// frame for as long as they stay away.
+const AFK_INVINCIBILITY_TIME: Duration = Duration::from_millis(250);
+
+/// Install this module.
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<AfkPlayers>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, detect_afk_players);
+}
+
+/// Settings for detecting AFK players.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct AfkMeta {
+    /// How long a player can go without input before they are warned that they are about to be
+    /// flagged as AFK.
+    pub warning_time: Duration,
+    /// How long a player can go without input before they are flagged as AFK.
+    pub idle_time: Duration,
+    /// Whether AFK players are taken out of the round in network matches.
+    pub remove_in_network: bool,
+    /// How long a player can go without input before they are taken out of the round, if
+    /// [`remove_in_network`][Self::remove_in_network] is set.
+    pub remove_time: Duration,
+}
+
+/// Session resource tracking how long each player has gone without input.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct AfkPlayers {
+    /// How long each player has gone without input.
+    pub idle: [Duration; MAX_PLAYERS as usize],
+    /// Whether each player is flagged as AFK.
+    pub afk: [bool; MAX_PLAYERS as usize],
+}
+
+impl AfkPlayers {
+    /// Whether the given player is flagged as AFK.
+    pub fn is_afk(&self, player: PlayerIdx) -> bool {
+        self.afk[player.0 as usize]
+    }
+
+    /// Whether the given player should be warned that they are about to be flagged as AFK.
+    pub fn is_warned(&self, player: PlayerIdx, meta: &AfkMeta) -> bool {
+        !self.is_afk(player) && self.idle[player.0 as usize] >= meta.warning_time
+    }
+}
+
+/// Count how long each human player with a fish of their own has gone without input, and flag the
+/// ones that have been idle for too long.
+fn detect_afk_players(
+    meta: Root<GameMeta>,
+    time: Res<Time>,
+    mode: Res<GameMode>,
+    entities: Res<Entities>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    mut invincibles: CompMut<Invincibility>,
+    mut afk_players: ResMut<AfkPlayers>,
+    mut commands: Commands,
+    #[cfg(not(target_arch = "wasm32"))] syncing_info: Option<Res<SyncingInfo>>,
+) {
+    let afk = &meta.core.afk;
+    #[allow(unused_mut)]
+    let mut is_online = false;
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        is_online = syncing_info.is_some_and(|x| x.is_online());
+    }
+
+    for (i, input) in player_inputs.players.iter().enumerate() {
+        if input.is_ai || !mode.spawns_player(i as u32, &player_inputs) || !input.control.is_idle()
+        {
+            afk_players.idle[i] = Duration::ZERO;
+            afk_players.afk[i] = false;
+            continue;
+        }
+        afk_players.idle[i] += time.delta();
+        afk_players.afk[i] = afk_players.idle[i] >= afk.idle_time;
+    }
+
+    for (player_ent, player_idx) in entities.iter_with(&player_indexes) {
+        let i = player_idx.0 as usize;
+        if !afk_players.afk[i] || killed_players.contains(player_ent) {
+            continue;
+        }
+
+        if is_online && afk.remove_in_network && afk_players.idle[i] >= afk.remove_time {
+            commands.add(PlayerCommand::kill(player_ent, None));
+            continue;
+        }
+
+        invincibles.insert(player_ent, Invincibility::new(AFK_INVINCIBILITY_TIME));
+    }
+}
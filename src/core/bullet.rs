@@ -1,6 +1,7 @@
 //! Generic bullet implementation.
 //!
-//! These components are used for things like the musket and sniper rifle bullets.
+//! These components are used for things like the musket and sniper rifle bullets. Weapons that
+//! hit instantly, like the laser rifle, use [`hitscan`] instead of spawning a bullet.
 
 use crate::prelude::*;
 
@@ -28,6 +29,8 @@ pub struct Bullet {
     pub direction: Vec2,
     /// The player entity that shot the bullet.
     pub owner: Entity,
+    /// How many times the bullet has bounced off of walls.
+    pub bounces: u32,
 }
 
 #[derive(HasSchema, Clone, Debug, Default)]
@@ -39,6 +42,11 @@ pub struct BulletMeta {
     pub atlas: Handle<Atlas>,
 
     pub lifetime: f32,
+    /// How many times the bullet bounces off of walls before it is stopped by one.
+    pub bounces: u32,
+    /// Whether the bullet keeps going after hitting a player, instead of stopping.
+    pub penetrates_players: bool,
+
     pub explosion_fps: f32,
     pub explosion_volume: f64,
     pub explosion_lifetime: f32,
@@ -47,6 +55,39 @@ pub struct BulletMeta {
     pub explosion_sound: Handle<AudioSource>,
 }
 
+/// Where a [`hitscan`] shot ended.
+#[derive(Clone, Copy, Debug)]
+pub struct HitscanTrace {
+    /// The point where the shot stopped.
+    pub end: Vec2,
+    /// Whether the shot was stopped by a wall before reaching its range.
+    pub hit_wall: bool,
+}
+
+/// Trace a shot that hits instantly from `origin` in `direction`, stopping at the first wall or
+/// after `range` pixels.
+///
+/// Only walls stop the shot. Weapons hit the players along it by covering the line from `origin`
+/// to [`HitscanTrace::end`] with a damage region.
+pub fn hitscan(
+    collision_world: &CollisionWorld,
+    origin: Vec2,
+    direction: Vec2,
+    range: f32,
+) -> HitscanTrace {
+    let direction = direction.normalize_or_zero();
+    match collision_world.cast_ray_solid(origin, direction, range) {
+        Some(distance) => HitscanTrace {
+            end: origin + direction * distance,
+            hit_wall: true,
+        },
+        None => HitscanTrace {
+            end: origin + direction * range,
+            hit_wall: false,
+        },
+    }
+}
+
 /// Component containing the bullet's metadata handle.
 #[derive(Deref, DerefMut, HasSchema, Default, Clone)]
 #[repr(C)]
@@ -109,8 +150,9 @@ fn update(
     collision_world: CollisionWorld,
     mut transforms: CompMut<Transform>,
     mut bullets: CompMut<Bullet>,
-    mut audio_center: ResMut<AudioCenter>,
+    mut spatial_audio: ResMut<SpatialAudio>,
     invincibles: CompMut<Invincibility>,
+    killed_players: Comp<PlayerKilled>,
     mut emote_regions: CompMut<EmoteRegion>,
     asset_server: Res<AssetServer>,
     time: Res<Time>,
@@ -121,6 +163,8 @@ fn update(
         let BulletMeta {
             speed,
             body_diameter,
+            bounces,
+            penetrates_players,
             explosion_fps,
             explosion_volume,
             explosion_sound,
@@ -130,12 +174,39 @@ fn update(
             ..
         } = &*bullet_meta;
 
+        let shape = ColliderShape::Circle {
+            diameter: *body_diameter,
+        };
+
         // Move bullet
         let position = {
             let position = transforms.get_mut(entity).unwrap();
             let delta_time = time.delta_seconds();
+            let previous = position.translation;
             position.translation += (bullet.direction * *speed * delta_time).extend(0.0);
 
+            // Bounce off of walls by reflecting the direction along the axis that hit the wall
+            if bullet.bounces < *bounces
+                && collision_world.tile_collision(*position, shape) == TileCollisionKind::Solid
+            {
+                let mut x_moved = *position;
+                x_moved.translation.y = previous.y;
+                let mut y_moved = *position;
+                y_moved.translation.x = previous.x;
+                let hit_x =
+                    collision_world.tile_collision(x_moved, shape) == TileCollisionKind::Solid;
+                let hit_y =
+                    collision_world.tile_collision(y_moved, shape) == TileCollisionKind::Solid;
+                match (hit_x, hit_y) {
+                    (true, false) => bullet.direction.x = -bullet.direction.x,
+                    (false, true) => bullet.direction.y = -bullet.direction.y,
+                    // Hit a corner straight on
+                    _ => bullet.direction = -bullet.direction,
+                }
+                position.translation = previous;
+                bullet.bounces += 1;
+            }
+
             let emote_size = Vec2::new(*body_diameter * 6.0, *body_diameter * 3.5);
             emote_regions.insert(entity, EmoteRegion::basic(Emote::Alarm, emote_size, true));
 
@@ -146,26 +217,31 @@ fn update(
         let mut hit_player = false;
         collision_world
             .actor_collisions_filtered(entity, |e| {
-                player_indexes.contains(e) && invincibles.get(e).is_none()
+                player_indexes.contains(e)
+                    && invincibles.get(e).is_none()
+                    && !killed_players.contains(e)
             })
             .into_iter()
             .filter(|player| *player != bullet.owner)
             .for_each(|player| {
                 hit_player = true;
-                commands.add(PlayerCommand::kill(player, Some(position.translation.xy())));
+                commands.add(PlayerCommand::kill_by(
+                    player,
+                    Some(position.translation.xy()),
+                    Some(bullet.owner),
+                ));
             });
 
         // check solid tile collisions
-        let hit_solid = collision_world.tile_collision(
-            position,
-            ColliderShape::Circle {
-                diameter: *body_diameter,
-            },
-        ) == TileCollisionKind::Solid;
+        let hit_solid = collision_world.tile_collision(position, shape) == TileCollisionKind::Solid;
 
         // Bullet hit something
-        if hit_player || hit_solid {
-            audio_center.play_sound(*explosion_sound, *explosion_volume);
+        if (hit_player && !*penetrates_players) || hit_solid {
+            spatial_audio.play_sound_at(
+                *explosion_sound,
+                *explosion_volume,
+                position.translation.xy(),
+            );
 
             let mut explosion_transform = *transforms.get(entity).unwrap();
             explosion_transform.translation.z += 1.0;
@@ -180,7 +256,8 @@ fn update(
                       mut transforms: CompMut<Transform>,
                       mut lifetimes: CompMut<Lifetime>,
                       mut sprites: CompMut<AtlasSprite>,
-                      mut animated_sprites: CompMut<AnimatedSprite>| {
+                      mut animated_sprites: CompMut<AnimatedSprite>,
+                      storage: Res<Storage>| {
                     // Despawn the bullet
                     entities.kill(entity);
 
@@ -198,12 +275,12 @@ fn update(
 
                         animated_sprites.insert(
                             ent,
-                            AnimatedSprite {
-                                frames: (0..explosion_frames).collect(),
-                                fps: explosion_fps,
-                                repeat: false,
-                                ..default()
-                            },
+                            explosion_animation(
+                                explosion_frames,
+                                explosion_fps,
+                                explosion_lifetime,
+                                &storage,
+                            ),
                         );
                         lifetimes.insert(ent, Lifetime::new(explosion_lifetime));
                     }
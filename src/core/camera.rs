@@ -10,6 +10,35 @@ pub fn install(session: &mut SessionBuilder) {
     session
         .stages
         .add_system_to_stage(CoreStage::Last, camera_parallax);
+    session
+        .stages
+        .add_system_to_stage(CoreStage::Last, apply_camera_shake_events);
+}
+
+/// A kind of event that shakes the camera, each with its own [`CameraShakeProfile`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraShakeKind {
+    Explosion,
+    /// A player landing after a long fall.
+    Landing,
+    /// A player being hit by a melee attack.
+    MeleeHit,
+}
+
+/// Session resource used to shake the camera.
+///
+/// This is used instead of sending trauma directly, so that every kind of event can have its
+/// own feel, configured in the [`CameraShakeMeta`].
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct CameraShakeEvents {
+    queue: Vec<CameraShakeKind>,
+}
+
+impl CameraShakeEvents {
+    /// Shake the camera with the profile for the given kind of event.
+    pub fn send(&mut self, kind: CameraShakeKind) {
+        self.queue.push(kind);
+    }
 }
 
 /// A sprite that is spawned as a part of the parallax background.
@@ -43,6 +72,8 @@ pub struct CameraState {
     /// Disables the default camera controller. Useful, for example, when taking over the camera
     /// from the editor.
     pub disable_controller: bool,
+    /// An entity to zoom in on instead of framing all of the camera subjects.
+    pub focus: Option<Entity>,
 }
 
 /// Implemenets the camera controller.
@@ -58,7 +89,7 @@ fn camera_controller(
     bodies: Comp<KinematicBody>,
     window: Res<Window>,
 ) {
-    let meta = &meta.core.camera;
+    let meta = &meta.core.camera.with_map_overrides(&map.camera);
 
     let Some((_ent, (camera, camera_shake, camera_state))) = entities
         .iter_with((&mut cameras, &mut camera_shakes, &camera_states))
@@ -128,20 +159,49 @@ fn camera_controller(
     let mut scale = camera_height / default_height;
     let map_size = map.grid_size.as_vec2() * map.tile_size;
 
+    // Get the areas that need to stay on screen, and the borders to keep around them
+    let (subject_rects, border_min, border_max) = match meta.framing {
+        CameraFraming::Tight => (
+            entities
+                .iter_with((&camera_subjects, &transforms, &bodies))
+                .map(|(_ent, (_subj, transform, body))| body.bounding_box(*transform))
+                .collect::<Vec<_>>(),
+            vec2(meta.border_left, meta.border_bottom),
+            vec2(meta.border_right, meta.border_top),
+        ),
+        CameraFraming::Loose => (
+            camera_subjects.iter().map(|x| x.rect).collect(),
+            vec2(meta.border_left, meta.border_bottom),
+            vec2(meta.border_right, meta.border_top),
+        ),
+        CameraFraming::FixedZone => {
+            let zone = map.camera.fixed_zone.option().unwrap_or(CameraZone {
+                min: Vec2::ZERO,
+                max: map_size,
+            });
+            (
+                vec![Rect {
+                    min: zone.min,
+                    max: zone.max,
+                }],
+                Vec2::ZERO,
+                Vec2::ZERO,
+            )
+        }
+    };
+
     let mut min = Vec2::MAX;
     let mut max = Vec2::MIN;
 
-    for CameraSubject { rect } in camera_subjects.iter_mut() {
-        min = (rect.min - vec2(meta.border_left, meta.border_bottom))
-            .min(min)
-            .max(Vec2::ZERO);
-        max = (rect.max + vec2(meta.border_right, meta.border_top)).max(max);
+    for rect in &subject_rects {
+        min = (rect.min - border_min).min(min).max(Vec2::ZERO);
+        max = (rect.max + border_max).max(max);
         max.x = max.x.min(map_size.x)
     }
 
     let camera_pos = &mut camera_shake.center;
 
-    let subject_count = camera_subjects.iter().count();
+    let subject_count = subject_rects.len();
     let mut middle_point = if subject_count == 0 {
         camera_pos.truncate()
     } else {
@@ -153,7 +213,23 @@ fn camera_controller(
 
     let rh = size.y / default_height;
     let rw = size.x / default_width;
-    let r_target = if rh > rw { rh } else { rw };
+    let mut r_target = if rh > rw { rh } else { rw };
+
+    // Don't zoom out so far that the players become too small to see
+    r_target = r_target.min(meta.max_height / default_height);
+
+    // Zoom in on the focused entity, if there is one
+    let focus = camera_state
+        .focus
+        .and_then(|ent| transforms.get(ent))
+        .map(|transform| transform.translation.truncate());
+    let size = if let Some(focus) = focus {
+        middle_point = focus;
+        r_target = meta.focus_height / default_height;
+        vec2(default_width, default_height) * r_target
+    } else {
+        size
+    };
     let r_diff = r_target - scale;
     if r_diff > 0.0 {
         scale += r_diff * meta.zoom_out_lerp_factor;
@@ -166,12 +242,49 @@ fn camera_controller(
         middle_point.y = size.y / 2.0;
     }
 
-    let delta = camera_pos.truncate() - middle_point;
+    let mut delta = camera_pos.truncate() - middle_point;
+    // Let the target wander around the deadzone without moving the camera
+    if focus.is_none() {
+        delta = delta.signum() * (delta.abs() - meta.deadzone / 2.0).max(Vec2::ZERO);
+    }
     let dist = delta * meta.move_lerp_factor;
     camera.size = CameraSize::FixedHeight(scale * default_height);
     *camera_pos -= dist.extend(0.0);
 }
 
+/// Turn camera shake events into trauma, using the shape of the strongest event this frame.
+///
+/// The events are dropped without shaking the camera while reduced motion is turned on.
+fn apply_camera_shake_events(
+    meta: Root<GameMeta>,
+    storage: Res<Storage>,
+    mut events: ResMutInit<CameraShakeEvents>,
+    mut trauma_events: ResMutInit<CameraTraumaEvents>,
+    mut camera_shakes: CompMut<CameraShake>,
+) {
+    if is_reduced_motion(&storage) {
+        events.queue.clear();
+        return;
+    }
+    let profiles = events
+        .queue
+        .drain(..)
+        .map(|kind| meta.core.camera_shake.profile(kind))
+        .collect::<Vec<_>>();
+    let Some(strongest) = profiles.iter().max_by(|a, b| a.trauma.total_cmp(&b.trauma)) else {
+        return;
+    };
+
+    for camera_shake in camera_shakes.iter_mut() {
+        camera_shake.max_offset = Vec2::splat(strongest.amplitude);
+        camera_shake.speed = strongest.frequency;
+        camera_shake.decay_rate = strongest.decay;
+    }
+    for profile in &profiles {
+        trauma_events.send(profile.trauma);
+    }
+}
+
 /// Implements the background layer parallax.
 fn camera_parallax(
     entities: Res<Entities>,
@@ -179,6 +292,7 @@ fn camera_parallax(
     parallax_bg_sprites: Comp<ParallaxBackgroundSprite>,
     cameras: Comp<Camera>,
     map: Res<LoadedMap>,
+    time: Res<Time>,
 ) {
     // TODO: This constant represents that maximum camera-visible distance, and should be moved
     // somewhere more appropriate.
@@ -203,7 +317,18 @@ fn camera_parallax(
         transform.translation.z = -FAR_PLANE + 1.0 - bg.meta.depth / 100.0;
         transform.translation += bg.meta.offset.extend(1.0);
 
-        transform.translation.x -= camera_offset.x * bg.meta.depth * map.background.speed.x;
-        transform.translation.y += camera_offset.y * bg.meta.depth * map.background.speed.y;
+        let scroll_factor = bg.meta.scroll_factor(map.background.speed);
+        transform.translation.x -= camera_offset.x * scroll_factor.x;
+        transform.translation.y += camera_offset.y * scroll_factor.y;
+
+        // Drifting layers loop around, so that they always cover the same area
+        if bg.meta.auto_scroll != 0.0 {
+            let drift = bg.meta.auto_scroll * time.elapsed_seconds();
+            transform.translation.x += if bg.meta.single {
+                drift
+            } else {
+                drift.rem_euclid(display_size.x)
+            };
+        }
     }
 }
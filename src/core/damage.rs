@@ -1,6 +1,10 @@
 //! Damage / kill regions.
 //!
-//! Any player that intersects a damage region will be killed.
+//! Any player that intersects a damage region will be killed, unless the region has knockback, in
+//! which case the player is launched and stunned instead. Players that are knocked back get a
+//! [`DamageNumber`] popping up over them with how hard they were hit.
+
+use std::collections::VecDeque;
 
 use crate::prelude::*;
 
@@ -10,19 +14,52 @@ use super::utils::Rect;
 pub fn install(session: &mut SessionBuilder) {
     DamageRegion::register_schema();
     DamageRegionOwner::register_schema();
+    DamageNumber::register_schema();
 
-    session.add_system_to_stage(CoreStage::PostUpdate, kill_players_in_damage_region);
+    session.init_resource::<DamageEvents>();
+    session.init_resource::<Hitstop>();
+    session
+        .add_system_to_stage(CoreStage::PostUpdate, kill_players_in_damage_region)
+        .add_system_to_stage(CoreStage::PostUpdate, expire_recent_attackers)
+        .add_system_to_stage(CoreStage::PostUpdate, update_damage_numbers)
+        .add_system_to_stage(CoreStage::PostUpdate, record_damage_events);
 }
 
+/// How many frames a [`DamageEvent`] is remembered for.
+pub const DAMAGE_EVENT_LIFETIME: u32 = 45;
+
+/// How long a player that knocked back another player is remembered in their
+/// [`RecentAttackers`], to be credited with a kill or an assist.
+pub const RECENT_ATTACKER_TIME: Duration = Duration::from_secs(3);
+
+/// How many attackers are remembered in [`RecentAttackers`].
+pub const RECENT_ATTACKERS_CAPACITY: usize = 4;
+
 /// A rectangular damage region.
 ///
-/// While this _might_ change in the future, damage regions will kill players immediately, so there
-/// is no "damage" field.
+/// Damage regions without knockback kill players immediately, so there is no "damage" field.
 #[derive(Debug, Clone, Default, HasSchema)]
 #[repr(C)]
 pub struct DamageRegion {
     /// The size of the damage region in pixels
     pub size: Vec2,
+    /// The velocity that players hit by this region are launched with, mirrored horizontally to
+    /// point away from the region.
+    ///
+    /// If this and [`hitstun`][Self::hitstun] are both zero, the region kills players instead.
+    pub knockback: Vec2,
+    /// How long players hit by this region are [`Stunned`] for.
+    pub hitstun: Duration,
+    /// How long the match freezes for when this region hits or kills a player.
+    pub hitstop: Duration,
+    /// Whether this region is made by a blade, which cuts things such as ropes.
+    pub sharp: bool,
+    /// Whether this region is an explosion, which launches the idle items lying in it.
+    pub blast: bool,
+    /// The player credited with kills by this region, such as whoever threw or last kicked the bomb
+    /// that made it. Defaults to the [`DamageRegionOwner`].
+    #[schema(opaque)]
+    pub attacker: Option<Entity>,
 }
 
 impl DamageRegion {
@@ -30,6 +67,38 @@ impl DamageRegion {
     pub fn collider_rect(&self, position: Vec3) -> Rect {
         Rect::new(position.x, position.y, self.size.x, self.size.y)
     }
+
+    /// Whether this region knocks players back instead of killing them.
+    pub fn has_knockback(&self) -> bool {
+        self.knockback != Vec2::ZERO || !self.hitstun.is_zero()
+    }
+}
+
+/// How an explosive, such as a grenade, reacts to the explosions around it.
+#[derive(Debug, Clone, Default, HasSchema)]
+#[repr(C)]
+pub struct ChainReactionMeta {
+    /// Whether explosions light the explosive when it is idle, and set it off when it is lit.
+    pub enabled: bool,
+    /// How long the explosive takes to go off after being caught in an explosion, so that chains
+    /// of explosions go off one after the other.
+    pub delay: Duration,
+}
+
+/// Get the collision rectangles of the [blast][DamageRegion::blast] damage regions, along with the
+/// player credited with each of them.
+pub fn blast_rects(
+    entities: &Entities,
+    damage_regions: &Comp<DamageRegion>,
+    transforms: &Comp<Transform>,
+) -> Vec<(Rect, Option<Entity>)> {
+    entities
+        .iter_with((damage_regions, transforms))
+        .filter(|(_, (region, _))| region.blast)
+        .map(|(_, (region, transform))| {
+            (region.collider_rect(transform.translation), region.attacker)
+        })
+        .collect()
 }
 
 /// A component that may be added to a damage region entity to indicate the triggering entity.
@@ -40,21 +109,177 @@ impl DamageRegion {
 #[repr(C)]
 pub struct DamageRegionOwner(pub Entity);
 
-/// System that will eliminate players that are intersecting with a damage region.
+/// Component with the players that recently knocked back a player with their damage regions,
+/// oldest first.
+///
+/// If the player dies soon after, the latest attacker is credited with the kill when nobody else
+/// is, such as for deaths to the environment, and the others get an assist.
+#[derive(Debug, Clone, HasSchema, Default)]
+pub struct RecentAttackers {
+    pub attackers: VecDeque<(Entity, Timer)>,
+}
+
+impl RecentAttackers {
+    /// Remember a hit from the given attacker, forgetting the oldest attacker if there are too
+    /// many.
+    pub fn push(&mut self, attacker: Entity) {
+        self.attackers.retain(|(x, _)| *x != attacker);
+        if self.attackers.len() >= RECENT_ATTACKERS_CAPACITY {
+            self.attackers.pop_front();
+        }
+        self.attackers
+            .push_back((attacker, Timer::new(RECENT_ATTACKER_TIME, TimerMode::Once)));
+    }
+
+    /// The player that hit last.
+    pub fn latest(&self) -> Option<Entity> {
+        self.attackers.back().map(|(x, _)| *x)
+    }
+
+    /// Iterate over the attackers, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.attackers.iter().map(|(x, _)| *x)
+    }
+}
+
+/// Settings for the [`DamageNumber`]s that pop up over players when they are knocked back.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct DamageNumbersMeta {
+    /// How long each number stays up for, fading out as it goes.
+    pub lifetime: Duration,
+    /// How fast the numbers drift up, in pixels per second.
+    pub drift_speed: f32,
+    /// How far above the top of the player the numbers pop up, in pixels.
+    pub offset: f32,
+    /// The knockback speed from which hits count as critical, and get a bigger, brighter number.
+    pub crit_knockback: f32,
+}
+
+/// A number popping up over a player that was knocked back, with how hard they were hit.
+///
+/// The numbers are spawned whether or not they are shown, so that the world stays the same for
+/// every player in network matches. The HUD only draws them when they are turned on in the
+/// settings, and always in training matches.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct DamageNumber {
+    /// The speed that the player was launched with.
+    pub amount: u32,
+    /// Whether the hit was strong enough to count as critical.
+    pub crit: bool,
+    /// How long the number has been up for.
+    pub age: Duration,
+}
+
+impl DamageNumber {
+    /// How far the number is through its lifetime, from `0.0` when it pops up to `1.0` when it
+    /// disappears.
+    pub fn progress(&self, meta: &DamageNumbersMeta) -> f32 {
+        if meta.lifetime.is_zero() {
+            return 1.0;
+        }
+        (self.age.as_secs_f32() / meta.lifetime.as_secs_f32()).min(1.0)
+    }
+}
+
+/// Drift the [`DamageNumber`]s up, and remove them once they have faded out.
+fn update_damage_numbers(
+    meta: Root<GameMeta>,
+    time: Res<Time>,
+    mut entities: ResMutInit<Entities>,
+    mut damage_numbers: CompMut<DamageNumber>,
+    mut transforms: CompMut<Transform>,
+) {
+    let meta = &meta.core.damage_numbers;
+    let mut expired = Vec::new();
+    for (ent, (number, transform)) in entities.iter_with((&mut damage_numbers, &mut transforms)) {
+        number.age += time.delta();
+        transform.translation.y += meta.drift_speed * time.delta_seconds();
+        if number.age >= meta.lifetime {
+            expired.push(ent);
+        }
+    }
+    for ent in expired {
+        entities.kill(ent);
+    }
+}
+
+/// A short-lived damage region that appeared somewhere in the map, such as an explosion.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DamageEvent {
+    /// Where the damage region appeared.
+    pub pos: Vec2,
+    /// How many frames ago the damage region appeared.
+    pub age: u32,
+}
+
+/// Session resource with the recent [`DamageEvent`]s, used to point them out on the HUD.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct DamageEvents {
+    pub events: Vec<DamageEvent>,
+}
+
+/// Marker for damage regions that already have a [`DamageEvent`].
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct DamageEventRecorded;
+
+/// Record a [`DamageEvent`] for every new temporary damage region, and forget the old ones.
+///
+/// Only regions with a [`Lifetime`] are recorded, so that permanent hazards don't count.
+fn record_damage_events(
+    entities: Res<Entities>,
+    transforms: Comp<Transform>,
+    damage_regions: Comp<DamageRegion>,
+    lifetimes: Comp<Lifetime>,
+    mut recorded: CompMut<DamageEventRecorded>,
+    mut damage_events: ResMut<DamageEvents>,
+) {
+    for event in &mut damage_events.events {
+        event.age += 1;
+    }
+    damage_events
+        .events
+        .retain(|event| event.age < DAMAGE_EVENT_LIFETIME);
+
+    let mut new_regions = Vec::new();
+    for (ent, (_region, transform, _lifetime)) in
+        entities.iter_with((&damage_regions, &transforms, &lifetimes))
+    {
+        if !recorded.contains(ent) {
+            new_regions.push((ent, transform.translation.xy()));
+        }
+    }
+    for (ent, pos) in new_regions {
+        recorded.insert(ent, DamageEventRecorded);
+        damage_events.events.push(DamageEvent { pos, age: 0 });
+    }
+}
+
+/// System that will eliminate or knock back players that are intersecting with a damage region.
 fn kill_players_in_damage_region(
+    meta: Root<GameMeta>,
     entities: Res<Entities>,
+    mutators: Res<Mutators>,
     mut commands: Commands,
     player_indexes: Comp<PlayerIdx>,
     transforms: Comp<Transform>,
     damage_regions: Comp<DamageRegion>,
     damage_region_owners: Comp<DamageRegionOwner>,
-    bodies: Comp<KinematicBody>,
+    mut bodies: CompMut<KinematicBody>,
+    mut stunned: CompMut<Stunned>,
     invincibles: CompMut<Invincibility>,
+    killed_players: Comp<PlayerKilled>,
+    mut recent_attackers: CompMut<RecentAttackers>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
+    mut hitstop: ResMutInit<Hitstop>,
 ) {
     let mut bitset = player_indexes.bitset().clone();
     bitset.bit_and(transforms.bitset());
     bitset.bit_and(bodies.bitset());
     bitset.bit_andnot(invincibles.bitset());
+    bitset.bit_andnot(killed_players.bitset());
 
     for player_ent in entities.iter_with_bitset(&bitset) {
         let transform = transforms.get(player_ent).unwrap();
@@ -70,14 +295,86 @@ fn kill_players_in_damage_region(
                     continue;
                 }
             }
+            let attacker = damage_region.attacker.or(owner.map(|x| x.0));
 
             let damage_rect = damage_region.collider_rect(transform.translation);
-            if player_rect.overlaps(&damage_rect) {
-                commands.add(PlayerCommand::kill(
-                    player_ent,
-                    Some(transform.translation.xy()),
-                ));
+            if !player_rect.overlaps(&damage_rect) {
+                continue;
             }
+
+            // With the one hit kill mutator, every hit is deadly
+            if damage_region.has_knockback() && !mutators.one_hit_kill {
+                // Players can't be knocked back again while they are still reeling from a hit.
+                if stunned.contains(player_ent) {
+                    continue;
+                }
+
+                let velocity =
+                    RadialImpulse::knockback(damage_rect.center(), damage_region.knockback)
+                        .velocity(player_rect.center());
+                bodies.get_mut(player_ent).unwrap().velocity = velocity;
+                let speed = velocity.length();
+                let number = DamageNumber {
+                    amount: speed.round() as u32,
+                    crit: speed >= meta.core.damage_numbers.crit_knockback,
+                    age: Duration::ZERO,
+                };
+                let number_pos = vec3(
+                    player_rect.center().x,
+                    player_rect.max.y + meta.core.damage_numbers.offset,
+                    0.0,
+                );
+                commands.add(
+                    move |mut entities: ResMutInit<Entities>,
+                          mut damage_numbers: CompMut<DamageNumber>,
+                          mut transforms: CompMut<Transform>| {
+                        let ent = entities.create();
+                        damage_numbers.insert(ent, number);
+                        transforms.insert(ent, Transform::from_translation(number_pos));
+                    },
+                );
+                stunned.insert(player_ent, Stunned::new(damage_region.hitstun));
+                if let Some(attacker) = attacker.filter(|x| *x != player_ent) {
+                    if !recent_attackers.contains(player_ent) {
+                        recent_attackers.insert(player_ent, default());
+                    }
+                    recent_attackers.get_mut(player_ent).unwrap().push(attacker);
+                }
+                camera_shake_events.send(CameraShakeKind::MeleeHit);
+                hitstop.trigger(damage_region.hitstop);
+                break;
+            }
+
+            commands.add(PlayerCommand::kill_by(
+                player_ent,
+                Some(transform.translation.xy()),
+                attacker,
+            ));
+            hitstop.trigger(damage_region.hitstop);
+
+            // The player is dead, no need to check the other damage regions.
+            break;
         }
     }
 }
+
+/// Forget the [`RecentAttackers`] that haven't hit their player in a while.
+fn expire_recent_attackers(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    mut recent_attackers: CompMut<RecentAttackers>,
+) {
+    let mut expired = Vec::new();
+    for (ent, recent) in entities.iter_with(&mut recent_attackers) {
+        for (_, timer) in &mut recent.attackers {
+            timer.tick(time.delta());
+        }
+        recent.attackers.retain(|(_, timer)| !timer.finished());
+        if recent.attackers.is_empty() {
+            expired.push(ent);
+        }
+    }
+    for ent in expired {
+        recent_attackers.remove(ent);
+    }
+}
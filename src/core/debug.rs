@@ -1,5 +1,7 @@
 //! Debug rendering for collision boxes, etc.
 
+use std::collections::VecDeque;
+
 use crate::prelude::*;
 use rapier2d::prelude as rapier;
 
@@ -9,7 +11,10 @@ pub fn plugin(session: &mut SessionBuilder) {
         .stages
         .add_system_to_stage(CoreStage::Last, debug_render_colliders)
         .add_system_to_stage(CoreStage::Last, debug_render_damage_regions)
-        .add_system_to_stage(CoreStage::Last, debug_render_emote_regions);
+        .add_system_to_stage(CoreStage::Last, debug_render_emote_regions)
+        .add_system_to_stage(CoreStage::Last, debug_render_map_bounds)
+        .add_system_to_stage(CoreStage::Last, debug_render_spatial_queries)
+        .add_system_to_stage(CoreStage::Last, debug_render_tile_materials);
 }
 
 /// Resource configuring various debugging settings.
@@ -21,6 +26,126 @@ pub struct DebugSettings {
     pub show_damage_regions: bool,
     /// Whether or not to show the pathfinding lines.
     pub show_pathfinding_lines: bool,
+    /// Whether or not to render the map bounds that players and items are killed outside of.
+    pub show_map_bounds: bool,
+    /// Whether or not to show how long the stages of the session take.
+    pub show_profiler: bool,
+    /// Whether or not to render the recent ray casts, shape casts, and overlap tests.
+    pub show_spatial_queries: bool,
+    /// Whether or not to outline the tiles that are made of a [`TileMaterial`].
+    pub show_tile_materials: bool,
+}
+
+/// How many frames of [`StageTimings`] are kept by default, which is how far back the profiler
+/// graph reaches.
+pub const STAGE_TIMING_FRAMES: usize = 240;
+
+/// Session resource with how long each stage of the session took on the recent frames.
+#[derive(HasSchema, Clone, Debug)]
+pub struct StageTimings {
+    /// When the last stage finished, or the frame started.
+    last_mark: Option<Instant>,
+    /// The names of the timed stages, in the order that they run.
+    pub stages: Vec<&'static str>,
+    /// How long each stage took on the recent frames, oldest first. The durations of each frame
+    /// are in the same order as the `stages`.
+    pub frames: VecDeque<Vec<Duration>>,
+    /// How many frames are kept.
+    pub capacity: usize,
+}
+
+impl Default for StageTimings {
+    fn default() -> Self {
+        Self {
+            last_mark: None,
+            stages: default(),
+            frames: default(),
+            capacity: STAGE_TIMING_FRAMES,
+        }
+    }
+}
+
+impl StageTimings {
+    /// Start timing a new frame, forgetting the oldest one if there are too many.
+    fn start_frame(&mut self) {
+        if self.frames.len() >= self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(Vec::with_capacity(self.stages.len()));
+        self.last_mark = Some(Instant::now());
+    }
+
+    /// Record that a stage just finished.
+    fn mark(&mut self, stage: &'static str) {
+        let now = Instant::now();
+        let elapsed = now - self.last_mark.unwrap_or(now);
+        self.last_mark = Some(now);
+        if !self.stages.contains(&stage) {
+            self.stages.push(stage);
+        }
+        if let Some(frame) = self.frames.back_mut() {
+            frame.push(elapsed);
+        }
+    }
+
+    /// Get how long each of the recent frames took as a whole, oldest first.
+    pub fn frame_times(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.frames.iter().map(|frame| frame.iter().sum())
+    }
+
+    /// Get how long a stage took on each of the recent frames, oldest first.
+    pub fn stage_times(&self, stage_idx: usize) -> impl Iterator<Item = Duration> + '_ {
+        self.frames
+            .iter()
+            .map(move |frame| frame.get(stage_idx).copied().unwrap_or_default())
+    }
+}
+
+/// The stage that starts the [`StageTimings`] of each frame.
+#[derive(Debug, Clone, Copy)]
+struct StageTimingStage;
+
+impl StageLabel for StageTimingStage {
+    fn name(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn id(&self) -> Ulid {
+        Ulid(2026101612004417361532911187365027181)
+    }
+}
+
+/// Time the stages of the session, recording them in the [`StageTimings`].
+///
+/// This has to be installed after everything else, so that the timers run at the end of each
+/// stage. The player state stage runs right before the pre-update stage, so it is counted with it.
+pub fn install_stage_timers(session: &mut SessionBuilder) {
+    session.init_resource::<StageTimings>();
+    session
+        .stages
+        .insert_stage_before(CoreStage::First, SimpleSystemStage::new(StageTimingStage))
+        .add_system_to_stage(StageTimingStage, |mut timings: ResMut<StageTimings>| {
+            timings.start_frame()
+        })
+        .add_system_to_stage(CoreStage::First, |mut timings: ResMut<StageTimings>| {
+            timings.mark("First")
+        })
+        .add_system_to_stage(CoreStage::PreUpdate, |mut timings: ResMut<StageTimings>| {
+            timings.mark("PreUpdate")
+        })
+        .add_system_to_stage(CoreStage::Update, |mut timings: ResMut<StageTimings>| {
+            timings.mark("Update")
+        })
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            |mut timings: ResMut<StageTimings>| timings.mark("PostUpdate"),
+        )
+        .add_system_to_stage(PhysicsStage::Update, |mut timings: ResMut<StageTimings>| {
+            timings.mark("Physics")
+        })
+        .add_system_to_stage(CoreStage::Last, |mut timings: ResMut<StageTimings>| {
+            timings.mark("Last")
+        });
 }
 
 /// Resource containing the physics debug line entity.
@@ -31,6 +156,72 @@ pub struct RapierDebugContext {
     debug_pipeline: rapier::DebugRenderPipeline,
 }
 
+/// Resource containing the entity used to render the map bounds.
+#[derive(HasSchema, Clone)]
+#[schema(no_default)]
+pub struct MapBoundsDebugEntity(pub Entity);
+
+impl FromWorld for MapBoundsDebugEntity {
+    fn from_world(world: &World) -> Self {
+        let entity = world.resource_mut::<Entities>().create();
+
+        let transforms = world.components.get::<Transform>();
+        let mut transforms = transforms.borrow_mut();
+        transforms.insert(entity, Transform::from_translation(vec3(0.0, 0.0, -1.0)));
+
+        Self(entity)
+    }
+}
+
+/// Resource containing the entities used to render the recent spatial queries, one for the queries
+/// that hit something and one for those that didn't.
+#[derive(HasSchema, Clone)]
+#[schema(no_default)]
+pub struct SpatialQueryDebugEntities {
+    hits: Entity,
+    misses: Entity,
+}
+
+impl FromWorld for SpatialQueryDebugEntities {
+    fn from_world(world: &World) -> Self {
+        let mut entities = world.resource_mut::<Entities>();
+        let hits = entities.create();
+        let misses = entities.create();
+
+        let transforms = world.components.get::<Transform>();
+        let mut transforms = transforms.borrow_mut();
+        for entity in [hits, misses] {
+            transforms.insert(entity, Transform::from_translation(vec3(0.0, 0.0, -1.0)));
+        }
+
+        Self { hits, misses }
+    }
+}
+
+/// Resource containing the entities used to outline the tiles of each [`TileMaterial`].
+#[derive(HasSchema, Clone)]
+#[schema(no_default)]
+pub struct TileMaterialDebugEntities {
+    ice: Entity,
+    rubber: Entity,
+}
+
+impl FromWorld for TileMaterialDebugEntities {
+    fn from_world(world: &World) -> Self {
+        let mut entities = world.resource_mut::<Entities>();
+        let ice = entities.create();
+        let rubber = entities.create();
+
+        let transforms = world.components.get::<Transform>();
+        let mut transforms = transforms.borrow_mut();
+        for entity in [ice, rubber] {
+            transforms.insert(entity, Transform::from_translation(vec3(0.0, 0.0, -1.0)));
+        }
+
+        Self { ice, rubber }
+    }
+}
+
 /// An implementation of the rapier `DebugRenderingBackend` that we use to create bones `Path2d`
 /// entities with.
 struct RapierDebugBackend<'a> {
@@ -219,3 +410,155 @@ fn debug_render_emote_regions(
         }
     }
 }
+
+/// Renders debug lines for the map bounds.
+fn debug_render_map_bounds(
+    settings: ResInit<DebugSettings>,
+    map: Res<LoadedMap>,
+    debug_entity: ResMutInit<MapBoundsDebugEntity>,
+    mut paths: CompMut<Path2d>,
+) {
+    if !settings.show_map_bounds {
+        paths.remove(debug_entity.0);
+        return;
+    }
+
+    let (min, max) = map.kill_bounds();
+    let points = if max.y.is_finite() {
+        vec![
+            vec2(min.x, max.y),
+            vec2(max.x, max.y),
+            vec2(max.x, min.y),
+            min,
+            vec2(min.x, max.y),
+        ]
+    } else {
+        // Without a top bound, leave the box open, with the sides reaching up to the top of the
+        // map.
+        let top = map.size().y;
+        vec![vec2(min.x, top), min, vec2(max.x, min.y), vec2(max.x, top)]
+    };
+
+    paths.insert(
+        debug_entity.0,
+        Path2d {
+            color: Color::YELLOW,
+            points,
+            thickness: 2.0,
+            ..default()
+        },
+    );
+}
+
+/// Renders debug lines for the recent spatial queries, and forgets the old ones.
+fn debug_render_spatial_queries(
+    settings: ResInit<DebugSettings>,
+    query_log: ResInit<SpatialQueryLog>,
+    debug_entities: ResMutInit<SpatialQueryDebugEntities>,
+    mut paths: CompMut<Path2d>,
+) {
+    let mut queries = query_log.queries.borrow_mut();
+    if !settings.show_spatial_queries {
+        queries.clear();
+        paths.remove(debug_entities.hits);
+        paths.remove(debug_entities.misses);
+        return;
+    }
+
+    let mut hits = (Vec::new(), Vec::new());
+    let mut misses = (Vec::new(), Vec::new());
+    for (query, _) in queries.iter() {
+        let ((points, line_breaks), query_points) = match *query {
+            SpatialQuery::Cast { start, end, hit } => {
+                (if hit { &mut hits } else { &mut misses }, vec![start, end])
+            }
+            SpatialQuery::Overlap { rect, hit } => (
+                if hit { &mut hits } else { &mut misses },
+                vec![
+                    rect.top_left(),
+                    rect.top_right(),
+                    rect.bottom_right(),
+                    rect.bottom_left(),
+                    rect.top_left(),
+                ],
+            ),
+        };
+        points.extend(query_points);
+        line_breaks.push(points.len());
+    }
+
+    for (entity, (points, line_breaks), color) in [
+        (debug_entities.hits, hits, Color::RED),
+        (debug_entities.misses, misses, Color::GREEN),
+    ] {
+        paths.insert(
+            entity,
+            Path2d {
+                color,
+                points,
+                line_breaks,
+                thickness: 1.0,
+                ..default()
+            },
+        );
+    }
+
+    for (_, age) in queries.iter_mut() {
+        *age += 1;
+    }
+    queries.retain(|(_, age)| *age < SPATIAL_QUERY_DEBUG_FRAMES);
+}
+
+/// Renders outlines around the tiles that are made of a [`TileMaterial`], in a color for each
+/// material.
+fn debug_render_tile_materials(
+    settings: ResInit<DebugSettings>,
+    entities: Res<Entities>,
+    tile_layers: Comp<TileLayer>,
+    tile_materials: Comp<TileMaterial>,
+    debug_entities: ResMutInit<TileMaterialDebugEntities>,
+    mut paths: CompMut<Path2d>,
+) {
+    if !settings.show_tile_materials {
+        paths.remove(debug_entities.ice);
+        paths.remove(debug_entities.rubber);
+        return;
+    }
+
+    let mut ice = (Vec::new(), Vec::new());
+    let mut rubber = (Vec::new(), Vec::new());
+    for (_, layer) in entities.iter_with(&tile_layers) {
+        for x in 0..layer.grid_size.x {
+            for y in 0..layer.grid_size.y {
+                let Some(tile_ent) = layer.get(uvec2(x, y)) else {
+                    continue;
+                };
+                let (points, line_breaks) = match tile_materials.get(tile_ent) {
+                    Some(TileMaterial::Ice) => &mut ice,
+                    Some(TileMaterial::Rubber) => &mut rubber,
+                    Some(TileMaterial::Default) | None => continue,
+                };
+                let min = vec2(x as f32, y as f32) * layer.tile_size;
+                let max = min + layer.tile_size;
+                points.extend([min, vec2(min.x, max.y), max, vec2(max.x, min.y), min]);
+                line_breaks.push(points.len());
+            }
+        }
+    }
+
+    for (entity, (points, line_breaks), color) in [
+        (debug_entities.ice, ice, Color::CYAN),
+        (debug_entities.rubber, rubber, Color::PINK),
+    ] {
+        paths.insert(
+            entity,
+            Path2d {
+                color,
+                points,
+                line_breaks,
+                thickness: 1.0,
+                ..default()
+            },
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Desync detection for network matches.
+//!
+//! Every [`CHECKSUM_INTERVAL`] frames, each client hashes the parts of the world that have to be
+//! identical on every client: transforms, physics bodies, and player states. A few bits of the
+//! hash of the latest confirmed checkpoint are sent along with the local player's inputs, tagged
+//! with which checkpoint they belong to. When the inputs of a remote player arrive, their hash bits
+//! are compared to the local hash of the same checkpoint, and a mismatch means that the clients
+//! have desynced.
+//!
+//! Only a few bits of each hash are exchanged, so a single desynced checkpoint may go unnoticed,
+//! but a lasting desync is caught within a few checkpoints.
+
+use crate::prelude::*;
+
+/// How many frames apart checksums are taken.
+pub const CHECKSUM_INTERVAL: i32 = 30;
+
+/// The number of checkpoints that are remembered, which is also how many can be told apart by
+/// their tags.
+const CHECKPOINT_COUNT: usize = 16;
+
+/// Set on checksum fragments that contain a checksum.
+const FRAGMENT_VALID_BIT: u16 = 1 << 9;
+/// The number of bits of the checksum sent in each fragment.
+const FRAGMENT_HASH_BITS: u16 = 5;
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<ChecksumHistory>();
+    session.init_resource::<DesyncState>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::Last, record_checksum)
+        .add_system_to_stage(CoreStage::Last, compare_remote_checksums);
+}
+
+/// The checksums of the most recent checkpoints.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct ChecksumHistory {
+    /// Pairs of checkpoint frames and their checksums, newest last.
+    checkpoints: VecDeque<(i32, u64)>,
+}
+
+impl ChecksumHistory {
+    /// Get the checksum of the newest checkpoint with the given tag that has been confirmed.
+    fn find(&self, tag: u16, last_confirmed_frame: i32) -> Option<(i32, u64)> {
+        self.checkpoints
+            .iter()
+            .rev()
+            .filter(|(frame, _)| *frame <= last_confirmed_frame)
+            .find(|(frame, _)| checkpoint_tag(*frame) == tag)
+            .copied()
+    }
+}
+
+/// Session resource with the first desync detected in the match.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct DesyncState {
+    /// The first checkpoint frame that didn't match, and the player that disagreed with us.
+    pub first_mismatch: Option<(i32, PlayerIdx)>,
+}
+
+fn checkpoint_tag(frame: i32) -> u16 {
+    ((frame / CHECKSUM_INTERVAL) as usize % CHECKPOINT_COUNT) as u16
+}
+
+fn encode_fragment(frame: i32, checksum: u64) -> u16 {
+    let hash = checksum as u16 & ((1 << FRAGMENT_HASH_BITS) - 1);
+    FRAGMENT_VALID_BIT | checkpoint_tag(frame) << FRAGMENT_HASH_BITS | hash
+}
+
+/// Split a checksum fragment into its checkpoint tag and hash bits.
+fn decode_fragment(fragment: u16) -> Option<(u16, u16)> {
+    (fragment & FRAGMENT_VALID_BIT != 0).then(|| {
+        let hash = fragment & ((1 << FRAGMENT_HASH_BITS) - 1);
+        let tag = (fragment & !FRAGMENT_VALID_BIT) >> FRAGMENT_HASH_BITS;
+        (tag, hash)
+    })
+}
+
+/// Hash of bytes with 64-bit FNV-1a, which gives the same result on every platform.
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_f32s(&mut self, values: &[f32]) {
+        for value in values {
+            self.write(&value.to_bits().to_le_bytes());
+        }
+    }
+}
+
+/// Hash the state that every client must agree on.
+fn world_checksum(
+    entities: &Entities,
+    transforms: &Comp<Transform>,
+    bodies: &Comp<KinematicBody>,
+    player_states: &Comp<PlayerState>,
+) -> u64 {
+    let mut hasher = Fnv64::new();
+    for (ent, (transform, body)) in entities.iter_with((transforms, bodies)) {
+        hasher.write(&ent.index().to_le_bytes());
+        hasher.write_f32s(&transform.translation.to_array());
+        hasher.write_f32s(&transform.rotation.to_array());
+        hasher.write_f32s(&body.velocity.to_array());
+        hasher.write_f32s(&[body.angular_velocity]);
+        hasher.write(&[body.is_on_ground as u8, body.is_deactivated as u8]);
+    }
+    for (ent, state) in entities.iter_with(player_states) {
+        hasher.write(&ent.index().to_le_bytes());
+        hasher.write(state.current.as_bytes());
+        hasher.write(&state.age.to_le_bytes());
+    }
+    hasher.0
+}
+
+/// Take a checksum on checkpoint frames, and pick the one to send with the local inputs.
+fn record_checksum(
+    entities: Res<Entities>,
+    transforms: Comp<Transform>,
+    bodies: Comp<KinematicBody>,
+    player_states: Comp<PlayerState>,
+    syncing_info: Option<Res<SyncingInfo>>,
+    mut history: ResMut<ChecksumHistory>,
+) {
+    let Some(syncing_info) = syncing_info.filter(|x| x.is_online()) else {
+        return;
+    };
+    let frame = syncing_info.current_frame();
+
+    if frame % CHECKSUM_INTERVAL == 0 {
+        let checksum = world_checksum(&entities, &transforms, &bodies, &player_states);
+        // Re-simulated frames replace the checksum that was taken during prediction
+        history.checkpoints.retain(|(x, _)| *x < frame);
+        history.checkpoints.push_back((frame, checksum));
+        while history.checkpoints.len() > CHECKPOINT_COUNT {
+            history.checkpoints.pop_front();
+        }
+    }
+
+    let fragment = history
+        .checkpoints
+        .iter()
+        .rev()
+        .find(|(x, _)| *x <= syncing_info.last_confirmed_frame())
+        .map(|(frame, checksum)| encode_fragment(*frame, *checksum))
+        .unwrap_or_default();
+    crate::input::LOCAL_CHECKSUM_FRAGMENT.store(fragment, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Compare the checksum fragments sent by remote players with our own checksums.
+fn compare_remote_checksums(
+    player_inputs: Res<MatchInputs>,
+    history: Res<ChecksumHistory>,
+    syncing_info: Option<Res<SyncingInfo>>,
+    mut desync: ResMut<DesyncState>,
+) {
+    let Some(syncing_info) = syncing_info.filter(|x| x.is_online()) else {
+        return;
+    };
+    if desync.first_mismatch.is_some() {
+        return;
+    }
+    let last_confirmed_frame = syncing_info.last_confirmed_frame();
+
+    for (i, player) in player_inputs.players.iter().enumerate() {
+        if !player.active || player.is_ai || player.control_source.is_some() {
+            continue;
+        }
+        let Some((tag, hash)) = decode_fragment(player.control.checksum) else {
+            continue;
+        };
+        let Some((frame, checksum)) = history.find(tag, last_confirmed_frame) else {
+            continue;
+        };
+        // Tags wrap around, so ignore checkpoints too old to be the one the player meant
+        if frame < last_confirmed_frame - CHECKSUM_INTERVAL * 4 {
+            continue;
+        }
+
+        if encode_fragment(frame, checksum) & ((1 << FRAGMENT_HASH_BITS) - 1) != hash {
+            error!("Desync detected: player {i} disagrees with the state of frame {frame}");
+            desync.first_mismatch = Some((frame, PlayerIdx(i as u32)));
+            return;
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Mid-match drop-in for local matches.
+//!
+//! When drop-in is enabled for a match, a local controller that isn't playing yet can press start
+//! to take a free player slot. The new player waits out the current round and joins at the start
+//! of the next one, or right away in modes without rounds, such as training, where they spawn at a
+//! spawner like any other respawning player.
+
+use crate::prelude::*;
+
+/// Install this module.
+pub fn install(session: &mut SessionBuilder, drop_in: DropIn) {
+    session.insert_resource(drop_in);
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, admit_waiting_players);
+}
+
+/// The drop-in settings for a match, along with the players waiting to join it.
+#[derive(HasSchema, Clone, Copy, Debug, Default)]
+pub struct DropIn {
+    /// Whether new local players may join during the match.
+    pub enabled: bool,
+    /// The player slots that have been taken by a new player who hasn't joined the match yet.
+    pub waiting: [bool; MAX_PLAYERS as usize],
+}
+
+impl DropIn {
+    /// Get the free player slot that the given control source would take by dropping in, if it
+    /// may drop in at all.
+    pub fn free_slot(&self, player_inputs: &MatchInputs, source: ControlSource) -> Option<usize> {
+        if !self.enabled
+            || player_inputs
+                .players
+                .iter()
+                .any(|x| x.control_source == Some(source))
+        {
+            return None;
+        }
+        player_inputs
+            .players
+            .iter()
+            .position(|x| !x.active && !x.is_ai && x.control_source.is_none())
+    }
+
+    /// Have the given control source take a free player slot, returning the slot that it took.
+    ///
+    /// The new player picks the first fish that nobody else is playing as.
+    pub fn join(
+        &mut self,
+        player_inputs: &mut MatchInputs,
+        source: ControlSource,
+        players: &[Handle<PlayerMeta>],
+    ) -> Option<usize> {
+        let slot = self.free_slot(player_inputs, source)?;
+        let selected_player = players
+            .iter()
+            .find(|handle| {
+                !player_inputs
+                    .players
+                    .iter()
+                    .any(|x| x.active && x.selected_player == **handle)
+            })
+            .or(players.first())
+            .copied()?;
+
+        player_inputs.players[slot] = PlayerInput {
+            active: false,
+            selected_player,
+            selected_hat: None,
+            control: default(),
+            editor_input: None,
+            control_source: Some(source),
+            is_ai: false,
+        };
+        self.waiting[slot] = true;
+
+        Some(slot)
+    }
+
+    /// Make the waiting players active, so that they get spawned, and add them to the score table.
+    pub fn admit(
+        &mut self,
+        players: &mut [PlayerInput; MAX_PLAYERS as usize],
+        score: &mut MatchScore,
+    ) {
+        for (slot, waiting) in self.waiting.iter_mut().enumerate() {
+            if !*waiting {
+                continue;
+            }
+            *waiting = false;
+            players[slot].active = true;
+            score.add_player(PlayerIdx(slot as u32));
+        }
+    }
+}
+
+/// Let the waiting players into modes without rounds right away.
+///
+/// Modes with rounds let them in when the match session is restarted for the next round instead.
+fn admit_waiting_players(
+    mode: Res<GameMode>,
+    mut drop_in: ResMut<DropIn>,
+    mut player_inputs: ResMut<MatchInputs>,
+    mut score: ResMut<MatchScore>,
+) {
+    if mode.has_rounds() {
+        return;
+    }
+    drop_in.admit(&mut player_inputs.players, &mut score);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn free_slot_skips_taken_slots() {
+        let mut drop_in = DropIn::default();
+        let mut player_inputs = MatchInputs::default();
+        player_inputs.players[0].active = true;
+        player_inputs.players[0].control_source = Some(ControlSource::Keyboard1);
+        assert_eq!(
+            drop_in.free_slot(&player_inputs, ControlSource::Gamepad(0)),
+            None
+        );
+
+        drop_in.enabled = true;
+        // Waiting players keep their slot before they become active
+        player_inputs.players[1].control_source = Some(ControlSource::Keyboard2);
+        player_inputs.players[2].is_ai = true;
+        assert_eq!(
+            drop_in.free_slot(&player_inputs, ControlSource::Gamepad(0)),
+            Some(3)
+        );
+        assert_eq!(
+            drop_in.free_slot(&player_inputs, ControlSource::Keyboard2),
+            None
+        );
+    }
+}
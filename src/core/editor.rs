@@ -7,9 +7,33 @@ use crate::prelude::*;
 
 /// Install this module.
 pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<EditorSymmetry>();
+    session.init_resource::<EditorWiringOverlay>();
     session
         .stages
-        .add_system_to_stage(CoreStage::PreUpdate, handle_editor_input);
+        .add_system_to_stage(CoreStage::PreUpdate, handle_editor_input)
+        .add_system_to_stage(CoreStage::Last, update_wiring_overlay);
+}
+
+/// The color of the wires drawn between linked elements.
+const WIRING_COLOR: Color = Color::YELLOW;
+
+/// Resource containing the symmetry that editor changes are mirrored with.
+///
+/// While this is set to anything other than [`MapSymmetry::None`], every tile and element edit
+/// made in the editor is duplicated to its mirrored position on the map.
+#[derive(HasSchema, Clone, Copy, Default, Deref, DerefMut)]
+pub struct EditorSymmetry(pub MapSymmetry);
+
+/// Resource with whether the editor shows the wiring between linked elements.
+///
+/// While it is visible, a wire is drawn from every [`Switch`] to each of the other elements that
+/// share its [`ElementLink`].
+#[derive(HasSchema, Clone, Copy, Default)]
+pub struct EditorWiringOverlay {
+    pub visible: bool,
+    /// The entity that the wires are drawn on, while they are visible.
+    pub entity: Option<Entity>,
 }
 
 impl_system_param! {
@@ -26,11 +50,14 @@ impl_system_param! {
         entities: ResMutInit<'a, Entities>,
         spawned_map_meta: ResMutInit<'a, SpawnedMapMeta>,
         element_handles: CompMut<'a, ElementHandle>,
+        spawner_waves: Comp<'a, SpawnerWaves>,
+        element_links: CompMut<'a, ElementLink>,
         transforms: CompMut<'a, Transform>,
         spawned_map_layer_metas: CompMut<'a, SpawnedMapLayerMeta>,
         tile_layers: CompMut<'a, TileLayer>,
         tiles: CompMut<'a, Tile>,
         tile_collisions: CompMut<'a, TileCollisionKind>,
+        tile_materials: Comp<'a, TileMaterial>,
         map: Res<'a, LoadedMap>,
         element_kill_callbacks: Comp<'a, ElementKillCallback>,
         spawner_manager: SpawnerManager<'a>,
@@ -151,12 +178,49 @@ impl<'a> MapManager<'a> {
             })
             .collect();
     }
+    /// Get the size of the map in pixels.
+    pub fn get_pixel_size(&self) -> Vec2 {
+        self.spawned_map_meta.grid_size.as_vec2() * self.spawned_map_meta.tile_size
+    }
+    /// Find the element that mirrors the given element with the given symmetry.
+    ///
+    /// This is an element of the same kind, on the same layer, within half a tile of the mirrored
+    /// position. Returns [`None`] if there is no such element, or if the element is its own mirror.
+    pub fn find_mirrored_element(&self, entity: Entity, symmetry: MapSymmetry) -> Option<Entity> {
+        let handle = self.element_handles.get(entity)?.0;
+        let layer_idx = self.spawned_map_layer_metas.get(entity)?.layer_idx;
+        let pos = self.transforms.get(entity)?.translation.truncate();
+        let mirrored_pos = symmetry.mirror_point(pos, self.get_pixel_size())?;
+        let tolerance = self.spawned_map_meta.tile_size.min_element() / 2.0;
+
+        self.entities
+            .iter_with((
+                &self.element_handles,
+                &self.spawned_map_layer_metas,
+                &self.transforms,
+            ))
+            .find(|(other, (other_handle, other_layer, transform))| {
+                *other != entity
+                    && other_handle.0 == handle
+                    && other_layer.layer_idx == layer_idx
+                    && transform.translation.truncate().distance(mirrored_pos) <= tolerance
+            })
+            .map(|(other, _)| other)
+    }
     /// Move an element to a new position on the map.
     pub fn move_element(&mut self, entity: Entity, position: &Vec2) {
         let transform = self.transforms.get_mut(entity).unwrap();
         transform.translation.x = position.x;
         transform.translation.y = position.y;
     }
+    /// Set the link of an element, or unlink it if the link is empty.
+    pub fn set_element_link(&mut self, entity: Entity, link: &str) {
+        if link.is_empty() {
+            self.element_links.remove(entity);
+        } else {
+            self.element_links.insert(entity, ElementLink(ustr(link)));
+        }
+    }
     /// Delete an element off of the map.
     pub fn delete_element(&mut self, entity: Entity) {
         if let Some(element_kill_callback) = self.element_kill_callbacks.get(entity) {
@@ -294,6 +358,94 @@ impl<'a> MapManager<'a> {
             }
         }
     }
+    /// Create the map metadata for the map as it currently is, including every change made to it
+    /// since it was spawned.
+    pub fn export_map(&self) -> MapMeta {
+        let grid_size = self.spawned_map_meta.grid_size;
+        let mut layers = self
+            .spawned_map_meta
+            .layer_names
+            .iter()
+            .map(|id| MapLayerMeta {
+                id: *id,
+                ..default()
+            })
+            .collect::<Vec<_>>();
+
+        for (_, (tile_layer, layer_meta)) in self
+            .entities
+            .iter_with((&self.tile_layers, &self.spawned_map_layer_metas))
+        {
+            let Some(layer) = layers.get_mut(layer_meta.layer_idx as usize) else {
+                continue;
+            };
+            if tile_layer.atlas != default() {
+                layer.tilemap = Set(tile_layer.atlas);
+            }
+            for y in 0..grid_size.y {
+                for x in 0..grid_size.x {
+                    let pos = uvec2(x, y);
+                    let Some(tile_ent) = tile_layer.get(pos) else {
+                        continue;
+                    };
+                    let Some(tile) = self.tiles.get(tile_ent) else {
+                        continue;
+                    };
+                    layer.tiles.push(MapTileMeta {
+                        pos,
+                        idx: tile.idx,
+                        collision: self
+                            .tile_collisions
+                            .get(tile_ent)
+                            .copied()
+                            .unwrap_or_default(),
+                        material: self
+                            .tile_materials
+                            .get(tile_ent)
+                            .copied()
+                            .unwrap_or_default(),
+                    });
+                }
+            }
+        }
+
+        for (element_ent, (element_handle, transform, layer_meta)) in self.entities.iter_with((
+            &self.element_handles,
+            &self.transforms,
+            &self.spawned_map_layer_metas,
+        )) {
+            let Some(layer) = layers.get_mut(layer_meta.layer_idx as usize) else {
+                continue;
+            };
+            layer.elements.push(ElementSpawn {
+                pos: transform.translation.truncate(),
+                element: element_handle.0,
+                waves: self
+                    .spawner_waves
+                    .get(element_ent)
+                    .copied()
+                    .unwrap_or_default(),
+                link: self
+                    .element_links
+                    .get(element_ent)
+                    .map(|x| x.0)
+                    .unwrap_or_default(),
+            });
+        }
+
+        MapMeta {
+            name: self.spawned_map_meta.name,
+            background: (*self.spawned_map_meta.background).clone(),
+            background_color: self.spawned_map_meta.background_color,
+            grid_size,
+            tile_size: self.spawned_map_meta.tile_size,
+            layers: layers.into_iter().collect(),
+            camera: self.map.camera.clone(),
+            bounds: self.spawned_map_meta.bounds,
+            weather: self.spawned_map_meta.weather,
+            events: self.spawned_map_meta.events.iter().copied().collect(),
+        }
+    }
     /// Clear all of the elements on the map.
     pub fn clear_elements(&mut self) {
         let mut to_kill: Vec<Entity> = Vec::new();
@@ -310,9 +462,15 @@ impl<'a> MapManager<'a> {
 }
 
 /// Handles user input comming from the editor and makes the required changes to the map.
-fn handle_editor_input(player_inputs: Res<MatchInputs>, mut map_manager: MapManager) {
+fn handle_editor_input(
+    player_inputs: Res<MatchInputs>,
+    mut symmetry: ResMutInit<EditorSymmetry>,
+    mut wiring_overlay: ResMutInit<EditorWiringOverlay>,
+    mut map_manager: MapManager,
+) {
     for player in &player_inputs.players {
         if let Some(editor_input) = &player.editor_input {
+            let symmetry = &mut **symmetry;
             match editor_input {
                 EditorInput::SpawnElement {
                     handle,
@@ -320,6 +478,14 @@ fn handle_editor_input(player_inputs: Res<MatchInputs>, mut map_manager: MapMana
                     layer,
                 } => {
                     map_manager.create_element(handle, translation, *layer as u32);
+
+                    let map_size = map_manager.get_pixel_size();
+                    if let Some(mirrored) = symmetry
+                        .mirror_point(*translation, map_size)
+                        .filter(|x| x != translation)
+                    {
+                        map_manager.create_element(handle, &mirrored, *layer as u32);
+                    }
                 }
                 EditorInput::CreateLayer { id } => {
                     map_manager.create_layer(ustr(id));
@@ -332,9 +498,24 @@ fn handle_editor_input(player_inputs: Res<MatchInputs>, mut map_manager: MapMana
                     name: new_name,
                 } => map_manager.rename_layer(*layer as u32, new_name),
                 EditorInput::MoveEntity { entity, pos } => {
+                    // Look up the mirrored element before moving the original, otherwise we won't
+                    // be able to find it anymore.
+                    let mirrored_entity = map_manager.find_mirrored_element(*entity, *symmetry);
                     map_manager.move_element(*entity, pos);
+
+                    let map_size = map_manager.get_pixel_size();
+                    if let Some((mirrored_entity, mirrored_pos)) =
+                        mirrored_entity.zip(symmetry.mirror_point(*pos, map_size))
+                    {
+                        map_manager.move_element(mirrored_entity, &mirrored_pos);
+                    }
                 }
                 EditorInput::DeleteEntity { entity } => {
+                    if let Some(mirrored_entity) =
+                        map_manager.find_mirrored_element(*entity, *symmetry)
+                    {
+                        map_manager.delete_element(mirrored_entity);
+                    }
                     map_manager.delete_element(*entity);
                 }
                 EditorInput::SetTilemap { layer, handle } => {
@@ -347,6 +528,18 @@ fn handle_editor_input(player_inputs: Res<MatchInputs>, mut map_manager: MapMana
                     collision,
                 } => {
                     map_manager.set_tile(*layer as u32, *pos, tilemap_tile_idx, *collision);
+
+                    if let Some(mirrored_pos) = symmetry
+                        .mirror_tile(*pos, map_manager.get_size())
+                        .filter(|x| x != pos)
+                    {
+                        map_manager.set_tile(
+                            *layer as u32,
+                            mirrored_pos,
+                            tilemap_tile_idx,
+                            *collision,
+                        );
+                    }
                 }
                 EditorInput::MoveLayer { layer, down } => {
                     map_manager.swap_layer(*layer as u32, *down)
@@ -354,6 +547,22 @@ fn handle_editor_input(player_inputs: Res<MatchInputs>, mut map_manager: MapMana
                 EditorInput::RenameMap { name } => {
                     map_manager.rename_map(name);
                 }
+                EditorInput::SetSymmetry {
+                    symmetry: new_symmetry,
+                } => {
+                    *symmetry = *new_symmetry;
+                }
+                EditorInput::SetElementLink { entity, link } => {
+                    if let Some(mirrored_entity) =
+                        map_manager.find_mirrored_element(*entity, *symmetry)
+                    {
+                        map_manager.set_element_link(mirrored_entity, link);
+                    }
+                    map_manager.set_element_link(*entity, link);
+                }
+                EditorInput::SetWiringOverlay { visible } => {
+                    wiring_overlay.visible = *visible;
+                }
                 EditorInput::RandomizeTiles {
                     tile_layers,
                     element_layers,
@@ -371,3 +580,56 @@ fn handle_editor_input(player_inputs: Res<MatchInputs>, mut map_manager: MapMana
         }
     }
 }
+
+/// Draw the wires between linked elements while the [`EditorWiringOverlay`] is visible.
+fn update_wiring_overlay(
+    mut entities: ResMutInit<Entities>,
+    mut wiring_overlay: ResMutInit<EditorWiringOverlay>,
+    links: Comp<ElementLink>,
+    switches: Comp<Switch>,
+    mut transforms: CompMut<Transform>,
+    mut paths: CompMut<Path2d>,
+) {
+    if !wiring_overlay.visible {
+        if let Some(entity) = wiring_overlay.entity.take() {
+            entities.kill(entity);
+        }
+        return;
+    }
+    let overlay_ent = match wiring_overlay.entity {
+        Some(entity) => entity,
+        None => {
+            let entity = entities.create();
+            transforms.insert(entity, Transform::from_translation(vec3(0.0, 0.0, -1.0)));
+            paths.insert(
+                entity,
+                Path2d {
+                    color: WIRING_COLOR,
+                    thickness: 1.0,
+                    ..default()
+                },
+            );
+            wiring_overlay.entity = Some(entity);
+            entity
+        }
+    };
+
+    let mut points = Vec::new();
+    let mut line_breaks = Vec::new();
+    for (switch_ent, (_, switch_link)) in entities.iter_with((&switches, &links)) {
+        let switch_pos = transforms.get(switch_ent).unwrap().translation.xy();
+        for (linked_ent, link) in entities.iter_with(&links) {
+            if linked_ent == switch_ent || link != switch_link {
+                continue;
+            }
+            if !points.is_empty() {
+                line_breaks.push(points.len());
+            }
+            points.push(switch_pos);
+            points.push(transforms.get(linked_ent).unwrap().translation.xy());
+        }
+    }
+    let path = paths.get_mut(overlay_ent).unwrap();
+    path.points = points;
+    path.line_breaks = line_breaks;
+}
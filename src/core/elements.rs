@@ -6,36 +6,53 @@ use std::sync::Mutex;
 
 use crate::{impl_system_param, prelude::*};
 
+pub mod boss;
 pub mod buss;
 pub mod cannon;
 pub mod crab;
 pub mod crate_item;
+pub mod critter;
 pub mod decoration;
+pub mod decoy;
+pub mod door;
 pub mod fish_school;
 pub mod flappy_jellyfish;
 pub mod grenade;
+pub mod hazard;
 pub mod jellyfish;
 pub mod kick_bomb;
+pub mod laser_rifle;
 pub mod machine_gun;
 pub mod mine;
+pub mod mount;
 pub mod musket;
 pub mod periscope;
 pub mod player_spawner;
+pub mod registry;
+pub mod remote_charge;
+pub mod rope;
+pub mod singularity;
 pub mod slippery;
 pub mod slippery_seaweed;
+pub mod smoke_grenade;
 pub mod snail;
 pub mod spike;
 pub mod sproinger;
 pub mod stomp_boots;
+pub mod switch;
 pub mod sword;
+pub mod thief_fish;
 pub mod urchin;
+pub mod water;
 
 pub mod prelude {
     pub use super::{
-        buss::*, crab::*, crate_item::*, decoration::*, fish_school::*, grenade::*, jellyfish::*,
-        kick_bomb::*, machine_gun::*, mine::*, musket::*, periscope::*, player_spawner::*,
-        slippery::*, slippery_seaweed::*, snail::*, spike::*, sproinger::*, stomp_boots::*,
-        sword::*, urchin::*, *,
+        boss::*, buss::*, crab::*, crate_item::*, critter::*, decoration::*, decoy::*, door::*,
+        fish_school::*, grenade::*, hazard::*, jellyfish::*, kick_bomb::*, laser_rifle::*,
+        machine_gun::*, mine::*, mount::*, musket::*, periscope::*, player_spawner::*, registry::*,
+        remote_charge::*, rope::*, singularity::*, slippery::*, slippery_seaweed::*,
+        smoke_grenade::*, snail::*, spike::*, sproinger::*, stomp_boots::*, switch::*, sword::*,
+        thief_fish::*, urchin::*, water::*, *,
     };
 }
 
@@ -48,6 +65,15 @@ pub struct ElementMeta {
     pub data: Handle<SchemaBox>,
     pub editor: ElementEditorMeta,
     pub plugin: Handle<LuaPlugin>,
+    /// How close an item has to be to a player to be pulled in when the auto pickup mutator is
+    /// enabled. Zero uses the default radius from the mutator settings.
+    pub auto_pickup_radius: f32,
+    /// How long this item can lie untouched after being dropped before it goes back to its
+    /// spawner. Zero uses the default from the game config.
+    pub idle_despawn_time: Duration,
+    /// Whether this item is an explosive, which is all that can be found in the map when the
+    /// explosives only mutator is enabled.
+    pub explosive: bool,
 }
 
 #[derive(HasSchema, Default, Debug, Clone, Copy)]
@@ -94,6 +120,51 @@ pub struct MapElementHydrated;
 #[repr(C)]
 pub struct DehydrateOutOfBounds(pub Entity);
 
+/// How a map element spawner paces the items it spawns, set per spawner in the map.
+///
+/// Spawners without any wave settings spawn their item right away, and spawn a new one as soon as
+/// the old one is used up or lost off the map.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[repr(C)]
+pub struct SpawnerWaves {
+    /// How long after the map is spawned before the first item comes out.
+    #[serde(default, with = "humantime_serde")]
+    pub initial_delay: Duration,
+    /// How long after an item is used up, lost, or picked up before the next one comes out.
+    #[serde(default, with = "humantime_serde")]
+    pub respawn_delay: Duration,
+    /// How many items from the spawner can be around at once.
+    ///
+    /// With more than one, the spawner puts out a new item whenever all of its items have been
+    /// picked up, instead of waiting for them to be used up. Zero is the same as one.
+    #[serde(default)]
+    pub max_spawned: u32,
+}
+
+impl SpawnerWaves {
+    pub fn is_default(&self) -> bool {
+        *self == default()
+    }
+}
+
+/// The link ID of a map element, set per element in the map.
+///
+/// Elements with the same link are wired together, so that a [`Switch`] opens the doors and
+/// elevators that share its link. Elements without a link don't have this component.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Deref, DerefMut)]
+#[repr(C)]
+pub struct ElementLink(pub Ustr);
+
+/// The progress of a spawner with [`SpawnerWaves`] towards spawning its next item.
+#[derive(Clone, HasSchema, Default)]
+pub struct SpawnerWaveState {
+    /// The delay before the spawner may spawn again, if it is waiting.
+    pub delay: Option<Timer>,
+    /// Whether the spawner has started its first spawn yet.
+    pub started: bool,
+}
+
 /// Component containing an element's metadata handle.
 #[derive(Clone, Copy, HasSchema, Default, Deref, DerefMut)]
 #[repr(C)]
@@ -191,8 +262,20 @@ impl<'a> SpawnerManager<'a> {
             self.spawners.insert(entity, spawner);
         }
     }
-    /// Stores the spawned elements as having been spawned by the provided entity
-    pub fn create_spawner(&mut self, entity: Entity, spawned_elements: Vec<Entity>) {
+    /// Stores the spawned elements as having been spawned by the provided entity.
+    ///
+    /// If the entity is already a spawner, the elements are added to the ones it spawned before.
+    pub fn create_spawner(&mut self, entity: Entity, mut spawned_elements: Vec<Entity>) {
+        if let Some(spawner) = self.spawners.get(entity) {
+            if let Some(existing) = self
+                .spawner_entities
+                .entities_per_spawner_group_identifier
+                .get_mut(&spawner.group_identifier)
+            {
+                existing.append(&mut spawned_elements);
+                return;
+            }
+        }
         let spawner = Spawner::new();
 
         // add the spawned elements to the newly created resource
@@ -235,6 +318,29 @@ impl<'a> SpawnerManager<'a> {
             .expect("There should exist a cooresponding SpawnerEntities for this spawner group identifier.")
             .retain(|entity| *entity != spawned_entity);
     }
+    /// Returns the entities spawned by the provided spawner entity, and the other spawners in its
+    /// group.
+    pub fn spawned_entities(&self, spawner_entity: Entity) -> &[Entity] {
+        self.spawners
+            .get(spawner_entity)
+            .and_then(|spawner| {
+                self.spawner_entities
+                    .entities_per_spawner_group_identifier
+                    .get(&spawner.group_identifier)
+            })
+            .map(|x| x.as_slice())
+            .unwrap_or_default()
+    }
+    /// Forgets spawned entities that have since been killed.
+    pub fn remove_dead_spawned_entities(&mut self, entities: &Entities) {
+        for spawned in self
+            .spawner_entities
+            .entities_per_spawner_group_identifier
+            .values_mut()
+        {
+            spawned.retain(|entity| entities.is_alive(*entity));
+        }
+    }
     /// Returns if the entity provided is a spawner
     pub fn is_entity_a_spawner(&self, entity: Entity) -> bool {
         self.spawners.contains(entity)
@@ -288,10 +394,21 @@ macro_rules! install_plugins {
             ElementHandle::register_schema();
             MapElementHydrated::register_schema();
             DehydrateOutOfBounds::register_schema();
+            SpawnerWaves::register_schema();
+            SpawnerWaveState::register_schema();
+            ElementLink::register_schema();
 
             session
                 .stages
-                .add_system_to_stage(CoreStage::First, handle_out_of_bounds_items);
+                .add_system_to_stage(CoreStage::First, handle_out_of_bounds_items)
+                .add_system_to_stage(CoreStage::First, update_spawner_waves);
+            session.add_startup_system(
+                |meta: Root<GameMeta>,
+                 assets: Res<AssetServer>,
+                 mut registry: ResMutInit<ElementRegistry>| {
+                    *registry = ElementRegistry::new(&meta, &assets);
+                },
+            );
 
             $(
                 session.install_plugin($module::session_plugin);
@@ -310,28 +427,42 @@ macro_rules! install_plugins {
 }
 
 install_plugins!(
+    boss,
     crab,
     crate_item,
+    critter,
     cannon,
     decoration,
+    decoy,
+    door,
     fish_school,
     grenade,
+    hazard,
     jellyfish,
     kick_bomb,
+    laser_rifle,
     mine,
     machine_gun,
+    mount,
     musket,
     buss,
     player_spawner,
     periscope,
+    remote_charge,
+    rope,
+    singularity,
     slippery_seaweed,
     slippery,
+    smoke_grenade,
     snail,
     spike,
     sproinger,
     stomp_boots,
+    switch,
     sword,
+    thief_fish,
     urchin,
+    water,
 );
 
 fn handle_out_of_bounds_items(
@@ -351,3 +482,60 @@ fn handle_out_of_bounds_items(
         }
     }
 }
+
+/// Hold back the hydration of spawners with [`SpawnerWaves`] until their delays are up.
+///
+/// A spawner is kept marked as hydrated while it waits, so that the element hydrate systems skip
+/// it, and is un-hydrated again when the delay is over so that it spawns its next item.
+fn update_spawner_waves(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    spawner_waves: Comp<SpawnerWaves>,
+    inventories: Comp<Inventory>,
+    mut wave_states: CompMut<SpawnerWaveState>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut spawner_manager: SpawnerManager,
+) {
+    spawner_manager.remove_dead_spawned_entities(&entities);
+    let held_items = entities
+        .iter_with(&inventories)
+        .filter_map(|(_ent, inventory)| inventory.0)
+        .collect::<Vec<_>>();
+
+    for (spawner_ent, (waves, state)) in entities.iter_with((&spawner_waves, &mut wave_states)) {
+        if let Some(delay) = &mut state.delay {
+            delay.tick(time.delta());
+            if delay.finished() {
+                state.delay = None;
+                hydrated.remove(spawner_ent);
+            }
+            continue;
+        }
+
+        let spawned = spawner_manager.spawned_entities(spawner_ent);
+        let max_spawned = waves.max_spawned.max(1) as usize;
+        if spawned.len() >= max_spawned {
+            // Don't let the spawner put out more items until some are gone
+            hydrated.insert(spawner_ent, MapElementHydrated);
+            continue;
+        }
+        if hydrated.contains(spawner_ent)
+            && (max_spawned == 1 || !spawned.iter().all(|x| held_items.contains(x)))
+        {
+            continue;
+        }
+
+        let delay = if state.started {
+            waves.respawn_delay
+        } else {
+            waves.initial_delay
+        };
+        state.started = true;
+        if delay.is_zero() {
+            hydrated.remove(spawner_ent);
+        } else {
+            hydrated.insert(spawner_ent, MapElementHydrated);
+            state.delay = Some(Timer::new(delay, TimerMode::Once));
+        }
+    }
+}
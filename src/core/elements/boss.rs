@@ -0,0 +1,473 @@
+use crate::prelude::*;
+
+/// A big, neutral creature, such as a giant crab, that attacks every player and drops loot when it
+/// is defeated.
+///
+/// The boss goes through its [`phases`][Self::phases] as it loses health, getting faster and
+/// changing its attacks. It takes a hit from every bullet and damage region that touches it, and
+/// picks who to go after the same way bot players do.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("boss"))]
+#[repr(C)]
+pub struct BossMeta {
+    pub atlas: Handle<Atlas>,
+    pub walk_frames: SVec<u32>,
+    pub fps: f32,
+    /// How much the sprite is scaled up.
+    pub scale: f32,
+    pub body_size: Vec2,
+    /// How many hits the boss takes to defeat.
+    pub health: u32,
+    /// How long the boss can't be hurt for after it is hit, so that one explosion only counts once.
+    pub hit_cooldown: Duration,
+    /// The bullet fired by the [`BossAttack::Shoot`] attack.
+    pub bullet: Handle<BulletMeta>,
+    /// How far apart the bullets of one shot are spread, in degrees.
+    pub bullet_spread: f32,
+    /// How fast the boss jumps up for the [`BossAttack::Slam`] attack.
+    pub slam_jump_speed: f32,
+    /// The size of the damage region made when the boss lands from a slam.
+    pub slam_size: Vec2,
+    /// The velocity that players hit by a slam are launched with. If this and `slam_hitstun` are
+    /// both zero, slams kill players instead.
+    pub slam_knockback: Vec2,
+    pub slam_hitstun: Duration,
+    /// The names of the elements dropped when the boss is defeated.
+    pub loot: SVec<Ustr>,
+    /// The phases of the fight, from the first to the last.
+    pub phases: SVec<BossPhaseMeta>,
+}
+
+/// One phase of a boss fight.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[repr(C)]
+pub struct BossPhaseMeta {
+    /// The fraction of its health that the boss must be down to for this phase to start.
+    pub health: f32,
+    pub walk_speed: f32,
+    /// How long the boss waits between attacks.
+    pub attack_cooldown: Duration,
+    /// The attacks used in this phase, in turn.
+    pub attacks: SVec<BossAttack>,
+    /// How many bullets are fired by each [`BossAttack::Shoot`].
+    pub bullets: u32,
+}
+
+/// The attacks that a boss can use.
+#[derive(HasSchema, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[derive_type_data(SchemaDeserialize)]
+pub enum BossAttack {
+    /// Jump up and make a damage region around the boss when it lands.
+    #[default]
+    Slam,
+    /// Fire a spread of bullets at the target.
+    Shoot,
+}
+
+/// How far apart the loot dropped by a defeated boss is spread, in pixels.
+const LOOT_SPACING: f32 = 16.0;
+
+/// How long the damage region of a slam lasts, in seconds.
+const SLAM_LIFETIME: f32 = 0.25;
+
+/// The tint of a boss while it can't be hurt after being hit.
+const HIT_COLOR: Color = Color::rgb(1.0, 0.4, 0.4);
+
+pub fn game_plugin(game: &mut Game) {
+    BossMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::Update, update_bosses)
+        .add_system_to_stage(CoreStage::PostUpdate, damage_bosses);
+}
+
+/// What a boss is doing.
+#[derive(Clone, Copy, Debug, HasSchema, Default, PartialEq, Eq)]
+#[repr(C, u8)]
+pub enum BossState {
+    /// Walking towards the target, waiting for the next attack.
+    #[default]
+    Walking,
+    /// In the air, waiting to land and slam the ground.
+    Slamming,
+}
+
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct Boss {
+    /// The name of the boss' element, shown over its health bar.
+    pub name: Ustr,
+    pub health: u32,
+    pub max_health: u32,
+    /// The index of the current phase in the boss' [`BossMeta::phases`].
+    pub phase: usize,
+    pub state: BossState,
+    /// The player that the boss is going after.
+    pub target: Option<Entity>,
+    pub attack_timer: Timer,
+    /// The index of the next attack in the current phase's attacks.
+    pub next_attack: usize,
+    pub hit_cooldown: Timer,
+}
+
+impl Boss {
+    /// The fraction of its health that the boss has left.
+    pub fn health_fraction(&self) -> f32 {
+        self.health as f32 / self.max_health.max(1) as f32
+    }
+}
+
+fn hydrate(
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut despawns: CompMut<DehydrateOutOfBounds>,
+    mut element_handles: CompMut<ElementHandle>,
+    mut bosses: CompMut<Boss>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut spawner_manager: SpawnerManager,
+    game_meta: Root<GameMeta>,
+    assets: Res<AssetServer>,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        let Ok(BossMeta {
+            atlas,
+            walk_frames,
+            fps,
+            scale,
+            body_size,
+            health,
+            hit_cooldown,
+            phases,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        else {
+            continue;
+        };
+        hydrated.insert(spawner_ent, MapElementHydrated);
+
+        let attack_cooldown = phases.get(0).map(|x| x.attack_cooldown).unwrap_or_default();
+        let mut hit_cooldown = Timer::new(*hit_cooldown, TimerMode::Once);
+        hit_cooldown.tick(hit_cooldown.duration());
+
+        let entity = entities.create();
+        bosses.insert(
+            entity,
+            Boss {
+                name: element_meta.name,
+                health: *health,
+                max_health: *health,
+                attack_timer: Timer::new(attack_cooldown, TimerMode::Once),
+                hit_cooldown,
+                ..default()
+            },
+        );
+        let mut transform = transform;
+        transform.scale = Vec3::new(*scale, *scale, 1.0);
+        transforms.insert(entity, transform);
+        element_handles.insert(entity, element_handle);
+        hydrated.insert(entity, MapElementHydrated);
+        despawns.insert(entity, DehydrateOutOfBounds(spawner_ent));
+        atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+        animated_sprites.insert(
+            entity,
+            AnimatedSprite {
+                frames: walk_frames.iter().cloned().collect(),
+                fps: *fps,
+                repeat: true,
+                ..default()
+            },
+        );
+        bodies.insert(
+            entity,
+            KinematicBody {
+                gravity: game_meta.core.physics.gravity,
+                has_mass: true,
+                has_friction: true,
+                shape: ColliderShape::Rectangle { size: *body_size },
+                ..default()
+            },
+        );
+
+        spawner_manager.create_spawner(spawner_ent, vec![entity]);
+    }
+}
+
+/// Move the bosses towards their targets and attack them.
+fn update_bosses(
+    time: Res<Time>,
+    rng: Res<GlobalRng>,
+    entities: Res<Entities>,
+    mut commands: Commands,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    player_indexes: Comp<PlayerIdx>,
+    decoys: Comp<Decoy>,
+    smoke_clouds: Comp<SmokeCloud>,
+    transforms: Comp<Transform>,
+    mut bosses: CompMut<Boss>,
+    mut bodies: CompMut<KinematicBody>,
+    mut sprites: CompMut<AtlasSprite>,
+) {
+    for (entity, (boss, body, sprite)) in
+        entities.iter_with((&mut bosses, &mut bodies, &mut sprites))
+    {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(
+            boss_meta @ BossMeta {
+                body_size,
+                slam_jump_speed,
+                slam_size,
+                slam_knockback,
+                slam_hitstun,
+                phases,
+                ..
+            },
+        ) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+        let Some(phase) = phases.get(boss.phase) else {
+            continue;
+        };
+        let pos = transforms.get(entity).unwrap().translation;
+
+        // Flash while the boss can't be hurt
+        sprite.color = if boss.hit_cooldown.finished() {
+            Color::WHITE
+        } else {
+            HIT_COLOR
+        };
+
+        if boss.state == BossState::Slamming {
+            if body.is_on_ground && body.velocity.y <= 0.0 {
+                boss.state = BossState::Walking;
+                let region_pos = pos - Vec3::new(0.0, (body_size.y - slam_size.y) / 2.0, 0.0);
+                let region = DamageRegion {
+                    size: *slam_size,
+                    knockback: *slam_knockback,
+                    hitstun: *slam_hitstun,
+                    ..default()
+                };
+                commands.add(
+                    move |mut entities: ResMutInit<Entities>,
+                          mut transforms: CompMut<Transform>,
+                          mut damage_regions: CompMut<DamageRegion>,
+                          mut damage_region_owners: CompMut<DamageRegionOwner>,
+                          mut lifetimes: CompMut<Lifetime>| {
+                        let ent = entities.create();
+                        transforms.insert(ent, Transform::from_translation(region_pos));
+                        damage_regions.insert(ent, region);
+                        damage_region_owners.insert(ent, DamageRegionOwner(entity));
+                        lifetimes.insert(ent, Lifetime::new(SLAM_LIFETIME));
+                    },
+                );
+            }
+            continue;
+        }
+
+        boss.target = pick_ai_target(
+            &entities,
+            &player_indexes,
+            &decoys,
+            &smoke_clouds,
+            &transforms,
+            &rng,
+            entity,
+            boss.target,
+        );
+        let Some(target_pos) = boss
+            .target
+            .and_then(|x| transforms.get(x))
+            .map(|x| x.translation)
+        else {
+            body.velocity.x = 0.0;
+            continue;
+        };
+
+        // Walk towards the target
+        let direction = (target_pos.x - pos.x).signum();
+        body.velocity.x = direction * phase.walk_speed;
+        sprite.flip_x = direction < 0.0;
+
+        boss.attack_timer.tick(time.delta());
+        if !boss.attack_timer.finished() || phase.attacks.is_empty() || !body.is_on_ground {
+            continue;
+        }
+        boss.attack_timer = Timer::new(phase.attack_cooldown, TimerMode::Once);
+        let attack = phase.attacks[boss.next_attack % phase.attacks.len()];
+        boss.next_attack = (boss.next_attack + 1) % phase.attacks.len();
+
+        match attack {
+            BossAttack::Slam => {
+                boss.state = BossState::Slamming;
+                body.velocity.y = *slam_jump_speed;
+            }
+            BossAttack::Shoot => {
+                let bullet_meta = boss_meta.bullet;
+                let aim = (target_pos - pos).truncate().normalize_or_zero();
+                let spread = boss_meta.bullet_spread.to_radians();
+                let count = phase.bullets.max(1);
+                let directions = (0..count)
+                    .map(|i| {
+                        let angle = (i as f32 - (count - 1) as f32 / 2.0) * spread;
+                        Vec2::from_angle(angle).rotate(aim)
+                    })
+                    .collect::<Vec<_>>();
+                commands.add(
+                    move |mut entities: ResMutInit<Entities>,
+                          mut transforms: CompMut<Transform>,
+                          mut bullets: CompMut<Bullet>,
+                          mut bullet_handles: CompMut<BulletHandle>| {
+                        for direction in &directions {
+                            let ent = entities.create();
+                            bullets.insert(
+                                ent,
+                                Bullet {
+                                    owner: entity,
+                                    direction: *direction,
+                                    ..default()
+                                },
+                            );
+                            transforms.insert(ent, Transform::from_translation(pos));
+                            bullet_handles.insert(ent, BulletHandle(bullet_meta));
+                        }
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Hurt the bosses that are hit by bullets and damage regions, move them on to their next phase,
+/// and drop their loot when they are defeated.
+fn damage_bosses(
+    time: Res<Time>,
+    mut entities: ResMutInit<Entities>,
+    mut commands: Commands,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    collision_world: CollisionWorld,
+    bullets: Comp<Bullet>,
+    damage_regions: Comp<DamageRegion>,
+    damage_region_owners: Comp<DamageRegionOwner>,
+    transforms: Comp<Transform>,
+    mut bosses: CompMut<Boss>,
+) {
+    let mut defeated = Vec::new();
+    let mut stopped_bullets = Vec::new();
+    for (entity, boss) in entities.iter_with(&mut bosses) {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(BossMeta {
+            body_size, phases, ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+        boss.hit_cooldown.tick(time.delta());
+
+        // Bullets stop at the boss, except for its own
+        let mut hit = false;
+        for collider_ent in collision_world.actor_collisions(entity) {
+            if bullets
+                .get(collider_ent)
+                .is_some_and(|bullet| bullet.owner != entity)
+            {
+                hit = true;
+                stopped_bullets.push(collider_ent);
+            }
+        }
+
+        let pos = transforms.get(entity).unwrap().translation;
+        let boss_rect = Rect::new(pos.x, pos.y, body_size.x, body_size.y);
+        hit |= entities
+            .iter_with((&damage_regions, &transforms))
+            .filter(|(region_ent, _)| {
+                damage_region_owners
+                    .get(*region_ent)
+                    .map_or(true, |owner| owner.0 != entity)
+            })
+            .any(|(_, (region, transform))| {
+                region
+                    .collider_rect(transform.translation)
+                    .overlaps(&boss_rect)
+            });
+
+        if !hit || !boss.hit_cooldown.finished() {
+            continue;
+        }
+        boss.hit_cooldown.reset();
+        boss.health = boss.health.saturating_sub(1);
+
+        // Move on to the last phase that the boss' health is low enough for
+        let health = boss.health_fraction();
+        let phase = phases
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| health <= x.health)
+            .map(|(i, _)| i)
+            .last();
+        if let Some(phase) = phase {
+            if phase > boss.phase {
+                boss.phase = phase;
+                boss.next_attack = 0;
+            }
+        }
+
+        if boss.health == 0 {
+            defeated.push((entity, pos));
+        }
+    }
+
+    for bullet in stopped_bullets {
+        entities.kill(bullet);
+    }
+
+    for (entity, pos) in defeated {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+        let BossMeta { loot, .. } = assets.get(element_meta.data).cast_ref();
+
+        // Spread the loot out around where the boss was defeated
+        let loot = loot.iter().copied().collect::<Vec<_>>();
+        commands.add(
+            move |mut entities: ResMutInit<Entities>,
+                  mut transforms: CompMut<Transform>,
+                  mut spawn_requests: CompMut<SpawnElementRequest>| {
+                for (i, name) in loot.iter().enumerate() {
+                    let offset = (i as f32 - (loot.len() - 1) as f32 / 2.0) * LOOT_SPACING;
+                    let ent = entities.create();
+                    transforms.insert(
+                        ent,
+                        Transform::from_translation(pos + Vec3::new(offset, 0.0, 0.0)),
+                    );
+                    spawn_requests.insert(ent, SpawnElementRequest { name: *name });
+                }
+            },
+        );
+        entities.kill(entity);
+    }
+}
@@ -0,0 +1,274 @@
+//! Small ambient creatures that wander around the map.
+//!
+//! Any element whose data is a [`CritterMeta`] gets the same behavior: it wanders back and forth,
+//! runs away from explosions, and is sent flying by the blasts that catch it. New critters can be
+//! added with just an atlas and some metadata.
+//!
+//! Only [`CrittersMeta::max_active`] critters are active at once, the rest wait at their spawners
+//! until there is room for them.
+
+use crate::prelude::*;
+
+/// The settings shared by all critters.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct CrittersMeta {
+    /// The most critters that can be active in the map at once.
+    pub max_active: u32,
+}
+
+/// A critter, such as a sea slug, that wanders around the map.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("critter"))]
+#[repr(C)]
+pub struct CritterMeta {
+    pub atlas: Handle<Atlas>,
+    pub body_size: Vec2,
+    pub idle_frames: SVec<u32>,
+    pub walk_frames: SVec<u32>,
+    pub fps: f32,
+    pub walk_speed: f32,
+    /// How fast the critter runs away from explosions.
+    pub flee_speed: f32,
+    /// The longest the critter keeps walking or standing still before it picks what to do next.
+    pub wander_time: Duration,
+    /// How close an explosion must be for the critter to run away from it.
+    pub flee_distance: f32,
+    /// How long the critter runs away for.
+    pub flee_time: Duration,
+    /// The velocity that the critter is launched with when a blast catches it, mirrored
+    /// horizontally to point away from the blast.
+    pub launch_velocity: Vec2,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    CritterMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, update_critters);
+}
+
+/// What a critter is doing.
+#[derive(Clone, Copy, Debug, HasSchema, Default, PartialEq, Eq)]
+#[repr(C, u8)]
+pub enum CritterState {
+    #[default]
+    Idle,
+    Walking {
+        left: bool,
+    },
+    Fleeing {
+        left: bool,
+    },
+    /// Flying through the air after being caught by a blast.
+    Launched,
+}
+
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct Critter {
+    pub state: CritterState,
+    /// How long until the critter picks what to do next.
+    pub timer: Timer,
+}
+
+fn hydrate(
+    game_meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut despawns: CompMut<DehydrateOutOfBounds>,
+    mut element_handles: CompMut<ElementHandle>,
+    mut critters: CompMut<Critter>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut spawner_manager: SpawnerManager,
+    assets: Res<AssetServer>,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    let mut active = entities.iter_with(&critters).count();
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        let Ok(CritterMeta {
+            atlas,
+            body_size,
+            idle_frames,
+            fps,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        else {
+            continue;
+        };
+        // Leave the spawner for later if there are too many critters already
+        if active >= game_meta.core.critters.max_active as usize {
+            continue;
+        }
+        active += 1;
+        hydrated.insert(spawner_ent, MapElementHydrated);
+
+        let entity = entities.create();
+        critters.insert(entity, default());
+        transforms.insert(entity, transform);
+        element_handles.insert(entity, element_handle);
+        hydrated.insert(entity, MapElementHydrated);
+        despawns.insert(entity, DehydrateOutOfBounds(spawner_ent));
+        atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+        animated_sprites.insert(
+            entity,
+            AnimatedSprite {
+                frames: idle_frames.iter().cloned().collect(),
+                fps: *fps,
+                repeat: true,
+                ..default()
+            },
+        );
+        bodies.insert(
+            entity,
+            KinematicBody {
+                gravity: game_meta.core.physics.gravity,
+                has_mass: true,
+                has_friction: true,
+                shape: ColliderShape::Rectangle { size: *body_size },
+                ..default()
+            },
+        );
+
+        spawner_manager.create_spawner(spawner_ent, vec![entity]);
+    }
+}
+
+fn update_critters(
+    rng: Res<GlobalRng>,
+    time: Res<Time>,
+    entities: Res<Entities>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    damage_events: Res<DamageEvents>,
+    damage_regions: Comp<DamageRegion>,
+    transforms: Comp<Transform>,
+    mut critters: CompMut<Critter>,
+    mut bodies: CompMut<KinematicBody>,
+    mut sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+) {
+    // Explosions launch critters, unlike other damage regions like sword swings
+    let blasts = entities
+        .iter_with((&damage_regions, &transforms))
+        .filter(|(_, (region, _))| region.blast)
+        .map(|(_, (region, transform))| region.collider_rect(transform.translation))
+        .collect::<Vec<_>>();
+
+    for (entity, (critter, body, sprite, animated_sprite)) in entities.iter_with((
+        &mut critters,
+        &mut bodies,
+        &mut sprites,
+        &mut animated_sprites,
+    )) {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(CritterMeta {
+            idle_frames,
+            walk_frames,
+            fps,
+            walk_speed,
+            flee_speed,
+            wander_time,
+            flee_distance,
+            flee_time,
+            launch_velocity,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+        let transform = transforms.get(entity).unwrap();
+        let pos = transform.translation.xy();
+        let previous_state = critter.state;
+
+        if critter.state == CritterState::Launched {
+            if body.is_on_ground && body.velocity.y <= 0.0 {
+                critter.state = CritterState::Idle;
+                critter.timer = Timer::new(*wander_time, TimerMode::Once);
+            }
+        } else if let Some(blast) = blasts
+            .iter()
+            .find(|x| x.overlaps(&body.bounding_box(*transform)))
+        {
+            critter.state = CritterState::Launched;
+            let mut velocity = *launch_velocity;
+            if pos.x < blast.center().x {
+                velocity.x = -velocity.x;
+            }
+            body.velocity = velocity;
+        } else if let Some(event) = damage_events
+            .events
+            .iter()
+            .find(|x| x.age == 0 && x.pos.distance(pos) <= *flee_distance)
+        {
+            critter.state = CritterState::Fleeing {
+                left: event.pos.x > pos.x,
+            };
+            critter.timer = Timer::new(*flee_time, TimerMode::Once);
+        } else {
+            // Wander about, standing still or walking in a random direction for a while
+            critter.timer.tick(time.delta());
+            if critter.timer.finished() {
+                critter.state = if rng.bool() {
+                    CritterState::Idle
+                } else {
+                    CritterState::Walking { left: rng.bool() }
+                };
+                critter.timer = Timer::new(wander_time.mul_f32(rng.f32()), TimerMode::Once);
+            }
+        }
+
+        match critter.state {
+            CritterState::Idle => body.velocity.x = 0.0,
+            CritterState::Walking { left } => {
+                body.velocity.x = if left { -walk_speed } else { *walk_speed };
+                sprite.flip_x = left;
+            }
+            CritterState::Fleeing { left } => {
+                body.velocity.x = if left { -flee_speed } else { *flee_speed };
+                sprite.flip_x = left;
+            }
+            CritterState::Launched => (),
+        }
+
+        let is_moving = |state| {
+            matches!(
+                state,
+                CritterState::Walking { .. } | CritterState::Fleeing { .. }
+            )
+        };
+        if is_moving(critter.state) != is_moving(previous_state) {
+            let frames = if is_moving(critter.state) {
+                walk_frames
+            } else {
+                idle_frames
+            };
+            *animated_sprite = AnimatedSprite {
+                frames: frames.iter().cloned().collect(),
+                fps: *fps,
+                repeat: true,
+                ..default()
+            };
+        }
+    }
+}
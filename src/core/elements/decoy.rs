@@ -0,0 +1,334 @@
+use crate::prelude::*;
+
+/// An item that sends out a fake copy of the player that uses it.
+///
+/// The copy walks forward until it hits a wall, is hit by a damage region, or runs out of time.
+/// AI players chase it like any other player.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("decoy"))]
+#[repr(C)]
+pub struct DecoyMeta {
+    pub atlas: Handle<Atlas>,
+    pub body_size: Vec2,
+    pub fin_anim: Ustr,
+    pub grab_offset: Vec2,
+    pub can_rotate: bool,
+    pub bounciness: f32,
+    pub throw_velocity: f32,
+    pub angular_velocity: f32,
+
+    /// How fast the copy walks, in pixels per second.
+    pub walk_speed: f32,
+    /// The longest that the copy lasts for if nothing stops it.
+    pub decoy_lifetime: Duration,
+    pub spawn_sound: Handle<AudioSource>,
+    pub spawn_sound_volume: f64,
+
+    /// The puff of smoke left behind when the copy disappears.
+    pub poof_atlas: Handle<Atlas>,
+    pub poof_frames: u32,
+    pub poof_fps: f32,
+    pub poof_lifetime: f32,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    DecoyMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, use_decoy_items)
+        .add_system_to_stage(CoreStage::PostUpdate, update_decoys);
+}
+
+#[derive(Clone, HasSchema, Debug, Copy, Default)]
+pub struct DecoyItem;
+
+/// A fake player spawned by a decoy item.
+#[derive(Clone, HasSchema, Debug, Default)]
+pub struct Decoy {
+    /// The player that the decoy is a copy of.
+    pub player: Entity,
+    /// The decoy element, used to look up its metadata.
+    pub element: Handle<ElementMeta>,
+    /// The amount of time left until the decoy disappears on its own.
+    pub lifetime: Timer,
+}
+
+fn hydrate(
+    game_meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut element_handles: CompMut<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut decoy_items: CompMut<DecoyItem>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut items: CompMut<Item>,
+    mut item_throws: CompMut<ItemThrow>,
+    mut item_grabs: CompMut<ItemGrab>,
+    mut respawn_points: CompMut<DehydrateOutOfBounds>,
+    mut spawner_manager: SpawnerManager,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        if let Ok(DecoyMeta {
+            atlas,
+            body_size,
+            fin_anim,
+            grab_offset,
+            can_rotate,
+            bounciness,
+            throw_velocity,
+            angular_velocity,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        {
+            hydrated.insert(spawner_ent, MapElementHydrated);
+
+            let entity = entities.create();
+            items.insert(entity, Item);
+            decoy_items.insert(entity, DecoyItem);
+            item_throws.insert(
+                entity,
+                ItemThrow::strength(*throw_velocity).with_spin(*angular_velocity),
+            );
+            item_grabs.insert(
+                entity,
+                ItemGrab {
+                    fin_anim: *fin_anim,
+                    sync_animation: false,
+                    grab_offset: *grab_offset,
+                },
+            );
+            atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+            respawn_points.insert(entity, DehydrateOutOfBounds(spawner_ent));
+            transforms.insert(entity, transform);
+            element_handles.insert(entity, element_handle);
+            hydrated.insert(entity, MapElementHydrated);
+            bodies.insert(
+                entity,
+                KinematicBody {
+                    shape: ColliderShape::Rectangle { size: *body_size },
+                    has_mass: true,
+                    has_friction: true,
+                    can_rotate: *can_rotate,
+                    bounciness: *bounciness,
+                    gravity: game_meta.core.physics.gravity,
+                    ..default()
+                },
+            );
+            spawner_manager.create_spawner(spawner_ent, vec![entity])
+        }
+    }
+}
+
+/// Send out a decoy when a decoy item is used, using up the item.
+fn use_decoy_items(
+    entities: Res<Entities>,
+    mut commands: Commands,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    decoy_items: Comp<DecoyItem>,
+    mut items_used: CompMut<ItemUsed>,
+    spawners: Comp<DehydrateOutOfBounds>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    player_indexes: Comp<PlayerIdx>,
+    player_inputs: Res<MatchInputs>,
+    transforms: Comp<Transform>,
+    sprites: Comp<AtlasSprite>,
+    mut audio_center: ResMut<AudioCenter>,
+) {
+    let used = entities
+        .iter_with((&decoy_items, &element_handles))
+        .filter(|(entity, _)| items_used.contains(*entity))
+        .map(|(entity, (_, element_handle))| (entity, *element_handle))
+        .collect::<Vec<_>>();
+
+    for (entity, element_handle) in used {
+        let owner = items_used.remove(entity).unwrap().owner;
+        let Some(player_idx) = player_indexes.get(owner) else {
+            continue;
+        };
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(DecoyMeta {
+            spawn_sound,
+            spawn_sound_volume,
+            decoy_lifetime,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        audio_center.play_sound(*spawn_sound, *spawn_sound_volume);
+
+        // Cause the item to respawn by un-hydrating it's spawner.
+        if let Some(spawner) = spawners.get(entity) {
+            hydrated.remove(**spawner);
+        }
+
+        let player_meta = assets.get(player_inputs.players[player_idx.0 as usize].selected_player);
+        let body_atlas = player_meta.layers.body.atlas;
+        let animations = player_meta.layers.body.animations.frames.clone();
+        let shape = player_collider_shape(&*player_meta);
+        let gravity = player_meta.gravity;
+        let transform = *transforms.get(owner).unwrap();
+        let flip_x = sprites.get(owner).map(|x| x.flip_x).unwrap_or_default();
+        let decoy = Decoy {
+            player: owner,
+            element: element_handle.0,
+            lifetime: Timer::new(*decoy_lifetime, TimerMode::Once),
+        };
+
+        commands.add(
+            move |mut entities: ResMutInit<Entities>,
+                  mut transforms: CompMut<Transform>,
+                  mut decoys: CompMut<Decoy>,
+                  mut atlas_sprites: CompMut<AtlasSprite>,
+                  mut animation_bank_sprites: CompMut<AnimationBankSprite>,
+                  mut bodies: CompMut<KinematicBody>| {
+                // Use up the item
+                entities.kill(entity);
+
+                let ent = entities.create();
+                transforms.insert(ent, transform);
+                decoys.insert(ent, decoy.clone());
+                atlas_sprites.insert(
+                    ent,
+                    AtlasSprite {
+                        atlas: body_atlas,
+                        flip_x,
+                        ..default()
+                    },
+                );
+                animation_bank_sprites.insert(
+                    ent,
+                    AnimationBankSprite {
+                        current: "idle".into(),
+                        animations: animations.clone(),
+                        last_animation: default(),
+                    },
+                );
+                bodies.insert(
+                    ent,
+                    KinematicBody {
+                        shape,
+                        has_mass: true,
+                        gravity,
+                        ..default()
+                    },
+                );
+            },
+        );
+    }
+}
+
+fn update_decoys(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    mut commands: Commands,
+    assets: Res<AssetServer>,
+    mut decoys: CompMut<Decoy>,
+    mut bodies: CompMut<KinematicBody>,
+    transforms: Comp<Transform>,
+    sprites: Comp<AtlasSprite>,
+    damage_regions: Comp<DamageRegion>,
+    damage_region_owners: Comp<DamageRegionOwner>,
+    map: Res<LoadedMap>,
+) {
+    for (entity, (decoy, body, transform, sprite)) in
+        entities.iter_with((&mut decoys, &mut bodies, &transforms, &sprites))
+    {
+        let element_meta = assets.get(decoy.element);
+        let asset = assets.get(element_meta.data);
+        let Ok(DecoyMeta {
+            walk_speed,
+            poof_atlas,
+            poof_frames,
+            poof_fps,
+            poof_lifetime,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        decoy.lifetime.tick(time.delta());
+
+        // The decoy was walking last frame, so if it isn't moving anymore it walked into a wall.
+        let hit_wall = decoy.lifetime.elapsed() > time.delta() && body.velocity.x == 0.0;
+
+        let decoy_rect = body.bounding_box(*transform);
+        let hit = entities.iter_with((&damage_regions, &transforms)).any(
+            |(region_ent, (damage_region, region_transform))| {
+                // Decoys aren't fooled by their own player's attacks
+                damage_region_owners.get(region_ent).map(|x| x.0) != Some(decoy.player)
+                    && damage_region
+                        .collider_rect(region_transform.translation)
+                        .overlaps(&decoy_rect)
+            },
+        );
+
+        if hit_wall
+            || hit
+            || decoy.lifetime.finished()
+            || map.is_out_of_bounds(&transform.translation)
+        {
+            let mut poof_transform = *transform;
+            poof_transform.translation.z += 1.0;
+            let poof_atlas = *poof_atlas;
+            let poof_frames = *poof_frames;
+            let poof_fps = *poof_fps;
+            let poof_lifetime = *poof_lifetime;
+            commands.add(
+                move |mut entities: ResMutInit<Entities>,
+                      mut transforms: CompMut<Transform>,
+                      mut lifetimes: CompMut<Lifetime>,
+                      mut sprites: CompMut<AtlasSprite>,
+                      mut animated_sprites: CompMut<AnimatedSprite>| {
+                    entities.kill(entity);
+
+                    let ent = entities.create();
+                    transforms.insert(ent, poof_transform);
+                    sprites.insert(
+                        ent,
+                        AtlasSprite {
+                            atlas: poof_atlas,
+                            ..default()
+                        },
+                    );
+                    animated_sprites.insert(
+                        ent,
+                        AnimatedSprite {
+                            frames: (0..poof_frames).collect(),
+                            fps: poof_fps,
+                            repeat: false,
+                            ..default()
+                        },
+                    );
+                    lifetimes.insert(ent, Lifetime::new(poof_lifetime));
+                },
+            );
+            continue;
+        }
+
+        body.velocity.x = if sprite.flip_x { -1.0 } else { 1.0 } * walk_speed;
+    }
+}
@@ -0,0 +1,144 @@
+use crate::prelude::*;
+
+/// A door or elevator: a solid block that moves when it is opened by a [`Switch`].
+///
+/// The door is open while any of the switches with the same [`ElementLink`] are on, and slides
+/// by its `open_offset` from where it was placed. Map events can flip the link too, which opens
+/// the door while the switches are off, and closes it while they are on. Doors without a link
+/// never move.
+///
+/// Only the solid block moves, the door element itself stays where it was placed, so that the
+/// map is saved with the door closed.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("door"))]
+#[repr(C)]
+pub struct DoorMeta {
+    /// The size of the solid block.
+    pub size: Vec2,
+    pub color: Color,
+    /// How far the door moves from where it was placed when it is opened.
+    pub open_offset: Vec2,
+    /// How fast the door moves, in pixels per second.
+    pub speed: f32,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    DoorMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::Update, update);
+}
+
+#[derive(Clone, Copy, Debug, HasSchema, Default)]
+pub struct Door {
+    pub open_offset: Vec2,
+    pub speed: f32,
+    /// How far the door has moved from where it was placed.
+    pub offset: Vec2,
+}
+
+fn hydrate(
+    entities: Res<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    transforms: Comp<Transform>,
+    mut doors: CompMut<Door>,
+    mut solids: CompMut<Solid>,
+    mut paths: CompMut<Path2d>,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    for entity in entities.iter_with_bitset(&not_hydrated_bitset) {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        let Ok(DoorMeta {
+            size,
+            color,
+            open_offset,
+            speed,
+        }) = assets.get(element_meta.data).try_cast_ref()
+        else {
+            continue;
+        };
+        hydrated.insert(entity, MapElementHydrated);
+        let pos = transforms.get(entity).unwrap().translation.xy();
+        doors.insert(
+            entity,
+            Door {
+                open_offset: *open_offset,
+                speed: *speed,
+                offset: Vec2::ZERO,
+            },
+        );
+        solids.insert(
+            entity,
+            Solid {
+                pos,
+                size: *size,
+                ..default()
+            },
+        );
+        // Draw the block as a line as thick as the door is wide
+        paths.insert(
+            entity,
+            Path2d {
+                color: *color,
+                points: vec![vec2(0.0, -size.y / 2.0), vec2(0.0, size.y / 2.0)],
+                thickness: size.x,
+                ..default()
+            },
+        );
+    }
+}
+
+/// Move the doors towards their open or closed positions, depending on their switches.
+fn update(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    switches: Comp<Switch>,
+    links: Comp<ElementLink>,
+    map_events: Res<MapEventScheduler>,
+    transforms: Comp<Transform>,
+    mut doors: CompMut<Door>,
+    mut solids: CompMut<Solid>,
+    mut paths: CompMut<Path2d>,
+) {
+    let on_links = entities
+        .iter_with((&switches, &links))
+        .filter(|(_, (switch, _))| switch.on)
+        .map(|(_, (_, link))| *link)
+        .collect::<Vec<_>>();
+
+    for (entity, (door, solid, path, transform)) in
+        entities.iter_with((&mut doors, &mut solids, &mut paths, &transforms))
+    {
+        let open = links
+            .get(entity)
+            .is_some_and(|x| on_links.contains(x) != map_events.is_link_toggled(x.0));
+        let target = if open { door.open_offset } else { Vec2::ZERO };
+
+        let step = door.speed * time.delta_seconds();
+        let to_target = target - door.offset;
+        door.offset = if to_target.length() <= step {
+            target
+        } else {
+            door.offset + to_target.normalize() * step
+        };
+        solid.pos = transform.translation.xy() + door.offset;
+
+        let half_height = solid.size.y / 2.0;
+        path.points = vec![
+            door.offset - vec2(0.0, half_height),
+            door.offset + vec2(0.0, half_height),
+        ];
+    }
+}
@@ -226,13 +226,14 @@ fn explode_flappy_jellyfish(
     element_handles: Comp<ElementHandle>,
     assets: Res<AssetServer>,
     mut transforms: CompMut<Transform>,
-    mut audio_center: ResMut<AudioCenter>,
-    mut trauma_events: ResMut<CameraTraumaEvents>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
     mut sprites: CompMut<AtlasSprite>,
     mut animated_sprites: CompMut<AnimatedSprite>,
     mut damage_regions: CompMut<DamageRegion>,
     mut lifetimes: CompMut<Lifetime>,
     mut dehydrate_jellyfish: CompMut<DehydrateJellyfish>,
+    storage: Res<Storage>,
 ) {
     // Collect the hitboxes of all players
     let mut player_hitboxes =
@@ -303,9 +304,13 @@ fn explode_flappy_jellyfish(
 
         entities.kill(flappy_ent);
 
-        audio_center.play_sound(flappy_meta.explosion_sound, flappy_meta.explosion_volume);
+        spatial_audio.play_sound_at(
+            flappy_meta.explosion_sound,
+            flappy_meta.explosion_volume,
+            explosion_transform.translation.xy(),
+        );
 
-        trauma_events.send(5.0);
+        camera_shake_events.send(CameraShakeKind::Explosion);
 
         // Explosion animation entity
         {
@@ -320,12 +325,12 @@ fn explode_flappy_jellyfish(
             );
             animated_sprites.insert(
                 explosion_ent,
-                AnimatedSprite {
-                    frames: (0..flappy_meta.explosion_frames).collect(),
-                    fps: flappy_meta.explosion_fps,
-                    repeat: false,
-                    ..default()
-                },
+                explosion_animation(
+                    flappy_meta.explosion_frames,
+                    flappy_meta.explosion_fps,
+                    flappy_meta.explosion_lifetime,
+                    &storage,
+                ),
             );
             lifetimes.insert(explosion_ent, Lifetime::new(flappy_meta.explosion_lifetime));
         }
@@ -338,6 +343,8 @@ fn explode_flappy_jellyfish(
                 damage_ent,
                 DamageRegion {
                     size: flappy_meta.damage_region_size,
+                    blast: true,
+                    ..default()
                 },
             );
             lifetimes.insert(
@@ -10,6 +10,8 @@ pub struct GrenadeMeta {
     pub grab_offset: Vec2,
     pub damage_region_size: Vec2,
     pub damage_region_lifetime: f32,
+    /// How long the match freezes for when the explosion kills a player.
+    pub hitstop: Duration,
     pub throw_velocity: f32,
     pub explosion_lifetime: f32,
     pub explosion_frames: u32,
@@ -26,6 +28,7 @@ pub struct GrenadeMeta {
     pub explosion_atlas: Handle<Atlas>,
     pub bounciness: f32,
     pub angular_velocity: f32,
+    pub chain_reaction: ChainReactionMeta,
 }
 
 pub fn game_plugin(game: &mut Game) {
@@ -36,6 +39,7 @@ pub fn game_plugin(game: &mut Game) {
 pub fn session_plugin(session: &mut SessionBuilder) {
     session
         .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, chain_react_grenades)
         .add_system_to_stage(CoreStage::PostUpdate, update_lit_grenades)
         .add_system_to_stage(CoreStage::PostUpdate, update_idle_grenades);
 }
@@ -51,6 +55,23 @@ pub struct LitGrenade {
     pub fuse_time: Timer,
 }
 
+impl LitGrenade {
+    /// Burn the fuse down so that no more than `left` of it remains.
+    pub fn shorten_fuse(&mut self, left: Duration) {
+        let remaining = self.fuse_time.remaining();
+        if remaining > left {
+            self.fuse_time.tick(remaining - left);
+        }
+    }
+}
+
+/// Show the burning fuse of a lit grenade.
+fn animate_lit_grenade(animated_sprite: &mut AnimatedSprite) {
+    animated_sprite.frames = [3, 4, 5].into_iter().collect();
+    animated_sprite.repeat = true;
+    animated_sprite.fps = 8.0;
+}
+
 fn hydrate(
     game_meta: Root<GameMeta>,
     mut entities: ResMutInit<Entities>,
@@ -65,6 +86,7 @@ fn hydrate(
     mut items: CompMut<Item>,
     mut item_throws: CompMut<ItemThrow>,
     mut item_grabs: CompMut<ItemGrab>,
+    mut hold_to_use: CompMut<HoldToUse>,
     mut respawn_points: CompMut<DehydrateOutOfBounds>,
     mut spawner_manager: SpawnerManager,
 ) {
@@ -98,6 +120,7 @@ fn hydrate(
             let entity = entities.create();
             items.insert(entity, Item);
             idle_grenades.insert(entity, IdleGrenade);
+            hold_to_use.insert(entity, HoldToUse::default());
             item_throws.insert(
                 entity,
                 ItemThrow::strength(*throw_velocity).with_spin(*angular_velocity),
@@ -164,10 +187,7 @@ fn update_idle_grenades(
 
         if items_used.get(entity).is_some() {
             // Animate Grenade
-            let animated_sprite = animated_sprites.get_mut(entity).unwrap();
-            animated_sprite.frames = [3, 4, 5].into_iter().collect();
-            animated_sprite.repeat = true;
-            animated_sprite.fps = 8.0;
+            animate_lit_grenade(animated_sprites.get_mut(entity).unwrap());
 
             // Play that hissss sound
             audio_center.play_sound(*fuse_sound, *fuse_sound_volume);
@@ -196,6 +216,67 @@ fn update_idle_grenades(
     }
 }
 
+/// Light the idle grenades caught in explosions, and set off the lit ones, if their chain reaction
+/// is enabled.
+fn chain_react_grenades(
+    entities: Res<Entities>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    damage_regions: Comp<DamageRegion>,
+    transforms: Comp<Transform>,
+    bodies: Comp<KinematicBody>,
+    player_inventories: PlayerInventories,
+    mut audio_center: ResMut<AudioCenter>,
+    mut idle_grenades: CompMut<IdleGrenade>,
+    mut lit_grenades: CompMut<LitGrenade>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+) {
+    let blasts = blast_rects(&entities, &damage_regions, &transforms);
+    if blasts.is_empty() {
+        return;
+    }
+
+    for (entity, (element_handle, transform, body)) in
+        entities.iter_with((&element_handles, &transforms, &bodies))
+    {
+        if !idle_grenades.contains(entity) && !lit_grenades.contains(entity) {
+            continue;
+        }
+        let element_meta = assets.get(element_handle.0);
+        let Ok(GrenadeMeta {
+            chain_reaction,
+            fuse_sound,
+            fuse_sound_volume,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        else {
+            continue;
+        };
+        if !chain_reaction.enabled {
+            continue;
+        }
+        let grenade_rect = body.bounding_box(*transform);
+        let Some((_, attacker)) = blasts.iter().find(|(x, _)| x.overlaps(&grenade_rect)) else {
+            continue;
+        };
+
+        if let Some(grenade) = lit_grenades.get_mut(entity) {
+            grenade.shorten_fuse(chain_reaction.delay);
+        } else if player_inventories.find_item(entity).is_none() {
+            idle_grenades.remove(entity);
+            lit_grenades.insert(
+                entity,
+                LitGrenade {
+                    owner: attacker.unwrap_or_default(),
+                    fuse_time: Timer::new(chain_reaction.delay, TimerMode::Once),
+                },
+            );
+            animate_lit_grenade(animated_sprites.get_mut(entity).unwrap());
+            audio_center.play_sound(*fuse_sound, *fuse_sound_volume);
+        }
+    }
+}
+
 fn update_lit_grenades(
     time: Res<Time>,
     mut commands: Commands,
@@ -203,14 +284,14 @@ fn update_lit_grenades(
     transforms: CompMut<Transform>,
     element_handles: Comp<ElementHandle>,
     spawners: Comp<DehydrateOutOfBounds>,
-    mut audio_center: ResMut<AudioCenter>,
+    mut spatial_audio: ResMut<SpatialAudio>,
     mut lit_grenades: CompMut<LitGrenade>,
     player_inventories: PlayerInventories,
     assets: Res<AssetServer>,
     mut emote_regions: CompMut<EmoteRegion>,
     mut player_layers: CompMut<PlayerLayers>,
     mut hydrated: CompMut<MapElementHydrated>,
-    mut trauma_events: ResMutInit<CameraTraumaEvents>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
 ) {
     for (entity, (grenade, element_handle, spawner)) in
         entities.iter_with((&mut lit_grenades, &element_handles, &spawners))
@@ -221,6 +302,7 @@ fn update_lit_grenades(
             explosion_sound,
             explosion_volume,
             damage_region_lifetime,
+            hitstop,
             damage_region_size,
             explosion_lifetime,
             explosion_atlas,
@@ -233,6 +315,7 @@ fn update_lit_grenades(
             unreachable!();
         };
 
+        // Keep burning the fuse while the grenade is cooked in hand
         grenade.fuse_time.tick(time.delta());
 
         if !emote_regions.contains(entity) {
@@ -265,9 +348,13 @@ fn update_lit_grenades(
 
         // If it's time to explode
         if grenade.fuse_time.finished() {
-            audio_center.play_sound(*explosion_sound, *explosion_volume);
+            spatial_audio.play_sound_at(
+                *explosion_sound,
+                *explosion_volume,
+                transforms.get(entity).unwrap().translation.xy(),
+            );
 
-            trauma_events.send(5.0);
+            camera_shake_events.send(CameraShakeKind::Explosion);
 
             // Cause the item to respawn by un-hydrating it's spawner.
             hydrated.remove(**spawner);
@@ -278,17 +365,20 @@ fn update_lit_grenades(
             // Clone types for move into closure
             let damage_region_size = *damage_region_size;
             let damage_region_lifetime = *damage_region_lifetime;
+            let hitstop = *hitstop;
             let explosion_lifetime = *explosion_lifetime;
             let explosion_atlas = *explosion_atlas;
             let explosion_fps = *explosion_fps;
             let explosion_frames = *explosion_frames;
+            let attacker = grenade.owner;
             commands.add(
                 move |mut entities: ResMutInit<Entities>,
                       mut transforms: CompMut<Transform>,
                       mut damage_regions: CompMut<DamageRegion>,
                       mut lifetimes: CompMut<Lifetime>,
                       mut sprites: CompMut<AtlasSprite>,
-                      mut animated_sprites: CompMut<AnimatedSprite>| {
+                      mut animated_sprites: CompMut<AnimatedSprite>,
+                      storage: Res<Storage>| {
                     // Despawn the grenade
                     entities.kill(entity);
 
@@ -299,6 +389,10 @@ fn update_lit_grenades(
                         ent,
                         DamageRegion {
                             size: damage_region_size,
+                            hitstop,
+                            attacker: Some(attacker),
+                            blast: true,
+                            ..default()
                         },
                     );
                     lifetimes.insert(ent, Lifetime::new(damage_region_lifetime));
@@ -315,12 +409,12 @@ fn update_lit_grenades(
                     );
                     animated_sprites.insert(
                         ent,
-                        AnimatedSprite {
-                            frames: (0..explosion_frames).collect(),
-                            fps: explosion_fps,
-                            repeat: false,
-                            ..default()
-                        },
+                        explosion_animation(
+                            explosion_frames,
+                            explosion_fps,
+                            explosion_lifetime,
+                            &storage,
+                        ),
                     );
                     lifetimes.insert(ent, Lifetime::new(explosion_lifetime));
                 },
@@ -0,0 +1,283 @@
+use crate::prelude::*;
+
+/// A hazard, such as a row of spikes or a pool of lava, that hurts players and destroys items that
+/// touch it.
+///
+/// Hazards without knockback kill players outright, and record a [`HazardKillEvent`] for the kill
+/// feed. Hazards with knockback launch and stun players instead, like a [`DamageRegion`].
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("hazard"))]
+#[repr(C)]
+pub struct HazardMeta {
+    pub kind: HazardKind,
+    /// The size of the hazard in pixels.
+    pub size: Vec2,
+    pub color: Color,
+    /// The velocity that players are launched with, mirrored horizontally to point away from the
+    /// hazard. If this and `hitstun` are both zero, the hazard kills players instead.
+    pub knockback: Vec2,
+    /// How long players hit by the hazard are [`Stunned`] for.
+    pub hitstun: Duration,
+    /// Whether items that fall into the hazard are destroyed. Items that are held are safe.
+    pub destroys_items: bool,
+    /// Whether destroyed items come back at their spawners, instead of being gone for the round.
+    pub respawn_items: bool,
+    /// Whether lit bombs that fall into the hazard blow up right away.
+    pub detonates_bombs: bool,
+}
+
+/// The kinds of hazards, which decide how they are drawn and their icon in the kill feed.
+#[derive(HasSchema, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[derive_type_data(SchemaDeserialize)]
+pub enum HazardKind {
+    #[default]
+    Spikes,
+    Lava,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    HazardMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session.init_resource::<HazardKillEvents>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, kill_players_in_hazards)
+        .add_system_to_stage(CoreStage::PostUpdate, destroy_items_in_hazards);
+}
+
+#[derive(Clone, Copy, Debug, HasSchema, Default)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub size: Vec2,
+    pub destroys_items: bool,
+    pub respawn_items: bool,
+    pub detonates_bombs: bool,
+}
+
+impl Hazard {
+    /// Get the rectangle covered by this hazard, given it's position.
+    pub fn rect(&self, position: Vec3) -> Rect {
+        Rect::new(position.x, position.y, self.size.x, self.size.y)
+    }
+}
+
+/// A player that was killed by a hazard.
+#[derive(Debug, Clone, Copy)]
+pub struct HazardKillEvent {
+    pub player: PlayerIdx,
+    pub kind: HazardKind,
+    /// How many frames ago the player was killed.
+    pub age: u32,
+}
+
+/// Session resource with the recent [`HazardKillEvent`]s, shown in the kill feed.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct HazardKillEvents {
+    pub events: Vec<HazardKillEvent>,
+}
+
+fn hydrate(
+    entities: Res<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut hazards: CompMut<Hazard>,
+    mut damage_regions: CompMut<DamageRegion>,
+    mut paths: CompMut<Path2d>,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    for entity in entities.iter_with_bitset(&not_hydrated_bitset) {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        let Ok(HazardMeta {
+            kind,
+            size,
+            color,
+            knockback,
+            hitstun,
+            destroys_items,
+            respawn_items,
+            detonates_bombs,
+        }) = assets.get(element_meta.data).try_cast_ref()
+        else {
+            continue;
+        };
+        hydrated.insert(entity, MapElementHydrated);
+        hazards.insert(
+            entity,
+            Hazard {
+                kind: *kind,
+                size: *size,
+                destroys_items: *destroys_items,
+                respawn_items: *respawn_items,
+                detonates_bombs: *detonates_bombs,
+            },
+        );
+
+        // Hazards that don't kill knock players back like any other damage region
+        let region = DamageRegion {
+            size: *size,
+            knockback: *knockback,
+            hitstun: *hitstun,
+            ..default()
+        };
+        if region.has_knockback() {
+            damage_regions.insert(entity, region);
+        }
+
+        let half_size = *size / 2.0;
+        let path = match kind {
+            // A row of spike points along the bottom of the hazard
+            HazardKind::Spikes => {
+                let count = (size.x / size.y).round().max(1.0) as usize;
+                let width = size.x / count as f32;
+                Path2d {
+                    color: *color,
+                    points: (0..=count * 2)
+                        .map(|i| {
+                            let y = if i % 2 == 0 {
+                                -half_size.y
+                            } else {
+                                half_size.y
+                            };
+                            vec2(-half_size.x + i as f32 * width / 2.0, y)
+                        })
+                        .collect(),
+                    thickness: 2.0,
+                    ..default()
+                }
+            }
+            // Fill the pool with a single line that is as thick as the pool is tall.
+            HazardKind::Lava => Path2d {
+                color: *color,
+                points: vec![vec2(-half_size.x, 0.0), vec2(half_size.x, 0.0)],
+                thickness: size.y,
+                ..default()
+            },
+        };
+        paths.insert(entity, path);
+    }
+}
+
+/// Kill the players in hazards that don't knock back, and forget the old [`HazardKillEvent`]s.
+fn kill_players_in_hazards(
+    entities: Res<Entities>,
+    mut commands: Commands,
+    hazards: Comp<Hazard>,
+    damage_regions: Comp<DamageRegion>,
+    player_indexes: Comp<PlayerIdx>,
+    transforms: Comp<Transform>,
+    bodies: Comp<KinematicBody>,
+    invincibles: Comp<Invincibility>,
+    killed_players: Comp<PlayerKilled>,
+    mut kill_events: ResMut<HazardKillEvents>,
+) {
+    for event in &mut kill_events.events {
+        event.age += 1;
+    }
+    kill_events
+        .events
+        .retain(|event| event.age < KILL_FEED_LIFETIME);
+
+    let mut bitset = player_indexes.bitset().clone();
+    bitset.bit_and(transforms.bitset());
+    bitset.bit_and(bodies.bitset());
+    bitset.bit_andnot(invincibles.bitset());
+    bitset.bit_andnot(killed_players.bitset());
+
+    for player_ent in entities.iter_with_bitset(&bitset) {
+        let transform = transforms.get(player_ent).unwrap();
+        let player_rect = bodies.get(player_ent).unwrap().bounding_box(*transform);
+
+        let hazard = entities
+            .iter_with((&hazards, &transforms))
+            .filter(|(ent, _)| !damage_regions.contains(*ent))
+            .find(|(_, (hazard, transform))| {
+                hazard.rect(transform.translation).overlaps(&player_rect)
+            });
+        let Some((_, (hazard, hazard_transform))) = hazard else {
+            continue;
+        };
+
+        commands.add(PlayerCommand::kill(
+            player_ent,
+            Some(hazard_transform.translation.xy()),
+        ));
+        kill_events.events.push(HazardKillEvent {
+            player: *player_indexes.get(player_ent).unwrap(),
+            kind: hazard.kind,
+            age: 0,
+        });
+    }
+}
+
+/// Destroy the items that fall into hazards, and blow up the lit bombs that fall into hazards that
+/// detonate them.
+fn destroy_items_in_hazards(
+    mut entities: ResMutInit<Entities>,
+    hazards: Comp<Hazard>,
+    items: Comp<Item>,
+    transforms: Comp<Transform>,
+    bodies: Comp<KinematicBody>,
+    spawners: Comp<DehydrateOutOfBounds>,
+    player_inventories: PlayerInventories,
+    mut lit_grenades: CompMut<LitGrenade>,
+    mut lit_kick_bombs: CompMut<LitKickBomb>,
+    mut hydrated: CompMut<MapElementHydrated>,
+) {
+    let hazard_rects = entities
+        .iter_with((&hazards, &transforms))
+        .map(|(_, (hazard, transform))| (*hazard, hazard.rect(transform.translation)))
+        .collect::<Vec<_>>();
+    if hazard_rects.is_empty() {
+        return;
+    }
+
+    let mut destroyed = Vec::new();
+    for (item_ent, (_, transform, body)) in entities.iter_with((&items, &transforms, &bodies)) {
+        if player_inventories.find_item(item_ent).is_some() {
+            continue;
+        }
+        let item_rect = body.bounding_box(*transform);
+        for (hazard, rect) in &hazard_rects {
+            if !rect.overlaps(&item_rect) {
+                continue;
+            }
+
+            if hazard.detonates_bombs {
+                if let Some(grenade) = lit_grenades.get_mut(item_ent) {
+                    let fuse = grenade.fuse_time.duration();
+                    grenade.fuse_time.tick(fuse);
+                    break;
+                }
+                if let Some(kick_bomb) = lit_kick_bombs.get_mut(item_ent) {
+                    kick_bomb.detonate();
+                    break;
+                }
+            }
+            if hazard.destroys_items {
+                destroyed.push((item_ent, hazard.respawn_items));
+                break;
+            }
+        }
+    }
+
+    for (item_ent, respawn) in destroyed {
+        // Send the item back to its spawner by un-hydrating the spawner
+        if respawn {
+            if let Some(spawner) = spawners.get(item_ent) {
+                hydrated.remove(**spawner);
+            }
+        }
+        entities.kill(item_ent);
+    }
+}
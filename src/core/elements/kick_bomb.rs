@@ -9,6 +9,8 @@ pub struct KickBombMeta {
     pub grab_offset: Vec2,
     pub damage_region_size: Vec2,
     pub damage_region_lifetime: f32,
+    /// How long the match freezes for when the explosion kills a player.
+    pub hitstop: Duration,
     pub kick_velocity: Vec2,
     pub kickable: bool,
     pub throw_velocity: f32,
@@ -32,6 +34,14 @@ pub struct KickBombMeta {
     pub angular_velocity: f32,
     pub arm_delay: Duration,
     pub explode_on_contact: bool,
+    /// How many kicks it takes to set off a lit kick bomb, or `0` to never explode from kicks.
+    pub max_kicks: u32,
+    /// How long after a kick until the next kick is counted, so that a kick that touches the bomb
+    /// over several frames is only counted once.
+    pub kick_cooldown: Duration,
+    /// The smoke coming off the fuse while the bomb is lit.
+    pub smoke_trail: Maybe<ParticleEmitterMeta>,
+    pub chain_reaction: ChainReactionMeta,
 }
 
 pub fn game_plugin(_game: &mut Game) {
@@ -42,6 +52,8 @@ pub fn session_plugin(session: &mut SessionBuilder) {
     session
         .stages
         .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PreUpdate, reload_kick_bombs)
+        .add_system_to_stage(CoreStage::PostUpdate, chain_react_kick_bombs)
         .add_system_to_stage(CoreStage::PostUpdate, update_lit_kick_bombs)
         .add_system_to_stage(CoreStage::PostUpdate, update_idle_kick_bombs);
 }
@@ -49,12 +61,62 @@ pub fn session_plugin(session: &mut SessionBuilder) {
 #[derive(Clone, HasSchema, Default, Debug, Copy)]
 pub struct IdleKickBomb;
 
+/// How long the player that last held a kick bomb can touch it without kicking it, so that
+/// dropping or throwing the bomb doesn't count as a kick.
+pub const HOLDER_KICK_GRACE: Duration = Duration::from_millis(500);
+
 #[derive(Clone, HasSchema, Default, Debug)]
 pub struct LitKickBomb {
     arm_delay: Timer,
     fuse_time: Timer,
     kicking: bool,
     kicks: u32,
+    /// Time since the last counted kick.
+    kick_cooldown: Timer,
+    /// The player that last held the bomb.
+    holder: Option<Entity>,
+    /// Time since the bomb left its holder's hands.
+    holder_grace: Timer,
+    /// The player that last held or kicked the bomb, who is credited with its kills.
+    attacker: Option<Entity>,
+}
+
+impl LitKickBomb {
+    pub fn new(arm_delay: Duration, fuse_time: Duration, kick_cooldown: Duration) -> Self {
+        // The first kick always counts
+        let mut kick_cooldown = Timer::new(kick_cooldown, TimerMode::Once);
+        kick_cooldown.tick(kick_cooldown.duration());
+        Self {
+            arm_delay: Timer::new(arm_delay, TimerMode::Once),
+            fuse_time: Timer::new(fuse_time, TimerMode::Once),
+            kicking: false,
+            kicks: 0,
+            kick_cooldown,
+            holder: None,
+            attacker: None,
+            holder_grace: Timer::new(HOLDER_KICK_GRACE, TimerMode::Once),
+        }
+    }
+
+    /// Burn the rest of the fuse, so that the bomb blows up on its next update.
+    pub fn detonate(&mut self) {
+        let fuse_time = self.fuse_time.duration();
+        self.fuse_time.tick(fuse_time);
+    }
+
+    /// Burn the fuse down so that no more than `left` of it remains.
+    pub fn shorten_fuse(&mut self, left: Duration) {
+        let remaining = self.fuse_time.remaining();
+        if remaining > left {
+            self.fuse_time.tick(remaining - left);
+        }
+    }
+
+    /// Credit the bomb's kills to the given player.
+    pub fn with_attacker(mut self, attacker: Option<Entity>) -> Self {
+        self.attacker = attacker;
+        self
+    }
 }
 
 /// Component containing the kick bombs's metadata handle.
@@ -89,9 +151,11 @@ impl KickBombCommand {
                mut entities: ResMutInit<Entities>,
                mut idle_bombs: CompMut<IdleKickBomb>,
                mut lit_bombs: CompMut<LitKickBomb>,
+               mut emitters: CompMut<ParticleEmitter>,
                mut items: CompMut<Item>,
                mut item_throws: CompMut<ItemThrow>,
                mut item_grabs: CompMut<ItemGrab>,
+               mut hold_to_use: CompMut<HoldToUse>,
                mut kick_bomb_handles: CompMut<KickBombHandle>,
                mut transforms: CompMut<Transform>| {
             // Unwrap entity or spawn if existing entity was not provided.
@@ -118,11 +182,13 @@ impl KickBombCommand {
                 angular_velocity,
                 arm_delay,
                 fuse_time,
+                kick_cooldown,
                 ..
             } = *assets.get(kick_bomb_meta_handle);
 
             kick_bomb_handles.insert(entity, KickBombHandle(kick_bomb_meta_handle));
             items.insert(entity, Item);
+            hold_to_use.insert(entity, HoldToUse::default());
             item_throws.insert(
                 entity,
                 ItemThrow::strength(throw_velocity).with_spin(angular_velocity),
@@ -156,13 +222,16 @@ impl KickBombCommand {
             if lit {
                 lit_bombs.insert(
                     entity,
-                    LitKickBomb {
-                        arm_delay: Timer::new(arm_delay, TimerMode::Once),
-                        fuse_time: Timer::new(fuse_time, TimerMode::Once),
-                        kicking: false,
-                        kicks: 0,
-                    },
+                    LitKickBomb::new(arm_delay, fuse_time, kick_cooldown),
                 );
+                if let Some(smoke_trail) = assets
+                    .get(kick_bomb_meta_handle)
+                    .smoke_trail
+                    .clone()
+                    .option()
+                {
+                    emitters.insert(entity, ParticleEmitter::new(smoke_trail));
+                }
 
                 if let Some(body) = bodies.get_mut(entity) {
                     let horizontal_flip_factor = if player_flip_f.unwrap() {
@@ -233,6 +302,60 @@ fn hydrate(
     }
 }
 
+/// Update the kick bombs in the map when their metadata is hot reloaded.
+fn reload_kick_bombs(
+    entities: Res<Entities>,
+    assets: Res<AssetServer>,
+    meta_reloads: Res<crate::hot_reload::MetaReloads>,
+    kick_bomb_handles: Comp<KickBombHandle>,
+    mut bodies: CompMut<KinematicBody>,
+    mut item_throws: CompMut<ItemThrow>,
+    mut item_grabs: CompMut<ItemGrab>,
+    mut lit_bombs: CompMut<LitKickBomb>,
+) {
+    if meta_reloads.handles.is_empty() {
+        return;
+    }
+    for (entity, kick_bomb_handle) in entities.iter_with(&kick_bomb_handles) {
+        if !meta_reloads.contains(kick_bomb_handle.0) {
+            continue;
+        }
+        let KickBombMeta {
+            fin_anim,
+            grab_offset,
+            body_diameter,
+            can_rotate,
+            bounciness,
+            throw_velocity,
+            angular_velocity,
+            arm_delay,
+            fuse_time,
+            kick_cooldown,
+            ..
+        } = *assets.get(kick_bomb_handle.0);
+
+        if let Some(body) = bodies.get_mut(entity) {
+            body.shape = ColliderShape::Circle {
+                diameter: body_diameter,
+            };
+            body.can_rotate = can_rotate;
+            body.bounciness = bounciness;
+        }
+        if let Some(item_throw) = item_throws.get_mut(entity) {
+            *item_throw = ItemThrow::strength(throw_velocity).with_spin(angular_velocity);
+        }
+        if let Some(item_grab) = item_grabs.get_mut(entity) {
+            item_grab.fin_anim = fin_anim;
+            item_grab.grab_offset = grab_offset;
+        }
+        if let Some(lit_bomb) = lit_bombs.get_mut(entity) {
+            lit_bomb.arm_delay.set_duration(arm_delay);
+            lit_bomb.fuse_time.set_duration(fuse_time);
+            lit_bomb.kick_cooldown.set_duration(kick_cooldown);
+        }
+    }
+}
+
 fn update_idle_kick_bombs(
     entities: Res<Entities>,
     mut commands: Commands,
@@ -253,6 +376,7 @@ fn update_idle_kick_bombs(
             fuse_sound_volume,
             arm_delay,
             fuse_time,
+            kick_cooldown,
             lit_frames_start,
             lit_frames_end,
             lit_fps,
@@ -265,32 +389,101 @@ fn update_idle_kick_bombs(
             animated_sprite.frames = (lit_frames_start..lit_frames_end).collect();
             animated_sprite.repeat = true;
             animated_sprite.fps = lit_fps;
+            let smoke_trail = kick_bomb_meta.smoke_trail.clone();
             commands.add(
-                move |mut idle: CompMut<IdleKickBomb>, mut lit: CompMut<LitKickBomb>| {
+                move |mut idle: CompMut<IdleKickBomb>,
+                      mut lit: CompMut<LitKickBomb>,
+                      mut emitters: CompMut<ParticleEmitter>| {
                     idle.remove(entity);
                     lit.insert(
                         entity,
-                        LitKickBomb {
-                            arm_delay: Timer::new(arm_delay, TimerMode::Once),
-                            fuse_time: Timer::new(fuse_time, TimerMode::Once),
-                            kicking: false,
-                            kicks: 0,
-                        },
+                        LitKickBomb::new(arm_delay, fuse_time, kick_cooldown),
                     );
+                    if let Some(smoke_trail) = smoke_trail.clone().option() {
+                        emitters.insert(entity, ParticleEmitter::new(smoke_trail));
+                    }
                 },
             );
         }
     }
 }
 
+/// Light the idle kick bombs caught in explosions, and set off the lit ones, if their chain reaction
+/// is enabled.
+fn chain_react_kick_bombs(
+    entities: Res<Entities>,
+    kick_bomb_handles: Comp<KickBombHandle>,
+    assets: Res<AssetServer>,
+    damage_regions: Comp<DamageRegion>,
+    transforms: Comp<Transform>,
+    bodies: Comp<KinematicBody>,
+    player_inventories: PlayerInventories,
+    mut audio_center: ResMut<AudioCenter>,
+    mut idle_bombs: CompMut<IdleKickBomb>,
+    mut lit_bombs: CompMut<LitKickBomb>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut emitters: CompMut<ParticleEmitter>,
+) {
+    let blasts = blast_rects(&entities, &damage_regions, &transforms);
+    if blasts.is_empty() {
+        return;
+    }
+
+    for (entity, (kick_bomb_handle, transform, body)) in
+        entities.iter_with((&kick_bomb_handles, &transforms, &bodies))
+    {
+        if !idle_bombs.contains(entity) && !lit_bombs.contains(entity) {
+            continue;
+        }
+        let kick_bomb_meta = assets.get(kick_bomb_handle.0);
+        let KickBombMeta {
+            chain_reaction,
+            fuse_sound,
+            fuse_sound_volume,
+            arm_delay,
+            kick_cooldown,
+            lit_frames_start,
+            lit_frames_end,
+            lit_fps,
+            ..
+        } = &*kick_bomb_meta;
+        if !chain_reaction.enabled {
+            continue;
+        }
+        let bomb_rect = body.bounding_box(*transform);
+        let Some((_, attacker)) = blasts.iter().find(|(x, _)| x.overlaps(&bomb_rect)) else {
+            continue;
+        };
+
+        if let Some(kick_bomb) = lit_bombs.get_mut(entity) {
+            kick_bomb.shorten_fuse(chain_reaction.delay);
+        } else if player_inventories.find_item(entity).is_none() {
+            idle_bombs.remove(entity);
+            lit_bombs.insert(
+                entity,
+                LitKickBomb::new(*arm_delay, chain_reaction.delay, *kick_cooldown)
+                    .with_attacker(*attacker),
+            );
+            let animated_sprite = animated_sprites.get_mut(entity).unwrap();
+            animated_sprite.frames = (*lit_frames_start..*lit_frames_end).collect();
+            animated_sprite.repeat = true;
+            animated_sprite.fps = *lit_fps;
+            if let Some(smoke_trail) = kick_bomb_meta.smoke_trail.clone().option() {
+                emitters.insert(entity, ParticleEmitter::new(smoke_trail));
+            }
+            audio_center.play_sound(*fuse_sound, *fuse_sound_volume);
+        }
+    }
+}
+
 fn update_lit_kick_bombs(
     entities: Res<Entities>,
     kick_bomb_handles: Comp<KickBombHandle>,
     assets: Res<AssetServer>,
     collision_world: CollisionWorld,
     player_indexes: Comp<PlayerIdx>,
-    mut audio_center: ResMut<AudioCenter>,
-    mut trauma_events: ResMutInit<CameraTraumaEvents>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
     mut lit_grenades: CompMut<LitKickBomb>,
     mut sprites: CompMut<AtlasSprite>,
     mut bodies: CompMut<KinematicBody>,
@@ -312,8 +505,10 @@ fn update_lit_kick_bombs(
             explode_on_contact,
             kick_velocity,
             kickable,
+            max_kicks,
             damage_region_lifetime,
             damage_region_size,
+            hitstop,
             explosion_lifetime,
             explosion_atlas,
             explosion_fps,
@@ -321,8 +516,12 @@ fn update_lit_kick_bombs(
             ..
         } = *kick_bomb_meta;
 
+        // The fuse burns even while the bomb is held, so cooking it for too long blows up the
+        // player holding it.
         kick_bomb.fuse_time.tick(time.delta());
         kick_bomb.arm_delay.tick(time.delta());
+        kick_bomb.kick_cooldown.tick(time.delta());
+        kick_bomb.holder_grace.tick(time.delta());
 
         let should_explode = 'should_explode: {
             if kick_bomb.fuse_time.finished() {
@@ -348,26 +547,36 @@ fn update_lit_kick_bombs(
             }
 
             // If the item is being held
-            if player_inventories.find_item(entity).is_some() {
+            if let Some(inventory) = player_inventories.find_item(entity) {
                 kick_bomb.kicking = false;
+                kick_bomb.holder = Some(inventory.player);
+                kick_bomb.attacker = Some(inventory.player);
+                kick_bomb.holder_grace.reset();
                 break 'should_explode false;
             }
 
             if kickable {
+                // The player that just let go of the bomb can't kick it right away
+                let in_holder_grace = !kick_bomb.holder_grace.finished();
+                let holder = kick_bomb.holder;
+
                 // If the item is colliding with a non-invincible player
                 if let Some(player_entity) = collision_world
-                    .actor_collisions_filtered(entity, |e| !invincibles.contains(e))
+                    .actor_collisions_filtered(entity, |e| {
+                        !invincibles.contains(e) && !(in_holder_grace && Some(e) == holder)
+                    })
                     .into_iter()
                     .find(|&x| player_indexes.contains(x))
                 {
-                    if !std::mem::replace(&mut kick_bomb.kicking, true) {
+                    kick_bomb.attacker = Some(player_entity);
+                    if !std::mem::replace(&mut kick_bomb.kicking, true)
+                        && kick_bomb.kick_cooldown.finished()
+                    {
                         kick_bomb.kicks += 1;
+                        kick_bomb.kick_cooldown.reset();
                     }
 
-                    // Explode on the 3rd kick.
-                    // Dropping the bomb is detected as a kick so we explode when
-                    // the counter reaches 4.
-                    if kick_bomb.kicks > 3 {
+                    if max_kicks > 0 && kick_bomb.kicks >= max_kicks {
                         break 'should_explode true;
                     }
 
@@ -402,9 +611,13 @@ fn update_lit_kick_bombs(
 
         // If it's time to explode
         if should_explode {
-            audio_center.play_sound(explosion_sound, explosion_volume);
+            spatial_audio.play_sound_at(
+                explosion_sound,
+                explosion_volume,
+                transforms.get(entity).unwrap().translation.xy(),
+            );
 
-            trauma_events.send(7.5);
+            camera_shake_events.send(CameraShakeKind::Explosion);
 
             if let Some(spawner) = spawner {
                 // Cause the item to respawn by un-hydrating it's spawner.
@@ -414,6 +627,7 @@ fn update_lit_kick_bombs(
             let mut explosion_transform = *transforms.get(entity).unwrap();
             explosion_transform.translation.z = -10.0; // On top of almost everything
             explosion_transform.rotation = Quat::IDENTITY;
+            let attacker = kick_bomb.attacker;
 
             commands.add(
                 move |mut entities: ResMutInit<Entities>,
@@ -421,7 +635,8 @@ fn update_lit_kick_bombs(
                       mut damage_regions: CompMut<DamageRegion>,
                       mut lifetimes: CompMut<Lifetime>,
                       mut sprites: CompMut<AtlasSprite>,
-                      mut animated_sprites: CompMut<AnimatedSprite>| {
+                      mut animated_sprites: CompMut<AnimatedSprite>,
+                      storage: Res<Storage>| {
                     // Despawn the kick bomb
                     entities.kill(entity);
 
@@ -432,6 +647,10 @@ fn update_lit_kick_bombs(
                         ent,
                         DamageRegion {
                             size: damage_region_size,
+                            hitstop,
+                            attacker,
+                            blast: true,
+                            ..default()
                         },
                     );
                     lifetimes.insert(ent, Lifetime::new(damage_region_lifetime));
@@ -448,12 +667,12 @@ fn update_lit_kick_bombs(
                     );
                     animated_sprites.insert(
                         ent,
-                        AnimatedSprite {
-                            frames: (0..explosion_frames).collect(),
-                            fps: explosion_fps,
-                            repeat: false,
-                            ..default()
-                        },
+                        explosion_animation(
+                            explosion_frames,
+                            explosion_fps,
+                            explosion_lifetime,
+                            &storage,
+                        ),
                     );
                     lifetimes.insert(ent, Lifetime::new(explosion_lifetime));
                 },
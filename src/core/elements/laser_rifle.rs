@@ -0,0 +1,374 @@
+use crate::prelude::*;
+
+/// A rifle that fires a beam which hits instantly, burning the first player along it and scorching
+/// the wall that stops it.
+///
+/// The beam stops at the first player found with [`hitscan_players`], so it is lag compensated when
+/// the host enables it, and burns everything along it with a damage region.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("laser_rifle"))]
+#[repr(C)]
+pub struct LaserRifleMeta {
+    pub grab_offset: Vec2,
+    pub fin_anim: Ustr,
+
+    pub body_size: Vec2,
+    pub bounciness: f32,
+    pub can_rotate: bool,
+    pub throw_velocity: f32,
+    pub angular_velocity: f32,
+    pub atlas: Handle<Atlas>,
+
+    pub max_ammo: u32,
+    pub cooldown: Duration,
+    pub kickback: f32,
+    /// Velocity added to the holder when firing, see [`apply_recoil`].
+    pub recoil: Vec2,
+
+    /// Where the beam starts, relative to the rifle, when facing right.
+    pub beam_spawn_offset: Vec2,
+    /// How far the beam reaches if it doesn't hit a wall.
+    pub range: f32,
+    pub beam_color: Color,
+    pub beam_thickness: f32,
+    /// How long the beam is visible and burns things that touch it for.
+    pub beam_lifetime: Duration,
+
+    /// The mark left on the wall that the beam hits.
+    pub scorch_atlas: Handle<Atlas>,
+    pub scorch_color: Color,
+    pub scorch_lifetime: Duration,
+
+    pub shoot_sound_volume: f64,
+    pub empty_shoot_sound_volume: f64,
+    pub shoot_sound: Handle<AudioSource>,
+    pub empty_shoot_sound: Handle<AudioSource>,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    LaserRifleMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, update);
+}
+
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct LaserRifle {
+    pub ammo: u32,
+    pub cooldown: Timer,
+}
+
+fn hydrate(
+    game_meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut element_handles: CompMut<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut laser_rifles: CompMut<LaserRifle>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut items: CompMut<Item>,
+    mut item_throws: CompMut<ItemThrow>,
+    mut item_grabs: CompMut<ItemGrab>,
+    mut respawn_points: CompMut<DehydrateOutOfBounds>,
+    mut spawner_manager: SpawnerManager,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        if let Ok(LaserRifleMeta {
+            atlas,
+            fin_anim,
+            grab_offset,
+            max_ammo,
+            body_size,
+            can_rotate,
+            bounciness,
+            throw_velocity,
+            angular_velocity,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        {
+            hydrated.insert(spawner_ent, MapElementHydrated);
+
+            let entity = entities.create();
+            items.insert(entity, Item);
+            item_throws.insert(
+                entity,
+                ItemThrow::strength(*throw_velocity)
+                    .with_spin(*angular_velocity)
+                    .with_system(laser_rifle_drop(entity, *max_ammo)),
+            );
+            item_grabs.insert(
+                entity,
+                ItemGrab {
+                    fin_anim: *fin_anim,
+                    sync_animation: false,
+                    grab_offset: *grab_offset,
+                },
+            );
+            laser_rifles.insert(
+                entity,
+                LaserRifle {
+                    ammo: *max_ammo,
+                    cooldown: Timer::new(Duration::from_millis(0), TimerMode::Once),
+                },
+            );
+            atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+            respawn_points.insert(entity, DehydrateOutOfBounds(spawner_ent));
+            transforms.insert(entity, transform);
+            element_handles.insert(entity, element_handle);
+            hydrated.insert(entity, MapElementHydrated);
+            bodies.insert(
+                entity,
+                KinematicBody {
+                    shape: ColliderShape::Rectangle { size: *body_size },
+                    has_mass: true,
+                    has_friction: true,
+                    can_rotate: *can_rotate,
+                    bounciness: *bounciness,
+                    gravity: game_meta.core.physics.gravity,
+                    ..default()
+                },
+            );
+            spawner_manager.create_spawner(spawner_ent, vec![entity])
+        }
+    }
+}
+
+fn update(
+    entities: Res<Entities>,
+    mut commands: Commands,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    collision_world: CollisionWorld,
+
+    mut laser_rifles: CompMut<LaserRifle>,
+    transforms: Comp<Transform>,
+    sprites: Comp<AtlasSprite>,
+    mut audio_center: ResMut<AudioCenter>,
+
+    player_inventories: PlayerInventories,
+    mut items_used: CompMut<ItemUsed>,
+    items_dropped: CompMut<ItemDropped>,
+    time: Res<Time>,
+
+    mut bodies: CompMut<KinematicBody>,
+    lag_compensation: Res<LagCompensation>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    invincibles: Comp<Invincibility>,
+    position_histories: Comp<PositionHistory>,
+) {
+    for (entity, (laser_rifle, element_handle)) in
+        entities.iter_with((&mut laser_rifles, &element_handles))
+    {
+        let element_meta = assets.get(element_handle.0);
+
+        let asset = assets.get(element_meta.data);
+        let Ok(LaserRifleMeta {
+            max_ammo,
+            cooldown,
+            kickback,
+            recoil,
+            beam_spawn_offset,
+            range,
+            beam_color,
+            beam_thickness,
+            beam_lifetime,
+            scorch_atlas,
+            scorch_color,
+            scorch_lifetime,
+            shoot_sound,
+            empty_shoot_sound,
+            shoot_sound_volume,
+            empty_shoot_sound_volume,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        laser_rifle.cooldown.tick(time.delta());
+
+        // If the item is being held
+        if let Some(Inv { player, .. }) = player_inventories.find_item(entity) {
+            // If the item is being used
+            let item_used = items_used.remove(entity).is_some();
+            if item_used && laser_rifle.cooldown.finished() {
+                // Reset fire cooldown
+                laser_rifle.cooldown = Timer::new(*cooldown, TimerMode::Once);
+                // Empty
+                if laser_rifle.ammo == 0 {
+                    audio_center.play_sound(*empty_shoot_sound, *empty_shoot_sound_volume);
+                    continue;
+                }
+
+                laser_rifle.ammo -= 1;
+                audio_center.play_sound(*shoot_sound, *shoot_sound_volume);
+
+                let player_flip_x = sprites.get(player).unwrap().flip_x;
+                apply_recoil(
+                    bodies.get_mut(player).unwrap(),
+                    player_flip_x,
+                    *kickback,
+                    *recoil,
+                );
+
+                let rifle_transform = *transforms.get(entity).unwrap();
+                let direction = if player_flip_x {
+                    vec2(-1.0, 0.0)
+                } else {
+                    vec2(1.0, 0.0)
+                };
+                let origin = rifle_transform.translation.xy()
+                    + vec2(beam_spawn_offset.x * direction.x, beam_spawn_offset.y);
+                let mut hit = hitscan(&collision_world, origin, direction, *range);
+
+                // Stop at the first player in front of the wall, where the shooter saw them
+                let player_hit = hitscan_players(
+                    player,
+                    origin,
+                    direction,
+                    (hit.end - origin).length(),
+                    &entities,
+                    &lag_compensation,
+                    &player_inputs,
+                    &player_indexes,
+                    &killed_players,
+                    &invincibles,
+                    &transforms,
+                    &bodies,
+                    &position_histories,
+                );
+                // The player that was hit, if they have moved off of the beam since the shooter
+                // saw them.
+                let mut rewound_target = None;
+                if let Some(player_hit) = player_hit {
+                    hit.end = player_hit.point;
+                    hit.hit_wall = false;
+
+                    let target_rect = bodies
+                        .get(player_hit.player)
+                        .unwrap()
+                        .bounding_box(*transforms.get(player_hit.player).unwrap());
+                    let beam = beam_rect(origin, hit.end, *beam_thickness);
+                    if !beam.overlaps(&target_rect) {
+                        rewound_target = Some(target_rect);
+                    }
+                }
+
+                let beam_z = rifle_transform.translation.z + 1.0;
+
+                // Clone types for move into closure
+                let beam_color = *beam_color;
+                let beam_thickness = *beam_thickness;
+                let beam_lifetime = *beam_lifetime;
+                let scorch_atlas = *scorch_atlas;
+                let scorch_color = *scorch_color;
+                let scorch_lifetime = *scorch_lifetime;
+
+                commands.add(
+                    move |mut entities: ResMutInit<Entities>,
+                          mut lifetimes: CompMut<Lifetime>,
+                          mut sprites: CompMut<AtlasSprite>,
+                          mut transforms: CompMut<Transform>,
+                          mut paths: CompMut<Path2d>,
+                          mut damage_regions: CompMut<DamageRegion>,
+                          mut damage_region_owners: CompMut<DamageRegionOwner>| {
+                        // Draw the beam
+                        let ent = entities.create();
+                        transforms.insert(ent, Transform::from_translation(origin.extend(beam_z)));
+                        paths.insert(
+                            ent,
+                            Path2d {
+                                color: beam_color,
+                                points: vec![Vec2::ZERO, hit.end - origin],
+                                thickness: beam_thickness,
+                                ..default()
+                            },
+                        );
+                        lifetimes.insert(ent, Lifetime::new(beam_lifetime.as_secs_f32()));
+
+                        // Burn everything along the beam, and the player it hit wherever they are
+                        // now.
+                        let burn_rects = std::iter::once(beam_rect(origin, hit.end, beam_thickness))
+                            .chain(rewound_target);
+                        for rect in burn_rects {
+                            let ent = entities.create();
+                            transforms.insert(
+                                ent,
+                                Transform::from_translation(rect.center().extend(beam_z)),
+                            );
+                            damage_regions.insert(
+                                ent,
+                                DamageRegion {
+                                    size: rect.size(),
+                                    attacker: Some(player),
+                                    ..default()
+                                },
+                            );
+                            damage_region_owners.insert(ent, DamageRegionOwner(player));
+                            lifetimes.insert(ent, Lifetime::new(beam_lifetime.as_secs_f32()));
+                        }
+
+                        // Scorch the wall
+                        if hit.hit_wall {
+                            let ent = entities.create();
+                            transforms
+                                .insert(ent, Transform::from_translation(hit.end.extend(beam_z)));
+                            sprites.insert(
+                                ent,
+                                AtlasSprite {
+                                    atlas: scorch_atlas,
+                                    color: scorch_color,
+                                    flip_x: player_flip_x,
+                                    ..default()
+                                },
+                            );
+                            lifetimes.insert(ent, Lifetime::new(scorch_lifetime.as_secs_f32()));
+                        }
+                    },
+                );
+            }
+        }
+
+        // If the item was dropped
+        if items_dropped.get(entity).is_some() {
+            // Recharge the rifle
+            laser_rifle.ammo = *max_ammo;
+        }
+    }
+}
+
+/// Get the rectangle covered by a beam from `origin` to `end`.
+fn beam_rect(origin: Vec2, end: Vec2, thickness: f32) -> Rect {
+    let center = (origin + end) / 2.0;
+    let size = (end - origin).abs() + Vec2::splat(thickness);
+    Rect::new(center.x, center.y, size.x, size.y)
+}
+
+fn laser_rifle_drop(entity: Entity, max_ammo: u32) -> StaticSystem<(), ()> {
+    (move |mut laser_rifles: CompMut<LaserRifle>| {
+        // Recharge the rifle
+        laser_rifles.get_mut(entity).unwrap().ammo = max_ammo;
+    })
+    .system()
+}
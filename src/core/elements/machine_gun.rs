@@ -21,6 +21,8 @@ pub struct MachineGunMeta {
     pub bullet_spread: f32,
     pub bullet_spawn_offset: Vec2,
     pub kickback: f32,
+    /// Velocity added to the holder when firing, see [`apply_recoil`].
+    pub recoil: Vec2,
 
     pub shoot_sound_volume: f64,
     pub empty_shoot_sound_volume: f64,
@@ -182,6 +184,7 @@ fn update(
             shoot_sound_volume,
             empty_shoot_sound_volume,
             kickback,
+            recoil,
             ..
         }) = asset.try_cast_ref()
         else {
@@ -243,8 +246,8 @@ fn update(
                     let player_flip_x = player_sprite.flip_x;
                     let player_body = bodies.get_mut(player).unwrap();
 
-                    //Set kickback
-                    player_body.velocity.x = if player_flip_x { 1.0 } else { -1.0 } * kickback;
+                    // Push the player back
+                    apply_recoil(player_body, player_flip_x, *kickback, *recoil);
 
                     let mut shoot_animation_transform = *transforms.get(entity).unwrap();
                     let bullet_spawn_offset = *bullet_spawn_offset;
@@ -277,6 +280,7 @@ fn update(
                                         } else {
                                             vec2(1.0, (rng.f32() - 0.5) * bullet_spread)
                                         },
+                                        ..default()
                                     },
                                 );
                                 transforms.insert(ent, shoot_animation_transform);
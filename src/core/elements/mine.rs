@@ -9,6 +9,8 @@ pub struct MineMeta {
 
     pub damage_region_size: Vec2,
     pub damage_region_lifetime: f32,
+    /// How long the match freezes for when the explosion kills a player.
+    pub hitstop: Duration,
     pub explosion_atlas: Handle<Atlas>,
     pub explosion_lifetime: f32,
     pub explosion_frames: u32,
@@ -49,6 +51,8 @@ pub struct IdleMine;
 pub struct ThrownMine {
     // The mine won't explode until this timer finishes.
     arm_delay: Timer,
+    /// The player that threw the mine, who is credited with its kills.
+    owner: Entity,
 }
 
 fn hydrate(
@@ -159,6 +163,7 @@ fn update_idle_mines(
                                     Duration::from_secs_f32(arm_delay),
                                     TimerMode::Once,
                                 ),
+                                owner: player,
                             },
                         );
                     },
@@ -173,7 +178,8 @@ fn update_thrown_mines(
     element_handles: Comp<ElementHandle>,
     assets: Res<AssetServer>,
     mut audio_center: ResMut<AudioCenter>,
-    mut trauma_events: ResMutInit<CameraTraumaEvents>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
     mut thrown_mines: CompMut<ThrownMine>,
     mut animated_sprites: CompMut<AnimatedSprite>,
     mut hydrated: CompMut<MapElementHydrated>,
@@ -208,6 +214,7 @@ fn update_thrown_mines(
             armed_fps,
             damage_region_size,
             damage_region_lifetime,
+            hitstop,
             explosion_volume,
             arm_sound_volume,
             explosion_lifetime,
@@ -237,33 +244,41 @@ fn update_thrown_mines(
         if !colliding_with_players.is_empty() && thrown_mine.arm_delay.finished() {
             let mine_transform = *transforms.get(entity).unwrap();
 
-            trauma_events.send(6.0);
+            camera_shake_events.send(CameraShakeKind::Explosion);
 
             for player in &colliding_with_players {
-                commands.add(PlayerCommand::kill(
+                commands.add(PlayerCommand::kill_by(
                     *player,
                     Some(mine_transform.translation.xy()),
+                    Some(thrown_mine.owner),
                 ));
             }
 
-            audio_center.play_sound(*explosion_sound, *explosion_volume);
+            spatial_audio.play_sound_at(
+                *explosion_sound,
+                *explosion_volume,
+                mine_transform.translation.xy(),
+            );
 
             hydrated.remove(**spawner);
 
             // Clone types for move into closure
             let damage_region_size = *damage_region_size;
             let damage_region_lifetime = *damage_region_lifetime;
+            let hitstop = *hitstop;
             let explosion_lifetime = *explosion_lifetime;
             let explosion_atlas = *explosion_atlas;
             let explosion_fps = *explosion_fps;
             let explosion_frames = *explosion_frames;
+            let attacker = thrown_mine.owner;
             commands.add(
                 move |mut entities: ResMutInit<Entities>,
                       mut transforms: CompMut<Transform>,
                       mut damage_regions: CompMut<DamageRegion>,
                       mut lifetimes: CompMut<Lifetime>,
                       mut sprites: CompMut<AtlasSprite>,
-                      mut animated_sprites: CompMut<AnimatedSprite>| {
+                      mut animated_sprites: CompMut<AnimatedSprite>,
+                      storage: Res<Storage>| {
                     let mut explosion_transform = mine_transform;
                     explosion_transform.translation.z = -10.0; // On top of almost everything
                     explosion_transform.rotation = Quat::IDENTITY;
@@ -278,6 +293,10 @@ fn update_thrown_mines(
                         damage_ent,
                         DamageRegion {
                             size: damage_region_size,
+                            hitstop,
+                            attacker: Some(attacker),
+                            blast: true,
+                            ..default()
                         },
                     );
                     lifetimes.insert(damage_ent, Lifetime::new(damage_region_lifetime));
@@ -294,12 +313,12 @@ fn update_thrown_mines(
                     );
                     animated_sprites.insert(
                         ent,
-                        AnimatedSprite {
-                            frames: (0..explosion_frames).collect(),
-                            fps: explosion_fps,
-                            repeat: false,
-                            ..default()
-                        },
+                        explosion_animation(
+                            explosion_frames,
+                            explosion_fps,
+                            explosion_lifetime,
+                            &storage,
+                        ),
                     );
                     lifetimes.insert(ent, Lifetime::new(explosion_lifetime));
                 },
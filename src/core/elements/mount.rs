@@ -0,0 +1,318 @@
+use crate::prelude::*;
+
+/// A creature that players can climb on and ride around.
+///
+/// The mount is picked up like any other item, and using it climbs on. While riding, the player's
+/// controls drive the mount instead of the player: moving walks it, jump makes it jump and grab
+/// climbs off. The rider is thrown into the air if the mount is hit or falls out of the map.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("mount"))]
+#[repr(C)]
+pub struct MountMeta {
+    pub atlas: Handle<Atlas>,
+    pub body_size: Vec2,
+    pub fin_anim: Ustr,
+    pub grab_offset: Vec2,
+    pub throw_velocity: f32,
+    pub angular_velocity: f32,
+    /// The frames of the atlas that are looped while the mount is being ridden.
+    pub ride_frames: SVec<u32>,
+    pub ride_fps: f32,
+
+    /// Where the rider sits, relative to the center of the mount, when facing right.
+    pub rider_offset: Vec2,
+    /// How fast the mount moves, in pixels per second.
+    pub speed: f32,
+    /// The upward velocity of the mount when it jumps.
+    pub jump_speed: f32,
+    pub gravity: f32,
+    /// The upward velocity of the rider when they climb off or are thrown off.
+    pub dismount_speed: f32,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    MountMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, mount_used)
+        .add_system_to_stage(CoreStage::PostUpdate, update_mounts);
+}
+
+/// A rideable mount.
+#[derive(Clone, HasSchema, Debug, Default)]
+pub struct Mount {
+    /// The player riding the mount.
+    pub rider: Option<Entity>,
+}
+
+/// Component for players that are riding a mount.
+#[derive(Clone, HasSchema, Debug, Default)]
+pub struct RidingMount {
+    pub mount: Entity,
+}
+
+fn hydrate(
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut element_handles: CompMut<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut mounts: CompMut<Mount>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut items: CompMut<Item>,
+    mut item_throws: CompMut<ItemThrow>,
+    mut item_grabs: CompMut<ItemGrab>,
+    mut respawn_points: CompMut<DehydrateOutOfBounds>,
+    mut spawner_manager: SpawnerManager,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        if let Ok(MountMeta {
+            atlas,
+            body_size,
+            fin_anim,
+            grab_offset,
+            throw_velocity,
+            angular_velocity,
+            gravity,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        {
+            hydrated.insert(spawner_ent, MapElementHydrated);
+
+            let entity = entities.create();
+            items.insert(entity, Item);
+            mounts.insert(entity, Mount::default());
+            item_throws.insert(
+                entity,
+                ItemThrow::strength(*throw_velocity).with_spin(*angular_velocity),
+            );
+            item_grabs.insert(
+                entity,
+                ItemGrab {
+                    fin_anim: *fin_anim,
+                    sync_animation: false,
+                    grab_offset: *grab_offset,
+                },
+            );
+            atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+            animated_sprites.insert(entity, default());
+            respawn_points.insert(entity, DehydrateOutOfBounds(spawner_ent));
+            transforms.insert(entity, transform);
+            element_handles.insert(entity, element_handle);
+            hydrated.insert(entity, MapElementHydrated);
+            bodies.insert(
+                entity,
+                KinematicBody {
+                    shape: ColliderShape::Rectangle { size: *body_size },
+                    has_mass: true,
+                    has_friction: true,
+                    gravity: *gravity,
+                    ..default()
+                },
+            );
+            spawner_manager.create_spawner(spawner_ent, vec![entity])
+        }
+    }
+}
+
+/// Climb onto mounts that are used by the player holding them.
+fn mount_used(
+    entities: Res<Entities>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut mounts: CompMut<Mount>,
+    mut items: CompMut<Item>,
+    mut items_used: CompMut<ItemUsed>,
+    mut commands: Commands,
+    mut attachments: CompMut<PlayerBodyAttachment>,
+    mut riding: CompMut<RidingMount>,
+    mut bodies: CompMut<KinematicBody>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+) {
+    let used = entities
+        .iter_with((&mounts, &element_handles))
+        .filter(|(entity, _)| items_used.contains(*entity))
+        .map(|(entity, (_, element_handle))| (entity, *element_handle))
+        .collect::<Vec<_>>();
+
+    for (entity, element_handle) in used {
+        let rider = items_used.remove(entity).unwrap().owner;
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(MountMeta {
+            ride_frames,
+            ride_fps,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        // Take the mount out of the rider's hands without throwing it
+        commands.add(PlayerCommand::set_inventory(rider, None));
+        commands.add(move |mut items_dropped: CompMut<ItemDropped>| {
+            items_dropped.remove(entity);
+        });
+        attachments.remove(entity);
+        items.remove(entity);
+        bodies.get_mut(entity).unwrap().is_deactivated = false;
+        if let Some(animated_sprite) = animated_sprites.get_mut(entity) {
+            animated_sprite.frames = ride_frames.clone();
+            animated_sprite.fps = *ride_fps;
+            animated_sprite.repeat = true;
+        }
+
+        // The rider is carried by the mount, so their own body is switched off
+        bodies.get_mut(rider).unwrap().is_deactivated = true;
+        riding.insert(rider, RidingMount { mount: entity });
+        mounts.get_mut(entity).unwrap().rider = Some(rider);
+    }
+}
+
+fn update_mounts(
+    entities: Res<Entities>,
+    mut commands: Commands,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    damage_regions: Comp<DamageRegion>,
+    damage_region_owners: Comp<DamageRegionOwner>,
+    spawners: Comp<DehydrateOutOfBounds>,
+    map: Res<LoadedMap>,
+    mut mounts: CompMut<Mount>,
+    mut items: CompMut<Item>,
+    mut riding: CompMut<RidingMount>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut hydrated: CompMut<MapElementHydrated>,
+) {
+    let ridden = entities
+        .iter_with((&mounts, &element_handles))
+        .filter_map(|(entity, (mount, element_handle))| {
+            Some((entity, mount.rider?, *element_handle))
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, rider, element_handle) in ridden {
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(MountMeta {
+            rider_offset,
+            speed,
+            jump_speed,
+            dismount_speed,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        // Let the mount go if its rider died
+        let Some(player_idx) = player_indexes
+            .get(rider)
+            .filter(|_| entities.is_alive(rider) && !killed_players.contains(rider))
+        else {
+            if let Some(body) = bodies.get_mut(rider) {
+                body.is_deactivated = false;
+            }
+            riding.remove(rider);
+            release_mount(entity, &mut mounts, &mut items, &mut animated_sprites);
+            continue;
+        };
+        let control = &player_inputs.players[player_idx.0 as usize].control;
+
+        let transform = *transforms.get(entity).unwrap();
+        let mount_rect = bodies.get(entity).unwrap().bounding_box(transform);
+        let destroyed = map.is_out_of_bounds(&transform.translation)
+            || entities.iter_with((&damage_regions, &transforms)).any(
+                |(region_ent, (damage_region, region_transform))| {
+                    damage_region_owners.get(region_ent).map(|x| x.0) != Some(rider)
+                        && damage_region
+                            .collider_rect(region_transform.translation)
+                            .overlaps(&mount_rect)
+                },
+            );
+
+        if destroyed || control.grab_just_pressed {
+            // Drop the rider into the air
+            let rider_body = bodies.get_mut(rider).unwrap();
+            rider_body.is_deactivated = false;
+            rider_body.velocity = vec2(0.0, *dismount_speed);
+            riding.remove(rider);
+
+            if destroyed {
+                // Cause the mount to respawn by un-hydrating it's spawner.
+                if let Some(spawner) = spawners.get(entity) {
+                    hydrated.remove(**spawner);
+                }
+                commands.add(move |mut entities: ResMutInit<Entities>| entities.kill(entity));
+            } else {
+                release_mount(entity, &mut mounts, &mut items, &mut animated_sprites);
+            }
+            continue;
+        }
+
+        // Drive the mount with the rider's controls
+        let body = bodies.get_mut(entity).unwrap();
+        body.velocity.x = control.move_direction.x * speed;
+        if control.jump_just_pressed && body.is_on_ground {
+            body.velocity.y = *jump_speed;
+        }
+
+        let mount_sprite = sprites.get_mut(entity).unwrap();
+        if control.move_direction.x != 0.0 {
+            mount_sprite.flip_x = control.move_direction.x < 0.0;
+        }
+        let flip_x = mount_sprite.flip_x;
+        if let Some(rider_sprite) = sprites.get_mut(rider) {
+            rider_sprite.flip_x = flip_x;
+        }
+
+        // Carry the rider
+        let rider_transform = transforms.get_mut(rider).unwrap();
+        rider_transform.translation.x = transform.translation.x
+            + if flip_x {
+                -rider_offset.x
+            } else {
+                rider_offset.x
+            };
+        rider_transform.translation.y = transform.translation.y + rider_offset.y;
+    }
+}
+
+/// Put a mount back on the map as an item that can be picked up again.
+fn release_mount(
+    entity: Entity,
+    mounts: &mut CompMut<Mount>,
+    items: &mut CompMut<Item>,
+    animated_sprites: &mut CompMut<AnimatedSprite>,
+) {
+    mounts.get_mut(entity).unwrap().rider = None;
+    items.insert(entity, Item);
+    if let Some(animated_sprite) = animated_sprites.get_mut(entity) {
+        *animated_sprite = default();
+    }
+}
@@ -19,6 +19,8 @@ pub struct MusketMeta {
     pub bullet_meta: Handle<BulletMeta>,
     pub bullet_spawn_offset: Vec2,
     pub kickback: f32,
+    /// Velocity added to the holder when firing, see [`apply_recoil`].
+    pub recoil: Vec2,
 
     pub shoot_fps: f32,
     pub shoot_lifetime: f32,
@@ -28,6 +30,8 @@ pub struct MusketMeta {
     pub shoot_atlas: Handle<Atlas>,
     pub shoot_sound: Handle<AudioSource>,
     pub empty_shoot_sound: Handle<AudioSource>,
+    /// The smoke puffed out of the barrel when firing.
+    pub muzzle_smoke: Maybe<ParticleEmitterMeta>,
 }
 
 pub fn game_plugin(game: &mut Game) {
@@ -173,6 +177,8 @@ fn update(
             shoot_sound_volume,
             empty_shoot_sound_volume,
             kickback,
+            recoil,
+            muzzle_smoke,
             ..
         }) = asset.try_cast_ref()
         else {
@@ -202,8 +208,8 @@ fn update(
                 let player_flip_x = player_sprite.flip_x;
                 let player_body = bodies.get_mut(player).unwrap();
 
-                //Set kickback
-                player_body.velocity.x = if player_flip_x { 1.0 } else { -1.0 } * kickback;
+                // Push the player back
+                apply_recoil(player_body, player_flip_x, *kickback, *recoil);
 
                 let mut shoot_animation_transform = *transforms.get(entity).unwrap();
                 let bullet_spawn_offset = *bullet_spawn_offset;
@@ -222,6 +228,14 @@ fn update(
 
                 let bullet_meta = *bullet_meta;
 
+                if let Some(muzzle_smoke) = muzzle_smoke.clone().option() {
+                    commands.add(ParticleEmitter::spawn_burst(
+                        muzzle_smoke,
+                        shoot_animation_transform.translation,
+                        player_flip_x,
+                    ));
+                }
+
                 commands.add(
                     move |mut entities: ResMutInit<Entities>,
                           mut lifetimes: CompMut<Lifetime>,
@@ -267,6 +281,7 @@ fn update(
                                     } else {
                                         vec2(1.0, 0.0)
                                     },
+                                    ..default()
                                 },
                             );
                             transforms.insert(ent, shoot_animation_transform);
@@ -19,6 +19,8 @@ pub struct PeriscopeMeta {
     pub bullet_meta: Handle<BulletMeta>,
     pub bullet_spawn_offset: Vec2,
     pub kickback: f32,
+    /// Velocity added to the holder when firing, see [`apply_recoil`].
+    pub recoil: Vec2,
 
     pub shoot_fps: f32,
     pub shoot_lifetime: f32,
@@ -174,6 +176,7 @@ fn update(
             shoot_sound_volume,
             empty_shoot_sound_volume,
             kickback,
+            recoil,
             ..
         }) = asset.try_cast_ref()
         else {
@@ -203,8 +206,8 @@ fn update(
                 let player_flip_x = player_sprite.flip_x;
                 let player_body = bodies.get_mut(player).unwrap();
 
-                //Set kickback
-                player_body.velocity.x = if player_flip_x { 1.0 } else { -1.0 } * kickback;
+                // Push the player back
+                apply_recoil(player_body, player_flip_x, *kickback, *recoil);
 
                 let mut shoot_animation_transform = *transforms.get(entity).unwrap();
                 let bullet_spawn_offset = *bullet_spawn_offset;
@@ -268,6 +271,7 @@ fn update(
                                     } else {
                                         vec2(1.0, 0.0)
                                     },
+                                    ..default()
                                 },
                             );
                             transforms.insert(ent, shoot_animation_transform);
@@ -15,7 +15,8 @@ pub fn session_plugin(session: &mut SessionBuilder) {
     session
         .stages
         .add_system_to_stage(CoreStage::First, hydrate)
-        .add_system_to_stage(CoreStage::First, update);
+        .add_system_to_stage(CoreStage::First, update)
+        .add_system_to_stage(CoreStage::PostUpdate, update_checkpoints);
 }
 
 /// Marker component for player spawners.
@@ -28,6 +29,16 @@ pub struct PlayerSpawner;
 #[derive(Clone, Debug, HasSchema, Default)]
 pub struct CurrentSpawner(pub usize);
 
+/// Resource tracking the last position that each player stood on the ground at.
+///
+/// Respawning players come back at the spawner farthest from their living opponents, measured from
+/// where those opponents last stood, and fall back to the spawner closest to their own checkpoint
+/// when there are no opponents around.
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct PlayerCheckpoints {
+    pub last_ground_pos: [Option<Vec2>; MAX_PLAYERS as usize],
+}
+
 fn hydrate(
     entities: Res<Entities>,
     mut hydrated: CompMut<MapElementHydrated>,
@@ -53,13 +64,57 @@ fn hydrate(
     }
 }
 
+/// Record where every player last stood on the ground.
+fn update_checkpoints(
+    entities: Res<Entities>,
+    player_indexes: Comp<PlayerIdx>,
+    bodies: Comp<KinematicBody>,
+    transforms: Comp<Transform>,
+    mut checkpoints: ResMutInit<PlayerCheckpoints>,
+) {
+    for (_ent, (player_idx, body, transform)) in
+        entities.iter_with((&player_indexes, &bodies, &transforms))
+    {
+        if body.is_on_ground {
+            checkpoints.last_ground_pos[player_idx.0 as usize] = Some(transform.translation.xy());
+        }
+    }
+}
+
+/// Pick the spawner for the given player to spawn at.
+///
+/// Returns the index of the spawn point that is farthest from the closest opponent, or, with no
+/// opponents to avoid, the one closest to the player's checkpoint.
+fn pick_spawn_point(
+    spawn_points: &[Vec3],
+    opponents: &[Vec2],
+    checkpoint: Option<Vec2>,
+) -> Option<usize> {
+    let score = |point: Vec2| {
+        if opponents.is_empty() {
+            checkpoint.map(|x| -x.distance(point))
+        } else {
+            opponents.iter().map(|x| x.distance(point)).reduce(f32::min)
+        }
+    };
+    spawn_points
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, point)| Some((idx, score(point.xy())?)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(idx, _)| idx)
+}
+
 fn update(
     mut entities: ResMutInit<Entities>,
     mut current_spawner: ResMutInit<CurrentSpawner>,
+    checkpoints: ResInit<PlayerCheckpoints>,
     player_spawners: Comp<PlayerSpawner>,
+    killed_players: Comp<PlayerKilled>,
     mut player_indexes: CompMut<PlayerIdx>,
     mut transforms: CompMut<Transform>,
     player_inputs: Res<MatchInputs>,
+    mode: Res<GameMode>,
     mut spawner_manager: SpawnerManager,
 ) {
     let alive_players = entities
@@ -70,20 +125,47 @@ fn update(
         .iter_with((&player_spawners, &transforms))
         .map(|(_ent, (_spawner, transform))| transform.translation)
         .collect::<Vec<_>>();
+    // Where the living players last stood, or where they are now if they haven't landed yet
+    let mut living_players = entities
+        .iter_with((&player_indexes, &transforms))
+        .filter(|(ent, _)| !killed_players.contains(*ent))
+        .map(|(_ent, (pidx, transform))| {
+            let pos =
+                checkpoints.last_ground_pos[pidx.0 as usize].unwrap_or(transform.translation.xy());
+            (pidx.0, pos)
+        })
+        .collect::<Vec<_>>();
 
     // For every player
     for i in 0..MAX_PLAYERS {
-        let player = &player_inputs.players[i as usize];
-
-        // If the player is active, but not alive
-        if player.active && !alive_players.contains(&i) {
-            // Increment the spawner index
-            current_spawner.0 += 1;
-            current_spawner.0 %= spawn_points.len().max(1);
-
-            let Some(mut spawn_point) = spawn_points.get(current_spawner.0).copied() else {
+        // If the player should have a fish, but it is not alive
+        if mode.spawns_player(i, &player_inputs) && !alive_players.contains(&i) {
+            let spawn_idx = if mode.uses_fixed_spawns() {
+                Some(i as usize % spawn_points.len().max(1))
+            } else {
+                let opponents = living_players
+                    .iter()
+                    .filter(|(idx, _)| *idx != i)
+                    .map(|(_, pos)| *pos)
+                    .collect::<Vec<_>>();
+                pick_spawn_point(
+                    &spawn_points,
+                    &opponents,
+                    checkpoints.last_ground_pos[i as usize],
+                )
+            };
+            let spawn_idx = spawn_idx.unwrap_or_else(|| {
+                // Increment the spawner index
+                current_spawner.0 += 1;
+                current_spawner.0 %= spawn_points.len().max(1);
+                current_spawner.0
+            });
+
+            let Some(mut spawn_point) = spawn_points.get(spawn_idx).copied() else {
                 return;
             };
+            // Keep players that spawn on the same frame away from each other too
+            living_players.push((i, spawn_point.xy()));
 
             // Make sure each player spawns at a different z level ( give enough room for 10 players
             // to fit between map layers )
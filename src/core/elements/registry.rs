@@ -0,0 +1,106 @@
+//! Spawning elements by name.
+//!
+//! Map elements are usually spawned by placing them in a map: a spawner entity with an
+//! [`ElementHandle`] is created, and the element's `hydrate` system spawns the element for it. The
+//! [`ElementRegistry`] gives the rest of the game, like the training menu and scripts, one way to
+//! spawn any element without knowing its type. Elements that have a spawn command of their own, like
+//! [`KickBombCommand::spawn_kick_bomb`], are spawned with it, and every other element is spawned
+//! through a spawner.
+
+use crate::prelude::*;
+
+/// A constructor for a command that spawns an element at a transform.
+pub type SpawnElementFn = fn(Handle<ElementMeta>, Transform) -> StaticSystem<(), ()>;
+
+/// Session resource with every element that can be spawned, by name.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct ElementRegistry {
+    elements: HashMap<Ustr, Handle<ElementMeta>>,
+    /// The spawn commands for element data types that have one, along with a check for whether an
+    /// element's data is of that type.
+    commands: Vec<(fn(&SchemaBox) -> bool, SpawnElementFn)>,
+}
+
+impl ElementRegistry {
+    /// Create a registry with the core elements and the elements from every pack.
+    ///
+    /// Elements from packs replace core elements with the same name.
+    pub fn new(meta: &GameMeta, assets: &AssetServer) -> Self {
+        let mut handles = meta.core.map_elements.iter().copied().collect::<Vec<_>>();
+        for pack in assets.packs() {
+            let pack_meta = assets.get(pack.root.typed::<PackMeta>());
+            handles.extend(pack_meta.map_elements.iter().copied());
+        }
+
+        let mut registry = Self {
+            elements: handles
+                .into_iter()
+                .map(|handle| (assets.get(handle).name, handle))
+                .collect(),
+            commands: Vec::new(),
+        };
+        registry.register::<KickBombMeta>(|handle, transform| {
+            KickBombCommand::spawn_kick_bomb(None, transform, handle.untyped(), false, None)
+        });
+        registry
+    }
+
+    /// Spawn elements with data of type `T` with the given command instead of through a spawner.
+    pub fn register<T: HasSchema>(&mut self, spawn: SpawnElementFn) {
+        fn is_type<T: HasSchema>(data: &SchemaBox) -> bool {
+            data.try_cast_ref::<T>().is_ok()
+        }
+        self.commands.push((is_type::<T>, spawn));
+    }
+
+    /// Get the handle of the element with the given name.
+    pub fn get(&self, name: &str) -> Option<Handle<ElementMeta>> {
+        self.elements.get(&ustr(name)).copied()
+    }
+
+    /// Iterate over the names of every element.
+    pub fn names(&self) -> impl Iterator<Item = Ustr> + '_ {
+        self.elements.keys().copied()
+    }
+
+    /// Get a command that spawns an element at a transform.
+    pub fn spawn(
+        &self,
+        handle: Handle<ElementMeta>,
+        transform: Transform,
+        assets: &AssetServer,
+    ) -> StaticSystem<(), ()> {
+        let data = assets.get(assets.get(handle).data);
+        let spawn = self
+            .commands
+            .iter()
+            .find(|(is_type, _)| is_type(&*data))
+            .map(|(_, spawn)| *spawn)
+            .unwrap_or(spawn_with_spawner);
+        spawn(handle, transform)
+    }
+
+    /// Get a command that spawns the element with the given name at a transform.
+    pub fn spawn_by_name(
+        &self,
+        name: &str,
+        transform: Transform,
+        assets: &AssetServer,
+    ) -> Option<StaticSystem<(), ()>> {
+        Some(self.spawn(self.get(name)?, transform, assets))
+    }
+}
+
+/// Spawn an element the way maps do, through a spawner that its `hydrate` system picks up.
+fn spawn_with_spawner(handle: Handle<ElementMeta>, transform: Transform) -> StaticSystem<(), ()> {
+    (move |mut entities: ResMut<Entities>,
+           mut element_handles: CompMut<ElementHandle>,
+           mut transforms: CompMut<Transform>,
+           mut spawned_map_layer_metas: CompMut<SpawnedMapLayerMeta>| {
+        let entity = entities.create();
+        element_handles.insert(entity, ElementHandle(handle));
+        transforms.insert(entity, transform);
+        spawned_map_layer_metas.insert(entity, SpawnedMapLayerMeta { layer_idx: 0 });
+    })
+    .system()
+}
@@ -0,0 +1,451 @@
+use crate::prelude::*;
+
+/// A charge that is thrown and then set off from anywhere with a detonator.
+///
+/// Using the charge throws it and puts a detonator in the player's hands. The charge sticks to the
+/// first floor or wall it hits, and using the detonator blows it up.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("remote_charge"))]
+#[repr(C)]
+pub struct RemoteChargeMeta {
+    pub atlas: Handle<Atlas>,
+    pub body_size: Vec2,
+    pub grab_offset: Vec2,
+    pub fin_anim: Ustr,
+    pub throw_velocity: f32,
+    pub bounciness: f32,
+    /// The frames to blink through while the charge is stuck and waiting to be set off.
+    pub armed_frames: u32,
+    pub armed_fps: f32,
+
+    pub detonator_atlas: Handle<Atlas>,
+    pub detonator_body_size: Vec2,
+    pub detonator_grab_offset: Vec2,
+
+    pub damage_region_size: Vec2,
+    pub damage_region_lifetime: f32,
+    /// How long the match freezes for when the explosion kills a player.
+    pub hitstop: Duration,
+    pub explosion_atlas: Handle<Atlas>,
+    pub explosion_lifetime: f32,
+    pub explosion_frames: u32,
+    pub explosion_fps: f32,
+    pub explosion_volume: f64,
+    pub explosion_sound: Handle<AudioSource>,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    RemoteChargeMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, update_idle_remote_charges)
+        .add_system_to_stage(CoreStage::PostUpdate, update_thrown_remote_charges)
+        .add_system_to_stage(CoreStage::PostUpdate, update_detonators);
+}
+
+#[derive(Clone, HasSchema, Default, Debug, Copy)]
+pub struct IdleRemoteCharge;
+
+#[derive(Clone, HasSchema, Default, Debug, Copy)]
+pub struct ThrownRemoteCharge {
+    /// Whether the charge has stuck to a floor or wall.
+    stuck: bool,
+    /// The velocity of the charge last frame, used to tell when it hits a wall.
+    last_velocity: Vec2,
+}
+
+/// Component linking a thrown charge and its detonator, added to both of them.
+///
+/// Either end can be removed without notice, like when it falls out of the map, so both ends check
+/// that the other one is still alive: a detonator without a charge disappears, and a charge
+/// without a detonator can be picked up again.
+#[derive(Clone, HasSchema, Default, Debug, Copy)]
+pub struct RemoteChargeLink {
+    pub charge: Entity,
+    pub detonator: Entity,
+}
+
+fn hydrate(
+    game_meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut element_handles: CompMut<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut idle_charges: CompMut<IdleRemoteCharge>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut items: CompMut<Item>,
+    mut item_throws: CompMut<ItemThrow>,
+    mut item_grabs: CompMut<ItemGrab>,
+    mut respawn_points: CompMut<DehydrateOutOfBounds>,
+    mut spawner_manager: SpawnerManager,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        if let Ok(RemoteChargeMeta {
+            atlas,
+            fin_anim,
+            grab_offset,
+            body_size,
+            bounciness,
+            throw_velocity,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        {
+            hydrated.insert(spawner_ent, MapElementHydrated);
+
+            let entity = entities.create();
+            items.insert(entity, Item);
+            idle_charges.insert(entity, IdleRemoteCharge);
+            atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+            item_throws.insert(entity, ItemThrow::strength(*throw_velocity));
+            item_grabs.insert(
+                entity,
+                ItemGrab {
+                    fin_anim: *fin_anim,
+                    sync_animation: false,
+                    grab_offset: *grab_offset,
+                },
+            );
+            respawn_points.insert(entity, DehydrateOutOfBounds(spawner_ent));
+            transforms.insert(entity, transform);
+            element_handles.insert(entity, element_handle);
+            hydrated.insert(entity, MapElementHydrated);
+            animated_sprites.insert(entity, default());
+            bodies.insert(
+                entity,
+                KinematicBody {
+                    shape: ColliderShape::Rectangle { size: *body_size },
+                    has_mass: true,
+                    has_friction: true,
+                    bounciness: *bounciness,
+                    gravity: game_meta.core.physics.gravity,
+                    ..default()
+                },
+            );
+            spawner_manager.create_spawner(spawner_ent, vec![entity])
+        }
+    }
+}
+
+/// Throw charges that are used, and hand their throwers a detonator.
+fn update_idle_remote_charges(
+    game_meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut idle_charges: CompMut<IdleRemoteCharge>,
+    mut thrown_charges: CompMut<ThrownRemoteCharge>,
+    mut links: CompMut<RemoteChargeLink>,
+    mut items_used: CompMut<ItemUsed>,
+    mut items: CompMut<Item>,
+    mut item_throws: CompMut<ItemThrow>,
+    mut item_grabs: CompMut<ItemGrab>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    player_inventories: PlayerInventories,
+    mut commands: Commands,
+) {
+    let used = entities
+        .iter_with((&idle_charges, &element_handles))
+        .filter(|(entity, _)| items_used.contains(*entity))
+        .map(|(entity, (_, element_handle))| (entity, *element_handle))
+        .collect::<Vec<_>>();
+
+    for (charge, element_handle) in used {
+        items_used.remove(charge);
+        let Some(Inv { player, .. }) = player_inventories.find_item(charge) else {
+            continue;
+        };
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(RemoteChargeMeta {
+            fin_anim,
+            detonator_atlas,
+            detonator_body_size,
+            detonator_grab_offset,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        idle_charges.remove(charge);
+        thrown_charges.insert(charge, ThrownRemoteCharge::default());
+
+        let detonator = entities.create();
+        let link = RemoteChargeLink { charge, detonator };
+        links.insert(charge, link);
+        links.insert(detonator, link);
+        items.insert(detonator, Item);
+        item_throws.insert(detonator, ItemThrow::strength(0.0));
+        item_grabs.insert(
+            detonator,
+            ItemGrab {
+                fin_anim: *fin_anim,
+                sync_animation: false,
+                grab_offset: *detonator_grab_offset,
+            },
+        );
+        atlas_sprites.insert(detonator, AtlasSprite::new(*detonator_atlas));
+        let transform = transforms.get(charge).copied().unwrap_or_default();
+        transforms.insert(detonator, transform);
+        bodies.insert(
+            detonator,
+            KinematicBody {
+                shape: ColliderShape::Rectangle {
+                    size: *detonator_body_size,
+                },
+                has_mass: true,
+                has_friction: true,
+                gravity: game_meta.core.physics.gravity,
+                ..default()
+            },
+        );
+
+        // Swapping the charge for the detonator throws the charge
+        commands.add(PlayerCommand::set_inventory(player, Some(detonator)));
+    }
+}
+
+/// Stick thrown charges to what they land on, and disarm charges that lost their detonator.
+fn update_thrown_remote_charges(
+    entities: Res<Entities>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut thrown_charges: CompMut<ThrownRemoteCharge>,
+    mut idle_charges: CompMut<IdleRemoteCharge>,
+    mut links: CompMut<RemoteChargeLink>,
+    mut items: CompMut<Item>,
+    mut bodies: CompMut<KinematicBody>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    player_inventories: PlayerInventories,
+) {
+    let thrown = entities
+        .iter_with((&thrown_charges, &element_handles))
+        .map(|(entity, (_, element_handle))| (entity, *element_handle))
+        .collect::<Vec<_>>();
+
+    for (charge, element_handle) in thrown {
+        let has_detonator = links
+            .get(charge)
+            .is_some_and(|link| entities.is_alive(link.detonator));
+        if !has_detonator {
+            // Let the charge be picked up and thrown again
+            thrown_charges.remove(charge);
+            links.remove(charge);
+            idle_charges.insert(charge, IdleRemoteCharge);
+            items.insert(charge, Item);
+            if let Some(body) = bodies.get_mut(charge) {
+                body.is_deactivated = false;
+            }
+            if let Some(sprite) = animated_sprites.get_mut(charge) {
+                *sprite = default();
+            }
+            continue;
+        }
+
+        let thrown_charge = thrown_charges.get_mut(charge).unwrap();
+        let Some(body) = bodies.get_mut(charge) else {
+            continue;
+        };
+        // Wait until the charge has left the thrower's hands
+        if thrown_charge.stuck || player_inventories.find_item(charge).is_some() {
+            continue;
+        }
+
+        let last_velocity = std::mem::replace(&mut thrown_charge.last_velocity, body.velocity);
+        let hit_wall = last_velocity.x != 0.0 && body.velocity.x * last_velocity.x <= 0.0;
+        if body.is_on_ground || hit_wall {
+            thrown_charge.stuck = true;
+            body.velocity = Vec2::ZERO;
+            body.angular_velocity = 0.0;
+            body.is_deactivated = true;
+            // A stuck charge can't be picked up
+            items.remove(charge);
+
+            let element_meta = assets.get(element_handle.0);
+            let asset = assets.get(element_meta.data);
+            let Ok(RemoteChargeMeta {
+                armed_frames,
+                armed_fps,
+                ..
+            }) = asset.try_cast_ref()
+            else {
+                unreachable!();
+            };
+            if let Some(sprite) = animated_sprites.get_mut(charge) {
+                sprite.frames = (0..*armed_frames).collect();
+                sprite.fps = *armed_fps;
+                sprite.repeat = true;
+            }
+        }
+    }
+}
+
+/// Set off charges when their detonator is used, and remove detonators that have no charge.
+fn update_detonators(
+    entities: Res<Entities>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    links: Comp<RemoteChargeLink>,
+    transforms: Comp<Transform>,
+    spawners: Comp<DehydrateOutOfBounds>,
+    map: Res<LoadedMap>,
+    mut items_used: CompMut<ItemUsed>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
+    player_inventories: PlayerInventories,
+    mut commands: Commands,
+) {
+    for (detonator, link) in entities.iter_with(&links) {
+        if link.detonator != detonator {
+            continue;
+        }
+        let holder = player_inventories
+            .find_item(detonator)
+            .map(|inv| inv.player);
+        let remove_detonator = move |mut entities: ResMutInit<Entities>| {
+            entities.kill(detonator);
+        };
+
+        let charge = link.charge;
+        if !entities.is_alive(charge) {
+            if let Some(player) = holder {
+                commands.add(PlayerCommand::set_inventory(player, None));
+            }
+            commands.add(remove_detonator);
+            continue;
+        }
+
+        // Detonators that fall out of the map are gone, which disarms their charge
+        let is_out_of_bounds = transforms
+            .get(detonator)
+            .is_some_and(|x| map.is_out_of_bounds(&x.translation));
+        if holder.is_none() && is_out_of_bounds {
+            commands.add(remove_detonator);
+            continue;
+        }
+
+        let Some(player) = holder else {
+            continue;
+        };
+        if items_used.remove(detonator).is_none() {
+            continue;
+        }
+        commands.add(PlayerCommand::set_inventory(player, None));
+        commands.add(remove_detonator);
+
+        let Some(element_handle) = element_handles.get(charge) else {
+            continue;
+        };
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(RemoteChargeMeta {
+            damage_region_size,
+            damage_region_lifetime,
+            hitstop,
+            explosion_atlas,
+            explosion_lifetime,
+            explosion_frames,
+            explosion_fps,
+            explosion_volume,
+            explosion_sound,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        let mut explosion_transform = *transforms.get(charge).unwrap();
+        spatial_audio.play_sound_at(
+            *explosion_sound,
+            *explosion_volume,
+            explosion_transform.translation.xy(),
+        );
+        camera_shake_events.send(CameraShakeKind::Explosion);
+
+        // Cause the charge to respawn by un-hydrating it's spawner.
+        if let Some(spawner) = spawners.get(charge) {
+            hydrated.remove(**spawner);
+        }
+        explosion_transform.translation.z = -10.0; // On top of almost everything
+        explosion_transform.rotation = Quat::IDENTITY;
+
+        // Clone types for move into closure
+        let damage_region_size = *damage_region_size;
+        let damage_region_lifetime = *damage_region_lifetime;
+        let hitstop = *hitstop;
+        let explosion_lifetime = *explosion_lifetime;
+        let explosion_atlas = *explosion_atlas;
+        let explosion_fps = *explosion_fps;
+        let explosion_frames = *explosion_frames;
+        commands.add(
+            move |mut entities: ResMutInit<Entities>,
+                  mut transforms: CompMut<Transform>,
+                  mut damage_regions: CompMut<DamageRegion>,
+                  mut lifetimes: CompMut<Lifetime>,
+                  mut sprites: CompMut<AtlasSprite>,
+                  mut animated_sprites: CompMut<AnimatedSprite>,
+                  storage: Res<Storage>| {
+                // Despawn the charge
+                entities.kill(charge);
+
+                // Spawn the damage region
+                let ent = entities.create();
+                transforms.insert(ent, explosion_transform);
+                damage_regions.insert(
+                    ent,
+                    DamageRegion {
+                        size: damage_region_size,
+                        hitstop,
+                        attacker: Some(player),
+                        blast: true,
+                        ..default()
+                    },
+                );
+                lifetimes.insert(ent, Lifetime::new(damage_region_lifetime));
+
+                // Spawn the explosion animation
+                let ent = entities.create();
+                transforms.insert(ent, explosion_transform);
+                sprites.insert(
+                    ent,
+                    AtlasSprite {
+                        atlas: explosion_atlas,
+                        ..default()
+                    },
+                );
+                animated_sprites.insert(
+                    ent,
+                    explosion_animation(
+                        explosion_frames,
+                        explosion_fps,
+                        explosion_lifetime,
+                        &storage,
+                    ),
+                );
+                lifetimes.insert(ent, Lifetime::new(explosion_lifetime));
+            },
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::prelude::*;
+
+/// A rope hanging from the point it is placed at, that players can grab and swing on.
+///
+/// The rope is a chain of small dynamic bodies held together with rope joints. Players in the air
+/// grab it by holding up while they are near it, and it can be cut by blades, which drops the part
+/// of the rope below the cut.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("rope"))]
+#[repr(C)]
+pub struct RopeMeta {
+    /// How many pieces the rope is made of.
+    pub segments: u32,
+    /// How far apart the pieces of the rope are, in pixels.
+    pub segment_length: f32,
+    pub color: Color,
+    pub thickness: f32,
+    /// How close a player has to be to a piece of the rope to grab it.
+    pub grab_distance: f32,
+    /// Where a player hangs, relative to the piece of the rope they are holding.
+    pub hang_offset: Vec2,
+    /// The horizontal velocity added to the rope every frame while a player swings on it.
+    pub swing_speed: f32,
+    /// The upward velocity of players that jump off of the rope.
+    pub jump_speed: f32,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    RopeMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, cut_ropes)
+        .add_system_to_stage(CoreStage::Last, update_rope_paths);
+}
+
+/// A rope, on the entity that it hangs from.
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct Rope {
+    /// The pieces of the rope, from the top down.
+    pub segments: Vec<Entity>,
+    /// The joint that holds each piece of the rope to the one above it, or to the entity the rope
+    /// hangs from for the first piece. Cut joints are killed.
+    pub joints: Vec<Entity>,
+    pub grab_distance: f32,
+    pub hang_offset: Vec2,
+    pub swing_speed: f32,
+    pub jump_speed: f32,
+}
+
+/// A piece of a [`Rope`].
+#[derive(Clone, Copy, Debug, HasSchema, Default)]
+pub struct RopeSegment {
+    /// The entity the rope hangs from.
+    pub rope: Entity,
+}
+
+/// Component for players that are hanging from a rope.
+#[derive(Clone, Copy, Debug, HasSchema, Default)]
+pub struct HangingOnRope {
+    /// The entity the rope hangs from.
+    pub rope: Entity,
+    /// The piece of the rope the player is holding.
+    pub segment: Entity,
+    /// Where the piece of the rope was on the last frame, to find how fast it is swinging.
+    pub last_pos: Vec2,
+}
+
+fn hydrate(
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut ropes: CompMut<Rope>,
+    mut segments: CompMut<RopeSegment>,
+    mut joints: CompMut<PhysicsJoint>,
+    mut bodies: CompMut<KinematicBody>,
+    mut dynamic_bodies: CompMut<DynamicBody>,
+    mut transforms: CompMut<Transform>,
+    mut paths: CompMut<Path2d>,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let element_handle = element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        let Ok(RopeMeta {
+            segments: segment_count,
+            segment_length,
+            color,
+            thickness,
+            grab_distance,
+            hang_offset,
+            swing_speed,
+            jump_speed,
+        }) = assets.get(element_meta.data).try_cast_ref()
+        else {
+            continue;
+        };
+        hydrated.insert(spawner_ent, MapElementHydrated);
+        let transform = *transforms.get(spawner_ent).unwrap();
+
+        // The rope hangs from a body that doesn't move, so that the joint has something to hold on
+        // to.
+        bodies.insert(
+            spawner_ent,
+            KinematicBody {
+                shape: ColliderShape::Circle {
+                    diameter: *thickness,
+                },
+                has_mass: false,
+                ..default()
+            },
+        );
+
+        let mut rope = Rope {
+            grab_distance: *grab_distance,
+            hang_offset: *hang_offset,
+            swing_speed: *swing_speed,
+            jump_speed: *jump_speed,
+            ..default()
+        };
+        let mut above = spawner_ent;
+        for i in 0..*segment_count {
+            let segment = entities.create();
+            let mut segment_transform = transform;
+            segment_transform.translation.y -= (i + 1) as f32 * segment_length;
+            transforms.insert(segment, segment_transform);
+            segments.insert(segment, RopeSegment { rope: spawner_ent });
+            bodies.insert(
+                segment,
+                KinematicBody {
+                    shape: ColliderShape::Circle {
+                        diameter: *thickness,
+                    },
+                    has_mass: true,
+                    ..default()
+                },
+            );
+            dynamic_bodies.insert(segment, DynamicBody::new(true));
+
+            let joint = entities.create();
+            joints.insert(joint, PhysicsJoint::rope(above, segment, *segment_length));
+
+            rope.segments.push(segment);
+            rope.joints.push(joint);
+            above = segment;
+        }
+        ropes.insert(spawner_ent, rope);
+        paths.insert(
+            spawner_ent,
+            Path2d {
+                color: *color,
+                thickness: *thickness,
+                ..default()
+            },
+        );
+    }
+}
+
+/// Cut the ropes that are hit by blades, dropping everything below the cut.
+fn cut_ropes(
+    mut entities: ResMutInit<Entities>,
+    ropes: Comp<Rope>,
+    damage_regions: Comp<DamageRegion>,
+    transforms: Comp<Transform>,
+) {
+    let blades = entities
+        .iter_with((&damage_regions, &transforms))
+        .filter(|(_, (region, _))| region.sharp)
+        .map(|(_, (region, transform))| region.collider_rect(transform.translation))
+        .collect::<Vec<_>>();
+    if blades.is_empty() {
+        return;
+    }
+
+    let mut cut = Vec::new();
+    for (_, rope) in entities.iter_with(&ropes) {
+        for (segment, joint) in rope.segments.iter().zip(&rope.joints) {
+            let Some(transform) = transforms.get(*segment) else {
+                continue;
+            };
+            let pos = transform.translation.xy();
+            if entities.is_alive(*joint) && blades.iter().any(|rect| rect.contains(pos)) {
+                cut.push(*joint);
+            }
+        }
+    }
+    for joint in cut {
+        entities.kill(joint);
+    }
+}
+
+/// Draw the ropes through their pieces, leaving gaps where they were cut.
+fn update_rope_paths(
+    entities: Res<Entities>,
+    ropes: Comp<Rope>,
+    transforms: Comp<Transform>,
+    mut paths: CompMut<Path2d>,
+) {
+    for (ent, (rope, path)) in entities.iter_with((&ropes, &mut paths)) {
+        let origin = transforms.get(ent).unwrap().translation.xy();
+        path.points.clear();
+        path.line_breaks.clear();
+        path.points.push(Vec2::ZERO);
+        for (segment, joint) in rope.segments.iter().zip(&rope.joints) {
+            let Some(transform) = transforms.get(*segment) else {
+                continue;
+            };
+            if !entities.is_alive(*joint) {
+                path.line_breaks.push(path.points.len());
+            }
+            path.points.push(transform.translation.xy() - origin);
+        }
+    }
+}
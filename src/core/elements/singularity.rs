@@ -0,0 +1,355 @@
+use crate::prelude::*;
+
+/// A gravity well that pulls everything around it in for a while and then explodes.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("singularity"))]
+#[repr(C)]
+pub struct SingularityMeta {
+    pub atlas: Handle<Atlas>,
+    pub body_diameter: f32,
+    pub fin_anim: Ustr,
+    pub grab_offset: Vec2,
+    pub can_rotate: bool,
+    pub bounciness: f32,
+    pub throw_velocity: f32,
+    pub angular_velocity: f32,
+    pub fuse_sound: Handle<AudioSource>,
+    pub fuse_sound_volume: f64,
+    /// How long after the singularity is used that it starts pulling.
+    pub fuse_time: Duration,
+
+    pub pull_sound: Handle<AudioSource>,
+    pub pull_sound_volume: f64,
+    /// How long the singularity pulls for before exploding.
+    pub pull_duration: Duration,
+    /// How far away bodies are pulled from.
+    pub pull_radius: f32,
+    /// The velocity per second that a body right at the center is pulled in with.
+    ///
+    /// The pull gets weaker further away, down to nothing at the edge of the radius.
+    pub pull_strength: f32,
+    /// The frames of the atlas that are shown while the singularity is pulling.
+    pub pull_frames: SVec<u32>,
+    pub pull_fps: f32,
+
+    pub damage_region_size: Vec2,
+    pub damage_region_lifetime: f32,
+    /// How long the match freezes for when the explosion kills a player.
+    pub hitstop: Duration,
+    pub explosion_atlas: Handle<Atlas>,
+    pub explosion_lifetime: f32,
+    pub explosion_frames: u32,
+    pub explosion_fps: f32,
+    pub explosion_sound: Handle<AudioSource>,
+    pub explosion_volume: f64,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    SingularityMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, update_lit_singularities)
+        .add_system_to_stage(CoreStage::PostUpdate, update_idle_singularities);
+}
+
+#[derive(Clone, HasSchema, Debug, Copy, Default)]
+pub struct IdleSingularity;
+
+#[derive(Clone, HasSchema, Debug, Default)]
+pub struct LitSingularity {
+    /// The amount of time left until the singularity starts pulling.
+    pub fuse_time: Timer,
+    /// The amount of time left until the singularity explodes, once it is pulling.
+    pub pull_time: Timer,
+    /// The player that set off the singularity, who is credited with its kills.
+    pub owner: Entity,
+}
+
+fn hydrate(
+    game_meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut element_handles: CompMut<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut idle_singularities: CompMut<IdleSingularity>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut items: CompMut<Item>,
+    mut item_throws: CompMut<ItemThrow>,
+    mut item_grabs: CompMut<ItemGrab>,
+    mut hold_to_use: CompMut<HoldToUse>,
+    mut respawn_points: CompMut<DehydrateOutOfBounds>,
+    mut spawner_manager: SpawnerManager,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        if let Ok(SingularityMeta {
+            atlas,
+            fin_anim,
+            grab_offset,
+            body_diameter,
+            can_rotate,
+            bounciness,
+            throw_velocity,
+            angular_velocity,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        {
+            hydrated.insert(spawner_ent, MapElementHydrated);
+
+            let entity = entities.create();
+            items.insert(entity, Item);
+            idle_singularities.insert(entity, IdleSingularity);
+            hold_to_use.insert(entity, HoldToUse::default());
+            item_throws.insert(
+                entity,
+                ItemThrow::strength(*throw_velocity).with_spin(*angular_velocity),
+            );
+            item_grabs.insert(
+                entity,
+                ItemGrab {
+                    fin_anim: *fin_anim,
+                    sync_animation: false,
+                    grab_offset: *grab_offset,
+                },
+            );
+            atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+            respawn_points.insert(entity, DehydrateOutOfBounds(spawner_ent));
+            transforms.insert(entity, transform);
+            element_handles.insert(entity, element_handle);
+            hydrated.insert(entity, MapElementHydrated);
+            animated_sprites.insert(entity, default());
+            bodies.insert(
+                entity,
+                KinematicBody {
+                    shape: ColliderShape::Circle {
+                        diameter: *body_diameter,
+                    },
+                    has_mass: true,
+                    has_friction: true,
+                    can_rotate: *can_rotate,
+                    bounciness: *bounciness,
+                    gravity: game_meta.core.physics.gravity,
+                    ..default()
+                },
+            );
+            spawner_manager.create_spawner(spawner_ent, vec![entity])
+        }
+    }
+}
+
+fn update_idle_singularities(
+    entities: Res<Entities>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut audio_center: ResMut<AudioCenter>,
+    mut items_used: CompMut<ItemUsed>,
+    mut idle_singularities: CompMut<IdleSingularity>,
+    mut lit_singularities: CompMut<LitSingularity>,
+) {
+    let used = entities
+        .iter_with((&idle_singularities, &element_handles))
+        .filter(|(entity, _)| items_used.contains(*entity))
+        .map(|(entity, (_, element_handle))| (entity, *element_handle))
+        .collect::<Vec<_>>();
+
+    for (entity, element_handle) in used {
+        let owner = items_used.remove(entity).unwrap().owner;
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(SingularityMeta {
+            fuse_sound,
+            fuse_sound_volume,
+            fuse_time,
+            pull_duration,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        audio_center.play_sound(*fuse_sound, *fuse_sound_volume);
+        idle_singularities.remove(entity);
+        lit_singularities.insert(
+            entity,
+            LitSingularity {
+                fuse_time: Timer::new(*fuse_time, TimerMode::Once),
+                pull_time: Timer::new(*pull_duration, TimerMode::Once),
+                owner,
+            },
+        );
+    }
+}
+
+fn update_lit_singularities(
+    time: Res<Time>,
+    mut commands: Commands,
+    entities: Res<Entities>,
+    transforms: Comp<Transform>,
+    element_handles: Comp<ElementHandle>,
+    spawners: Comp<DehydrateOutOfBounds>,
+    assets: Res<AssetServer>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+    mut lit_singularities: CompMut<LitSingularity>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
+) {
+    // The pull of every singularity that is pulling this frame.
+    let mut pulls = Vec::new();
+
+    for (entity, (singularity, element_handle, spawner)) in
+        entities.iter_with((&mut lit_singularities, &element_handles, &spawners))
+    {
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(SingularityMeta {
+            pull_sound,
+            pull_sound_volume,
+            pull_radius,
+            pull_strength,
+            pull_frames,
+            pull_fps,
+            explosion_sound,
+            explosion_volume,
+            damage_region_lifetime,
+            damage_region_size,
+            hitstop,
+            explosion_lifetime,
+            explosion_atlas,
+            explosion_fps,
+            explosion_frames,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+        let position = transforms.get(entity).unwrap().translation.xy();
+
+        // Burn the fuse, even while the singularity is held
+        if !singularity.fuse_time.finished() {
+            singularity.fuse_time.tick(time.delta());
+            if singularity.fuse_time.just_finished() {
+                spatial_audio.play_sound_at(*pull_sound, *pull_sound_volume, position);
+
+                let animated_sprite = animated_sprites.get_mut(entity).unwrap();
+                animated_sprite.frames = pull_frames.clone();
+                animated_sprite.repeat = true;
+                animated_sprite.fps = *pull_fps;
+
+                // Hang in the air while pulling
+                let body = bodies.get_mut(entity).unwrap();
+                body.has_mass = false;
+                body.velocity = Vec2::ZERO;
+                body.angular_velocity = 0.0;
+            }
+            continue;
+        }
+
+        singularity.pull_time.tick(time.delta());
+        if !singularity.pull_time.finished() {
+            let pull = RadialImpulse {
+                radius: *pull_radius,
+                ..RadialImpulse::new(position, -*pull_strength)
+            };
+            pulls.push((entity, pull));
+            continue;
+        }
+
+        spatial_audio.play_sound_at(*explosion_sound, *explosion_volume, position);
+        camera_shake_events.send(CameraShakeKind::Explosion);
+
+        // Cause the item to respawn by un-hydrating it's spawner.
+        hydrated.remove(**spawner);
+        let mut explosion_transform = *transforms.get(entity).unwrap();
+        explosion_transform.translation.z = -10.0; // On top of almost everything
+        explosion_transform.rotation = Quat::IDENTITY;
+
+        // Clone types for move into closure
+        let damage_region_size = *damage_region_size;
+        let damage_region_lifetime = *damage_region_lifetime;
+        let hitstop = *hitstop;
+        let attacker = singularity.owner;
+        let explosion_lifetime = *explosion_lifetime;
+        let explosion_atlas = *explosion_atlas;
+        let explosion_fps = *explosion_fps;
+        let explosion_frames = *explosion_frames;
+        commands.add(
+            move |mut entities: ResMutInit<Entities>,
+                  mut transforms: CompMut<Transform>,
+                  mut damage_regions: CompMut<DamageRegion>,
+                  mut lifetimes: CompMut<Lifetime>,
+                  mut sprites: CompMut<AtlasSprite>,
+                  mut animated_sprites: CompMut<AnimatedSprite>,
+                  storage: Res<Storage>| {
+                // Despawn the singularity
+                entities.kill(entity);
+
+                // Spawn the damage region
+                let ent = entities.create();
+                transforms.insert(ent, explosion_transform);
+                damage_regions.insert(
+                    ent,
+                    DamageRegion {
+                        size: damage_region_size,
+                        hitstop,
+                        attacker: Some(attacker),
+                        blast: true,
+                        ..default()
+                    },
+                );
+                lifetimes.insert(ent, Lifetime::new(damage_region_lifetime));
+
+                // Spawn the explosion animation
+                let ent = entities.create();
+                transforms.insert(ent, explosion_transform);
+                sprites.insert(
+                    ent,
+                    AtlasSprite {
+                        atlas: explosion_atlas,
+                        ..default()
+                    },
+                );
+                animated_sprites.insert(
+                    ent,
+                    explosion_animation(
+                        explosion_frames,
+                        explosion_fps,
+                        explosion_lifetime,
+                        &storage,
+                    ),
+                );
+                lifetimes.insert(ent, Lifetime::new(explosion_lifetime));
+            },
+        );
+    }
+
+    // Pull every free body in range, players, items and other bombs alike.
+    let delta = time.delta_seconds();
+    for (singularity_ent, pull) in pulls {
+        for (entity, (body, transform)) in entities.iter_with((&mut bodies, &transforms)) {
+            if entity == singularity_ent || body.is_deactivated {
+                continue;
+            }
+            body.velocity += pull.velocity(transform.translation.xy()) * delta;
+        }
+    }
+}
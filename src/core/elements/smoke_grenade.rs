@@ -0,0 +1,334 @@
+use crate::prelude::*;
+
+/// A grenade that lets out a cloud of smoke instead of exploding.
+///
+/// The cloud is drawn over everything in its area, and AI players can't see through it, which makes
+/// it good for getting away.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("smoke_grenade"))]
+#[repr(C)]
+pub struct SmokeGrenadeMeta {
+    pub atlas: Handle<Atlas>,
+    pub body_diameter: f32,
+    pub fin_anim: Ustr,
+    pub grab_offset: Vec2,
+    pub can_rotate: bool,
+    pub bounciness: f32,
+    pub throw_velocity: f32,
+    pub angular_velocity: f32,
+    pub fuse_sound: Handle<AudioSource>,
+    pub fuse_sound_volume: f64,
+    /// How long after the grenade is used that the smoke comes out.
+    pub fuse_time: Duration,
+
+    pub smoke_sound: Handle<AudioSource>,
+    pub smoke_sound_volume: f64,
+    /// How long the smoke cloud lasts.
+    pub smoke_duration: Duration,
+    /// The size of the area that the cloud covers.
+    pub cloud_size: Vec2,
+    pub cloud_atlas: Handle<Atlas>,
+    /// How much the cloud atlas is scaled up to cover the cloud area.
+    pub cloud_scale: f32,
+    /// The range of frames in the cloud atlas to loop through.
+    pub cloud_frames_start: u32,
+    pub cloud_frames_end: u32,
+    pub cloud_fps: f32,
+    pub cloud_color: Color,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    SmokeGrenadeMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, update_lit_smoke_grenades)
+        .add_system_to_stage(CoreStage::PostUpdate, update_idle_smoke_grenades);
+}
+
+#[derive(Clone, HasSchema, Debug, Copy, Default)]
+pub struct IdleSmokeGrenade;
+
+#[derive(Clone, HasSchema, Debug, Default)]
+pub struct LitSmokeGrenade {
+    /// The amount of time left until the smoke comes out.
+    pub fuse_time: Timer,
+}
+
+/// A cloud of smoke that blocks the sight of AI players.
+///
+/// The cloud is centered on the entity's transform.
+#[derive(Clone, HasSchema, Debug, Default)]
+#[repr(C)]
+pub struct SmokeCloud {
+    pub size: Vec2,
+}
+
+/// Whether a smoke cloud is in the way of a straight line between two points.
+pub fn is_sight_blocked(
+    entities: &Entities,
+    smoke_clouds: &Comp<SmokeCloud>,
+    transforms: &Comp<Transform>,
+    from: Vec2,
+    to: Vec2,
+) -> bool {
+    entities
+        .iter_with((smoke_clouds, transforms))
+        .any(|(_, (cloud, transform))| {
+            let center = transform.translation.xy();
+            segment_intersects_rect(
+                from,
+                to,
+                center - cloud.size / 2.0,
+                center + cloud.size / 2.0,
+            )
+        })
+}
+
+/// Check whether the line segment from `a` to `b` touches the rectangle from `min` to `max`.
+fn segment_intersects_rect(a: Vec2, b: Vec2, min: Vec2, max: Vec2) -> bool {
+    let delta = b - a;
+    let mut t_min = 0.0f32;
+    let mut t_max = 1.0f32;
+    for axis in 0..2 {
+        if delta[axis] == 0.0 {
+            if a[axis] < min[axis] || a[axis] > max[axis] {
+                return false;
+            }
+            continue;
+        }
+        let t1 = (min[axis] - a[axis]) / delta[axis];
+        let t2 = (max[axis] - a[axis]) / delta[axis];
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+        if t_min > t_max {
+            return false;
+        }
+    }
+    true
+}
+
+fn hydrate(
+    game_meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut element_handles: CompMut<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut idle_grenades: CompMut<IdleSmokeGrenade>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut items: CompMut<Item>,
+    mut item_throws: CompMut<ItemThrow>,
+    mut item_grabs: CompMut<ItemGrab>,
+    mut hold_to_use: CompMut<HoldToUse>,
+    mut respawn_points: CompMut<DehydrateOutOfBounds>,
+    mut spawner_manager: SpawnerManager,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        if let Ok(SmokeGrenadeMeta {
+            atlas,
+            fin_anim,
+            grab_offset,
+            body_diameter,
+            can_rotate,
+            bounciness,
+            throw_velocity,
+            angular_velocity,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        {
+            hydrated.insert(spawner_ent, MapElementHydrated);
+
+            let entity = entities.create();
+            items.insert(entity, Item);
+            idle_grenades.insert(entity, IdleSmokeGrenade);
+            hold_to_use.insert(entity, HoldToUse::default());
+            item_throws.insert(
+                entity,
+                ItemThrow::strength(*throw_velocity).with_spin(*angular_velocity),
+            );
+            item_grabs.insert(
+                entity,
+                ItemGrab {
+                    fin_anim: *fin_anim,
+                    sync_animation: false,
+                    grab_offset: *grab_offset,
+                },
+            );
+            atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+            respawn_points.insert(entity, DehydrateOutOfBounds(spawner_ent));
+            transforms.insert(entity, transform);
+            element_handles.insert(entity, element_handle);
+            hydrated.insert(entity, MapElementHydrated);
+            animated_sprites.insert(entity, default());
+            bodies.insert(
+                entity,
+                KinematicBody {
+                    shape: ColliderShape::Circle {
+                        diameter: *body_diameter,
+                    },
+                    has_mass: true,
+                    has_friction: true,
+                    can_rotate: *can_rotate,
+                    bounciness: *bounciness,
+                    gravity: game_meta.core.physics.gravity,
+                    ..default()
+                },
+            );
+            spawner_manager.create_spawner(spawner_ent, vec![entity])
+        }
+    }
+}
+
+fn update_idle_smoke_grenades(
+    entities: Res<Entities>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut audio_center: ResMut<AudioCenter>,
+    mut items_used: CompMut<ItemUsed>,
+    mut idle_grenades: CompMut<IdleSmokeGrenade>,
+    mut lit_grenades: CompMut<LitSmokeGrenade>,
+) {
+    let used = entities
+        .iter_with((&idle_grenades, &element_handles))
+        .filter(|(entity, _)| items_used.contains(*entity))
+        .map(|(entity, (_, element_handle))| (entity, *element_handle))
+        .collect::<Vec<_>>();
+
+    for (entity, element_handle) in used {
+        items_used.remove(entity);
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(SmokeGrenadeMeta {
+            fuse_sound,
+            fuse_sound_volume,
+            fuse_time,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        audio_center.play_sound(*fuse_sound, *fuse_sound_volume);
+        idle_grenades.remove(entity);
+        lit_grenades.insert(
+            entity,
+            LitSmokeGrenade {
+                fuse_time: Timer::new(*fuse_time, TimerMode::Once),
+            },
+        );
+    }
+}
+
+fn update_lit_smoke_grenades(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    element_handles: Comp<ElementHandle>,
+    transforms: Comp<Transform>,
+    spawners: Comp<DehydrateOutOfBounds>,
+    assets: Res<AssetServer>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+    mut lit_grenades: CompMut<LitSmokeGrenade>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut commands: Commands,
+) {
+    for (entity, (grenade, element_handle, spawner)) in
+        entities.iter_with((&mut lit_grenades, &element_handles, &spawners))
+    {
+        grenade.fuse_time.tick(time.delta());
+        if !grenade.fuse_time.finished() {
+            continue;
+        }
+
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(SmokeGrenadeMeta {
+            smoke_sound,
+            smoke_sound_volume,
+            smoke_duration,
+            cloud_size,
+            cloud_atlas,
+            cloud_scale,
+            cloud_frames_start,
+            cloud_frames_end,
+            cloud_fps,
+            cloud_color,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        let mut cloud_transform = *transforms.get(entity).unwrap();
+        spatial_audio.play_sound_at(
+            *smoke_sound,
+            *smoke_sound_volume,
+            cloud_transform.translation.xy(),
+        );
+
+        // Cause the grenade to respawn by un-hydrating it's spawner.
+        hydrated.remove(**spawner);
+        cloud_transform.translation.z = -10.0; // On top of almost everything
+        cloud_transform.rotation = Quat::IDENTITY;
+        cloud_transform.scale = Vec3::splat(*cloud_scale);
+
+        // Clone types for move into closure
+        let cloud = SmokeCloud { size: *cloud_size };
+        let smoke_duration = smoke_duration.as_secs_f32();
+        let cloud_atlas = *cloud_atlas;
+        let cloud_frames = (*cloud_frames_start..*cloud_frames_end).collect::<SVec<_>>();
+        let cloud_fps = *cloud_fps;
+        let cloud_color = *cloud_color;
+        commands.add(
+            move |mut entities: ResMutInit<Entities>,
+                  mut transforms: CompMut<Transform>,
+                  mut smoke_clouds: CompMut<SmokeCloud>,
+                  mut lifetimes: CompMut<Lifetime>,
+                  mut sprites: CompMut<AtlasSprite>,
+                  mut animated_sprites: CompMut<AnimatedSprite>| {
+                // Despawn the grenade
+                entities.kill(entity);
+
+                // Spawn the cloud
+                let ent = entities.create();
+                transforms.insert(ent, cloud_transform);
+                smoke_clouds.insert(ent, cloud.clone());
+                sprites.insert(
+                    ent,
+                    AtlasSprite {
+                        atlas: cloud_atlas,
+                        color: cloud_color,
+                        ..default()
+                    },
+                );
+                animated_sprites.insert(
+                    ent,
+                    AnimatedSprite {
+                        frames: cloud_frames.clone(),
+                        fps: cloud_fps,
+                        repeat: true,
+                        ..default()
+                    },
+                );
+                lifetimes.insert(ent, Lifetime::new(smoke_duration));
+            },
+        );
+    }
+}
@@ -227,9 +227,10 @@ fn update_wearer(
                         .center()
                         .y
                 {
-                    commands.add(PlayerCommand::kill(
+                    commands.add(PlayerCommand::kill_by(
                         player,
                         Some(player_transform.translation.xy()),
+                        Some(entity),
                     ))
                 }
             });
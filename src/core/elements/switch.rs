@@ -0,0 +1,130 @@
+use crate::prelude::*;
+
+/// A switch that is flipped by standing on it or shooting it.
+///
+/// A switch controls the doors and elevators that share its [`ElementLink`]: they are open while
+/// any of the switches linked to them are on.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("switch"))]
+#[repr(C)]
+pub struct SwitchMeta {
+    /// The size of the plate that players stand on or shoot to flip the switch.
+    pub size: Vec2,
+    pub off_color: Color,
+    pub on_color: Color,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    SwitchMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, update);
+}
+
+#[derive(Clone, Copy, Debug, HasSchema, Default)]
+pub struct Switch {
+    /// Whether the switch is on.
+    pub on: bool,
+    /// Whether a player was standing on the switch on the last frame, so that it is only flipped
+    /// when a player steps onto it.
+    pub pressed: bool,
+}
+
+fn hydrate(
+    entities: Res<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut switches: CompMut<Switch>,
+    mut bodies: CompMut<KinematicBody>,
+    mut paths: CompMut<Path2d>,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    for entity in entities.iter_with_bitset(&not_hydrated_bitset) {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        let Ok(SwitchMeta {
+            size, off_color, ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        else {
+            continue;
+        };
+        hydrated.insert(entity, MapElementHydrated);
+        switches.insert(entity, default());
+        bodies.insert(
+            entity,
+            KinematicBody {
+                shape: ColliderShape::Rectangle { size: *size },
+                has_mass: false,
+                ..default()
+            },
+        );
+        // Draw the plate as a line as thick as the switch is tall
+        paths.insert(
+            entity,
+            Path2d {
+                color: *off_color,
+                points: vec![vec2(-size.x / 2.0, 0.0), vec2(size.x / 2.0, 0.0)],
+                thickness: size.y,
+                ..default()
+            },
+        );
+    }
+}
+
+/// Flip the switches that players step onto or that are shot.
+fn update(
+    entities: Res<Entities>,
+    mut commands: Commands,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    collision_world: CollisionWorld,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    bullets: Comp<Bullet>,
+    mut switches: CompMut<Switch>,
+    mut paths: CompMut<Path2d>,
+) {
+    for (entity, (switch, path)) in entities.iter_with((&mut switches, &mut paths)) {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(SwitchMeta {
+            off_color,
+            on_color,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
+        let mut pressed = false;
+        let mut shot = false;
+        for collider_ent in collision_world.actor_collisions(entity) {
+            if player_indexes.contains(collider_ent) && !killed_players.contains(collider_ent) {
+                pressed = true;
+            } else if bullets.contains(collider_ent) {
+                // The switch stops the bullet, so that it only flips the switch once
+                shot = true;
+                commands.add(move |mut entities: ResMutInit<Entities>| {
+                    entities.kill(collider_ent);
+                });
+            }
+        }
+
+        if (pressed && !switch.pressed) || shot {
+            switch.on = !switch.on;
+        }
+        switch.pressed = pressed;
+        path.color = if switch.on { *on_color } else { *off_color };
+    }
+}
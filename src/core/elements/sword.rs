@@ -17,6 +17,12 @@ pub struct SwordMeta {
     pub bounciness: f32,
     pub throw_velocity: f32,
     pub cooldown_frames: u32,
+    /// The velocity that players hit by a swing are knocked back with.
+    pub knockback: Vec2,
+    /// How long players hit by a swing are stunned for.
+    pub hitstun: Duration,
+    /// How long the match freezes for when a swing connects.
+    pub hitstop: Duration,
 }
 
 pub fn game_plugin(game: &mut Game) {
@@ -154,6 +160,21 @@ fn update(
     for (entity, (sword, element_handle)) in entities.iter_with((&mut swords, &element_handles)) {
         let element_meta = assets.get(element_handle.0);
 
+        let asset = assets.get(element_meta.data);
+        let Ok(SwordMeta {
+            cooldown_frames,
+            sound,
+            sound_volume,
+            killing_speed,
+            knockback,
+            hitstun,
+            hitstop,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+
         // Helper to spawn a damage region for the sword attack
         let mut spawn_damage_region = |pos: Vec3, size: Vec2, owner: Entity| {
             commands.add(
@@ -178,25 +199,23 @@ fn update(
                     );
 
                     lifetimes.insert(entity, Lifetime::new(2.0 / 60.0));
-                    damage_regions.insert(entity, DamageRegion { size });
+                    damage_regions.insert(
+                        entity,
+                        DamageRegion {
+                            size,
+                            knockback: *knockback,
+                            hitstun: *hitstun,
+                            hitstop: *hitstop,
+                            sharp: true,
+                            ..default()
+                        },
+                    );
                     transforms.insert(entity, Transform::from_translation(pos));
                     damage_region_owners.insert(entity, DamageRegionOwner(owner));
                 },
             );
         };
 
-        let asset = assets.get(element_meta.data);
-        let Ok(SwordMeta {
-            cooldown_frames,
-            sound,
-            sound_volume,
-            killing_speed,
-            ..
-        }) = asset.try_cast_ref()
-        else {
-            unreachable!();
-        };
-
         // If the item is being held
         if let Some(Inv { player, .. }) = player_inventories.find_item(entity) {
             let sprite = sprites.get_mut(entity).unwrap();
@@ -0,0 +1,366 @@
+use crate::prelude::*;
+
+/// A fish that lives in a water volume and steals items.
+///
+/// Every so often the fish leaps at an item lying near the water, or at the item held by a player
+/// swimming in it, and carries it around the water until the fish is killed by a bullet or a
+/// damage region, which makes it drop the item. Killed fish stay dead for the rest of the round.
+///
+/// The fish must be placed inside a water volume, otherwise it just floats where it was placed.
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("thief_fish"))]
+#[repr(C)]
+pub struct ThiefFishMeta {
+    pub atlas: Handle<Atlas>,
+    pub frames: SVec<u32>,
+    pub fps: f32,
+    pub body_size: Vec2,
+    /// How fast the fish swims around, in pixels per second.
+    pub swim_speed: f32,
+    /// How fast the fish swims while carrying an item.
+    pub carry_speed: f32,
+    /// How fast the fish leaps at items.
+    pub leap_speed: f32,
+    /// How long the fish waits between leaps.
+    pub leap_cooldown: Duration,
+    /// How long the fish chases an item before it gives up and goes back into the water.
+    pub leap_time: Duration,
+    /// How far from the fish an item can be for the fish to leap at it.
+    pub reach: f32,
+    /// Where the carried item is held, relative to the fish when it faces right.
+    pub mouth_offset: Vec2,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    ThiefFishMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::Update, update_thief_fish)
+        .add_system_to_stage(CoreStage::PostUpdate, kill_thief_fish);
+}
+
+/// What a thief fish is doing.
+#[derive(Clone, Copy, Debug, HasSchema, Default, PartialEq, Eq)]
+#[repr(C, u8)]
+pub enum ThiefFishState {
+    /// Swimming back and forth in the water, waiting to leap.
+    #[default]
+    Swimming,
+    /// Leaping at an item.
+    Leaping { item: Entity },
+    /// Carrying a stolen item around the water.
+    Carrying { item: Entity },
+}
+
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct ThiefFish {
+    pub state: ThiefFishState,
+    /// The left and right edges of the water that the fish lives in.
+    pub water_left: f32,
+    pub water_right: f32,
+    /// The depth that the fish swims at.
+    pub depth: f32,
+    /// Whether the fish is swimming to the left.
+    pub left: bool,
+    /// Counts down to the next leap while swimming, and to giving up on a leap while leaping.
+    pub timer: Timer,
+}
+
+/// Component added to items carried by a [`ThiefFish`], so that players can't grab them out of
+/// its mouth.
+#[derive(Clone, Copy, Debug, HasSchema, Default)]
+pub struct StolenItem {
+    /// The fish carrying the item.
+    pub thief: Entity,
+}
+
+fn hydrate(
+    mut entities: ResMutInit<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut element_handles: CompMut<ElementHandle>,
+    assets: Res<AssetServer>,
+    volumes: Comp<WaterVolume>,
+    mut transforms: CompMut<Transform>,
+    mut thief_fish: CompMut<ThiefFish>,
+    mut atlas_sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut spawner_manager: SpawnerManager,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    let spawner_entities = entities
+        .iter_with_bitset(&not_hydrated_bitset)
+        .collect::<Vec<_>>();
+
+    for spawner_ent in spawner_entities {
+        let transform = *transforms.get(spawner_ent).unwrap();
+        let element_handle = *element_handles.get(spawner_ent).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        let Ok(ThiefFishMeta {
+            atlas,
+            frames,
+            fps,
+            body_size,
+            leap_cooldown,
+            ..
+        }) = assets.get(element_meta.data).try_cast_ref()
+        else {
+            continue;
+        };
+        hydrated.insert(spawner_ent, MapElementHydrated);
+
+        let pos = transform.translation.xy();
+        let (water_left, water_right) = water_volume_at(&entities, &volumes, &transforms, pos)
+            .map(|(_, rect)| (rect.left(), rect.right()))
+            .unwrap_or((pos.x, pos.x));
+
+        let entity = entities.create();
+        thief_fish.insert(
+            entity,
+            ThiefFish {
+                water_left,
+                water_right,
+                depth: pos.y,
+                timer: Timer::new(*leap_cooldown, TimerMode::Once),
+                ..default()
+            },
+        );
+        transforms.insert(entity, transform);
+        element_handles.insert(entity, element_handle);
+        hydrated.insert(entity, MapElementHydrated);
+        atlas_sprites.insert(entity, AtlasSprite::new(*atlas));
+        animated_sprites.insert(
+            entity,
+            AnimatedSprite {
+                frames: frames.iter().cloned().collect(),
+                fps: *fps,
+                repeat: true,
+                ..default()
+            },
+        );
+        bodies.insert(
+            entity,
+            KinematicBody {
+                shape: ColliderShape::Rectangle { size: *body_size },
+                has_mass: false,
+                ..default()
+            },
+        );
+
+        spawner_manager.create_spawner(spawner_ent, vec![entity]);
+    }
+}
+
+/// Swim the fish around, leap at items, and carry the stolen ones.
+fn update_thief_fish(
+    time: Res<Time>,
+    entities: Res<Entities>,
+    mut commands: Commands,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    items: Comp<Item>,
+    player_states: Comp<PlayerState>,
+    player_inventories: PlayerInventories,
+    mut stolen_items: CompMut<StolenItem>,
+    mut thief_fish: CompMut<ThiefFish>,
+    mut transforms: CompMut<Transform>,
+    mut bodies: CompMut<KinematicBody>,
+    mut sprites: CompMut<AtlasSprite>,
+) {
+    let fish_entities = entities
+        .iter_with(&thief_fish)
+        .map(|(ent, _)| ent)
+        .collect::<Vec<_>>();
+    for entity in fish_entities {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+        let asset = assets.get(element_meta.data);
+        let Ok(ThiefFishMeta {
+            swim_speed,
+            carry_speed,
+            leap_speed,
+            leap_cooldown,
+            leap_time,
+            reach,
+            mouth_offset,
+            ..
+        }) = asset.try_cast_ref()
+        else {
+            unreachable!();
+        };
+        let fish = thief_fish.get_mut(entity).unwrap();
+        let pos = transforms.get(entity).unwrap().translation.xy();
+        let step = time.delta_seconds();
+        fish.timer.tick(time.delta());
+
+        // Where the fish swims to this frame, and how fast
+        let (target, speed) = match fish.state {
+            ThiefFishState::Swimming | ThiefFishState::Carrying { .. } => {
+                let speed = if matches!(fish.state, ThiefFishState::Swimming) {
+                    *swim_speed
+                } else {
+                    *carry_speed
+                };
+                // Turn around at the edges of the water
+                if pos.x <= fish.water_left {
+                    fish.left = false;
+                } else if pos.x >= fish.water_right {
+                    fish.left = true;
+                }
+                let x = if fish.left {
+                    fish.water_left
+                } else {
+                    fish.water_right
+                };
+                // Head back down to the fish's depth after a leap
+                let y = if (pos.y - fish.depth).abs() > speed * step {
+                    fish.depth
+                } else {
+                    pos.y
+                };
+                (vec2(x, y), speed)
+            }
+            ThiefFishState::Leaping { item } => {
+                let item_pos = transforms.get(item).map(|x| x.translation.xy());
+                match item_pos {
+                    Some(item_pos) if entities.is_alive(item) => (item_pos, *leap_speed),
+                    _ => (pos, 0.0),
+                }
+            }
+        };
+
+        let to_target = target - pos;
+        let new_pos = if to_target.length() <= speed * step {
+            target
+        } else {
+            pos + to_target.normalize_or_zero() * speed * step
+        };
+        if to_target.x != 0.0 {
+            sprites.get_mut(entity).unwrap().flip_x = to_target.x < 0.0;
+        }
+        let transform = transforms.get_mut(entity).unwrap();
+        transform.translation.x = new_pos.x;
+        transform.translation.y = new_pos.y;
+
+        match fish.state {
+            ThiefFishState::Swimming => {
+                if !fish.timer.finished() {
+                    continue;
+                }
+                fish.timer = Timer::new(*leap_cooldown, TimerMode::Once);
+
+                // Leap at the closest item lying around, or held by a swimming player
+                let closest = entities
+                    .iter_with((&items, &transforms))
+                    .filter(|(item, _)| !stolen_items.contains(*item))
+                    .filter_map(|(item, (_, transform))| {
+                        let item_pos = transform.translation.xy();
+                        let distance = item_pos.distance(new_pos);
+                        if distance > *reach {
+                            return None;
+                        }
+                        match player_inventories.find_item(item) {
+                            None => Some((item, None, distance)),
+                            Some(Inv { player, .. }) => {
+                                let state = player_states.get(player)?;
+                                (state.current == *swim::ID || state.current == *dive::ID)
+                                    .then_some((item, Some(player), distance))
+                            }
+                        }
+                    })
+                    .min_by(|a, b| a.2.total_cmp(&b.2));
+                let Some((item, holder, _)) = closest else {
+                    continue;
+                };
+                if let Some(player) = holder {
+                    commands.add(PlayerCommand::set_inventory(player, None));
+                }
+                fish.state = ThiefFishState::Leaping { item };
+                fish.timer = Timer::new(*leap_time, TimerMode::Once);
+            }
+            ThiefFishState::Leaping { item } => {
+                if !entities.is_alive(item) || fish.timer.finished() {
+                    fish.state = ThiefFishState::Swimming;
+                    fish.timer = Timer::new(*leap_cooldown, TimerMode::Once);
+                } else if new_pos == target && player_inventories.find_item(item).is_none() {
+                    fish.state = ThiefFishState::Carrying { item };
+                    stolen_items.insert(item, StolenItem { thief: entity });
+                    if let Some(body) = bodies.get_mut(item) {
+                        body.is_deactivated = true;
+                        body.velocity = Vec2::ZERO;
+                    }
+                }
+            }
+            ThiefFishState::Carrying { item } => {
+                if !entities.is_alive(item) {
+                    fish.state = ThiefFishState::Swimming;
+                    fish.timer = Timer::new(*leap_cooldown, TimerMode::Once);
+                    continue;
+                }
+                // Hold the item in the fish's mouth
+                let mut offset = *mouth_offset;
+                if sprites.get(entity).unwrap().flip_x {
+                    offset.x = -offset.x;
+                }
+                if let Some(transform) = transforms.get_mut(item) {
+                    transform.translation.x = new_pos.x + offset.x;
+                    transform.translation.y = new_pos.y + offset.y;
+                }
+            }
+        }
+    }
+}
+
+/// Kill the fish that are shot or hit by damage regions, and drop the items they were carrying.
+fn kill_thief_fish(
+    mut entities: ResMutInit<Entities>,
+    collision_world: CollisionWorld,
+    bullets: Comp<Bullet>,
+    damage_regions: Comp<DamageRegion>,
+    transforms: Comp<Transform>,
+    thief_fish: Comp<ThiefFish>,
+    mut stolen_items: CompMut<StolenItem>,
+    mut bodies: CompMut<KinematicBody>,
+) {
+    let mut killed = Vec::new();
+    for (entity, (fish, transform)) in entities.iter_with((&thief_fish, &transforms)) {
+        let fish_rect = bodies.get(entity).unwrap().bounding_box(*transform);
+        let shot = collision_world
+            .actor_collisions(entity)
+            .into_iter()
+            .find(|x| bullets.contains(*x));
+        let hit =
+            entities
+                .iter_with((&damage_regions, &transforms))
+                .any(|(_, (region, transform))| {
+                    region
+                        .collider_rect(transform.translation)
+                        .overlaps(&fish_rect)
+                });
+        if shot.is_some() || hit {
+            killed.push((entity, fish.state, shot));
+        }
+    }
+
+    for (entity, state, shot) in killed {
+        if let ThiefFishState::Carrying { item } = state {
+            stolen_items.remove(item);
+            if let Some(body) = bodies.get_mut(item) {
+                body.is_deactivated = false;
+            }
+        }
+        if let Some(bullet) = shot {
+            entities.kill(bullet);
+        }
+        entities.kill(entity);
+    }
+}
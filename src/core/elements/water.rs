@@ -0,0 +1,178 @@
+use crate::prelude::*;
+
+#[derive(HasSchema, Default, Debug, Clone)]
+#[type_data(metadata_asset("water"))]
+#[repr(C)]
+pub struct WaterMeta {
+    /// The size of the water volume in pixels.
+    pub size: Vec2,
+    /// The tint of the water.
+    pub color: Color,
+    /// How often a diving player lets out a bubble.
+    pub bubble_interval: Duration,
+    /// How long bubbles float for before popping.
+    pub bubble_lifetime: Duration,
+    /// How fast bubbles rise in pixels per frame.
+    pub bubble_speed: f32,
+}
+
+pub fn game_plugin(game: &mut Game) {
+    WaterMeta::register_schema();
+    game.init_shared_resource::<AssetServer>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, hydrate)
+        .add_system_to_stage(CoreStage::PostUpdate, update_bubbles);
+}
+
+/// A rectangular volume of water that players can swim and dive in.
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct WaterVolume {
+    pub size: Vec2,
+    pub bubble_interval: Duration,
+    pub bubble_lifetime: Duration,
+    pub bubble_speed: f32,
+}
+
+impl WaterVolume {
+    /// Get the rectangle covered by this volume, given it's position.
+    pub fn rect(&self, position: Vec3) -> Rect {
+        Rect::new(position.x, position.y, self.size.x, self.size.y)
+    }
+}
+
+/// A bubble rising out of a diving player.
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct Bubble {
+    pub speed: f32,
+}
+
+/// Find the water volume that contains the given point, returning its entity and rectangle.
+pub fn water_volume_at(
+    entities: &Entities,
+    volumes: &Comp<WaterVolume>,
+    transforms: &Comp<Transform>,
+    point: Vec2,
+) -> Option<(Entity, Rect)> {
+    entities
+        .iter_with((volumes, transforms))
+        .map(|(ent, (volume, transform))| (ent, volume.rect(transform.translation)))
+        .find(|(_, rect)| rect.contains(point))
+}
+
+fn hydrate(
+    entities: Res<Entities>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    element_handles: Comp<ElementHandle>,
+    assets: Res<AssetServer>,
+    mut volumes: CompMut<WaterVolume>,
+    mut paths: CompMut<Path2d>,
+) {
+    let mut not_hydrated_bitset = hydrated.bitset().clone();
+    not_hydrated_bitset.bit_not();
+    not_hydrated_bitset.bit_and(element_handles.bitset());
+
+    for entity in entities.iter_with_bitset(&not_hydrated_bitset) {
+        let element_handle = element_handles.get(entity).unwrap();
+        let element_meta = assets.get(element_handle.0);
+
+        if let Ok(WaterMeta {
+            size,
+            color,
+            bubble_interval,
+            bubble_lifetime,
+            bubble_speed,
+        }) = assets.get(element_meta.data).try_cast_ref()
+        {
+            hydrated.insert(entity, MapElementHydrated);
+            volumes.insert(
+                entity,
+                WaterVolume {
+                    size: *size,
+                    bubble_interval: *bubble_interval,
+                    bubble_lifetime: *bubble_lifetime,
+                    bubble_speed: *bubble_speed,
+                },
+            );
+            // Fill the volume with a single line that is as thick as the volume is tall.
+            paths.insert(
+                entity,
+                Path2d {
+                    color: *color,
+                    points: vec![vec2(-size.x / 2.0, 0.0), vec2(size.x / 2.0, 0.0)],
+                    thickness: size.y,
+                    ..default()
+                },
+            );
+        }
+    }
+}
+
+/// Spawn a bubble at the given position.
+pub fn spawn_bubble(position: Vec3, volume: WaterVolume) -> StaticSystem<(), ()> {
+    (move |mut entities: ResMutInit<Entities>,
+           mut bubbles: CompMut<Bubble>,
+           mut lifetimes: CompMut<Lifetime>,
+           mut transforms: CompMut<Transform>,
+           mut paths: CompMut<Path2d>| {
+        const RADIUS: f32 = 3.0;
+        const SEGMENTS: usize = 8;
+
+        let ent = entities.create();
+        bubbles.insert(
+            ent,
+            Bubble {
+                speed: volume.bubble_speed,
+            },
+        );
+        lifetimes.insert(ent, Lifetime::new(volume.bubble_lifetime.as_secs_f32()));
+        transforms.insert(ent, Transform::from_translation(position));
+        paths.insert(
+            ent,
+            Path2d {
+                color: Color::WHITE,
+                points: (0..=SEGMENTS)
+                    .map(|i| {
+                        Vec2::from_angle(i as f32 / SEGMENTS as f32 * std::f32::consts::TAU)
+                            * RADIUS
+                    })
+                    .collect(),
+                thickness: 1.0,
+                ..default()
+            },
+        );
+    })
+    .system()
+}
+
+/// Floats bubbles upwards, popping them when they reach the surface.
+fn update_bubbles(
+    mut entities: ResMutInit<Entities>,
+    bubbles: Comp<Bubble>,
+    volumes: Comp<WaterVolume>,
+    mut transforms: CompMut<Transform>,
+) {
+    let water_rects = entities
+        .iter_with((&volumes, &transforms))
+        .map(|(_, (volume, transform))| volume.rect(transform.translation))
+        .collect::<Vec<_>>();
+
+    let mut to_pop = Vec::new();
+    for (ent, (bubble, transform)) in entities.iter_with((&bubbles, &mut transforms)) {
+        transform.translation.y += bubble.speed;
+
+        // Wobble from side to side as it rises
+        transform.translation.x += (transform.translation.y / 8.0).sin() * 0.5;
+
+        let pos = transform.translation.truncate();
+        if !water_rects.iter().any(|rect| rect.contains(pos)) {
+            to_pop.push(ent);
+        }
+    }
+    for ent in to_pop {
+        entities.kill(ent);
+    }
+}
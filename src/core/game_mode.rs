@@ -0,0 +1,118 @@
+//! Game modes.
+//!
+//! The [`GameMode`] is picked during match setup and inserted into the match session as a
+//! resource. Every mode shares the same core simulation, but installs its own round flow and may
+//! change how players are spawned and controlled.
+
+use crate::prelude::*;
+
+pub mod bomb_tag;
+pub mod hot_seat;
+pub mod infection;
+pub mod training;
+
+pub use bomb_tag::{BombTagMeta, BombTagState};
+pub use hot_seat::{HotSeatMeta, HotSeatPhase, HotSeatState};
+pub use infection::{InfectionMeta, InfectionState, InfectionTeam};
+pub use training::{FrameStep, TrainingHit, TrainingMeta, TrainingState};
+
+/// Install the systems for the given game mode.
+pub fn install(session: &mut SessionBuilder, mode: GameMode) {
+    session.insert_resource(mode);
+    scoring::install_kill_scoring(session);
+
+    match mode {
+        GameMode::Elimination => scoring::session_plugin(session),
+        GameMode::HotSeat => hot_seat::install(session),
+        GameMode::BombTag => bomb_tag::install(session),
+        GameMode::Infection => infection::install(session),
+        GameMode::Training => training::install(session),
+    }
+}
+
+/// The rules that a match is played with.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum GameMode {
+    /// Last fish standing wins the round.
+    #[default]
+    Elimination,
+    /// Players take turns controlling the same fish, setting traps for each other.
+    HotSeat,
+    /// Pass the bomb by touching other players before it explodes.
+    BombTag,
+    /// Survive until the timer runs out while the infected grow their ranks.
+    Infection,
+    /// Practice on any map with endless respawns and debugging aids, without rounds.
+    Training,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [
+        GameMode::Elimination,
+        GameMode::HotSeat,
+        GameMode::BombTag,
+        GameMode::Infection,
+        GameMode::Training,
+    ];
+
+    /// The identifier of the mode, used in match reports.
+    pub fn id(&self) -> &'static str {
+        match self {
+            GameMode::Elimination => "elimination",
+            GameMode::HotSeat => "hot_seat",
+            GameMode::BombTag => "bomb_tag",
+            GameMode::Infection => "infection",
+            GameMode::Training => "training",
+        }
+    }
+
+    /// The localization key for the name of the mode.
+    pub fn localization_key(&self) -> &'static str {
+        match self {
+            GameMode::Elimination => "game-mode-elimination",
+            GameMode::HotSeat => "game-mode-hot-seat",
+            GameMode::BombTag => "game-mode-bomb-tag",
+            GameMode::Infection => "game-mode-infection",
+            GameMode::Training => "game-mode-training",
+        }
+    }
+
+    /// Whether the mode can be played in online matches.
+    pub fn supports_network(&self) -> bool {
+        match self {
+            GameMode::Elimination | GameMode::BombTag | GameMode::Infection => true,
+            GameMode::HotSeat | GameMode::Training => false,
+        }
+    }
+
+    /// Whether the player in the given slot gets a fish of their own.
+    pub fn spawns_player(&self, player_idx: u32, player_inputs: &MatchInputs) -> bool {
+        match self {
+            GameMode::Elimination
+            | GameMode::BombTag
+            | GameMode::Infection
+            | GameMode::Training => player_inputs.players[player_idx as usize].active,
+            GameMode::HotSeat => hot_seat::shared_fish_slot(player_inputs) == Some(player_idx),
+        }
+    }
+
+    /// Whether the match is played in rounds, as opposed to going on until the players leave.
+    pub fn has_rounds(&self) -> bool {
+        match self {
+            GameMode::Elimination | GameMode::HotSeat | GameMode::BombTag | GameMode::Infection => {
+                true
+            }
+            GameMode::Training => false,
+        }
+    }
+
+    /// Whether each player always spawns at the same spawner, picked by their slot, instead of at
+    /// the spawner farthest from their opponents.
+    pub fn uses_fixed_spawns(&self) -> bool {
+        match self {
+            GameMode::Elimination | GameMode::BombTag | GameMode::Infection => false,
+            GameMode::HotSeat | GameMode::Training => true,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Bomb-tag game mode.
+//!
+//! One player is handed a bomb that can't be thrown or dropped, and passes it on by touching
+//! another player. When the fuse runs out the bomb explodes and its holder is eliminated, after
+//! which a new bomb is handed to one of the survivors. The round is scored like an elimination
+//! round, so the last fish standing wins.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<BombTagState>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PostUpdate, update_bomb_tag)
+        .add_system_to_stage(CoreStage::Last, update_tag_bomb_sprite);
+
+    scoring::session_plugin(session);
+}
+
+/// Settings for the bomb-tag game mode.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct BombTagMeta {
+    /// The kick bomb element used for the look and sound of the tag bomb.
+    pub bomb: Handle<ElementMeta>,
+    /// How long it takes for the bomb to explode.
+    pub fuse_time: Duration,
+    /// How long after being tagged the bomb can't be passed on.
+    ///
+    /// This prevents players from tagging each other back and forth every frame.
+    pub tag_cooldown: Duration,
+    /// The offset of the bomb from the center of the player holding it.
+    pub bomb_offset: Vec2,
+}
+
+/// Session resource tracking the bomb in a bomb-tag round.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct BombTagState {
+    /// The player holding the bomb.
+    pub holder: Option<Entity>,
+    /// Counts down to the explosion.
+    pub fuse: Timer,
+    /// Counts down until the bomb may be passed on again.
+    pub tag_cooldown: Timer,
+    /// The entity used to render the bomb over the holder.
+    pub bomb_ent: Option<Entity>,
+}
+
+impl BombTagState {
+    fn give_bomb(&mut self, player: Entity, meta: &BombTagMeta) {
+        self.holder = Some(player);
+        self.tag_cooldown = Timer::new(meta.tag_cooldown, TimerMode::Once);
+    }
+}
+
+fn update_bomb_tag(
+    meta: Root<GameMeta>,
+    time: Res<Time>,
+    entities: Res<Entities>,
+    rng: Res<GlobalRng>,
+    assets: Res<AssetServer>,
+    collision_world: CollisionWorld,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    transforms: Comp<Transform>,
+    mut state: ResMut<BombTagState>,
+    mut audio_center: ResMut<AudioCenter>,
+    mut spatial_audio: ResMut<SpatialAudio>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
+    mut commands: Commands,
+) {
+    let bomb_tag = &meta.core.bomb_tag;
+    let Ok(bomb_meta) = assets
+        .get(assets.get(bomb_tag.bomb).data)
+        .try_cast_ref::<KickBombMeta>()
+        .cloned()
+    else {
+        return;
+    };
+
+    let alive_players = entities
+        .iter_with(&player_indexes)
+        .filter(|(ent, _)| !killed_players.contains(*ent))
+        .map(|(ent, _)| ent)
+        .collect::<Vec<_>>();
+
+    // Tag is over once there is nobody left to pass the bomb to
+    if alive_players.len() < 2 {
+        state.holder = None;
+        return;
+    }
+
+    // Start a new round of tag with a random survivor holding a fresh bomb
+    if !state.holder.is_some_and(|ent| alive_players.contains(&ent)) {
+        let player = alive_players[rng.usize(0..alive_players.len())];
+        state.give_bomb(player, bomb_tag);
+        state.fuse = Timer::new(bomb_tag.fuse_time, TimerMode::Once);
+        audio_center.play_sound(bomb_meta.fuse_sound, bomb_meta.fuse_sound_volume);
+    }
+    let holder = state.holder.unwrap();
+
+    state.fuse.tick(time.delta());
+    state.tag_cooldown.tick(time.delta());
+
+    if state.fuse.finished() {
+        let mut explosion_transform = *transforms.get(holder).unwrap();
+        spatial_audio.play_sound_at(
+            bomb_meta.explosion_sound,
+            bomb_meta.explosion_volume,
+            explosion_transform.translation.xy(),
+        );
+        camera_shake_events.send(CameraShakeKind::Explosion);
+
+        explosion_transform.translation.z = -10.0; // On top of almost everything
+        explosion_transform.rotation = Quat::IDENTITY;
+
+        commands.add(PlayerCommand::kill(
+            holder,
+            Some(explosion_transform.translation.xy()),
+        ));
+        commands.add(
+            move |mut entities: ResMutInit<Entities>,
+                  mut transforms: CompMut<Transform>,
+                  mut lifetimes: CompMut<Lifetime>,
+                  mut sprites: CompMut<AtlasSprite>,
+                  mut animated_sprites: CompMut<AnimatedSprite>,
+                  storage: Res<Storage>| {
+                let ent = entities.create();
+                transforms.insert(ent, explosion_transform);
+                sprites.insert(
+                    ent,
+                    AtlasSprite {
+                        atlas: bomb_meta.explosion_atlas,
+                        ..default()
+                    },
+                );
+                animated_sprites.insert(
+                    ent,
+                    explosion_animation(
+                        bomb_meta.explosion_frames,
+                        bomb_meta.explosion_fps,
+                        bomb_meta.explosion_lifetime,
+                        &storage,
+                    ),
+                );
+                lifetimes.insert(ent, Lifetime::new(bomb_meta.explosion_lifetime));
+            },
+        );
+
+        state.holder = None;
+        return;
+    }
+
+    // Pass the bomb on to the first player the holder touches
+    if state.tag_cooldown.finished() {
+        let tagged = collision_world
+            .actor_collisions_filtered(holder, |ent| ent != holder && alive_players.contains(&ent))
+            .into_iter()
+            .next();
+        if let Some(tagged) = tagged {
+            state.give_bomb(tagged, bomb_tag);
+            audio_center.play_sound(bomb_meta.fuse_sound, bomb_meta.fuse_sound_volume);
+        }
+    }
+}
+
+/// Keep the bomb sprite attached to whoever is holding the bomb.
+fn update_tag_bomb_sprite(
+    meta: Root<GameMeta>,
+    assets: Res<AssetServer>,
+    mut entities: ResMutInit<Entities>,
+    mut state: ResMut<BombTagState>,
+    mut transforms: CompMut<Transform>,
+    mut attachments: CompMut<Attachment>,
+    mut sprites: CompMut<AtlasSprite>,
+    mut animated_sprites: CompMut<AnimatedSprite>,
+) {
+    let Some(holder) = state.holder else {
+        if let Some(bomb_ent) = state.bomb_ent.take() {
+            entities.kill(bomb_ent);
+        }
+        return;
+    };
+    let bomb_tag = &meta.core.bomb_tag;
+
+    let bomb_ent = match state.bomb_ent {
+        Some(ent) if entities.is_alive(ent) => ent,
+        _ => {
+            let Ok(bomb_meta) = assets
+                .get(assets.get(bomb_tag.bomb).data)
+                .try_cast_ref::<KickBombMeta>()
+            else {
+                return;
+            };
+
+            let ent = entities.create();
+            transforms.insert(ent, default());
+            sprites.insert(
+                ent,
+                AtlasSprite {
+                    atlas: bomb_meta.atlas,
+                    ..default()
+                },
+            );
+            animated_sprites.insert(
+                ent,
+                AnimatedSprite {
+                    frames: (bomb_meta.lit_frames_start..bomb_meta.lit_frames_end).collect(),
+                    fps: bomb_meta.lit_fps,
+                    repeat: true,
+                    ..default()
+                },
+            );
+            state.bomb_ent = Some(ent);
+            ent
+        }
+    };
+
+    attachments.insert(
+        bomb_ent,
+        Attachment {
+            entity: holder,
+            offset: bomb_tag.bomb_offset.extend(1.0),
+            ..default()
+        },
+    );
+}
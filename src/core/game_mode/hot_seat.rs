@@ -0,0 +1,271 @@
+//! Hot-seat party mode.
+//!
+//! All players share a single fish and pass the controller around. Every turn starts with a trap
+//! phase, where the current player places hazards around the map, followed by a survive phase,
+//! where the next player in line has to stay alive for as long as they can. The time each player
+//! survives is added up, and once everybody has had their turns the longest survivor wins the
+//! round.
+//!
+//! This mode is only available in local play.
+
+use crate::{prelude::*, ui::scoring::ScoringMenuState};
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<HotSeatState>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, route_hot_seat_input)
+        .add_system_to_stage(CoreStage::PostUpdate, update_hot_seat);
+}
+
+/// Settings for the hot-seat game mode.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct HotSeatMeta {
+    /// How long the trap phase of each turn lasts.
+    pub trap_time: Duration,
+    /// The longest a player can survive in one turn.
+    pub survive_time: Duration,
+    /// How many traps may be placed in each trap phase.
+    pub max_traps: u32,
+    /// How many times each player gets to survive per round.
+    pub turns_per_player: u32,
+    /// The elements that may be placed as traps, cycled through in order.
+    pub traps: SVec<Handle<ElementMeta>>,
+}
+
+/// The phase of a hot-seat turn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HotSeatPhase {
+    /// The trapper is placing traps.
+    #[default]
+    Trap,
+    /// The survivor is trying to stay alive.
+    Survive,
+    /// Everybody has had their turns, and the round is about to end.
+    RoundOver,
+}
+
+/// Session resource tracking the progress of a hot-seat round.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct HotSeatState {
+    /// The players taking turns, in order.
+    pub turn_order: Vec<PlayerIdx>,
+    /// The number of turns completed this round.
+    pub turn: u32,
+    pub phase: HotSeatPhase,
+    /// Timer for the current phase.
+    pub timer: Timer,
+    /// The number of traps placed in the current trap phase.
+    pub traps_placed: u32,
+    /// Whether the trapper pressed the button to place a trap this frame.
+    pub trap_requested: bool,
+    /// The total time survived by each player this round.
+    pub survival_times: [Duration; MAX_PLAYERS as usize],
+    /// The winner of the round, once it is over.
+    pub winner: Option<PlayerIdx>,
+}
+
+impl HotSeatState {
+    /// The player placing traps this turn.
+    pub fn trapper(&self) -> Option<PlayerIdx> {
+        let len = self.turn_order.len() as u32;
+        (len > 0).then(|| self.turn_order[(self.turn % len) as usize])
+    }
+
+    /// The player trying to survive this turn.
+    pub fn survivor(&self) -> Option<PlayerIdx> {
+        let len = self.turn_order.len() as u32;
+        (len > 0).then(|| self.turn_order[((self.turn + 1) % len) as usize])
+    }
+
+    /// The player that is currently controlling the shared fish.
+    pub fn controller(&self) -> Option<PlayerIdx> {
+        match self.phase {
+            HotSeatPhase::Trap => self.trapper(),
+            HotSeatPhase::Survive => self.survivor(),
+            HotSeatPhase::RoundOver => None,
+        }
+    }
+
+    /// The total number of turns in a round.
+    pub fn total_turns(&self, meta: &HotSeatMeta) -> u32 {
+        self.turn_order.len() as u32 * meta.turns_per_player.max(1)
+    }
+
+    fn start_phase(&mut self, phase: HotSeatPhase, duration: Duration) {
+        self.phase = phase;
+        self.timer = Timer::new(duration, TimerMode::Once);
+    }
+
+    /// Find the player that survived the longest, or [`None`] if there is a tie.
+    fn longest_survivor(&self) -> Option<PlayerIdx> {
+        let time = |idx: &PlayerIdx| self.survival_times[idx.0 as usize];
+        let best = self.turn_order.iter().max_by_key(|idx| time(idx))?;
+        let tied = self
+            .turn_order
+            .iter()
+            .filter(|idx| time(idx) == time(best))
+            .count()
+            > 1;
+        (!tied).then_some(*best)
+    }
+}
+
+/// Get the player slot whose fish is shared by everybody in hot-seat mode.
+pub fn shared_fish_slot(player_inputs: &MatchInputs) -> Option<u32> {
+    player_inputs
+        .players
+        .iter()
+        .position(|player| player.active && !player.is_ai)
+        .map(|i| i as u32)
+}
+
+/// Drive the shared fish with the controls of whoever's turn it is.
+fn route_hot_seat_input(mut state: ResMut<HotSeatState>, mut player_inputs: ResMut<MatchInputs>) {
+    let Some(fish_slot) = shared_fish_slot(&player_inputs) else {
+        return;
+    };
+
+    let mut control = state
+        .controller()
+        .map(|idx| player_inputs.players[idx.0 as usize].control)
+        .unwrap_or_default();
+
+    // The trapper uses the shoot button to place traps instead.
+    state.trap_requested = state.phase == HotSeatPhase::Trap && control.shoot_just_pressed;
+    if state.phase == HotSeatPhase::Trap {
+        control.shoot_pressed = false;
+        control.shoot_just_pressed = false;
+    }
+
+    player_inputs.players[fish_slot as usize].control = control;
+}
+
+fn update_hot_seat(
+    meta: Root<GameMeta>,
+    time: Res<Time>,
+    entities: Res<Entities>,
+    rng: Res<GlobalRng>,
+    map_pool: Res<MapPool>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    transforms: Comp<Transform>,
+    player_spawners: Comp<PlayerSpawner>,
+    map_layers: Comp<SpawnedMapLayerMeta>,
+    mut invincibles: CompMut<Invincibility>,
+    mut state: ResMut<HotSeatState>,
+    mut score: ResMutInit<MatchScore>,
+    mut sessions: ResMut<Sessions>,
+    mut session_options: ResMut<SessionOptions>,
+    mut scoring_menu: ResMut<ScoringMenuState>,
+    mut commands: Commands,
+) {
+    let hot_seat = &meta.core.hot_seat;
+
+    // Everybody that is playing takes turns, except for AI players
+    if state.turn_order.is_empty() {
+        state.turn_order = (0..MAX_PLAYERS)
+            .filter(|i| {
+                let player = &player_inputs.players[*i as usize];
+                player.active && !player.is_ai
+            })
+            .map(PlayerIdx)
+            .collect();
+        if state.turn_order.is_empty() {
+            return;
+        }
+        state.start_phase(HotSeatPhase::Trap, hot_seat.trap_time);
+    }
+
+    let fish_slot = shared_fish_slot(&player_inputs);
+    let fish = entities
+        .iter_with(&player_indexes)
+        .find(|(_, idx)| Some(idx.0) == fish_slot)
+        .map(|(ent, _)| ent);
+    let fish_is_dead = fish.is_some_and(|ent| killed_players.contains(ent));
+
+    state.timer.tick(time.delta());
+
+    match state.phase {
+        HotSeatPhase::Trap => {
+            if let Some(fish) = fish.filter(|_| !fish_is_dead) {
+                // The fish can't be killed while traps are being set
+                if !invincibles.contains(fish) {
+                    invincibles.insert(fish, Invincibility::new(state.timer.remaining()));
+                }
+
+                if state.trap_requested
+                    && state.traps_placed < hot_seat.max_traps
+                    && !hot_seat.traps.is_empty()
+                {
+                    let trap = hot_seat.traps[state.traps_placed as usize % hot_seat.traps.len()];
+                    let position = transforms.get(fish).unwrap().translation.truncate();
+                    let layer_index = entities
+                        .iter_with((&player_spawners, &map_layers))
+                        .next()
+                        .map(|(_, (_, layer))| layer.layer_idx)
+                        .unwrap_or_default();
+                    commands.add(move |mut map_manager: MapManager| {
+                        map_manager.create_element(&trap, &position, layer_index);
+                    });
+                    state.traps_placed += 1;
+                }
+            }
+
+            if state.timer.finished() {
+                // Send the fish back to a spawn point for the survivor
+                if let Some(fish) = fish {
+                    commands.add(PlayerCommand::despawn(fish));
+                }
+                state.start_phase(HotSeatPhase::Survive, hot_seat.survive_time);
+            }
+        }
+        HotSeatPhase::Survive => {
+            if !fish_is_dead && !state.timer.finished() {
+                return;
+            }
+
+            if let Some(survivor) = state.survivor() {
+                let survived = state.timer.elapsed();
+                state.survival_times[survivor.0 as usize] += survived;
+            }
+
+            state.turn += 1;
+            state.traps_placed = 0;
+            if state.turn >= state.total_turns(hot_seat) {
+                let winner = state.longest_survivor();
+                state.winner = winner;
+                score.complete_round(winner);
+                state.start_phase(
+                    HotSeatPhase::RoundOver,
+                    meta.core.config.round_end_post_score_linger_time,
+                );
+            } else {
+                // The player that just survived sets the traps for the next one
+                state.start_phase(HotSeatPhase::Trap, hot_seat.trap_time);
+            }
+        }
+        HotSeatPhase::RoundOver => {
+            if !state.timer.just_finished() {
+                return;
+            }
+
+            // Hot-seat is local only, so there is no need to wait for the round end to be
+            // confirmed by remote players.
+            commands.add(complete_round_report(state.winner));
+
+            let mut next_maps = map_pool.clone();
+            next_maps.randomize_current_map(&rng);
+            transition_to_next_round(
+                next_maps,
+                &score,
+                &meta,
+                &mut scoring_menu,
+                &mut session_options,
+                &mut sessions,
+            );
+        }
+    }
+}
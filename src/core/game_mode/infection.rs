@@ -0,0 +1,232 @@
+//! Infection game mode.
+//!
+//! One random player starts the round infected. Infected players may only fight with melee
+//! weapons, but they come back shortly after dying. Any survivor that dies comes back on the
+//! infected team. The survivors that are still around when the round timer runs out win the
+//! round, while patient zero wins if everybody gets infected.
+
+use crate::{prelude::*, ui::scoring::ScoringMenuState};
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<InfectionState>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PostUpdate, update_infection)
+        .add_system_to_stage(CoreStage::PostUpdate, restrict_infected_loadout)
+        .add_system_to_stage(CoreStage::Last, tint_infected_players);
+}
+
+/// Settings for the infection game mode.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct InfectionMeta {
+    /// How long the survivors have to last to win the round.
+    pub round_time: Duration,
+    /// How long it takes for infected players to come back after dying.
+    pub respawn_time: Duration,
+    /// The items that infected players are allowed to hold. Anything else is dropped.
+    pub infected_items: SVec<Handle<ElementMeta>>,
+    /// The tint applied to infected players.
+    pub infected_color: Color,
+}
+
+/// The team that a player is on in an infection round.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InfectionTeam {
+    #[default]
+    Survivor,
+    Infected,
+}
+
+/// Session resource tracking the teams and timer of an infection round.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct InfectionState {
+    /// The team of every player slot.
+    pub teams: [InfectionTeam; MAX_PLAYERS as usize],
+    /// The player that started the round infected, once the round has started.
+    pub patient_zero: Option<PlayerIdx>,
+    /// Counts down until the survivors win.
+    pub timer: Timer,
+    /// The players that won the round, once it is over.
+    pub winners: Option<Vec<PlayerIdx>>,
+    /// Counts down until the transition to the next round after the round is over.
+    pub round_over_timer: Timer,
+}
+
+impl InfectionState {
+    /// Get the team of the given player.
+    pub fn team(&self, player: PlayerIdx) -> InfectionTeam {
+        self.teams[player.0 as usize]
+    }
+
+    /// Whether the given player is infected.
+    pub fn is_infected(&self, player: PlayerIdx) -> bool {
+        self.team(player) == InfectionTeam::Infected
+    }
+
+    fn end_round(&mut self, winners: Vec<PlayerIdx>, linger_time: Duration) {
+        self.winners = Some(winners);
+        self.round_over_timer = Timer::new(linger_time, TimerMode::Once);
+    }
+}
+
+fn update_infection(
+    meta: Root<GameMeta>,
+    time: Res<Time>,
+    entities: Res<Entities>,
+    rng: Res<GlobalRng>,
+    map_pool: Res<MapPool>,
+    player_inputs: Res<MatchInputs>,
+    mut map_vote: ResMut<MapVote>,
+    player_indexes: Comp<PlayerIdx>,
+    player_states: Comp<PlayerState>,
+    killed_players: Comp<PlayerKilled>,
+    mut state: ResMut<InfectionState>,
+    mut scoring_state: ResMutInit<RoundScoringState>,
+    mut score: ResMutInit<MatchScore>,
+    mut sessions: ResMut<Sessions>,
+    mut session_options: ResMut<SessionOptions>,
+    mut scoring_menu: ResMut<ScoringMenuState>,
+    mut commands: Commands,
+    afk_players: Res<AfkPlayers>,
+    #[cfg(not(target_arch = "wasm32"))] syncing_info: Option<Res<SyncingInfo>>,
+) {
+    let infection = &meta.core.infection;
+
+    if let Some(winners) = state.winners.clone() {
+        state.round_over_timer.tick(time.delta());
+        if !state.round_over_timer.finished() {
+            return;
+        }
+
+        let Some(next_maps) = map_vote.update(&map_pool, &rng, &player_inputs, &meta, time.delta())
+        else {
+            return;
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let next_maps =
+            synchronize_round_transition(&mut scoring_state, next_maps, syncing_info.as_deref());
+        #[cfg(target_arch = "wasm32")]
+        let next_maps = synchronize_round_transition(&mut scoring_state, next_maps);
+
+        if let Some(next_maps) = next_maps {
+            let report_winner = (winners.len() == 1).then(|| winners[0]);
+            commands.add(complete_round_report(report_winner));
+            transition_to_next_round(
+                next_maps,
+                &score,
+                &meta,
+                &mut scoring_menu,
+                &mut session_options,
+                &mut sessions,
+            );
+        }
+        return;
+    }
+
+    let players = entities
+        .iter_with(&player_indexes)
+        .map(|(ent, idx)| (ent, *idx))
+        .collect::<Vec<_>>();
+
+    // Infect a random player once everybody has spawned in
+    if state.patient_zero.is_none() {
+        if players.len() < 2 {
+            return;
+        }
+        let (_, patient_zero) = players[rng.usize(0..players.len())];
+        state.teams[patient_zero.0 as usize] = InfectionTeam::Infected;
+        state.patient_zero = Some(patient_zero);
+        state.timer = Timer::new(infection.round_time, TimerMode::Once);
+    }
+
+    state.timer.tick(time.delta());
+
+    let respawn_frames = (infection.respawn_time.as_secs_f32() * FPS) as u64;
+    for (ent, idx) in &players {
+        if !killed_players.contains(*ent) {
+            continue;
+        }
+
+        // Whoever dies joins the infected, and comes back after a short while
+        state.teams[idx.0 as usize] = InfectionTeam::Infected;
+        let is_dead = player_states
+            .get(*ent)
+            .is_some_and(|x| x.current == *dead::ID && x.age >= respawn_frames);
+        if is_dead {
+            commands.add(PlayerCommand::despawn(*ent));
+        }
+    }
+
+    let survivors = players
+        .iter()
+        .map(|(_, idx)| *idx)
+        .filter(|idx| !state.is_infected(*idx))
+        .collect::<Vec<_>>();
+    let linger_time = meta.core.config.round_end_post_score_linger_time;
+
+    // AFK players can't win the round
+    let without_afk = |winners: &[PlayerIdx]| {
+        winners
+            .iter()
+            .copied()
+            .filter(|x| !afk_players.is_afk(*x))
+            .collect::<Vec<_>>()
+    };
+    if survivors.is_empty() {
+        let winners = state.patient_zero.into_iter().collect::<Vec<_>>();
+        score.complete_team_round(&without_afk(&winners));
+        state.end_round(winners, linger_time);
+    } else if state.timer.finished() {
+        score.complete_team_round(&without_afk(&survivors));
+        state.end_round(survivors, linger_time);
+    }
+}
+
+/// Make infected players drop anything that isn't a melee weapon.
+fn restrict_infected_loadout(
+    meta: Root<GameMeta>,
+    entities: Res<Entities>,
+    state: Res<InfectionState>,
+    player_indexes: Comp<PlayerIdx>,
+    inventories: Comp<Inventory>,
+    element_handles: Comp<ElementHandle>,
+    mut commands: Commands,
+) {
+    let allowed = &meta.core.infection.infected_items;
+    for (player, (idx, inventory)) in entities.iter_with((&player_indexes, &inventories)) {
+        let Some(item) = inventory.0 else {
+            continue;
+        };
+        if !state.is_infected(*idx) {
+            continue;
+        }
+
+        let is_allowed = element_handles
+            .get(item)
+            .is_some_and(|handle| allowed.iter().any(|x| *x == handle.0));
+        if !is_allowed {
+            commands.add(PlayerCommand::set_inventory(player, None));
+        }
+    }
+}
+
+fn tint_infected_players(
+    meta: Root<GameMeta>,
+    entities: Res<Entities>,
+    state: Res<InfectionState>,
+    player_indexes: Comp<PlayerIdx>,
+    mut sprites: CompMut<AtlasSprite>,
+) {
+    for (_, (idx, sprite)) in entities.iter_with((&player_indexes, &mut sprites)) {
+        // Keep the alpha, which is used to flicker invincible players
+        let alpha = sprite.color.a();
+        sprite.color = if state.is_infected(*idx) {
+            meta.core.infection.infected_color
+        } else {
+            Color::WHITE
+        };
+        sprite.color.set_a(alpha);
+    }
+}
@@ -0,0 +1,164 @@
+//! Training game mode.
+//!
+//! A sandbox for trying out maps and weapons. Rounds never end and players come back shortly after
+//! dying. Any element can be dropped next to the player from the training menu, hitboxes, damage
+//! regions and map bounds are always drawn, and hits are labeled where they land. The simulation can
+//! be frozen and advanced one frame at a time with [`FREEZE_KEY`] and [`STEP_KEY`], which shows the
+//! frame data of every player on the HUD.
+
+use crate::prelude::*;
+
+/// The key that freezes and unfreezes the simulation.
+pub const FREEZE_KEY: KeyCode = KeyCode::F6;
+/// The key that advances the frozen simulation by one frame.
+pub const STEP_KEY: KeyCode = KeyCode::F7;
+
+/// How long hits stay labeled, in frames.
+pub const HIT_LABEL_FRAMES: u32 = 60;
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<TrainingState>();
+    session.init_resource::<FrameStep>();
+    session.insert_resource(DebugSettings {
+        show_kinematic_colliders: true,
+        show_damage_regions: true,
+        show_map_bounds: true,
+        ..default()
+    });
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PreUpdate, spawn_requested_elements)
+        .add_system_to_stage(CoreStage::PostUpdate, update_training);
+}
+
+/// Settings for the training game mode.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct TrainingMeta {
+    /// How long it takes for players to come back after dying.
+    pub respawn_time: Duration,
+    /// How far above the player spawned elements are dropped.
+    pub spawn_offset: Vec2,
+}
+
+/// A player getting hit in a training match.
+#[derive(Clone, Copy, Debug)]
+pub struct TrainingHit {
+    /// Where the player was hit.
+    pub pos: Vec2,
+    /// How far away the hit came from, if it is known.
+    pub distance: Option<f32>,
+    /// How many frames ago the hit happened.
+    pub age: u32,
+}
+
+/// Session resource with the state of a training match.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct TrainingState {
+    /// Elements to drop next to the first player, requested from the training menu.
+    pub requested_spawns: Vec<Handle<ElementMeta>>,
+    /// The recent hits, used to label them on the HUD.
+    pub hits: Vec<TrainingHit>,
+    /// The players that were hit, so that each death is only counted once.
+    hit_players: Vec<Entity>,
+}
+
+/// Session resource that lets the match runner freeze the simulation and advance it frame by frame.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct FrameStep {
+    /// Whether the simulation is frozen.
+    pub frozen: bool,
+    /// Whether to advance the frozen simulation by one frame.
+    step: bool,
+}
+
+impl FrameStep {
+    /// Freeze, unfreeze, or step the simulation with the keyboard.
+    pub fn handle_keyboard(&mut self, keyboard: &KeyboardInputs) {
+        for event in &keyboard.key_events {
+            if event.button_state.pressed() {
+                continue;
+            }
+            match event.key_code.option() {
+                Some(FREEZE_KEY) => self.frozen = !self.frozen,
+                Some(STEP_KEY) if self.frozen => self.step = true,
+                _ => (),
+            }
+        }
+    }
+
+    /// Whether the frozen simulation should advance one frame, which resets the step request.
+    pub fn take_step(&mut self) -> bool {
+        std::mem::take(&mut self.step)
+    }
+}
+
+/// Drop the elements requested from the training menu above the first player.
+fn spawn_requested_elements(
+    meta: Root<GameMeta>,
+    entities: Res<Entities>,
+    player_indexes: Comp<PlayerIdx>,
+    transforms: Comp<Transform>,
+    assets: Res<AssetServer>,
+    registry: Res<ElementRegistry>,
+    mut state: ResMut<TrainingState>,
+    mut commands: Commands,
+) {
+    if state.requested_spawns.is_empty() {
+        return;
+    }
+    let Some(pos) = entities
+        .iter_with((&player_indexes, &transforms))
+        .min_by_key(|(_, (idx, _))| idx.0)
+        .map(|(_, (_, transform))| transform.translation.xy())
+    else {
+        return;
+    };
+
+    let pos = pos + meta.core.training.spawn_offset;
+    for handle in state.requested_spawns.drain(..) {
+        let transform = Transform::from_translation(pos.extend(z_depth_for_map_layer(0)));
+        commands.add(registry.spawn(handle, transform, &assets));
+    }
+}
+
+/// Respawn dead players, and record the hits that killed them.
+fn update_training(
+    meta: Root<GameMeta>,
+    entities: Res<Entities>,
+    player_indexes: Comp<PlayerIdx>,
+    player_states: Comp<PlayerState>,
+    killed_players: Comp<PlayerKilled>,
+    transforms: Comp<Transform>,
+    mut state: ResMut<TrainingState>,
+    mut commands: Commands,
+) {
+    for hit in &mut state.hits {
+        hit.age += 1;
+    }
+    state.hits.retain(|x| x.age < HIT_LABEL_FRAMES);
+
+    let respawn_frames = (meta.core.training.respawn_time.as_secs_f32() * FPS) as u64;
+    let mut hit_players = Vec::new();
+    for (ent, (_, transform, killed)) in
+        entities.iter_with((&player_indexes, &transforms, &killed_players))
+    {
+        hit_players.push(ent);
+        if !state.hit_players.contains(&ent) {
+            let pos = transform.translation.xy();
+            state.hits.push(TrainingHit {
+                pos,
+                distance: killed.hit_from.map(|x| x.distance(pos)),
+                age: 0,
+            });
+        }
+
+        let is_dead = player_states
+            .get(ent)
+            .is_some_and(|x| x.current == *dead::ID && x.age >= respawn_frames);
+        if is_dead {
+            commands.add(PlayerCommand::despawn(ent));
+        }
+    }
+    state.hit_players = hit_players;
+}
@@ -0,0 +1,174 @@
+//! Heatmaps of where things happen during matches.
+//!
+//! The deaths, kills, and item pickups in the [`MatchEventLog`] are saved per-map to persistent
+//! [`Storage`] once each round is over. The heatmap for the current map can then be rendered as an
+//! overlay on top of the map, to help map makers balance spawn and weapon placement.
+
+use crate::prelude::*;
+
+/// Install this module.
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<HeatmapOverlay>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::Last, render_heatmap_overlay);
+}
+
+/// The kind of event recorded in a heatmap.
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug, Hash, HasSchema)]
+#[repr(u8)]
+pub enum HeatmapEventKind {
+    /// A player died at this position.
+    #[default]
+    Death,
+    /// A player killed another player from this position.
+    Kill,
+    /// A player picked up an item at this position.
+    Pickup,
+}
+
+impl HeatmapEventKind {
+    /// Get the heatmap event kind for a match event, if it is one that is shown on heatmaps.
+    pub fn from_match_event(kind: MatchEventKind) -> Option<Self> {
+        match kind {
+            MatchEventKind::Death => Some(HeatmapEventKind::Death),
+            MatchEventKind::Kill => Some(HeatmapEventKind::Kill),
+            MatchEventKind::Pickup => Some(HeatmapEventKind::Pickup),
+            MatchEventKind::Assist => None,
+        }
+    }
+
+    /// All of the heatmap event kinds.
+    pub const ALL: [Self; 3] = [Self::Death, Self::Kill, Self::Pickup];
+
+    /// The color that this kind of event is rendered with in the overlay.
+    fn color(&self) -> Color {
+        match self {
+            HeatmapEventKind::Death => Color::RED,
+            HeatmapEventKind::Kill => Color::ORANGE,
+            HeatmapEventKind::Pickup => Color::CYAN,
+        }
+    }
+}
+
+/// A single recorded heatmap event.
+#[derive(HasSchema, Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct HeatmapSample {
+    pub kind: HeatmapEventKind,
+    pub pos: Vec2,
+}
+
+/// All of the heatmap samples recorded for a single map.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct MapHeatmap {
+    pub samples: SVec<HeatmapSample>,
+}
+
+impl MapHeatmap {
+    /// Count the samples of the given kind in each tile of the map.
+    pub fn bucket(&self, kind: HeatmapEventKind, tile_size: Vec2) -> HashMap<IVec2, u32> {
+        let mut buckets = HashMap::default();
+        for sample in self.samples.iter().filter(|x| x.kind == kind) {
+            let cell = (sample.pos / tile_size).floor().as_ivec2();
+            *buckets.entry(cell).or_insert(0) += 1;
+        }
+        buckets
+    }
+}
+
+/// Heatmaps for every map that has been played, stored in persistent [`Storage`].
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct HeatmapStats {
+    /// The heatmap for each map, by map name.
+    pub maps: SMap<Ustr, MapHeatmap>,
+}
+
+impl HeatmapStats {
+    /// Add the positions of the given match events to the heatmap for the given map.
+    pub fn record_events(&mut self, map_name: Ustr, events: &[MatchEvent]) {
+        if events.is_empty() {
+            return;
+        }
+
+        let mut heatmap = self.maps.get(&map_name).cloned().unwrap_or_default();
+        heatmap.samples.extend(events.iter().filter_map(|event| {
+            Some(HeatmapSample {
+                kind: HeatmapEventKind::from_match_event(event.kind)?,
+                pos: Vec2::from_array(event.pos),
+            })
+        }));
+        self.maps.insert(map_name, heatmap);
+    }
+}
+
+/// Session resource controlling which heatmap, if any, is drawn over the map.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct HeatmapOverlay {
+    /// The kind of event to show the heatmap for, or [`None`] to hide the overlay.
+    pub kind: Option<HeatmapEventKind>,
+    /// The entities used to render the overlay cells.
+    cell_ents: Vec<Entity>,
+    /// The kind of event that the overlay cells were created for.
+    rendered_kind: Option<HeatmapEventKind>,
+}
+
+/// Spawns and despawns the overlay cells when the [`HeatmapOverlay`] changes.
+fn render_heatmap_overlay(
+    mut entities: ResMut<Entities>,
+    mut overlay: ResMut<HeatmapOverlay>,
+    map: Res<LoadedMap>,
+    storage: Res<Storage>,
+    mut transforms: CompMut<Transform>,
+    mut paths: CompMut<Path2d>,
+) {
+    if overlay.kind == overlay.rendered_kind {
+        return;
+    }
+
+    for ent in overlay.cell_ents.drain(..) {
+        entities.kill(ent);
+    }
+    overlay.rendered_kind = overlay.kind;
+
+    let Some(kind) = overlay.kind else {
+        return;
+    };
+    let Some(heatmap) = storage
+        .get::<HeatmapStats>()
+        .and_then(|stats| stats.maps.get(&map.name).cloned())
+    else {
+        return;
+    };
+
+    let tile_size = map.tile_size;
+    let buckets = heatmap.bucket(kind, tile_size);
+    let max_count = buckets.values().copied().max().unwrap_or(1) as f32;
+
+    for (cell, count) in buckets {
+        let intensity = count as f32 / max_count;
+        let mut color = kind.color();
+        color.set_a(0.15 + intensity * 0.6);
+
+        let ent = entities.create();
+        let corner = cell.as_vec2() * tile_size;
+        transforms.insert(ent, Transform::from_translation(corner.extend(900.0)));
+        // Draw a single line through the middle of the cell that is as thick as the cell is tall,
+        // which fills the whole cell.
+        paths.insert(
+            ent,
+            Path2d {
+                color,
+                points: vec![
+                    vec2(0.0, tile_size.y / 2.0),
+                    vec2(tile_size.x, tile_size.y / 2.0),
+                ],
+                thickness: tile_size.y,
+                ..default()
+            },
+        );
+        overlay.cell_ents.push(ent);
+    }
+}
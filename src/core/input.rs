@@ -148,6 +148,22 @@ pub enum EditorInput {
     RenameMap {
         name: String,
     },
+    /// Set the symmetry mode that edits are mirrored with.
+    SetSymmetry {
+        /// The symmetry to mirror edits with, [`MapSymmetry::None`] to disable mirroring.
+        symmetry: MapSymmetry,
+    },
+    /// Set the link ID that wires an element to the other elements with the same link.
+    SetElementLink {
+        /// The element to link.
+        entity: Entity,
+        /// The link ID, or an empty string to unlink the element.
+        link: String,
+    },
+    /// Show or hide the wires between linked elements.
+    SetWiringOverlay {
+        visible: bool,
+    },
     RandomizeTiles {
         tile_layers: Vec<LocatedTileLayer>,
         element_layers: Vec<ElementLayer>,
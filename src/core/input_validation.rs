@@ -0,0 +1,175 @@
+//! Validation of the player inputs received in network matches.
+//!
+//! Every human player's controls are checked each frame before the rest of the simulation sees
+//! them. Move directions that aren't finite or are longer than a full stick tilt are clamped,
+//! button states that can't happen together are cleared, and button presses beyond what
+//! [`InputValidationMeta::max_presses_per_second`] allows are ignored, which takes the edge off
+//! of turbo macros.
+//!
+//! Each of these counts as a violation. Players with too many violations are flagged in the debug
+//! tools, and, if [`InputValidationMeta::drop_flagged`] is set, taken out of the match.
+//!
+//! The checks only look at the match inputs, so every client in a network match fixes up the same
+//! inputs in the same way and agrees on who has been flagged.
+
+use crate::prelude::*;
+
+/// The length of the window that button presses are counted over.
+const PRESS_WINDOW: Duration = Duration::from_secs(1);
+
+/// How far past a length of one a move direction may be before it's clamped, to allow for the
+/// rounding of the move directions sent over the network.
+const MOVE_DIRECTION_TOLERANCE: f32 = 0.01;
+
+/// Install this module.
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<InputViolations>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, validate_network_inputs);
+}
+
+/// Settings for validating the inputs of network players.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct InputValidationMeta {
+    /// The most button presses a player may make in a second. Presses beyond this are ignored.
+    pub max_presses_per_second: u32,
+    /// How many violations a player can make before they are flagged.
+    pub max_violations: u32,
+    /// Whether flagged players are taken out of the match.
+    pub drop_flagged: bool,
+}
+
+/// Session resource with the input violations of each player.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct InputViolations {
+    /// How many violations each player has made.
+    pub counts: [u32; MAX_PLAYERS as usize],
+    /// Whether each player has been flagged for making too many violations.
+    pub flagged: [bool; MAX_PLAYERS as usize],
+    /// How many button presses each player has made in the current window.
+    presses: [u32; MAX_PLAYERS as usize],
+    /// How long the current press window has been going on for.
+    window: Duration,
+}
+
+impl InputViolations {
+    /// Whether the given player has been flagged for making too many violations.
+    pub fn is_flagged(&self, player: PlayerIdx) -> bool {
+        self.flagged[player.0 as usize]
+    }
+}
+
+/// Fix up the controls of every human player in a network match, counting and flagging the
+/// players that sent controls that needed fixing.
+fn validate_network_inputs(
+    meta: Root<GameMeta>,
+    time: Res<Time>,
+    entities: Res<Entities>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    mut player_inputs: ResMut<MatchInputs>,
+    mut violations: ResMut<InputViolations>,
+    mut commands: Commands,
+    #[cfg(not(target_arch = "wasm32"))] syncing_info: Option<Res<SyncingInfo>>,
+) {
+    #[allow(unused_mut)]
+    let mut is_online = false;
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        is_online = syncing_info.is_some_and(|x| x.is_online());
+    }
+    if !is_online {
+        return;
+    }
+    let validation = &meta.core.input_validation;
+
+    violations.window += time.delta();
+    if violations.window >= PRESS_WINDOW {
+        violations.window = Duration::ZERO;
+        violations.presses = default();
+    }
+
+    let mut newly_flagged = Vec::new();
+    for (i, input) in player_inputs.players.iter_mut().enumerate() {
+        if !input.active || input.is_ai {
+            continue;
+        }
+        let control = &mut input.control;
+        let mut violated = false;
+
+        // Move direction out of range
+        if !control.move_direction.is_finite() {
+            control.move_direction = Vec2::ZERO;
+            violated = true;
+        } else if control.move_direction.length() > 1.0 + MOVE_DIRECTION_TOLERANCE {
+            control.move_direction = control.move_direction.clamp_length_max(1.0);
+            violated = true;
+        }
+
+        // Buttons that were just pressed without being held
+        for (pressed, just_pressed) in [
+            (control.jump_pressed, &mut control.jump_just_pressed),
+            (control.shoot_pressed, &mut control.shoot_just_pressed),
+            (control.grab_pressed, &mut control.grab_just_pressed),
+            (control.slide_pressed, &mut control.slide_just_pressed),
+            (control.ragdoll_pressed, &mut control.ragdoll_just_pressed),
+        ] {
+            if *just_pressed && !pressed {
+                *just_pressed = false;
+                violated = true;
+            }
+        }
+        if control.just_moved && control.move_direction == Vec2::ZERO {
+            control.just_moved = false;
+            violated = true;
+        }
+
+        // Pressing buttons faster than anybody could
+        let presses = [
+            control.jump_just_pressed,
+            control.shoot_just_pressed,
+            control.grab_just_pressed,
+            control.slide_just_pressed,
+            control.ragdoll_just_pressed,
+        ]
+        .into_iter()
+        .filter(|x| *x)
+        .count() as u32;
+        violations.presses[i] += presses;
+        if presses > 0 && violations.presses[i] > validation.max_presses_per_second {
+            control.jump_just_pressed = false;
+            control.shoot_just_pressed = false;
+            control.grab_just_pressed = false;
+            control.slide_just_pressed = false;
+            control.ragdoll_just_pressed = false;
+            violated = true;
+        }
+
+        if violated {
+            violations.counts[i] += 1;
+            if !violations.flagged[i] && violations.counts[i] >= validation.max_violations {
+                warn!("Player {} flagged for sending invalid inputs", i + 1);
+                violations.flagged[i] = true;
+                newly_flagged.push(i as u32);
+            }
+        }
+    }
+
+    if !validation.drop_flagged {
+        return;
+    }
+    for (i, input) in player_inputs.players.iter_mut().enumerate() {
+        if violations.flagged[i] {
+            input.active = false;
+        }
+    }
+    // Players are only killed when they are flagged, since they stay inactive after that and
+    // don't come back in later rounds
+    for (player_ent, player_idx) in entities.iter_with(&player_indexes) {
+        if newly_flagged.contains(&player_idx.0) && !killed_players.contains(player_ent) {
+            commands.add(PlayerCommand::kill(player_ent, None));
+        }
+    }
+}
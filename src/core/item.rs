@@ -10,12 +10,23 @@ pub fn install(session: &mut SessionBuilder) {
     ItemGrab::register_schema();
     DropItem::register_schema();
     ItemUsed::register_schema();
+    ItemThrown::register_schema();
+    CatchAttempt::register_schema();
+    HoldToUse::register_schema();
+    AutoPickupIgnore::register_schema();
+    IdleDespawn::register_schema();
+    BlastLaunched::register_schema();
 
     session
         .stages
+        .add_system_to_stage(CoreStage::PreUpdate, catch_thrown_items)
+        .add_system_to_stage(CoreStage::PreUpdate, auto_pickup_items)
         .add_system_to_stage(CoreStage::Last, grab_items)
+        .add_system_to_stage(CoreStage::Last, throw_released_items)
         .add_system_to_stage(CoreStage::Last, drop_items)
-        .add_system_to_stage(CoreStage::Last, throw_dropped_items);
+        .add_system_to_stage(CoreStage::Last, throw_dropped_items)
+        .add_system_to_stage(CoreStage::PostUpdate, despawn_idle_items)
+        .add_system_to_stage(CoreStage::PostUpdate, launch_items_in_blasts);
 }
 
 /// Marker component for items.
@@ -105,6 +116,48 @@ pub struct ItemUsed {
     pub owner: Entity,
 }
 
+/// Push the player holding a weapon when it fires.
+///
+/// The `kickback` replaces the holder's horizontal velocity, pushing them straight back. The
+/// `recoil` is then added on top of their velocity, with `x` pointing away from where they are
+/// facing and `y` pointing up, so strong recoil can carry players across gaps or up to ledges.
+pub fn apply_recoil(body: &mut KinematicBody, facing_left: bool, kickback: f32, recoil: Vec2) {
+    let backwards = if facing_left { 1.0 } else { -1.0 };
+    body.velocity.x = backwards * kickback;
+    body.velocity += vec2(backwards * recoil.x, recoil.y);
+    if recoil.y > 0.0 {
+        body.is_on_ground = false;
+    }
+}
+
+/// Component for items that are used by holding the use button, like grenades that are cooked in
+/// hand before being thrown.
+///
+/// Once the holder presses the use button with the item in hand, letting go of it throws the item.
+#[derive(Clone, Copy, HasSchema, Default)]
+#[repr(C)]
+pub struct HoldToUse {
+    /// Whether the holder is holding the use button down.
+    pub held: bool,
+}
+
+/// Component added to items while they are flying through the air after being thrown.
+#[derive(Clone, Copy, HasSchema, Default)]
+pub struct ItemThrown {
+    /// The player that threw the item
+    pub thrower: Entity,
+}
+
+/// Component added to players with an empty inventory that just pressed grab, giving them a short
+/// window in which they will catch any thrown item that hits them.
+#[derive(Clone, HasSchema, Default, Deref, DerefMut)]
+pub struct CatchAttempt(pub Timer);
+
+/// Component added to dropped items that counts down until the item is sent back to its spawner,
+/// unless someone picks it up first.
+#[derive(Clone, HasSchema, Default, Deref, DerefMut)]
+pub struct IdleDespawn(pub Timer);
+
 /// Component defining the grab settings when an item is grabbed.
 ///
 /// Mainly handled by the [`grab_items`] system which consumes the
@@ -131,14 +184,44 @@ pub fn drop_items(
     }
 }
 
+/// Throw [`HoldToUse`] items when their holder lets go of the use button.
+pub fn throw_released_items(
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    player_inventories: PlayerInventories,
+    mut hold_to_use: CompMut<HoldToUse>,
+    mut drop_items: CompMut<DropItem>,
+) {
+    for Inv { player, inventory } in player_inventories.iter().flatten() {
+        let Some(hold) = hold_to_use.get_mut(*inventory) else {
+            continue;
+        };
+        let Some(idx) = player_indexes.get(*player) else {
+            continue;
+        };
+        let control = &player_inputs.players[idx.0 as usize].control;
+
+        if control.shoot_just_pressed {
+            hold.held = true;
+        } else if hold.held && !control.shoot_pressed {
+            hold.held = false;
+            drop_items.insert(*inventory, DropItem);
+        }
+    }
+}
+
 pub fn grab_items(
     entities: Res<Entities>,
     item_grab: Comp<ItemGrab>,
     items: Comp<Item>,
     mut items_grabbed: CompMut<ItemGrabbed>,
+    mut idle_despawns: CompMut<IdleDespawn>,
     mut bodies: CompMut<KinematicBody>,
     mut attachments: CompMut<PlayerBodyAttachment>,
     mut player_layers: CompMut<PlayerLayers>,
+    transforms: Comp<Transform>,
+    player_indexes: Comp<PlayerIdx>,
+    mut event_log: ResMut<MatchEventLog>,
 ) {
     for (entity, (_item, item_grab)) in entities.iter_with((&items, &item_grab)) {
         let ItemGrab {
@@ -148,7 +231,16 @@ pub fn grab_items(
         } = *item_grab;
 
         if let Some(ItemGrabbed { player }) = items_grabbed.remove(entity) {
+            if let Some(transform) = transforms.get(entity) {
+                event_log.record(
+                    MatchEventKind::Pickup,
+                    player_indexes.get(player).copied(),
+                    transform.translation.xy(),
+                );
+            }
+
             player_layers.get_mut(player).unwrap().fin_anim = fin_anim;
+            idle_despawns.remove(entity);
 
             if let Some(body) = bodies.get_mut(entity) {
                 body.is_deactivated = true
@@ -279,8 +371,13 @@ pub fn throw_dropped_items(
     mut sprites: CompMut<AtlasSprite>,
     mut transforms: CompMut<Transform>,
     item_spawners: Comp<DehydrateOutOfBounds>,
+    game_meta: Root<GameMeta>,
+    assets: Res<AssetServer>,
+    element_handles: Comp<ElementHandle>,
+    mut idle_despawns: CompMut<IdleDespawn>,
     map_layers: Comp<SpawnedMapLayerMeta>,
     player_spawnwers: Comp<PlayerSpawner>,
+    mut items_thrown: CompMut<ItemThrown>,
     mut commands: Commands,
 ) {
     for (entity, (_items, item_throw, transform)) in
@@ -325,6 +422,281 @@ pub fn throw_dropped_items(
 
                 body.is_deactivated = false;
             }
+
+            if throw_velocity != Vec2::ZERO {
+                items_thrown.insert(entity, ItemThrown { thrower: player });
+            }
+
+            // Only items that can go back to a spawner are cleaned up
+            if item_spawners.contains(entity) {
+                let despawn_time = element_handles
+                    .get(entity)
+                    .map(|handle| assets.get(handle.0).idle_despawn_time)
+                    .filter(|time| !time.is_zero())
+                    .unwrap_or(game_meta.core.config.item_idle_despawn_time);
+                if !despawn_time.is_zero() {
+                    idle_despawns.insert(
+                        entity,
+                        IdleDespawn(Timer::new(despawn_time, TimerMode::Once)),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Let players catch thrown items that hit them shortly after they pressed grab.
+///
+/// Caught items go straight into the player's inventory, so a lit kick bomb that is caught keeps
+/// its fuse running in the catcher's hands.
+pub fn catch_thrown_items(
+    entities: Res<Entities>,
+    time: Res<Time>,
+    game_meta: Root<GameMeta>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    assets: Res<AssetServer>,
+    bodies: Comp<KinematicBody>,
+    collision_world: CollisionWorld,
+    inventories: Comp<Inventory>,
+    mut items_thrown: CompMut<ItemThrown>,
+    mut catch_attempts: CompMut<CatchAttempt>,
+    mut audio_center: ResMut<AudioCenter>,
+    mut commands: Commands,
+) {
+    let catch_window = game_meta.core.config.catch_window;
+
+    // Start and expire catch attempts
+    for (player_ent, (player_idx, inventory)) in entities.iter_with((&player_indexes, &inventories))
+    {
+        let control = &player_inputs.players[player_idx.0 as usize].control;
+        if control.grab_just_pressed && inventory.is_none() && !catch_window.is_zero() {
+            catch_attempts.insert(
+                player_ent,
+                CatchAttempt(Timer::new(catch_window, TimerMode::Once)),
+            );
+        } else if let Some(attempt) = catch_attempts.get_mut(player_ent) {
+            attempt.tick(time.delta());
+            if attempt.finished() {
+                catch_attempts.remove(player_ent);
+            }
+        }
+    }
+
+    let thrown_items = entities
+        .iter_with((&items_thrown, &bodies))
+        .map(|(ent, (thrown, body))| (ent, *thrown, body.is_on_ground))
+        .collect::<Vec<_>>();
+    let mut catchers = Vec::new();
+    for (item_ent, ItemThrown { thrower }, is_on_ground) in thrown_items {
+        // Items can only be caught while they are still in the air
+        if is_on_ground {
+            items_thrown.remove(item_ent);
+            continue;
+        }
+
+        let catcher = collision_world
+            .actor_collisions_filtered(item_ent, |ent| {
+                ent != thrower
+                    && !catchers.contains(&ent)
+                    && catch_attempts.contains(ent)
+                    && !killed_players.contains(ent)
+                    && inventories.get(ent).is_some_and(|inv| inv.is_none())
+            })
+            .into_iter()
+            .next();
+
+        if let Some(player_ent) = catcher {
+            items_thrown.remove(item_ent);
+            catch_attempts.remove(player_ent);
+            catchers.push(player_ent);
+            commands.add(PlayerCommand::set_inventory(player_ent, Some(item_ent)));
+
+            let player_idx = player_indexes.get(player_ent).unwrap();
+            let meta = assets.get(player_inputs.players[player_idx.0 as usize].selected_player);
+            audio_center.play_sound(meta.sounds.grab, meta.sounds.grab_volume);
+        }
+    }
+}
+
+/// Component added to players by [`auto_pickup_items`] to remember the item they were last holding,
+/// so that it isn't pulled straight back into their hands after they drop or throw it.
+#[derive(Clone, Copy, HasSchema, Default, Deref, DerefMut)]
+pub struct AutoPickupIgnore(pub Option<Entity>);
+
+/// Pull nearby items toward empty-handed players, and pick them up when they touch, if the auto
+/// pickup mutator is enabled.
+///
+/// An item that a player lets go of is ignored by that player until it leaves their pickup radius.
+pub fn auto_pickup_items(
+    entities: Res<Entities>,
+    time: Res<Time>,
+    game_meta: Root<GameMeta>,
+    mutators: Res<Mutators>,
+    assets: Res<AssetServer>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    items: Comp<Item>,
+    stolen_items: Comp<StolenItem>,
+    element_handles: Comp<ElementHandle>,
+    inventories: Comp<Inventory>,
+    transforms: Comp<Transform>,
+    collision_world: CollisionWorld,
+    mut ignored: CompMut<AutoPickupIgnore>,
+    mut bodies: CompMut<KinematicBody>,
+    mut audio_center: ResMut<AudioCenter>,
+    mut commands: Commands,
+) {
+    if !mutators.auto_pickup {
+        return;
+    }
+    let meta = &game_meta.core.mutators;
+    let pull = meta.auto_pickup_speed * time.delta_seconds();
+
+    let held_items = entities
+        .iter_with(&inventories)
+        .filter_map(|(_ent, inventory)| inventory.0)
+        .collect::<Vec<_>>();
+    let mut free_items = entities
+        .iter_with((&items, &transforms))
+        .filter(|(ent, _)| !held_items.contains(ent) && !stolen_items.contains(*ent))
+        .map(|(ent, (_item, transform))| {
+            let radius = element_handles
+                .get(ent)
+                .map(|handle| assets.get(handle.0).auto_pickup_radius)
+                .filter(|radius| *radius > 0.0)
+                .unwrap_or(meta.auto_pickup_radius);
+            (ent, transform.translation.xy(), radius)
+        })
+        .collect::<Vec<_>>();
+
+    for (player_ent, (player_idx, inventory, transform)) in
+        entities.iter_with((&player_indexes, &inventories, &transforms))
+    {
+        if killed_players.contains(player_ent) {
+            continue;
+        }
+        if let Some(item) = inventory.0 {
+            ignored.insert(player_ent, AutoPickupIgnore(Some(item)));
+            continue;
+        }
+        let player_pos = transform.translation.xy();
+        let ignore = ignored.get_mut(player_ent).and_then(|x| x.0);
+
+        let mut picked_up = None;
+        for (item_ent, item_pos, radius) in &free_items {
+            let offset = player_pos - *item_pos;
+            if offset.length() > *radius {
+                if ignore == Some(*item_ent) {
+                    ignored.remove(player_ent);
+                }
+                continue;
+            }
+            if ignore == Some(*item_ent) {
+                continue;
+            }
+
+            if collision_world
+                .actor_collisions(player_ent)
+                .contains(item_ent)
+            {
+                picked_up = Some(*item_ent);
+                break;
+            }
+
+            if let Some(body) = bodies.get_mut(*item_ent) {
+                body.velocity += offset.normalize_or_zero() * pull;
+            }
+        }
+
+        if let Some(item_ent) = picked_up {
+            // Each item can only go to one player
+            free_items.retain(|(ent, ..)| *ent != item_ent);
+            commands.add(PlayerCommand::set_inventory(player_ent, Some(item_ent)));
+
+            let meta = assets.get(player_inputs.players[player_idx.0 as usize].selected_player);
+            audio_center.play_sound(meta.sounds.grab, meta.sounds.grab_volume);
+        }
+    }
+}
+
+/// Send dropped items that have been lying around too long back to their spawners, the same way
+/// that items falling out of the map are.
+pub fn despawn_idle_items(
+    entities: Res<Entities>,
+    time: Res<Time>,
+    spawners: Comp<DehydrateOutOfBounds>,
+    mut idle_despawns: CompMut<IdleDespawn>,
+    mut hydrated: CompMut<MapElementHydrated>,
+    mut commands: Commands,
+) {
+    for (item_ent, (idle_despawn, spawner)) in entities.iter_with((&mut idle_despawns, &spawners)) {
+        idle_despawn.tick(time.delta());
+        if idle_despawn.finished() {
+            hydrated.remove(**spawner);
+            commands.add(move |mut entities: ResMutInit<Entities>| {
+                entities.kill(item_ent);
+            });
+        }
+    }
+}
+
+/// Marker for [blast][DamageRegion::blast] damage regions that have already launched the items
+/// lying in them, so that each explosion only launches items once.
+#[derive(Clone, Copy, HasSchema, Default)]
+pub struct BlastLaunched;
+
+/// Launch the idle items lying in new explosions away from them.
+///
+/// Only the items' velocity is changed, so lit bombs keep their fuse running while they fly, which
+/// can carry them into other explosives.
+pub fn launch_items_in_blasts(
+    entities: Res<Entities>,
+    game_meta: Root<GameMeta>,
+    items: Comp<Item>,
+    stolen_items: Comp<StolenItem>,
+    player_inventories: PlayerInventories,
+    damage_regions: Comp<DamageRegion>,
+    transforms: Comp<Transform>,
+    mut launched: CompMut<BlastLaunched>,
+    mut bodies: CompMut<KinematicBody>,
+) {
+    let blasts = entities
+        .iter_with((&damage_regions, &transforms))
+        .filter(|(ent, (region, _))| region.blast && !launched.contains(*ent))
+        .map(|(ent, (region, transform))| (ent, region.collider_rect(transform.translation)))
+        .collect::<Vec<_>>();
+    if blasts.is_empty() {
+        return;
+    }
+
+    let speed = game_meta.core.physics.blast_launch_speed;
+    for (item_ent, (_, transform)) in entities.iter_with((&items, &transforms)) {
+        if player_inventories.find_item(item_ent).is_some() || stolen_items.contains(item_ent) {
+            continue;
         }
+        let Some(body) = bodies.get_mut(item_ent) else {
+            continue;
+        };
+        if body.is_deactivated {
+            continue;
+        }
+        let item_rect = body.bounding_box(*transform);
+        let Some((_, blast_rect)) = blasts.iter().find(|(_, x)| x.overlaps(&item_rect)) else {
+            continue;
+        };
+
+        // Launch the item away from the center of the blast, always a bit upwards
+        let impulse = RadialImpulse {
+            min_lift: 0.45,
+            ..RadialImpulse::new(blast_rect.center(), speed)
+        };
+        body.velocity = impulse.velocity(item_rect.center());
+    }
+
+    for (blast, _) in blasts {
+        launched.insert(blast, BlastLaunched);
     }
 }
@@ -0,0 +1,155 @@
+//! Lag compensation for hitscan weapons.
+//!
+//! Every client in a network match runs the same simulation, but a remote player aims at where
+//! they saw their target, which is behind the rest of the match by their latency. When the host
+//! enables lag compensation, hitscan shots are checked against where their targets were that many
+//! frames ago instead, up to a bounded number of frames.
+//!
+//! The latency of each player is sent along with their inputs, so every client rewinds by the same
+//! amount and the simulation stays deterministic.
+
+use crate::prelude::*;
+
+use super::utils::Rect;
+
+/// Install this module.
+pub fn install(session: &mut SessionBuilder, lag_compensation: LagCompensation) {
+    session.insert_resource(lag_compensation);
+    session
+        .stages
+        .add_system_to_stage(CoreStage::Last, record_position_history);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, measure_local_latency);
+}
+
+/// The lag compensation settings for a match, picked by the host.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct LagCompensation {
+    /// The furthest back that targets may be rewound, in frames. Zero disables lag compensation.
+    pub max_rewind_frames: u32,
+}
+
+/// The hitboxes of a player over the last few frames, newest first.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct PositionHistory(VecDeque<Rect>);
+
+impl PositionHistory {
+    /// Get the hitbox of the player the given number of frames ago, if it was recorded.
+    pub fn get(&self, frames_ago: usize) -> Option<Rect> {
+        self.0.get(frames_ago).copied()
+    }
+}
+
+/// A player hit by a hitscan shot.
+#[derive(Clone, Copy, Debug)]
+pub struct HitscanHit {
+    pub player: Entity,
+    /// Where the shot hit the player's hitbox.
+    pub point: Vec2,
+}
+
+fn record_position_history(
+    entities: Res<Entities>,
+    lag_compensation: Res<LagCompensation>,
+    player_indexes: Comp<PlayerIdx>,
+    transforms: Comp<Transform>,
+    bodies: Comp<KinematicBody>,
+    mut histories: CompMut<PositionHistory>,
+) {
+    if lag_compensation.max_rewind_frames == 0 {
+        return;
+    }
+
+    for (player_ent, (_idx, transform, body)) in
+        entities.iter_with((&player_indexes, &transforms, &bodies))
+    {
+        if !histories.contains(player_ent) {
+            histories.insert(player_ent, default());
+        }
+        let history = histories.get_mut(player_ent).unwrap();
+        history.0.push_front(body.bounding_box(*transform));
+        history
+            .0
+            .truncate(lag_compensation.max_rewind_frames as usize + 1);
+    }
+}
+
+/// Keep track of how far behind the confirmed frame the local player is, so that it can be sent
+/// with their inputs.
+#[cfg(not(target_arch = "wasm32"))]
+fn measure_local_latency(syncing_info: Option<Res<SyncingInfo>>) {
+    let latency = syncing_info
+        .map(|x| (x.current_frame() - x.last_confirmed_frame()).clamp(0, u8::MAX as i32) as u8)
+        .unwrap_or_default();
+    crate::input::LOCAL_LATENCY_FRAMES.store(latency, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Find the first player hit by a hitscan shot fired by `shooter` from `origin` towards
+/// `direction`, within `range` pixels.
+///
+/// If lag compensation is enabled, targets are checked against where they were when the shooter
+/// saw them.
+///
+/// The bodies are taken as a plain store, so that weapons that also push their holder around can
+/// pass them in mutably borrowed.
+pub fn hitscan_players(
+    shooter: Entity,
+    origin: Vec2,
+    direction: Vec2,
+    range: f32,
+    entities: &Entities,
+    lag_compensation: &LagCompensation,
+    player_inputs: &MatchInputs,
+    player_indexes: &Comp<PlayerIdx>,
+    killed_players: &Comp<PlayerKilled>,
+    invincibles: &Comp<Invincibility>,
+    transforms: &Comp<Transform>,
+    bodies: &ComponentStore<KinematicBody>,
+    histories: &Comp<PositionHistory>,
+) -> Option<HitscanHit> {
+    let direction = direction.normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return None;
+    }
+
+    let rewind_frames = player_indexes
+        .get(shooter)
+        .map(|idx| player_inputs.players[idx.0 as usize].control.latency_frames as u32)
+        .unwrap_or_default()
+        .min(lag_compensation.max_rewind_frames) as usize;
+
+    entities
+        .iter_with((player_indexes, transforms))
+        .filter(|(ent, _)| {
+            *ent != shooter && !killed_players.contains(*ent) && !invincibles.contains(*ent)
+        })
+        .filter_map(|(ent, (_idx, transform))| {
+            let body = bodies.get(ent)?;
+            let hitbox = histories
+                .get(ent)
+                .and_then(|history| history.get(rewind_frames))
+                .unwrap_or_else(|| body.bounding_box(*transform));
+            let distance = ray_rect_distance(origin, direction, &hitbox)?;
+            (distance <= range).then_some((ent, distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(player, distance)| HitscanHit {
+            player,
+            point: origin + direction * distance,
+        })
+}
+
+/// Get the distance along a ray to where it enters the given rectangle, if it hits it at all.
+fn ray_rect_distance(origin: Vec2, direction: Vec2, rect: &Rect) -> Option<f32> {
+    let inv = direction.recip();
+    let t1 = (rect.min - origin) * inv;
+    let t2 = (rect.max - origin) * inv;
+    let t_enter = t1.min(t2).max_element();
+    let t_exit = t1.max(t2).min_element();
+
+    (t_exit >= t_enter.max(0.0)).then_some(t_enter.max(0.0))
+}
@@ -61,6 +61,11 @@ impl Invincibility {
     pub fn new(duration: Duration) -> Self {
         Self(Timer::new(duration, TimerMode::Once))
     }
+
+    /// How long the entity has been invincible for.
+    pub fn elapsed(&self) -> Duration {
+        self.0.elapsed()
+    }
 }
 
 fn invincibility(
@@ -45,6 +45,9 @@ pub struct SpawnedMapMeta {
     pub grid_size: UVec2,
     pub tile_size: Vec2,
     pub layer_names: Arc<[Ustr]>,
+    pub bounds: MapBoundsMeta,
+    pub weather: MapWeatherMeta,
+    pub events: Arc<[MapEventMeta]>,
 }
 
 impl Default for SpawnedMapMeta {
@@ -56,6 +59,9 @@ impl Default for SpawnedMapMeta {
             grid_size: default(),
             tile_size: default(),
             layer_names: Arc::new([]),
+            bounds: default(),
+            weather: default(),
+            events: Arc::new([]),
         }
     }
 }
@@ -154,7 +160,11 @@ fn spawn_map(
     mut tile_layers: CompMut<TileLayer>,
     mut transforms: CompMut<Transform>,
     mut element_handles: CompMut<ElementHandle>,
+    mut spawner_waves: CompMut<SpawnerWaves>,
+    mut spawner_wave_states: CompMut<SpawnerWaveState>,
+    mut element_links: CompMut<ElementLink>,
     mut tile_collisions: CompMut<TileCollisionKind>,
+    mut tile_materials: CompMut<TileMaterial>,
     mut tile_dynamic_colliders: CompMut<TileDynamicCollider>,
     mut parallax_bg_sprites: CompMut<ParallaxBackgroundSprite>,
     mut sprites: CompMut<Sprite>,
@@ -177,6 +187,9 @@ fn spawn_map(
         grid_size: map.grid_size,
         tile_size: map.tile_size,
         layer_names: map.layers.iter().map(|x| x.id).collect(),
+        bounds: map.bounds,
+        weather: map.weather,
+        events: map.events.iter().copied().collect(),
     };
 
     // Spawn the camera
@@ -202,15 +215,36 @@ fn spawn_map(
 
     // Spawn parallax backgrounds
     for layer in &map.background.layers {
-        for i in -1..=1 {
+        let copies = if layer.single { 0..=0 } else { -1..=1 };
+        for i in copies {
             let ent = entities.create();
-            sprites.insert(
-                ent,
-                Sprite {
-                    image: layer.image,
-                    ..default()
-                },
-            );
+            if let Set(atlas) = layer.atlas {
+                let frames = layer.frames.clone();
+                let fps = layer.fps;
+                commands.add(
+                    move |mut atlas_sprites: CompMut<AtlasSprite>,
+                          mut animated_sprites: CompMut<AnimatedSprite>| {
+                        atlas_sprites.insert(ent, AtlasSprite::new(atlas));
+                        animated_sprites.insert(
+                            ent,
+                            AnimatedSprite {
+                                frames: frames.clone(),
+                                fps,
+                                repeat: true,
+                                ..default()
+                            },
+                        );
+                    },
+                );
+            } else {
+                sprites.insert(
+                    ent,
+                    Sprite {
+                        image: layer.image,
+                        ..default()
+                    },
+                );
+            }
             transforms.insert(ent, default());
             parallax_bg_sprites.insert(
                 ent,
@@ -250,6 +284,9 @@ fn spawn_map(
                 if tile_meta.collision != TileCollisionKind::Empty {
                     tile_collisions.insert(tile_ent, tile_meta.collision);
                 }
+                if !tile_meta.material.is_default() {
+                    tile_materials.insert(tile_ent, tile_meta.material);
+                }
 
                 let atlas = assets.get(tilemap);
                 let optional_tile_colliders = &atlas.tile_collision;
@@ -298,6 +335,13 @@ fn spawn_map(
                 Transform::from_translation(element_meta.pos.extend(layer_z)),
             );
             element_handles.insert(element_ent, ElementHandle(element_meta.element));
+            if !element_meta.waves.is_default() {
+                spawner_waves.insert(element_ent, element_meta.waves);
+                spawner_wave_states.insert(element_ent, default());
+            }
+            if !element_meta.link.is_empty() {
+                element_links.insert(element_ent, ElementLink(element_meta.link));
+            }
         }
     }
 
@@ -323,7 +367,7 @@ fn handle_out_of_bounds_players(
 }
 
 /// Helper method to create a navigation graph from the map metadata.
-fn create_nav_graph(meta: &MapMeta) -> Arc<NavGraphInner> {
+pub fn create_nav_graph(meta: &MapMeta) -> Arc<NavGraphInner> {
     // Load the navigation graph
     let mut graph = NavGraphInner::default();
 
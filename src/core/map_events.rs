@@ -0,0 +1,85 @@
+//! Timed map events, such as floodgates that open half way through a round, or a boss that shows
+//! up after a minute.
+//!
+//! A map lists its [`MapEventMeta`]s in its metadata, and the [`MapEventScheduler`] runs each of
+//! them once the round has gone on for long enough. The scheduler counts time in fixed simulation
+//! steps and is part of the session state, so it is rolled back with everything else, and every
+//! peer runs each event on the same frame.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<MapEventScheduler>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, run_map_events);
+}
+
+/// Session resource that runs the map's events as the round goes on.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct MapEventScheduler {
+    /// How long the round has been going for.
+    pub elapsed: Duration,
+    /// How many of the map's events, in order of time, have been run.
+    pub done: usize,
+    /// The links that have been toggled an odd number of times by events.
+    pub toggled_links: Vec<Ustr>,
+}
+
+impl MapEventScheduler {
+    /// Whether the elements with the given link have been flipped by the map's events.
+    pub fn is_link_toggled(&self, link: Ustr) -> bool {
+        self.toggled_links.contains(&link)
+    }
+}
+
+/// Run the map events whose time has come.
+fn run_map_events(
+    time: Res<Time>,
+    map_spawned: ResInit<MapSpawned>,
+    spawned_map_meta: ResInit<SpawnedMapMeta>,
+    mut scheduler: ResMut<MapEventScheduler>,
+    mut entities: ResMutInit<Entities>,
+    mut transforms: CompMut<Transform>,
+    mut spawn_requests: CompMut<SpawnElementRequest>,
+) {
+    if !map_spawned.0 {
+        return;
+    }
+    scheduler.elapsed += time.delta();
+
+    // Events at the same time run in the order they are listed in
+    let mut events = spawned_map_meta.events.iter().collect::<Vec<_>>();
+    events.sort_by_key(|event| event.time);
+
+    for event in events.into_iter().skip(scheduler.done) {
+        if event.time > scheduler.elapsed {
+            break;
+        }
+        scheduler.done += 1;
+
+        match event.action {
+            MapEventAction::ToggleLink => {
+                if let Some(idx) = scheduler
+                    .toggled_links
+                    .iter()
+                    .position(|x| *x == event.link)
+                {
+                    scheduler.toggled_links.remove(idx);
+                } else {
+                    scheduler.toggled_links.push(event.link);
+                }
+            }
+            MapEventAction::SpawnElement => {
+                let ent = entities.create();
+                transforms.insert(ent, Transform::from_translation(event.pos.extend(0.0)));
+                spawn_requests.insert(
+                    ent,
+                    SpawnElementRequest {
+                        name: event.element,
+                    },
+                );
+            }
+        }
+    }
+}
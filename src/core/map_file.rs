@@ -0,0 +1,643 @@
+//! Saving and loading maps made in the editor.
+//!
+//! Map files are YAML (or JSON, which is read the same way) documents that look like this:
+//!
+//! ```yaml
+//! # The version of the format the file was written with. Older files are migrated when loaded.
+//! version: 2
+//! name: My Map
+//! # The name of the map whose parallax background is used.
+//! background: Level 1
+//! background_color: [91, 87, 114, 255]
+//! # The size of the map in tiles, and the size of the tiles in pixels.
+//! grid_size: [27, 21]
+//! tile_size: [32.0, 32.0]
+//! # How far past each edge of the map players and items can go before they are killed. May be
+//! # left out to use the defaults, and any side may be left out on its own.
+//! bounds:
+//!   left: 500.0
+//!   right: 500.0
+//!   bottom: 500.0
+//!   top: .inf
+//! # The rain or snow falling over the map. May be left out for clear weather.
+//! weather:
+//!   kind: rain # Or `snow`, defaults to `clear`
+//!   density: 1.5
+//!   wind: -40.0
+//!   gust_interval: 6s
+//!   gust_strength: 120.0
+//!   music_low_pass: 2000.0
+//! layers:
+//!   - id: main layer
+//!     # The index of the tileset in the `map_tilesets` of the core metadata, followed by the
+//!     # tilesets of each asset pack. May be left out for layers without tiles.
+//!     tileset: 0
+//!     tiles:
+//!       - pos: [3, 0]
+//!         idx: 12
+//!         collision: Solid # Or `Empty` or `JumpThrough`, defaults to `Empty`
+//!         material: Ice # Or `Rubber`, may be left out for normal tiles
+//!     elements:
+//!       # Elements are referred to by the name in their element metadata.
+//!       - pos: [432.0, 362.0]
+//!         element: Sproinger
+//!       # Elements with the same link are wired together, like a switch and the doors it opens.
+//!       - pos: [200.0, 104.0]
+//!         element: Switch
+//!         link: gate
+//!       - pos: [320.0, 136.0]
+//!         element: Door
+//!         link: gate
+//! # Things that happen at set times in the round. May be left out.
+//! events:
+//!   # Open the doors linked to `gate`, as if a switch was flipped.
+//!   - time: 30s
+//!     action: toggle_link
+//!     link: gate
+//!   - time: 1m
+//!     action: spawn_element
+//!     element: Crab
+//!     pos: [432.0, 362.0]
+//! ```
+//!
+//! Unlike map assets, map files don't refer to other assets by path, so that they can be saved
+//! from a running match and shared between installs. When a file is loaded, every field and every
+//! element name is checked, and errors point to the exact place in the file that is wrong.
+//!
+//! Camera overrides aren't part of the format yet, so maps loaded from files use the camera
+//! settings from the [`GameMeta`].
+
+use serde_yaml::{Mapping, Value};
+
+use crate::{prelude::*, PackMeta};
+
+/// The version of the map file format that is written by [`MapFile::to_yaml`].
+pub const MAP_FORMAT_VERSION: u32 = 2;
+
+/// Migrations from each older version of the format to the next one, starting at version `0`.
+///
+/// A file of version `n` is brought up to date by running every migration from index `n` onwards.
+const MIGRATIONS: [fn(&mut Mapping); MAP_FORMAT_VERSION as usize] =
+    [migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Version `0` files are the drafts written before the format was versioned. They are identical
+/// to version `1`, apart from not having a version field.
+fn migrate_v0_to_v1(_file: &mut Mapping) {}
+
+/// Version `1` files give the time between gusts of wind as a number of seconds, where version `2`
+/// files use a duration like the event times do.
+fn migrate_v1_to_v2(file: &mut Mapping) {
+    let Some(Value::Mapping(weather)) = file.get_mut("weather") else {
+        return;
+    };
+    if let Some(seconds) = weather.get("gust_interval").and_then(Value::as_f64) {
+        let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+        weather.insert("gust_interval".into(), format!("{millis}ms").into());
+    }
+}
+
+/// An error loading or saving a map file.
+#[derive(thiserror::Error, Debug)]
+pub enum MapFileError {
+    #[error("Could not read or write map file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid map file: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("Map file must be a mapping of fields")]
+    NotAMapping,
+    #[error("version: expected a whole number")]
+    InvalidVersion,
+    #[error(
+        "Map file version {0} is newer than the newest supported version {MAP_FORMAT_VERSION}"
+    )]
+    UnsupportedVersion(u64),
+    #[error("{path}: unknown field `{field}`, expected one of: {expected}")]
+    UnknownField {
+        path: String,
+        field: String,
+        expected: String,
+    },
+    #[error("{path}: unknown element `{name}`")]
+    UnknownElement { path: String, name: String },
+    #[error("{path}: unknown tileset {idx}")]
+    UnknownTileset { path: String, idx: u32 },
+    #[error("background: unknown map `{0}`")]
+    UnknownBackground(String),
+    #[error("There is no spawned map to save")]
+    NoMap,
+    #[error("Map has a background that doesn't belong to any map")]
+    UnregisteredBackground,
+    #[error("Layer {layer}: element is not registered in the game or an asset pack")]
+    UnregisteredElement { layer: u32 },
+    #[error("Layer {layer}: tileset is not registered in the game or an asset pack")]
+    UnregisteredTileset { layer: u32 },
+}
+
+/// A map in the map file format.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MapFile {
+    pub version: u32,
+    pub name: String,
+    /// The name of the map whose parallax background is used.
+    pub background: String,
+    pub background_color: [u8; 4],
+    pub grid_size: [u32; 2],
+    pub tile_size: [f32; 2],
+    #[serde(default, skip_serializing_if = "MapBoundsMeta::is_default")]
+    pub bounds: MapBoundsMeta,
+    #[serde(default, skip_serializing_if = "MapWeatherMeta::is_default")]
+    pub weather: MapWeatherMeta,
+    #[serde(default)]
+    pub layers: Vec<MapFileLayer>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<MapFileEvent>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MapFileLayer {
+    pub id: String,
+    /// The index of the tileset in [`MapFileAssets::tilesets`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tileset: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiles: Vec<MapFileTile>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<MapFileElement>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MapFileTile {
+    pub pos: [u32; 2],
+    pub idx: u32,
+    #[serde(default)]
+    pub collision: TileCollisionKind,
+    #[serde(default, skip_serializing_if = "TileMaterial::is_default")]
+    pub material: TileMaterial,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MapFileElement {
+    pub pos: [f32; 2],
+    /// The name of the element, from its [`ElementMeta`].
+    pub element: String,
+    #[serde(default, skip_serializing_if = "SpawnerWaves::is_default")]
+    pub waves: SpawnerWaves,
+    /// The [`ElementLink`] that wires the element to others, like a switch to the doors it opens.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub link: String,
+}
+
+/// A [`MapEventMeta`] in a map file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MapFileEvent {
+    #[serde(with = "humantime_serde")]
+    pub time: Duration,
+    pub action: MapEventAction,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub link: String,
+    /// The name of the element to spawn, from its [`ElementMeta`].
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub element: String,
+    #[serde(default)]
+    pub pos: [f32; 2],
+}
+
+/// The fields allowed in each part of a map file, used to report unknown fields with their
+/// location.
+const MAP_FIELDS: &[&str] = &[
+    "version",
+    "name",
+    "background",
+    "background_color",
+    "grid_size",
+    "tile_size",
+    "bounds",
+    "weather",
+    "layers",
+    "events",
+];
+const LAYER_FIELDS: &[&str] = &["id", "tileset", "tiles", "elements"];
+const TILE_FIELDS: &[&str] = &["pos", "idx", "collision", "material"];
+const ELEMENT_FIELDS: &[&str] = &["pos", "element", "waves", "link"];
+const EVENT_FIELDS: &[&str] = &["time", "action", "link", "element", "pos"];
+
+/// The assets that map files can refer to: every registered map element, tileset, and map
+/// background in the game and its asset packs.
+#[derive(Clone, Debug, Default)]
+pub struct MapFileAssets {
+    pub elements: HashMap<Ustr, Handle<ElementMeta>>,
+    pub tilesets: Vec<Handle<Atlas>>,
+    /// The backgrounds of the maps, by map name.
+    pub backgrounds: Vec<(Ustr, BackgroundMeta)>,
+}
+
+impl MapFileAssets {
+    /// Collect the assets registered in the core metadata and in every asset pack.
+    pub fn new(meta: &GameMeta, asset_server: &AssetServer) -> Self {
+        let mut elements = meta.core.map_elements.iter().copied().collect::<Vec<_>>();
+        let mut tilesets = meta.core.map_tilesets.iter().copied().collect::<Vec<_>>();
+        let mut maps = meta.core.stable_maps.iter().copied().collect::<Vec<_>>();
+        maps.extend(meta.core.experimental_maps.iter().copied());
+        for pack in asset_server.packs() {
+            let pack_meta = asset_server.get(pack.root.typed::<PackMeta>());
+            elements.extend(pack_meta.map_elements.iter().copied());
+            tilesets.extend(pack_meta.map_tilesets.iter().copied());
+            maps.extend(pack_meta.maps.iter().copied());
+        }
+
+        Self {
+            elements: elements
+                .into_iter()
+                .map(|handle| (asset_server.get(handle).name, handle))
+                .collect(),
+            tilesets,
+            backgrounds: maps
+                .into_iter()
+                .map(|handle| {
+                    let map = asset_server.get(handle);
+                    (map.name, map.background.clone())
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the name of the map that has the given background.
+    fn background_name(&self, background: &BackgroundMeta) -> Option<Ustr> {
+        let is_same = |other: &BackgroundMeta| {
+            other.speed == background.speed
+                && other.layers.len() == background.layers.len()
+                && other
+                    .layers
+                    .iter()
+                    .zip(background.layers.iter())
+                    .all(|(a, b)| a.image == b.image && a.depth == b.depth)
+        };
+        self.backgrounds
+            .iter()
+            .find(|(_, other)| is_same(other))
+            .map(|(name, _)| *name)
+    }
+}
+
+impl MapFile {
+    /// Parse a map file, migrating it to the current version of the format and checking it for
+    /// unknown fields and elements.
+    pub fn parse(text: &str, assets: &MapFileAssets) -> Result<Self, MapFileError> {
+        let Value::Mapping(mut file) = serde_yaml::from_str::<Value>(text)? else {
+            return Err(MapFileError::NotAMapping);
+        };
+
+        let version = match file.get("version") {
+            Some(version) => version.as_u64().ok_or(MapFileError::InvalidVersion)?,
+            None => 0,
+        };
+        if version > MAP_FORMAT_VERSION as u64 {
+            return Err(MapFileError::UnsupportedVersion(version));
+        }
+        for migrate in &MIGRATIONS[version as usize..] {
+            migrate(&mut file);
+        }
+        file.insert("version".into(), MAP_FORMAT_VERSION.into());
+
+        check_fields(&file)?;
+        let map_file: MapFile = serde_yaml::from_value(Value::Mapping(file))?;
+        map_file.validate(assets)?;
+        Ok(map_file)
+    }
+
+    /// Read a map file from disk.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &std::path::Path, assets: &MapFileAssets) -> Result<Self, MapFileError> {
+        Self::parse(&std::fs::read_to_string(path)?, assets)
+    }
+
+    /// Write the map file to disk.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: &std::path::Path) -> Result<(), MapFileError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_yaml()?)?;
+        Ok(())
+    }
+
+    /// Serialize the map file to YAML.
+    pub fn to_yaml(&self) -> Result<String, MapFileError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Check that every element, tileset and background that the map refers to is registered.
+    fn validate(&self, assets: &MapFileAssets) -> Result<(), MapFileError> {
+        if !assets
+            .backgrounds
+            .iter()
+            .any(|(name, _)| name.as_str() == self.background)
+        {
+            return Err(MapFileError::UnknownBackground(self.background.clone()));
+        }
+        for (layer_idx, layer) in self.layers.iter().enumerate() {
+            if let Some(idx) = layer.tileset {
+                if idx as usize >= assets.tilesets.len() {
+                    return Err(MapFileError::UnknownTileset {
+                        path: format!("layers[{layer_idx}].tileset"),
+                        idx,
+                    });
+                }
+            }
+            for (element_idx, element) in layer.elements.iter().enumerate() {
+                if !assets.elements.contains_key(&ustr(&element.element)) {
+                    return Err(MapFileError::UnknownElement {
+                        path: format!("layers[{layer_idx}].elements[{element_idx}].element"),
+                        name: element.element.clone(),
+                    });
+                }
+            }
+        }
+        for (event_idx, event) in self.events.iter().enumerate() {
+            if event.action == MapEventAction::SpawnElement
+                && !assets.elements.contains_key(&ustr(&event.element))
+            {
+                return Err(MapFileError::UnknownElement {
+                    path: format!("events[{event_idx}].element"),
+                    name: event.element.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the map metadata for a map file that has been checked by [`MapFile::parse`].
+    pub fn to_map_meta(&self, assets: &MapFileAssets) -> Result<MapMeta, MapFileError> {
+        self.validate(assets)?;
+        let background = assets
+            .backgrounds
+            .iter()
+            .find(|(name, _)| name.as_str() == self.background)
+            .map(|(_, background)| background.clone())
+            .unwrap_or_default();
+        let [r, g, b, a] = self.background_color;
+
+        Ok(MapMeta {
+            name: ustr(&self.name),
+            background,
+            background_color: Color::rgba_u8(r, g, b, a),
+            grid_size: self.grid_size.into(),
+            tile_size: self.tile_size.into(),
+            layers: self
+                .layers
+                .iter()
+                .map(|layer| MapLayerMeta {
+                    id: ustr(&layer.id),
+                    tilemap: layer
+                        .tileset
+                        .map(|idx| assets.tilesets[idx as usize])
+                        .into(),
+                    tiles: layer
+                        .tiles
+                        .iter()
+                        .map(|tile| MapTileMeta {
+                            pos: tile.pos.into(),
+                            idx: tile.idx,
+                            collision: tile.collision,
+                            material: tile.material,
+                        })
+                        .collect(),
+                    elements: layer
+                        .elements
+                        .iter()
+                        .map(|element| ElementSpawn {
+                            pos: element.pos.into(),
+                            element: assets.elements[&ustr(&element.element)],
+                            waves: element.waves,
+                            link: ustr(&element.link),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            camera: default(),
+            bounds: self.bounds,
+            weather: self.weather,
+            events: self
+                .events
+                .iter()
+                .map(|event| MapEventMeta {
+                    time: event.time,
+                    action: event.action,
+                    link: ustr(&event.link),
+                    element: ustr(&event.element),
+                    pos: event.pos.into(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Create a map file for the map that is spawned in the world, including any changes made to
+    /// it in the editor.
+    pub fn from_world(
+        world: &World,
+        assets: &MapFileAssets,
+        asset_server: &AssetServer,
+    ) -> Result<Self, MapFileError> {
+        let (Some(spawned_map_meta), Some(entities)) = (
+            world.resources.get::<SpawnedMapMeta>(),
+            world.resources.get::<Entities>(),
+        ) else {
+            return Err(MapFileError::NoMap);
+        };
+        let layer_metas = world.components.get::<SpawnedMapLayerMeta>().borrow();
+        let tile_layers = world.components.get::<TileLayer>().borrow();
+        let tiles = world.components.get::<Tile>().borrow();
+        let tile_collisions = world.components.get::<TileCollisionKind>().borrow();
+        let tile_materials = world.components.get::<TileMaterial>().borrow();
+        let element_handles = world.components.get::<ElementHandle>().borrow();
+        let spawner_waves = world.components.get::<SpawnerWaves>().borrow();
+        let element_links = world.components.get::<ElementLink>().borrow();
+        let transforms = world.components.get::<Transform>().borrow();
+
+        let background = assets
+            .background_name(&spawned_map_meta.background)
+            .ok_or(MapFileError::UnregisteredBackground)?;
+        let mut layers = spawned_map_meta
+            .layer_names
+            .iter()
+            .map(|id| MapFileLayer {
+                id: id.to_string(),
+                tileset: None,
+                tiles: Vec::new(),
+                elements: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        for (_, (layer_meta, tile_layer)) in entities.iter_with((&layer_metas, &tile_layers)) {
+            let layer_idx = layer_meta.layer_idx;
+            let Some(layer) = layers.get_mut(layer_idx as usize) else {
+                continue;
+            };
+            let tileset = assets
+                .tilesets
+                .iter()
+                .position(|x| *x == tile_layer.atlas)
+                .ok_or(MapFileError::UnregisteredTileset { layer: layer_idx })?;
+            layer.tileset = Some(tileset as u32);
+
+            for y in 0..spawned_map_meta.grid_size.y {
+                for x in 0..spawned_map_meta.grid_size.x {
+                    let Some(tile_ent) = tile_layer.get(uvec2(x, y)) else {
+                        continue;
+                    };
+                    let Some(tile) = tiles.get(tile_ent) else {
+                        continue;
+                    };
+                    layer.tiles.push(MapFileTile {
+                        pos: [x, y],
+                        idx: tile.idx,
+                        collision: tile_collisions.get(tile_ent).copied().unwrap_or_default(),
+                        material: tile_materials.get(tile_ent).copied().unwrap_or_default(),
+                    });
+                }
+            }
+        }
+
+        for (element_ent, (layer_meta, element_handle, transform)) in
+            entities.iter_with((&layer_metas, &element_handles, &transforms))
+        {
+            let layer_idx = layer_meta.layer_idx;
+            let Some(layer) = layers.get_mut(layer_idx as usize) else {
+                continue;
+            };
+            let name = asset_server.get(element_handle.0).name;
+            if assets.elements.get(&name) != Some(&element_handle.0) {
+                return Err(MapFileError::UnregisteredElement { layer: layer_idx });
+            }
+            let pos = transform.translation.xy();
+            layer.elements.push(MapFileElement {
+                pos: pos.into(),
+                element: name.to_string(),
+                waves: spawner_waves.get(element_ent).copied().unwrap_or_default(),
+                link: element_links
+                    .get(element_ent)
+                    .map(|x| x.to_string())
+                    .unwrap_or_default(),
+            });
+        }
+
+        Ok(Self {
+            version: MAP_FORMAT_VERSION,
+            name: spawned_map_meta.name.to_string(),
+            background: background.to_string(),
+            background_color: spawned_map_meta.background_color.as_rgba_u8(),
+            grid_size: spawned_map_meta.grid_size.into(),
+            tile_size: spawned_map_meta.tile_size.into(),
+            bounds: spawned_map_meta.bounds,
+            weather: spawned_map_meta.weather,
+            layers,
+            events: spawned_map_meta
+                .events
+                .iter()
+                .map(|event| MapFileEvent {
+                    time: event.time,
+                    action: event.action,
+                    link: event.link.to_string(),
+                    element: event.element.to_string(),
+                    pos: event.pos.into(),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Check every mapping in the file for fields that aren't part of the format, so that they can be
+/// reported with where they are instead of only their name.
+fn check_fields(file: &Mapping) -> Result<(), MapFileError> {
+    check_mapping_fields("map", file, MAP_FIELDS)?;
+    if let Some(Value::Sequence(events)) = file.get("events") {
+        for (event_idx, event) in events.iter().enumerate() {
+            if let Value::Mapping(event) = event {
+                check_mapping_fields(&format!("events[{event_idx}]"), event, EVENT_FIELDS)?;
+            }
+        }
+    }
+    let Some(Value::Sequence(layers)) = file.get("layers") else {
+        return Ok(());
+    };
+    for (layer_idx, layer) in layers.iter().enumerate() {
+        let Value::Mapping(layer) = layer else {
+            continue;
+        };
+        let layer_path = format!("layers[{layer_idx}]");
+        check_mapping_fields(&layer_path, layer, LAYER_FIELDS)?;
+        for (field, fields) in [("tiles", TILE_FIELDS), ("elements", ELEMENT_FIELDS)] {
+            let Some(Value::Sequence(items)) = layer.get(field) else {
+                continue;
+            };
+            for (item_idx, item) in items.iter().enumerate() {
+                if let Value::Mapping(item) = item {
+                    check_mapping_fields(
+                        &format!("{layer_path}.{field}[{item_idx}]"),
+                        item,
+                        fields,
+                    )?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_mapping_fields(
+    path: &str,
+    mapping: &Mapping,
+    fields: &[&str],
+) -> Result<(), MapFileError> {
+    for key in mapping.keys() {
+        let field = match key {
+            Value::String(field) => field.clone(),
+            other => serde_yaml::to_string(other)?.trim().to_string(),
+        };
+        if !fields.contains(&field.as_str()) {
+            return Err(MapFileError::UnknownField {
+                path: path.to_string(),
+                field,
+                expected: fields.join(", "),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_migrates_older_versions() {
+        let assets = MapFileAssets {
+            backgrounds: vec![(ustr("Level 1"), default())],
+            ..default()
+        };
+        let draft = "
+name: Draft
+background: Level 1
+background_color: [91, 87, 114, 255]
+grid_size: [27, 21]
+tile_size: [32.0, 32.0]
+";
+        let map_file = MapFile::parse(draft, &assets).unwrap();
+        assert_eq!(map_file.version, MAP_FORMAT_VERSION);
+
+        let newer = format!("version: {}{draft}", MAP_FORMAT_VERSION + 1);
+        assert!(matches!(
+            MapFile::parse(&newer, &assets),
+            Err(MapFileError::UnsupportedVersion(_))
+        ));
+
+        let windy = format!("version: 1{draft}weather:\n  gust_interval: 1.5\n");
+        let map_file = MapFile::parse(&windy, &assets).unwrap();
+        assert_eq!(map_file.weather.gust_interval, Duration::from_millis(1500));
+    }
+}
@@ -55,6 +55,31 @@ impl MapPool {
     pub fn get_random_map(&self, rng: &GlobalRng) -> Handle<MapMeta> {
         *rng.sample(&self.maps).unwrap()
     }
+
+    /// Check every map in the pool against the given symmetry, returning the maps that don't
+    /// satisfy it along with where they break it.
+    ///
+    /// Ranked pools use this to reject maps that would favor one side of the map.
+    pub fn check_symmetry(
+        &self,
+        assets: &AssetServer,
+        symmetry: MapSymmetry,
+    ) -> Vec<(Handle<MapMeta>, Vec<MapAsymmetry>)> {
+        self.maps
+            .iter()
+            .filter_map(|handle| {
+                let asymmetries = assets.get(*handle).find_asymmetries(symmetry);
+                if asymmetries.is_empty() {
+                    return None;
+                }
+                let map_name = assets.get(*handle).name;
+                for asymmetry in &asymmetries {
+                    warn!("Map `{map_name}` is not {symmetry:?} symmetric: {asymmetry}");
+                }
+                Some((*handle, asymmetries))
+            })
+            .collect()
+    }
 }
 
 #[derive(Serialize, Deserialize)]
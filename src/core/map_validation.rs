@@ -0,0 +1,195 @@
+//! Checks for common mistakes in maps.
+//!
+//! Maps are checked when they are loaded and after every change made in the editor. Problems are
+//! logged, and listed in the debug tools so that map makers can find them while playtesting.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<MapWarnings>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::Last, update_map_warnings);
+}
+
+/// A problem found in a map by [`validate_map`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum MapWarning {
+    /// The map has nowhere for players to spawn.
+    NoPlayerSpawners,
+    /// An element is inside of a solid tile.
+    ElementInSolidTile {
+        layer_idx: usize,
+        name: Ustr,
+        pos: Vec2,
+    },
+    /// A player spawner that players can't walk out of to any other spawner, and that can't be
+    /// reached from them.
+    UnreachableSpawner { layer_idx: usize, pos: Vec2 },
+    /// An element is outside of the map, where it falls out of the world or can't be reached.
+    ElementOutOfBounds {
+        layer_idx: usize,
+        name: Ustr,
+        pos: Vec2,
+    },
+    /// A tile is outside of the map's grid, where it isn't spawned.
+    TileOutOfBounds { layer_idx: usize, pos: UVec2 },
+}
+
+impl std::fmt::Display for MapWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapWarning::NoPlayerSpawners => write!(f, "Map has no player spawners"),
+            MapWarning::ElementInSolidTile {
+                layer_idx,
+                name,
+                pos,
+            } => write!(
+                f,
+                "Layer {layer_idx}: {name} at {pos} is inside a solid tile"
+            ),
+            MapWarning::UnreachableSpawner { layer_idx, pos } => write!(
+                f,
+                "Layer {layer_idx}: player spawner at {pos} is cut off from the other spawners"
+            ),
+            MapWarning::ElementOutOfBounds {
+                layer_idx,
+                name,
+                pos,
+            } => write!(
+                f,
+                "Layer {layer_idx}: {name} at {pos} is outside of the map"
+            ),
+            MapWarning::TileOutOfBounds { layer_idx, pos } => {
+                write!(f, "Layer {layer_idx}: tile at {pos} is outside of the map")
+            }
+        }
+    }
+}
+
+/// Session resource with the problems found in the current map.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct MapWarnings {
+    pub warnings: Vec<MapWarning>,
+    /// Whether the map has been checked since it was spawned.
+    checked: bool,
+}
+
+/// Check a map for missing player spawners, elements inside solid tiles or outside of the map,
+/// and player spawners that are cut off from each other.
+///
+/// Elements in the `Decorations` category are allowed to overlap tiles, since they are often
+/// placed in front of them on purpose.
+pub fn validate_map(map: &MapMeta, assets: &AssetServer) -> Vec<MapWarning> {
+    let mut warnings = Vec::new();
+    let map_size = map.size();
+    let tile_pos = |pos: Vec2| (pos / map.tile_size).floor().as_ivec2();
+
+    let solid_tiles = map
+        .layers
+        .iter()
+        .flat_map(|layer| layer.tiles.iter())
+        .filter(|tile| tile.collision == TileCollisionKind::Solid)
+        .map(|tile| tile.pos.as_ivec2())
+        .collect::<HashSet<_>>();
+
+    let mut spawners = Vec::new();
+    for (layer_idx, layer) in map.layers.iter().enumerate() {
+        for tile in layer.tiles.iter() {
+            if tile.pos.x >= map.grid_size.x || tile.pos.y >= map.grid_size.y {
+                warnings.push(MapWarning::TileOutOfBounds {
+                    layer_idx,
+                    pos: tile.pos,
+                });
+            }
+        }
+
+        for spawn in layer.elements.iter() {
+            let element_meta = assets.get(spawn.element);
+            let name = element_meta.name;
+            let pos = spawn.pos;
+            if assets
+                .get(element_meta.data)
+                .try_cast_ref::<PlayerSpawner>()
+                .is_ok()
+            {
+                spawners.push((layer_idx, pos));
+            }
+
+            if pos.x < 0.0 || pos.x > map_size.x || pos.y < 0.0 {
+                warnings.push(MapWarning::ElementOutOfBounds {
+                    layer_idx,
+                    name,
+                    pos,
+                });
+            } else if element_meta.category.as_str() != "Decorations"
+                && solid_tiles.contains(&tile_pos(pos))
+            {
+                warnings.push(MapWarning::ElementInSolidTile {
+                    layer_idx,
+                    name,
+                    pos,
+                });
+            }
+        }
+    }
+
+    if spawners.is_empty() {
+        warnings.push(MapWarning::NoPlayerSpawners);
+    } else if spawners.len() > 1 {
+        let nav_graph = create_nav_graph(map);
+        let is_connected = |a: NavNode, b: NavNode| {
+            petgraph::algo::has_path_connecting(&*nav_graph, a, b, None)
+                || petgraph::algo::has_path_connecting(&*nav_graph, b, a, None)
+        };
+        for (i, (layer_idx, pos)) in spawners.iter().enumerate() {
+            let node = NavNode(tile_pos(*pos));
+            let is_reachable = nav_graph.contains_node(node)
+                && spawners.iter().enumerate().any(|(j, (_, other))| {
+                    let other = NavNode(tile_pos(*other));
+                    i != j && nav_graph.contains_node(other) && is_connected(node, other)
+                });
+            if !is_reachable {
+                warnings.push(MapWarning::UnreachableSpawner {
+                    layer_idx: *layer_idx,
+                    pos: *pos,
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Check the map once it has been spawned, and again whenever it is edited.
+fn update_map_warnings(
+    assets: Res<AssetServer>,
+    map_spawned: ResInit<MapSpawned>,
+    player_inputs: Res<MatchInputs>,
+    map_manager: MapManager,
+    mut map_warnings: ResMut<MapWarnings>,
+) {
+    if !**map_spawned {
+        map_warnings.checked = false;
+        return;
+    }
+    let was_edited = player_inputs
+        .players
+        .iter()
+        .any(|x| x.editor_input.is_some());
+    if map_warnings.checked && !was_edited {
+        return;
+    }
+
+    let map = map_manager.export_map();
+    let warnings = validate_map(&map, &assets);
+    if !map_warnings.checked {
+        for warning in &warnings {
+            warn!("Map `{}`: {warning}", map.name);
+        }
+    }
+    *map_warnings = MapWarnings {
+        warnings,
+        checked: true,
+    };
+}
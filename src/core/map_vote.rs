@@ -0,0 +1,183 @@
+//! Voting on the map for the next round.
+//!
+//! Once a round is over, players pick one of [`MAP_VOTE_CANDIDATES`] maps from the map pool by
+//! moving left and right, and lock their pick in with jump. Votes are made with the regular player
+//! controls, so in network matches they reach every client through the synchronized match inputs
+//! and every client counts the same votes. The vote ends once every player has locked in, or when
+//! [`CoreConfigMeta::map_vote_time`] runs out. The map with the most votes wins, and ties are
+//! broken with the match's random seed.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<MapVote>();
+}
+
+/// How many maps players get to choose from.
+pub const MAP_VOTE_CANDIDATES: usize = 3;
+
+/// How far the stick has to be pushed to move the selection.
+const MOVE_THRESHOLD: f32 = 0.5;
+
+/// Session resource with the state of the vote for the next map.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct MapVote {
+    /// The maps that can be voted for. Empty until the vote starts.
+    pub candidates: Vec<Handle<MapMeta>>,
+    /// The candidate that each player has selected.
+    pub selections: [usize; MAX_PLAYERS as usize],
+    /// Whether each player has locked in their selection.
+    pub locked: [bool; MAX_PLAYERS as usize],
+    /// The direction each player was moving last frame, so that holding a direction only moves
+    /// the selection once.
+    last_move: [i8; MAX_PLAYERS as usize],
+    /// How much time is left to vote.
+    pub timer: Timer,
+    /// The maps to move on to, once the vote is over.
+    result: Option<MapPool>,
+}
+
+impl MapVote {
+    /// Whether players are voting right now.
+    pub fn is_voting(&self) -> bool {
+        !self.candidates.is_empty() && self.result.is_none()
+    }
+
+    /// The number of votes each candidate has, counting only locked in votes.
+    pub fn votes(&self, player_inputs: &MatchInputs) -> [u32; MAP_VOTE_CANDIDATES] {
+        let mut votes = [0; MAP_VOTE_CANDIDATES];
+        for (i, _) in voters(player_inputs) {
+            if self.locked[i] {
+                votes[self.selections[i]] += 1;
+            }
+        }
+        votes
+    }
+
+    /// The candidates tied for the most votes once the vote is over.
+    ///
+    /// Players that didn't lock in still count for what they had selected.
+    fn leaders(&self, player_inputs: &MatchInputs) -> Vec<usize> {
+        let mut votes = [0; MAP_VOTE_CANDIDATES];
+        for (i, _) in voters(player_inputs) {
+            votes[self.selections[i]] += 1;
+        }
+        let most_votes = votes.iter().copied().max().unwrap_or_default();
+        (0..self.candidates.len())
+            .filter(|x| votes[*x] == most_votes)
+            .collect()
+    }
+
+    /// Run the vote, returning the maps for the next round once it is over.
+    ///
+    /// The vote starts the first time this is called. If voting is turned off, or there aren't
+    /// enough maps to choose from, a random map is picked right away.
+    pub fn update(
+        &mut self,
+        map_pool: &MapPool,
+        rng: &GlobalRng,
+        player_inputs: &MatchInputs,
+        meta: &GameMeta,
+        delta: Duration,
+    ) -> Option<MapPool> {
+        if let Some(result) = &self.result {
+            return Some(result.clone());
+        }
+
+        if self.candidates.is_empty() {
+            let vote_time = meta.core.config.map_vote_time;
+            let has_voters = voters(player_inputs).next().is_some();
+            if vote_time.is_zero() || map_pool.maps.len() < 2 || !has_voters {
+                let mut next_maps = map_pool.clone();
+                next_maps.randomize_current_map(rng);
+                self.result = Some(next_maps.clone());
+                return Some(next_maps);
+            }
+
+            let mut maps = map_pool.maps.clone();
+            rng.shuffle(&mut maps);
+            maps.truncate(MAP_VOTE_CANDIDATES);
+            self.candidates = maps;
+            self.timer = Timer::new(vote_time, TimerMode::Once);
+        }
+
+        self.timer.tick(delta);
+        let candidate_count = self.candidates.len();
+        for (i, player) in voters(player_inputs) {
+            let control = &player.control;
+            let direction = if control.move_direction.x > MOVE_THRESHOLD {
+                1
+            } else if control.move_direction.x < -MOVE_THRESHOLD {
+                -1
+            } else {
+                0
+            };
+            let last_move = std::mem::replace(&mut self.last_move[i], direction);
+
+            if self.locked[i] {
+                if control.grab_just_pressed {
+                    self.locked[i] = false;
+                }
+                continue;
+            }
+            if direction != 0 && direction != last_move {
+                let selection = self.selections[i] as i32 + direction as i32;
+                self.selections[i] = selection.rem_euclid(candidate_count as i32) as usize;
+            }
+            if control.jump_just_pressed {
+                self.locked[i] = true;
+            }
+        }
+
+        let all_locked = voters(player_inputs).all(|(i, _)| self.locked[i]);
+        if !all_locked && !self.timer.finished() {
+            return None;
+        }
+
+        let winner = *rng.sample(&self.leaders(player_inputs)).unwrap();
+
+        let next_maps = MapPool {
+            maps: map_pool.maps.clone(),
+            current_map: self.candidates[winner],
+        };
+        self.result = Some(next_maps.clone());
+        Some(next_maps)
+    }
+}
+
+/// The players that get to vote: every present player that isn't an AI.
+fn voters(player_inputs: &MatchInputs) -> impl Iterator<Item = (usize, &PlayerInput)> {
+    player_inputs
+        .players
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.active && !x.is_ai)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tallies_the_votes_of_players() {
+        let mut vote = MapVote {
+            candidates: vec![default(); MAP_VOTE_CANDIDATES],
+            ..default()
+        };
+        let mut player_inputs = MatchInputs::default();
+        for (i, selection) in [2, 1, 1, 0].into_iter().enumerate() {
+            player_inputs.players[i].active = true;
+            vote.selections[i] = selection;
+        }
+        // AI players don't vote
+        player_inputs.players[3].is_ai = true;
+        vote.locked[0] = true;
+        vote.locked[1] = true;
+
+        assert_eq!(vote.votes(&player_inputs), [0, 1, 1]);
+        assert_eq!(vote.leaders(&player_inputs), vec![1]);
+
+        vote.selections[2] = 0;
+        assert_eq!(vote.leaders(&player_inputs), vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Match event logging and machine-readable match reports.
+//!
+//! Notable events such as deaths, kills, and item pickups are recorded to the [`MatchEventLog`]
+//! during each round. When the round is over, a JSON [`MatchReport`] is written to the reports
+//! directory, and optionally sent to the endpoint configured in the [`Settings`], so that
+//! community stat sites and tournament tooling can consume it.
+
+use crate::prelude::*;
+
+/// Install this module.
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<MatchEventLog>();
+    session.init_resource::<KillFeed>();
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, advance_event_log_frame)
+        .add_system_to_stage(CoreStage::First, age_kill_feed);
+}
+
+/// The version of the [`MatchReport`] format, incremented on breaking changes.
+pub const MATCH_REPORT_VERSION: u32 = 1;
+
+/// The kind of a [`MatchEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchEventKind {
+    /// A player died.
+    Death,
+    /// A player killed another player.
+    Kill,
+    /// A player helped kill another player by hitting them shortly before they died.
+    Assist,
+    /// A player picked up an item.
+    Pickup,
+}
+
+/// An event that happened during a round.
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchEvent {
+    /// The frame of the round that the event happened on.
+    pub frame: u32,
+    pub kind: MatchEventKind,
+    /// The player that the event happened to.
+    pub player: Option<u32>,
+    /// The position of the event on the map.
+    pub pos: [f32; 2],
+}
+
+/// Session resource containing the events that have happened this round.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct MatchEventLog {
+    /// The number of frames since the round started.
+    pub frame: u32,
+    pub events: Vec<MatchEvent>,
+}
+
+impl MatchEventLog {
+    /// Record an event that happened to the given player at the given position.
+    pub fn record(&mut self, kind: MatchEventKind, player: Option<PlayerIdx>, pos: Vec2) {
+        self.events.push(MatchEvent {
+            frame: self.frame,
+            kind,
+            player: player.map(|x| x.0),
+            pos: pos.to_array(),
+        });
+    }
+
+    /// Count the events of the given kind that happened to the given player.
+    pub fn count(&self, kind: MatchEventKind, player: PlayerIdx) -> u32 {
+        self.events
+            .iter()
+            .filter(|x| x.kind == kind && x.player == Some(player.0))
+            .count() as u32
+    }
+}
+
+fn advance_event_log_frame(mut event_log: ResMut<MatchEventLog>) {
+    event_log.frame = event_log.frame.saturating_add(1);
+}
+
+/// How many frames a kill stays in the [`KillFeed`].
+pub const KILL_FEED_LIFETIME: u32 = 300;
+
+/// A kill shown in the [`KillFeed`].
+#[derive(Clone, Debug)]
+pub struct KillFeedEntry {
+    pub killer: PlayerIdx,
+    /// The players that helped with the kill.
+    pub assists: Vec<PlayerIdx>,
+    pub victim: PlayerIdx,
+    /// How many frames ago the kill happened.
+    pub age: u32,
+}
+
+/// Session resource with the recent kills, shown on the HUD.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct KillFeed {
+    pub entries: Vec<KillFeedEntry>,
+}
+
+fn age_kill_feed(mut kill_feed: ResMut<KillFeed>) {
+    for entry in &mut kill_feed.entries {
+        entry.age += 1;
+    }
+    kill_feed
+        .entries
+        .retain(|entry| entry.age < KILL_FEED_LIFETIME);
+}
+
+/// A player's entry in a [`MatchReport`].
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchReportPlayer {
+    pub index: u32,
+    pub skin: String,
+    pub hat: Option<String>,
+    pub is_ai: bool,
+    /// The number of rounds the player has won so far this match, minus any penalties.
+    pub score: i32,
+    pub kills: u32,
+    pub assists: u32,
+    pub deaths: u32,
+}
+
+/// A machine-readable report of a completed round.
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchReport {
+    pub version: u32,
+    pub mode: String,
+    pub map: String,
+    /// The number of rounds completed this match, including this one.
+    pub round: u32,
+    /// The index of the player that won the round, or [`None`] on a draw.
+    pub winner: Option<u32>,
+    pub frames: u32,
+    pub players: Vec<MatchReportPlayer>,
+    pub events: Vec<MatchEvent>,
+}
+
+impl MatchReport {
+    /// Write the report to the reports directory, and send it to the given endpoint if it isn't
+    /// empty.
+    ///
+    /// Both happen on a background thread so that they don't stall the game.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn publish(self, endpoint: String) {
+        std::thread::spawn(move || {
+            let json = match serde_json::to_string_pretty(&self) {
+                Ok(json) => json,
+                Err(e) => {
+                    error!("Could not serialize match report: {e}");
+                    return;
+                }
+            };
+
+            if let Some(dirs) = directories::ProjectDirs::from("org", "fishfolk", "jumpy") {
+                let reports_dir = dirs.data_dir().join("reports");
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis();
+                let path = reports_dir.join(format!("round-{timestamp}.json"));
+                match std::fs::create_dir_all(&reports_dir)
+                    .and_then(|_| std::fs::write(&path, &json))
+                {
+                    Ok(()) => info!("Wrote match report to {}", path.display()),
+                    Err(e) => error!("Could not write match report to {}: {e}", path.display()),
+                }
+            }
+
+            if !endpoint.is_empty() {
+                if let Err(e) = ureq::post(&endpoint)
+                    .set("Content-Type", "application/json")
+                    .send_string(&json)
+                {
+                    warn!("Could not send match report to {endpoint}: {e}");
+                }
+            }
+        });
+    }
+
+    /// Match reports can't be saved on web.
+    #[cfg(target_arch = "wasm32")]
+    pub fn publish(self, _endpoint: String) {}
+}
+
+/// Save the events of the round that just ended to the map heatmap, and publish a
+/// [`MatchReport`] for it.
+///
+/// This should only be run once the round end is confirmed by all players, otherwise events from
+/// rolled back frames could be included.
+pub fn complete_round_report(winner: Option<PlayerIdx>) -> StaticSystem<(), ()> {
+    (move |entities: Res<Entities>,
+           assets: Res<AssetServer>,
+           map: Res<LoadedMap>,
+           score: Res<MatchScore>,
+           player_inputs: Res<MatchInputs>,
+           mode: Res<GameMode>,
+           player_indexes: Comp<PlayerIdx>,
+           mut event_log: ResMut<MatchEventLog>,
+           mut storage: ResMut<Storage>| {
+        let mut heatmaps = storage.get::<HeatmapStats>().cloned().unwrap_or_default();
+        heatmaps.record_events(map.name, &event_log.events);
+        storage.insert(heatmaps);
+        storage.save();
+
+        let endpoint = storage
+            .get::<Settings>()
+            .map(|x| x.match_report_endpoint.clone())
+            .unwrap_or_default();
+
+        let players = entities
+            .iter_with(&player_indexes)
+            .map(|(_, idx)| {
+                let input = &player_inputs.players[idx.0 as usize];
+                MatchReportPlayer {
+                    index: idx.0,
+                    skin: assets.get(input.selected_player).name.to_string(),
+                    hat: input
+                        .selected_hat
+                        .map(|hat| assets.get(hat).name.to_string()),
+                    is_ai: input.is_ai,
+                    score: score.score(*idx),
+                    kills: event_log.count(MatchEventKind::Kill, *idx),
+                    assists: event_log.count(MatchEventKind::Assist, *idx),
+                    deaths: event_log.count(MatchEventKind::Death, *idx),
+                }
+            })
+            .collect();
+
+        let report = MatchReport {
+            version: MATCH_REPORT_VERSION,
+            mode: mode.id().into(),
+            map: map.name.to_string(),
+            round: score.rounds_completed(),
+            winner: winner.map(|x| x.0),
+            frames: event_log.frame,
+            players,
+            events: std::mem::take(&mut event_log.events),
+        };
+        report.publish(endpoint);
+    })
+    .system()
+}
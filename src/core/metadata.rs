@@ -7,9 +7,11 @@ use crate::prelude::*;
 
 mod map;
 mod player;
+pub mod validation;
 
 pub use map::*;
 pub use player::*;
+pub use validation::{FieldRule, MetaProblem, MetaProblems, MetaRules, META_RULES};
 
 /// Extension trait for the bones [`AssetServer`].
 pub trait MatchAssetServerExt {
@@ -33,8 +35,21 @@ impl MatchAssetServerExt for &mut AssetServer {
 #[repr(C)]
 pub struct CoreMeta {
     pub camera: CameraMeta,
+    pub camera_shake: CameraShakeMeta,
     pub physics: PhysicsMeta,
     pub config: CoreConfigMeta,
+    pub hot_seat: HotSeatMeta,
+    pub bomb_tag: BombTagMeta,
+    pub infection: InfectionMeta,
+    pub training: TrainingMeta,
+    pub mutators: MutatorsMeta,
+    pub spatial_audio: SpatialAudioMeta,
+    pub particles: CoreParticlesMeta,
+    pub trails: TrailMeta,
+    pub damage_numbers: DamageNumbersMeta,
+    pub critters: CrittersMeta,
+    pub afk: AfkMeta,
+    pub input_validation: InputValidationMeta,
     pub map_tilesets: SVec<Handle<Atlas>>,
     pub players: SVec<Handle<PlayerMeta>>,
     pub player_emotes: SMap<Ustr, Handle<EmoteMeta>>,
@@ -48,7 +63,12 @@ pub struct CoreMeta {
 #[derive(HasSchema, Clone, Debug)]
 #[repr(C)]
 pub struct CameraMeta {
+    /// How the camera frames the players. May be overridden per map.
+    pub framing: CameraFraming,
     pub default_height: f32,
+    /// The tallest the camera may get when zooming out, so that players don't shrink to a few
+    /// pixels on huge maps. May be overridden per map.
+    pub max_height: f32,
     pub border_top: f32,
     pub border_bottom: f32,
     pub border_left: f32,
@@ -58,12 +78,106 @@ pub struct CameraMeta {
     pub zoom_out_lerp_factor: f32,
     pub min_camera_size: Vec2,
     pub player_camera_box_size: Vec2,
+    /// The height of the camera when zoomed in on a single entity, such as during the kill cam.
+    pub focus_height: f32,
+    /// The size of the area around the camera's target that it can move in without the camera
+    /// following it.
+    pub deadzone: Vec2,
+    /// How close to the edge of the screen a player has to be, in pixels, for an indicator to
+    /// point them out.
+    pub edge_indicator_margin: f32,
+}
+
+impl CameraMeta {
+    /// Get the camera settings with the overrides from the given map applied.
+    pub fn with_map_overrides(&self, map: &MapCameraMeta) -> Self {
+        Self {
+            framing: map.framing.option().unwrap_or(self.framing),
+            max_height: map.max_height.option().unwrap_or(self.max_height),
+            move_lerp_factor: map
+                .move_lerp_factor
+                .option()
+                .unwrap_or(self.move_lerp_factor),
+            zoom_in_lerp_factor: map
+                .zoom_in_lerp_factor
+                .option()
+                .unwrap_or(self.zoom_in_lerp_factor),
+            zoom_out_lerp_factor: map
+                .zoom_out_lerp_factor
+                .option()
+                .unwrap_or(self.zoom_out_lerp_factor),
+            ..self.clone()
+        }
+    }
+}
+
+/// Particle effects that don't belong to any element.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct CoreParticlesMeta {
+    /// The dust kicked up when a player lands after falling at least as fast as
+    /// `landing_dust_min_speed`.
+    pub landing_dust: Maybe<ParticleEmitterMeta>,
+    pub landing_dust_min_speed: f32,
+}
+
+/// How much the camera shakes for each [`CameraShakeKind`].
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct CameraShakeMeta {
+    pub explosion: CameraShakeProfile,
+    pub landing: CameraShakeProfile,
+    pub melee_hit: CameraShakeProfile,
+    /// How fast a player has to be falling for their landing to shake the camera.
+    pub landing_min_speed: f32,
+}
+
+impl CameraShakeMeta {
+    /// Get the profile for the given kind of event.
+    pub fn profile(&self, kind: CameraShakeKind) -> &CameraShakeProfile {
+        match kind {
+            CameraShakeKind::Explosion => &self.explosion,
+            CameraShakeKind::Landing => &self.landing,
+            CameraShakeKind::MeleeHit => &self.melee_hit,
+        }
+    }
+}
+
+/// The feel of a camera shake.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct CameraShakeProfile {
+    /// How much trauma is added to the camera. Trauma is capped at `1.0`.
+    pub trauma: f32,
+    /// The furthest the camera may move from its center, in pixels.
+    pub amplitude: f32,
+    /// How fast the camera shakes.
+    pub frequency: f32,
+    /// How fast the shake dies down.
+    pub decay: f32,
+}
+
+/// The ways that the camera can frame the players.
+#[derive(HasSchema, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[derive_type_data(SchemaDeserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CameraFraming {
+    /// Zoom in as far as possible while keeping every player's body on screen.
+    Tight,
+    /// Keep a loose box around every player, so that the camera doesn't follow every hop.
+    #[default]
+    Loose,
+    /// Show a fixed zone of the map, regardless of where the players are.
+    FixedZone,
 }
 
 impl Default for CameraMeta {
     fn default() -> Self {
         Self {
+            framing: CameraFraming::Loose,
             default_height: 400.0,
+            max_height: f32::INFINITY,
             border_top: 0.0,
             border_bottom: 0.0,
             border_left: 0.0,
@@ -73,6 +187,9 @@ impl Default for CameraMeta {
             zoom_out_lerp_factor: 1.0,
             min_camera_size: Vec2::ZERO,
             player_camera_box_size: Vec2::ZERO,
+            focus_height: 200.0,
+            deadzone: Vec2::ZERO,
+            edge_indicator_margin: 0.0,
         }
     }
 }
@@ -84,7 +201,50 @@ pub struct PhysicsMeta {
     pub terminal_velocity: f32,
     pub friction_lerp: f32,
     pub stop_threshold: f32,
+    /// How much players can steer their launch while stunned by knockback, as the velocity added
+    /// every frame in the held direction.
+    pub knockback_influence: f32,
+    /// How far a player has to be squeezed into a moving solid, such as a platform or a door,
+    /// before they are crushed.
+    pub crush_threshold: f32,
+    /// How fast idle items caught in an explosion are launched away from it.
+    pub blast_launch_speed: f32,
     pub player: PhysicsPlayerMeta,
+    pub materials: TileMaterialsMeta,
+}
+
+/// The physics of the [`TileMaterial`]s that map tiles can be made of.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct TileMaterialsMeta {
+    pub ice: TileMaterialMeta,
+    pub rubber: TileMaterialMeta,
+}
+
+impl TileMaterialsMeta {
+    /// Get the physics of the given material, or [`None`] for [`TileMaterial::Default`] tiles,
+    /// which use the normal physics.
+    pub fn get(&self, material: TileMaterial) -> Option<&TileMaterialMeta> {
+        match material {
+            TileMaterial::Default => None,
+            TileMaterial::Ice => Some(&self.ice),
+            TileMaterial::Rubber => Some(&self.rubber),
+        }
+    }
+}
+
+/// The physics of one [`TileMaterial`].
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct TileMaterialMeta {
+    /// How many times longer players slide for when they stop on the tile.
+    pub player_slide: f32,
+    /// The friction of other bodies on the tile, used instead of the
+    /// [`friction_lerp`][PhysicsMeta::friction_lerp].
+    pub body_friction: f32,
+    /// The least bounciness that bodies bounce off of the tile with. Bodies that don't bounce at
+    /// all still don't bounce off of it.
+    pub bounciness: f32,
 }
 
 #[derive(HasSchema, Clone, Debug, Default)]
@@ -105,6 +265,12 @@ pub struct CoreConfigMeta {
     #[serde(with = "humantime_serde")]
     pub respawn_invincibility_time: Duration,
 
+    /// How long invincible players stay faded out or shown between flickers. Zero turns off the
+    /// flicker.
+    #[serde(default)]
+    #[serde(with = "humantime_serde")]
+    pub invincibility_flicker_interval: Duration,
+
     /// After one or fewer players left, how long to watch before scoring
     #[serde(default)]
     #[serde(with = "humantime_serde")]
@@ -120,4 +286,46 @@ pub struct CoreConfigMeta {
 
     /// How many rounds between intermissions
     pub rounds_between_intermission: u32,
+
+    /// How long after pressing grab a player will catch a thrown item that hits them
+    #[serde(default)]
+    #[serde(with = "humantime_serde")]
+    pub catch_window: Duration,
+
+    /// How long a dropped item can lie untouched before it is sent back to its spawner. Zero keeps
+    /// dropped items around forever.
+    #[serde(default)]
+    #[serde(with = "humantime_serde")]
+    pub item_idle_despawn_time: Duration,
+
+    /// How long the final kill of a round plays in slow motion, in game time
+    #[serde(default)]
+    #[serde(with = "humantime_serde")]
+    pub kill_cam_time: Duration,
+
+    /// How fast the game runs during the kill cam
+    pub kill_cam_time_scale: f32,
+
+    /// How long players have to vote on the next map between rounds. Zero picks a random map
+    /// instead.
+    #[serde(default)]
+    #[serde(with = "humantime_serde")]
+    pub map_vote_time: Duration,
+
+    /// The points taken away from players for killing themselves or their teammates, per game
+    /// mode. Modes that aren't listed have no penalties.
+    #[serde(default)]
+    pub score_penalties: HashMap<GameMode, ScorePenaltiesMeta>,
+}
+
+/// The points taken away from a player's score for bad kills.
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ScorePenaltiesMeta {
+    /// Points lost for killing yourself.
+    #[serde(default)]
+    pub suicide: u32,
+    /// Points lost for killing a player on your own team.
+    #[serde(default)]
+    pub team_kill: u32,
 }
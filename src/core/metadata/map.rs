@@ -15,6 +15,137 @@ pub struct MapMeta {
     pub tile_size: Vec2,
     /// The layers of the map
     pub layers: SVec<MapLayerMeta>,
+    /// Overrides for the camera settings in the [`GameMeta`].
+    pub camera: MapCameraMeta,
+    /// How far outside of the map players and items can go before they are killed.
+    pub bounds: MapBoundsMeta,
+    /// The rain or snow falling over the map.
+    pub weather: MapWeatherMeta,
+    /// The things that happen at set times in the round, run by the [`MapEventScheduler`].
+    pub events: SVec<MapEventMeta>,
+}
+
+/// The kill margins around the edges of a map, in pixels.
+///
+/// Players that cross a margin are killed, and items that cross one are sent back to their
+/// spawners.
+#[derive(HasSchema, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+#[repr(C)]
+pub struct MapBoundsMeta {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    /// Defaults to infinity, so that nothing is killed for going above the map.
+    pub top: f32,
+}
+
+impl Default for MapBoundsMeta {
+    fn default() -> Self {
+        Self {
+            left: 500.0,
+            right: 500.0,
+            bottom: 500.0,
+            top: f32::INFINITY,
+        }
+    }
+}
+
+impl MapBoundsMeta {
+    pub fn is_default(&self) -> bool {
+        *self == default()
+    }
+}
+
+/// The kinds of weather that a map can have.
+#[derive(HasSchema, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[derive_type_data(SchemaDeserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+}
+
+/// The weather of a map. It is only for looks, apart from the gusts of wind, which push around
+/// everything with a [`KinematicBody`].
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+#[repr(C)]
+pub struct MapWeatherMeta {
+    pub kind: WeatherKind,
+    /// How many raindrops or snowflakes there are in every 100 by 100 pixel area of the map.
+    pub density: f32,
+    /// How fast the rain or snow drifts sideways, in pixels per second. Positive values blow it to
+    /// the right, and gusts blow in the same direction.
+    pub wind: f32,
+    /// How long there is between gusts of wind, or zero for no gusts.
+    #[serde(with = "humantime_serde")]
+    pub gust_interval: Duration,
+    /// How much horizontal velocity a gust adds to everything it pushes.
+    pub gust_strength: f32,
+    /// The cutoff frequency of the low-pass filter put on the music, in hertz, or `0.0` to leave
+    /// the music as it is.
+    pub music_low_pass: f32,
+}
+
+impl MapWeatherMeta {
+    pub fn is_default(&self) -> bool {
+        *self == default()
+    }
+}
+
+/// Something that a map does at a set time in the round, like opening floodgates or spawning a
+/// boss.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq)]
+#[repr(C)]
+pub struct MapEventMeta {
+    /// How long after the round starts the event happens.
+    pub time: Duration,
+    pub action: MapEventAction,
+    /// The [`ElementLink`] to toggle, for [`MapEventAction::ToggleLink`].
+    pub link: Ustr,
+    /// The name of the element to spawn, for [`MapEventAction::SpawnElement`].
+    pub element: Ustr,
+    /// Where to spawn the element, for [`MapEventAction::SpawnElement`].
+    pub pos: Vec2,
+}
+
+/// What a [`MapEventMeta`] does.
+#[derive(HasSchema, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[derive_type_data(SchemaDeserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapEventAction {
+    /// Flip the doors and elevators with the event's link, as if a switch with the link was
+    /// flipped.
+    #[default]
+    ToggleLink,
+    /// Spawn an element by name.
+    SpawnElement,
+}
+
+/// Per-map overrides for the [`CameraMeta`].
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct MapCameraMeta {
+    pub framing: Maybe<CameraFraming>,
+    /// The area shown when using [`CameraFraming::FixedZone`]. Defaults to the whole map.
+    pub fixed_zone: Maybe<CameraZone>,
+    pub max_height: Maybe<f32>,
+    pub move_lerp_factor: Maybe<f32>,
+    pub zoom_in_lerp_factor: Maybe<f32>,
+    pub zoom_out_lerp_factor: Maybe<f32>,
+}
+
+/// A rectangle of the map, in pixels.
+#[derive(HasSchema, Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct CameraZone {
+    pub min: Vec2,
+    pub max: Vec2,
 }
 
 #[derive(HasSchema, Clone, Debug, Default)]
@@ -29,9 +160,31 @@ pub struct BackgroundMeta {
 pub struct ParallaxLayerMeta {
     pub image: Handle<Image>,
     pub size: Vec2,
+    /// How far back the layer is. Deeper layers move more slowly as the camera moves.
     pub depth: f32,
     pub scale: f32,
     pub offset: Vec2,
+    /// How much the layer moves as the camera moves, overriding the scroll that comes from its
+    /// `depth` and the background `speed`.
+    pub scroll_factor: Maybe<Vec2>,
+    /// How fast the layer drifts sideways on its own, in pixels per second, like for clouds.
+    pub auto_scroll: f32,
+    /// Whether the layer is only shown once, instead of repeating to the sides.
+    pub single: bool,
+    /// An animated atlas to show instead of the `image`.
+    pub atlas: Maybe<Handle<Atlas>>,
+    /// The frames of the `atlas` to loop through.
+    pub frames: SVec<u32>,
+    pub fps: f32,
+}
+
+impl ParallaxLayerMeta {
+    /// Get how much the layer moves as the camera moves.
+    pub fn scroll_factor(&self, background_speed: Vec2) -> Vec2 {
+        self.scroll_factor
+            .option()
+            .unwrap_or(self.depth * background_speed)
+    }
 }
 
 #[derive(HasSchema, Clone, Debug, Default)]
@@ -48,6 +201,9 @@ pub struct MapLayerMeta {
 pub struct ElementSpawn {
     pub pos: Vec2,
     pub element: Handle<ElementMeta>,
+    pub waves: SpawnerWaves,
+    /// The [`ElementLink`] of the element, or empty if it isn't linked to anything.
+    pub link: Ustr,
 }
 
 #[derive(HasSchema, Clone, Debug, Default)]
@@ -56,16 +212,178 @@ pub struct MapTileMeta {
     pub pos: UVec2,
     pub idx: u32,
     pub collision: TileCollisionKind,
+    pub material: TileMaterial,
 }
 
 impl MapMeta {
+    /// Get the corners of the area that players and items are killed outside of, as `(min, max)`.
+    ///
+    /// The top of the area is infinite unless the map sets a top kill margin.
+    pub fn kill_bounds(&self) -> (Vec2, Vec2) {
+        let size = self.grid_size.as_vec2() * self.tile_size;
+        let bounds = &self.bounds;
+        (
+            vec2(-bounds.left, -bounds.bottom),
+            vec2(size.x + bounds.right, size.y + bounds.top),
+        )
+    }
+
     /// Checks if the given position is out of the bounds of the map.
     pub fn is_out_of_bounds(&self, pos: &Vec3) -> bool {
-        const KILL_ZONE_BORDER: f32 = 500.0;
-        let map_width = self.grid_size.x as f32 * self.tile_size.x;
-        let left_kill_zone = -KILL_ZONE_BORDER;
-        let right_kill_zone = map_width + KILL_ZONE_BORDER;
-        let bottom_kill_zone = -KILL_ZONE_BORDER;
-        pos.x < left_kill_zone || pos.x > right_kill_zone || pos.y < bottom_kill_zone
+        let (min, max) = self.kill_bounds();
+        pos.x < min.x || pos.x > max.x || pos.y < min.y || pos.y > max.y
+    }
+}
+
+/// A kind of symmetry that a map can be built with.
+///
+/// Used by the editor to mirror edits, and by [`MapMeta::find_asymmetries`] to check that
+/// competitive maps are fair for every spawn position.
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug, HasSchema, Serialize, Deserialize)]
+#[repr(u8)]
+#[derive_type_data(SchemaDeserialize)]
+pub enum MapSymmetry {
+    /// No mirroring.
+    #[default]
+    None,
+    /// Mirrored across the vertical center line, so the left half matches the right half.
+    Horizontal,
+    /// Mirrored across the horizontal center line, so the top half matches the bottom half.
+    Vertical,
+    /// Rotated 180 degrees around the center of the map.
+    Rotational,
+}
+
+impl MapSymmetry {
+    /// Get the tile position that mirrors `pos` on a map with the given grid size.
+    ///
+    /// Returns [`None`] if there is no symmetry, or if `pos` is outside of the grid.
+    pub fn mirror_tile(&self, pos: UVec2, grid_size: UVec2) -> Option<UVec2> {
+        if pos.x >= grid_size.x || pos.y >= grid_size.y {
+            return None;
+        }
+        let flip_x = grid_size.x - 1 - pos.x;
+        let flip_y = grid_size.y - 1 - pos.y;
+        match self {
+            MapSymmetry::None => None,
+            MapSymmetry::Horizontal => Some(uvec2(flip_x, pos.y)),
+            MapSymmetry::Vertical => Some(uvec2(pos.x, flip_y)),
+            MapSymmetry::Rotational => Some(uvec2(flip_x, flip_y)),
+        }
+    }
+
+    /// Get the world position that mirrors `pos` on a map with the given size in pixels.
+    ///
+    /// Returns [`None`] if there is no symmetry.
+    pub fn mirror_point(&self, pos: Vec2, map_size: Vec2) -> Option<Vec2> {
+        let flipped = map_size - pos;
+        match self {
+            MapSymmetry::None => None,
+            MapSymmetry::Horizontal => Some(vec2(flipped.x, pos.y)),
+            MapSymmetry::Vertical => Some(vec2(pos.x, flipped.y)),
+            MapSymmetry::Rotational => Some(flipped),
+        }
+    }
+}
+
+/// A place where a map breaks the symmetry it was checked against.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MapAsymmetry {
+    /// A tile has no matching tile, or a tile with a different collision, at its mirrored position.
+    Tile {
+        layer_idx: usize,
+        pos: UVec2,
+        mirrored_pos: UVec2,
+    },
+    /// An element has no element of the same kind at its mirrored position.
+    Element {
+        layer_idx: usize,
+        pos: Vec2,
+        mirrored_pos: Vec2,
+    },
+}
+
+impl std::fmt::Display for MapAsymmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapAsymmetry::Tile {
+                layer_idx,
+                pos,
+                mirrored_pos,
+            } => write!(
+                f,
+                "Layer {layer_idx}: tile at {pos} doesn't match the tile at {mirrored_pos}"
+            ),
+            MapAsymmetry::Element {
+                layer_idx,
+                pos,
+                mirrored_pos,
+            } => write!(
+                f,
+                "Layer {layer_idx}: element at {pos} has no matching element at {mirrored_pos}"
+            ),
+        }
+    }
+}
+
+impl MapMeta {
+    /// The size of the map in pixels.
+    pub fn size(&self) -> Vec2 {
+        self.grid_size.as_vec2() * self.tile_size
+    }
+
+    /// Check the map against the given symmetry, returning every tile and element that doesn't
+    /// have a match at its mirrored position.
+    ///
+    /// Only tile collisions are compared, not the tile sprites, since maps commonly use flipped
+    /// or alternate artwork on each side. Element positions are compared with a tolerance of half
+    /// a tile.
+    pub fn find_asymmetries(&self, symmetry: MapSymmetry) -> Vec<MapAsymmetry> {
+        let mut asymmetries = Vec::new();
+        if symmetry == MapSymmetry::None {
+            return asymmetries;
+        }
+
+        let map_size = self.size();
+        let tolerance = self.tile_size.min_element() / 2.0;
+
+        for (layer_idx, layer) in self.layers.iter().enumerate() {
+            let collisions = layer
+                .tiles
+                .iter()
+                .map(|tile| (tile.pos, tile.collision))
+                .collect::<HashMap<_, _>>();
+
+            for tile in layer.tiles.iter() {
+                let Some(mirrored_pos) = symmetry.mirror_tile(tile.pos, self.grid_size) else {
+                    continue;
+                };
+                if collisions.get(&mirrored_pos) != Some(&tile.collision) {
+                    asymmetries.push(MapAsymmetry::Tile {
+                        layer_idx,
+                        pos: tile.pos,
+                        mirrored_pos,
+                    });
+                }
+            }
+
+            for spawn in layer.elements.iter() {
+                let Some(mirrored_pos) = symmetry.mirror_point(spawn.pos, map_size) else {
+                    continue;
+                };
+                let has_match = layer.elements.iter().any(|other| {
+                    other.element == spawn.element && other.pos.distance(mirrored_pos) <= tolerance
+                });
+                if !has_match {
+                    asymmetries.push(MapAsymmetry::Element {
+                        layer_idx,
+                        pos: spawn.pos,
+                        mirrored_pos,
+                    });
+                }
+            }
+        }
+
+        asymmetries
     }
 }
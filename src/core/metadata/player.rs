@@ -77,6 +77,17 @@ pub struct PlayerStatsMeta {
     pub walk_speed: f32,
     pub slowdown: f32,
     pub accel_walk_speed: f32,
+    /// The max horizontal speed while swimming at the surface of water.
+    pub swim_speed: f32,
+    pub accel_swim_speed: f32,
+    /// The speed of jumping out of the water while swimming.
+    pub swim_jump_speed: f32,
+    /// The max speed while diving under water.
+    pub dive_speed: f32,
+    /// How fast a diving player floats back up when not swimming down.
+    pub buoyancy: f32,
+    /// How long a player can stay under water before drowning.
+    pub air_time: Duration,
 }
 
 #[derive(HasSchema, Clone, Debug, Default)]
@@ -0,0 +1,412 @@
+//! Checks for mistakes in metadata files.
+//!
+//! The asset server fills in defaults for fields it doesn't find and skips fields it doesn't know,
+//! so a typo in an element's YAML usually shows up as an element that behaves strangely, or that
+//! crashes once it is spawned. On startup, every metadata file with [`MetaRules`] is read again and
+//! checked for unknown fields, missing or broken asset paths, and values that are out of range.
+//! Problems are logged with the file and line they are on, and listed in the debug tools.
+
+use std::path::{Path, PathBuf};
+
+use serde_yaml::Value;
+
+use crate::prelude::*;
+
+pub fn game_plugin(game: &mut Game) {
+    game.init_shared_resource::<MetaProblems>();
+    #[cfg(not(target_arch = "wasm32"))]
+    game.systems.add_startup_system(validate_metadata);
+}
+
+/// A check for a field of a metadata asset.
+#[derive(Clone, Copy, Debug)]
+pub enum FieldRule {
+    /// The field must be a path to a file that exists.
+    Handle,
+    /// The field must be a number greater than zero.
+    Positive,
+    /// The field must be a number that isn't negative.
+    NonNegative,
+    /// The field must be a pair of numbers that are both greater than zero.
+    PositiveSize,
+    /// The field must be a duration longer than zero.
+    PositiveDuration,
+}
+
+/// The checks for a type of metadata asset.
+pub struct MetaRules {
+    /// The extension that the asset type is loaded from, like `kick_bomb` for
+    /// `*.kick_bomb.yaml` files.
+    pub extension: &'static str,
+    /// The schema of the asset, used to find unknown fields.
+    pub schema: fn() -> &'static Schema,
+    pub fields: &'static [(&'static str, FieldRule)],
+}
+
+/// The metadata types that are checked on startup.
+pub const META_RULES: &[MetaRules] = {
+    use FieldRule::*;
+    &[
+        MetaRules {
+            extension: "element",
+            schema: ElementMeta::schema,
+            fields: &[("data", Handle)],
+        },
+        MetaRules {
+            extension: "kick_bomb",
+            schema: KickBombMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("explosion_atlas", Handle),
+                ("explosion_sound", Handle),
+                ("fuse_sound", Handle),
+                ("body_diameter", Positive),
+                ("fuse_time", PositiveDuration),
+                ("arm_delay", PositiveDuration),
+                ("damage_region_size", PositiveSize),
+                ("damage_region_lifetime", Positive),
+                ("explosion_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "grenade",
+            schema: GrenadeMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("explosion_atlas", Handle),
+                ("explosion_sound", Handle),
+                ("fuse_sound", Handle),
+                ("body_diameter", Positive),
+                ("fuse_time", Positive),
+                ("damage_region_size", PositiveSize),
+                ("damage_region_lifetime", Positive),
+                ("explosion_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "singularity",
+            schema: SingularityMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("explosion_atlas", Handle),
+                ("explosion_sound", Handle),
+                ("fuse_sound", Handle),
+                ("pull_sound", Handle),
+                ("body_diameter", Positive),
+                ("fuse_time", PositiveDuration),
+                ("pull_duration", PositiveDuration),
+                ("pull_radius", Positive),
+                ("pull_strength", Positive),
+                ("damage_region_size", PositiveSize),
+                ("damage_region_lifetime", Positive),
+                ("explosion_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "smoke_grenade",
+            schema: SmokeGrenadeMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("cloud_atlas", Handle),
+                ("fuse_sound", Handle),
+                ("smoke_sound", Handle),
+                ("body_diameter", Positive),
+                ("fuse_time", PositiveDuration),
+                ("smoke_duration", PositiveDuration),
+                ("cloud_size", PositiveSize),
+                ("cloud_scale", Positive),
+                ("cloud_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "mine",
+            schema: MineMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("explosion_atlas", Handle),
+                ("explosion_sound", Handle),
+                ("arm_sound", Handle),
+                ("body_size", PositiveSize),
+                ("arm_delay", NonNegative),
+                ("damage_region_size", PositiveSize),
+                ("damage_region_lifetime", Positive),
+                ("explosion_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "remote_charge",
+            schema: RemoteChargeMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("detonator_atlas", Handle),
+                ("explosion_atlas", Handle),
+                ("explosion_sound", Handle),
+                ("body_size", PositiveSize),
+                ("detonator_body_size", PositiveSize),
+                ("damage_region_size", PositiveSize),
+                ("damage_region_lifetime", Positive),
+                ("explosion_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "crate",
+            schema: CrateMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("breaking_atlas", Handle),
+                ("break_sound", Handle),
+                ("bounce_sound", Handle),
+                ("body_size", PositiveSize),
+                ("break_timeout", PositiveDuration),
+            ],
+        },
+        MetaRules {
+            extension: "musket",
+            schema: MusketMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("shoot_atlas", Handle),
+                ("shoot_sound", Handle),
+                ("empty_shoot_sound", Handle),
+                ("bullet_meta", Handle),
+                ("body_size", PositiveSize),
+                ("shoot_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "laser_rifle",
+            schema: LaserRifleMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("scorch_atlas", Handle),
+                ("shoot_sound", Handle),
+                ("empty_shoot_sound", Handle),
+                ("body_size", PositiveSize),
+                ("range", Positive),
+                ("beam_thickness", Positive),
+                ("beam_lifetime", PositiveDuration),
+            ],
+        },
+        MetaRules {
+            extension: "decoy",
+            schema: DecoyMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("poof_atlas", Handle),
+                ("spawn_sound", Handle),
+                ("body_size", PositiveSize),
+                ("walk_speed", Positive),
+                ("decoy_lifetime", PositiveDuration),
+                ("poof_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "mount",
+            schema: MountMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("body_size", PositiveSize),
+                ("speed", Positive),
+                ("jump_speed", Positive),
+                ("gravity", Positive),
+                ("ride_fps", Positive),
+            ],
+        },
+        MetaRules {
+            extension: "sword",
+            schema: SwordMeta::schema,
+            fields: &[
+                ("atlas", Handle),
+                ("sound", Handle),
+                ("body_size", PositiveSize),
+            ],
+        },
+    ]
+};
+
+/// A problem found in a metadata file.
+#[derive(Clone, Debug)]
+pub struct MetaProblem {
+    pub file: PathBuf,
+    /// The line of the field that has the problem, if it is in the file.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for MetaProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{line}: {}", self.file.display(), self.message),
+            None => write!(f, "{}: {}", self.file.display(), self.message),
+        }
+    }
+}
+
+/// Shared resource with the problems found in the metadata files on startup.
+#[derive(HasSchema, Clone, Debug, Default, Deref, DerefMut)]
+pub struct MetaProblems(pub Vec<MetaProblem>);
+
+/// Check a metadata file against its rules.
+///
+/// `root` is the directory of the pack that the file is in, which paths that start with `/` are
+/// relative to.
+pub fn validate_meta_file(path: &Path, root: &Path, rules: &MetaRules) -> Vec<MetaProblem> {
+    let mut problems = Vec::new();
+    let problem = |line, message| MetaProblem {
+        file: path.to_path_buf(),
+        line,
+        message,
+    };
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => return vec![problem(None, e.to_string())],
+    };
+    let fields = match serde_yaml::from_str::<Value>(&text) {
+        Ok(Value::Mapping(fields)) => fields,
+        Ok(_) => return vec![problem(Some(1), "expected a mapping of fields".into())],
+        Err(e) => {
+            let line = e.location().map(|x| x.line());
+            return vec![problem(line, e.to_string())];
+        }
+    };
+
+    if let SchemaKind::Struct(info) = &(rules.schema)().kind {
+        let known = info
+            .fields
+            .iter()
+            .filter_map(|x| x.name.as_ref().map(|x| x.to_string()))
+            .collect::<Vec<_>>();
+        for key in fields.keys() {
+            let Some(key) = key.as_str() else {
+                continue;
+            };
+            if !known.iter().any(|x| x == key) {
+                problems.push(problem(
+                    field_line(&text, key),
+                    format!(
+                        "unknown field `{key}`, expected one of: {}",
+                        known.join(", ")
+                    ),
+                ));
+            }
+        }
+    }
+
+    for (field, rule) in rules.fields {
+        let line = field_line(&text, field);
+        let Some(value) = fields.get(*field) else {
+            // Missing numbers default to zero, which is only fine for non-negative fields
+            if !matches!(rule, FieldRule::NonNegative) {
+                problems.push(problem(None, format!("missing field `{field}`")));
+            }
+            continue;
+        };
+        if let Err(message) = check_rule(value, *rule, path, root) {
+            problems.push(problem(line, format!("`{field}` {message}")));
+        }
+    }
+
+    problems
+}
+
+/// Check a field value against a rule, returning what is wrong with it.
+fn check_rule(value: &Value, rule: FieldRule, path: &Path, root: &Path) -> Result<(), String> {
+    match rule {
+        FieldRule::Handle => {
+            let asset_path = value.as_str().unwrap_or_default();
+            if asset_path.is_empty() {
+                return Err("must be a path to an asset".into());
+            }
+            let resolved = match asset_path.strip_prefix('/') {
+                Some(asset_path) => root.join(asset_path),
+                None => path.parent().unwrap_or(root).join(asset_path),
+            };
+            if !resolved.is_file() {
+                return Err(format!("points to `{asset_path}`, which doesn't exist"));
+            }
+        }
+        FieldRule::Positive | FieldRule::NonNegative => {
+            let Some(number) = value.as_f64() else {
+                return Err("must be a number".into());
+            };
+            if number < 0.0 || (matches!(rule, FieldRule::Positive) && number == 0.0) {
+                return Err(format!("is {number}, but must be greater than zero"));
+            }
+        }
+        FieldRule::PositiveSize => {
+            let size = value
+                .as_sequence()
+                .filter(|x| x.len() == 2)
+                .and_then(|x| Some([x[0].as_f64()?, x[1].as_f64()?]));
+            let Some(size) = size else {
+                return Err("must be a pair of numbers".into());
+            };
+            if size.iter().any(|x| *x <= 0.0) {
+                return Err(format!(
+                    "is [{}, {}], but both sizes must be greater than zero",
+                    size[0], size[1]
+                ));
+            }
+        }
+        FieldRule::PositiveDuration => {
+            let duration = match value {
+                Value::String(duration) => humantime_serde::re::humantime::parse_duration(duration)
+                    .map_err(|e| format!("is not a duration: {e}"))?,
+                Value::Number(seconds) => {
+                    let seconds = seconds.as_f64().unwrap_or_default();
+                    if seconds < 0.0 {
+                        return Err(format!("is {seconds}, but must be longer than zero"));
+                    }
+                    Duration::from_secs_f64(seconds)
+                }
+                _ => return Err("must be a duration, like `1.5s`".into()),
+            };
+            if duration.is_zero() {
+                return Err("must be longer than zero".into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the line that a top level field is on.
+fn field_line(text: &str, field: &str) -> Option<usize> {
+    text.lines()
+        .position(|line| {
+            line.strip_prefix(field)
+                .is_some_and(|x| x.trim_start().starts_with(':'))
+        })
+        .map(|x| x + 1)
+}
+
+/// Check the metadata files in the asset directory and every pack.
+#[cfg(not(target_arch = "wasm32"))]
+fn validate_metadata(game: &mut Game) {
+    let mut roots = vec![crate::mods::asset_dir()];
+    if let Ok(entries) = std::fs::read_dir(crate::mods::packs_dir()) {
+        roots.extend(entries.flatten().map(|x| x.path()).filter(|x| x.is_dir()));
+    }
+
+    let mut problems = Vec::new();
+    for root in roots {
+        for path in crate::mods::yaml_files(&root) {
+            let file_name = path
+                .file_name()
+                .map(|x| x.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let rules = META_RULES.iter().find(|rules| {
+                file_name == format!("{}.yaml", rules.extension)
+                    || file_name.ends_with(&format!(".{}.yaml", rules.extension))
+            });
+            if let Some(rules) = rules {
+                problems.extend(validate_meta_file(&path, &root, rules));
+            }
+        }
+    }
+
+    for problem in &problems {
+        error!("Invalid metadata: {problem}");
+    }
+    game.insert_shared_resource(MetaProblems(problems));
+}
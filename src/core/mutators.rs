@@ -0,0 +1,246 @@
+//! Match mutators.
+//!
+//! Mutators are optional rule tweaks that are picked during match setup. Any number of them can
+//! be enabled at once, on top of any [`GameMode`].
+
+use crate::prelude::*;
+
+/// Install the systems for the given mutators.
+///
+/// Each mutator only installs its systems when it is enabled. The mutators that just tweak numbers,
+/// such as gravity, are read from the [`Mutators`] resource by the systems that they affect.
+pub fn install(session: &mut SessionBuilder, mutators: Mutators) {
+    session.insert_resource(mutators);
+    if mutators.mirror {
+        mirror_plugin(session);
+    }
+    if mutators.explosives_only {
+        explosives_only_plugin(session);
+    }
+    if mutators.big_heads {
+        big_heads_plugin(session);
+    }
+}
+
+fn mirror_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, mirror_player_inputs)
+        .add_system_to_stage(CoreStage::Last, mirror_camera);
+}
+
+fn explosives_only_plugin(session: &mut SessionBuilder) {
+    session
+        .stages
+        .add_system_to_stage(CoreStage::Update, remove_non_explosive_items);
+}
+
+fn big_heads_plugin(session: &mut SessionBuilder) {
+    // Runs after the attachments have been moved to their players' heads
+    session
+        .stages
+        .add_system_to_stage(CoreStage::Last, scale_player_heads);
+}
+
+/// Settings for the mutators.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct MutatorsMeta {
+    /// What gravity is multiplied by in low gravity matches.
+    pub low_gravity_scale: f32,
+    /// What the height of every jump is multiplied by in low gravity matches.
+    pub low_gravity_jump_height_scale: f32,
+    /// How close an item has to be to an empty-handed player to be pulled in by auto pickup,
+    /// unless the item's [`ElementMeta::auto_pickup_radius`] overrides it.
+    pub auto_pickup_radius: f32,
+    /// How fast items are pulled toward players by auto pickup.
+    pub auto_pickup_speed: f32,
+    /// What player movement speeds are multiplied by in double speed matches.
+    pub double_speed_scale: f32,
+    /// How much bigger player heads, and the hats on them, are in big head matches.
+    pub big_heads_scale: f32,
+}
+
+/// The mutators enabled for a match.
+#[derive(HasSchema, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Mutators {
+    /// Gravity is lowered, and players jump higher.
+    pub low_gravity: bool,
+    /// The whole match is flipped horizontally, along with the controls.
+    pub mirror: bool,
+    /// Empty-handed players pull in nearby items and pick them up on touch.
+    pub auto_pickup: bool,
+    /// Only [explosive][ElementMeta::explosive] items can be found in the map.
+    pub explosives_only: bool,
+    /// Hits that would knock players back kill them instead.
+    pub one_hit_kill: bool,
+    /// Players move twice as fast.
+    pub double_speed: bool,
+    /// Players have comically large heads.
+    pub big_heads: bool,
+}
+
+/// A single mutator, used to list them in menus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutator {
+    LowGravity,
+    Mirror,
+    AutoPickup,
+    ExplosivesOnly,
+    OneHitKill,
+    DoubleSpeed,
+    BigHeads,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 7] = [
+        Mutator::LowGravity,
+        Mutator::Mirror,
+        Mutator::AutoPickup,
+        Mutator::ExplosivesOnly,
+        Mutator::OneHitKill,
+        Mutator::DoubleSpeed,
+        Mutator::BigHeads,
+    ];
+
+    /// The localization key for the name of the mutator.
+    pub fn localization_key(&self) -> &'static str {
+        match self {
+            Mutator::LowGravity => "mutator-low-gravity",
+            Mutator::Mirror => "mutator-mirror",
+            Mutator::AutoPickup => "mutator-auto-pickup",
+            Mutator::ExplosivesOnly => "mutator-explosives-only",
+            Mutator::OneHitKill => "mutator-one-hit-kill",
+            Mutator::DoubleSpeed => "mutator-double-speed",
+            Mutator::BigHeads => "mutator-big-heads",
+        }
+    }
+}
+
+impl Mutators {
+    /// Whether the given mutator is enabled.
+    pub fn is_enabled(&self, mutator: Mutator) -> bool {
+        match mutator {
+            Mutator::LowGravity => self.low_gravity,
+            Mutator::Mirror => self.mirror,
+            Mutator::AutoPickup => self.auto_pickup,
+            Mutator::ExplosivesOnly => self.explosives_only,
+            Mutator::OneHitKill => self.one_hit_kill,
+            Mutator::DoubleSpeed => self.double_speed,
+            Mutator::BigHeads => self.big_heads,
+        }
+    }
+
+    /// Enable or disable the given mutator.
+    pub fn toggle(&mut self, mutator: Mutator) {
+        match mutator {
+            Mutator::LowGravity => self.low_gravity = !self.low_gravity,
+            Mutator::Mirror => self.mirror = !self.mirror,
+            Mutator::AutoPickup => self.auto_pickup = !self.auto_pickup,
+            Mutator::ExplosivesOnly => self.explosives_only = !self.explosives_only,
+            Mutator::OneHitKill => self.one_hit_kill = !self.one_hit_kill,
+            Mutator::DoubleSpeed => self.double_speed = !self.double_speed,
+            Mutator::BigHeads => self.big_heads = !self.big_heads,
+        }
+    }
+
+    /// What gravity is multiplied by.
+    pub fn gravity_scale(&self, meta: &MutatorsMeta) -> f32 {
+        if self.low_gravity {
+            meta.low_gravity_scale
+        } else {
+            1.0
+        }
+    }
+
+    /// What jump speeds are multiplied by.
+    ///
+    /// This accounts for the gravity scale, so that jump heights only change by the configured
+    /// amount, and maps stay playable.
+    pub fn jump_scale(&self, meta: &MutatorsMeta) -> f32 {
+        if self.low_gravity {
+            (meta.low_gravity_scale * meta.low_gravity_jump_height_scale).sqrt()
+        } else {
+            1.0
+        }
+    }
+
+    /// What player movement speeds are multiplied by.
+    pub fn move_speed_scale(&self, meta: &MutatorsMeta) -> f32 {
+        if self.double_speed {
+            meta.double_speed_scale
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Flip the horizontal controls of every human player, so that they match the mirrored view.
+///
+/// This runs on the simulation inputs, so every client in a network match agrees on it. AI
+/// players are left alone, since they already move in world space.
+fn mirror_player_inputs(mut player_inputs: ResMut<MatchInputs>) {
+    for player in player_inputs.players.iter_mut().filter(|x| !x.is_ai) {
+        let control = &mut player.control;
+        control.move_direction.x = -control.move_direction.x;
+        std::mem::swap(&mut control.left, &mut control.right);
+    }
+}
+
+/// Flip the camera horizontally in mirror matches.
+fn mirror_camera(
+    entities: Res<Entities>,
+    cameras: Comp<Camera>,
+    mut transforms: CompMut<Transform>,
+) {
+    for (_ent, (_camera, transform)) in entities.iter_with((&cameras, &mut transforms)) {
+        transform.scale.x = -transform.scale.x.abs();
+    }
+}
+
+/// Remove the items lying around the map that aren't explosives, as soon as they are spawned.
+///
+/// Their spawners stay hydrated, so they don't come back. Items held by players, such as the
+/// swords given out by some game modes, are left alone until they are dropped.
+fn remove_non_explosive_items(
+    mut entities: ResMut<Entities>,
+    assets: Res<AssetServer>,
+    items: Comp<Item>,
+    element_handles: Comp<ElementHandle>,
+    player_inventories: PlayerInventories,
+) {
+    let to_remove = entities
+        .iter_with((&items, &element_handles))
+        .filter(|(ent, (_item, handle))| {
+            !assets.get(handle.0).explosive && player_inventories.find_item(*ent).is_none()
+        })
+        .map(|(ent, _)| ent)
+        .collect::<Vec<_>>();
+    for ent in to_remove {
+        entities.kill(ent);
+    }
+}
+
+/// Grow the players' heads, along with anything on them, around the center of the player.
+fn scale_player_heads(
+    entities: Res<Entities>,
+    game_meta: Root<GameMeta>,
+    player_body_attachments: Comp<PlayerBodyAttachment>,
+    mut transforms: CompMut<Transform>,
+) {
+    let scale = game_meta.core.mutators.big_heads_scale;
+    for (ent, attachment) in entities.iter_with(&player_body_attachments) {
+        if !attachment.head {
+            continue;
+        }
+        let Some(player_pos) = transforms.get(attachment.player).map(|x| x.translation) else {
+            continue;
+        };
+        let transform = transforms.get_mut(ent).unwrap();
+        let offset = transform.translation.xy() - player_pos.xy();
+        transform.translation = (player_pos.xy() + offset * scale).extend(transform.translation.z);
+        transform.scale.x *= scale;
+        transform.scale.y *= scale;
+    }
+}
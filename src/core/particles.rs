@@ -0,0 +1,224 @@
+//! General purpose particle emitters.
+//!
+//! Elements describe their particles with a [`ParticleEmitterMeta`], and either attach a
+//! [`ParticleEmitter`] to themselves, for effects like the smoke trail of a lit bomb, or spawn a
+//! one-shot emitter with [`ParticleEmitter::spawn_burst`], for effects like landing dust. Each
+//! particle is its own sprite entity that is cleaned up when its lifetime runs out.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session
+        .add_system_to_stage(CoreStage::PostUpdate, emit_particles)
+        .add_system_to_stage(CoreStage::PostUpdate, update_particles);
+}
+
+/// How the particles of an emitter look and move.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct ParticleEmitterMeta {
+    pub atlas: Handle<Atlas>,
+    /// The range of frames in the atlas that each particle plays through once over its lifetime.
+    pub frames_start: u32,
+    pub frames_end: u32,
+    /// How many particles are emitted per second while the emitter is enabled.
+    pub rate: f32,
+    /// How many particles are emitted at once when the emitter starts.
+    pub burst: u32,
+    /// How long each particle lives for, in seconds.
+    pub lifetime: f32,
+    /// The direction particles are emitted in, in degrees counter-clockwise from the right.
+    pub direction: f32,
+    /// How far particles may stray from `direction` to either side, in degrees.
+    pub spread: f32,
+    /// The slowest and fastest a particle may be emitted at, in pixels per second.
+    pub speed: Vec2,
+    /// How fast particles accelerate downwards, in pixels per second squared. Negative values make
+    /// them float up.
+    pub gravity: f32,
+    pub scale: f32,
+    pub color: Color,
+    /// Whether particles fade out over their lifetime.
+    pub fade_out: bool,
+    /// How far the particles are drawn in front of the emitter.
+    pub z_offset: f32,
+}
+
+/// Emits particles from the entity's [`Transform`].
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct ParticleEmitter {
+    pub meta: ParticleEmitterMeta,
+    /// Where the particles come from, relative to the entity.
+    pub offset: Vec2,
+    /// Whether the particles are emitted mirrored horizontally.
+    pub flip_x: bool,
+    /// Whether particles are emitted at the meta's `rate`.
+    pub enabled: bool,
+    /// Whether the entity is despawned once the burst has been emitted.
+    pub one_shot: bool,
+    /// Particles that are due to be emitted but haven't been, since only whole particles can be.
+    accumulated: f32,
+    /// Whether the burst has been emitted.
+    burst_emitted: bool,
+}
+
+impl ParticleEmitter {
+    /// Create an emitter that keeps emitting particles.
+    pub fn new(meta: ParticleEmitterMeta) -> Self {
+        Self {
+            meta,
+            enabled: true,
+            ..default()
+        }
+    }
+
+    /// Spawn an emitter entity that emits a single burst of particles at the given position and
+    /// then goes away.
+    pub fn spawn_burst(meta: ParticleEmitterMeta, pos: Vec3, flip_x: bool) -> StaticSystem<(), ()> {
+        (move |mut entities: ResMutInit<Entities>,
+               mut transforms: CompMut<Transform>,
+               mut emitters: CompMut<ParticleEmitter>| {
+            let ent = entities.create();
+            transforms.insert(ent, Transform::from_translation(pos));
+            emitters.insert(
+                ent,
+                ParticleEmitter {
+                    meta: meta.clone(),
+                    flip_x,
+                    one_shot: true,
+                    ..default()
+                },
+            );
+        })
+        .system()
+    }
+}
+
+/// A single particle emitted by a [`ParticleEmitter`].
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub gravity: f32,
+    /// How long the particle has lived for, in seconds.
+    pub age: f32,
+    pub lifetime: f32,
+    pub frames_start: u32,
+    pub frames_end: u32,
+    pub fade_out: bool,
+    /// The alpha of the particle's color when it was emitted.
+    pub alpha: f32,
+}
+
+fn emit_particles(
+    mut entities: ResMutInit<Entities>,
+    rng: Res<GlobalRng>,
+    mut emitters: CompMut<ParticleEmitter>,
+    mut transforms: CompMut<Transform>,
+    mut sprites: CompMut<AtlasSprite>,
+    mut particles: CompMut<Particle>,
+) {
+    let mut new_particles = Vec::new();
+    let mut finished = Vec::new();
+
+    for (ent, (emitter, transform)) in entities.iter_with((&mut emitters, &transforms)) {
+        let mut count = 0;
+        if !emitter.burst_emitted {
+            emitter.burst_emitted = true;
+            count += emitter.meta.burst;
+        }
+        if emitter.enabled && !emitter.one_shot {
+            emitter.accumulated += emitter.meta.rate / FPS;
+            count += emitter.accumulated as u32;
+            emitter.accumulated = emitter.accumulated.fract();
+        }
+        if emitter.one_shot {
+            finished.push(ent);
+        }
+        if count == 0 {
+            continue;
+        }
+
+        let offset = if emitter.flip_x {
+            vec2(-emitter.offset.x, emitter.offset.y)
+        } else {
+            emitter.offset
+        };
+        let pos = transform.translation + offset.extend(emitter.meta.z_offset);
+        for _ in 0..count {
+            let meta = &emitter.meta;
+            let angle = (meta.direction + rng.f32_normalized() * meta.spread).to_radians();
+            let speed = meta.speed.x + rng.f32() * (meta.speed.y - meta.speed.x);
+            let mut velocity = Vec2::from_angle(angle) * speed;
+            if emitter.flip_x {
+                velocity.x = -velocity.x;
+            }
+            new_particles.push((pos, velocity, meta.clone(), emitter.flip_x));
+        }
+    }
+
+    for ent in finished {
+        entities.kill(ent);
+    }
+
+    for (pos, velocity, meta, flip_x) in new_particles {
+        let ent = entities.create();
+        let mut transform = Transform::from_translation(pos);
+        transform.scale = Vec3::splat(meta.scale);
+        transforms.insert(ent, transform);
+        sprites.insert(
+            ent,
+            AtlasSprite {
+                atlas: meta.atlas,
+                index: meta.frames_start,
+                color: meta.color,
+                flip_x,
+                ..default()
+            },
+        );
+        particles.insert(
+            ent,
+            Particle {
+                velocity,
+                gravity: meta.gravity,
+                lifetime: meta.lifetime,
+                frames_start: meta.frames_start,
+                frames_end: meta.frames_end.max(meta.frames_start + 1),
+                fade_out: meta.fade_out,
+                alpha: meta.color.a(),
+                ..default()
+            },
+        );
+    }
+}
+
+/// Move the particles, play through their frames, and despawn them once their lifetime is over.
+fn update_particles(
+    mut entities: ResMutInit<Entities>,
+    mut particles: CompMut<Particle>,
+    mut transforms: CompMut<Transform>,
+    mut sprites: CompMut<AtlasSprite>,
+) {
+    let mut expired = Vec::new();
+    for (ent, (particle, transform, sprite)) in
+        entities.iter_with((&mut particles, &mut transforms, &mut sprites))
+    {
+        particle.age += 1.0 / FPS;
+        if particle.age >= particle.lifetime {
+            expired.push(ent);
+            continue;
+        }
+        particle.velocity.y -= particle.gravity / FPS;
+        transform.translation += (particle.velocity / FPS).extend(0.0);
+
+        let progress = particle.age / particle.lifetime;
+        let frames = particle.frames_end - particle.frames_start;
+        sprite.index = particle.frames_start + ((progress * frames as f32) as u32).min(frames - 1);
+        if particle.fade_out {
+            sprite.color.set_a(particle.alpha * (1.0 - progress));
+        }
+    }
+
+    for ent in expired {
+        entities.kill(ent);
+    }
+}
@@ -3,23 +3,27 @@
 use crate::prelude::*;
 
 pub use collisions::{
-    Actor, Collider, ColliderShape, CollisionWorld, PhysicsParams, RapierContext, RapierUserData,
-    Solid, TileCollisionKind,
+    Actor, Collider, ColliderShape, CollisionWorld, JointKind, PhysicsJoint, PhysicsParams,
+    QueryHit, RapierContext, RapierUserData, Solid, SpatialQuery, SpatialQueryLog,
+    TileCollisionKind, TileMaterial, SPATIAL_QUERY_DEBUG_FRAMES,
 };
 
 use super::utils::Rect;
 pub use rapier2d::prelude as rapier;
 
 pub mod collisions;
+pub mod crush;
 pub mod dynamic_body;
 
+pub use crush::*;
 pub use dynamic_body::*;
 
 /// For now kinematic mode is globally position based.
 pub static KINEMATIC_MODE: rapier::RigidBodyType = rapier::RigidBodyType::KinematicPositionBased;
 
+/// The stage that the kinematic bodies are moved in, right after [`CoreStage::PostUpdate`].
 #[derive(Debug, Clone, Copy)]
-enum PhysicsStage {
+pub enum PhysicsStage {
     Update,
 }
 
@@ -51,6 +55,7 @@ pub fn install(session: &mut SessionBuilder) {
         )
         .add_system_to_stage(PhysicsStage::Update, hydrate_physics_bodies)
         .add_system_to_stage(PhysicsStage::Update, update_kinematic_bodies);
+    crush::install(session);
 }
 
 /// A kinematic physics body
@@ -80,13 +85,18 @@ pub struct KinematicBody {
     pub was_on_ground: bool,
     /// Will be `true` if the body is currently on top of a platform/jumpthrough tile
     pub is_on_platform: bool,
+    /// The material of the tiles that the body is standing on.
+    pub ground_material: TileMaterial,
     /// If this is `true` the body will be affected by gravity
     pub has_mass: bool,
     pub has_friction: bool,
     pub can_rotate: bool,
     /// Whether or not physics has been disabled for this body.
     pub is_deactivated: bool,
-    /// Whether or not the body should fall through jump_through platforms
+    /// Whether or not the body should fall through jump_through platforms.
+    ///
+    /// Bodies always pass through jump-through platforms from below and land on them from above
+    /// unless this is set. It is cleared automatically for bodies that aren't controlled.
     pub fall_through: bool,
     /// Indicates that we should reset the collider like it was just added to the world.
     ///
@@ -122,6 +132,61 @@ impl KinematicBody {
     }
 }
 
+/// A push away from a point, or a pull towards it.
+///
+/// Knockback, items launched out of explosions and the pull of the singularity all move bodies
+/// with one of these.
+#[derive(Clone, Copy, Debug)]
+pub struct RadialImpulse {
+    /// The point that bodies are pushed away from.
+    pub origin: Vec2,
+    /// The speed that bodies are pushed away from the origin at. Negative strengths pull bodies
+    /// towards the origin instead.
+    pub strength: f32,
+    /// The distance at which the impulse has faded out. It is at full strength at the origin and
+    /// gets weaker in a straight line up to this distance.
+    pub radius: f32,
+    /// The least upwards part of the direction bodies are pushed in, from `-1.0` to `1.0`, so that
+    /// bodies level with or below the origin are still launched into the air.
+    pub min_lift: f32,
+}
+
+impl RadialImpulse {
+    /// Create an impulse straight away from `origin` that doesn't fade with distance.
+    pub fn new(origin: Vec2, strength: f32) -> Self {
+        Self {
+            origin,
+            strength,
+            radius: f32::INFINITY,
+            min_lift: -1.0,
+        }
+    }
+
+    /// Create the impulse of a knockback from `origin`.
+    ///
+    /// `knockback` is the velocity given to a body level with the origin and to the right of it.
+    /// Bodies to the left are knocked back mirrored, and bodies further above are knocked back
+    /// more steeply.
+    pub fn knockback(origin: Vec2, knockback: Vec2) -> Self {
+        Self {
+            min_lift: knockback.normalize_or_zero().y,
+            ..Self::new(origin, knockback.length())
+        }
+    }
+
+    /// Get the velocity that the impulse gives to a body at `position`.
+    pub fn velocity(&self, position: Vec2) -> Vec2 {
+        let offset = position - self.origin;
+        let falloff = (1.0 - offset.length() / self.radius).max(0.0);
+        let mut direction = offset.normalize_or_zero();
+        if direction.y < self.min_lift {
+            let side = if offset.x < 0.0 { -1.0 } else { 1.0 };
+            direction = vec2(side * (1.0 - self.min_lift.powi(2)).sqrt(), self.min_lift);
+        }
+        direction * self.strength * falloff
+    }
+}
+
 /// Hydrate newly added [`KinematicBody`]s.
 fn hydrate_physics_bodies(
     entities: Res<Entities>,
@@ -151,6 +216,7 @@ fn hydrate_physics_bodies(
 fn update_kinematic_bodies(
     meta: Root<GameMeta>,
     entities: Res<Entities>,
+    mutators: Res<Mutators>,
     mut bodies: CompMut<KinematicBody>,
     mut dynamic_bodies: CompMut<DynamicBody>,
     mut collision_world: CollisionWorld,
@@ -161,7 +227,8 @@ fn update_kinematic_bodies(
 
     let time_factor = time.delta().as_secs_f32();
 
-    let global_gravity = meta.core.physics.gravity;
+    let gravity_scale = mutators.gravity_scale(&meta.core.mutators);
+    let global_gravity = meta.core.physics.gravity * gravity_scale;
     collision_world.update(
         time_factor,
         PhysicsParams {
@@ -269,7 +336,14 @@ fn update_kinematic_bodies(
 
             if collision_world.move_vertical(&mut transforms, entity, body.velocity.y * time_factor)
             {
-                body.velocity.y *= -body.bounciness;
+                let bounciness = surface_bounciness(
+                    &collision_world,
+                    &meta.core.physics,
+                    body,
+                    *transforms.get(entity).unwrap(),
+                    vec2(0.0, body.velocity.y),
+                );
+                body.velocity.y *= -bounciness;
             }
 
             // NOTE: It's important that we move horizontally after we move vertically, or else the
@@ -280,7 +354,14 @@ fn update_kinematic_bodies(
                 entity,
                 body.velocity.x * time_factor,
             ) {
-                body.velocity.x *= -body.bounciness;
+                let bounciness = surface_bounciness(
+                    &collision_world,
+                    &meta.core.physics,
+                    body,
+                    *transforms.get(entity).unwrap(),
+                    vec2(body.velocity.x, 0.0),
+                );
+                body.velocity.x *= -bounciness;
             }
         }
 
@@ -294,16 +375,23 @@ fn update_kinematic_bodies(
             if has_moved || !body.is_controlled {
                 puffin::profile_scope!("fall through check");
                 // Don't get stuck floating in fall-through platforms
-                if body.velocity == Vec2::ZERO
+                let stuck_in_platform = body.velocity == Vec2::ZERO
                     && collision_world.tile_collision_filtered(transform, body.shape, |ent| {
                         collision_world
                             .tile_collision_kinds
                             .get(ent)
                             .map(|x| *x == TileCollisionKind::JumpThrough)
                             .unwrap_or(false)
-                    }) == TileCollisionKind::JumpThrough
-                {
+                    }) == TileCollisionKind::JumpThrough;
+                if stuck_in_platform {
                     body.fall_through = true;
+
+                // Nothing else clears the flag for bodies that aren't controlled, such as items and
+                // kick bombs, so clear it here once they are out of the platform. The collider keeps
+                // falling through the platform it is in until it has left it, but the body should
+                // land on the next platform from above again.
+                } else if !body.is_controlled {
+                    body.fall_through = false;
                 }
             }
 
@@ -330,12 +418,20 @@ fn update_kinematic_bodies(
             body.is_on_ground =
                 tile != TileCollisionKind::Empty && !(on_jump_through_tile && body.fall_through);
             body.is_on_platform = body.is_on_ground && on_jump_through_tile;
+            body.ground_material = if body.is_on_ground {
+                collision_world.tile_material(transform, body.shape)
+            } else {
+                TileMaterial::Default
+            };
         }
 
         if body.is_on_ground {
             if body.has_friction {
+                let material = meta.core.physics.materials.get(body.ground_material);
                 body.velocity.x *= if let Some(friction) = body.frame_friction_override {
                     friction
+                } else if let Some(material) = material {
+                    material.body_friction
                 } else {
                     meta.core.physics.friction_lerp
                 };
@@ -348,13 +444,13 @@ fn update_kinematic_bodies(
                 body.velocity.y *= meta.core.physics.friction_lerp;
             }
 
-            if body.velocity.y <= body.gravity * time_factor {
+            if body.velocity.y <= body.gravity * gravity_scale * time_factor {
                 body.velocity.y = 0.0;
             }
         }
 
         if !body.is_on_ground && body.has_mass {
-            body.velocity.y -= body.gravity * time_factor;
+            body.velocity.y -= body.gravity * gravity_scale * time_factor;
 
             if body.velocity.y < -meta.core.physics.terminal_velocity {
                 body.velocity.y = -meta.core.physics.terminal_velocity;
@@ -374,6 +470,31 @@ fn update_kinematic_bodies(
     }
 }
 
+/// Get how much a body bounces off of what it just ran into while moving with the given
+/// `velocity`, taking the material of the tile it ran into into account.
+fn surface_bounciness(
+    collision_world: &CollisionWorld,
+    physics: &PhysicsMeta,
+    body: &KinematicBody,
+    mut transform: Transform,
+    velocity: Vec2,
+) -> f32 {
+    // Bodies that don't bounce don't need to look for bouncy tiles
+    if body.bounciness == 0.0 {
+        return 0.0;
+    }
+
+    // The body is stopped just short of the tile, so look a little further along
+    transform.translation += (velocity.normalize_or_zero() * 0.5).extend(0.0);
+    match physics
+        .materials
+        .get(collision_world.tile_material(transform, body.shape))
+    {
+        Some(material) => body.bounciness.max(material.bounciness),
+        None => body.bounciness,
+    }
+}
+
 /// Helper function to apply rotation to a kinematic body.
 fn apply_rotation(
     delta_time: f32,
@@ -397,3 +518,33 @@ fn apply_rotation(
 
     transform.rotation = Quat::from_rotation_z(angle);
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn knockback_is_mirrored_for_level_bodies() {
+        let knockback = RadialImpulse::knockback(Vec2::ZERO, vec2(480.0, 360.0));
+
+        let right = knockback.velocity(vec2(10.0, 0.0));
+        let left = knockback.velocity(vec2(-10.0, -5.0));
+        assert!(right.abs_diff_eq(vec2(480.0, 360.0), 0.01));
+        assert!(left.abs_diff_eq(vec2(-480.0, 360.0), 0.01));
+        // Bodies above the hit are launched more steeply
+        assert!(knockback.velocity(vec2(10.0, 30.0)).y > 360.0);
+    }
+
+    #[test]
+    fn pull_fades_out_at_its_radius() {
+        let pull = RadialImpulse {
+            radius: 100.0,
+            ..RadialImpulse::new(Vec2::ZERO, -200.0)
+        };
+
+        assert!(pull
+            .velocity(vec2(0.0, 50.0))
+            .abs_diff_eq(vec2(0.0, -100.0), 0.01));
+        assert_eq!(pull.velocity(vec2(150.0, 0.0)), Vec2::ZERO);
+    }
+}
@@ -11,10 +11,15 @@ pub use rapier2d::prelude as rapier;
 pub use shape::*;
 
 pub mod filtering;
+mod joints;
+mod queries;
 mod shape;
+pub use joints::*;
+pub use queries::*;
 
 use crate::collisions::filtering::CollisionGroup;
 use crate::collisions::filtering::SolverGroup;
+use crate::core::utils::Rect;
 use crate::impl_system_param;
 use crate::prelude::*;
 
@@ -292,13 +297,20 @@ impl_system_param! {
         /// A collider is anything that can detect collisions in the world other than tiles, and
         /// must either be an [`Actor`] or [`Solid`] to participate in collision detection.
         colliders: CompMut<'a, Collider>,
+        /// Joints that connect the bodies of colliders, solids, and tiles.
+        joints: CompMut<'a, PhysicsJoint>,
         /// Contains the rapier collider handles for each map tile.
         tile_rapier_handles: CompMut<'a, TileRapierHandle>,
 
         tile_layers: Comp<'a, TileLayer>,
         tile_collision_kinds: Comp<'a, TileCollisionKind>,
+        tile_materials: Comp<'a, TileMaterial>,
         tile_dynamic_colliders: Comp<'a, TileDynamicCollider>,
         spawned_map_layer_metas: Comp<'a, SpawnedMapLayerMeta>,
+
+        /// The recent spatial queries, remembered while they are being debugged.
+        query_log: ResInit<'a, SpatialQueryLog>,
+        debug_settings: ResInit<'a, DebugSettings>,
     }
 }
 
@@ -542,6 +554,27 @@ pub enum TileCollisionKind {
     JumpThrough,
 }
 
+/// What a map tile is made of, which changes how bodies move on it and bounce off of it.
+///
+/// The physics of each material are set in the [`PhysicsMeta::materials`].
+#[derive(Default, PartialEq, Eq, Clone, Copy, Debug, HasSchema, Serialize, Deserialize)]
+#[repr(u8)]
+#[derive_type_data(SchemaDeserialize)]
+pub enum TileMaterial {
+    #[default]
+    Default,
+    /// Players slide further and items don't slow down as much.
+    Ice,
+    /// Bouncy things bounce higher.
+    Rubber,
+}
+
+impl TileMaterial {
+    pub fn is_default(&self) -> bool {
+        *self == Self::Default
+    }
+}
+
 impl TileCollisionKind {
     /// Get the solver group for tile, this is collision filtering group
     /// used against dynamic bodies that simulate physical collision.
@@ -583,6 +616,7 @@ impl<'a> CollisionWorld<'a> {
         puffin::profile_function!();
 
         self.sync_bodies(&*transforms, dynamic_bodies);
+        self.sync_joints();
         self.apply_simulation_commands(&mut *dynamic_bodies);
 
         let RapierContext {
@@ -1373,6 +1407,61 @@ impl<'a> CollisionWorld<'a> {
         false
     }
 
+    /// Get the solid that is squeezing a body with the given `shape` at `transform` into a solid
+    /// tile, or into another solid, by more than `threshold`.
+    ///
+    /// The body is squeezed if it is further than the threshold inside of the solid, and pushing it
+    /// out of the solid along the axis that it is the least inside of would push it into something
+    /// else solid.
+    pub fn squeezing_solid(
+        &self,
+        transform: Transform,
+        shape: ColliderShape,
+        threshold: f32,
+    ) -> Option<Entity> {
+        let rect = shape.bounding_box(transform);
+        for (solid_ent, solid) in self.entities.iter_with(&self.solids) {
+            if solid.disabled {
+                continue;
+            }
+            let solid_rect = Rect::new(solid.pos.x, solid.pos.y, solid.size.x, solid.size.y);
+            let penetration = rect.max.min(solid_rect.max) - rect.min.max(solid_rect.min);
+            if penetration.min_element() <= threshold {
+                continue;
+            }
+
+            // Push the body out of the solid the shortest way, and shrink it by the threshold so
+            // that just touching something doesn't count.
+            let away = rect.center() - solid_rect.center();
+            let push = if penetration.x < penetration.y {
+                vec2(penetration.x.copysign(away.x), 0.0)
+            } else {
+                vec2(0.0, penetration.y.copysign(away.y))
+            };
+            let half_extents = (rect.size() / 2.0 - threshold).max(Vec2::ZERO);
+            let center = rect.center() + push;
+            let blocked = self
+                .ctx
+                .query_pipeline
+                .intersection_with_shape(
+                    &self.ctx.rigid_body_set,
+                    &self.ctx.collider_set,
+                    &rapier::Isometry::translation(center.x, center.y),
+                    &rapier::Cuboid::new(rapier::Vector::new(half_extents.x, half_extents.y)),
+                    rapier::QueryFilter::new().predicate(&|_handle, collider| {
+                        let ent = RapierUserData::entity(collider.user_data);
+                        ent != solid_ent && self.is_solid(ent)
+                    }),
+                )
+                .is_some();
+            if blocked {
+                return Some(solid_ent);
+            }
+        }
+
+        None
+    }
+
     /// Returns the tile collision at the given point.
     ///
     /// > ⚠️ **Warning:** There is a slight difference to how `tile_collision_point` and
@@ -1439,6 +1528,41 @@ impl<'a> CollisionWorld<'a> {
             .unwrap_or_default()
     }
 
+    /// Get the material of the tiles colliding with the `shape` at the given `transform`.
+    ///
+    /// If the shape is touching tiles of more than one material, the material of any one of the
+    /// tiles that isn't [`TileMaterial::Default`] is returned.
+    pub fn tile_material(&self, transform: Transform, shape: ColliderShape) -> TileMaterial {
+        let mut material = TileMaterial::Default;
+        self.ctx.query_pipeline.intersections_with_shape(
+            &self.ctx.rigid_body_set,
+            &self.ctx.collider_set,
+            &(
+                transform.translation.truncate(),
+                transform.rotation.to_euler(EulerRot::XYZ).2,
+            )
+                .into(),
+            &*shape.shared_shape(),
+            rapier::QueryFilter::new().predicate(&|_handle, collider| {
+                let ent = RapierUserData::entity(collider.user_data);
+                self.tile_materials.contains(ent) && self.tile_collision_kinds.contains(ent)
+            }),
+            |handle| {
+                let ent = RapierUserData::entity(self.ctx.collider_set[handle].user_data);
+                material = self.tile_materials.get(ent).copied().unwrap_or_default();
+                material.is_default()
+            },
+        );
+        material
+    }
+
+    /// Cast a ray from `origin` in `direction` and get the distance to the first solid, or solid
+    /// tile, that it hits within `max_distance`.
+    pub fn cast_ray_solid(&self, origin: Vec2, direction: Vec2, max_distance: f32) -> Option<f32> {
+        self.raycast(origin, direction, max_distance, |ent| self.is_solid(ent))
+            .map(|hit| hit.distance)
+    }
+
     /// Get the collider for the given entity.
     pub fn get_collider(&self, actor: Entity) -> &Collider {
         assert!(self.actors.contains(actor));
@@ -0,0 +1,155 @@
+//! Joints between physics bodies, for building composite elements such as swinging maces, chained
+//! bombs, and rope bridges.
+//!
+//! A joint is an entity with a [`PhysicsJoint`] component that names the two bodies it connects.
+//! The rapier joint is created once both bodies are in the physics world, and removed when the
+//! joint entity is killed, or along with either of the bodies. Joints only pull on bodies that are
+//! simulated by rapier, which are [`DynamicBody`]s that are dynamic, but they can be anchored to
+//! any body, including kinematic bodies, solids, and map tiles.
+//!
+//! The rapier joints live in the [`RapierContext`], which is snapshotted along with the rest of the
+//! world, so joints are rolled back correctly in network matches.
+
+use super::*;
+
+/// The way that a [`PhysicsJoint`] holds its bodies together.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum JointKind {
+    /// The bodies are held together at the anchors, and can't move or turn relative to each
+    /// other.
+    #[default]
+    Fixed,
+    /// The bodies are held together at the anchors, but can turn around them, like a hinge.
+    Revolute,
+    /// The anchors can't get further apart than the given length, but can get closer, like a rope.
+    Rope { length: f32 },
+}
+
+/// A joint that connects two physics bodies.
+#[derive(HasSchema, Clone, Copy, Debug, Default)]
+pub struct PhysicsJoint {
+    /// The first body, which is usually the one that the second body hangs from.
+    pub body1: Entity,
+    pub body2: Entity,
+    pub kind: JointKind,
+    /// Where the joint is attached to the first body, relative to its position.
+    pub anchor1: Vec2,
+    /// Where the joint is attached to the second body, relative to its position.
+    pub anchor2: Vec2,
+    /// The handle to the rapier joint, if it has been created as of yet.
+    handle: Option<rapier::ImpulseJointHandle>,
+}
+
+impl PhysicsJoint {
+    /// Create a joint of the given kind, attached at the positions of both bodies.
+    pub fn new(kind: JointKind, body1: Entity, body2: Entity) -> Self {
+        Self {
+            body1,
+            body2,
+            kind,
+            ..default()
+        }
+    }
+
+    /// Create a hinge between the bodies, like the chain of a mace swinging around its anchor.
+    pub fn revolute(body1: Entity, body2: Entity) -> Self {
+        Self::new(JointKind::Revolute, body1, body2)
+    }
+
+    /// Create a rope of the given length between the bodies.
+    pub fn rope(body1: Entity, body2: Entity, length: f32) -> Self {
+        Self::new(JointKind::Rope { length }, body1, body2)
+    }
+
+    /// Set where the joint is attached to each body, relative to their positions.
+    pub fn with_anchors(mut self, anchor1: Vec2, anchor2: Vec2) -> Self {
+        self.anchor1 = anchor1;
+        self.anchor2 = anchor2;
+        self
+    }
+
+    /// Whether the rapier joint has been created.
+    pub fn is_connected(&self) -> bool {
+        self.handle.is_some()
+    }
+
+    /// Build the rapier joint data for the joint.
+    fn rapier_joint(&self) -> rapier::GenericJoint {
+        let anchor1 = rapier::Point::new(self.anchor1.x, self.anchor1.y);
+        let anchor2 = rapier::Point::new(self.anchor2.x, self.anchor2.y);
+        match self.kind {
+            JointKind::Fixed => rapier::FixedJointBuilder::new()
+                .local_anchor1(anchor1)
+                .local_anchor2(anchor2)
+                .build()
+                .into(),
+            JointKind::Revolute => rapier::RevoluteJointBuilder::new()
+                .local_anchor1(anchor1)
+                .local_anchor2(anchor2)
+                .build()
+                .into(),
+            JointKind::Rope { length } => rapier::RopeJointBuilder::new(length)
+                .local_anchor1(anchor1)
+                .local_anchor2(anchor2)
+                .build()
+                .into(),
+        }
+    }
+}
+
+impl<'a> CollisionWorld<'a> {
+    /// Get the handle to the rapier body of an actor, solid, or tile, if it has one yet.
+    pub fn rapier_body_handle(&self, entity: Entity) -> Option<rapier::RigidBodyHandle> {
+        self.colliders
+            .get(entity)
+            .and_then(|x| x.rapier_handle)
+            .or_else(|| self.solids.get(entity).and_then(|x| x.rapier_handle))
+            .or_else(|| self.tile_rapier_handles.get(entity).map(|x| **x))
+    }
+
+    /// Create the rapier joints for new [`PhysicsJoint`]s, and remove the joints of killed joint
+    /// entities.
+    pub(super) fn sync_joints(&mut self) {
+        puffin::profile_function!();
+
+        let dead_joints = self
+            .ctx
+            .impulse_joints
+            .iter()
+            .filter(|(_, joint)| {
+                !self
+                    .entities
+                    .is_alive(RapierUserData::entity(joint.data.user_data))
+            })
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        for handle in dead_joints {
+            self.ctx.impulse_joints.remove(handle, true);
+        }
+
+        let mut new_joints = Vec::new();
+        for (ent, joint) in self.entities.iter_with(&self.joints) {
+            // Joints are removed by rapier along with their bodies, so make sure the joint is still
+            // there.
+            if joint
+                .handle
+                .is_some_and(|x| self.ctx.impulse_joints.get(x).is_some())
+            {
+                continue;
+            }
+            let (Some(body1), Some(body2)) = (
+                self.rapier_body_handle(joint.body1),
+                self.rapier_body_handle(joint.body2),
+            ) else {
+                continue;
+            };
+            let mut data = joint.rapier_joint();
+            data.user_data = RapierUserData::from(ent);
+            new_joints.push((ent, body1, body2, data));
+        }
+        for (ent, body1, body2, data) in new_joints {
+            let handle = self.ctx.impulse_joints.insert(body1, body2, data, true);
+            self.joints.get_mut(ent).unwrap().handle = Some(handle);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Spatial queries against the collision world: ray casts, shape casts, and overlap tests.
+//!
+//! The queries are checked against every collider in the world, including actors, solids, and
+//! tiles, and take a filter to pick which of those count. Recent queries are remembered in the
+//! [`SpatialQueryLog`] while [`DebugSettings::show_spatial_queries`] is enabled, so that they can be
+//! drawn.
+
+use super::*;
+use crate::core::utils::Rect;
+
+/// How many frames a spatial query is drawn for when debugging.
+pub const SPATIAL_QUERY_DEBUG_FRAMES: u32 = 20;
+
+/// Something that a spatial query hit.
+#[derive(Clone, Copy, Debug)]
+pub struct QueryHit {
+    /// The entity of the collider that was hit.
+    pub entity: Entity,
+    /// How far the ray or shape travelled before it hit.
+    pub distance: f32,
+    /// Where the ray hit, or where the shape was when it hit.
+    pub point: Vec2,
+    /// The outward normal of the collider that was hit, where it was hit.
+    pub normal: Vec2,
+}
+
+/// A spatial query remembered for debug rendering.
+#[derive(Clone, Copy, Debug)]
+pub enum SpatialQuery {
+    /// A ray or shape cast from `start` that reached `end`, with whether it hit something there.
+    Cast { start: Vec2, end: Vec2, hit: bool },
+    /// An overlap test with a rectangle, with whether it found anything.
+    Overlap { rect: Rect, hit: bool },
+}
+
+/// Session resource with the recent spatial queries, for debug rendering.
+///
+/// Queries are made through a shared [`CollisionWorld`], so they are recorded through a cell.
+#[derive(HasSchema)]
+pub struct SpatialQueryLog {
+    /// The queries, with how many frames ago they were made.
+    pub queries: AtomicCell<Vec<(SpatialQuery, u32)>>,
+}
+
+impl Default for SpatialQueryLog {
+    fn default() -> Self {
+        Self {
+            queries: AtomicCell::new(Vec::new()),
+        }
+    }
+}
+
+impl Clone for SpatialQueryLog {
+    fn clone(&self) -> Self {
+        Self {
+            queries: AtomicCell::new(self.queries.borrow().clone()),
+        }
+    }
+}
+
+impl<'a> CollisionWorld<'a> {
+    /// Whether the entity is a solid, or a solid tile.
+    pub fn is_solid(&self, entity: Entity) -> bool {
+        self.solids.contains(entity)
+            || self.tile_collision_kinds.get(entity) == Some(&TileCollisionKind::Solid)
+    }
+
+    /// Remember a query to draw it, if spatial queries are being debugged.
+    fn log_query(&self, query: SpatialQuery) {
+        if self.debug_settings.show_spatial_queries {
+            self.query_log.queries.borrow_mut().push((query, 0));
+        }
+    }
+
+    /// Cast a ray from `origin` in `direction`, and get the first collider within `max_distance`
+    /// that passes the `filter`.
+    pub fn raycast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        filter: impl Fn(Entity) -> bool,
+    ) -> Option<QueryHit> {
+        let direction = direction.normalize_or_zero();
+        let ray = rapier::Ray::new(origin.into(), direction.into());
+        let hit = self
+            .ctx
+            .query_pipeline
+            .cast_ray_and_get_normal(
+                &self.ctx.rigid_body_set,
+                &self.ctx.collider_set,
+                &ray,
+                max_distance,
+                true,
+                rapier::QueryFilter::new().predicate(&|_handle, collider| {
+                    filter(RapierUserData::entity(collider.user_data))
+                }),
+            )
+            .map(|(handle, intersection)| QueryHit {
+                entity: RapierUserData::entity(self.ctx.collider_set[handle].user_data),
+                distance: intersection.time_of_impact,
+                point: origin + direction * intersection.time_of_impact,
+                normal: vec2(intersection.normal.x, intersection.normal.y),
+            });
+
+        self.log_query(SpatialQuery::Cast {
+            start: origin,
+            end: origin + direction * hit.map_or(max_distance, |x| x.distance),
+            hit: hit.is_some(),
+        });
+        hit
+    }
+
+    /// Move `shape` from `transform` in `direction`, and get the first collider within
+    /// `max_distance` that it runs into and that passes the `filter`.
+    ///
+    /// Colliders that the shape already overlaps at the start are hit at a distance of zero.
+    pub fn shapecast(
+        &self,
+        transform: Transform,
+        shape: ColliderShape,
+        direction: Vec2,
+        max_distance: f32,
+        filter: impl Fn(Entity) -> bool,
+    ) -> Option<QueryHit> {
+        let direction = direction.normalize_or_zero();
+        let start = transform.translation.xy();
+        let position =
+            rapier::Isometry::new(start.into(), transform.rotation.to_euler(EulerRot::XYZ).2);
+        let velocity = rapier::Vector::new(direction.x, direction.y);
+        let hit = self
+            .ctx
+            .query_pipeline
+            .cast_shape(
+                &self.ctx.rigid_body_set,
+                &self.ctx.collider_set,
+                &position,
+                &velocity,
+                &*shape.shared_shape(),
+                ShapeCastOptions::with_max_time_of_impact(max_distance),
+                rapier::QueryFilter::new().predicate(&|_handle, collider| {
+                    filter(RapierUserData::entity(collider.user_data))
+                }),
+            )
+            .map(|(handle, hit)| {
+                let collider = &self.ctx.collider_set[handle];
+                let normal = collider.position().rotation * hit.normal1.into_inner();
+                QueryHit {
+                    entity: RapierUserData::entity(collider.user_data),
+                    distance: hit.time_of_impact,
+                    point: start + direction * hit.time_of_impact,
+                    normal: vec2(normal.x, normal.y),
+                }
+            });
+
+        self.log_query(SpatialQuery::Cast {
+            start,
+            end: start + direction * hit.map_or(max_distance, |x| x.distance),
+            hit: hit.is_some(),
+        });
+        hit
+    }
+
+    /// Get every collider that overlaps the given rectangle and passes the `filter`.
+    pub fn overlap_aabb(&self, rect: Rect, filter: impl Fn(Entity) -> bool) -> Vec<Entity> {
+        let mut overlapping = Vec::new();
+        let half_extents = rect.size() / 2.0;
+        let position = rapier::Isometry::translation(rect.center().x, rect.center().y);
+        self.ctx.query_pipeline.intersections_with_shape(
+            &self.ctx.rigid_body_set,
+            &self.ctx.collider_set,
+            &position,
+            &rapier::Cuboid::new(rapier::Vector::new(half_extents.x, half_extents.y)),
+            rapier::QueryFilter::new()
+                .predicate(&|_handle, collider| filter(RapierUserData::entity(collider.user_data))),
+            |handle| {
+                overlapping.push(RapierUserData::entity(
+                    self.ctx.collider_set[handle].user_data,
+                ));
+                true
+            },
+        );
+
+        self.log_query(SpatialQuery::Overlap {
+            rect,
+            hit: !overlapping.is_empty(),
+        });
+        overlapping
+    }
+}
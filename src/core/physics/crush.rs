@@ -0,0 +1,86 @@
+//! Crushing players that get squeezed between a moving [`Solid`], such as a platform or a closing
+//! door, and the map.
+//!
+//! Kinematic bodies are pushed out of solids when they can be, so a player only stays inside of one
+//! when there is something else solid on the other side of them. Once they are further inside than
+//! the [`crush_threshold`][PhysicsMeta::crush_threshold], they are killed and a [`CrushEvent`] is
+//! recorded for the kill feed.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session.init_resource::<CrushEvents>();
+    session
+        .stages
+        .add_system_to_stage(PhysicsStage::Update, crush_players);
+}
+
+/// A player that was crushed.
+#[derive(Debug, Clone, Copy)]
+pub struct CrushEvent {
+    /// The player that was crushed.
+    pub player: PlayerIdx,
+    /// The solid that crushed them.
+    pub solid: Entity,
+    /// Where the player was crushed.
+    pub pos: Vec2,
+    /// How many frames ago the player was crushed.
+    pub age: u32,
+}
+
+/// Session resource with the recent [`CrushEvent`]s, shown in the kill feed.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct CrushEvents {
+    pub events: Vec<CrushEvent>,
+}
+
+/// Kill the players that are squeezed too far into a solid, and forget the old [`CrushEvent`]s.
+fn crush_players(
+    meta: Root<GameMeta>,
+    entities: Res<Entities>,
+    mut commands: Commands,
+    collision_world: CollisionWorld,
+    player_indexes: Comp<PlayerIdx>,
+    transforms: Comp<Transform>,
+    bodies: Comp<KinematicBody>,
+    invincibles: Comp<Invincibility>,
+    killed_players: Comp<PlayerKilled>,
+    mut crush_events: ResMut<CrushEvents>,
+) {
+    for event in &mut crush_events.events {
+        event.age += 1;
+    }
+    crush_events
+        .events
+        .retain(|event| event.age < KILL_FEED_LIFETIME);
+
+    let mut bitset = player_indexes.bitset().clone();
+    bitset.bit_and(transforms.bitset());
+    bitset.bit_and(bodies.bitset());
+    bitset.bit_andnot(invincibles.bitset());
+    bitset.bit_andnot(killed_players.bitset());
+
+    for player_ent in entities.iter_with_bitset(&bitset) {
+        let transform = *transforms.get(player_ent).unwrap();
+        let body = bodies.get(player_ent).unwrap();
+        if body.is_deactivated {
+            continue;
+        }
+        let Some(solid) = collision_world.squeezing_solid(
+            transform,
+            body.shape,
+            meta.core.physics.crush_threshold,
+        ) else {
+            continue;
+        };
+
+        let pos = transform.translation.xy();
+        commands.add(PlayerCommand::kill(player_ent, None));
+        crush_events.events.push(CrushEvent {
+            player: *player_indexes.get(player_ent).unwrap(),
+            solid,
+            pos,
+            age: 0,
+        });
+    }
+}
@@ -4,19 +4,60 @@ use std::collections::VecDeque;
 
 use crate::prelude::*;
 
+mod outline;
 mod state;
+pub use outline::*;
 pub use state::*;
 use turborand::GenCore;
 
-const PLAYER_COLORS: [Color; 4] = [
+/// The color used to tell each player apart.
+pub const PLAYER_COLORS: [Color; 4] = [
     Color::RED,
     Color::GREEN,
     Color::BLUE,
     Color::rgb(1.0, 0.0, 1.0),
 ];
 
+/// Player colors that can be told apart with red-green color blindness, from the Okabe-Ito
+/// palette.
+pub const PLAYER_COLORS_RED_GREEN: [Color; 4] = [
+    Color::rgb(0.90, 0.62, 0.0),
+    Color::rgb(0.34, 0.71, 0.91),
+    Color::rgb(0.94, 0.89, 0.26),
+    Color::rgb(0.80, 0.47, 0.65),
+];
+
+/// Player colors that can be told apart with blue-yellow color blindness, from the Okabe-Ito
+/// palette.
+pub const PLAYER_COLORS_BLUE_YELLOW: [Color; 4] = [
+    Color::rgb(0.84, 0.37, 0.0),
+    Color::rgb(0.0, 0.62, 0.45),
+    Color::rgb(0.80, 0.47, 0.65),
+    Color::WHITE,
+];
+
+/// The pattern used to tell each player apart when patterns are turned on.
+pub const PLAYER_PATTERNS: [PlayerPattern; 4] = [
+    PlayerPattern::Solid,
+    PlayerPattern::Stripes,
+    PlayerPattern::Dots,
+    PlayerPattern::Cross,
+];
+
+/// A pattern that players are marked with on top of their color, so that they can be told apart
+/// without telling their colors apart.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlayerPattern {
+    #[default]
+    Solid,
+    Stripes,
+    Dots,
+    Cross,
+}
+
 pub fn plugin(session: &mut SessionBuilder) {
     session.install_plugin(state::plugin);
+    outline::install(session);
 
     // Add other player systems
     session
@@ -27,6 +68,7 @@ pub fn plugin(session: &mut SessionBuilder) {
         .add_system_to_stage(CoreStage::PostUpdate, player_facial_animations)
         .add_system_to_stage(CoreStage::PostUpdate, equip_hats)
         .add_system_to_stage(CoreStage::Last, delete_dead_ai_swords)
+        .add_system_to_stage(CoreStage::Last, flicker_invincible_players)
         .add_system_to_stage(CoreStage::Last, update_player_layers);
 }
 
@@ -203,6 +245,13 @@ impl Emote {
 #[derive(Clone, HasSchema, Default)]
 pub struct PlayerKilled {
     pub hit_from: Option<Vec2>,
+    /// The player credited with the kill, which is the killed player themselves for suicides, or
+    /// [`None`] if nobody is to blame.
+    pub killer: Option<PlayerIdx>,
+    /// Whether the killer was on the same team as the killed player when they died.
+    pub team_kill: bool,
+    /// The other players that recently hit the killed player, and get an assist.
+    pub assists: Vec<PlayerIdx>,
 }
 
 /// Events that can be used to trigger player actions, such as killing, setting inventory, etc.
@@ -212,12 +261,31 @@ pub struct PlayerCommand;
 impl PlayerCommand {
     /// Kill a player.
     ///
+    /// The kill is credited to whoever last hit the player, if anybody did recently. Use
+    /// [`kill_by`][Self::kill_by] when the killer is known.
+    ///
     /// > **Note:** This doesn't despawn the player, it just puts the player into it's death animation.
     pub fn kill(player: Entity, hit_from: Option<Vec2>) -> StaticSystem<(), ()> {
+        Self::kill_by(player, hit_from, None)
+    }
+
+    /// Kill a player, crediting the kill to the `attacker` player entity.
+    ///
+    /// > **Note:** This doesn't despawn the player, it just puts the player into it's death animation.
+    pub fn kill_by(
+        player: Entity,
+        hit_from: Option<Vec2>,
+        attacker: Option<Entity>,
+    ) -> StaticSystem<(), ()> {
         (move |entities: Res<Entities>,
                mut players_killed: CompMut<PlayerKilled>,
                mut items_dropped: CompMut<ItemDropped>,
                mut inventories: CompMut<Inventory>,
+               mut event_log: ResMut<MatchEventLog>,
+               mut kill_feed: ResMutInit<KillFeed>,
+               recent_attackers: Comp<RecentAttackers>,
+               infection: Option<Res<InfectionState>>,
+               transforms: Comp<Transform>,
                player_indexes: Comp<PlayerIdx>| {
             if players_killed.contains(player) {
                 // No need to kill him again
@@ -232,6 +300,46 @@ impl PlayerCommand {
 
             debug!("Killing player: {}", idx.0);
 
+            if let Some(transform) = transforms.get(player) {
+                event_log.record(
+                    MatchEventKind::Death,
+                    Some(*idx),
+                    transform.translation.xy(),
+                );
+            }
+
+            // Fall back to whoever last knocked the player around, so that environmental deaths are
+            // credited to them.
+            let recent = recent_attackers.get(player);
+            let attacker = attacker.or_else(|| recent.and_then(|x| x.latest()));
+            let killer = attacker.and_then(|x| player_indexes.get(x)).copied();
+            let assists = recent
+                .into_iter()
+                .flat_map(|x| x.iter())
+                .filter_map(|x| player_indexes.get(x).copied())
+                .filter(|x| x != idx && Some(*x) != killer)
+                .collect::<Vec<_>>();
+            if let Some(killer_idx) = killer.filter(|x| x != idx) {
+                let pos = attacker
+                    .and_then(|x| transforms.get(x))
+                    .map(|x| x.translation.xy())
+                    .unwrap_or_default();
+                event_log.record(MatchEventKind::Kill, Some(killer_idx), pos);
+                for assist in &assists {
+                    event_log.record(MatchEventKind::Assist, Some(*assist), pos);
+                }
+                kill_feed.entries.push(KillFeedEntry {
+                    killer: killer_idx,
+                    assists: assists.clone(),
+                    victim: *idx,
+                    age: 0,
+                });
+            }
+            // Check the teams now, since the killed player may switch teams once they are dead.
+            let team_kill = killer.filter(|x| x != idx).is_some_and(|killer_idx| {
+                infection.is_some_and(|x| x.team(killer_idx) == x.team(*idx))
+            });
+
             // Drop any items the player was carrying
             let inventory = inventories.get(player).cloned().unwrap_or_default();
             if let Some(item) = inventory.0 {
@@ -243,7 +351,15 @@ impl PlayerCommand {
             // Update the inventory
             inventories.insert(player, Inventory(None));
 
-            players_killed.insert(player, PlayerKilled { hit_from });
+            players_killed.insert(
+                player,
+                PlayerKilled {
+                    hit_from,
+                    killer,
+                    team_kill,
+                    assists,
+                },
+            );
         })
         .system()
     }
@@ -400,6 +516,50 @@ impl FromWorld for PathfindingDebugLines {
     }
 }
 
+/// Pick the player that an AI controlled entity, such as a bot player or a boss, should go after.
+///
+/// The `current` target is kept for as long as the AI can still see it, otherwise a random player
+/// is picked among the ones that it can see. Players hidden behind smoke can't be targeted, and
+/// decoys look just like players, so they are chased until they are gone.
+pub fn pick_ai_target(
+    entities: &Entities,
+    player_indexes: &Comp<PlayerIdx>,
+    decoys: &Comp<Decoy>,
+    smoke_clouds: &Comp<SmokeCloud>,
+    transforms: &Comp<Transform>,
+    rng: &GlobalRng,
+    ai_ent: Entity,
+    current: Option<Entity>,
+) -> Option<Entity> {
+    let ai_pos = transforms.get(ai_ent)?.translation.truncate();
+    let can_see = |target: &Transform| {
+        !is_sight_blocked(
+            entities,
+            smoke_clouds,
+            transforms,
+            ai_pos,
+            target.translation.truncate(),
+        )
+    };
+
+    if let Some(current) = current {
+        if transforms.get(current).is_some_and(can_see) {
+            return Some(current);
+        }
+    }
+
+    let targets = entities
+        .iter_with(transforms)
+        .filter(|(ent, _)| player_indexes.contains(*ent) || decoys.contains(*ent))
+        .filter(|(ent, transform)| *ent != ai_ent && can_see(transform))
+        .map(|(ent, _)| ent)
+        .collect::<Vec<_>>();
+    if targets.is_empty() {
+        return None;
+    }
+    Some(targets[rng.gen_usize() % targets.len()])
+}
+
 fn player_ai_system(
     entities: Res<Entities>,
     nav_graph: ResMutInit<NavGraph>,
@@ -411,6 +571,8 @@ fn player_ai_system(
     pathfinding_debug_line: ResMutInit<PathfindingDebugLines>,
     mut paths: CompMut<Path2d>,
     bodies: Comp<KinematicBody>,
+    smoke_clouds: Comp<SmokeCloud>,
+    decoys: Comp<Decoy>,
     debug_settings: ResInit<DebugSettings>,
     rng: Res<GlobalRng>,
     time: Res<Time>,
@@ -444,29 +606,28 @@ fn player_ai_system(
             continue;
         }
 
-        let target_transform = match ai_player.target_player {
-            Some(target_player) if transforms.contains(target_player) => {
-                transforms.get(target_player).unwrap()
-            }
-            _ => {
-                let players = entities
-                    .iter_with((&player_indexes, &transforms))
-                    .filter(|(ent, _)| *ent != ai_ent)
-                    .collect::<Vec<_>>();
-                if players.is_empty() {
-                    continue;
-                }
-
-                let (target_player, (_, transform)) = players[rng.gen_usize() % players.len()];
-
-                ai_player.target_player = Some(target_player);
-                transform
-            }
+        let ai_pos = transform.translation.truncate();
+        let Some(target_player) = pick_ai_target(
+            &entities,
+            &player_indexes,
+            &decoys,
+            &smoke_clouds,
+            &transforms,
+            &rng,
+            ai_ent,
+            ai_player.target_player,
+        ) else {
+            // Stand still until someone comes out of hiding
+            ai_player.target_player = None;
+            ai_player.movement_buffer = None;
+            player_inputs.players[player_idx.0 as usize].control = default();
+            continue;
         };
+        ai_player.target_player = Some(target_player);
+        let target_transform = transforms.get(target_player).unwrap();
         let target_pos = target_transform.translation.truncate();
         let tile = (target_pos / map.tile_size).floor().as_ivec2();
         let target_node = NavNode(tile);
-        let ai_pos = transform.translation.truncate();
         let tile = (ai_pos / map.tile_size).floor().as_ivec2();
         let current_node = NavNode(tile);
 
@@ -1020,6 +1181,33 @@ fn player_facial_animations(
     }
 }
 
+/// The alpha of invincible players while they are faded out.
+const INVINCIBLE_FLICKER_ALPHA: f32 = 0.3;
+
+/// Fade players in and out while they have [`Invincibility`], so that everybody can see that they
+/// can't be hit. The player's layers and hat pick up the alpha through their attachments.
+fn flicker_invincible_players(
+    meta: Root<GameMeta>,
+    entities: Res<Entities>,
+    player_indexes: Comp<PlayerIdx>,
+    invincibles: Comp<Invincibility>,
+    mut sprites: CompMut<AtlasSprite>,
+) {
+    let interval = meta
+        .core
+        .config
+        .invincibility_flicker_interval
+        .as_secs_f32();
+    for (ent, (_, sprite)) in entities.iter_with((&player_indexes, &mut sprites)) {
+        let faded = invincibles.get(ent).is_some_and(|invincible| {
+            interval > 0.0 && (invincible.elapsed().as_secs_f32() / interval) as u32 % 2 == 0
+        });
+        sprite
+            .color
+            .set_a(if faded { INVINCIBLE_FLICKER_ALPHA } else { 1.0 });
+    }
+}
+
 /// System that reads the [`PlayerLayers`] component and updates the animated sprite banks to match
 /// the animations specified.
 fn update_player_layers(
@@ -0,0 +1,138 @@
+//! Colored outlines around players, so that they stand out against busy backgrounds.
+//!
+//! The outline is made of copies of the player's body sprite, tinted with the player's color and
+//! drawn just behind the body, nudged out a little in each direction. Outlines are turned on and
+//! sized in the accessibility settings.
+//!
+//! When player patterns are turned on, each player's pattern decides which sides of the outline
+//! are drawn and in what color, so that outlines can be told apart without their colors.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session.add_system_to_stage(CoreStage::Last, update_player_outlines);
+}
+
+/// The directions that the copies of the body sprite are nudged in to make the outline.
+const OUTLINE_DIRECTIONS: [Vec2; 4] = [Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y];
+
+/// How far behind the body the outline is drawn.
+const OUTLINE_Z_OFFSET: f32 = -0.005;
+
+/// One of the copies of a player's body sprite that make up their outline.
+#[derive(Clone, Copy, HasSchema, Default)]
+pub struct PlayerOutlinePart {
+    pub player: Entity,
+    /// The direction the copy is nudged in.
+    pub direction: Vec2,
+}
+
+/// Get the color of a player's outline, which is their team color in team modes and their own
+/// color otherwise.
+fn outline_color(
+    player: PlayerIdx,
+    infection: Option<&InfectionState>,
+    meta: &CoreMeta,
+    colors: &PlayerColorSettings,
+) -> Color {
+    match infection {
+        Some(infection) if infection.is_infected(player) => meta.infection.infected_color,
+        Some(_) => Color::WHITE,
+        None => colors.color(player),
+    }
+}
+
+/// Get the color of the side of an outline nudged in the given direction, with the player's
+/// pattern, or `None` if the pattern leaves that side out.
+fn pattern_color(pattern: PlayerPattern, direction: Vec2, color: Color) -> Option<Color> {
+    let horizontal = direction.y == 0.0;
+    match pattern {
+        PlayerPattern::Solid => Some(color),
+        PlayerPattern::Stripes => horizontal.then_some(color),
+        PlayerPattern::Dots => (!horizontal).then_some(color),
+        PlayerPattern::Cross if horizontal => Some(color),
+        PlayerPattern::Cross => Some(Color::BLACK),
+    }
+}
+
+fn update_player_outlines(
+    meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    storage: Res<Storage>,
+    infection: Option<Res<InfectionState>>,
+    player_indexes: Comp<PlayerIdx>,
+    mut outline_parts: CompMut<PlayerOutlinePart>,
+    mut transforms: CompMut<Transform>,
+    mut sprites: CompMut<AtlasSprite>,
+) {
+    let (settings, colors) = storage
+        .get::<Settings>()
+        .map(|x| (x.player_outlines.clone(), x.player_colors.clone()))
+        .unwrap_or_default();
+
+    // Remove the outlines of players that are gone. The outline entities exist whether or not
+    // outlines are turned on, since the setting is local and every client has to spawn the same
+    // entities in network matches.
+    let mut outlined = Vec::new();
+    let stale = entities
+        .iter_with(&outline_parts)
+        .filter(|(_, part)| {
+            let alive = entities.is_alive(part.player);
+            if alive {
+                outlined.push(part.player);
+            }
+            !alive
+        })
+        .map(|(ent, _)| ent)
+        .collect::<Vec<_>>();
+    for ent in stale {
+        entities.kill(ent);
+    }
+
+    // Give new players an outline
+    let new_players = entities
+        .iter_with(&player_indexes)
+        .map(|(ent, _)| ent)
+        .filter(|ent| !outlined.contains(ent))
+        .collect::<Vec<_>>();
+    for player in new_players {
+        for direction in OUTLINE_DIRECTIONS {
+            let ent = entities.create();
+            outline_parts.insert(ent, PlayerOutlinePart { player, direction });
+        }
+    }
+
+    // Copy the body sprite to the outline
+    for (ent, part) in entities.iter_with(&outline_parts) {
+        if !settings.enabled {
+            sprites.remove(ent);
+            continue;
+        }
+        let (Some(player_idx), Some(body_sprite), Some(transform)) = (
+            player_indexes.get(part.player),
+            sprites.get(part.player).cloned(),
+            transforms.get(part.player).copied(),
+        ) else {
+            continue;
+        };
+        let color = outline_color(*player_idx, infection.as_deref(), &meta.core, &colors);
+        let Some(mut color) = pattern_color(colors.pattern(*player_idx), part.direction, color)
+        else {
+            sprites.remove(ent);
+            continue;
+        };
+        color.set_a(body_sprite.color.a());
+
+        let mut part_transform = transform;
+        part_transform.translation +=
+            (part.direction * settings.thickness).extend(OUTLINE_Z_OFFSET);
+        transforms.insert(ent, part_transform);
+        sprites.insert(
+            ent,
+            AtlasSprite {
+                color,
+                ..body_sprite
+            },
+        );
+    }
+}
@@ -49,11 +49,16 @@ pub fn plugin(session: &mut SessionBuilder) {
     crouch::install(session);
     dead::install(session);
     default::install(session);
+    dive::install(session);
     drive_jellyfish::install(session);
     idle::install(session);
     incapacitated::install(session);
     ragdoll::install(session);
+    ride_mount::install(session);
     midair::install(session);
+    stunned::install(session);
+    swim::install(session);
+    swing::install(session);
     walk::install(session);
 }
 
@@ -70,6 +75,7 @@ fn use_drop_or_grab_items_system(id: Ustr) -> StaticSystem<(), ()> {
            player_states: Comp<PlayerState>,
            assets: Res<AssetServer>,
            items: Comp<Item>,
+           stolen_items: Comp<StolenItem>,
            collision_world: CollisionWorld,
            mut inventories: CompMut<Inventory>,
            mut audio_center: ResMut<AudioCenter>,
@@ -103,6 +109,8 @@ fn use_drop_or_grab_items_system(id: Ustr) -> StaticSystem<(), ()> {
                         // TODO: Use the ItemGrabbed tag for this detection after fixing the ItemGrabbed handling
                         // Filter out any items held by other players
                         .filter(|ent| !held_items.contains(ent))
+                        // Filter out items carried off by thief fish
+                        .filter(|ent| !stolen_items.contains(*ent))
                         .collect::<Vec<_>>();
 
                     // Grab the first item we are touching
@@ -3,9 +3,14 @@ use super::*;
 pub mod crouch;
 pub mod dead;
 pub mod default;
+pub mod dive;
 pub mod drive_jellyfish;
 pub mod idle;
 pub mod incapacitated;
 pub mod midair;
 pub mod ragdoll;
+pub mod ride_mount;
+pub mod stunned;
+pub mod swim;
+pub mod swing;
 pub mod walk;
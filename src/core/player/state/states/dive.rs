@@ -0,0 +1,234 @@
+use super::*;
+
+pub static ID: Lazy<Ustr> = Lazy::new(|| ustr("core::dive"));
+
+pub fn install(session: &mut SessionBuilder) {
+    PlayerState::add_player_state_transition_system(session, player_state_transition);
+    PlayerState::add_player_state_update_system(session, handle_player_state);
+    PlayerState::add_player_state_update_system(session, use_drop_or_grab_items_system(*ID));
+    session
+        .stages
+        .add_system_to_stage(CoreStage::PostUpdate, update_air_meters);
+}
+
+/// Component tracking how much air a player has left while diving.
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct AirMeter {
+    /// The amount of air left.
+    pub remaining: Duration,
+    /// The time until the next bubble is let out.
+    pub bubble_timer: Duration,
+    /// The entity used to render the meter above the player.
+    pub bar_ent: Entity,
+}
+
+/// Marker component for the entity that renders an [`AirMeter`] above its player.
+#[derive(Clone, Debug, HasSchema, Default)]
+pub struct AirMeterBar;
+
+pub fn player_state_transition(
+    entities: Res<Entities>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    assets: Res<AssetServer>,
+    mut player_states: CompMut<PlayerState>,
+    transforms: Comp<Transform>,
+    water_volumes: Comp<WaterVolume>,
+) {
+    for (_ent, (player_idx, player_state, transform)) in
+        entities.iter_with((&player_indexes, &mut player_states, &transforms))
+    {
+        if player_state.current != *ID {
+            continue;
+        }
+        let meta_handle = player_inputs.players[player_idx.0 as usize].selected_player;
+        let meta = assets.get(meta_handle);
+        let control = &player_inputs.players[player_idx.0 as usize].control;
+
+        let position = transform.translation.truncate();
+        let water = water_volume_at(&entities, &water_volumes, &transforms, position);
+
+        if control.ragdoll_just_pressed {
+            player_state.current = *ragdoll::ID;
+        } else if let Some((_, water_rect)) = water {
+            // Go back to swimming once we have floated up to the surface
+            let float_height = water_rect.top() - meta.body_size.y / 4.0;
+            if position.y >= float_height && control.move_direction.y >= -0.5 {
+                player_state.current = *swim::ID;
+            }
+        } else {
+            player_state.current = *midair::ID;
+        }
+    }
+}
+
+pub fn handle_player_state(
+    entities: Res<Entities>,
+    game_meta: Root<GameMeta>,
+    mutators: Res<Mutators>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    player_states: Comp<PlayerState>,
+    assets: Res<AssetServer>,
+    mut sprites: CompMut<AtlasSprite>,
+    mut animations: CompMut<AnimationBankSprite>,
+    mut bodies: CompMut<KinematicBody>,
+) {
+    let players = entities.iter_with((
+        &player_states,
+        &player_indexes,
+        &mut animations,
+        &mut sprites,
+        &mut bodies,
+    ));
+    for (_player_ent, (player_state, player_idx, animation, sprite, body)) in players {
+        if player_state.current != *ID {
+            continue;
+        }
+        let meta_handle = player_inputs.players[player_idx.0 as usize].selected_player;
+        let meta = assets.get(meta_handle);
+        let control = &player_inputs.players[player_idx.0 as usize].control;
+
+        if player_state.age == 0 {
+            animation.current = "rise".into();
+        }
+
+        // Swim in any direction, floating up when not swimming down
+        let mut target_velocity = control.move_direction
+            * meta.stats.dive_speed
+            * mutators.move_speed_scale(&game_meta.core.mutators);
+        if control.move_direction.y > -0.5 {
+            target_velocity.y = target_velocity.y.max(meta.stats.buoyancy);
+        }
+        body.velocity += (target_velocity - body.velocity) * 0.15;
+
+        // Point in movement direction
+        if control.move_direction.x > 0.0 {
+            sprite.flip_x = false;
+        } else if control.move_direction.x < 0.0 {
+            sprite.flip_x = true;
+        }
+    }
+}
+
+/// Drains the air of diving players, drowning them when they run out, and refills it when they
+/// come up for air.
+fn update_air_meters(
+    mut entities: ResMutInit<Entities>,
+    mut commands: Commands,
+    time: Res<Time>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    player_states: Comp<PlayerState>,
+    killed_players: Comp<PlayerKilled>,
+    assets: Res<AssetServer>,
+    water_volumes: Comp<WaterVolume>,
+    mut air_meters: CompMut<AirMeter>,
+    mut air_meter_bars: CompMut<AirMeterBar>,
+    mut transforms: CompMut<Transform>,
+    mut attachments: CompMut<Attachment>,
+    mut paths: CompMut<Path2d>,
+) {
+    const BAR_WIDTH: f32 = 24.0;
+    const BAR_OFFSET: f32 = 36.0;
+    // How many times faster air refills than it drains
+    const REFILL_RATE: u32 = 3;
+
+    // Remove the meters of players that are gone
+    let orphaned_bars = entities
+        .iter_with((&air_meter_bars, &attachments))
+        .filter(|(_, (_, attachment))| {
+            !entities.is_alive(attachment.entity) || !air_meters.contains(attachment.entity)
+        })
+        .map(|(ent, _)| ent)
+        .collect::<Vec<_>>();
+    for bar_ent in orphaned_bars {
+        entities.kill(bar_ent);
+    }
+
+    // Give every player an air meter
+    let new_players = entities
+        .iter_with(&player_indexes)
+        .filter(|(ent, _)| !air_meters.contains(*ent))
+        .map(|(ent, idx)| (ent, *idx))
+        .collect::<Vec<_>>();
+    for (player_ent, player_idx) in new_players {
+        let meta_handle = player_inputs.players[player_idx.0 as usize].selected_player;
+        let meta = assets.get(meta_handle);
+        let bar_ent = entities.create();
+        air_meter_bars.insert(bar_ent, AirMeterBar);
+        transforms.insert(bar_ent, default());
+        attachments.insert(
+            bar_ent,
+            Attachment {
+                entity: player_ent,
+                offset: vec3(0.0, BAR_OFFSET, 1.0),
+                ..default()
+            },
+        );
+        air_meters.insert(
+            player_ent,
+            AirMeter {
+                remaining: meta.stats.air_time,
+                bubble_timer: Duration::ZERO,
+                bar_ent,
+            },
+        );
+    }
+
+    let waters = entities
+        .iter_with((&water_volumes, &transforms))
+        .map(|(_, (volume, transform))| (volume.clone(), volume.rect(transform.translation)))
+        .collect::<Vec<_>>();
+
+    let delta = time.delta();
+    for (player_ent, (player_idx, player_state, air_meter)) in
+        entities.iter_with((&player_indexes, &player_states, &mut air_meters))
+    {
+        let meta_handle = player_inputs.players[player_idx.0 as usize].selected_player;
+        let meta = assets.get(meta_handle);
+        let max_air = meta.stats.air_time;
+
+        if player_state.current == *ID {
+            air_meter.remaining = air_meter.remaining.saturating_sub(delta);
+
+            if air_meter.remaining.is_zero() && !killed_players.contains(player_ent) {
+                commands.add(PlayerCommand::kill(player_ent, None));
+            }
+
+            // Let out bubbles
+            air_meter.bubble_timer = air_meter.bubble_timer.saturating_sub(delta);
+            let position = transforms.get(player_ent).unwrap().translation;
+            if let Some((volume, _)) = waters
+                .iter()
+                .find(|(_, rect)| rect.contains(position.truncate()))
+                .filter(|_| air_meter.bubble_timer.is_zero())
+            {
+                air_meter.bubble_timer = volume.bubble_interval;
+                let mouth = position + vec3(0.0, meta.body_size.y / 4.0, 1.0);
+                commands.add(spawn_bubble(mouth, volume.clone()));
+            }
+        } else {
+            air_meter.remaining = (air_meter.remaining + REFILL_RATE * delta).min(max_air);
+        }
+
+        // Only show the meter while the player is missing air
+        if air_meter.remaining < max_air {
+            let fraction = air_meter.remaining.as_secs_f32() / max_air.as_secs_f32();
+            paths.insert(
+                air_meter.bar_ent,
+                Path2d {
+                    color: Color::CYAN,
+                    points: vec![
+                        vec2(-BAR_WIDTH / 2.0, 0.0),
+                        vec2(-BAR_WIDTH / 2.0 + BAR_WIDTH * fraction, 0.0),
+                    ],
+                    thickness: 3.0,
+                    ..default()
+                },
+            );
+        } else {
+            paths.remove(air_meter.bar_ent);
+        }
+    }
+}
@@ -38,6 +38,8 @@ pub fn player_state_transition(
 
 pub fn handle_player_state(
     entities: Res<Entities>,
+    game_meta: Root<GameMeta>,
+    mutators: Res<Mutators>,
     player_inputs: Res<MatchInputs>,
     player_indexes: Comp<PlayerIdx>,
     player_states: Comp<PlayerState>,
@@ -70,10 +72,13 @@ pub fn handle_player_state(
             audio_center.play_sound(meta.sounds.jump, meta.sounds.jump_volume);
 
             // Move up
-            body.velocity.y = meta.stats.jump_speed;
+            body.velocity.y = meta.stats.jump_speed * mutators.jump_scale(&game_meta.core.mutators);
         }
 
         let mut slide_factor = 1.;
+        if let Some(material) = game_meta.core.physics.materials.get(body.ground_material) {
+            slide_factor = 1. / material.player_slide;
+        }
         for (slippery_ent, slippery_meta) in entities.iter_with(&slippery) {
             if collision_world
                 .actor_collisions(player_ent)
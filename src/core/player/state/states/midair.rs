@@ -8,16 +8,25 @@ pub fn install(session: &mut SessionBuilder) {
     PlayerState::add_player_state_update_system(session, use_drop_or_grab_items_system(*ID));
 }
 
+/// How fast a player was falling on their last frame in the air.
+#[derive(Clone, Copy, Debug, Default, HasSchema)]
+pub struct FallSpeed(pub f32);
+
 pub fn player_state_transition(
     entities: Res<Entities>,
+    game_meta: Root<GameMeta>,
     player_inputs: Res<MatchInputs>,
     player_indexes: Comp<PlayerIdx>,
     assets: Res<AssetServer>,
+    fall_speeds: Comp<FallSpeed>,
     mut player_states: CompMut<PlayerState>,
     bodies: Comp<KinematicBody>,
+    transforms: Comp<Transform>,
     mut audio_center: ResMut<AudioCenter>,
+    mut camera_shake_events: ResMutInit<CameraShakeEvents>,
+    mut commands: Commands,
 ) {
-    for (_ent, (player_idx, player_state, body)) in
+    for (player_ent, (player_idx, player_state, body)) in
         entities.iter_with((&player_indexes, &mut player_states, &bodies))
     {
         let meta_handle = player_inputs.players[player_idx.0 as usize].selected_player;
@@ -30,6 +39,22 @@ pub fn player_state_transition(
         if body.is_on_ground {
             // Play land sound
             audio_center.play_sound(meta.sounds.land, meta.sounds.land_volume);
+            // Shake the camera on hard landings
+            let fall_speed = fall_speeds.get(player_ent).map(|x| x.0).unwrap_or_default();
+            if fall_speed >= game_meta.core.camera_shake.landing_min_speed {
+                camera_shake_events.send(CameraShakeKind::Landing);
+            }
+            // Kick up dust
+            let particles = &game_meta.core.particles;
+            if let (Some(dust), Some(transform)) = (
+                particles.landing_dust.clone().option(),
+                transforms.get(player_ent),
+            ) {
+                if fall_speed >= particles.landing_dust_min_speed {
+                    let feet = transform.translation - Vec3::Y * meta.body_size.y / 2.0;
+                    commands.add(ParticleEmitter::spawn_burst(dust, feet, false));
+                }
+            }
             // Switch to idle state
             player_state.current = *idle::ID;
         } else if control.ragdoll_just_pressed {
@@ -41,6 +66,8 @@ pub fn player_state_transition(
 
 pub fn handle_player_state(
     entities: Res<Entities>,
+    game_meta: Root<GameMeta>,
+    mutators: Res<Mutators>,
     player_inputs: Res<MatchInputs>,
     player_indexes: Comp<PlayerIdx>,
     player_states: Comp<PlayerState>,
@@ -48,6 +75,7 @@ pub fn handle_player_state(
     mut sprites: CompMut<AtlasSprite>,
     mut animations: CompMut<AnimationBankSprite>,
     mut bodies: CompMut<KinematicBody>,
+    mut fall_speeds: CompMut<FallSpeed>,
 ) {
     let players = entities.iter_with((
         &player_states,
@@ -56,10 +84,11 @@ pub fn handle_player_state(
         &mut sprites,
         &mut bodies,
     ));
-    for (_player_ent, (player_state, player_idx, animation, sprite, body)) in players {
+    for (player_ent, (player_state, player_idx, animation, sprite, body)) in players {
         if player_state.current != *ID {
             continue;
         }
+        fall_speeds.insert(player_ent, FallSpeed(-body.velocity.y));
         let meta_handle = player_inputs.players[player_idx.0 as usize].selected_player;
         let meta = assets.get(meta_handle);
         let control = &player_inputs.players[player_idx.0 as usize].control;
@@ -76,11 +105,13 @@ pub fn handle_player_state(
         }
 
         // Walk in movement direction
-        body.velocity.x += meta.stats.accel_air_speed * control.move_direction.x;
+        let speed_scale = mutators.move_speed_scale(&game_meta.core.mutators);
+        let air_speed = meta.stats.air_speed * speed_scale;
+        body.velocity.x += meta.stats.accel_air_speed * speed_scale * control.move_direction.x;
         if control.move_direction.x.is_sign_positive() {
-            body.velocity.x = body.velocity.x.min(meta.stats.air_speed);
+            body.velocity.x = body.velocity.x.min(air_speed);
         } else {
-            body.velocity.x = body.velocity.x.max(-meta.stats.air_speed);
+            body.velocity.x = body.velocity.x.max(-air_speed);
         }
 
         if control.move_direction.x == 0.0 {
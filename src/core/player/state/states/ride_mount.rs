@@ -0,0 +1,46 @@
+use super::*;
+
+pub static ID: Lazy<Ustr> = Lazy::new(|| ustr("core::ride_mount"));
+
+pub fn install(session: &mut SessionBuilder) {
+    PlayerState::add_player_state_transition_system(session, player_state_transition);
+    PlayerState::add_player_state_update_system(session, handle_player_state);
+}
+
+pub fn player_state_transition(
+    entities: Res<Entities>,
+    riding: Comp<RidingMount>,
+    mut player_states: CompMut<PlayerState>,
+    killed_players: Comp<PlayerKilled>,
+) {
+    for (player_ent, player_state) in entities.iter_with(&mut player_states) {
+        if riding.contains(player_ent) {
+            if killed_players.contains(player_ent) {
+                continue;
+            }
+            if player_state.current != *ID {
+                player_state.current = *ID;
+            }
+        } else if player_state.current == *ID {
+            // Riders always come off the mount into the air
+            player_state.current = *midair::ID;
+        }
+    }
+}
+
+pub fn handle_player_state(
+    entities: Res<Entities>,
+    player_states: Comp<PlayerState>,
+    mut animations: CompMut<AnimationBankSprite>,
+) {
+    for (_player_ent, (player_state, animation)) in
+        entities.iter_with((&player_states, &mut animations))
+    {
+        if player_state.current != *ID {
+            continue;
+        }
+
+        // The mount does the moving, so the rider just sits still
+        animation.current = "idle".into();
+    }
+}
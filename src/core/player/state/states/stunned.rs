@@ -0,0 +1,100 @@
+use super::*;
+
+pub static ID: Lazy<Ustr> = Lazy::new(|| ustr("core::stunned"));
+
+pub fn install(session: &mut SessionBuilder) {
+    PlayerState::add_player_state_transition_system(session, player_state_transition);
+    PlayerState::add_player_state_update_system(session, handle_player_state);
+}
+
+/// The states that a player can be knocked out of into hitstun.
+static STUN_FROM: Lazy<[Ustr; 6]> = Lazy::new(|| {
+    [
+        *idle::ID,
+        *walk::ID,
+        *midair::ID,
+        *crouch::ID,
+        *swim::ID,
+        *dive::ID,
+    ]
+});
+
+/// Component added to players that have been knocked back by a [`DamageRegion`].
+///
+/// The player can't be controlled until the hitstun runs out, other than steering their launch
+/// slightly with directional influence.
+#[derive(Clone, HasSchema, Default)]
+pub struct Stunned {
+    pub timer: Timer,
+}
+
+impl Stunned {
+    pub fn new(hitstun: Duration) -> Self {
+        Self {
+            timer: Timer::new(hitstun, TimerMode::Once),
+        }
+    }
+}
+
+pub fn player_state_transition(
+    entities: Res<Entities>,
+    killed_players: Comp<PlayerKilled>,
+    stunned: Comp<Stunned>,
+    mut player_states: CompMut<PlayerState>,
+) {
+    for (player_ent, (player_state, stunned)) in entities.iter_with((&mut player_states, &stunned))
+    {
+        if killed_players.contains(player_ent) {
+            continue;
+        }
+
+        if player_state.current == *ID {
+            if stunned.timer.finished() {
+                player_state.current = *midair::ID;
+            }
+        } else if STUN_FROM.contains(&player_state.current) && !stunned.timer.finished() {
+            player_state.current = *ID;
+        }
+    }
+}
+
+pub fn handle_player_state(
+    entities: Res<Entities>,
+    time: Res<Time>,
+    game_meta: Root<GameMeta>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    player_states: Comp<PlayerState>,
+    mut stunned: CompMut<Stunned>,
+    mut animations: CompMut<AnimationBankSprite>,
+    mut bodies: CompMut<KinematicBody>,
+) {
+    let influence = game_meta.core.physics.knockback_influence;
+
+    let mut recovered = Vec::new();
+    for (player_ent, (player_state, player_idx, stunned, animation, body)) in entities.iter_with((
+        &player_states,
+        &player_indexes,
+        &mut stunned,
+        &mut animations,
+        &mut bodies,
+    )) {
+        stunned.timer.tick(time.delta());
+
+        if player_state.current == *ID {
+            if player_state.age == 0 {
+                animation.current = "ragdoll".into();
+            }
+
+            // Let the player steer their launch a little with the held direction
+            let control = &player_inputs.players[player_idx.0 as usize].control;
+            body.velocity += control.move_direction * influence;
+        } else if stunned.timer.finished() {
+            recovered.push(player_ent);
+        }
+    }
+
+    for player_ent in recovered {
+        stunned.remove(player_ent);
+    }
+}
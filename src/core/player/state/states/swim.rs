@@ -0,0 +1,133 @@
+use super::*;
+
+pub static ID: Lazy<Ustr> = Lazy::new(|| ustr("core::swim"));
+
+pub fn install(session: &mut SessionBuilder) {
+    PlayerState::add_player_state_transition_system(session, player_state_transition);
+    PlayerState::add_player_state_update_system(session, handle_player_state);
+    PlayerState::add_player_state_update_system(session, use_drop_or_grab_items_system(*ID));
+}
+
+/// The states that a player will start swimming from when they enter water.
+static ENTER_FROM: Lazy<[Ustr; 4]> = Lazy::new(|| [*idle::ID, *walk::ID, *midair::ID, *crouch::ID]);
+
+pub fn player_state_transition(
+    entities: Res<Entities>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    mut player_states: CompMut<PlayerState>,
+    transforms: Comp<Transform>,
+    water_volumes: Comp<WaterVolume>,
+) {
+    for (_ent, (player_idx, player_state, transform)) in
+        entities.iter_with((&player_indexes, &mut player_states, &transforms))
+    {
+        let in_water = water_volume_at(
+            &entities,
+            &water_volumes,
+            &transforms,
+            transform.translation.truncate(),
+        )
+        .is_some();
+
+        if ENTER_FROM.contains(&player_state.current) {
+            if in_water {
+                player_state.current = *ID;
+            }
+            continue;
+        }
+
+        if player_state.current != *ID {
+            continue;
+        }
+
+        let control = &player_inputs.players[player_idx.0 as usize].control;
+
+        if control.ragdoll_just_pressed {
+            player_state.current = *ragdoll::ID;
+        } else if !in_water {
+            player_state.current = *midair::ID;
+        } else if control.move_direction.y < -0.5 {
+            player_state.current = *dive::ID;
+        }
+    }
+}
+
+pub fn handle_player_state(
+    entities: Res<Entities>,
+    game_meta: Root<GameMeta>,
+    mutators: Res<Mutators>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    player_states: Comp<PlayerState>,
+    assets: Res<AssetServer>,
+    transforms: Comp<Transform>,
+    water_volumes: Comp<WaterVolume>,
+    mut sprites: CompMut<AtlasSprite>,
+    mut animations: CompMut<AnimationBankSprite>,
+    mut bodies: CompMut<KinematicBody>,
+    mut audio_center: ResMut<AudioCenter>,
+) {
+    let players = entities.iter_with((
+        &player_states,
+        &player_indexes,
+        &transforms,
+        &mut animations,
+        &mut sprites,
+        &mut bodies,
+    ));
+    for (_player_ent, (player_state, player_idx, transform, animation, sprite, body)) in players {
+        if player_state.current != *ID {
+            continue;
+        }
+        let meta_handle = player_inputs.players[player_idx.0 as usize].selected_player;
+        let meta = assets.get(meta_handle);
+        let control = &player_inputs.players[player_idx.0 as usize].control;
+
+        let position = transform.translation.truncate();
+        let Some((_, water_rect)) =
+            water_volume_at(&entities, &water_volumes, &transforms, position)
+        else {
+            continue;
+        };
+
+        animation.current = if control.move_direction.x == 0.0 {
+            "idle".into()
+        } else {
+            "walk".into()
+        };
+
+        if control.jump_just_pressed {
+            audio_center.play_sound(meta.sounds.jump, meta.sounds.jump_volume);
+            body.velocity.y =
+                meta.stats.swim_jump_speed * mutators.jump_scale(&game_meta.core.mutators);
+        } else {
+            // Float so that the player's head sticks out of the water
+            let float_height = water_rect.top() - meta.body_size.y / 4.0;
+            let target_velocity = ((float_height - position.y) * 8.0)
+                .clamp(-meta.stats.swim_speed, meta.stats.swim_speed);
+            body.velocity.y += (target_velocity - body.velocity.y) * 0.2;
+        }
+
+        // Swim in movement direction
+        let speed_scale = mutators.move_speed_scale(&game_meta.core.mutators);
+        let swim_speed = meta.stats.swim_speed * speed_scale;
+        body.velocity.x += meta.stats.accel_swim_speed * speed_scale * control.move_direction.x;
+        body.velocity.x = body.velocity.x.clamp(-swim_speed, swim_speed);
+
+        if control.move_direction.x == 0.0 {
+            if body.velocity.x.is_sign_positive() {
+                body.velocity.x = (body.velocity.x - meta.stats.slowdown).max(0.0);
+            } else {
+                body.velocity.x = (body.velocity.x + meta.stats.slowdown).min(0.0);
+            }
+        }
+
+        // Point in movement direction
+        if control.move_direction.x > 0.0 {
+            sprite.flip_x = false;
+        } else if control.move_direction.x < 0.0 {
+            sprite.flip_x = true;
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use super::*;
+
+pub static ID: Lazy<Ustr> = Lazy::new(|| ustr("core::swing"));
+
+/// How many frames a player has to be in the air before they can grab a rope, so that they don't
+/// grab the rope they just jumped off of right away.
+const REGRAB_FRAMES: u64 = 15;
+
+pub fn install(session: &mut SessionBuilder) {
+    PlayerState::add_player_state_transition_system(session, player_state_transition);
+    PlayerState::add_player_state_update_system(session, handle_player_state);
+}
+
+pub fn player_state_transition(
+    entities: Res<Entities>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    stunned: Comp<Stunned>,
+    ropes: Comp<Rope>,
+    rope_segments: Comp<RopeSegment>,
+    transforms: Comp<Transform>,
+    mut bodies: CompMut<KinematicBody>,
+    mut hanging: CompMut<HangingOnRope>,
+    mut player_states: CompMut<PlayerState>,
+) {
+    for (player_ent, (player_idx, player_state)) in
+        entities.iter_with((&player_indexes, &mut player_states))
+    {
+        if let Some(hold) = hanging.get(player_ent).copied() {
+            // Let go of the rope when the player dies or is hit, or the piece they were holding is
+            // gone
+            if killed_players.contains(player_ent)
+                || stunned.contains(player_ent)
+                || !entities.is_alive(hold.segment)
+            {
+                hanging.remove(player_ent);
+                if let Some(body) = bodies.get_mut(player_ent) {
+                    body.is_deactivated = false;
+                }
+            } else if player_state.current != *ID {
+                player_state.current = *ID;
+            }
+            continue;
+        }
+        if player_state.current == *ID {
+            player_state.current = *midair::ID;
+            continue;
+        }
+
+        // Grab a rope by holding up while jumping or falling past it
+        let control = &player_inputs.players[player_idx.0 as usize].control;
+        if player_state.current != *midair::ID
+            || player_state.age < REGRAB_FRAMES
+            || control.move_direction.y <= 0.5
+        {
+            continue;
+        }
+        let Some(pos) = transforms.get(player_ent).map(|x| x.translation.xy()) else {
+            continue;
+        };
+        let closest = entities
+            .iter_with((&rope_segments, &transforms))
+            .filter_map(|(segment, (rope_segment, transform))| {
+                let rope = ropes.get(rope_segment.rope)?;
+                let distance = transform.translation.xy().distance(pos);
+                (distance <= rope.grab_distance).then_some((segment, *rope_segment, distance))
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((segment, rope_segment, _)) = closest else {
+            continue;
+        };
+
+        hanging.insert(
+            player_ent,
+            HangingOnRope {
+                rope: rope_segment.rope,
+                segment,
+                last_pos: transforms.get(segment).unwrap().translation.xy(),
+            },
+        );
+        if let Some(body) = bodies.get_mut(player_ent) {
+            body.is_deactivated = true;
+            body.velocity = Vec2::ZERO;
+        }
+        player_state.current = *ID;
+    }
+}
+
+pub fn handle_player_state(
+    entities: Res<Entities>,
+    time: Res<Time>,
+    player_inputs: Res<MatchInputs>,
+    player_indexes: Comp<PlayerIdx>,
+    player_states: Comp<PlayerState>,
+    ropes: Comp<Rope>,
+    mut hanging: CompMut<HangingOnRope>,
+    mut dynamic_bodies: CompMut<DynamicBody>,
+    mut bodies: CompMut<KinematicBody>,
+    mut transforms: CompMut<Transform>,
+    mut sprites: CompMut<AtlasSprite>,
+    mut animations: CompMut<AnimationBankSprite>,
+) {
+    let players = entities
+        .iter_with((&player_states, &player_indexes, &hanging))
+        .filter(|(_, (player_state, _, _))| player_state.current == *ID)
+        .map(|(ent, (_, player_idx, hold))| (ent, *player_idx, *hold))
+        .collect::<Vec<_>>();
+
+    for (player_ent, player_idx, hold) in players {
+        let (Some(rope), Some(segment_pos)) = (
+            ropes.get(hold.rope),
+            transforms.get(hold.segment).map(|x| x.translation.xy()),
+        ) else {
+            continue;
+        };
+        let control = &player_inputs.players[player_idx.0 as usize].control;
+        let swing_velocity =
+            (segment_pos - hold.last_pos) / time.delta().as_secs_f32().max(f32::EPSILON);
+
+        if let Some(animation) = animations.get_mut(player_ent) {
+            animation.current = "rise".into();
+        }
+
+        // Jump off with the speed of the swing, or just let go when holding down
+        if control.jump_just_pressed || control.move_direction.y < -0.5 {
+            hanging.remove(player_ent);
+            let body = bodies.get_mut(player_ent).unwrap();
+            body.is_deactivated = false;
+            body.velocity = swing_velocity;
+            if control.jump_just_pressed {
+                body.velocity.y = body.velocity.y.max(0.0) + rope.jump_speed;
+            }
+            continue;
+        }
+
+        // Swing the rope in the direction the player is holding
+        if control.move_direction.x != 0.0 {
+            if let Some(dynamic_body) = dynamic_bodies.get_mut(hold.segment) {
+                let push = control.move_direction.x.signum() * rope.swing_speed;
+                dynamic_body.push_simulation_command(Box::new(
+                    move |body: &mut rapier::RigidBody| {
+                        let velocity = *body.linvel() + rapier::Vector::new(push, 0.0);
+                        body.set_linvel(velocity, true);
+                    },
+                ));
+            }
+            if let Some(sprite) = sprites.get_mut(player_ent) {
+                sprite.flip_x = control.move_direction.x < 0.0;
+            }
+        }
+
+        // Hang from the rope
+        let flip = sprites.get(player_ent).is_some_and(|x| x.flip_x);
+        let mut offset = rope.hang_offset;
+        if flip {
+            offset.x = -offset.x;
+        }
+        let transform = transforms.get_mut(player_ent).unwrap();
+        transform.translation.x = segment_pos.x + offset.x;
+        transform.translation.y = segment_pos.y + offset.y;
+        hanging.get_mut(player_ent).unwrap().last_pos = segment_pos;
+    }
+}
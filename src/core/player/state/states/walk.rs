@@ -38,6 +38,8 @@ pub fn player_state_transition(
 
 pub fn handle_player_state(
     entities: Res<Entities>,
+    game_meta: Root<GameMeta>,
+    mutators: Res<Mutators>,
     player_inputs: Res<MatchInputs>,
     player_indexes: Comp<PlayerIdx>,
     player_states: Comp<PlayerState>,
@@ -73,21 +75,17 @@ pub fn handle_player_state(
             audio_center.play_sound(meta.sounds.jump, meta.sounds.jump_volume);
 
             // Move up
-            body.velocity.y = meta.stats.jump_speed;
+            body.velocity.y = meta.stats.jump_speed * mutators.jump_scale(&game_meta.core.mutators);
         }
 
         // Walk in movement direction
-        body.velocity.x += meta.stats.accel_walk_speed * control.move_direction.x;
+        let speed_scale = mutators.move_speed_scale(&game_meta.core.mutators);
+        let walk_speed = meta.stats.walk_speed * speed_scale;
+        body.velocity.x += meta.stats.accel_walk_speed * speed_scale * control.move_direction.x;
         if control.move_direction.x.is_sign_positive() {
-            body.velocity.x = body
-                .velocity
-                .x
-                .min(meta.stats.walk_speed * control.move_direction.x);
+            body.velocity.x = body.velocity.x.min(walk_speed * control.move_direction.x);
         } else {
-            body.velocity.x = body
-                .velocity
-                .x
-                .max(meta.stats.walk_speed * control.move_direction.x);
+            body.velocity.x = body.velocity.x.max(walk_speed * control.move_direction.x);
         }
 
         // Point in movement direction
@@ -0,0 +1,38 @@
+//! Reduced motion, for players that are sensitive to shaking and flashing.
+//!
+//! When reduced motion is turned on in the accessibility settings, the camera doesn't shake, the
+//! screen doesn't flash white for nearby explosions, and explosions play their animation slowly
+//! enough that their frames don't strobe.
+//!
+//! The setting is local to each client, so it only changes how the match looks and never what
+//! happens in it.
+
+use crate::prelude::*;
+
+/// Whether reduced motion is turned on in the settings.
+pub fn is_reduced_motion(storage: &Storage) -> bool {
+    storage.get::<Settings>().is_some_and(|x| x.reduced_motion)
+}
+
+/// Get the animation of an explosion with the given number of frames.
+///
+/// With reduced motion, the animation is slowed down to last for the whole lifetime of the
+/// explosion, instead of flashing through its frames.
+pub fn explosion_animation(
+    frames: u32,
+    fps: f32,
+    lifetime: f32,
+    storage: &Storage,
+) -> AnimatedSprite {
+    let fps = if is_reduced_motion(storage) && lifetime > 0.0 {
+        fps.min(frames as f32 / lifetime)
+    } else {
+        fps
+    };
+    AnimatedSprite {
+        frames: (0..frames).collect(),
+        fps,
+        repeat: false,
+        ..default()
+    }
+}
@@ -42,41 +42,238 @@ impl RoundScoringState {
 #[derive(HasSchema, Clone, Default, Debug)]
 pub struct MatchScore {
     /// Map player to score, if no entry is 0.
-    player_score: HashMap<PlayerIdx, u32>,
+    ///
+    /// Scores can go negative from [`ScorePenaltiesMeta`] penalties.
+    player_score: HashMap<PlayerIdx, i32>,
 
     /// How many rounds have completed this match
     rounds_completed: u32,
+
+    /// How many times in a row each player has killed each other player without being killed by
+    /// them in return, by `(killer, victim)`.
+    kill_streaks: HashMap<(PlayerIdx, PlayerIdx), u32>,
 }
 
 impl MatchScore {
     /// Get player's score
-    pub fn score(&self, player: PlayerIdx) -> u32 {
+    pub fn score(&self, player: PlayerIdx) -> i32 {
         self.player_score.get(&player).map_or(0, |s| *s)
     }
 
+    /// Take the given number of points away from the player's score.
+    pub fn penalize(&mut self, player: PlayerIdx, points: u32) {
+        *self.player_score.entry(player).or_default() -= points as i32;
+    }
+
     /// Mark round as completed and increment score of winner. None should be provided
     /// on a draw.
     pub fn complete_round(&mut self, winner: Option<PlayerIdx>) {
+        self.complete_team_round(winner.as_slice());
+    }
+
+    /// Mark round as completed and increment the score of every winner, for modes where a whole
+    /// team wins the round. An empty slice should be provided on a draw.
+    pub fn complete_team_round(&mut self, winners: &[PlayerIdx]) {
         self.rounds_completed += 1;
 
-        // Increment winner's score if not a draw
-        if let Some(winner) = winner {
-            if let Some(score) = self.player_score.get_mut(&winner) {
+        for winner in winners {
+            if let Some(score) = self.player_score.get_mut(winner) {
                 *score += 1;
             } else {
-                self.player_score.insert(winner, 1);
+                self.player_score.insert(*winner, 1);
             }
         }
     }
 
+    /// Record a kill between two players for the rivalry between them, returning the callout it
+    /// earns, if any.
+    pub fn record_rivalry_kill(
+        &mut self,
+        killer: PlayerIdx,
+        victim: PlayerIdx,
+    ) -> Option<RivalryKind> {
+        let was_dominated = self
+            .kill_streaks
+            .insert((victim, killer), 0)
+            .is_some_and(|x| x >= DOMINATION_KILLS);
+        let streak = self.kill_streaks.entry((killer, victim)).or_default();
+        *streak += 1;
+
+        if was_dominated {
+            Some(RivalryKind::Revenge)
+        } else if *streak == DOMINATION_KILLS {
+            Some(RivalryKind::Domination)
+        } else {
+            None
+        }
+    }
+
+    /// Add a player that joined part way through the match to the score table, with no points.
+    pub fn add_player(&mut self, player: PlayerIdx) {
+        self.player_score.entry(player).or_default();
+    }
+
     /// How many rounds have been played in this match
     pub fn rounds_completed(&self) -> u32 {
         self.rounds_completed
     }
 }
 
+/// Tracks the slow motion replay of the final kill of a round.
+#[derive(HasSchema, Clone, Default)]
+pub struct KillCamState {
+    /// Counts down until the kill cam is over. Is `None` if it hasn't started.
+    pub timer: Option<Timer>,
+}
+
+/// How many kills in a row on the same player, without them getting one back, count as
+/// dominating them.
+pub const DOMINATION_KILLS: u32 = 3;
+
+/// How many frames a [`RivalryCallout`] is shown for.
+pub const RIVALRY_CALLOUT_LIFETIME: u32 = 150;
+
+/// A notable kill between two rivals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RivalryKind {
+    /// The killer got back at a player that was dominating them.
+    Revenge,
+    /// The killer has killed the victim [`DOMINATION_KILLS`] times in a row.
+    Domination,
+}
+
+/// A [`RivalryKind`] kill to call out on the HUD and by the announcer.
+#[derive(Clone, Copy, Debug)]
+pub struct RivalryCallout {
+    pub kind: RivalryKind,
+    pub killer: PlayerIdx,
+    pub victim: PlayerIdx,
+    /// How many frames ago the kill happened.
+    pub age: u32,
+}
+
+/// Session resource with the recent [`RivalryCallout`]s.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct RivalryCallouts {
+    pub callouts: Vec<RivalryCallout>,
+}
+
+/// Marker for killed players whose kill has been scored for penalties and rivalries.
+#[derive(HasSchema, Clone, Default)]
+pub struct KillScored;
+
+/// Install the kill scoring for penalties and rivalries, which apply in every game mode.
+pub fn install_kill_scoring(session: &mut SessionBuilder) {
+    session.init_resource::<RivalryCallouts>();
+    session
+        .add_system_to_stage(CoreStage::First, age_rivalry_callouts)
+        .add_system_to_stage(CoreStage::PostUpdate, score_kills);
+}
+
 pub fn session_plugin(session: &mut SessionBuilder) {
-    session.add_system_to_stage(CoreStage::PostUpdate, round_end);
+    session
+        .add_system_to_stage(CoreStage::PostUpdate, round_end)
+        .add_system_to_stage(CoreStage::PostUpdate, kill_cam);
+}
+
+fn age_rivalry_callouts(mut rivalry_callouts: ResMut<RivalryCallouts>) {
+    for callout in &mut rivalry_callouts.callouts {
+        callout.age += 1;
+    }
+    rivalry_callouts
+        .callouts
+        .retain(|x| x.age < RIVALRY_CALLOUT_LIFETIME);
+}
+
+/// Take points away from players that killed themselves or their teammates, and call out revenge
+/// kills and dominations.
+fn score_kills(
+    meta: Root<GameMeta>,
+    mode: Res<GameMode>,
+    entities: Res<Entities>,
+    killed_players: Comp<PlayerKilled>,
+    player_indices: Comp<PlayerIdx>,
+    mut scored: CompMut<KillScored>,
+    mut score: ResMutInit<MatchScore>,
+    mut rivalry_callouts: ResMut<RivalryCallouts>,
+) {
+    let penalties = meta
+        .core
+        .config
+        .score_penalties
+        .get(&*mode)
+        .copied()
+        .unwrap_or_default();
+
+    for (ent, (player_idx, killed)) in entities.iter_with((&player_indices, &killed_players)) {
+        if scored.contains(ent) {
+            continue;
+        }
+        scored.insert(ent, KillScored);
+
+        let Some(killer) = killed.killer else {
+            continue;
+        };
+        if killer == *player_idx {
+            score.penalize(killer, penalties.suicide);
+        } else if killed.team_kill {
+            score.penalize(killer, penalties.team_kill);
+        } else if let Some(kind) = score.record_rivalry_kill(killer, *player_idx) {
+            rivalry_callouts.callouts.push(RivalryCallout {
+                kind,
+                killer,
+                victim: *player_idx,
+                age: 0,
+            });
+        }
+    }
+}
+
+/// Play the final kill of the round in slow motion, zoomed in on the last player standing.
+pub fn kill_cam(
+    meta: Root<GameMeta>,
+    time: Res<Time>,
+    entities: Res<Entities>,
+    killed_players: Comp<PlayerKilled>,
+    player_indices: Comp<PlayerIdx>,
+    mut camera_states: CompMut<CameraState>,
+    mut time_scale: ResMutInit<TimeScale>,
+    mut state: ResMutInit<KillCamState>,
+) {
+    if let Some(timer) = state.timer.as_mut() {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            time_scale.0 = 1.0;
+            for camera_state in camera_states.iter_mut() {
+                camera_state.focus = None;
+            }
+        }
+        return;
+    }
+
+    let players = entities
+        .iter_with(&player_indices)
+        .map(|(ent, _)| ent)
+        .collect::<Vec<_>>();
+    if players.len() < 2 {
+        // Single player match - the round never ends.
+        return;
+    }
+
+    // Wait for the kill that leaves one or fewer players alive
+    let mut alive_players = players.iter().filter(|ent| !killed_players.contains(**ent));
+    let last_player = alive_players.next().copied();
+    if alive_players.next().is_some() {
+        return;
+    }
+
+    state.timer = Some(Timer::new(meta.core.config.kill_cam_time, TimerMode::Once));
+    time_scale.0 = meta.core.config.kill_cam_time_scale;
+    if let Some(last_player) = last_player {
+        for camera_state in camera_states.iter_mut() {
+            camera_state.focus = Some(last_player);
+        }
+    }
 }
 
 pub fn round_end(
@@ -85,6 +282,8 @@ pub fn round_end(
     entities: Res<Entities>,
     rng: Res<GlobalRng>,
     map_pool: Res<MapPool>,
+    player_inputs: Res<MatchInputs>,
+    mut map_vote: ResMut<MapVote>,
     mut score: ResMutInit<MatchScore>,
     mut sessions: ResMut<Sessions>,
     mut session_options: ResMut<SessionOptions>,
@@ -93,6 +292,7 @@ pub fn round_end(
     mut scoring_menu: ResMut<ScoringMenuState>,
     killed_players: Comp<PlayerKilled>,
     player_indices: Comp<PlayerIdx>,
+    afk_players: Res<AfkPlayers>,
     #[cfg(not(target_arch = "wasm32"))] syncing_info: Option<Res<SyncingInfo>>,
 ) {
     // Count players so we can avoid ending round if it's a one player match
@@ -133,12 +333,15 @@ pub fn round_end(
 
     // There are one or fewer players alive if we have not already returned from function
 
+    // AFK players can't win the round
+    let winner = last_player_or_draw.filter(|(idx, _)| !afk_players.is_afk(*idx));
+
     // Ready to score the round?
     if state.should_score_round() {
         state.round_scored = true;
-        score.complete_round(last_player_or_draw.map(|x| x.0));
+        score.complete_round(winner.map(|x| x.0));
 
-        if let Some((_, winner_ent)) = last_player_or_draw {
+        if let Some((_, winner_ent)) = winner {
             // commands.add(PlayerCommand::won_round(winner));
             commands.add(spawn_win_indicator(winner_ent));
         }
@@ -149,66 +352,31 @@ pub fn round_end(
             TimerMode::Once,
         ));
     } else if state.transition_timers_done() {
-        // post-score linger timer complete, go to next round if all players confirmed transition
-
-        // Is round transition sycnrhonized on all clients in network play?
-        // Will evaluate to true in local play.
-        #[allow(unused_assignments)]
-        let mut round_transition_synchronized = false;
-
-        // If in network play and determined a prev frame round should end on:
-        #[allow(unused_variables)]
-        if let Some(end_net_frame) = state.network_round_end_frame {
-            // check if this frame is confirmed by all players.
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                round_transition_synchronized = match syncing_info {
-                    Some(syncing_info) => end_net_frame <= syncing_info.last_confirmed_frame(),
-                    None => true,
-                };
-            }
-        } else {
-            // Network frame for round end not yet recorded (or in local only)
-
-            // Randomize map and save MapPool to be used for transition
-            let mut map_pool = map_pool.clone();
-            map_pool.randomize_current_map(&rng);
-            state.next_maps = Some(map_pool);
-
-            // Save current predicted frame for round end.
-            // Will not follow through with transition until this frame is confirmed
-            // by all players in network play.
-            #[cfg(not(target_arch = "wasm32"))]
-            if let Some(syncing_info) = syncing_info {
-                state.network_round_end_frame = Some(syncing_info.current_frame());
-            } else {
-                // No sync info - must be local and sychronized.
-                round_transition_synchronized = true;
-            }
-        }
-
-        // Wasm32 is always local, can transition now.
+        // post-score linger timer complete, vote on the next map and go to next round if all
+        // players confirmed transition
+        let Some(next_maps) = map_vote.update(&map_pool, &rng, &player_inputs, &meta, time.delta())
+        else {
+            return;
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let next_maps =
+            synchronize_round_transition(&mut state, next_maps, syncing_info.as_deref());
         #[cfg(target_arch = "wasm32")]
-        {
-            round_transition_synchronized = true;
-        }
-
-        if round_transition_synchronized {
-            if score.rounds_completed % meta.core.config.rounds_between_intermission == 0 {
-                scoring_menu.active = true;
-                scoring_menu.match_score = score.clone();
-                scoring_menu.next_maps = state.next_maps.clone();
-
-                session_options.active = false;
-            } else {
-                // Not at intermission, tranisition immediately
-
-                // Use maps originally determined on synchronized transition frame
-                let next_maps = state.next_maps.clone().unwrap();
-                sessions.add_command(Box::new(|sessions: &mut Sessions| {
-                    sessions.restart_game(Some(next_maps), false);
-                }));
-            }
+        let next_maps = synchronize_round_transition(&mut state, next_maps);
+
+        if let Some(next_maps) = next_maps {
+            commands.add(complete_round_report(winner.map(|x| x.0)));
+
+            // Use maps originally determined on synchronized transition frame
+            transition_to_next_round(
+                next_maps,
+                &score,
+                &meta,
+                &mut scoring_menu,
+                &mut session_options,
+                &mut sessions,
+            );
         }
     } else if state.timer.is_none() {
         // Scoring timer does not exist, start a new one
@@ -222,3 +390,98 @@ pub fn round_end(
         // Timer still ticking
     }
 }
+
+/// Get the maps to move on to once the end of the round is confirmed by all players.
+///
+/// The next maps are saved the first time this is called for a round. In network play, [`None`]
+/// is returned until the frame that happened on has been confirmed by the remote players, so that
+/// every client transitions on the same frame.
+pub fn synchronize_round_transition(
+    state: &mut RoundScoringState,
+    next_maps: MapPool,
+    #[cfg(not(target_arch = "wasm32"))] syncing_info: Option<&SyncingInfo>,
+) -> Option<MapPool> {
+    // Is round transition sycnrhonized on all clients in network play?
+    // Will evaluate to true in local play.
+    #[allow(unused_assignments)]
+    let mut round_transition_synchronized = false;
+
+    // If in network play and determined a prev frame round should end on:
+    #[allow(unused_variables)]
+    if let Some(end_net_frame) = state.network_round_end_frame {
+        // check if this frame is confirmed by all players.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            round_transition_synchronized = match syncing_info {
+                Some(syncing_info) => end_net_frame <= syncing_info.last_confirmed_frame(),
+                None => true,
+            };
+        }
+    } else {
+        // Network frame for round end not yet recorded (or in local only)
+
+        // Save MapPool to be used for transition
+        state.next_maps = Some(next_maps);
+
+        // Save current predicted frame for round end.
+        // Will not follow through with transition until this frame is confirmed
+        // by all players in network play.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(syncing_info) = syncing_info {
+            state.network_round_end_frame = Some(syncing_info.current_frame());
+        } else {
+            // No sync info - must be local and sychronized.
+            round_transition_synchronized = true;
+        }
+    }
+
+    // Wasm32 is always local, can transition now.
+    #[cfg(target_arch = "wasm32")]
+    {
+        round_transition_synchronized = true;
+    }
+
+    round_transition_synchronized.then(|| state.next_maps.clone().unwrap())
+}
+
+/// Move on to the next round with the given maps, or open the scoring menu instead if it is time
+/// for an intermission.
+pub fn transition_to_next_round(
+    next_maps: MapPool,
+    score: &MatchScore,
+    meta: &GameMeta,
+    scoring_menu: &mut ScoringMenuState,
+    session_options: &mut SessionOptions,
+    sessions: &mut Sessions,
+) {
+    if score.rounds_completed % meta.core.config.rounds_between_intermission == 0 {
+        scoring_menu.active = true;
+        scoring_menu.match_score = score.clone();
+        scoring_menu.next_maps = Some(next_maps);
+
+        session_options.active = false;
+    } else {
+        // Not at intermission, tranisition immediately
+        sessions.add_command(Box::new(|sessions: &mut Sessions| {
+            sessions.restart_game(Some(next_maps), false);
+        }));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn penalties_can_take_scores_below_zero() {
+        let mut score = MatchScore::default();
+        score.penalize(PlayerIdx(1), 2);
+        score.complete_round(Some(PlayerIdx(0)));
+        score.complete_round(Some(PlayerIdx(1)));
+        score.penalize(PlayerIdx(0), 1);
+
+        assert_eq!(score.score(PlayerIdx(0)), 0);
+        assert_eq!(score.score(PlayerIdx(1)), -1);
+        assert_eq!(score.rounds_completed(), 2);
+    }
+}
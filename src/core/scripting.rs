@@ -0,0 +1,146 @@
+//! Helpers for map elements written in Lua.
+//!
+//! Element plugins are Lua scripts listed in an element's `plugin` field, or in the `plugins` of a
+//! pack. They run inside the match session and can only reach the match through the `entities`,
+//! `components`, `resources` and `assets` APIs, so they can spawn entities and insert any
+//! component that has a schema, like [`KinematicBody`], [`AtlasSprite`] or [`DamageRegion`], but
+//! can't touch the file system or the rest of the game.
+//!
+//! Some things are awkward to do from a script, so this module adds components for them:
+//!
+//! - [`ScriptCollider`] sets the shape of a [`KinematicBody`], since scripts can't create enum
+//!   values like [`ColliderShape`].
+//! - [`CollisionEvents`] subscribes an entity to collisions: every frame it is filled with the
+//!   entities that it is touching.
+//! - [`ScriptedItem`] turns [`ItemUsed`] into an event, removing it at the end of the frame so
+//!   that scripts see each use once.
+//! - [`SpawnElementRequest`] spawns any element by name through the [`ElementRegistry`].
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    ScriptCollider::register_schema();
+    CollisionEvents::register_schema();
+    ScriptedItem::register_schema();
+    SpawnElementRequest::register_schema();
+
+    session
+        .stages
+        .add_system_to_stage(CoreStage::First, update_collision_events)
+        .add_system_to_stage(CoreStage::PostUpdate, spawn_requested_elements)
+        .add_system_to_stage(CoreStage::PostUpdate, apply_script_colliders)
+        .add_system_to_stage(CoreStage::Last, clear_scripted_items_used);
+}
+
+/// Component that sets the collider shape of an entity's [`KinematicBody`].
+///
+/// The body gets a circle if `diameter` is set, and a rectangle of `size` otherwise. The
+/// component is removed once the shape has been applied, so it can be inserted again to change
+/// the shape.
+#[derive(Clone, Copy, Debug, Default, HasSchema)]
+#[repr(C)]
+pub struct ScriptCollider {
+    pub size: Vec2,
+    pub diameter: f32,
+}
+
+impl ScriptCollider {
+    /// Get the collider shape that this component describes.
+    pub fn shape(&self) -> ColliderShape {
+        if self.diameter > 0.0 {
+            ColliderShape::Circle {
+                diameter: self.diameter,
+            }
+        } else {
+            ColliderShape::Rectangle { size: self.size }
+        }
+    }
+}
+
+/// Component with the entities that an entity with a body is colliding with this frame.
+///
+/// Only entities that have this component get their collisions collected.
+#[derive(Clone, Debug, Default, HasSchema)]
+pub struct CollisionEvents {
+    pub entities: SVec<Entity>,
+}
+
+/// Marker component for items whose behavior is written in a script.
+///
+/// The [`ItemUsed`] component of these items is removed at the end of every frame.
+#[derive(Clone, Copy, Debug, Default, HasSchema)]
+#[repr(C)]
+pub struct ScriptedItem;
+
+/// Component that spawns the element with the given name at the entity's [`Transform`].
+///
+/// The entity is removed once the element has been spawned.
+#[derive(Clone, Copy, Debug, Default, HasSchema)]
+#[repr(C)]
+pub struct SpawnElementRequest {
+    pub name: Ustr,
+}
+
+fn update_collision_events(
+    entities: Res<Entities>,
+    collision_world: CollisionWorld,
+    mut collision_events: CompMut<CollisionEvents>,
+) {
+    for (entity, events) in entities.iter_with(&mut collision_events) {
+        events.entities.clear();
+        for other in collision_world.actor_collisions(entity) {
+            events.entities.push(other);
+        }
+    }
+}
+
+fn apply_script_colliders(
+    entities: Res<Entities>,
+    mut script_colliders: CompMut<ScriptCollider>,
+    mut bodies: CompMut<KinematicBody>,
+) {
+    let mut applied = Vec::new();
+    for (entity, (collider, body)) in entities.iter_with((&script_colliders, &mut bodies)) {
+        body.shape = collider.shape();
+        applied.push(entity);
+    }
+    for entity in applied {
+        script_colliders.remove(entity);
+    }
+}
+
+fn spawn_requested_elements(
+    mut entities: ResMut<Entities>,
+    assets: Res<AssetServer>,
+    registry: Res<ElementRegistry>,
+    requests: Comp<SpawnElementRequest>,
+    transforms: Comp<Transform>,
+    mut commands: Commands,
+) {
+    let mut spawned = Vec::new();
+    for (entity, request) in entities.iter_with(&requests) {
+        let transform = transforms.get(entity).copied().unwrap_or_default();
+        match registry.spawn_by_name(&request.name, transform, &assets) {
+            Some(spawn) => commands.add(spawn),
+            None => warn!("Can't spawn unknown element `{}`", request.name),
+        }
+        spawned.push(entity);
+    }
+    for entity in spawned {
+        entities.kill(entity);
+    }
+}
+
+fn clear_scripted_items_used(
+    entities: Res<Entities>,
+    scripted_items: Comp<ScriptedItem>,
+    mut items_used: CompMut<ItemUsed>,
+) {
+    let used = entities
+        .iter_with((&scripted_items, &items_used))
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    for entity in used {
+        items_used.remove(entity);
+    }
+}
@@ -0,0 +1,156 @@
+//! Trails drawn behind fast moving things, so that their path is easy to follow.
+//!
+//! Thrown items and bullets get a [`Trail`] automatically. The trail records where the entity has
+//! been while it moves faster than the [`TrailMeta::min_speed`], and shrinks away once it slows
+//! down.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session
+        .add_system_to_stage(CoreStage::Last, add_trails)
+        .add_system_to_stage(CoreStage::Last, update_trails);
+}
+
+/// How trails look.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct TrailMeta {
+    pub color: Color,
+    pub thickness: f32,
+    /// How many frames of positions the trail reaches back.
+    pub length: u32,
+    /// How fast an entity has to move for its trail to grow, in pixels per second.
+    pub min_speed: f32,
+}
+
+/// Records the recent positions of an entity to draw a trail behind it.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct Trail {
+    /// The recorded positions, oldest first.
+    pub points: Vec<Vec2>,
+    /// The entity that the trail's [`Path2d`] is drawn on, separate from the entity itself so that
+    /// the trail doesn't turn with it.
+    pub path_entity: Option<Entity>,
+}
+
+/// Marker for the entities that [`Trail`]s are drawn on.
+#[derive(HasSchema, Clone, Copy, Debug, Default)]
+pub struct TrailPath;
+
+/// Give thrown items and bullets a trail.
+fn add_trails(
+    entities: Res<Entities>,
+    items_thrown: Comp<ItemThrown>,
+    bullets: Comp<Bullet>,
+    mut trails: CompMut<Trail>,
+) {
+    let mut needs_trail = items_thrown.bitset().clone();
+    needs_trail.bit_or(bullets.bitset());
+    let new = entities
+        .iter_with_bitset(&needs_trail)
+        .filter(|ent| !trails.contains(*ent))
+        .collect::<Vec<_>>();
+    for ent in new {
+        trails.insert(ent, Trail::default());
+    }
+}
+
+/// Record the positions of entities with trails, and draw the trails.
+fn update_trails(
+    meta: Root<GameMeta>,
+    mut entities: ResMutInit<Entities>,
+    items_thrown: Comp<ItemThrown>,
+    bullets: Comp<Bullet>,
+    mut trails: CompMut<Trail>,
+    mut trail_paths: CompMut<TrailPath>,
+    mut transforms: CompMut<Transform>,
+    mut paths: CompMut<Path2d>,
+) {
+    let TrailMeta {
+        color,
+        thickness,
+        length,
+        min_speed,
+    } = meta.core.trails;
+
+    // Get rid of the trails of entities that are gone
+    let orphans = entities
+        .iter_with(&trail_paths)
+        .map(|(ent, _)| ent)
+        .filter(|path_ent| {
+            !entities
+                .iter_with(&trails)
+                .any(|(_, trail)| trail.path_entity == Some(*path_ent))
+        })
+        .collect::<Vec<_>>();
+    for path_ent in orphans {
+        entities.kill(path_ent);
+    }
+
+    // Create the entities to draw new trails on
+    let new_trails = entities
+        .iter_with(&trails)
+        .filter(|(_, trail)| trail.path_entity.is_none())
+        .map(|(ent, _)| ent)
+        .collect::<Vec<_>>();
+    for ent in new_trails {
+        let path_ent = entities.create();
+        trail_paths.insert(path_ent, TrailPath);
+        trails.get_mut(ent).unwrap().path_entity = Some(path_ent);
+    }
+
+    let mut finished = Vec::new();
+    for (ent, trail) in entities.iter_with(&mut trails) {
+        let Some(transform) = transforms.get(ent).copied() else {
+            continue;
+        };
+        let pos = transform.translation.xy();
+        let is_fast = trail
+            .points
+            .last()
+            .map_or(true, |last| (pos - *last).length() * FPS >= min_speed);
+
+        if is_fast {
+            trail.points.push(pos);
+        }
+        if !is_fast || trail.points.len() > length as usize {
+            trail.points.remove(0);
+        }
+
+        let Some(path_ent) = trail.path_entity else {
+            continue;
+        };
+        if trail.points.len() < 2 {
+            paths.remove(path_ent);
+            // Stop following entities that are done flying around
+            if trail.points.is_empty() && !items_thrown.contains(ent) && !bullets.contains(ent) {
+                finished.push(ent);
+            }
+            continue;
+        }
+
+        // Fade the trail as it shrinks
+        let mut color = color;
+        color.set_a(color.a() * trail.points.len() as f32 / length.max(1) as f32);
+        transforms.insert(
+            path_ent,
+            Transform::from_translation(vec3(0.0, 0.0, transform.translation.z - 0.01)),
+        );
+        paths.insert(
+            path_ent,
+            Path2d {
+                color,
+                points: trail.points.clone(),
+                thickness,
+                ..default()
+            },
+        );
+    }
+
+    for ent in finished {
+        if let Some(path_ent) = trails.remove(ent).and_then(|trail| trail.path_entity) {
+            entities.kill(path_ent);
+        }
+    }
+}
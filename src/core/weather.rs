@@ -0,0 +1,146 @@
+//! Rain, snow, and wind, configured with the [`MapWeatherMeta`] of each map.
+//!
+//! The raindrops and snowflakes aren't entities of their own. Their positions are worked out from
+//! the match time every frame and drawn as a single [`Path2d`], so that heavy weather stays cheap
+//! and doesn't add anything to the simulation.
+
+use crate::prelude::*;
+
+pub fn install(session: &mut SessionBuilder) {
+    session
+        .init_resource::<WeatherState>()
+        .add_system_to_stage(CoreStage::PreUpdate, wind_gusts)
+        .add_system_to_stage(CoreStage::Last, render_weather)
+        .add_system_to_stage(CoreStage::Last, muffle_music);
+}
+
+/// How long a raindrop streak is, in pixels.
+const RAINDROP_LENGTH: f32 = 10.0;
+/// How fast raindrops fall, in pixels per second.
+const RAIN_SPEED: f32 = 700.0;
+/// How fast snowflakes fall, in pixels per second.
+const SNOW_SPEED: f32 = 60.0;
+/// How far snowflakes sway from side to side, in pixels.
+const SNOW_SWAY: f32 = 12.0;
+
+/// Session resource with the state of the weather.
+#[derive(HasSchema, Clone, Debug)]
+pub struct WeatherState {
+    /// Counts down to the next gust of wind.
+    pub gust_timer: Timer,
+    /// The entity that the weather is drawn on.
+    pub path_entity: Option<Entity>,
+}
+
+impl Default for WeatherState {
+    fn default() -> Self {
+        Self {
+            gust_timer: Timer::new(Duration::ZERO, TimerMode::Repeating),
+            path_entity: None,
+        }
+    }
+}
+
+/// Get a pseudo-random number from `0.0` to `1.0` that is always the same for the same inputs.
+fn hash01(i: u32, salt: u32) -> f32 {
+    let mut x = i.wrapping_mul(0x9E37_79B9) ^ salt.wrapping_mul(0x85EB_CA6B);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x as f32 / u32::MAX as f32
+}
+
+/// Push every kinematic body sideways when a gust of wind comes through.
+fn wind_gusts(
+    entities: Res<Entities>,
+    map: Res<LoadedMap>,
+    time: Res<Time>,
+    mut state: ResMut<WeatherState>,
+    mut bodies: CompMut<KinematicBody>,
+) {
+    let weather = &map.weather;
+    if weather.gust_interval.is_zero() || weather.gust_strength == 0.0 {
+        return;
+    }
+
+    if state.gust_timer.duration() != weather.gust_interval {
+        state.gust_timer = Timer::new(weather.gust_interval, TimerMode::Repeating);
+    }
+    state.gust_timer.tick(time.delta());
+    if !state.gust_timer.just_finished() {
+        return;
+    }
+
+    let direction = if weather.wind < 0.0 { -1.0 } else { 1.0 };
+    for (_, body) in entities.iter_with(&mut bodies) {
+        if !body.is_deactivated {
+            body.velocity.x += direction * weather.gust_strength;
+        }
+    }
+}
+
+/// Draw the rain or snow over the map.
+fn render_weather(
+    mut entities: ResMutInit<Entities>,
+    map: Res<LoadedMap>,
+    time: Res<Time>,
+    mut state: ResMut<WeatherState>,
+    mut transforms: CompMut<Transform>,
+    mut paths: CompMut<Path2d>,
+) {
+    let weather = &map.weather;
+    let map_size = map.grid_size.as_vec2() * map.tile_size;
+    let count = (weather.density * map_size.x * map_size.y / 10_000.0) as u32;
+    if weather.kind == WeatherKind::Clear || count == 0 {
+        if let Some(path_ent) = state.path_entity.take() {
+            entities.kill(path_ent);
+        }
+        return;
+    }
+
+    let path_ent = *state.path_entity.get_or_insert_with(|| entities.create());
+    transforms.insert(path_ent, Transform::from_translation(vec3(0.0, 0.0, 0.0)));
+
+    let t = time.elapsed_seconds();
+    let (fall_speed, color, thickness) = match weather.kind {
+        WeatherKind::Rain => (RAIN_SPEED, Color::from([0.7, 0.8, 1.0, 0.5]), 1.0),
+        _ => (SNOW_SPEED, Color::from([1.0, 1.0, 1.0, 0.8]), 2.5),
+    };
+    let velocity = vec2(weather.wind, -fall_speed);
+    let streak = match weather.kind {
+        WeatherKind::Rain => velocity.normalize() * RAINDROP_LENGTH,
+        _ => vec2(0.0, thickness),
+    };
+
+    let mut points = Vec::with_capacity(count as usize * 2);
+    let mut line_breaks = Vec::with_capacity(count as usize);
+    for i in 0..count {
+        // Every drop falls at its own speed from its own starting point, wrapping around the map
+        let speed = 0.75 + hash01(i, 2) * 0.5;
+        let start = vec2(hash01(i, 0), hash01(i, 1)) * map_size;
+        let mut pos = (start + velocity * speed * t).rem_euclid(map_size);
+        if weather.kind == WeatherKind::Snow {
+            pos.x += (t * (1.0 + hash01(i, 3)) + i as f32).sin() * SNOW_SWAY;
+        }
+        points.push(pos);
+        points.push(pos + streak);
+        line_breaks.push(points.len());
+    }
+
+    paths.insert(
+        path_ent,
+        Path2d {
+            color,
+            points,
+            thickness,
+            line_breaks,
+            ..default()
+        },
+    );
+}
+
+/// Muffle the music while the map's weather calls for it.
+fn muffle_music(map: Res<LoadedMap>, mut music_low_pass: ResMut<MusicLowPass>) {
+    let cutoff = map.weather.music_low_pass;
+    music_low_pass.cutoff = (cutoff > 0.0).then_some(cutoff as f64);
+}
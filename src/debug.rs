@@ -6,6 +6,9 @@ use bones_framework::debug::frame_time_diagnostics_plugin;
 #[cfg(not(target_arch = "wasm32"))]
 use bones_framework::networking::debug::network_debug_window;
 
+mod inspector;
+pub use inspector::*;
+
 pub fn game_plugin(game: &mut Game) {
     game.sessions.create_with(SessionNames::DEBUG, |builder| {
         builder
@@ -17,7 +20,9 @@ pub fn game_plugin(game: &mut Game) {
 fn session_plugin(session: &mut SessionBuilder) {
     session
         .stages
-        .add_system_to_stage(CoreStage::First, debug_menu);
+        .add_system_to_stage(CoreStage::First, debug_menu)
+        .add_system_to_stage(CoreStage::First, stage_profiler)
+        .add_system_to_stage(CoreStage::First, inspector::entity_inspector);
 
     #[cfg(not(target_arch = "wasm32"))]
     session
@@ -37,6 +42,8 @@ fn debug_menu(
     mut state: ResMutInit<DebugMenuState>,
     ctx: ResMut<EguiCtx>,
     localization: Localization<GameMeta>,
+    meta_problems: Res<MetaProblems>,
+    mut inspector: ResMutInit<InspectorState>,
 ) {
     let DebugMenuState {
         snapshot,
@@ -51,7 +58,7 @@ fn debug_menu(
     if toggle_debug {
         *show_menu = !*show_menu;
     }
-    let game_session = sessions.get_mut(SessionNames::GAME);
+    let mut game_session = sessions.get_mut(SessionNames::GAME);
 
     // Delete the snapshot if there is one and we are not in the middle of a game.
     if game_session.is_none() && snapshot.is_some() {
@@ -66,11 +73,11 @@ fn debug_menu(
                 ui.set_enabled(game_session.is_some());
 
                 if ui.button(localization.get("take-snapshot")).clicked() {
-                    if let Some(session) = game_session {
+                    if let Some(session) = game_session.as_mut() {
                         *snapshot = Some(session.snapshot());
                     }
                 } else if ui.button(localization.get("restore-snapshot")).clicked() {
-                    if let Some(session) = game_session {
+                    if let Some(session) = game_session.as_mut() {
                         if let Some(mut snapshot) = snapshot.clone() {
                             session.restore(&mut snapshot);
                         }
@@ -108,6 +115,240 @@ fn debug_menu(
                 ui.add_enabled_ui(false, |ui| {
                     let _ = ui.button(localization.get("network-debug"));
                 });
-            })
+            });
+
+            // Desync warning for network matches
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some((frame, player)) = game_session.as_ref().and_then(|session| {
+                session
+                    .world
+                    .get_resource::<crate::core::desync::DesyncState>()
+                    .and_then(|x| x.first_mismatch)
+            }) {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    localization.get_with(
+                        "desync-detected",
+                        &fluent_args! {
+                            "frame" => frame,
+                            "player" => player.0 + 1
+                        },
+                    ),
+                );
+            }
+
+            // Players sending invalid inputs in network matches
+            if let Some(violations) = game_session
+                .as_ref()
+                .and_then(|session| session.world.get_resource::<InputViolations>())
+            {
+                for (i, count) in violations.counts.iter().enumerate() {
+                    if *count == 0 {
+                        continue;
+                    }
+                    let color = if violations.flagged[i] {
+                        egui::Color32::RED
+                    } else {
+                        egui::Color32::YELLOW
+                    };
+                    ui.colored_label(
+                        color,
+                        localization.get_with(
+                            "input-violations",
+                            &fluent_args! {
+                                "player" => i + 1,
+                                "count" => *count,
+                                "flagged" => violations.flagged[i].to_string()
+                            },
+                        ),
+                    );
+                }
+            }
+
+            // Problems found in the current map
+            if let Some(map_warnings) = game_session
+                .as_ref()
+                .and_then(|session| session.world.get_resource::<MapWarnings>())
+            {
+                egui::CollapsingHeader::new(localization.get_with(
+                    "map-warnings",
+                    &fluent_args! { "count" => map_warnings.warnings.len() },
+                ))
+                .id_source("map-warnings")
+                .default_open(!map_warnings.warnings.is_empty())
+                .show(ui, |ui| {
+                    if map_warnings.warnings.is_empty() {
+                        ui.label(localization.get("map-warnings-none"));
+                    }
+                    for warning in &map_warnings.warnings {
+                        ui.colored_label(egui::Color32::YELLOW, warning.to_string());
+                    }
+                });
+            }
+
+            // Problems found in the metadata files on startup
+            if !meta_problems.is_empty() {
+                egui::CollapsingHeader::new(localization.get_with(
+                    "metadata-problems",
+                    &fluent_args! { "count" => meta_problems.len() },
+                ))
+                .id_source("metadata-problems")
+                .show(ui, |ui| {
+                    for problem in meta_problems.iter() {
+                        ui.colored_label(egui::Color32::RED, problem.to_string());
+                    }
+                });
+            }
+
+            // Entity inspector toggle
+            ui.add_enabled_ui(game_session.is_some(), |ui| {
+                ui.checkbox(&mut inspector.enabled, localization.get("entity-inspector"));
+            });
+
+            // Debug overlay toggles
+            if let Some(mut settings) = game_session
+                .as_ref()
+                .and_then(|session| session.world.get_resource_mut::<DebugSettings>())
+            {
+                ui.checkbox(
+                    &mut settings.show_profiler,
+                    localization.get("stage-profiler"),
+                );
+                ui.checkbox(
+                    &mut settings.show_spatial_queries,
+                    localization.get("spatial-queries"),
+                );
+                ui.checkbox(
+                    &mut settings.show_tile_materials,
+                    localization.get("tile-materials"),
+                );
+            }
+
+            // Heatmap overlay selection
+            if let Some(session) = game_session.as_mut() {
+                let mut overlay = session.world.resource_mut::<HeatmapOverlay>();
+                let kind_label = |kind: Option<HeatmapEventKind>| match kind {
+                    None => localization.get("heatmap-off"),
+                    Some(HeatmapEventKind::Death) => localization.get("heatmap-deaths"),
+                    Some(HeatmapEventKind::Kill) => localization.get("heatmap-kills"),
+                    Some(HeatmapEventKind::Pickup) => localization.get("heatmap-pickups"),
+                };
+                egui::ComboBox::from_label(localization.get("heatmap"))
+                    .selected_text(kind_label(overlay.kind))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut overlay.kind, None, kind_label(None));
+                        for kind in HeatmapEventKind::ALL {
+                            ui.selectable_value(
+                                &mut overlay.kind,
+                                Some(kind),
+                                kind_label(Some(kind)),
+                            );
+                        }
+                    });
+            }
+        });
+}
+
+/// The size of the stage profiler graph, in points.
+const PROFILER_GRAPH_SIZE: egui::Vec2 = egui::vec2(240.0, 80.0);
+
+/// The colors that the stages are drawn with in the stage profiler graph.
+const PROFILER_STAGE_COLORS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(0x4e, 0x79, 0xa7),
+    egui::Color32::from_rgb(0xf2, 0x8e, 0x2b),
+    egui::Color32::from_rgb(0xe1, 0x57, 0x59),
+    egui::Color32::from_rgb(0x76, 0xb7, 0xb2),
+    egui::Color32::from_rgb(0x59, 0xa1, 0x4f),
+    egui::Color32::from_rgb(0xed, 0xc9, 0x48),
+];
+
+/// Show how long the stages of the game session take, with a graph of the recent frames, while
+/// [`DebugSettings::show_profiler`] is enabled.
+fn stage_profiler(
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    localization: Localization<GameMeta>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(settings), Some(timings)) = (
+        world.get_resource::<DebugSettings>(),
+        world.get_resource::<StageTimings>(),
+    ) else {
+        return;
+    };
+    if !settings.show_profiler {
+        return;
+    }
+
+    let stage_color = |i: usize| PROFILER_STAGE_COLORS[i % PROFILER_STAGE_COLORS.len()];
+    let time_row = |ui: &mut egui::Ui, times: Vec<Duration>| {
+        let last = times.last().copied().unwrap_or_default();
+        let average = times.iter().sum::<Duration>() / times.len().max(1) as u32;
+        let max = times.iter().max().copied().unwrap_or_default();
+        for time in [last, average, max] {
+            ui.label(format!("{:.2}ms", time.as_secs_f64() * 1000.0));
+        }
+        ui.end_row();
+    };
+
+    egui::Window::new(localization.get("stage-profiler"))
+        .id(egui::Id::new("stage_profiler"))
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+        .resizable(false)
+        .show(&ctx, |ui| {
+            egui::Grid::new("stage_profiler_times")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label(localization.get("time-last"));
+                    ui.label(localization.get("time-average"));
+                    ui.label(localization.get("time-max"));
+                    ui.end_row();
+
+                    for (i, stage) in timings.stages.iter().enumerate() {
+                        ui.colored_label(stage_color(i), *stage);
+                        time_row(ui, timings.stage_times(i).collect());
+                    }
+                    ui.label(localization.get("frame"));
+                    time_row(ui, timings.frame_times().collect());
+                });
+
+            // Rolling graph of the recent frames, with the stages stacked on top of each other. The
+            // graph is scaled to fit the slowest frame, but always reaches at least one fixed step
+            // so that spikes stand out.
+            let (rect, _) = ui.allocate_exact_size(PROFILER_GRAPH_SIZE, egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(180));
+
+            let step = Duration::from_secs_f64(1.0 / FPS as f64);
+            let scale = timings.frame_times().max().unwrap_or_default().max(step);
+            let to_height =
+                |time: Duration| (time.as_secs_f64() / scale.as_secs_f64()) as f32 * rect.height();
+            let bar_width = rect.width() / timings.capacity.max(1) as f32;
+            let first_bar = timings.capacity.saturating_sub(timings.frames.len());
+            for (i, frame) in timings.frames.iter().enumerate() {
+                let x = rect.left() + (first_bar + i) as f32 * bar_width;
+                let mut bottom = rect.bottom();
+                for (stage_idx, time) in frame.iter().enumerate() {
+                    let top = bottom - to_height(*time);
+                    painter.rect_filled(
+                        egui::Rect::from_x_y_ranges(x..=x + bar_width, top..=bottom),
+                        0.0,
+                        stage_color(stage_idx),
+                    );
+                    bottom = top;
+                }
+            }
+
+            // Mark the time budget of one fixed step
+            let budget_y = rect.bottom() - to_height(step);
+            painter.hline(
+                rect.x_range(),
+                budget_y,
+                egui::Stroke::new(1.0, egui::Color32::WHITE),
+            );
         });
 }
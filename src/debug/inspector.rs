@@ -0,0 +1,250 @@
+//! Entity inspector for the game session.
+//!
+//! While the inspector is enabled in the debug tools, clicking on an entity in the match selects it,
+//! and [`PREVIOUS_ENTITY_KEY`] and [`NEXT_ENTITY_KEY`] cycle through the entities with a
+//! [`Transform`]. The components of the selected entity are listed with their fields, found through
+//! their schemas, and numbers, flags, and timers can be edited while the match runs.
+
+use crate::{core::utils::Rect, prelude::*, ui::hud::CameraView};
+
+/// The key that selects the previous entity.
+pub const PREVIOUS_ENTITY_KEY: KeyCode = KeyCode::PageUp;
+/// The key that selects the next entity.
+pub const NEXT_ENTITY_KEY: KeyCode = KeyCode::PageDown;
+
+/// How far from an entity's position a click may be to select it, in pixels, for entities without a
+/// body to click on.
+const PICK_DISTANCE: f32 = 12.0;
+
+/// Debug session resource with the state of the inspector.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct InspectorState {
+    pub enabled: bool,
+    pub selected: Option<Entity>,
+}
+
+/// Show the components of one type on the selected entity, if it has one.
+type InspectComponentFn = fn(&World, Entity, &mut egui::Ui);
+
+/// The components that are listed by the inspector.
+const INSPECTED_COMPONENTS: &[InspectComponentFn] = &[
+    inspect_component::<Transform>,
+    inspect_component::<KinematicBody>,
+    inspect_component::<DynamicBody>,
+    inspect_component::<PlayerIdx>,
+    inspect_component::<PlayerState>,
+    inspect_component::<Stunned>,
+    inspect_component::<Invincibility>,
+    inspect_component::<Item>,
+    inspect_component::<ItemThrown>,
+    inspect_component::<Bullet>,
+    inspect_component::<DamageRegion>,
+    inspect_component::<Lifetime>,
+    inspect_component::<ParticleEmitter>,
+    inspect_component::<AtlasSprite>,
+    inspect_component::<Sprite>,
+    inspect_component::<AnimatedSprite>,
+];
+
+/// Select entities in the game session and show their components.
+pub(super) fn entity_inspector(
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    keyboard_inputs: Res<KeyboardInputs>,
+    localization: Localization<GameMeta>,
+    mut state: ResMutInit<InspectorState>,
+) {
+    if !state.enabled {
+        return;
+    }
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        state.selected = None;
+        return;
+    };
+    let world = &game_session.world;
+    let Some(entities) = world.get_resource::<Entities>() else {
+        return;
+    };
+    let view = CameraView::new(world, ctx.screen_rect());
+
+    {
+        let transforms = world.components.get::<Transform>().borrow();
+        let bodies = world.components.get::<KinematicBody>().borrow();
+
+        // Cycle through the entities with the keyboard
+        let candidates = entities
+            .iter_with(&*transforms)
+            .map(|(ent, _)| ent)
+            .collect::<Vec<_>>();
+        for event in &keyboard_inputs.key_events {
+            if event.button_state.pressed() || candidates.is_empty() {
+                continue;
+            }
+            let current = state
+                .selected
+                .and_then(|x| candidates.iter().position(|y| *y == x));
+            let len = candidates.len();
+            let next = match event.key_code.option() {
+                Some(NEXT_ENTITY_KEY) => current.map_or(0, |i| (i + 1) % len),
+                Some(PREVIOUS_ENTITY_KEY) => current.map_or(len - 1, |i| (i + len - 1) % len),
+                _ => continue,
+            };
+            state.selected = Some(candidates[next]);
+        }
+
+        // Select what was clicked on, preferring bodies over anything that is just nearby
+        let click = ctx.input(|i| {
+            i.pointer
+                .primary_clicked()
+                .then(|| i.pointer.interact_pos())
+                .flatten()
+        });
+        if let (Some(click), Some(view)) = (click, &view) {
+            if !ctx.is_pointer_over_area() {
+                let pos = view.world_pos(click);
+                let clicked_body = entities
+                    .iter_with((&*bodies, &*transforms))
+                    .find(|(_, (body, transform))| body.bounding_box(**transform).contains(pos))
+                    .map(|(ent, _)| ent);
+                let nearby = || {
+                    entities
+                        .iter_with(&*transforms)
+                        .map(|(ent, transform)| (ent, transform.translation.xy().distance(pos)))
+                        .filter(|(_, distance)| *distance <= PICK_DISTANCE)
+                        .min_by(|a, b| a.1.total_cmp(&b.1))
+                        .map(|(ent, _)| ent)
+                };
+                state.selected = clicked_body.or_else(nearby);
+            }
+        }
+
+        if let Some(ent) = state.selected {
+            if !entities.is_alive(ent) {
+                state.selected = None;
+            }
+        }
+
+        // Point out the selected entity
+        if let (Some(ent), Some(view)) = (state.selected, &view) {
+            if let Some(transform) = transforms.get(ent) {
+                let rect = match bodies.get(ent) {
+                    Some(body) => body.bounding_box(*transform),
+                    None => Rect::new(
+                        transform.translation.x,
+                        transform.translation.y,
+                        PICK_DISTANCE,
+                        PICK_DISTANCE,
+                    ),
+                };
+                let painter = ctx.layer_painter(egui::LayerId::new(
+                    egui::Order::Foreground,
+                    egui::Id::new("inspector_selection"),
+                ));
+                painter.rect_stroke(
+                    egui::Rect::from_two_pos(
+                        view.screen_pos(view.offset(rect.min)),
+                        view.screen_pos(view.offset(rect.max)),
+                    ),
+                    0.0,
+                    egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                );
+            }
+        }
+    }
+
+    egui::Window::new(localization.get("entity-inspector"))
+        .id(egui::Id::new("entity_inspector"))
+        .default_width(260.0)
+        .show(&ctx, |ui| {
+            ui.label(localization.get_with(
+                "entity-inspector-help",
+                &fluent_args! {
+                    "previous" => format!("{PREVIOUS_ENTITY_KEY:?}"),
+                    "next" => format!("{NEXT_ENTITY_KEY:?}"),
+                },
+            ));
+            ui.separator();
+
+            let Some(ent) = state.selected else {
+                ui.label(localization.get("entity-inspector-none"));
+                return;
+            };
+            ui.label(format!("{ent:?}"));
+            egui::ScrollArea::vertical()
+                .max_height(ctx.screen_rect().height() / 2.0)
+                .show(ui, |ui| {
+                    for inspect in INSPECTED_COMPONENTS {
+                        inspect(world, ent, ui);
+                    }
+                });
+        });
+}
+
+/// Show a component of the selected entity, if it has one.
+fn inspect_component<T: HasSchema>(world: &World, ent: Entity, ui: &mut egui::Ui) {
+    let store = world.components.get::<T>();
+    let mut store = store.borrow_mut();
+    let Some(component) = store.get_mut(ent) else {
+        return;
+    };
+    let type_name = std::any::type_name::<T>();
+    let name = type_name.rsplit("::").next().unwrap_or(type_name);
+    egui::CollapsingHeader::new(name)
+        .id_source(type_name)
+        .default_open(true)
+        .show(ui, |ui| inspect_value(ui, SchemaRefMut::new(component)));
+}
+
+/// Show the fields of a value, with editors for the ones that are simple enough to edit.
+fn inspect_value(ui: &mut egui::Ui, mut value: SchemaRefMut) {
+    if let Ok(x) = value.try_cast_mut::<f32>() {
+        ui.add(egui::DragValue::new(x).speed(0.1));
+    } else if let Ok(x) = value.try_cast_mut::<f64>() {
+        ui.add(egui::DragValue::new(x).speed(0.1));
+    } else if let Ok(x) = value.try_cast_mut::<u32>() {
+        ui.add(egui::DragValue::new(x));
+    } else if let Ok(x) = value.try_cast_mut::<i32>() {
+        ui.add(egui::DragValue::new(x));
+    } else if let Ok(x) = value.try_cast_mut::<u64>() {
+        ui.add(egui::DragValue::new(x));
+    } else if let Ok(x) = value.try_cast_mut::<u8>() {
+        ui.add(egui::DragValue::new(x));
+    } else if let Ok(x) = value.try_cast_mut::<bool>() {
+        ui.checkbox(x, "");
+    } else if let Ok(x) = value.try_cast_mut::<Ustr>() {
+        ui.label(x.as_str());
+    } else if let Ok(x) = value.try_cast_mut::<Duration>() {
+        let mut secs = x.as_secs_f32();
+        if ui
+            .add(egui::DragValue::new(&mut secs).speed(0.01).suffix("s"))
+            .changed()
+        {
+            *x = Duration::from_secs_f32(secs.max(0.0));
+        }
+    } else if let Ok(timer) = value.try_cast_mut::<Timer>() {
+        let duration = timer.duration().as_secs_f32();
+        let mut elapsed = timer.elapsed().as_secs_f32();
+        if ui
+            .add(egui::Slider::new(&mut elapsed, 0.0..=duration).suffix("s"))
+            .changed()
+        {
+            timer.set_elapsed(Duration::from_secs_f32(elapsed));
+        }
+    } else if let SchemaKind::Struct(info) = &value.schema().kind {
+        egui::Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            for (i, field) in info.fields.iter().enumerate() {
+                let Ok(field_value) = value.field(i) else {
+                    continue;
+                };
+                match &field.name {
+                    Some(name) => ui.label(name.as_str()),
+                    None => ui.label(i.to_string()),
+                };
+                inspect_value(ui, field_value);
+                ui.end_row();
+            }
+        });
+    } else {
+        ui.weak("…");
+    }
+}
@@ -0,0 +1,123 @@
+//! Reloading metadata assets while the game is running.
+//!
+//! In debug builds, or when the `JUMPY_HOT_RELOAD` environment variable is set, the asset and
+//! packs directories are checked for changed YAML files twice a second. Changed files are loaded
+//! again through the [`AssetServer`], and once they have finished loading their handles are put
+//! in the [`MetaReloads`] shared resource for a single frame.
+//!
+//! Most systems read element metadata from the asset server every frame, so they pick up the new
+//! values on their own. Elements that copy metadata into their components when they are spawned
+//! check [`MetaReloads`] to update their live entities, like the kick bomb does for its fuse time
+//! and velocities.
+//!
+//! Reloads only happen on the local machine, so this is meant for tuning elements in local games.
+//! Changing metadata during a network match will desync it.
+
+use crate::prelude::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// How often the asset files are checked for changes.
+#[cfg(not(target_arch = "wasm32"))]
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+pub fn game_plugin(game: &mut Game) {
+    game.init_shared_resource::<MetaReloads>();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if cfg!(debug_assertions) || std::env::var_os("JUMPY_HOT_RELOAD").is_some() {
+        game.systems.add_before_system(watch_meta_files);
+    }
+}
+
+/// Shared resource with the metadata assets that were reloaded this frame.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct MetaReloads {
+    /// The handles of the assets that were reloaded. Only set for the frame that they finished
+    /// loading on.
+    pub handles: Vec<UntypedHandle>,
+}
+
+impl MetaReloads {
+    /// Whether the asset with the given handle was reloaded this frame.
+    pub fn contains<T>(&self, handle: Handle<T>) -> bool {
+        self.handles.contains(&handle.untyped())
+    }
+}
+
+/// The state of the file watcher, kept between frames.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct MetaWatcher {
+    last_poll: Option<Instant>,
+    /// When each watched file was last modified.
+    modified: HashMap<PathBuf, SystemTime>,
+    /// Assets that are being reloaded.
+    pending: Vec<UntypedHandle>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+static WATCHER: std::sync::Mutex<Option<MetaWatcher>> = std::sync::Mutex::new(None);
+
+/// Reload the metadata files that have changed since the last poll.
+#[cfg(not(target_arch = "wasm32"))]
+fn watch_meta_files(game: &mut Game) {
+    let mut reloads = game.shared_resource_mut::<MetaReloads>().unwrap();
+    reloads.handles.clear();
+
+    let mut watcher = WATCHER.lock().unwrap();
+    let watcher = watcher.get_or_insert_with(default);
+    let mut asset_server = game.shared_resource_mut::<AssetServer>().unwrap();
+
+    // Hand out the reloaded assets once everything has finished loading
+    if !watcher.pending.is_empty() && asset_server.load_progress.is_finished() {
+        reloads.handles = std::mem::take(&mut watcher.pending);
+        info!("Reloaded {} metadata asset(s)", reloads.handles.len());
+    }
+
+    if watcher
+        .last_poll
+        .is_some_and(|x| x.elapsed() < POLL_INTERVAL)
+    {
+        return;
+    }
+    let is_first_poll = watcher.last_poll.is_none();
+    watcher.last_poll = Some(Instant::now());
+
+    let mut roots = vec![(crate::mods::asset_dir(), None)];
+    // Assets in packs are located by the name of the pack's directory
+    if let Ok(entries) = std::fs::read_dir(crate::mods::packs_dir()) {
+        for dir in entries.flatten().map(|x| x.path()).filter(|x| x.is_dir()) {
+            let name = dir.file_name().map(|x| x.to_string_lossy().into_owned());
+            roots.push((dir, name));
+        }
+    }
+
+    for (root, pack) in roots {
+        for path in crate::mods::yaml_files(&root) {
+            let Some(modified) = path.metadata().and_then(|x| x.modified()).ok() else {
+                continue;
+            };
+            let previous = watcher.modified.insert(path.clone(), modified);
+            // The first poll only records what the files looked like on startup
+            if is_first_poll || previous.is_none() || previous == Some(modified) {
+                continue;
+            }
+
+            let Ok(relative) = path.strip_prefix(&root) else {
+                continue;
+            };
+            let asset_path = Path::new("/").join(relative);
+            info!("Reloading {}", path.display());
+            let handle = asset_server.load_asset_forced(AssetLocRef {
+                path: &asset_path,
+                pack: pack.as_deref(),
+            });
+            watcher.pending.push(handle);
+        }
+    }
+}
@@ -7,6 +7,10 @@ use crate::{
 use bones_framework::networking::{input::NetworkPlayerControl, proto::DenseMoveDirection};
 use strum::EnumIter;
 
+use crate::ui::touch::TouchInput;
+
+pub mod rumble;
+
 pub fn game_plugin(game: &mut Game) {
     game.systems.add_startup_system(load_controler_mapping);
     game.insert_shared_resource(EguiInputHook::new(handle_egui_input));
@@ -32,7 +36,23 @@ fn collect_player_controls(game: &mut Game) {
         };
         let keyboard = game.shared_resource::<KeyboardInputs>().unwrap();
         let gamepad = game.shared_resource::<GamepadInputs>().unwrap();
-        collector.apply_inputs(&mapping, &keyboard, &gamepad);
+
+        // The keyboard doesn't control players while it is used to write chat messages.
+        let no_keyboard = KeyboardInputs::default();
+        #[allow(unused_mut)]
+        let mut keyboard: &KeyboardInputs = &keyboard;
+        #[cfg(not(target_arch = "wasm32"))]
+        if game
+            .shared_resource::<crate::ui::chat::ChatState>()
+            .is_some_and(|x| x.is_typing)
+        {
+            keyboard = &no_keyboard;
+        }
+
+        collector.apply_inputs(&mapping, keyboard, &gamepad);
+        if let Some(touch) = game.shared_resource::<crate::ui::touch::TouchControls>() {
+            collector.apply_touch(&touch.input);
+        }
         collector.update_just_pressed();
         collector.advance_frame();
         GlobalPlayerControls(
@@ -181,18 +201,76 @@ pub struct PlayerControl {
 
     pub ragdoll_pressed: bool,
     pub ragdoll_just_pressed: bool,
+
+    /// How many frames behind the rest of the match this player was when sending this input, as
+    /// measured on their end. Always zero in local play.
+    pub latency_frames: u8,
+
+    /// A fragment of this player's checksum of a recent frame, used to detect desyncs. Always
+    /// zero in local play.
+    pub checksum: u16,
+}
+
+impl PlayerControl {
+    /// Whether the player isn't moving or pressing any of the gameplay buttons.
+    pub fn is_idle(&self) -> bool {
+        self.move_direction == Vec2::ZERO
+            && !self.jump_pressed
+            && !self.shoot_pressed
+            && !self.grab_pressed
+            && !self.slide_pressed
+            && !self.ragdoll_pressed
+            && !self.pause_pressed
+    }
 }
 
 #[derive(HasSchema, Clone)]
 pub struct PlayerInputCollector {
     current_controls: HashMap<ControlSource, PlayerControl>,
     last_controls: HashMap<ControlSource, PlayerControl>,
+    /// The touch inputs applied last frame.
+    last_touch: TouchInput,
 }
 
 impl PlayerInputCollector {
     pub fn get_current_controls(&self) -> &HashMap<ControlSource, PlayerControl> {
         &self.current_controls
     }
+
+    /// Apply the inputs of the touch controls to the first keyboard player.
+    ///
+    /// Like key events, only the touch inputs that changed since last frame are applied, so that
+    /// the keyboard can still be used alongside them.
+    pub fn apply_touch(&mut self, touch: &TouchInput) {
+        let last = std::mem::replace(&mut self.last_touch, *touch);
+        let control = self
+            .current_controls
+            .entry(ControlSource::Keyboard1)
+            .or_default();
+
+        for (button_pressed, current, last) in [
+            (&mut control.jump_pressed, touch.jump, last.jump),
+            (&mut control.menu_confirm_pressed, touch.jump, last.jump),
+            (&mut control.shoot_pressed, touch.shoot, last.shoot),
+            (&mut control.grab_pressed, touch.grab, last.grab),
+            (&mut control.menu_back_pressed, touch.grab, last.grab),
+            (&mut control.slide_pressed, touch.slide, last.slide),
+            (&mut control.pause_pressed, touch.pause, last.pause),
+            (&mut control.menu_start_pressed, touch.pause, last.pause),
+        ] {
+            if current != last {
+                *button_pressed = current;
+            }
+        }
+
+        if touch.move_direction != last.move_direction {
+            let direction = touch.move_direction;
+            control.left = (-direction.x).max(0.0);
+            control.right = direction.x.max(0.0);
+            control.up = direction.y.max(0.0);
+            control.down = (-direction.y).max(0.0);
+        }
+    }
 }
 
 impl Default for PlayerInputCollector {
@@ -210,6 +288,7 @@ impl Default for PlayerInputCollector {
         Self {
             current_controls: def_controls(),
             last_controls: def_controls(),
+            last_touch: default(),
         }
     }
 }
@@ -350,11 +429,7 @@ impl<'a>
         };
 
         for (source, control) in self.current_controls.iter_mut() {
-            let mapping = match source {
-                ControlSource::Keyboard1 => &mapping.keyboard1,
-                ControlSource::Keyboard2 => &mapping.keyboard2,
-                ControlSource::Gamepad(_) => &mapping.gamepad,
-            };
+            let mapping = mapping.map_control_source(*source);
 
             for (button_pressed, button_map) in [
                 (&mut control.pause_pressed, &mapping.pause),
@@ -421,6 +496,14 @@ impl NetworkPlayerControl<DensePlayerControl> for PlayerControl {
         dense_control.set_shoot_pressed(self.shoot_pressed);
         dense_control.set_ragdoll_pressed(self.ragdoll_pressed);
         dense_control.set_move_direction(proto::DenseMoveDirection(self.move_direction));
+        dense_control.set_latency_frames(
+            LOCAL_LATENCY_FRAMES
+                .load(std::sync::atomic::Ordering::Relaxed)
+                .min(MAX_DENSE_LATENCY_FRAMES),
+        );
+        dense_control.set_checksum(
+            LOCAL_CHECKSUM_FRAGMENT.load(std::sync::atomic::Ordering::Relaxed) & 0x3ff,
+        );
         dense_control
     }
 
@@ -445,9 +528,30 @@ impl NetworkPlayerControl<DensePlayerControl> for PlayerControl {
         self.move_direction = new_control.move_direction().0;
         let is_moving = self.move_direction.length_squared() > f32::MIN_POSITIVE;
         self.just_moved = !was_moving && is_moving;
+
+        self.latency_frames = new_control.latency_frames();
+        self.checksum = new_control.checksum();
     }
 }
 
+/// The latency of the local player in the current network match, in frames.
+///
+/// This is measured by the match session and sent along with the local player's inputs, so that
+/// every client agrees on it.
+#[cfg(not(target_arch = "wasm32"))]
+pub static LOCAL_LATENCY_FRAMES: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(0);
+
+/// The checksum fragment to send with the local player's inputs in the current network match.
+///
+/// See [`crate::core::desync`] for how it is put together.
+#[cfg(not(target_arch = "wasm32"))]
+pub static LOCAL_CHECKSUM_FRAGMENT: std::sync::atomic::AtomicU16 =
+    std::sync::atomic::AtomicU16::new(0);
+
+/// The largest latency that fits in a [`DensePlayerControl`].
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_DENSE_LATENCY_FRAMES: u8 = 31;
+
 #[cfg(not(target_arch = "wasm32"))]
 bitfield::bitfield! {
     /// A player's controller inputs densely packed into a single u32.
@@ -463,6 +567,8 @@ bitfield::bitfield! {
     pub slide_pressed, set_slide_pressed: 3;
     pub ragdoll_pressed, set_ragdoll_pressed: 4;
     pub from into DenseMoveDirection, move_direction, set_move_direction: 16, 5;
+    pub u8, latency_frames, set_latency_frames: 21, 17;
+    pub u16, checksum, set_checksum: 31, 22;
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -0,0 +1,159 @@
+//! Gamepad rumble for things that happen to local players.
+//!
+//! Players controlled with a gamepad feel it rumble when they are killed, when an explosion goes
+//! off near them, and when they land after a long fall. How strong and how long each of these are
+//! is set in [`RumbleMeta`], and rumble can be turned off for each gamepad in the settings.
+
+use bones_framework::input::gamepad::{
+    GamepadRumbleIntensity, GamepadRumbleRequest, GamepadsRumble,
+};
+
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use crate::prelude::*;
+
+/// The newest frame that rumble was played for in network matches. Frames that are re-simulated
+/// after a rollback don't rumble again, and since session resources are rolled back too, this has
+/// to be kept outside of the session.
+static LAST_RUMBLE_FRAME: AtomicI32 = AtomicI32::new(i32::MIN);
+
+/// Install the rumble systems in a match session.
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session.init_resource::<RumbleState>();
+    session.add_startup_system(|| LAST_RUMBLE_FRAME.store(i32::MIN, Ordering::Relaxed));
+    session.add_system_to_stage(CoreStage::Last, rumble_on_events);
+}
+
+/// The rumble for everything that may make a gamepad rumble.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct RumbleMeta {
+    /// When the player is killed.
+    pub damage: RumbleProfile,
+    /// When an explosion goes off within `explosion_radius` of the player, scaled down with the
+    /// distance to it.
+    pub explosion: RumbleProfile,
+    pub explosion_radius: f32,
+    /// When the player lands after falling from at least `landing_min_height` pixels, scaled up to
+    /// full strength at `landing_max_height`.
+    pub landing: RumbleProfile,
+    pub landing_min_height: f32,
+    pub landing_max_height: f32,
+}
+
+/// How a gamepad rumbles.
+#[derive(HasSchema, Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct RumbleProfile {
+    /// The strength of the low frequency motor, from `0.0` to `1.0`.
+    pub strong_motor: f32,
+    /// The strength of the high frequency motor, from `0.0` to `1.0`.
+    pub weak_motor: f32,
+    /// How long to rumble for.
+    pub duration: Duration,
+}
+
+/// Session resource with what rumble has already been played for.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct RumbleState {
+    /// Which players were dead last frame.
+    killed: [bool; MAX_PLAYERS as usize],
+    /// The height at which each player left the ground, while they are in the air.
+    fall_start: [Option<f32>; MAX_PLAYERS as usize],
+}
+
+/// Rumble a gamepad, scaling the strength of the profile by `scale`.
+fn rumble(gamepads_rumble: &mut GamepadsRumble, gamepad: u32, profile: &RumbleProfile, scale: f32) {
+    let scale = scale.clamp(0.0, 1.0);
+    if profile.duration.is_zero() || scale <= 0.0 {
+        return;
+    }
+    gamepads_rumble
+        .requests
+        .push_back(GamepadRumbleRequest::AddRumble {
+            gamepad,
+            intensity: GamepadRumbleIntensity {
+                strong_motor: profile.strong_motor * scale,
+                weak_motor: profile.weak_motor * scale,
+            },
+            duration: profile.duration,
+        });
+}
+
+fn rumble_on_events(
+    meta: Root<GameMeta>,
+    entities: Res<Entities>,
+    storage: Res<Storage>,
+    player_inputs: Res<MatchInputs>,
+    damage_events: Res<DamageEvents>,
+    player_indexes: Comp<PlayerIdx>,
+    killed_players: Comp<PlayerKilled>,
+    transforms: Comp<Transform>,
+    bodies: Comp<KinematicBody>,
+    mut state: ResMut<RumbleState>,
+    mut gamepads_rumble: ResMut<GamepadsRumble>,
+    #[cfg(not(target_arch = "wasm32"))] syncing_info: Option<Res<SyncingInfo>>,
+) {
+    #[allow(unused_mut)]
+    let mut is_resimulating = false;
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        is_resimulating = syncing_info.filter(|x| x.is_online()).is_some_and(|x| {
+            let frame = x.current_frame();
+            LAST_RUMBLE_FRAME.fetch_max(frame, Ordering::Relaxed) >= frame
+        });
+    }
+
+    let rumble_meta = &meta.rumble;
+    let disabled_gamepads = storage
+        .get::<Settings>()
+        .map(|x| x.rumble_disabled_gamepads.clone())
+        .unwrap_or_default();
+
+    for (player_ent, (idx, transform, body)) in
+        entities.iter_with((&player_indexes, &transforms, &bodies))
+    {
+        let i = idx.0 as usize;
+        let pos = transform.translation.xy();
+        let is_killed = killed_players.contains(player_ent);
+        let was_killed = std::mem::replace(&mut state.killed[i], is_killed);
+
+        // Keep track of falls even for players that don't rumble, so that the state is consistent
+        let fall_height = if body.is_on_ground || is_killed {
+            state.fall_start[i].take().map(|start| start - pos.y)
+        } else {
+            state.fall_start[i] = Some(state.fall_start[i].map_or(pos.y, |x| x.max(pos.y)));
+            None
+        };
+
+        let Some(ControlSource::Gamepad(gamepad)) = player_inputs.players[i].control_source else {
+            continue;
+        };
+        if is_resimulating || disabled_gamepads.contains(&gamepad) {
+            continue;
+        }
+
+        if is_killed && !was_killed {
+            rumble(&mut gamepads_rumble, gamepad, &rumble_meta.damage, 1.0);
+        }
+
+        if let Some(height) = fall_height.filter(|x| *x >= rumble_meta.landing_min_height) {
+            let range = (rumble_meta.landing_max_height - rumble_meta.landing_min_height).max(1.0);
+            let scale = (height - rumble_meta.landing_min_height) / range;
+            rumble(
+                &mut gamepads_rumble,
+                gamepad,
+                &rumble_meta.landing,
+                scale.max(0.1),
+            );
+        }
+
+        for event in damage_events.events.iter().filter(|x| x.age == 0) {
+            let distance = event.pos.distance(pos);
+            if distance < rumble_meta.explosion_radius {
+                let scale = 1.0 - distance / rumble_meta.explosion_radius;
+                rumble(&mut gamepads_rumble, gamepad, &rumble_meta.explosion, scale);
+            }
+        }
+    }
+}
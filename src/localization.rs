@@ -0,0 +1,185 @@
+//! Localization of the text shown in the game.
+//!
+//! All of the text in the menus and the HUD comes from the Fluent files listed in
+//! `locales/localization.yaml`, with a set of files for each language. Bones picks the language of
+//! the system on startup, and this module puts a chain of languages on top of it: the one picked in
+//! the settings, then the system language, and then [`FALLBACK_LANGUAGE`]. Text is looked up in
+//! each language in turn, so a language that is missing some text shows it in English instead.
+//!
+//! The chain is rebuilt whenever the language in the settings changes, so the language can be
+//! switched without restarting the game.
+
+use std::{borrow::Cow, marker::PhantomData};
+
+use fluent::FluentArgs;
+
+use crate::prelude::*;
+
+/// The language that text is shown in when no other language has it.
+pub const FALLBACK_LANGUAGE: &str = "en-US";
+
+pub fn game_plugin(game: &mut Game) {
+    game.init_shared_resource::<LocalizationBundles>();
+    game.systems.add_before_system(select_language);
+}
+
+/// A language that the game has text for.
+#[derive(Clone, Debug)]
+pub struct LanguageInfo {
+    /// The language identifier, such as `en-US`.
+    pub id: String,
+    /// The name of the language, in that language.
+    pub name: String,
+}
+
+/// Shared resource with the languages to look text up in.
+#[derive(HasSchema, Clone, Default)]
+pub struct LocalizationBundles {
+    /// The language picked in the settings when the chain was built, or `None` if it hasn't been
+    /// built yet.
+    language: Option<String>,
+    /// The bundles to look text up in, in order.
+    chain: Vec<FluentBundleAsset>,
+    /// Every language that the game has text for.
+    pub available: Vec<LanguageInfo>,
+}
+
+/// Format a message from a bundle, if the bundle has it.
+fn format_message(
+    bundle: &FluentBundleAsset,
+    id: &str,
+    args: Option<&FluentArgs>,
+) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    let text = bundle
+        .format_pattern(pattern, args, &mut errors)
+        .into_owned();
+    if !errors.is_empty() {
+        warn!("Errors formatting localized message `{id}`: {errors:?}");
+    }
+    Some(text)
+}
+
+/// Get the identifier of the language that a bundle has text for.
+fn bundle_language(bundle: &FluentBundleAsset) -> Option<String> {
+    bundle.locales.first().map(|x| x.to_string())
+}
+
+/// Rebuild the language chain if the language in the settings has changed.
+fn select_language(game: &mut Game) {
+    let language = game
+        .shared_resource::<Storage>()
+        .and_then(|x| x.get::<Settings>().map(|x| x.language.clone()))
+        .unwrap_or_default();
+    let mut bundles = game.shared_resource_mut::<LocalizationBundles>().unwrap();
+    if bundles.language.as_ref() == Some(&language) {
+        return;
+    }
+    let assets = game.shared_resource::<AssetServer>().unwrap();
+    if !assets.load_progress.is_finished() {
+        return;
+    }
+
+    let root = assets.root::<GameMeta>();
+    let localization = assets.get(root.localization);
+    let all = localization
+        .bundles
+        .iter()
+        .map(|x| assets.get(*x).clone())
+        .collect::<Vec<_>>();
+    let find = |language: &str| {
+        all.iter()
+            .find(|x| bundle_language(x).as_deref() == Some(language))
+            .cloned()
+    };
+
+    let mut chain = Vec::new();
+    if !language.is_empty() {
+        match find(&language) {
+            Some(bundle) => chain.push(bundle),
+            None => warn!("The language picked in the settings isn't available: {language}"),
+        }
+    }
+    chain.push(localization.current_bundle.clone());
+    chain.extend(find(FALLBACK_LANGUAGE));
+
+    bundles.available = all
+        .iter()
+        .filter_map(|bundle| {
+            let id = bundle_language(bundle)?;
+            let name = format_message(bundle, "language-name", None).unwrap_or_else(|| id.clone());
+            Some(LanguageInfo { id, name })
+        })
+        .collect();
+    bundles.chain = chain;
+    bundles.language = Some(language);
+}
+
+/// System parameter for looking up localized text.
+///
+/// This stands in for the `Localization` parameter from bones, so that text is looked up through
+/// the language chain of [`LocalizationBundles`]. Messages that no language has are shown as
+/// their id.
+///
+/// The type parameter is the root metadata that the localization is found in, which is always
+/// [`GameMeta`].
+pub struct Localization<'a, T> {
+    bundles: Res<'a, LocalizationBundles>,
+    _phantom: PhantomData<T>,
+}
+
+impl<'a> SystemParam for Localization<'a, GameMeta> {
+    type State = <Res<'a, LocalizationBundles> as SystemParam>::State;
+    type Param<'p> = Localization<'p, GameMeta>;
+
+    fn get_state(world: &World) -> Self::State {
+        <Res<'a, LocalizationBundles> as SystemParam>::get_state(world)
+    }
+
+    fn borrow<'s>(world: &'s World, state: &'s mut Self::State) -> Self::Param<'s> {
+        Localization {
+            bundles: <Res<'a, LocalizationBundles> as SystemParam>::borrow(world, state),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Localization<'_, T> {
+    /// Get a message in the first language that has it, if any language has it.
+    pub fn try_get_with(&self, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        self.bundles
+            .chain
+            .iter()
+            .find_map(|bundle| format_message(bundle, id, args))
+    }
+
+    /// Get a message.
+    pub fn get(&self, id: &str) -> Cow<'static, str> {
+        self.try_get_with(id, None)
+            .unwrap_or_else(|| id.to_string())
+            .into()
+    }
+
+    /// Get a message with arguments.
+    pub fn get_with(&self, id: &str, args: &FluentArgs) -> String {
+        self.try_get_with(id, Some(args))
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Get the name of a map element in the current language, falling back to the name in its
+    /// metadata for elements that the language files don't have a name for.
+    ///
+    /// The message ids of element names are `element-` followed by the name in lowercase, with
+    /// dashes instead of spaces, such as `element-kick-bomb`.
+    pub fn element_name(&self, name: &str) -> String {
+        let id = format!("element-{}", name.to_lowercase().replace(' ', "-"));
+        self.try_get_with(&id, None)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Get the languages that the game has text for.
+    pub fn available_languages(&self) -> &[LanguageInfo] {
+        &self.bundles.available
+    }
+}
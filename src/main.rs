@@ -13,19 +13,26 @@ use bones_bevy_renderer::BonesBevyRenderer;
 use bones_framework::prelude::*;
 
 pub mod audio;
+pub mod bench;
 pub mod core;
 pub mod debug;
 pub mod fullscreen;
+pub mod hot_reload;
 pub mod input;
+pub mod localization;
+pub mod mods;
 pub mod profiler;
 pub mod sessions;
 pub mod settings;
+pub mod stream_overlay;
 pub mod ui;
 
 mod prelude {
     pub use crate::{
         audio::*, core::prelude::*, impl_system_param, input::*, sessions::*, settings::*, GameMeta,
     };
+    // Explicitly imported so that it takes the place of the one in the bones prelude
+    pub use crate::localization::Localization;
     pub use bones_framework::prelude::*;
     pub use once_cell::sync::Lazy;
     pub use serde::{Deserialize, Serialize};
@@ -53,6 +60,9 @@ pub struct GameMeta {
     pub theme: ui::UiTheme,
     pub main_menu: ui::main_menu::MainMenuMeta,
     pub music: GameMusic,
+    pub announcer: audio::announcer::AnnouncerMeta,
+    pub rumble: input::rumble::RumbleMeta,
+    pub post_processing: ui::post_processing::PostProcessingMeta,
     pub network: NetworkMeta,
 }
 
@@ -61,6 +71,11 @@ pub struct GameMeta {
 pub struct NetworkMeta {
     pub max_prediction_window: usize,
     pub local_input_delay: usize,
+    /// The furthest back that hitscan targets may be rewound when the host enables lag
+    /// compensation, in frames.
+    pub max_lag_compensation_frames: u32,
+    /// Round trip times above this are shown in the warning color, in milliseconds.
+    pub high_ping_threshold: u32,
 }
 
 // In wasm build get derivable_impls clippy warning which breaks CI
@@ -72,6 +87,8 @@ impl Default for NetworkMeta {
             Self {
                 local_input_delay: 0,
                 max_prediction_window: 0,
+                max_lag_compensation_frames: 0,
+                high_ping_threshold: 0,
             }
         }
         #[cfg(not(target_arch = "wasm32"))]
@@ -80,6 +97,8 @@ impl Default for NetworkMeta {
                 local_input_delay: bones_framework::networking::NETWORK_LOCAL_INPUT_DELAY_DEFAULT,
                 max_prediction_window:
                     bones_framework::networking::NETWORK_MAX_PREDICTION_WINDOW_DEFAULT,
+                max_lag_compensation_frames: 8,
+                high_ping_threshold: 150,
             }
         }
     }
@@ -144,6 +163,7 @@ fn main() {
 
     // Register types that we will load from persistent storage.
     settings::Settings::register_schema();
+    core::heatmap::HeatmapStats::register_schema();
 
     // First create bones game.
     let mut game = Game::new();
@@ -157,19 +177,34 @@ fn main() {
         .install_plugin(DefaultGamePlugin)
         .install_plugin(audio::game_plugin)
         .install_plugin(settings::game_plugin)
+        .install_plugin(localization::game_plugin)
+        .install_plugin(mods::game_plugin)
+        .install_plugin(hot_reload::game_plugin)
         .install_plugin(fullscreen::game_plugin)
         .install_plugin(input::game_plugin)
         .install_plugin(core::game_plugin)
         .install_plugin(debug::game_plugin)
         .install_plugin(profiler::game_plugin)
+        .install_plugin(stream_overlay::game_plugin)
+        .install_plugin(ui::focus::game_plugin)
+        .install_plugin(ui::fonts::game_plugin)
         .install_plugin(ui::scoring::game_plugin)
+        .install_plugin(ui::touch::game_plugin);
+    #[cfg(not(target_arch = "wasm32"))]
+    game.install_plugin(ui::chat::game_plugin)
+        .install_plugin(ui::ping::game_plugin);
+    game
         // We initialize the asset server and register asset types
         .init_shared_resource::<AssetServer>()
         .register_default_assets();
 
     // Create a new session for the game menu. Each session is it's own bones world with it's own
-    // plugins, systems, and entities.
-    game.sessions.start_menu();
+    // plugins, systems, and entities. When benchmarking the simulation, a match is started right
+    // away instead.
+    match bench::bench_sim_frames() {
+        Some(frames) => bench::game_plugin(&mut game, frames),
+        None => game.sessions.start_menu(),
+    }
 
     // Create a new session for the pause menu, which sits in the background by default and only
     // does anything while the game is running.
@@ -189,12 +224,29 @@ fn main() {
         builder.install_plugin(ui::scoring::session_plugin);
     });
 
+    // In-game HUD, drawn over the match while it is running
+    game.sessions.create_with(SessionNames::HUD, |builder| {
+        builder
+            .install_plugin(ui::post_processing::session_plugin)
+            .install_plugin(ui::hud::session_plugin)
+            .install_plugin(ui::touch::session_plugin);
+    });
+
     // session for pop-ups / nofication UI
     game.sessions
         .create_with(SessionNames::NOTIFICATION, |builder| {
             builder.install_plugin(ui::notification::session_plugin);
         });
 
+    // Chat and pings over network lobbies and matches
+    #[cfg(not(target_arch = "wasm32"))]
+    game.sessions
+        .create_with(SessionNames::NETWORK_OVERLAY, |builder| {
+            builder
+                .install_plugin(ui::chat::session_plugin)
+                .install_plugin(ui::ping::session_plugin);
+        });
+
     // Create a bevy renderer for the bones game and run it.
     BonesBevyRenderer {
         game,
@@ -205,12 +257,14 @@ fn main() {
             env!("CARGO_PKG_VERSION_PATCH").parse().unwrap(),
         ),
         app_namespace: ("org".into(), "fishfolk".into(), "jumpy".into()),
-        asset_dir: std::env::var("JUMPY_ASSETS")
-            .unwrap_or_else(|_| "assets".into())
-            .into(),
-        packs_dir: std::env::var("JUMPY_ASSET_PACKS")
-            .unwrap_or_else(|_| "packs".into())
-            .into(),
+        #[cfg(not(target_arch = "wasm32"))]
+        asset_dir: mods::asset_dir(),
+        #[cfg(target_arch = "wasm32")]
+        asset_dir: "assets".into(),
+        #[cfg(not(target_arch = "wasm32"))]
+        packs_dir: mods::packs_dir(),
+        #[cfg(target_arch = "wasm32")]
+        packs_dir: "packs".into(),
         custom_load_progress: Some(Box::new(load_progress)),
         preload: true,
     }
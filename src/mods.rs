@@ -0,0 +1,467 @@
+//! User made content loaded from the mods directory.
+//!
+//! Mods are asset packs, like the ones in the packs directory, that players drop into the `mods/`
+//! directory (or the one in the `JUMPY_MODS` environment variable). They usually add maps and map
+//! elements.
+//!
+//! The asset server only loads packs from the packs directory, so on startup every mod is mirrored
+//! into it, in a directory starting with [`MOD_DIR_PREFIX`]. Mirrored mods that have been removed
+//! from the mods directory are removed from the packs directory too.
+//!
+//! Two packs can't have the same id, so a mod with the same id as an installed pack, or as a mod
+//! that comes before it alphabetically, isn't loaded. Mods whose maps or elements have the same
+//! name as ones from other packs are still loaded, and the menus tell them apart by showing which
+//! mod they came from.
+
+use crate::prelude::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+
+/// The name of the file that describes an asset pack.
+pub const PACK_FILE: &str = "pack.yaml";
+
+/// The prefix of the directories in the packs directory that mods are mirrored to.
+pub const MOD_DIR_PREFIX: &str = "mod-";
+
+/// The file in a mirrored mod directory that records where the mod was mirrored from, and what it
+/// looked like at the time.
+#[cfg(not(target_arch = "wasm32"))]
+const MOD_STAMP_FILE: &str = ".jumpy-mod";
+
+pub fn game_plugin(game: &mut Game) {
+    #[cfg(not(target_arch = "wasm32"))]
+    let mods = load_mods();
+    #[cfg(target_arch = "wasm32")]
+    let mods = ModList::default();
+    game.insert_shared_resource(mods);
+
+    // Hashed after the mods are mirrored, so that the hashes match what the asset server loads
+    #[cfg(not(target_arch = "wasm32"))]
+    let hashes = hash_assets();
+    #[cfg(target_arch = "wasm32")]
+    let hashes = AssetHashes::default();
+    game.insert_shared_resource(hashes);
+}
+
+/// A mod that was found in the mods directory.
+#[derive(Clone, Debug)]
+pub struct ModInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    /// The names of the maps in the mod.
+    pub maps: Vec<String>,
+    /// The names of the map elements in the mod.
+    pub elements: Vec<String>,
+}
+
+/// A mod that couldn't be loaded.
+#[derive(Clone, Debug)]
+pub struct ModConflict {
+    /// The name of the mod's directory.
+    pub dir: String,
+    pub reason: String,
+}
+
+/// Shared resource with the mods that were loaded on startup.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct ModList {
+    pub mods: Vec<ModInfo>,
+    pub conflicts: Vec<ModConflict>,
+    /// The names of maps and elements that more than one pack has.
+    pub duplicate_names: HashSet<String>,
+}
+
+impl ModList {
+    /// Get the mod that a map comes from, if it comes from one.
+    ///
+    /// Maps are matched by name, so other maps with the same name as a mod map are attributed to
+    /// the mod too. [`ModList::duplicate_names`] has the names that this happens for.
+    pub fn map_source(&self, name: &str) -> Option<&ModInfo> {
+        self.mods.iter().find(|x| x.maps.iter().any(|x| x == name))
+    }
+
+    /// Get the mod that a map element comes from, if it comes from one.
+    pub fn element_source(&self, name: &str) -> Option<&ModInfo> {
+        self.mods
+            .iter()
+            .find(|x| x.elements.iter().any(|x| x == name))
+    }
+
+    /// Get the name to show for a map in the menus, tagged with the mod it comes from.
+    pub fn map_label(&self, name: &str, localization: &Localization<GameMeta>) -> String {
+        match self.map_source(name) {
+            Some(source) => localization.get_with(
+                "mod-content",
+                &fluent_args! { "name" => name, "mod" => source.name.as_str() },
+            ),
+            None => name.to_string(),
+        }
+    }
+
+    /// Get the name to show for a map element in the menus, in the current language and tagged
+    /// with the mod it comes from.
+    pub fn element_label(&self, name: &str, localization: &Localization<GameMeta>) -> String {
+        let display_name = localization.element_name(name);
+        match self.element_source(name) {
+            Some(source) => localization.get_with(
+                "mod-content",
+                &fluent_args! { "name" => display_name, "mod" => source.name.as_str() },
+            ),
+            None => display_name,
+        }
+    }
+}
+
+/// The hash of the contents of an asset pack that was loaded on startup.
+#[derive(Clone, Debug)]
+pub struct PackHash {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub hash: u64,
+}
+
+/// Shared resource with hashes of the contents of the core assets and the asset packs, taken on
+/// startup.
+///
+/// Network lobbies compare them between players, since matches desync when players have different
+/// metadata.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct AssetHashes {
+    /// Hash of the core asset directory.
+    pub core: u64,
+    /// The hashes of the asset packs, sorted by id.
+    pub packs: Vec<PackHash>,
+}
+
+/// The fields of the pack file that are needed to identify a pack.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deserialize)]
+struct PackFile {
+    id: String,
+    name: String,
+    version: String,
+    root: String,
+}
+
+/// The lists of maps and elements in a pack's root asset.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deserialize, Default)]
+struct PackRoot {
+    #[serde(default)]
+    maps: Vec<String>,
+    #[serde(default)]
+    map_elements: Vec<String>,
+}
+
+/// Anything with a name, used to read the names of maps and elements.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Deserialize)]
+struct Named {
+    name: String,
+}
+
+/// Get the directory that the core assets are loaded from.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn asset_dir() -> PathBuf {
+    std::env::var("JUMPY_ASSETS")
+        .unwrap_or_else(|_| "assets".into())
+        .into()
+}
+
+/// Get the directory that asset packs are loaded from.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn packs_dir() -> PathBuf {
+    std::env::var("JUMPY_ASSET_PACKS")
+        .unwrap_or_else(|_| "packs".into())
+        .into()
+}
+
+/// Get the directory that mods are loaded from.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn mods_dir() -> PathBuf {
+    std::env::var("JUMPY_MODS")
+        .unwrap_or_else(|_| "mods".into())
+        .into()
+}
+
+/// Get the YAML files in a directory and its subdirectories.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn yaml_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in sorted_dirs_and_files(dir) {
+        if path.is_dir() {
+            files.extend(yaml_files(&path));
+        } else if path.extension().is_some_and(|x| x == "yaml" || x == "yml") {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// Read a pack directory, returning its pack file and the names of its maps and elements.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_pack(dir: &Path) -> Result<ModInfo, String> {
+    let read_yaml = |path: &Path| -> Result<serde_yaml::Value, String> {
+        let file = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        serde_yaml::from_slice(&file).map_err(|e| format!("{}: {e}", path.display()))
+    };
+    // Asset paths are relative to the pack root if they start with `/`, and to the file they are
+    // in otherwise.
+    let resolve = |base: &Path, path: &str| match path.strip_prefix('/') {
+        Some(path) => dir.join(path),
+        None => base.parent().unwrap_or(dir).join(path),
+    };
+
+    let pack_file: PackFile = serde_yaml::from_value(read_yaml(&dir.join(PACK_FILE))?)
+        .map_err(|e| format!("{PACK_FILE}: {e}"))?;
+    let root_path = resolve(&dir.join(PACK_FILE), &pack_file.root);
+    let root: PackRoot = serde_yaml::from_value(read_yaml(&root_path)?).unwrap_or_default();
+
+    let names = |paths: &[String]| {
+        paths
+            .iter()
+            .filter_map(|path| {
+                let value = read_yaml(&resolve(&root_path, path)).ok()?;
+                Some(serde_yaml::from_value::<Named>(value).ok()?.name)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    Ok(ModInfo {
+        id: pack_file.id,
+        name: pack_file.name,
+        version: pack_file.version,
+        maps: names(&root.maps),
+        elements: names(&root.map_elements),
+    })
+}
+
+/// Get the subdirectories of a directory, sorted by name.
+#[cfg(not(target_arch = "wasm32"))]
+fn sorted_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs = entries
+        .flatten()
+        .map(|x| x.path())
+        .filter(|x| x.is_dir())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs
+}
+
+/// A fingerprint of the files in a directory, to tell whether a mod has changed since it was
+/// mirrored.
+#[cfg(not(target_arch = "wasm32"))]
+fn fingerprint(dir: &Path) -> String {
+    fn visit(dir: &Path, entries: &mut Vec<String>) {
+        for path in sorted_dirs_and_files(dir) {
+            if path.is_dir() {
+                visit(&path, entries);
+            } else if let Ok(metadata) = path.metadata() {
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|x| x.duration_since(std::time::UNIX_EPOCH).ok())
+                    .unwrap_or_default();
+                entries.push(format!(
+                    "{} {} {}",
+                    path.display(),
+                    metadata.len(),
+                    modified.as_millis()
+                ));
+            }
+        }
+    }
+    let mut entries = Vec::new();
+    visit(dir, &mut entries);
+    entries.join("\n")
+}
+
+/// Get the files and subdirectories of a directory, sorted by name.
+#[cfg(not(target_arch = "wasm32"))]
+fn sorted_dirs_and_files(dir: &Path) -> Vec<PathBuf> {
+    let mut paths = std::fs::read_dir(dir)
+        .map(|x| x.flatten().map(|x| x.path()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+/// Copy a directory and everything in it.
+#[cfg(not(target_arch = "wasm32"))]
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        if path.is_dir() {
+            copy_dir(&path, &to.join(name))?;
+        } else {
+            std::fs::copy(&path, to.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Replace the mirror of a mod in the packs directory with a fresh copy.
+#[cfg(not(target_arch = "wasm32"))]
+fn mirror_mod(dir: &Path, mirror_dir: &Path, stamp: &str) -> std::io::Result<()> {
+    if mirror_dir.exists() {
+        std::fs::remove_dir_all(mirror_dir)?;
+    }
+    copy_dir(dir, mirror_dir)?;
+    std::fs::write(mirror_dir.join(MOD_STAMP_FILE), stamp)
+}
+
+/// Find the mods in the mods directory, and mirror the ones that can be loaded into the packs
+/// directory.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_mods() -> ModList {
+    let packs_dir = packs_dir();
+    let mut list = ModList::default();
+
+    // The packs that were installed directly, which take priority over mods
+    let mut pack_ids = HashSet::default();
+    let mut names = HashSet::<String>::default();
+    for dir in sorted_dirs(&packs_dir) {
+        let is_mirror = dir
+            .file_name()
+            .is_some_and(|x| x.to_string_lossy().starts_with(MOD_DIR_PREFIX));
+        if is_mirror {
+            continue;
+        }
+        if let Ok(pack) = read_pack(&dir) {
+            pack_ids.insert(pack.id);
+            names.extend(pack.maps.into_iter().chain(pack.elements));
+        }
+    }
+
+    let mut mirrored_dirs = Vec::new();
+    for dir in sorted_dirs(&mods_dir()) {
+        let dir_name = dir
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut conflict = |reason: String| {
+            warn!("Not loading mod `{dir_name}`: {reason}");
+            list.conflicts.push(ModConflict {
+                dir: dir_name.clone(),
+                reason,
+            });
+        };
+
+        let info = match read_pack(&dir) {
+            Ok(info) => info,
+            Err(e) => {
+                conflict(e);
+                continue;
+            }
+        };
+        if !pack_ids.insert(info.id.clone()) {
+            conflict(format!("another pack has the id `{}`", info.id));
+            continue;
+        }
+
+        // Mirror the mod into the packs directory if it changed since it was last mirrored
+        let mirror_dir = packs_dir.join(format!("{MOD_DIR_PREFIX}{dir_name}"));
+        let stamp = format!("{}\n{}", dir.display(), fingerprint(&dir));
+        let is_up_to_date =
+            std::fs::read_to_string(mirror_dir.join(MOD_STAMP_FILE)).is_ok_and(|x| x == stamp);
+        if !is_up_to_date {
+            if let Err(e) = mirror_mod(&dir, &mirror_dir, &stamp) {
+                conflict(format!("could not copy it to the packs directory: {e}"));
+                continue;
+            }
+        }
+        mirrored_dirs.push(mirror_dir);
+
+        for name in info.maps.iter().chain(info.elements.iter()) {
+            if !names.insert(name.clone()) {
+                warn!(
+                    "Mod `{}` has a map or element named `{name}` like another pack",
+                    info.name
+                );
+                list.duplicate_names.insert(name.clone());
+            }
+        }
+        info!("Loaded mod `{}` version {}", info.name, info.version);
+        list.mods.push(info);
+    }
+
+    // Remove the mirrors of mods that are gone. Only directories with a stamp file are removed, so
+    // that packs installed by hand are never touched.
+    for dir in sorted_dirs(&packs_dir) {
+        if dir.join(MOD_STAMP_FILE).exists() && !mirrored_dirs.contains(&dir) {
+            if let Err(e) = std::fs::remove_dir_all(&dir) {
+                warn!("Could not remove old mod from {}: {e}", dir.display());
+            }
+        }
+    }
+
+    list
+}
+
+/// Hash the contents of the core asset directory and every pack in the packs directory.
+#[cfg(not(target_arch = "wasm32"))]
+fn hash_assets() -> AssetHashes {
+    let core = hash_dir(&asset_dir());
+    let mut packs = sorted_dirs(&packs_dir())
+        .into_iter()
+        .filter_map(|dir| {
+            let info = read_pack(&dir).ok()?;
+            Some(PackHash {
+                id: info.id,
+                name: info.name,
+                version: info.version,
+                hash: hash_dir(&dir),
+            })
+        })
+        .collect::<Vec<_>>();
+    packs.sort_by(|a, b| a.id.cmp(&b.id));
+    info!("Hashed core assets and {} asset pack(s)", packs.len());
+
+    AssetHashes { core, packs }
+}
+
+/// Hash the paths and contents of the files in a directory with 64-bit FNV-1a, which gives the
+/// same result on every platform.
+///
+/// Paths are hashed relative to the directory with `/` separators. The stamp files of mirrored
+/// mods are skipped, since they contain the path that the mod was mirrored from.
+#[cfg(not(target_arch = "wasm32"))]
+fn hash_dir(dir: &Path) -> u64 {
+    fn visit(root: &Path, dir: &Path, hash: &mut u64) {
+        for path in sorted_dirs_and_files(dir) {
+            if path.is_dir() {
+                visit(root, &path, hash);
+                continue;
+            }
+            if path.file_name().is_some_and(|x| x == MOD_STAMP_FILE) {
+                continue;
+            }
+            let Ok(data) = std::fs::read(&path) else {
+                continue;
+            };
+            let relative = path
+                .strip_prefix(root)
+                .unwrap()
+                .components()
+                .map(|x| x.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            for byte in relative.bytes().chain([0]).chain(data) {
+                *hash = (*hash ^ byte as u64).wrapping_mul(0x100000001b3);
+            }
+        }
+    }
+    let mut hash = 0xcbf29ce484222325;
+    visit(dir, dir, &mut hash);
+    hash
+}
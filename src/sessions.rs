@@ -5,11 +5,14 @@ impl SessionNames {
     pub const AUDIO: &'static str = DEFAULT_BONES_AUDIO_SESSION;
     pub const DEBUG: &'static str = "debug";
     pub const GAME: &'static str = "game";
+    pub const HUD: &'static str = "hud";
     pub const MAIN_MENU: &'static str = "main_menu";
     pub const PAUSE_MENU: &'static str = "pause_menu";
     pub const PROFILER: &'static str = "profiler";
     pub const SCORING: &'static str = "scoring";
+    pub const NETWORK_OVERLAY: &'static str = "network_overlay";
     pub const NOTIFICATION: &'static str = "notification";
+    pub const STREAM_OVERLAY: &'static str = "stream_overlay";
 }
 
 pub trait SessionExt {
@@ -41,27 +44,39 @@ impl SessionExt for Sessions {
 
     #[track_caller]
     fn restart_game(&mut self, map_pool: Option<MapPool>, reset_score: bool) {
-        if let Some((existing_map_pool, player_info, plugins, mut session_runner, score)) =
-            self.get_mut(SessionNames::GAME).map(|session| {
-                let existing_map_pool = (*session.world.resource::<MapPool>()).clone();
-                let match_inputs = session.world.resource::<MatchInputs>();
-                let score = (*session.world.resource::<MatchScore>()).clone();
+        if let Some((
+            existing_map_pool,
+            player_info,
+            plugins,
+            mode,
+            mutators,
+            lag_compensation,
+            drop_in,
+            mut session_runner,
+            score,
+        )) = self.get_mut(SessionNames::GAME).map(|session| {
+            let existing_map_pool = (*session.world.resource::<MapPool>()).clone();
+            let match_inputs = session.world.resource::<MatchInputs>();
+            let score = (*session.world.resource::<MatchScore>()).clone();
 
-                // Take ownership of session runner (we want to preserve socket and such for network runner)
-                // by swapping a dummy one with session.
-                let mut session_runner: Box<dyn SessionRunner> =
-                    Box::<JumpyDefaultMatchRunner>::default();
-                std::mem::swap(&mut session.runner, &mut session_runner);
+            // Take ownership of session runner (we want to preserve socket and such for network runner)
+            // by swapping a dummy one with session.
+            let mut session_runner: Box<dyn SessionRunner> =
+                Box::<JumpyDefaultMatchRunner>::default();
+            std::mem::swap(&mut session.runner, &mut session_runner);
 
-                (
-                    existing_map_pool,
-                    match_inputs.players.clone(),
-                    session.world.resource::<LuaPlugins>().0.clone(),
-                    session_runner,
-                    score,
-                )
-            })
-        {
+            (
+                existing_map_pool,
+                match_inputs.players.clone(),
+                session.world.resource::<LuaPlugins>().0.clone(),
+                *session.world.resource::<GameMode>(),
+                *session.world.resource::<Mutators>(),
+                *session.world.resource::<LagCompensation>(),
+                *session.world.resource::<DropIn>(),
+                session_runner,
+                score,
+            )
+        }) {
             self.end_game();
 
             // Reset session runner
@@ -79,6 +94,10 @@ impl SessionExt for Sessions {
                     maps: map_pool,
                     player_info,
                     plugins,
+                    mode,
+                    mutators,
+                    lag_compensation,
+                    drop_in,
                     session_runner,
                     score,
                 });
@@ -31,12 +31,46 @@ pub struct Settings {
     pub music_volume: f32,
     /// The scaling factor for sound effects.
     pub effects_volume: f32,
+    /// The scaling factor for announcer voice lines, on top of the effects volume.
+    pub announcer_volume: f32,
+    /// Whether to show subtitles for the announcer's voice lines.
+    pub subtitles: bool,
+    /// The language to show text in, such as `en-US`, or empty for the language of the system.
+    pub language: String,
+    /// Whether to speak the menu widgets as they are focused, if there is a speech engine.
+    pub speak_focus: bool,
     /// Whether to display the game fullscreen.
     pub fullscreen: bool,
     /// The player controller bindings
     pub player_controls: PlayerControlMapping,
+    /// The gamepads that shouldn't rumble.
+    pub rumble_disabled_gamepads: SVec<u32>,
     /// The address of the matchmaking server to connect to for online games.
     pub matchmaking_server: String,
+    /// The URL to send match reports to after each round, or empty to only save them to disk.
+    pub match_report_endpoint: String,
+    /// Whether to broadcast the match state over a local websocket for streaming overlays.
+    pub stream_overlay: bool,
+    /// Whether to rewind hitscan targets by the shooter's latency in network matches we host.
+    pub lag_compensation: bool,
+    /// Whether to show an overview of the map in the corner of the screen during matches.
+    pub minimap: bool,
+    /// Whether to pop up numbers over players with how hard they were hit.
+    pub damage_numbers: bool,
+    /// Whether to flash the screen white when explosions go off nearby.
+    pub screen_flash: bool,
+    /// Whether to fringe the sides of the screen with color during the slow motion kill cam.
+    pub chromatic_aberration: bool,
+    /// Whether to darken the edges of the screen during matches.
+    pub vignette: bool,
+    /// Whether to turn off camera shake and flashing effects, and calm down explosions.
+    pub reduced_motion: bool,
+    /// The on-screen controls for touch devices.
+    pub touch_controls: TouchControlSettings,
+    /// The colored outlines drawn around players.
+    pub player_outlines: PlayerOutlineSettings,
+    /// The colors and patterns that players are told apart by.
+    pub player_colors: PlayerColorSettings,
 }
 
 impl Default for Settings {
@@ -45,9 +79,131 @@ impl Default for Settings {
             main_volume: 1.0,
             music_volume: 1.0,
             effects_volume: 1.0,
+            announcer_volume: 1.0,
+            subtitles: false,
+            language: default(),
+            speak_focus: false,
             fullscreen: true,
             player_controls: default(),
+            rumble_disabled_gamepads: default(),
             matchmaking_server: default(),
+            match_report_endpoint: default(),
+            stream_overlay: false,
+            lag_compensation: false,
+            minimap: false,
+            damage_numbers: false,
+            screen_flash: true,
+            chromatic_aberration: true,
+            vignette: true,
+            reduced_motion: false,
+            touch_controls: default(),
+            player_outlines: default(),
+            player_colors: default(),
+        }
+    }
+}
+
+#[derive(HasSchema, Clone, Debug)]
+#[repr(C)]
+pub struct PlayerOutlineSettings {
+    /// Whether to outline players with their player or team color.
+    pub enabled: bool,
+    /// How thick the outline is, in pixels.
+    pub thickness: f32,
+}
+
+impl Default for PlayerOutlineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            thickness: 1.5,
+        }
+    }
+}
+
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct PlayerColorSettings {
+    /// The palette that players are colored with.
+    pub palette: PlayerPalette,
+    /// Whether to mark players with patterns on top of their color, on their outlines, on their
+    /// markers in the HUD, and with a name tag over their head.
+    pub patterns: bool,
+}
+
+impl PlayerColorSettings {
+    /// Get the color of a player.
+    pub fn color(&self, player: PlayerIdx) -> Color {
+        let colors = self.palette.colors();
+        colors[player.0 as usize % colors.len()]
+    }
+
+    /// Get the pattern that a player is marked with, which is always solid if patterns are turned
+    /// off.
+    pub fn pattern(&self, player: PlayerIdx) -> PlayerPattern {
+        if !self.patterns {
+            return PlayerPattern::Solid;
+        }
+        PLAYER_PATTERNS[player.0 as usize % PLAYER_PATTERNS.len()]
+    }
+}
+
+/// The palettes that players can be colored with.
+#[derive(HasSchema, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[derive_type_data(SchemaDeserialize)]
+pub enum PlayerPalette {
+    #[default]
+    Standard,
+    /// Colors for protanopia and deuteranopia.
+    RedGreen,
+    /// Colors for tritanopia.
+    BlueYellow,
+}
+
+impl PlayerPalette {
+    /// Every palette, in the order that the settings cycle through them.
+    pub const ALL: [Self; 3] = [Self::Standard, Self::RedGreen, Self::BlueYellow];
+
+    /// Get the player colors of the palette.
+    pub fn colors(&self) -> &'static [Color; 4] {
+        match self {
+            PlayerPalette::Standard => &PLAYER_COLORS,
+            PlayerPalette::RedGreen => &PLAYER_COLORS_RED_GREEN,
+            PlayerPalette::BlueYellow => &PLAYER_COLORS_BLUE_YELLOW,
+        }
+    }
+
+    /// Get the id of the name of the palette in the localization files.
+    pub fn localization_key(&self) -> &'static str {
+        match self {
+            PlayerPalette::Standard => "player-palette-standard",
+            PlayerPalette::RedGreen => "player-palette-red-green",
+            PlayerPalette::BlueYellow => "player-palette-blue-yellow",
+        }
+    }
+}
+
+#[derive(HasSchema, Clone, Debug)]
+#[repr(C)]
+pub struct TouchControlSettings {
+    /// Whether to show the touch controls once the screen is touched.
+    pub enabled: bool,
+    /// How opaque the touch controls are, from `0.0` to `1.0`.
+    pub opacity: f32,
+    /// How big the touch controls are, relative to their default size.
+    pub size: f32,
+    /// Whether to put the joystick on the right and the buttons on the left.
+    pub swap_sides: bool,
+}
+
+impl Default for TouchControlSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            opacity: 0.6,
+            size: 1.0,
+            swap_sides: false,
         }
     }
 }
@@ -61,6 +217,8 @@ pub struct PlayerControlMapping {
     pub keyboard1: PlayerControlSetting,
     /// Controls for keyboard player 2
     pub keyboard2: PlayerControlSetting,
+    /// Controls for specific gamepads, used instead of the shared gamepad controls.
+    pub gamepad_overrides: SVec<GamepadControlSetting>,
 }
 
 impl PlayerControlMapping {
@@ -68,10 +226,18 @@ impl PlayerControlMapping {
         match source {
             ControlSource::Keyboard1 => &self.keyboard1,
             ControlSource::Keyboard2 => &self.keyboard2,
-            ControlSource::Gamepad(_) => &self.gamepad,
+            ControlSource::Gamepad(idx) => self.gamepad_override(idx).unwrap_or(&self.gamepad),
         }
     }
 
+    /// Get the controls of a specific gamepad, if it doesn't use the shared gamepad controls.
+    pub fn gamepad_override(&self, gamepad: u32) -> Option<&PlayerControlSetting> {
+        self.gamepad_overrides
+            .iter()
+            .find(|x| x.gamepad == gamepad)
+            .map(|x| &x.controls)
+    }
+
     /// Return an iterator for PlayerControlSetting for all control sources.
     pub fn all_settings(&self) -> impl Iterator<Item = &PlayerControlSetting> {
         ControlSource::iter().map(|s| self.map_control_source(s))
@@ -104,7 +270,15 @@ pub struct VirtualDPad {
     pub right: InputKind,
 }
 
+/// Controls for a specific gamepad.
 #[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct GamepadControlSetting {
+    pub gamepad: u32,
+    pub controls: PlayerControlSetting,
+}
+
+#[derive(HasSchema, Clone, Debug, Default, PartialEq, Eq)]
 #[repr(C, u8)]
 pub enum InputKind {
     #[default]
@@ -126,3 +300,31 @@ impl std::fmt::Display for InputKind {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gamepads_use_their_override_or_the_shared_controls() {
+        let mut mapping = PlayerControlMapping::default();
+        mapping.gamepad.jump = InputKind::Button(GamepadButton::South);
+        mapping.gamepad_overrides.push(GamepadControlSetting {
+            gamepad: 2,
+            controls: PlayerControlSetting {
+                jump: InputKind::Button(GamepadButton::East),
+                ..default()
+            },
+        });
+
+        let jump = |gamepad| {
+            mapping
+                .map_control_source(ControlSource::Gamepad(gamepad))
+                .jump
+                .clone()
+        };
+        assert_eq!(jump(0), InputKind::Button(GamepadButton::South));
+        assert_eq!(jump(2), InputKind::Button(GamepadButton::East));
+        assert!(mapping.gamepad_override(1).is_none());
+    }
+}
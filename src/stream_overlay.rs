@@ -0,0 +1,219 @@
+//! Local websocket server for streaming overlays.
+//!
+//! When enabled in the settings, a websocket server is started on localhost that broadcasts the
+//! live match state as JSON, so that streamers can build overlays in OBS without screen scraping.
+//!
+//! Each message looks like this:
+//!
+//! ```json
+//! {
+//!   "map": "Blue Beach",
+//!   "round": 2,
+//!   "round_time": 12.5,
+//!   "players": [{ "index": 0, "name": "Fishy", "score": 1, "alive": true }],
+//!   "kills": [{ "frame": 640, "player": 0, "pos": [120.0, 96.0] }]
+//! }
+//! ```
+//!
+//! `kills` only contains the kills that happened since the previous message.
+
+use crate::prelude::*;
+
+/// The port that the overlay websocket server listens on.
+pub const STREAM_OVERLAY_PORT: u16 = 7710;
+
+/// How many frames to wait in between broadcasts.
+const BROADCAST_INTERVAL: u32 = 6;
+
+pub fn game_plugin(game: &mut Game) {
+    game.sessions
+        .create_with(SessionNames::STREAM_OVERLAY, |builder| {
+            builder.install_plugin(session_plugin);
+        });
+}
+
+#[allow(unused_variables)]
+fn session_plugin(session: &mut SessionBuilder) {
+    #[cfg(not(target_arch = "wasm32"))]
+    session
+        .stages
+        .add_system_to_stage(CoreStage::Last, broadcast_match_state);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+use server::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use std::{
+        net::{TcpListener, TcpStream},
+        sync::Mutex,
+    };
+
+    use serde_json::json;
+    use tungstenite::{Message, WebSocket};
+
+    use super::*;
+
+    /// The websocket server and its connected clients.
+    pub struct StreamOverlayServer {
+        clients: Arc<Mutex<Vec<WebSocket<TcpStream>>>>,
+    }
+
+    impl StreamOverlayServer {
+        /// Start listening for clients on a background thread.
+        pub fn start(port: u16) -> std::io::Result<Self> {
+            let listener = TcpListener::bind(("127.0.0.1", port))?;
+            let clients = Arc::new(Mutex::new(Vec::new()));
+
+            let accepted_clients = clients.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    // Don't let a stalled client stall the game when broadcasting.
+                    let _ = stream.set_write_timeout(Some(Duration::from_millis(5)));
+                    match tungstenite::accept(stream) {
+                        Ok(client) => accepted_clients.lock().unwrap().push(client),
+                        Err(e) => warn!("Stream overlay client failed to connect: {e}"),
+                    }
+                }
+            });
+
+            info!("Stream overlay server listening on ws://127.0.0.1:{port}");
+            Ok(Self { clients })
+        }
+
+        /// Send a message to every client, dropping the ones that have disconnected.
+        pub fn broadcast(&self, message: String) {
+            let mut clients = self.clients.lock().unwrap();
+            clients.retain_mut(|client| client.send(Message::Text(message.clone())).is_ok());
+        }
+    }
+
+    #[derive(HasSchema, Clone, Default)]
+    pub struct StreamOverlayState {
+        server: Option<Arc<StreamOverlayServer>>,
+        /// Whether starting the server failed, so that we don't try again every frame.
+        failed_to_start: bool,
+        frames_since_broadcast: u32,
+        /// The number of events in the match event log that have already been broadcast.
+        sent_event_count: usize,
+    }
+
+    impl StreamOverlayState {
+        /// Get the kills in the event log that haven't been broadcast yet, and mark every event
+        /// in it as broadcast.
+        pub fn take_new_kills<'a>(&mut self, events: &'a [MatchEvent]) -> Vec<&'a MatchEvent> {
+            // The event log is cleared at the end of every round
+            if events.len() < self.sent_event_count {
+                self.sent_event_count = 0;
+            }
+            let kills = events[self.sent_event_count..]
+                .iter()
+                .filter(|event| event.kind == MatchEventKind::Kill)
+                .collect();
+            self.sent_event_count = events.len();
+            kills
+        }
+    }
+
+    pub fn broadcast_match_state(
+        sessions: Res<Sessions>,
+        storage: Res<Storage>,
+        assets: Res<AssetServer>,
+        mut state: ResMutInit<StreamOverlayState>,
+    ) {
+        let enabled = storage
+            .get::<Settings>()
+            .map(|x| x.stream_overlay)
+            .unwrap_or_default();
+        if !enabled {
+            return;
+        }
+
+        if state.server.is_none() && !state.failed_to_start {
+            match StreamOverlayServer::start(STREAM_OVERLAY_PORT) {
+                Ok(server) => state.server = Some(Arc::new(server)),
+                Err(e) => {
+                    error!("Could not start stream overlay server: {e}");
+                    state.failed_to_start = true;
+                }
+            }
+        }
+        let Some(server) = state.server.clone() else {
+            return;
+        };
+
+        state.frames_since_broadcast += 1;
+        if state.frames_since_broadcast < BROADCAST_INTERVAL {
+            return;
+        }
+        state.frames_since_broadcast = 0;
+
+        let Some(game_session) = sessions.get(SessionNames::GAME) else {
+            return;
+        };
+        let world = &game_session.world;
+        let (Some(entities), Some(map), Some(score), Some(event_log), Some(player_inputs)) = (
+            world.resources.get::<Entities>(),
+            world.resources.get::<LoadedMap>(),
+            world.resources.get::<MatchScore>(),
+            world.resources.get::<MatchEventLog>(),
+            world.resources.get::<MatchInputs>(),
+        ) else {
+            return;
+        };
+        let player_indexes = world.components.get::<PlayerIdx>().borrow();
+        let killed_players = world.components.get::<PlayerKilled>().borrow();
+
+        let players = entities
+            .iter_with((&*player_indexes, &Optional(&*killed_players)))
+            .map(|(_, (idx, killed))| {
+                let input = &player_inputs.players[idx.0 as usize];
+                json!({
+                    "index": idx.0,
+                    "name": assets.get(input.selected_player).name.as_str(),
+                    "score": score.score(*idx),
+                    "alive": killed.is_none(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let kills = state.take_new_kills(&event_log.events);
+
+        let message = json!({
+            "map": map.name.as_str(),
+            "round": score.rounds_completed() + 1,
+            "round_time": event_log.frame as f32 / FPS,
+            "players": players,
+            "kills": kills,
+        });
+        server.broadcast(message.to_string());
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sends_each_kill_once_per_round() {
+        let kill = MatchEvent {
+            frame: 40,
+            kind: MatchEventKind::Kill,
+            player: Some(1),
+            pos: [0.0, 0.0],
+        };
+        let death = MatchEvent {
+            kind: MatchEventKind::Death,
+            ..kill.clone()
+        };
+        let mut state = StreamOverlayState::default();
+
+        let events = vec![kill.clone(), death];
+        assert_eq!(state.take_new_kills(&events).len(), 1);
+        assert!(state.take_new_kills(&events).is_empty());
+
+        // The log is cleared when the next round starts
+        assert_eq!(state.take_new_kills(&[kill]).len(), 1);
+    }
+}
@@ -1,14 +1,23 @@
 use crate::prelude::*;
 
+pub mod focus;
+pub mod fonts;
+pub mod hud;
 pub mod main_menu;
 pub mod map_select;
 pub mod notification;
 pub mod pause_menu;
 pub mod player_image;
+pub mod post_processing;
 pub mod scoring;
+pub mod touch;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod chat;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod network_game;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ping;
 
 #[derive(HasSchema, Clone, Debug)]
 #[repr(C)]
@@ -17,6 +26,9 @@ pub struct UiTheme {
     pub colors: UiThemeColors,
     pub widgets: UiThemeWidgets,
     pub fonts: SVec<Handle<Font>>,
+    /// The font families to draw characters with when the font of the text doesn't have them, in
+    /// order. See [`fonts`].
+    pub fallback_families: SVec<String>,
     pub font_styles: UiThemeFontStyles,
     pub buttons: UiThemeButtons,
     pub panel: UiThemePanel,
@@ -30,6 +42,7 @@ impl Default for UiTheme {
             colors: default(),
             widgets: default(),
             fonts: default(),
+            fallback_families: default(),
             buttons: default(),
             font_styles: default(),
             panel: default(),
@@ -50,6 +63,8 @@ pub struct ImageMeta {
 pub struct UiThemeColors {
     pub positive: Color,
     pub negative: Color,
+    /// The color of the ring drawn around the focused widget.
+    pub focus: Color,
 }
 
 #[derive(HasSchema, Default, Debug, Clone)]
@@ -0,0 +1,284 @@
+//! Text chat between the players of network lobbies and matches.
+//!
+//! Chat messages are sent over the reliable channel of the match socket, which is shared with the
+//! lobby and scoring menus. Whoever drains the socket passes what it received through
+//! [`ChatState::filter_messages`], which takes the chat messages out and hands back the rest.
+//! During matches, the chat also drains the socket while the scoring menu doesn't.
+
+use std::time::Instant;
+
+use bones_framework::networking::{NetworkMatchSocket, NetworkSocket, SocketTarget, SyncingInfo};
+
+use crate::prelude::*;
+
+use super::{
+    hud::{player_color32, player_colors, KILL_FEED_HEIGHT_ID},
+    ping::NetworkPing,
+    scoring::ScoringMenuState,
+};
+
+pub fn game_plugin(game: &mut Game) {
+    game.init_shared_resource::<ChatState>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session.add_system_to_stage(Update, chat_overlay);
+}
+
+const CHAT_MESSAGE_MAGIC: u8 = 67;
+
+/// The number of messages kept in the scrollback.
+const MAX_CHAT_MESSAGES: usize = 50;
+
+/// The longest message that may be sent, in characters.
+const MAX_CHAT_MESSAGE_LEN: usize = 200;
+
+/// How long messages stay on screen during a match while the chat is closed.
+const CHAT_MESSAGE_DURATION: Duration = Duration::from_secs(8);
+
+/// The most received messages that are kept around for a menu that hasn't read them yet.
+const MAX_DEFERRED_MESSAGES: usize = 64;
+
+#[derive(Serialize, Deserialize)]
+struct ChatNetworkMessage {
+    magic: u8,
+    text: String,
+}
+
+/// A message in the chat scrollback.
+#[derive(Clone, Debug)]
+pub struct ChatMessage {
+    pub player: u32,
+    pub text: String,
+    pub received_at: Instant,
+}
+
+/// Shared resource with the chat scrollback and the state of the chat input.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct ChatState {
+    messages: VecDeque<ChatMessage>,
+    /// The players whose messages are hidden.
+    pub muted: HashSet<u32>,
+    /// Whether the local player is writing a message, during which their keyboard doesn't control
+    /// their character.
+    pub is_typing: bool,
+    /// Whether the message being written was sent with the enter key.
+    is_sending: bool,
+    input: String,
+    /// Messages that weren't chat messages, received while no menu was reading the socket.
+    deferred: Vec<(u32, Vec<u8>)>,
+}
+
+impl ChatState {
+    /// Take the chat messages out of the messages received from the socket, and return the others
+    /// along with any that were deferred earlier.
+    pub fn filter_messages(&mut self, messages: Vec<(u32, Vec<u8>)>) -> Vec<(u32, Vec<u8>)> {
+        let mut others = std::mem::take(&mut self.deferred);
+        for (player, data) in messages {
+            match postcard::from_bytes::<ChatNetworkMessage>(&data) {
+                Ok(message) if message.magic == CHAT_MESSAGE_MAGIC => {
+                    self.push(player, message.text);
+                }
+                _ => others.push((player, data)),
+            }
+        }
+        others
+    }
+
+    /// Keep messages that nobody could handle yet, so they can be read later.
+    pub fn defer(&mut self, messages: Vec<(u32, Vec<u8>)>) {
+        self.deferred.extend(messages);
+        let overflow = self.deferred.len().saturating_sub(MAX_DEFERRED_MESSAGES);
+        self.deferred.drain(..overflow);
+    }
+
+    /// Forget everything about the chat, once there is no lobby or match to chat in.
+    pub fn clear(&mut self) {
+        *self = default();
+    }
+
+    fn push(&mut self, player: u32, text: String) {
+        let text = text.chars().take(MAX_CHAT_MESSAGE_LEN).collect();
+        self.messages.push_back(ChatMessage {
+            player,
+            text,
+            received_at: Instant::now(),
+        });
+        while self.messages.len() > MAX_CHAT_MESSAGES {
+            self.messages.pop_front();
+        }
+    }
+
+    /// Open the chat input with the T key, send the message with enter, or close it with escape.
+    pub fn handle_keyboard(&mut self, keyboard: &KeyboardInputs) {
+        for event in &keyboard.key_events {
+            if event.button_state.pressed() {
+                continue;
+            }
+            match event.key_code.option() {
+                Some(KeyCode::T) if !self.is_typing => self.is_typing = true,
+                Some(KeyCode::Return) if self.is_typing => self.is_sending = true,
+                Some(KeyCode::Escape) if self.is_typing => {
+                    self.is_typing = false;
+                    self.input.clear();
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Show the scrollback, the input if the local player is typing, and mute toggles for the
+    /// other players. Messages sent by the local player are sent over the socket.
+    ///
+    /// If `fade` is set, only recent messages are shown while the chat is closed.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        meta: &GameMeta,
+        localization: &Localization<GameMeta>,
+        colors: &PlayerColorSettings,
+        socket: &(impl NetworkSocket + ?Sized),
+        fade: bool,
+    ) {
+        let local_player = socket.player_idx();
+        let text_style = &meta.theme.font_styles.smaller;
+
+        let now = Instant::now();
+        for message in &self.messages {
+            if self.muted.contains(&message.player) {
+                continue;
+            }
+            if fade && !self.is_typing && now - message.received_at > CHAT_MESSAGE_DURATION {
+                continue;
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.label(
+                    text_style
+                        .rich(localization.get_with(
+                            "chat-player",
+                            &fluent_args! { "player" => message.player + 1 },
+                        ))
+                        .color(player_color32(colors, PlayerIdx(message.player))),
+                );
+                ui.label(text_style.rich(&message.text));
+            });
+        }
+
+        if !self.is_typing {
+            if !fade {
+                ui.label(text_style.rich(localization.get("chat-hint")));
+            }
+            return;
+        }
+
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.input)
+                .char_limit(MAX_CHAT_MESSAGE_LEN)
+                .hint_text(localization.get("chat-hint"))
+                .font(text_style.id())
+                .desired_width(ui.available_width()),
+        );
+        response.request_focus();
+
+        if std::mem::take(&mut self.is_sending) {
+            let text = std::mem::take(&mut self.input).trim().to_string();
+            if !text.is_empty() {
+                socket.send_reliable(
+                    SocketTarget::All,
+                    &postcard::to_allocvec(&ChatNetworkMessage {
+                        magic: CHAT_MESSAGE_MAGIC,
+                        text: text.clone(),
+                    })
+                    .unwrap(),
+                );
+                self.push(local_player, text);
+            }
+            self.is_typing = false;
+        }
+
+        // Mute toggles
+        ui.horizontal(|ui| {
+            for player in (0..socket.player_count()).filter(|x| *x != local_player) {
+                let is_muted = self.muted.contains(&player);
+                let label = localization.get_with(
+                    if is_muted { "chat-unmute" } else { "chat-mute" },
+                    &fluent_args! { "player" => player + 1 },
+                );
+                if BorderedButton::themed(&meta.theme.buttons.small, label)
+                    .show(ui)
+                    .clicked()
+                {
+                    if is_muted {
+                        self.muted.remove(&player);
+                    } else {
+                        self.muted.insert(player);
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Show the chat in network lobbies and matches, and read chat messages during matches while the
+/// scoring menu isn't.
+fn chat_overlay(
+    meta: Root<GameMeta>,
+    localization: Localization<GameMeta>,
+    ctx: Res<EguiCtx>,
+    keyboard: Res<KeyboardInputs>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+    scoring_menu: Res<ScoringMenuState>,
+    mut chat: ResMut<ChatState>,
+    mut ping: ResMut<NetworkPing>,
+) {
+    let colors = player_colors(&storage);
+    // Stack the chat above the kill feed, which is in the same corner of the screen
+    let kill_feed_height = ctx
+        .data(|d| d.get_temp::<f32>(egui::Id::new(KILL_FEED_HEIGHT_ID)))
+        .map_or(0.0, |height| height + 8.0);
+    let mut show = |chat: &mut ChatState, socket: &dyn NetworkSocket, fade: bool| {
+        chat.handle_keyboard(&keyboard);
+        egui::Area::new("chat")
+            .anchor(
+                egui::Align2::LEFT_BOTTOM,
+                egui::vec2(8.0, -8.0 - kill_feed_height),
+            )
+            .order(egui::Order::Foreground)
+            .show(&ctx, |ui| {
+                ui.set_max_width(ctx.screen_rect().width() / 3.0);
+                chat.show(ui, &meta, &localization, &colors, socket, fade);
+            });
+    };
+
+    // In a match
+    let match_socket = sessions.get(SessionNames::GAME).and_then(|session| {
+        session
+            .world
+            .get_resource::<SyncingInfo>()
+            .and_then(|x| x.socket().cloned())
+    });
+    if let Some(socket) = match_socket {
+        if !scoring_menu.active {
+            let messages = ping.filter_messages(chat.filter_messages(socket.recv_reliable()));
+            chat.defer(messages);
+        }
+        show(&mut chat, &socket, true);
+        return;
+    }
+
+    // In a lobby, where the menus read the socket
+    let lobby_socket = sessions
+        .get(SessionNames::MAIN_MENU)
+        .and_then(|session| session.world.get_resource::<NetworkMatchSocket>());
+    if let Some(socket) = lobby_socket {
+        show(&mut chat, &socket, false);
+        // Let egui have the keyboard that the lobby menus usually take over
+        if chat.is_typing {
+            ctx.set_state(EguiInputSettings::default());
+        }
+        return;
+    }
+
+    chat.clear();
+}
@@ -0,0 +1,136 @@
+//! Focus navigation for the menus.
+//!
+//! The menus can be used without a mouse: the arrow keys, and the gamepad inputs that are turned
+//! into arrow keys in [`handle_egui_input`][crate::input::handle_egui_input], move the focus
+//! between the widgets, and confirming clicks the focused one. Buttons show that they are focused
+//! with their own border, and every other widget gets a ring drawn around it with
+//! [`FocusExt::focus_ring`].
+//!
+//! When speaking focused widgets is turned on in the settings, the label of each widget that gains
+//! focus is passed to the [`SpeechHook`], if there is one. The game only comes with a speech hook
+//! when it's built with the `tts` feature, but one may also be put in by the platform.
+
+use crate::prelude::*;
+
+/// How thick the focus ring is, in points.
+const FOCUS_RING_WIDTH: f32 = 2.0;
+
+/// How far out from the widget the focus ring is drawn, in points.
+const FOCUS_RING_MARGIN: f32 = 2.0;
+
+pub fn game_plugin(game: &mut Game) {
+    #[cfg(all(feature = "tts", not(target_arch = "wasm32")))]
+    game.insert_shared_resource(SpeechHook::tts());
+    game.systems.add_before_system(update_focus_state);
+}
+
+/// Shared resource with the function that focused widgets are spoken with.
+#[derive(HasSchema, Clone)]
+#[schema(no_default)]
+pub struct SpeechHook(Arc<dyn Fn(&str) + Send + Sync>);
+
+impl SpeechHook {
+    /// Create a speech hook that speaks with the given function.
+    pub fn new(speak: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(speak))
+    }
+
+    /// Speak some text.
+    pub fn speak(&self, text: &str) {
+        (self.0)(text)
+    }
+
+    /// Create a speech hook that speaks with the text-to-speech engine of the system.
+    ///
+    /// The engine runs on a thread of its own, and each text interrupts the one before it, so that
+    /// moving through the menus quickly doesn't queue up a backlog of labels.
+    #[cfg(all(feature = "tts", not(target_arch = "wasm32")))]
+    fn tts() -> Self {
+        let (sender, receiver) = async_channel::unbounded::<String>();
+        std::thread::spawn(move || {
+            let mut tts = match tts::Tts::default() {
+                Ok(tts) => tts,
+                Err(e) => {
+                    warn!("Couldn't start text-to-speech: {e}");
+                    return;
+                }
+            };
+            while let Ok(text) = receiver.recv_blocking() {
+                if let Err(e) = tts.speak(text, true) {
+                    warn!("Couldn't speak text: {e}");
+                }
+            }
+        });
+        Self::new(move |text| {
+            sender.try_send(text.to_string()).ok();
+        })
+    }
+}
+
+/// Egui state with what focused widgets are drawn and spoken with.
+#[derive(Clone, Default)]
+struct FocusState {
+    ring_color: egui::Color32,
+    /// The speech hook, if speaking focused widgets is turned on.
+    speech: Option<SpeechHook>,
+}
+
+/// Copy the focus ring color from the theme and the speech hook to the egui state, for the widgets
+/// to use.
+fn update_focus_state(game: &mut Game) {
+    let Some(ctx) = game.shared_resource::<EguiCtx>().map(|x| (*x).clone()) else {
+        return;
+    };
+    let assets = game.shared_resource::<AssetServer>().unwrap();
+    if !assets.load_progress.is_finished() {
+        return;
+    }
+    let [r, g, b, a] = assets.root::<GameMeta>().theme.colors.focus.as_rgba_u8();
+    let speak_focus = game
+        .shared_resource::<Storage>()
+        .and_then(|x| x.get::<Settings>().map(|x| x.speak_focus))
+        .unwrap_or_default();
+    let speech = game
+        .shared_resource::<SpeechHook>()
+        .filter(|_| speak_focus)
+        .map(|x| (*x).clone());
+
+    ctx.set_state(FocusState {
+        ring_color: egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+        speech,
+    });
+}
+
+/// Extension trait for showing which widget has focus.
+pub trait FocusExt {
+    /// Speak the label of the widget when it gains focus.
+    ///
+    /// This is for widgets that show their focus themselves, such as buttons.
+    fn speak_on_focus(self, ui: &egui::Ui, label: &str) -> Self;
+
+    /// Draw a ring around the widget while it has focus, and speak its label when it gains focus.
+    fn focus_ring(self, ui: &egui::Ui, label: &str) -> Self;
+}
+
+impl FocusExt for egui::Response {
+    fn speak_on_focus(self, ui: &egui::Ui, label: &str) -> Self {
+        if self.gained_focus() {
+            if let Some(speech) = &ui.ctx().get_state::<FocusState>().speech {
+                speech.speak(label);
+            }
+        }
+        self
+    }
+
+    fn focus_ring(self, ui: &egui::Ui, label: &str) -> Self {
+        if self.has_focus() {
+            let ring_color = ui.ctx().get_state::<FocusState>().ring_color;
+            ui.painter().rect_stroke(
+                self.rect.expand(FOCUS_RING_MARGIN),
+                2.0,
+                egui::Stroke::new(FOCUS_RING_WIDTH, ring_color),
+            );
+        }
+        self.speak_on_focus(ui, label)
+    }
+}
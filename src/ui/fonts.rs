@@ -0,0 +1,74 @@
+//! Fallback fonts for text that the theme fonts don't have glyphs for.
+//!
+//! Each font in [`UiTheme::fonts`] is registered with egui as a family of its own, so text in a
+//! family is only drawn with that one font, and characters that it doesn't have show up as boxes.
+//! The pixel font that the menus and the HUD use only covers latin text, which isn't enough for
+//! player names and translations in scripts such as Cyrillic or CJK.
+//!
+//! [`UiTheme::fallback_families`] lists the families that every other family falls back on, in
+//! order, so that a character missing from a font is looked for in each of them in turn. Fonts for
+//! more scripts can be added by listing them in [`UiTheme::fonts`] and their family names in the
+//! fallback families.
+
+use crate::prelude::*;
+
+pub fn game_plugin(game: &mut Game) {
+    game.systems.add_before_system(apply_font_fallbacks);
+}
+
+/// Append the fallback families of the theme to every font family that doesn't have them yet.
+///
+/// This checks the fonts every frame, so that the fallbacks are put back if the fonts are set
+/// again, such as when the theme fonts are hot reloaded.
+fn apply_font_fallbacks(game: &mut Game) {
+    let Some(ctx) = game.shared_resource::<EguiCtx>().map(|x| (*x).clone()) else {
+        return;
+    };
+    // The fonts aren't available until egui has run once
+    if ctx.frame_nr() == 0 {
+        return;
+    }
+    let assets = game.shared_resource::<AssetServer>().unwrap();
+    if !assets.load_progress.is_finished() {
+        return;
+    }
+    let meta = assets.root::<GameMeta>();
+    let fallbacks = &meta.theme.fallback_families;
+    if fallbacks.is_empty() {
+        return;
+    }
+
+    let missing_fallbacks = |definitions: &egui::FontDefinitions, fonts: &[String]| {
+        fallbacks
+            .iter()
+            // Families may only refer to fonts that are loaded
+            .filter(|x| definitions.font_data.contains_key(x.as_str()))
+            .filter(|x| !fonts.contains(x))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    // Only clone the font definitions when they need changing, since they hold the font data
+    let needs_update = ctx.fonts(|fonts| {
+        let definitions = fonts.lock().fonts.definitions();
+        definitions
+            .families
+            .values()
+            .any(|fonts| !missing_fallbacks(definitions, fonts).is_empty())
+    });
+    if !needs_update {
+        return;
+    }
+
+    let mut definitions = ctx.fonts(|fonts| fonts.lock().fonts.definitions().clone());
+    let families = definitions.families.clone();
+    for (family, fonts) in families {
+        let missing = missing_fallbacks(&definitions, &fonts);
+        definitions
+            .families
+            .get_mut(&family)
+            .unwrap()
+            .extend(missing);
+    }
+    ctx.set_fonts(definitions);
+}
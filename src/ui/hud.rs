@@ -0,0 +1,1382 @@
+//! In-game heads up display, drawn over the running match.
+
+use crate::{
+    audio::announcer::{AnnouncerLine, AnnouncerState},
+    mods::ModList,
+    prelude::*,
+    PackMeta,
+};
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .add_system_to_stage(Update, hot_seat_hud)
+        .add_system_to_stage(Update, infection_hud)
+        .add_system_to_stage(Update, damage_numbers)
+        .add_system_to_stage(Update, boss_health_bars)
+        .add_system_to_stage(Update, name_tags)
+        .add_system_to_stage(Update, edge_indicators)
+        .add_system_to_stage(Update, damage_indicators)
+        .add_system_to_stage(Update, afk_warnings)
+        .add_system_to_stage(Update, kill_feed)
+        .add_system_to_stage(Update, rivalry_banners)
+        .add_system_to_stage(Update, announcer_subtitles)
+        .add_system_to_stage(Update, minimap)
+        .add_system_to_stage(Update, training_hud)
+        .add_system_to_stage(Update, frame_data_hud)
+        .add_system_to_stage(Update, map_vote_hud);
+}
+
+/// The size of the edge indicator arrows, in points.
+const ARROW_SIZE: f32 = 10.0;
+
+/// The size of the boss health bars, in points.
+const BOSS_HEALTH_BAR_SIZE: egui::Vec2 = egui::vec2(200.0, 8.0);
+
+/// The size of the hazard icons in the kill feed.
+const HAZARD_ICON_SIZE: f32 = 12.0;
+
+/// The size of the player markers in the kill feed.
+const KILL_FEED_MARKER_SIZE: f32 = 10.0;
+
+/// The egui ID that the height of the kill feed is stored under while it is shown, so that the chat
+/// can be stacked above it.
+pub(crate) const KILL_FEED_HEIGHT_ID: &str = "kill_feed_height";
+
+/// The radius of the player markers on name tags, in points.
+const NAME_TAG_MARKER_RADIUS: f32 = 5.0;
+
+/// How long the subtitle of an announcer line stays on screen.
+const SUBTITLE_TIME: Duration = Duration::from_secs(2);
+
+/// The part of the game world visible through the game camera, used to place things on screen.
+pub(crate) struct CameraView {
+    center: Vec2,
+    size: Vec2,
+    mirrored: bool,
+    screen: egui::Rect,
+}
+
+impl CameraView {
+    /// Get the view of the first camera in the game world.
+    pub(crate) fn new(world: &World, screen: egui::Rect) -> Option<Self> {
+        let entities = world.resources.get::<Entities>()?;
+        let cameras = world.components.get::<Camera>().borrow();
+        let transforms = world.components.get::<Transform>().borrow();
+
+        let (_, (camera, transform)) = entities.iter_with((&*cameras, &*transforms)).next()?;
+        let CameraSize::FixedHeight(height) = camera.size else {
+            return None;
+        };
+
+        Some(Self {
+            center: transform.translation.xy(),
+            size: vec2(height * screen.aspect_ratio(), height),
+            // The view is flipped in mirror matches
+            mirrored: transform.scale.x < 0.0,
+            screen,
+        })
+    }
+
+    /// Get the offset of a world position from the center of the view, in world units.
+    pub(crate) fn offset(&self, pos: Vec2) -> Vec2 {
+        let mut offset = pos - self.center;
+        if self.mirrored {
+            offset.x = -offset.x;
+        }
+        offset
+    }
+
+    /// Get the screen position of a view offset.
+    pub(crate) fn screen_pos(&self, offset: Vec2) -> egui::Pos2 {
+        let screen_offset = offset / self.size * vec2(self.screen.width(), -self.screen.height());
+        self.screen.center() + egui::vec2(screen_offset.x, screen_offset.y)
+    }
+
+    /// Get the world position under a screen position.
+    pub(crate) fn world_pos(&self, screen_pos: egui::Pos2) -> Vec2 {
+        let screen_offset = screen_pos - self.screen.center();
+        let offset = vec2(screen_offset.x, screen_offset.y) * self.size
+            / vec2(self.screen.width(), -self.screen.height());
+        // Mirroring the offset again undoes it
+        self.center + self.offset(self.center + offset)
+    }
+
+    /// Get where to put an arrow at the edge of the screen pointing towards the given view offset,
+    /// and the direction it should point in.
+    fn edge_arrow(&self, offset: Vec2) -> (egui::Pos2, egui::Vec2) {
+        let screen = self.screen;
+        let screen_offset = offset / self.size * vec2(screen.width(), -screen.height());
+        let edge = screen.shrink(ARROW_SIZE * 2.0);
+        let pos = egui::pos2(
+            (screen.center().x + screen_offset.x).clamp(edge.left(), edge.right()),
+            (screen.center().y + screen_offset.y).clamp(edge.top(), edge.bottom()),
+        );
+        (
+            pos,
+            egui::vec2(screen_offset.x, screen_offset.y).normalized(),
+        )
+    }
+}
+
+/// Draw an arrow at the given position.
+fn paint_arrow(painter: &egui::Painter, pos: egui::Pos2, dir: egui::Vec2, color: egui::Color32) {
+    let side = dir.rot90() * ARROW_SIZE * 0.6;
+    painter.add(egui::Shape::convex_polygon(
+        vec![pos + dir * ARROW_SIZE, pos + side, pos - side],
+        color,
+        egui::Stroke::new(1.0, egui::Color32::BLACK),
+    ));
+}
+
+/// Draw a round marker for a player in their color, with their pattern drawn over it in black.
+fn paint_player_marker(
+    painter: &egui::Painter,
+    center: egui::Pos2,
+    radius: f32,
+    color: egui::Color32,
+    pattern: PlayerPattern,
+) {
+    let mark_color = egui::Color32::from_black_alpha(color.a());
+    let mark = egui::Stroke::new((radius / 3.0).max(1.0), mark_color);
+    painter.circle(center, radius, color, egui::Stroke::new(1.0, mark_color));
+    match pattern {
+        PlayerPattern::Solid => (),
+        PlayerPattern::Stripes => {
+            // Two vertical stripes, ending at the edge of the marker
+            for x in [-radius / 2.5, radius / 2.5] {
+                let half_height = (radius * radius - x * x).sqrt();
+                painter.line_segment(
+                    [
+                        center + egui::vec2(x, -half_height),
+                        center + egui::vec2(x, half_height),
+                    ],
+                    mark,
+                );
+            }
+        }
+        PlayerPattern::Dots => {
+            for x in [-radius / 2.5, radius / 2.5] {
+                painter.circle_filled(
+                    center + egui::vec2(x, 0.0),
+                    (radius / 4.0).max(0.75),
+                    mark_color,
+                );
+            }
+        }
+        PlayerPattern::Cross => {
+            painter.line_segment(
+                [
+                    center - egui::vec2(radius, 0.0),
+                    center + egui::vec2(radius, 0.0),
+                ],
+                mark,
+            );
+            painter.line_segment(
+                [
+                    center - egui::vec2(0.0, radius),
+                    center + egui::vec2(0.0, radius),
+                ],
+                mark,
+            );
+        }
+    }
+}
+
+/// Get the player color settings, or the defaults if there are no settings yet.
+pub(crate) fn player_colors(storage: &Storage) -> PlayerColorSettings {
+    storage
+        .get::<Settings>()
+        .map(|x| x.player_colors.clone())
+        .unwrap_or_default()
+}
+
+/// Get the color of a player as an egui color.
+pub(crate) fn player_color32(colors: &PlayerColorSettings, player: PlayerIdx) -> egui::Color32 {
+    let [r, g, b, a] = colors.color(player).as_rgba_u8();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Draw the kill feed icon of a hazard in the given rectangle.
+fn paint_hazard_icon(painter: &egui::Painter, rect: egui::Rect, kind: HazardKind, alpha: u8) {
+    match kind {
+        HazardKind::Spikes => painter.add(egui::Shape::convex_polygon(
+            vec![rect.left_bottom(), rect.center_top(), rect.right_bottom()],
+            egui::Color32::from_rgba_unmultiplied(200, 200, 210, alpha),
+            egui::Stroke::new(1.0, egui::Color32::BLACK),
+        )),
+        HazardKind::Lava => painter.add(egui::Shape::circle_filled(
+            rect.center(),
+            rect.width() / 2.0,
+            egui::Color32::from_rgba_unmultiplied(255, 110, 20, alpha),
+        )),
+    };
+}
+
+/// Shows whose turn it is in hot-seat mode, and how long everybody has survived.
+fn hot_seat_hud(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    assets: Res<AssetServer>,
+    localization: Localization<GameMeta>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(state), Some(player_inputs)) = (
+        world.resources.get::<HotSeatState>(),
+        world.resources.get::<MatchInputs>(),
+    ) else {
+        return;
+    };
+    let player_name = |idx: PlayerIdx| {
+        assets
+            .get(player_inputs.players[idx.0 as usize].selected_player)
+            .name
+            .to_string()
+    };
+
+    let status = match state.phase {
+        HotSeatPhase::Trap => state.trapper().map(|player| {
+            localization.get_with(
+                "hot-seat-trap-phase",
+                &fluent_args! {
+                    "player" => player_name(player),
+                    "seconds" => state.timer.remaining_secs().ceil(),
+                },
+            )
+        }),
+        HotSeatPhase::Survive => state.survivor().map(|player| {
+            localization.get_with(
+                "hot-seat-survive-phase",
+                &fluent_args! {
+                    "player" => player_name(player),
+                    "seconds" => format!("{:.1}", state.timer.elapsed_secs()),
+                },
+            )
+        }),
+        HotSeatPhase::RoundOver => Some(match state.winner {
+            Some(player) => localization.get_with(
+                "hot-seat-round-winner",
+                &fluent_args! { "player" => player_name(player) },
+            ),
+            None => localization.get("hot-seat-round-draw"),
+        }),
+    };
+
+    egui::Area::new("hot_seat_hud")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 8.0))
+        .interactable(false)
+        .show(&ctx, |ui| {
+            BorderedFrame::new(&meta.theme.panel.border)
+                .padding(meta.theme.panel.padding)
+                .show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        let heading = meta
+                            .theme
+                            .font_styles
+                            .bigger
+                            .with_color(meta.theme.panel.font_color);
+                        let normal = meta
+                            .theme
+                            .font_styles
+                            .normal
+                            .with_color(meta.theme.panel.font_color);
+
+                        if let Some(status) = status {
+                            ui.label(heading.rich(status));
+                        }
+                        for player in &state.turn_order {
+                            let survived = state.survival_times[player.0 as usize];
+                            ui.label(normal.rich(format!(
+                                "{}: {:.1}s",
+                                player_name(*player),
+                                survived.as_secs_f32()
+                            )));
+                        }
+                    });
+                });
+        });
+}
+
+/// Shows how long the survivors have left to hold out in infection mode.
+fn infection_hud(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    localization: Localization<GameMeta>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(state), Some(player_inputs)) = (
+        world.resources.get::<InfectionState>(),
+        world.resources.get::<MatchInputs>(),
+    ) else {
+        return;
+    };
+    if state.patient_zero.is_none() {
+        return;
+    }
+
+    let (infected, survivors) = (0..MAX_PLAYERS)
+        .filter(|i| player_inputs.players[*i as usize].active)
+        .partition::<Vec<_>, _>(|i| state.is_infected(PlayerIdx(*i)));
+
+    let status = match &state.winners {
+        Some(_) if survivors.is_empty() => localization.get("infection-infected-win"),
+        Some(_) => localization.get("infection-survivors-win"),
+        None => localization.get_with(
+            "infection-time-left",
+            &fluent_args! { "seconds" => state.timer.remaining_secs().ceil() },
+        ),
+    };
+    let teams = localization.get_with(
+        "infection-teams",
+        &fluent_args! {
+            "survivors" => survivors.len(),
+            "infected" => infected.len(),
+        },
+    );
+
+    egui::Area::new("infection_hud")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 8.0))
+        .interactable(false)
+        .show(&ctx, |ui| {
+            BorderedFrame::new(&meta.theme.panel.border)
+                .padding(meta.theme.panel.padding)
+                .show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        let font_color = meta.theme.panel.font_color;
+                        ui.label(
+                            meta.theme
+                                .font_styles
+                                .bigger
+                                .with_color(font_color)
+                                .rich(status),
+                        );
+                        ui.label(
+                            meta.theme
+                                .font_styles
+                                .normal
+                                .with_color(font_color)
+                                .rich(teams),
+                        );
+                    });
+                });
+        });
+}
+
+/// Show the name and health of every boss in the match at the bottom of the screen.
+fn boss_health_bars(meta: Root<GameMeta>, ctx: Res<EguiCtx>, sessions: Res<Sessions>) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let Some(entities) = world.resources.get::<Entities>() else {
+        return;
+    };
+    let bosses = world.components.get::<Boss>().borrow();
+    let bosses = entities
+        .iter_with(&*bosses)
+        .map(|(_, boss)| (boss.name, boss.health_fraction()))
+        .collect::<Vec<_>>();
+    if bosses.is_empty() {
+        return;
+    }
+
+    egui::Area::new("boss_health_bars")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -8.0))
+        .interactable(false)
+        .show(&ctx, |ui| {
+            BorderedFrame::new(&meta.theme.panel.border)
+                .padding(meta.theme.panel.padding)
+                .show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        for (name, health) in bosses {
+                            ui.label(
+                                meta.theme
+                                    .font_styles
+                                    .normal
+                                    .with_color(meta.theme.panel.font_color)
+                                    .rich(name.as_str()),
+                            );
+                            let (rect, _) =
+                                ui.allocate_exact_size(BOSS_HEALTH_BAR_SIZE, egui::Sense::hover());
+                            let painter = ui.painter();
+                            painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(140));
+                            let mut filled = rect;
+                            filled.set_width(rect.width() * health.clamp(0.0, 1.0));
+                            painter.rect_filled(filled, 2.0, egui::Color32::from_rgb(200, 40, 40));
+                        }
+                    });
+                });
+        });
+}
+
+/// Show a tag over each player with their name and their marker while player patterns are turned
+/// on, so that players can be told apart by more than their color.
+fn name_tags(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    assets: Res<AssetServer>,
+    storage: Res<Storage>,
+) {
+    let colors = player_colors(&storage);
+    if !colors.patterns {
+        return;
+    }
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(entities), Some(player_inputs), Some(view)) = (
+        world.resources.get::<Entities>(),
+        world.resources.get::<MatchInputs>(),
+        CameraView::new(world, ctx.screen_rect()),
+    ) else {
+        return;
+    };
+    let transforms = world.components.get::<Transform>().borrow();
+    let player_indexes = world.components.get::<PlayerIdx>().borrow();
+    let killed_players = world.components.get::<PlayerKilled>().borrow();
+    let bodies = world.components.get::<KinematicBody>().borrow();
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("name_tags"),
+    ));
+    for (player_ent, (idx, transform, body)) in
+        entities.iter_with((&*player_indexes, &*transforms, &*bodies))
+    {
+        if killed_players.contains(player_ent) {
+            continue;
+        }
+        let top = body.bounding_box(*transform).max.y;
+        let pos =
+            view.screen_pos(view.offset(vec2(transform.translation.x, top))) - egui::vec2(0.0, 4.0);
+        let color = player_color32(&colors, *idx);
+        let name = assets
+            .get(player_inputs.players[idx.0 as usize].selected_player)
+            .name
+            .to_string();
+
+        // The name, with the marker to the left of it
+        let text_rect = painter.text(
+            pos + egui::vec2(NAME_TAG_MARKER_RADIUS, 0.0),
+            egui::Align2::CENTER_BOTTOM,
+            name,
+            meta.theme.font_styles.smaller.id(),
+            color,
+        );
+        paint_player_marker(
+            &painter,
+            egui::pos2(
+                text_rect.left() - NAME_TAG_MARKER_RADIUS * 1.5,
+                text_rect.center().y,
+            ),
+            NAME_TAG_MARKER_RADIUS,
+            color,
+            colors.pattern(*idx),
+        );
+    }
+}
+
+/// Draw the [`DamageNumber`]s over the players that were hit, fading them out as they drift up.
+///
+/// They are drawn when they are turned on in the settings, and always in training matches.
+fn damage_numbers(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let is_training = world.get_resource::<TrainingState>().is_some();
+    if !is_training && !storage.get::<Settings>().is_some_and(|x| x.damage_numbers) {
+        return;
+    }
+    let (Some(entities), Some(view)) = (
+        world.resources.get::<Entities>(),
+        CameraView::new(world, ctx.screen_rect()),
+    ) else {
+        return;
+    };
+    let transforms = world.components.get::<Transform>().borrow();
+    let numbers = world.components.get::<DamageNumber>().borrow();
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("damage_numbers"),
+    ));
+    for (_, (number, transform)) in entities.iter_with((&*numbers, &*transforms)) {
+        let alpha = ((1.0 - number.progress(&meta.core.damage_numbers)) * 255.0) as u8;
+        let pos = view.screen_pos(view.offset(transform.translation.xy()));
+        // Critical hits stand out with a bigger number in the negative color
+        let (text, font, color) = if number.crit {
+            let [r, g, b, _] = meta.theme.colors.negative.as_rgba_u8();
+            (
+                format!("{}!", number.amount),
+                &meta.theme.font_styles.bigger,
+                egui::Color32::from_rgba_unmultiplied(r, g, b, alpha),
+            )
+        } else {
+            (
+                number.amount.to_string(),
+                &meta.theme.font_styles.normal,
+                egui::Color32::from_rgba_unmultiplied(255, 255, 255, alpha),
+            )
+        };
+        painter.text(pos, egui::Align2::CENTER_BOTTOM, text, font.id(), color);
+    }
+}
+
+/// Point out players that are close to, or past, the edge of the screen, so that they don't get
+/// lost during chases.
+fn edge_indicators(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(entities), Some(map), Some(view)) = (
+        world.resources.get::<Entities>(),
+        world.resources.get::<LoadedMap>(),
+        CameraView::new(world, ctx.screen_rect()),
+    ) else {
+        return;
+    };
+    let transforms = world.components.get::<Transform>().borrow();
+    let player_indexes = world.components.get::<PlayerIdx>().borrow();
+    let killed_players = world.components.get::<PlayerKilled>().borrow();
+
+    let colors = player_colors(&storage);
+    let half_view = view.size / 2.0;
+    let inner_view = (half_view - meta.core.camera.edge_indicator_margin).max(Vec2::ZERO);
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("edge_indicators"),
+    ));
+
+    for (player_ent, (idx, transform)) in entities.iter_with((&*player_indexes, &*transforms)) {
+        if killed_players.contains(player_ent) {
+            continue;
+        }
+
+        let offset = view.offset(transform.translation.xy());
+        if offset.x.abs() <= inner_view.x && offset.y.abs() <= inner_view.y {
+            continue;
+        }
+
+        let (pos, dir) = view.edge_arrow(offset);
+        let color = player_color32(&colors, *idx);
+        paint_arrow(&painter, pos, dir, color);
+
+        // Show how far away players that are off-screen are, in tiles
+        let outside = (offset.abs() - half_view).max(Vec2::ZERO);
+        if outside != Vec2::ZERO {
+            let distance = (outside.length() / map.tile_size.x).ceil();
+            painter.text(
+                pos - dir * ARROW_SIZE * 1.5,
+                egui::Align2::CENTER_CENTER,
+                format!("{distance}m"),
+                egui::FontId::proportional(meta.theme.font_styles.normal.size),
+                color,
+            );
+        }
+    }
+}
+
+/// Flash arrows at the edge of the screen pointing towards off-screen explosions, and towards
+/// bullets headed for a local player. The arrows stay on instead of flashing with reduced motion.
+fn damage_indicators(ctx: Res<EguiCtx>, sessions: Res<Sessions>, storage: Res<Storage>) {
+    /// How close a bullet's path must pass by a local player to count as aimed at them, in pixels.
+    const AIM_TOLERANCE: f32 = 24.0;
+    /// How many frames each flash of the arrows lasts.
+    const FLASH_FRAMES: u32 = 6;
+
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(entities), Some(damage_events), Some(player_inputs), Some(time), Some(view)) = (
+        world.resources.get::<Entities>(),
+        world.resources.get::<DamageEvents>(),
+        world.resources.get::<MatchInputs>(),
+        world.resources.get::<Time>(),
+        CameraView::new(world, ctx.screen_rect()),
+    ) else {
+        return;
+    };
+    let transforms = world.components.get::<Transform>().borrow();
+    let bullets = world.components.get::<Bullet>().borrow();
+    let player_indexes = world.components.get::<PlayerIdx>().borrow();
+    let killed_players = world.components.get::<PlayerKilled>().borrow();
+
+    let half_view = view.size / 2.0;
+    let is_offscreen = |offset: Vec2| offset.x.abs() > half_view.x || offset.y.abs() > half_view.y;
+
+    // Positions of the players controlled on this device
+    let local_players = entities
+        .iter_with((&*player_indexes, &*transforms))
+        .filter(|(ent, (idx, _))| {
+            !killed_players.contains(*ent)
+                && player_inputs.players[idx.0 as usize]
+                    .control_source
+                    .is_some()
+        })
+        .map(|(_, (_, transform))| transform.translation.xy())
+        .collect::<Vec<_>>();
+    if local_players.is_empty() {
+        return;
+    }
+
+    // Pairs of view offsets and how faded the arrow pointing at them is
+    let mut threats = damage_events
+        .events
+        .iter()
+        .map(|event| {
+            let fade = 1.0 - event.age as f32 / DAMAGE_EVENT_LIFETIME as f32;
+            (view.offset(event.pos), fade)
+        })
+        .collect::<Vec<_>>();
+    for (_, (bullet, transform)) in entities.iter_with((&*bullets, &*transforms)) {
+        let pos = transform.translation.xy();
+        let direction = bullet.direction.normalize_or_zero();
+        let is_incoming = local_players.iter().any(|player| {
+            let to_player = *player - pos;
+            let along = to_player.dot(direction);
+            along > 0.0 && (to_player - direction * along).length() < AIM_TOLERANCE
+        });
+        if is_incoming {
+            threats.push((view.offset(pos), 1.0));
+        }
+    }
+
+    // Flash the arrows on and off
+    let frame = (time.elapsed().as_secs_f32() * FPS) as u32;
+    if (frame / FLASH_FRAMES) % 2 == 1 && !is_reduced_motion(&storage) {
+        return;
+    }
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("damage_indicators"),
+    ));
+    for (offset, fade) in threats {
+        if !is_offscreen(offset) {
+            continue;
+        }
+        let (pos, dir) = view.edge_arrow(offset);
+        let color = egui::Color32::from_rgba_unmultiplied(230, 40, 40, (fade * 255.0) as u8);
+        paint_arrow(&painter, pos, dir, color);
+    }
+}
+
+/// Show a warning over players that are about to be flagged as AFK, and mark the ones that are.
+fn afk_warnings(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    localization: Localization<GameMeta>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(entities), Some(afk_players), Some(view)) = (
+        world.resources.get::<Entities>(),
+        world.resources.get::<AfkPlayers>(),
+        CameraView::new(world, ctx.screen_rect()),
+    ) else {
+        return;
+    };
+    let transforms = world.components.get::<Transform>().borrow();
+    let bodies = world.components.get::<KinematicBody>().borrow();
+    let player_indexes = world.components.get::<PlayerIdx>().borrow();
+
+    let afk = &meta.core.afk;
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("afk_warnings"),
+    ));
+    for (_ent, (idx, transform, body)) in
+        entities.iter_with((&*player_indexes, &*transforms, &*bodies))
+    {
+        let (text, color) = if afk_players.is_afk(*idx) {
+            (localization.get("afk"), meta.theme.colors.negative)
+        } else if afk_players.is_warned(*idx, afk) {
+            let left = afk
+                .idle_time
+                .saturating_sub(afk_players.idle[idx.0 as usize]);
+            let text = localization.get_with(
+                "afk-warning",
+                &fluent_args! { "seconds" => left.as_secs_f32().ceil() as u32 },
+            );
+            (text, meta.theme.panel.font_color)
+        } else {
+            continue;
+        };
+
+        // Just above the player's head
+        let head = vec2(transform.translation.x, body.bounding_box(*transform).max.y);
+        let [r, g, b, a] = color.as_rgba_u8();
+        painter.text(
+            view.screen_pos(view.offset(head)) - egui::vec2(0.0, 4.0),
+            egui::Align2::CENTER_BOTTOM,
+            text,
+            meta.theme.font_styles.normal.id(),
+            egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+        );
+    }
+}
+
+/// List the recent kills in the corner of the screen, such as "A + B eliminated C", with the
+/// killer first, followed by the players that got an assist, and then the players that were
+/// crushed or killed by hazards, with an icon for the hazard.
+fn kill_feed(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    assets: Res<AssetServer>,
+    storage: Res<Storage>,
+    localization: Localization<GameMeta>,
+) {
+    let height_id = egui::Id::new(KILL_FEED_HEIGHT_ID);
+    ctx.data_mut(|d| d.remove::<f32>(height_id));
+
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(kill_feed), Some(crush_events), Some(hazard_kill_events), Some(player_inputs)) = (
+        world.resources.get::<KillFeed>(),
+        world.resources.get::<CrushEvents>(),
+        world.resources.get::<HazardKillEvents>(),
+        world.resources.get::<MatchInputs>(),
+    ) else {
+        return;
+    };
+    if kill_feed.entries.is_empty()
+        && crush_events.events.is_empty()
+        && hazard_kill_events.events.is_empty()
+    {
+        return;
+    }
+    let player_name = |idx: PlayerIdx| {
+        assets
+            .get(player_inputs.players[idx.0 as usize].selected_player)
+            .name
+            .to_string()
+    };
+
+    let colors = player_colors(&storage);
+
+    let fade = |age: u32| ((1.0 - age as f32 / KILL_FEED_LIFETIME as f32) * 255.0) as u8;
+    // Each line starts with the marker of the player it is about
+    let feed_line = |ui: &mut egui::Ui, player: PlayerIdx, text: String, age: u32| {
+        let (rect, _) = ui.allocate_exact_size(
+            egui::Vec2::splat(KILL_FEED_MARKER_SIZE),
+            egui::Sense::hover(),
+        );
+        let [r, g, b, _] = colors.color(player).as_rgba_u8();
+        paint_player_marker(
+            ui.painter(),
+            rect.center(),
+            KILL_FEED_MARKER_SIZE / 2.0,
+            egui::Color32::from_rgba_unmultiplied(r, g, b, fade(age)),
+            colors.pattern(player),
+        );
+
+        let [r, g, b, _] = meta.theme.panel.font_color.as_rgba_u8();
+        ui.label(
+            meta.theme
+                .font_styles
+                .normal
+                .rich(text)
+                .color(egui::Color32::from_rgba_unmultiplied(r, g, b, fade(age))),
+        );
+    };
+
+    let response = egui::Area::new("kill_feed")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(8.0, -8.0))
+        .interactable(false)
+        .show(&ctx, |ui| {
+            for entry in &kill_feed.entries {
+                let attackers = std::iter::once(entry.killer)
+                    .chain(entry.assists.iter().copied())
+                    .map(player_name)
+                    .collect::<Vec<_>>()
+                    .join(" + ");
+                let text = localization.get_with(
+                    "kill-feed",
+                    &fluent_args! {
+                        "attackers" => attackers,
+                        "victim" => player_name(entry.victim),
+                    },
+                );
+                ui.horizontal(|ui| feed_line(ui, entry.killer, text, entry.age));
+            }
+            for event in &crush_events.events {
+                let text = localization.get_with(
+                    "kill-feed-crushed",
+                    &fluent_args! { "victim" => player_name(event.player) },
+                );
+                ui.horizontal(|ui| feed_line(ui, event.player, text, event.age));
+            }
+            for event in &hazard_kill_events.events {
+                let key = match event.kind {
+                    HazardKind::Spikes => "kill-feed-spikes",
+                    HazardKind::Lava => "kill-feed-lava",
+                };
+                let text = localization
+                    .get_with(key, &fluent_args! { "victim" => player_name(event.player) });
+                ui.horizontal(|ui| {
+                    let (rect, _) = ui.allocate_exact_size(
+                        egui::Vec2::splat(HAZARD_ICON_SIZE),
+                        egui::Sense::hover(),
+                    );
+                    paint_hazard_icon(ui.painter(), rect, event.kind, fade(event.age));
+                    feed_line(ui, event.player, text, event.age);
+                });
+            }
+        });
+    let height = response.response.rect.height();
+    ctx.data_mut(|d| d.insert_temp(height_id, height));
+}
+
+/// Show a banner for revenge kills and dominations between rivals.
+fn rivalry_banners(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    assets: Res<AssetServer>,
+    storage: Res<Storage>,
+    localization: Localization<GameMeta>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(rivalry_callouts), Some(player_inputs)) = (
+        world.resources.get::<RivalryCallouts>(),
+        world.resources.get::<MatchInputs>(),
+    ) else {
+        return;
+    };
+    // Only the latest callout is shown, so that banners don't pile up
+    let Some(callout) = rivalry_callouts.callouts.last() else {
+        return;
+    };
+    let player_name = |idx: PlayerIdx| {
+        assets
+            .get(player_inputs.players[idx.0 as usize].selected_player)
+            .name
+            .to_string()
+    };
+    let key = match callout.kind {
+        RivalryKind::Revenge => "rivalry-revenge",
+        RivalryKind::Domination => "rivalry-domination",
+    };
+    let text = localization.get_with(
+        key,
+        &fluent_args! {
+            "killer" => player_name(callout.killer),
+            "victim" => player_name(callout.victim),
+        },
+    );
+    let color = player_color32(&player_colors(&storage), callout.killer);
+
+    egui::Area::new("rivalry_banner")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 64.0))
+        .interactable(false)
+        .show(&ctx, |ui| {
+            BorderedFrame::new(&meta.theme.panel.border)
+                .padding(meta.theme.panel.padding)
+                .show(ui, |ui| {
+                    ui.label(meta.theme.font_styles.bigger.rich(text).color(color));
+                });
+        });
+}
+
+/// Show a subtitle for the last announcer line at the bottom of the screen, if subtitles are
+/// turned on.
+fn announcer_subtitles(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+    localization: Localization<GameMeta>,
+) {
+    if !storage.get::<Settings>().is_some_and(|x| x.subtitles) {
+        return;
+    }
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(announcer), Some(time)) = (
+        world.resources.get::<AnnouncerState>(),
+        world.resources.get::<Time>(),
+    ) else {
+        return;
+    };
+    let Some((line, said_at)) = announcer.last_line() else {
+        return;
+    };
+    if time.elapsed() >= said_at + SUBTITLE_TIME {
+        return;
+    }
+    let text = match line {
+        AnnouncerLine::Countdown(seconds) => localization.get_with(
+            line.localization_key(),
+            &fluent_args! { "seconds" => seconds },
+        ),
+        _ => localization.get(line.localization_key()).into_owned(),
+    };
+
+    egui::Area::new("announcer_subtitle")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -48.0))
+        .interactable(false)
+        .show(&ctx, |ui| {
+            BorderedFrame::new(&meta.theme.panel.border)
+                .padding(meta.theme.panel.padding)
+                .show(ui, |ui| {
+                    ui.label(
+                        meta.theme
+                            .font_styles
+                            .normal
+                            .with_color(meta.theme.panel.font_color)
+                            .rich(text),
+                    );
+                });
+        });
+}
+
+/// Draw an overview of the whole map in the corner of the screen, with the players and the items
+/// lying around.
+fn minimap(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+) {
+    /// The width of the minimap, in points.
+    const MINIMAP_WIDTH: f32 = 160.0;
+    /// The distance between the minimap and the edges of the screen, in points.
+    const MINIMAP_MARGIN: f32 = 8.0;
+
+    if !storage.get::<Settings>().is_some_and(|x| x.minimap) {
+        return;
+    }
+    let colors = player_colors(&storage);
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(entities), Some(map)) = (
+        world.resources.get::<Entities>(),
+        world.resources.get::<LoadedMap>(),
+    ) else {
+        return;
+    };
+    let infection = world.resources.get::<InfectionState>();
+    let tile_layers = world.components.get::<TileLayer>().borrow();
+    let tile_collisions = world.components.get::<TileCollisionKind>().borrow();
+    let transforms = world.components.get::<Transform>().borrow();
+    let player_indexes = world.components.get::<PlayerIdx>().borrow();
+    let killed_players = world.components.get::<PlayerKilled>().borrow();
+    let items = world.components.get::<Item>().borrow();
+    let inventories = world.components.get::<Inventory>().borrow();
+
+    let map_size = map.grid_size.as_vec2() * map.tile_size;
+    if map_size.x <= 0.0 || map_size.y <= 0.0 {
+        return;
+    }
+    let scale = MINIMAP_WIDTH / map_size.x;
+    let screen = ctx.screen_rect();
+    let rect = egui::Rect::from_min_size(
+        egui::pos2(
+            screen.right() - MINIMAP_MARGIN - MINIMAP_WIDTH,
+            screen.top() + MINIMAP_MARGIN,
+        ),
+        egui::vec2(MINIMAP_WIDTH, map_size.y * scale),
+    );
+    // Map positions are y-up, with the origin in the bottom left corner
+    let to_screen =
+        |pos: Vec2| egui::pos2(rect.left() + pos.x * scale, rect.bottom() - pos.y * scale);
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("minimap"),
+    ));
+    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(140));
+
+    // The silhouette of the tiles that can be stood on
+    let tile_color = egui::Color32::from_white_alpha(160);
+    for (_, layer) in entities.iter_with(&*tile_layers) {
+        for x in 0..layer.grid_size.x {
+            for y in 0..layer.grid_size.y {
+                let is_solid = layer
+                    .get(UVec2::new(x, y))
+                    .and_then(|tile| tile_collisions.get(tile))
+                    .is_some_and(|kind| *kind != TileCollisionKind::Empty);
+                if !is_solid {
+                    continue;
+                }
+                let min = vec2(x as f32, y as f32) * layer.tile_size;
+                let max = min + layer.tile_size;
+                painter.rect_filled(
+                    egui::Rect::from_two_pos(to_screen(min), to_screen(max)),
+                    0.0,
+                    tile_color,
+                );
+            }
+        }
+    }
+
+    // Items that are lying around, but not the ones that are being held
+    let held_items = entities
+        .iter_with(&*inventories)
+        .filter_map(|(_, inventory)| inventory.0)
+        .collect::<Vec<_>>();
+    for (item_ent, (_item, transform)) in entities.iter_with((&*items, &*transforms)) {
+        if held_items.contains(&item_ent) {
+            continue;
+        }
+        let pos = to_screen(transform.translation.xy());
+        painter.rect_filled(
+            egui::Rect::from_center_size(pos, egui::vec2(3.0, 3.0)),
+            0.0,
+            egui::Color32::GOLD,
+        );
+    }
+
+    // Players, colored by their team in team modes. The markers are bigger with patterns on, so
+    // that the patterns can be made out.
+    let marker_radius = if colors.patterns { 5.0 } else { 3.0 };
+    for (player_ent, (idx, transform)) in entities.iter_with((&*player_indexes, &*transforms)) {
+        if killed_players.contains(player_ent) {
+            continue;
+        }
+        let color = match &infection {
+            Some(infection) if infection.is_infected(*idx) => meta.core.infection.infected_color,
+            _ => colors.color(*idx),
+        };
+        let [r, g, b, a] = color.as_rgba_u8();
+        paint_player_marker(
+            &painter,
+            to_screen(transform.translation.xy()),
+            marker_radius,
+            egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+            colors.pattern(*idx),
+        );
+    }
+}
+
+/// The menu to drop elements from in training matches, along with the labels of recent hits.
+fn training_hud(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    assets: Res<AssetServer>,
+    mods: Res<ModList>,
+    localization: Localization<GameMeta>,
+    sessions: Res<Sessions>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(mut state), Some(frame_step)) = (
+        world.get_resource_mut::<TrainingState>(),
+        world.get_resource::<FrameStep>(),
+    ) else {
+        return;
+    };
+
+    // Label hits with how far away they came from, drifting up as they fade
+    if let Some(view) = CameraView::new(world, ctx.screen_rect()) {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("training_hits"),
+        ));
+        for hit in &state.hits {
+            let progress = hit.age as f32 / training::HIT_LABEL_FRAMES as f32;
+            let pos =
+                view.screen_pos(view.offset(hit.pos)) - egui::vec2(0.0, 20.0 + progress * 30.0);
+            let label = match hit.distance {
+                Some(distance) => localization.get_with(
+                    "training-hit-distance",
+                    &fluent_args! { "distance" => distance.round() as i64 },
+                ),
+                None => localization.get("training-hit"),
+            };
+            let [r, g, b, _] = meta.theme.colors.negative.as_rgba_u8();
+            painter.text(
+                pos,
+                egui::Align2::CENTER_BOTTOM,
+                label,
+                meta.theme.font_styles.normal.id(),
+                egui::Color32::from_rgba_unmultiplied(r, g, b, ((1.0 - progress) * 255.0) as u8),
+            );
+        }
+    }
+
+    // Every element that can be placed on maps, grouped by category
+    let mut elements = meta.core.map_elements.iter().copied().collect::<Vec<_>>();
+    for pack in assets.packs() {
+        let pack_meta = assets.get(pack.root.typed::<PackMeta>());
+        elements.extend(pack_meta.map_elements.iter().copied());
+    }
+    let mut elements = elements
+        .into_iter()
+        .map(|handle| {
+            let element = assets.get(handle);
+            (element.category, element.name, handle)
+        })
+        .collect::<Vec<_>>();
+    elements.sort_by(|a, b| (a.0.as_str(), a.1.as_str()).cmp(&(b.0.as_str(), b.1.as_str())));
+
+    egui::Window::new(localization.get("training-menu"))
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(8.0, 8.0))
+        .default_open(false)
+        .resizable(false)
+        .show(&ctx, |ui| {
+            let text_style = &meta.theme.font_styles.smaller;
+            ui.label(text_style.rich(localization.get_with(
+                "training-frame-step",
+                &fluent_args! {
+                    "freeze" => format!("{:?}", training::FREEZE_KEY),
+                    "step" => format!("{:?}", training::STEP_KEY),
+                },
+            )));
+            if frame_step.frozen {
+                ui.label(text_style.rich(localization.get("training-frozen")));
+            }
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(ctx.screen_rect().height() / 2.0)
+                .show(ui, |ui| {
+                    let mut category = None;
+                    for (element_category, name, handle) in &elements {
+                        if category != Some(element_category) {
+                            category = Some(element_category);
+                            ui.label(text_style.rich(element_category.as_str()).strong());
+                        }
+                        if ui.button(mods.element_label(name, &localization)).clicked() {
+                            state.requested_spawns.push(*handle);
+                        }
+                    }
+                });
+        });
+}
+
+/// Show the frame data of every player while a training match is frozen: their state, how many
+/// frames they have been in it, their velocity, and their hurtboxes along with the active hitboxes.
+fn frame_data_hud(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    localization: Localization<GameMeta>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    if !world.get_resource::<FrameStep>().is_some_and(|x| x.frozen) {
+        return;
+    }
+    let (Some(entities), Some(view)) = (
+        world.resources.get::<Entities>(),
+        CameraView::new(world, ctx.screen_rect()),
+    ) else {
+        return;
+    };
+    let transforms = world.components.get::<Transform>().borrow();
+    let player_indexes = world.components.get::<PlayerIdx>().borrow();
+    let player_states = world.components.get::<PlayerState>().borrow();
+    let bodies = world.components.get::<KinematicBody>().borrow();
+    let damage_regions = world.components.get::<DamageRegion>().borrow();
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("frame_data"),
+    ));
+    let to_screen = |min: Vec2, max: Vec2| {
+        egui::Rect::from_two_pos(
+            view.screen_pos(view.offset(min)),
+            view.screen_pos(view.offset(max)),
+        )
+    };
+
+    // Hitboxes
+    let [r, g, b, _] = meta.theme.colors.negative.as_rgba_u8();
+    let hitbox_color = egui::Color32::from_rgb(r, g, b);
+    for (_, (region, transform)) in entities.iter_with((&*damage_regions, &*transforms)) {
+        let rect = region.collider_rect(transform.translation);
+        painter.rect(
+            to_screen(rect.min, rect.max),
+            0.0,
+            hitbox_color.gamma_multiply(0.25),
+            egui::Stroke::new(1.0, hitbox_color),
+        );
+    }
+
+    // Hurtboxes and frame data of the players
+    let colors = player_colors(&storage);
+    for (_, (idx, state, body, transform)) in
+        entities.iter_with((&*player_indexes, &*player_states, &*bodies, &*transforms))
+    {
+        let player_color = player_color32(&colors, *idx);
+        let rect = body.bounding_box(*transform);
+        let screen_rect = to_screen(rect.min, rect.max);
+        painter.rect_stroke(screen_rect, 0.0, egui::Stroke::new(1.0, player_color));
+
+        let label = localization.get_with(
+            "training-frame-data",
+            &fluent_args! {
+                "state" => state.current.as_str(),
+                "age" => state.age,
+                "x" => format!("{:.1}", body.velocity.x),
+                "y" => format!("{:.1}", body.velocity.y),
+            },
+        );
+        painter.text(
+            screen_rect.center_top() - egui::vec2(0.0, 4.0),
+            egui::Align2::CENTER_BOTTOM,
+            label,
+            meta.theme.font_styles.smaller.id(),
+            player_color,
+        );
+    }
+}
+
+/// Show the maps that can be voted for between rounds, with who picked which.
+fn map_vote_hud(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    assets: Res<AssetServer>,
+    localization: Localization<GameMeta>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+) {
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(vote), Some(player_inputs)) = (
+        world.resources.get::<MapVote>(),
+        world.resources.get::<MatchInputs>(),
+    ) else {
+        return;
+    };
+    if !vote.is_voting() {
+        return;
+    }
+
+    let votes = vote.votes(&player_inputs);
+    let font_color = meta.theme.panel.font_color;
+    let colors = player_colors(&storage);
+    egui::Area::new("map_vote_hud")
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .interactable(false)
+        .show(&ctx, |ui| {
+            BorderedFrame::new(&meta.theme.panel.border)
+                .padding(meta.theme.panel.padding)
+                .show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(meta.theme.font_styles.heading.with_color(font_color).rich(
+                            localization.get_with(
+                                "map-vote",
+                                &fluent_args! {
+                                    "seconds" => vote.timer.remaining_secs().ceil()
+                                },
+                            ),
+                        ));
+
+                        ui.horizontal(|ui| {
+                            for (i, handle) in vote.candidates.iter().enumerate() {
+                                BorderedFrame::new(&meta.theme.panel.border)
+                                    .padding(meta.theme.panel.padding)
+                                    .show(ui, |ui| {
+                                        ui.vertical_centered(|ui| {
+                                            ui.label(
+                                                meta.theme
+                                                    .font_styles
+                                                    .bigger
+                                                    .with_color(font_color)
+                                                    .rich(assets.get(*handle).name.to_string()),
+                                            );
+                                            ui.label(
+                                                meta.theme
+                                                    .font_styles
+                                                    .normal
+                                                    .with_color(font_color)
+                                                    .rich(localization.get_with(
+                                                        "map-vote-count",
+                                                        &fluent_args! { "votes" => votes[i] },
+                                                    )),
+                                            );
+
+                                            // The players that have this map selected
+                                            ui.horizontal(|ui| {
+                                                for (player, input) in
+                                                    player_inputs.players.iter().enumerate()
+                                                {
+                                                    if !input.active
+                                                        || input.is_ai
+                                                        || vote.selections[player] != i
+                                                    {
+                                                        continue;
+                                                    }
+                                                    let color = player_color32(
+                                                        &colors,
+                                                        PlayerIdx(player as u32),
+                                                    );
+                                                    let key = if vote.locked[player] {
+                                                        "map-vote-player-locked"
+                                                    } else {
+                                                        "map-vote-player"
+                                                    };
+                                                    ui.label(
+                                                        meta.theme
+                                                            .font_styles
+                                                            .normal
+                                                            .rich(localization.get_with(
+                                                                key,
+                                                                &fluent_args! {
+                                                                    "player" => player + 1
+                                                                },
+                                                            ))
+                                                            .color(color),
+                                                    );
+                                                }
+                                            });
+                                        });
+                                    });
+                            }
+                        });
+
+                        ui.label(
+                            meta.theme
+                                .font_styles
+                                .smaller
+                                .with_color(font_color)
+                                .rich(localization.get("map-vote-hint")),
+                        );
+                    });
+                });
+        });
+}
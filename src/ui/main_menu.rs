@@ -13,6 +13,10 @@ shadow!(build_info);
 
 #[cfg(not(target_arch = "wasm32"))]
 mod network_game;
+#[cfg(not(target_arch = "wasm32"))]
+mod pack_transfer;
+#[cfg(not(target_arch = "wasm32"))]
+mod settings_handshake;
 
 #[derive(HasSchema, Debug, Default, Clone)]
 #[repr(C)]
@@ -21,6 +25,8 @@ pub struct MainMenuMeta {
     pub subtitle_font: FontMeta,
     pub background_image: ImageMeta,
     pub menu_width: f32,
+    /// How long the player select menu counts down for once every joined player is ready.
+    pub ready_countdown: Duration,
 }
 
 pub fn session_plugin(session: &mut SessionBuilder) {
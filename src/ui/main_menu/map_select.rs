@@ -4,9 +4,11 @@ use crate::prelude::*;
 use crate::ui::map_select::{map_select_menu, MapSelectAction};
 
 #[cfg(not(target_arch = "wasm32"))]
-use crate::ui::network_game::NetworkGameState;
+use crate::ui::{chat::ChatState, network_game::NetworkGameState, ping::NetworkPing};
 
 use super::player_select::PlayerSelectState;
+#[cfg(not(target_arch = "wasm32"))]
+use super::settings_handshake::SettingsHandshakeState;
 use super::MenuPage;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -15,7 +17,8 @@ use bones_framework::networking::{GgrsSessionRunner, GgrsSessionRunnerInfo, Netw
 /// Network message that may be sent when selecting a map.
 #[derive(Serialize, Deserialize)]
 pub enum MapSelectMessage {
-    SelectMap(MapPoolNetwork),
+    /// The maps to play, and the rules picked by the player that selected them.
+    SelectMap(MapPoolNetwork, Mutators, LagCompensation),
 }
 
 pub fn widget(
@@ -26,16 +29,35 @@ pub fn widget(
     mut session_options: ResMut<SessionOptions>,
     assets: Res<AssetServer>,
 
+    #[cfg(not(target_arch = "wasm32"))] storage: Res<Storage>,
     #[cfg(not(target_arch = "wasm32"))] network_socket: Option<Res<NetworkMatchSocket>>,
+    #[cfg(not(target_arch = "wasm32"))] mut chat: ResMut<ChatState>,
+    #[cfg(not(target_arch = "wasm32"))] mut ping: ResMut<NetworkPing>,
 ) {
     let mut select_action = MapSelectAction::None;
+    let player_select_state = ui.ctx().get_state::<PlayerSelectState>();
+    #[allow(unused_mut)]
+    let mut mutators = player_select_state.mutators;
+    #[allow(unused_mut)]
+    let mut lag_compensation = LagCompensation::default();
+    #[cfg(not(target_arch = "wasm32"))]
+    if network_socket.is_some()
+        && storage
+            .get::<Settings>()
+            .is_some_and(|settings| settings.lag_compensation)
+    {
+        lag_compensation.max_rewind_frames = meta.network.max_lag_compensation_frames;
+    }
 
     // Get map select action from network
     #[cfg(not(target_arch = "wasm32"))]
-    if let Some(MapSelectAction::SelectMap(map_meta)) =
-        handle_match_setup_messages(&network_socket, &assets)
+    if let Some((MapSelectAction::SelectMap(map_meta), remote_mutators, remote_lag_compensation)) =
+        handle_match_setup_messages(&network_socket, &mut chat, &mut ping, &assets)
     {
         select_action = MapSelectAction::SelectMap(map_meta);
+        // Everybody plays with the rules of whoever picked the map
+        mutators = remote_mutators;
+        lag_compensation = remote_lag_compensation;
     }
 
     // If the `TEST_MAP` debug env var is present start the game with the map
@@ -93,7 +115,13 @@ pub fn widget(
 
         #[cfg(not(target_arch = "wasm32"))]
         // Replicate local action
-        replicate_map_select_action(&select_action, &network_socket, &assets);
+        replicate_map_select_action(
+            &select_action,
+            mutators,
+            lag_compensation,
+            &network_socket,
+            &assets,
+        );
     }
 
     match select_action {
@@ -102,6 +130,22 @@ pub fn widget(
             session_options.delete = true;
             ui.ctx().set_state(MenuPage::Home);
 
+            #[allow(unused_mut)]
+            let mut mode = player_select_state.mode;
+            #[allow(unused_mut)]
+            let mut drop_in = DropIn {
+                enabled: player_select_state.drop_in,
+                ..default()
+            };
+            #[cfg(not(target_arch = "wasm32"))]
+            if network_socket.is_some() {
+                if !mode.supports_network() {
+                    mode = GameMode::Elimination;
+                }
+                // Only local players can drop in
+                drop_in.enabled = false;
+            }
+
             #[cfg(not(target_arch = "wasm32"))]
             let session_runner: Box<dyn SessionRunner> = match network_socket {
                 Some(socket) => {
@@ -122,7 +166,6 @@ pub fn widget(
             #[cfg(target_arch = "wasm32")]
             let session_runner = Box::<JumpyDefaultMatchRunner>::default();
 
-            let player_select_state = ui.ctx().get_state::<PlayerSelectState>();
             sessions.start_game(MatchPlugin {
                 maps,
                 player_info: std::array::from_fn(|i| {
@@ -141,10 +184,17 @@ pub fn widget(
                     }
                 }),
                 plugins: meta.get_plugins(&assets),
+                mode,
+                mutators,
+                lag_compensation,
+                drop_in,
                 session_runner,
                 score: default(),
             });
             ui.ctx().set_state(PlayerSelectState::default());
+            // The next lobby has to shake hands again
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.ctx().set_state(SettingsHandshakeState::default());
         }
         MapSelectAction::GoBack => ui.ctx().set_state(MenuPage::PlayerSelect),
     }
@@ -154,6 +204,8 @@ pub fn widget(
 #[cfg(not(target_arch = "wasm32"))]
 fn replicate_map_select_action(
     action: &MapSelectAction,
+    mutators: Mutators,
+    lag_compensation: LagCompensation,
     socket: &Option<Res<NetworkMatchSocket>>,
     asset_server: &AssetServer,
 ) {
@@ -165,6 +217,8 @@ fn replicate_map_select_action(
                 SocketTarget::All,
                 &postcard::to_allocvec(&MapSelectMessage::SelectMap(
                     maps.into_network(asset_server),
+                    mutators,
+                    lag_compensation,
                 ))
                 .unwrap(),
             );
@@ -175,21 +229,25 @@ fn replicate_map_select_action(
 #[cfg(not(target_arch = "wasm32"))]
 fn handle_match_setup_messages(
     socket: &Option<Res<NetworkMatchSocket>>,
+    chat: &mut ChatState,
+    ping: &mut NetworkPing,
     asset_server: &AssetServer,
-) -> Option<MapSelectAction> {
+) -> Option<(MapSelectAction, Mutators, LagCompensation)> {
     if let Some(socket) = socket {
-        let datas: Vec<(u32, Vec<u8>)> = socket.recv_reliable();
+        let datas: Vec<(u32, Vec<u8>)> =
+            ping.filter_messages(chat.filter_messages(socket.recv_reliable()));
 
         for (_player, data) in datas {
             match postcard::from_bytes::<MapSelectMessage>(&data) {
                 Ok(message) => match message {
-                    MapSelectMessage::SelectMap(maps) => {
+                    MapSelectMessage::SelectMap(maps, mutators, lag_compensation) => {
                         info!("Map select message received, starting game");
 
-                        return Some(MapSelectAction::SelectMap(MapPool::from_network(
-                            maps,
-                            asset_server,
-                        )));
+                        return Some((
+                            MapSelectAction::SelectMap(MapPool::from_network(maps, asset_server)),
+                            mutators,
+                            lag_compensation,
+                        ));
                     }
                 },
                 Err(e) => {
@@ -0,0 +1,480 @@
+//! Asset pack transfers between the host and clients of a network lobby.
+//!
+//! When the lobby opens, the host tells everybody which asset packs it has, along with a hash of
+//! their contents. Clients that are missing a pack, or have a different version of it, ask the
+//! host for it, and the host streams it over the reliable channel in small chunks. Once a pack has
+//! been received and its hash matches the one in the manifest, it is installed to the packs
+//! directory.
+//!
+//! Asset packs are only loaded on startup, so the downloaded packs are available after the game is
+//! restarted.
+
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use bones_framework::networking::{NetworkMatchSocket, SocketTarget};
+
+use crate::{
+    mods::{packs_dir, PACK_FILE},
+    prelude::*,
+};
+
+use super::player_select::PlayerSelectMessage;
+
+/// The size of the pieces that packs are sent in, in bytes.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+/// The largest pack that clients accept from the host, in bytes.
+const MAX_PACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// The most chunks that a pack may be split into, so that the host can't make clients allocate
+/// an arbitrary amount of memory.
+const MAX_PACK_CHUNKS: usize = MAX_PACK_SIZE / CHUNK_SIZE;
+
+/// The most chunks the host sends per frame, so that the reliable channel isn't flooded.
+const CHUNKS_PER_FRAME: usize = 8;
+
+/// An asset pack that the host has.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PackInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    /// Hash of the bundled contents of the pack.
+    pub hash: u64,
+}
+
+/// A piece of an asset pack sent by the host.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PackChunk {
+    /// The id of the pack.
+    pub id: String,
+    pub index: u32,
+    /// The total number of chunks in the pack.
+    pub count: u32,
+    pub data: Vec<u8>,
+}
+
+/// The fields of the pack file that are needed to identify a pack.
+#[derive(Deserialize)]
+struct PackFile {
+    id: String,
+    name: String,
+    version: String,
+}
+
+/// A file in a bundled asset pack.
+#[derive(Serialize, Deserialize)]
+struct BundledFile {
+    /// The path of the file, relative to the pack directory, with `/` separators.
+    path: String,
+    data: Vec<u8>,
+}
+
+/// An asset pack found in the local packs directory.
+struct LocalPack {
+    info: PackInfo,
+    dir: PathBuf,
+}
+
+/// A pack that is being downloaded from the host.
+#[derive(Clone, Debug)]
+struct PackDownload {
+    info: PackInfo,
+    chunks: Vec<Option<Arc<[u8]>>>,
+}
+
+impl PackDownload {
+    fn received(&self) -> usize {
+        self.chunks.iter().filter(|x| x.is_some()).count()
+    }
+}
+
+/// A pack that the host is sending to a client.
+#[derive(Clone, Debug)]
+struct PackUpload {
+    player: u32,
+    id: String,
+    chunks: Arc<Vec<Vec<u8>>>,
+    next: usize,
+}
+
+/// State of the pack transfers in the current lobby.
+#[derive(Clone, Debug, Default)]
+pub struct PackTransferState {
+    /// Whether the host has sent the pack manifest yet.
+    manifest_sent: bool,
+    /// Whether the client has received the pack manifest from the host.
+    manifest_received: bool,
+    downloads: Vec<PackDownload>,
+    uploads: Vec<PackUpload>,
+    /// The names of the packs installed in this lobby.
+    installed: Vec<String>,
+    /// The last error that happened while downloading or installing a pack.
+    error: Option<String>,
+}
+
+impl PackTransferState {
+    /// Whether packs are still being downloaded.
+    pub fn is_downloading(&self) -> bool {
+        !self.downloads.is_empty()
+    }
+
+    /// Whether packs are still being sent or received, in which case the lobby should wait.
+    pub fn is_busy(&self) -> bool {
+        self.is_downloading() || !self.uploads.is_empty()
+    }
+
+    /// Whether packs were downloaded that will only be loaded once the game is restarted.
+    pub fn needs_restart(&self) -> bool {
+        !self.installed.is_empty()
+    }
+
+    /// Send the pack manifest if this is the host, and send the next chunks of any packs that
+    /// clients asked for.
+    pub fn update(&mut self, socket: &NetworkMatchSocket) {
+        if socket.player_idx() == 0 && !self.manifest_sent {
+            self.manifest_sent = true;
+            let manifest = local_packs().into_iter().map(|x| x.info).collect();
+            send(
+                socket,
+                SocketTarget::All,
+                &PlayerSelectMessage::PackManifest(manifest),
+            );
+        }
+
+        let mut budget = CHUNKS_PER_FRAME;
+        for upload in &mut self.uploads {
+            while budget > 0 && upload.next < upload.chunks.len() {
+                let chunk = PackChunk {
+                    id: upload.id.clone(),
+                    index: upload.next as u32,
+                    count: upload.chunks.len() as u32,
+                    data: upload.chunks[upload.next].clone(),
+                };
+                send(
+                    socket,
+                    SocketTarget::Player(upload.player),
+                    &PlayerSelectMessage::PackChunk(chunk),
+                );
+                upload.next += 1;
+                budget -= 1;
+            }
+        }
+        self.uploads.retain(|x| x.next < x.chunks.len());
+    }
+
+    /// Compare the host's packs to the local ones, and ask for the ones that differ.
+    pub fn handle_manifest(&mut self, socket: &NetworkMatchSocket, manifest: Vec<PackInfo>) {
+        if self.manifest_received {
+            return;
+        }
+        self.manifest_received = true;
+
+        let local = local_packs();
+        let missing = manifest
+            .into_iter()
+            .filter(|pack| !local.iter().any(|x| x.info == *pack))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            return;
+        }
+
+        info!(
+            "Downloading {} asset pack(s) missing from this client",
+            missing.len()
+        );
+        let ids = missing.iter().map(|x| x.id.clone()).collect();
+        send(
+            socket,
+            SocketTarget::Player(0),
+            &PlayerSelectMessage::RequestPacks(ids),
+        );
+        self.downloads = missing
+            .into_iter()
+            .map(|info| PackDownload {
+                info,
+                chunks: Vec::new(),
+            })
+            .collect();
+    }
+
+    /// Queue up the requested packs to be sent to a client.
+    pub fn handle_request(&mut self, player: u32, ids: Vec<String>) {
+        let local = local_packs();
+        for id in ids {
+            let Some(pack) = local.iter().find(|x| x.info.id == id) else {
+                warn!("Player {player} requested unknown asset pack: {id}");
+                continue;
+            };
+            match bundle_pack(&pack.dir) {
+                Ok(bundle) => self.uploads.push(PackUpload {
+                    player,
+                    id,
+                    chunks: Arc::new(bundle.chunks(CHUNK_SIZE).map(|x| x.to_vec()).collect()),
+                    next: 0,
+                }),
+                Err(e) => error!("Could not bundle asset pack {id}: {e}"),
+            }
+        }
+    }
+
+    /// Store a received chunk, and install its pack once it is complete.
+    pub fn handle_chunk(&mut self, chunk: PackChunk) {
+        let Some(download_idx) = self.downloads.iter().position(|x| x.info.id == chunk.id) else {
+            warn!(
+                "Received chunk of asset pack that was not requested: {}",
+                chunk.id
+            );
+            return;
+        };
+        if chunk.count == 0 || chunk.count as usize > MAX_PACK_CHUNKS {
+            let download = self.downloads.remove(download_idx);
+            error!(
+                "Asset pack {} is split into too many chunks: {}",
+                chunk.id, chunk.count
+            );
+            self.error = Some(format!("{}: pack is too large", download.info.name));
+            return;
+        }
+        if chunk.data.len() > CHUNK_SIZE {
+            warn!("Received oversized chunk of asset pack: {}", chunk.id);
+            return;
+        }
+        let download = &mut self.downloads[download_idx];
+        if download.chunks.is_empty() {
+            download.chunks = vec![None; chunk.count as usize];
+        } else if download.chunks.len() != chunk.count as usize {
+            warn!(
+                "Received chunk of asset pack with a different chunk count: {}",
+                chunk.id
+            );
+            return;
+        }
+        let Some(slot) = download.chunks.get_mut(chunk.index as usize) else {
+            warn!("Received out of range chunk of asset pack: {}", chunk.id);
+            return;
+        };
+        *slot = Some(chunk.data.into());
+
+        if download.received() < download.chunks.len() {
+            return;
+        }
+
+        let download = self.downloads.remove(download_idx);
+        let bundle = download
+            .chunks
+            .iter()
+            .flatten()
+            .flat_map(|x| x.iter().copied())
+            .collect::<Vec<_>>();
+        match install_pack(&download.info, &bundle) {
+            Ok(()) => {
+                info!("Installed asset pack {}", download.info.id);
+                self.installed.push(download.info.name);
+            }
+            Err(e) => {
+                error!("Could not install asset pack {}: {e}", download.info.id);
+                self.error = Some(format!("{}: {e}", download.info.name));
+            }
+        }
+    }
+
+    /// Show the progress of the downloads, and what to do once they are done.
+    pub fn show(&self, ui: &mut egui::Ui, meta: &GameMeta, localization: &Localization<GameMeta>) {
+        let text_style = meta
+            .theme
+            .font_styles
+            .normal
+            .with_color(meta.theme.panel.font_color);
+
+        for download in &self.downloads {
+            ui.label(text_style.rich(localization.get_with(
+                "downloading-pack",
+                &fluent_args! { "pack" => download.info.name.as_str() },
+            )));
+            let progress = if download.chunks.is_empty() {
+                0.0
+            } else {
+                download.received() as f32 / download.chunks.len() as f32
+            };
+            ui.add(egui::ProgressBar::new(progress).show_percentage());
+        }
+
+        if let Some(error) = &self.error {
+            let message = localization.get_with(
+                "pack-download-failed",
+                &fluent_args! { "error" => error.as_str() },
+            );
+            ui.label(text_style.rich(message).color(egui::Color32::RED));
+        } else if self.needs_restart() && !self.is_downloading() {
+            ui.label(text_style.rich(localization.get_with(
+                "packs-downloaded",
+                &fluent_args! { "packs" => self.installed.join(", ") },
+            )));
+        }
+    }
+}
+
+fn send(socket: &NetworkMatchSocket, target: SocketTarget, message: &PlayerSelectMessage) {
+    socket.send_reliable(target, &postcard::to_allocvec(message).unwrap());
+}
+
+/// Find the asset packs in the packs directory.
+fn local_packs() -> Vec<LocalPack> {
+    let Ok(dirs) = std::fs::read_dir(packs_dir()) else {
+        return Vec::new();
+    };
+
+    dirs.flatten()
+        .map(|entry| entry.path())
+        .filter_map(|dir| {
+            let pack_file = std::fs::read(dir.join(PACK_FILE)).ok()?;
+            let pack_file = serde_yaml::from_slice::<PackFile>(&pack_file).ok()?;
+            let bundle = bundle_pack(&dir).ok()?;
+            Some(LocalPack {
+                info: PackInfo {
+                    id: pack_file.id,
+                    name: pack_file.name,
+                    version: pack_file.version,
+                    hash: hash_bundle(&bundle),
+                },
+                dir,
+            })
+        })
+        .collect()
+}
+
+/// Serialize all of the files in a pack directory into a single buffer.
+///
+/// Files are sorted by path, so the same pack always produces the same bundle.
+fn bundle_pack(dir: &Path) -> io::Result<Vec<u8>> {
+    fn collect_files(root: &Path, dir: &Path, files: &mut Vec<BundledFile>) -> io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                collect_files(root, &path, files)?;
+            } else {
+                let relative = path.strip_prefix(root).unwrap();
+                files.push(BundledFile {
+                    path: relative
+                        .components()
+                        .map(|x| x.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                    data: std::fs::read(&path)?,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    postcard::to_allocvec(&files).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Hash a pack bundle with 64-bit FNV-1a, which gives the same result on every platform.
+pub(super) fn hash_bundle(bundle: &[u8]) -> u64 {
+    bundle.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Verify a downloaded pack against its manifest entry and write it to the packs directory,
+/// replacing any other version of the pack.
+fn install_pack(info: &PackInfo, bundle: &[u8]) -> io::Result<()> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    if hash_bundle(bundle) != info.hash {
+        return Err(invalid("hash mismatch"));
+    }
+    let files = postcard::from_bytes::<Vec<BundledFile>>(bundle)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    // Don't let the host write anywhere outside of the pack directory
+    let is_safe_path = |path: &Path| path.components().all(|x| matches!(x, Component::Normal(_)));
+    if !is_safe_path(Path::new(&info.id)) || !files.iter().all(|x| is_safe_path(Path::new(&x.path)))
+    {
+        return Err(invalid("unsafe path in pack"));
+    }
+
+    let dir = local_packs()
+        .into_iter()
+        .find(|x| x.info.id == info.id)
+        .map(|x| x.dir)
+        .unwrap_or_else(|| packs_dir().join(&info.id));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    for file in files {
+        let path = dir.join(&file.path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, file.data)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn downloading(id: &str) -> PackTransferState {
+        PackTransferState {
+            downloads: vec![PackDownload {
+                info: PackInfo {
+                    id: id.into(),
+                    name: id.into(),
+                    version: "1.0.0".into(),
+                    hash: 0,
+                },
+                chunks: Vec::new(),
+            }],
+            ..default()
+        }
+    }
+
+    fn chunk(id: &str, index: u32, count: u32) -> PackChunk {
+        PackChunk {
+            id: id.into(),
+            index,
+            count,
+            data: vec![0; 4],
+        }
+    }
+
+    #[test]
+    fn hash_bundle_is_fnv1a() {
+        assert_eq!(hash_bundle(b""), 0xcbf29ce484222325);
+        assert_eq!(hash_bundle(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash_bundle(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn hash_bundle_depends_on_order() {
+        assert_ne!(hash_bundle(b"ab"), hash_bundle(b"ba"));
+    }
+
+    #[test]
+    fn oversized_pack_is_rejected() {
+        let mut state = downloading("pack");
+        state.handle_chunk(chunk("pack", 0, MAX_PACK_CHUNKS as u32 + 1));
+        assert!(!state.is_downloading());
+        assert!(state.error.is_some());
+    }
+
+    #[test]
+    fn chunk_with_different_count_is_ignored() {
+        let mut state = downloading("pack");
+        state.handle_chunk(chunk("pack", 0, 3));
+        state.handle_chunk(chunk("pack", 1, 2));
+        assert_eq!(state.downloads[0].chunks.len(), 3);
+        assert_eq!(state.downloads[0].received(), 1);
+    }
+}
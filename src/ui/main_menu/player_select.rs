@@ -2,10 +2,22 @@
 use bones_framework::networking::{NetworkMatchSocket, SocketTarget};
 use smallvec::SmallVec;
 
-use crate::{ui::player_image::player_image, PackMeta};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::mods::AssetHashes;
+use crate::{
+    ui::{focus::FocusExt, player_image::player_image},
+    PackMeta,
+};
 
 use super::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+use super::pack_transfer::{PackChunk, PackInfo, PackTransferState};
+#[cfg(not(target_arch = "wasm32"))]
+use super::settings_handshake::{MatchSettings, SettingsHandshakeState};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ui::{chat::ChatState, ping::NetworkPing};
+
 #[derive(Default, Clone, Debug, HasSchema)]
 pub struct PlayerSelectState {
     pub slots: [PlayerSlot; MAX_PLAYERS as usize],
@@ -13,6 +25,14 @@ pub struct PlayerSelectState {
     pub players: Vec<Handle<PlayerMeta>>,
     /// Cache of available hats from the game and packs.
     pub hats: Vec<Option<Handle<HatMeta>>>,
+    /// The game mode to play.
+    pub mode: GameMode,
+    /// The mutators to enable on top of the game mode.
+    pub mutators: Mutators,
+    /// Whether new local players may join during the match.
+    pub drop_in: bool,
+    /// How long until the match starts, once all of the joined players are ready.
+    pub countdown: Option<Duration>,
 }
 
 impl PlayerSelectState {
@@ -265,6 +285,18 @@ pub enum PlayerSelectMessage {
     SelectPlayer(NetworkHandle<PlayerMeta>),
     SelectHat(Option<NetworkHandle<HatMeta>>),
     ConfirmSelection(bool),
+    /// The asset packs that the host has.
+    #[cfg(not(target_arch = "wasm32"))]
+    PackManifest(Vec<PackInfo>),
+    /// Ask the host for the asset packs with the given ids.
+    #[cfg(not(target_arch = "wasm32"))]
+    RequestPacks(Vec<String>),
+    /// A piece of an asset pack requested from the host.
+    #[cfg(not(target_arch = "wasm32"))]
+    PackChunk(PackChunk),
+    /// The settings of the sender that have to match everybody else's.
+    #[cfg(not(target_arch = "wasm32"))]
+    MatchSettings(MatchSettings),
 }
 
 pub fn widget(
@@ -272,9 +304,13 @@ pub fn widget(
     meta: Root<GameMeta>,
     localization: Localization<GameMeta>,
     controls: Res<GlobalPlayerControls>,
+    time: Res<Time>,
     world: &World,
     asset_server: Res<AssetServer>,
     #[cfg(not(target_arch = "wasm32"))] network_socket: Option<Res<NetworkMatchSocket>>,
+    #[cfg(not(target_arch = "wasm32"))] asset_hashes: Res<AssetHashes>,
+    #[cfg(not(target_arch = "wasm32"))] mut chat: ResMut<ChatState>,
+    #[cfg(not(target_arch = "wasm32"))] mut ping: ResMut<NetworkPing>,
 ) {
     let mut state = ui.ctx().get_state::<PlayerSelectState>();
     ui.ctx().set_state(EguiInputSettings {
@@ -282,9 +318,26 @@ pub fn widget(
         disable_gamepad_input: true,
     });
 
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut pack_transfer = ui.ctx().get_state::<PackTransferState>();
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut handshake = ui.ctx().get_state::<SettingsHandshakeState>();
     #[cfg(not(target_arch = "wasm32"))]
     if let Some(socket) = network_socket.as_ref() {
-        handle_match_setup_messages(socket, &mut state, &asset_server);
+        handle_match_setup_messages(
+            socket,
+            &mut state,
+            &mut pack_transfer,
+            &mut handshake,
+            &mut chat,
+            &mut ping,
+            &asset_server,
+        );
+        pack_transfer.update(socket);
+        handshake.update(
+            socket,
+            MatchSettings::local(&meta, &asset_hashes, state.mutators),
+        );
     }
 
     // Set player slot 0 using the debug env vars and go to the map select menu.
@@ -372,7 +425,7 @@ pub fn widget(
         }
     }
 
-    // Whether or not the continue button should be enabled
+    // Whether or not the match may start
     let mut ready_players = 0;
     let mut unconfirmed_players = 0;
 
@@ -387,16 +440,49 @@ pub fn widget(
             unconfirmed_players += 1;
         }
     }
-    let may_continue = ready_players >= 1 && unconfirmed_players == 0 && at_least_one_non_ai_ready;
+    #[allow(unused_mut)]
+    let mut may_continue =
+        ready_players >= 1 && unconfirmed_players == 0 && at_least_one_non_ai_ready;
 
+    // Wait for missing asset packs to finish transferring
     #[cfg(not(target_arch = "wasm32"))]
-    if let Some(socket) = network_socket.as_ref() {
-        if may_continue {
-            // The first player picks the map
-            let is_waiting = socket.player_idx() != 0;
+    if pack_transfer.is_busy() {
+        may_continue = false;
+    }
+
+    // Refuse to start until everybody agrees on the settings that would desync the match
+    #[cfg(not(target_arch = "wasm32"))]
+    if network_socket
+        .as_ref()
+        .is_some_and(|socket| !handshake.is_agreed(socket))
+    {
+        may_continue = false;
+    }
+
+    // Count down to the match once everybody is ready, starting over whenever somebody unreadies
+    // or joins.
+    if may_continue {
+        let countdown = state
+            .countdown
+            .get_or_insert(meta.main_menu.ready_countdown);
+        *countdown = countdown.saturating_sub(time.delta());
+    } else {
+        state.countdown = None;
+    }
 
-            ui.ctx().set_state(MenuPage::MapSelect { is_waiting });
+    if state.countdown.is_some_and(|x| x.is_zero()) {
+        state.countdown = None;
+
+        #[allow(unused_mut)]
+        let mut is_waiting = false;
+        // The first player picks the map
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(socket) = network_socket.as_ref() {
+            is_waiting = socket.player_idx() != 0;
         }
+
+        ui.ctx().set_state(MenuPage::MapSelect { is_waiting });
+        ui.ctx().set_state(EguiInputSettings::default());
     }
 
     let bigger_text_style = &meta
@@ -427,6 +513,68 @@ pub fn widget(
         }
 
         ui.label(bigger_text_style.rich(localization.get("player-select-title")));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        pack_transfer.show(ui, &meta, &localization);
+        #[cfg(not(target_arch = "wasm32"))]
+        handshake.show(ui, &meta, &localization);
+
+        // Online matches are always elimination, and only local players can drop in
+        if !is_network {
+            ui.horizontal(|ui| {
+                let mode_name = localization.get(state.mode.localization_key());
+                let mode_button =
+                    BorderedButton::themed(&meta.theme.buttons.small, format!("< {} >", mode_name))
+                        .show(ui)
+                        .speak_on_focus(ui, &mode_name);
+
+                if mode_button.clicked() {
+                    let current = GameMode::ALL
+                        .iter()
+                        .position(|mode| *mode == state.mode)
+                        .unwrap_or_default();
+                    state.mode = GameMode::ALL[(current + 1) % GameMode::ALL.len()];
+                }
+
+                let check = if state.drop_in { "x" } else { " " };
+                let label = format!("[{check}] {}", localization.get("drop-in"));
+                let drop_in_button =
+                    BorderedButton::themed(&meta.theme.buttons.small, label.clone())
+                        .show(ui)
+                        .speak_on_focus(ui, &label);
+
+                if drop_in_button.clicked() {
+                    state.drop_in = !state.drop_in;
+                }
+            });
+        }
+
+        // Mutators can be combined with any mode, online or not, but only the host picks them
+        #[allow(unused_mut)]
+        let mut is_host = true;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(socket) = network_socket.as_ref() {
+            is_host = socket.player_idx() == 0;
+        }
+        ui.horizontal_wrapped(|ui| {
+            ui.set_enabled(is_host);
+            for mutator in Mutator::ALL {
+                let check = if state.mutators.is_enabled(mutator) {
+                    "x"
+                } else {
+                    " "
+                };
+                let label = format!("[{check}] {}", localization.get(mutator.localization_key()));
+                let mutator_button =
+                    BorderedButton::themed(&meta.theme.buttons.small, label.clone())
+                        .show(ui)
+                        .speak_on_focus(ui, &label);
+
+                if mutator_button.clicked() {
+                    state.mutators.toggle(mutator);
+                }
+            }
+        });
         ui.add_space(normal_button_style.font.size);
 
         ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
@@ -445,7 +593,8 @@ pub fn widget(
                     BorderedButton::themed(normal_button_style, localization.get("back"))
                         .min_size(button_min_size)
                         .show(ui)
-                        .focus_by_default(ui);
+                        .focus_by_default(ui)
+                        .speak_on_focus(ui, &localization.get("back"));
 
                 if back_button.clicked()
                     || (ready_players == 0
@@ -455,6 +604,11 @@ pub fn widget(
                     ui.ctx().set_state(MenuPage::Home);
                     ui.ctx().set_state(EguiInputSettings::default());
                     ui.ctx().set_state(PlayerSelectState::default());
+                    #[cfg(not(target_arch = "wasm32"))]
+                    {
+                        pack_transfer = default();
+                        handshake = default();
+                    }
 
                     #[cfg(not(target_arch = "wasm32"))]
                     if let Some(socket) = network_socket {
@@ -464,26 +618,20 @@ pub fn widget(
 
                 ui.add_space(button_spacing);
 
-                // Continue button
-                let continue_button = ui
-                    .scope(|ui| {
-                        ui.set_enabled(may_continue);
-
-                        BorderedButton::themed(normal_button_style, localization.get("continue"))
-                            .min_size(button_min_size)
-                            .show(ui)
-                    })
-                    .inner;
-
-                if !controls.values().any(|x| x.menu_back_just_pressed)
-                    && (continue_button.clicked()
-                        || (controls.values().any(|x| x.menu_start_just_pressed) && may_continue))
-                {
-                    ui.ctx()
-                        .set_state(MenuPage::MapSelect { is_waiting: false });
-                    ui.ctx().set_state(EguiInputSettings::default());
-                    ui.ctx().set_state(PlayerSelectState::default());
-                }
+                // Match countdown
+                let countdown_text = match state.countdown {
+                    Some(remaining) => localization.get_with(
+                        "player-select-starting-in",
+                        &fluent_args! {
+                            "seconds" => remaining.as_secs_f32().ceil() as u32
+                        },
+                    ),
+                    None => localization.get("player-select-waiting-for-ready"),
+                };
+                ui.add_sized(
+                    button_min_size,
+                    egui::Label::new(bigger_text_style.rich(countdown_text)),
+                );
             });
 
             ui.add_space(normal_button_style.font.size);
@@ -504,15 +652,24 @@ pub fn widget(
     });
 
     ui.ctx().set_state(state);
+    #[cfg(not(target_arch = "wasm32"))]
+    ui.ctx().set_state(pack_transfer);
+    #[cfg(not(target_arch = "wasm32"))]
+    ui.ctx().set_state(handshake);
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 fn handle_match_setup_messages(
     network_socket: &NetworkMatchSocket,
     player_select_state: &mut PlayerSelectState,
+    pack_transfer: &mut PackTransferState,
+    handshake: &mut SettingsHandshakeState,
+    chat: &mut ChatState,
+    ping: &mut NetworkPing,
     asset_server: &AssetServer,
 ) {
-    let datas: Vec<(u32, Vec<u8>)> = network_socket.recv_reliable();
+    let datas: Vec<(u32, Vec<u8>)> =
+        ping.filter_messages(chat.filter_messages(network_socket.recv_reliable()));
 
     for (player, data) in datas {
         match postcard::from_bytes::<PlayerSelectMessage>(&data) {
@@ -570,6 +727,27 @@ fn handle_match_setup_messages(
                         current_hat,
                     };
                 }
+                // Only the host sends packs
+                PlayerSelectMessage::PackManifest(manifest) => {
+                    if player == 0 {
+                        pack_transfer.handle_manifest(network_socket, manifest);
+                    }
+                }
+                PlayerSelectMessage::RequestPacks(ids) => {
+                    pack_transfer.handle_request(player, ids);
+                }
+                PlayerSelectMessage::PackChunk(chunk) => {
+                    if player == 0 {
+                        pack_transfer.handle_chunk(chunk);
+                    }
+                }
+                PlayerSelectMessage::MatchSettings(settings) => {
+                    // Everybody plays with the host's mutators
+                    if player == 0 {
+                        player_select_state.mutators = settings.mutators;
+                    }
+                    handshake.handle_settings(player, settings);
+                }
             },
             Err(e) => warn!("Ignoring network message that was not understood: {e}"),
         }
@@ -585,6 +763,7 @@ fn player_select_panel(
     mapping: Res<PlayerControlMapping>,
     world: &World,
     #[cfg(not(target_arch = "wasm32"))] network_socket: Option<Res<NetworkMatchSocket>>,
+    #[cfg(not(target_arch = "wasm32"))] ping: Res<NetworkPing>,
 ) {
     let (ui, slot_id, state) = &mut *params;
     let slot_id = *slot_id;
@@ -829,11 +1008,40 @@ fn player_select_panel(
 
             let slot = state.slots[slot_id as usize];
 
-            // Marker for current player in online matches
+            #[cfg(target_arch = "wasm32")]
+            let round_trip_time = None::<Duration>;
+            #[cfg(not(target_arch = "wasm32"))]
+            let round_trip_time = ping.round_trip_time(slot_id);
+
+            // Marker for current player in online matches, and the ping of the others
             if is_network && slot.is_local_player() {
                 ui.vertical_centered(|ui| {
                     ui.label(normal_font.rich(localization.get("you-marker")));
                 });
+            } else if let Some(round_trip_time) = round_trip_time.filter(|_| is_network) {
+                #[cfg(not(target_arch = "wasm32"))]
+                let [r, g, b, a] = crate::ui::ping::ping_color(&meta, round_trip_time).as_rgba_u8();
+                #[cfg(target_arch = "wasm32")]
+                let [r, g, b, a] = panel.font_color.as_rgba_u8();
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        normal_font
+                            .rich(localization.get_with(
+                                "ping-ms",
+                                &fluent_args! { "ping" => round_trip_time.as_millis() as u64 },
+                            ))
+                            .color(egui::Color32::from_rgba_unmultiplied(r, g, b, a)),
+                    );
+                });
+            } else if let Some(source) = slot.user_control_source().filter(|_| !is_network) {
+                // The device of local players, to tell the keyboard players apart
+                ui.vertical_centered(|ui| {
+                    ui.label(smaller_font.rich(control_source_name(
+                        source,
+                        &mapping,
+                        &localization,
+                    )));
+                });
             } else {
                 ui.add_space(normal_font.size);
             }
@@ -974,20 +1182,18 @@ fn player_select_panel(
 
             // If this slot has not selected player
             } else {
-                let bindings = available_input_sources
-                    .iter()
-                    .map(|s| mapping.map_control_source(*s).menu_confirm.to_string())
-                    .collect::<SmallVec<[_; 3]>>()
-                    .join("/");
-
                 ui.vertical_centered(|ui| {
                     if !is_network || slot.is_local_player() {
-                        ui.label(normal_font.rich(localization.get_with(
-                            "press-button-to-join",
-                            &fluent_args! {
-                                "button" => bindings
-                            },
-                        )));
+                        // Each keyboard binding set is its own device, joined with its own button
+                        for source in &available_input_sources {
+                            let button =
+                                mapping.map_control_source(*source).menu_confirm.to_string();
+                            let device = control_source_name(*source, &mapping, &localization);
+                            ui.label(normal_font.rich(localization.get_with(
+                                "press-button-to-join-with",
+                                &fluent_args! { "button" => button, "device" => device },
+                            )));
+                        }
                     }
 
                     if !is_network {
@@ -1021,3 +1227,29 @@ fn player_select_panel(
         state.slots[slot_id as usize] = slot;
     }
 }
+
+/// Get the name of a control source to show players, which names keyboards after their movement
+/// keys since they share the same physical keyboard.
+fn control_source_name(
+    source: ControlSource,
+    mapping: &PlayerControlMapping,
+    localization: &Localization<GameMeta>,
+) -> String {
+    match source {
+        ControlSource::Keyboard1 | ControlSource::Keyboard2 => {
+            let movement = &mapping.map_control_source(source).movement;
+            let keys = [
+                &movement.up,
+                &movement.left,
+                &movement.down,
+                &movement.right,
+            ]
+            .map(|x| x.to_string())
+            .join(" ");
+            localization
+                .get_with("keyboard-with-keys", &fluent_args! { "keys" => keys })
+                .to_string()
+        }
+        ControlSource::Gamepad(_) => localization.get("gamepad").to_string(),
+    }
+}
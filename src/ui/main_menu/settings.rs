@@ -1,8 +1,10 @@
 use crate::{
     prelude::*,
     settings::{PlayerControlMapping, Settings},
+    ui::focus::FocusExt,
 };
 
+mod accessibility;
 mod audio;
 mod controls;
 mod graphics;
@@ -14,6 +16,9 @@ struct SettingsState {
     modified_settings: Settings,
     settings_loaded: bool,
     currently_binding_input_idx: Option<usize>,
+    /// The gamepad whose controls are being edited, or `None` for the controls shared by all of
+    /// them.
+    selected_gamepad: Option<u32>,
 }
 
 /// Which settings tab we are on
@@ -24,6 +29,7 @@ enum SettingsTab {
     Networking,
     Audio,
     Graphics,
+    Accessibility,
 }
 
 impl SettingsTab {
@@ -32,6 +38,7 @@ impl SettingsTab {
         (Self::Networking, "networking"),
         (Self::Audio, "audio"),
         (Self::Graphics, "graphics"),
+        (Self::Accessibility, "accessibility"),
     ];
 }
 
@@ -85,16 +92,17 @@ pub fn widget(
                 // Add tab list at the top of the panel
                 ui.horizontal(|ui| {
                     for (i, (tab, name)) in SettingsTab::TABS.iter().enumerate() {
-                        let name = localization.get(name);
-                        let mut name = egui::RichText::new(name);
+                        let label = localization.get(name);
+                        let mut name = egui::RichText::new(label.clone());
 
                         // Underline the current tab
                         if *tab == state.tab {
                             name = name.underline();
                         }
 
-                        let mut button =
-                            BorderedButton::themed(&meta.theme.buttons.normal, name).show(ui);
+                        let mut button = BorderedButton::themed(&meta.theme.buttons.normal, name)
+                            .show(ui)
+                            .speak_on_focus(ui, &label);
 
                         // Focus the first tab by default
                         if i == 0 {
@@ -126,6 +134,7 @@ pub fn widget(
                         )
                         .min_size(button_min_size)
                         .show(ui)
+                        .speak_on_focus(ui, &localization.get("cancel"))
                         .clicked()
                             || input.values().any(|x| x.menu_back_just_pressed))
                             && state.currently_binding_input_idx.is_none()
@@ -148,6 +157,7 @@ pub fn widget(
                         )
                         .min_size(button_min_size)
                         .show(ui)
+                        .speak_on_focus(ui, &localization.get("reset"))
                         .clicked()
                         {
                             should_reset = true;
@@ -162,6 +172,7 @@ pub fn widget(
                         )
                         .min_size(button_min_size)
                         .show(ui)
+                        .speak_on_focus(ui, &localization.get("save"))
                         .clicked()
                         {
                             // Save the settings to disk.
@@ -188,6 +199,9 @@ pub fn widget(
                         SettingsTab::Graphics => {
                             world.run_system(graphics::widget, (ui, &mut state, should_reset))
                         }
+                        SettingsTab::Accessibility => {
+                            world.run_system(accessibility::widget, (ui, &mut state, should_reset))
+                        }
                     });
                 });
             });
@@ -196,3 +210,13 @@ pub fn widget(
     // Update the state with our modified one
     ui.ctx().set_state(state);
 }
+
+/// Add a checkbox that has a ring around it while it's focused.
+fn focusable_checkbox(
+    ui: &mut egui::Ui,
+    checked: &mut bool,
+    label: egui::RichText,
+) -> egui::Response {
+    let text = label.text().to_string();
+    ui.checkbox(checked, label).focus_ring(ui, &text)
+}
@@ -0,0 +1,129 @@
+use super::*;
+
+pub(super) fn widget(
+    mut args: In<(&mut egui::Ui, &mut SettingsState, bool)>,
+    meta: Root<GameMeta>,
+    localization: Localization<GameMeta>,
+) {
+    let (ui, state, should_reset) = &mut *args;
+
+    let normal_font = meta
+        .theme
+        .font_styles
+        .normal
+        .with_color(meta.theme.panel.font_color);
+
+    if *should_reset {
+        state.modified_settings.player_outlines = meta.default_settings.player_outlines.clone();
+        state.modified_settings.player_colors = meta.default_settings.player_colors.clone();
+        state.modified_settings.subtitles = meta.default_settings.subtitles;
+        state.modified_settings.language = meta.default_settings.language.clone();
+        state.modified_settings.speak_focus = meta.default_settings.speak_focus;
+        state.modified_settings.reduced_motion = meta.default_settings.reduced_motion;
+    }
+
+    ui.add_space(normal_font.size / 2.0);
+
+    // Language, cycling through the system language and then every available language
+    let languages = localization.available_languages();
+    let language = &mut state.modified_settings.language;
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        ui.label(normal_font.rich(localization.get("language")));
+        let current = languages.iter().position(|x| x.id == *language);
+        let name = match current {
+            Some(i) => languages[i].name.clone(),
+            None => localization.get("language-system").into_owned(),
+        };
+        let button = BorderedButton::themed(&meta.theme.buttons.small, format!("< {name} >"))
+            .show(ui)
+            .speak_on_focus(ui, &format!("{} {name}", localization.get("language")));
+        if button.clicked() {
+            *language = match current {
+                None => languages.first(),
+                Some(i) => languages.get(i + 1),
+            }
+            .map(|x| x.id.clone())
+            .unwrap_or_default();
+        }
+    });
+
+    // Announcer subtitles
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        focusable_checkbox(
+            ui,
+            &mut state.modified_settings.subtitles,
+            normal_font.rich(localization.get("subtitles")),
+        );
+    });
+
+    // Reduced motion
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        focusable_checkbox(
+            ui,
+            &mut state.modified_settings.reduced_motion,
+            normal_font.rich(localization.get("reduced-motion")),
+        );
+    });
+
+    // Speaking focused widgets
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        focusable_checkbox(
+            ui,
+            &mut state.modified_settings.speak_focus,
+            normal_font.rich(localization.get("speak-focus")),
+        );
+    });
+
+    // Player outlines
+    let player_outlines = &mut state.modified_settings.player_outlines;
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        focusable_checkbox(
+            ui,
+            &mut player_outlines.enabled,
+            normal_font.rich(localization.get("player-outlines")),
+        );
+    });
+    if player_outlines.enabled {
+        ui.horizontal(|ui| {
+            ui.add_space(normal_font.size * 3.0);
+            let label = localization.get("player-outline-thickness");
+            ui.label(normal_font.rich(label.clone()));
+            ui.add(egui::Slider::new(&mut player_outlines.thickness, 0.5..=4.0).step_by(0.5))
+                .focus_ring(ui, &label);
+        });
+    }
+
+    // Player colors, cycling through the palettes
+    let player_colors = &mut state.modified_settings.player_colors;
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        ui.label(normal_font.rich(localization.get("player-palette")));
+        let name = localization.get(player_colors.palette.localization_key());
+        let button = BorderedButton::themed(&meta.theme.buttons.small, format!("< {name} >"))
+            .show(ui)
+            .speak_on_focus(
+                ui,
+                &format!("{} {name}", localization.get("player-palette")),
+            );
+        if button.clicked() {
+            let current = PlayerPalette::ALL
+                .iter()
+                .position(|x| *x == player_colors.palette)
+                .unwrap_or_default();
+            player_colors.palette = PlayerPalette::ALL[(current + 1) % PlayerPalette::ALL.len()];
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        focusable_checkbox(
+            ui,
+            &mut player_colors.patterns,
+            normal_font.rich(localization.get("player-patterns")),
+        );
+    });
+}
@@ -39,6 +39,7 @@ pub(super) fn widget(
 
     if should_reset {
         state.modified_settings.main_volume = meta.default_settings.main_volume;
+        state.modified_settings.announcer_volume = meta.default_settings.announcer_volume;
         audio_center.push_event(state.modified_settings.volume_change_event());
     }
 
@@ -84,6 +85,14 @@ pub(super) fn widget(
                 .changed();
                 ui.end_row();
 
+                // Announcer
+                volume_control_widget(
+                    ui,
+                    normal_font.rich(localization.get("volume-announcer")),
+                    &mut state.modified_settings.announcer_volume,
+                );
+                ui.end_row();
+
                 if main_changed || music_changed || effects_changed {
                     audio_center.push_event(state.modified_settings.volume_change_event());
                 }
@@ -97,6 +106,8 @@ fn volume_control_widget(
     label: impl Into<egui::WidgetText>,
     value: &mut f32,
 ) -> egui::Response {
+    let label = label.into();
+    let text = label.text().to_string();
     ui.label(label);
     let slider = egui::Slider::new(value, 0.0..=1.0)
         .smallest_positive(0.0)
@@ -104,5 +115,5 @@ fn volume_control_widget(
         .fixed_decimals(3)
         .custom_formatter(|x, _| format!("{:5.1}", x * 100.0))
         .custom_parser(|s| s.parse::<f64>().ok().map(|f| f / 100.0));
-    ui.add(slider)
+    ui.add(slider).focus_ring(ui, &text)
 }
@@ -1,6 +1,6 @@
 use egui_extras::{Column, TableBuilder};
 
-use crate::settings::InputKind;
+use crate::settings::{GamepadControlSetting, InputKind, PlayerControlMapping};
 
 use super::*;
 
@@ -36,147 +36,266 @@ pub(super) fn widget(
         + small_button_style.padding.top
         + small_button_style.padding.bottom;
 
-    let mapping = &mut state.modified_settings.player_controls;
+    // Pick the gamepad to edit the controls of
+    let gamepad_label = match state.selected_gamepad {
+        None => localization.get("all-gamepads"),
+        Some(idx) => localization.get_with("gamepad-n", &fluent_args! { "n" => idx + 1 }),
+    };
+    ui.horizontal(|ui| {
+        if BorderedButton::themed(small_button_style, format!("< {gamepad_label} >"))
+            .show(ui)
+            .clicked()
+        {
+            state.selected_gamepad = match state.selected_gamepad {
+                None => Some(0),
+                Some(idx) if idx + 1 < MAX_PLAYERS => Some(idx + 1),
+                Some(_) => None,
+            };
+        }
+
+        // Rumble can be turned off for one gamepad, or for all of them at once
+        let rumble_disabled = &mut state.modified_settings.rumble_disabled_gamepads;
+        let rumble_enabled = match state.selected_gamepad {
+            Some(idx) => !rumble_disabled.contains(&idx),
+            None => rumble_disabled.is_empty(),
+        };
+        let check = if rumble_enabled { "x" } else { " " };
+        let label = format!("[{check}] {}", localization.get("gamepad-rumble"));
+        if BorderedButton::themed(small_button_style, label)
+            .show(ui)
+            .clicked()
+        {
+            *rumble_disabled = match (state.selected_gamepad, rumble_enabled) {
+                (Some(idx), true) => rumble_disabled.iter().copied().chain([idx]).collect(),
+                (Some(idx), false) => rumble_disabled
+                    .iter()
+                    .copied()
+                    .filter(|x| *x != idx)
+                    .collect(),
+                (None, true) => (0..MAX_PLAYERS).collect(),
+                (None, false) => default(),
+            };
+        }
+
+        let Some(selected) = state.selected_gamepad else {
+            return;
+        };
+        let mapping = &mut state.modified_settings.player_controls;
+        let has_override = mapping.gamepad_override(selected).is_some();
+        let check = if has_override { " " } else { "x" };
+        let label = format!(
+            "[{check}] {}",
+            localization.get("use-shared-gamepad-controls")
+        );
+        if BorderedButton::themed(small_button_style, label)
+            .show(ui)
+            .clicked()
+        {
+            if has_override {
+                mapping.gamepad_overrides = mapping
+                    .gamepad_overrides
+                    .iter()
+                    .filter(|x| x.gamepad != selected)
+                    .cloned()
+                    .collect();
+            } else {
+                mapping.gamepad_overrides.push(GamepadControlSetting {
+                    gamepad: selected,
+                    controls: mapping.gamepad.clone(),
+                });
+            }
+        }
+    });
+
+    let PlayerControlMapping {
+        keyboard1,
+        keyboard2,
+        gamepad,
+        gamepad_overrides,
+    } = &mut state.modified_settings.player_controls;
+    let gamepad = match state.selected_gamepad {
+        Some(selected) => gamepad_overrides
+            .iter_mut()
+            .find(|x| x.gamepad == selected)
+            .map(|x| &mut x.controls)
+            .unwrap_or(gamepad),
+        None => gamepad,
+    };
 
     let mut input_rows = [
         (
             localization.get("move-up"),
             [
-                &mut mapping.keyboard1.movement.up,
-                &mut mapping.keyboard2.movement.up,
-                &mut mapping.gamepad.movement.up,
+                &mut keyboard1.movement.up,
+                &mut keyboard2.movement.up,
+                &mut gamepad.movement.up,
             ],
         ),
         (
             localization.get("move-down"),
             [
-                &mut mapping.keyboard1.movement.down,
-                &mut mapping.keyboard2.movement.down,
-                &mut mapping.gamepad.movement.down,
+                &mut keyboard1.movement.down,
+                &mut keyboard2.movement.down,
+                &mut gamepad.movement.down,
             ],
         ),
         (
             localization.get("move-left"),
             [
-                &mut mapping.keyboard1.movement.left,
-                &mut mapping.keyboard2.movement.left,
-                &mut mapping.gamepad.movement.left,
+                &mut keyboard1.movement.left,
+                &mut keyboard2.movement.left,
+                &mut gamepad.movement.left,
             ],
         ),
         (
             localization.get("move-right"),
             [
-                &mut mapping.keyboard1.movement.right,
-                &mut mapping.keyboard2.movement.right,
-                &mut mapping.gamepad.movement.right,
+                &mut keyboard1.movement.right,
+                &mut keyboard2.movement.right,
+                &mut gamepad.movement.right,
             ],
         ),
         (
             localization.get("move-up-alt"),
             [
-                &mut mapping.keyboard1.movement_alt.up,
-                &mut mapping.keyboard2.movement_alt.up,
-                &mut mapping.gamepad.movement_alt.up,
+                &mut keyboard1.movement_alt.up,
+                &mut keyboard2.movement_alt.up,
+                &mut gamepad.movement_alt.up,
             ],
         ),
         (
             localization.get("move-down-alt"),
             [
-                &mut mapping.keyboard1.movement_alt.down,
-                &mut mapping.keyboard2.movement_alt.down,
-                &mut mapping.gamepad.movement_alt.down,
+                &mut keyboard1.movement_alt.down,
+                &mut keyboard2.movement_alt.down,
+                &mut gamepad.movement_alt.down,
             ],
         ),
         (
             localization.get("move-left-alt"),
             [
-                &mut mapping.keyboard1.movement_alt.left,
-                &mut mapping.keyboard2.movement_alt.left,
-                &mut mapping.gamepad.movement_alt.left,
+                &mut keyboard1.movement_alt.left,
+                &mut keyboard2.movement_alt.left,
+                &mut gamepad.movement_alt.left,
             ],
         ),
         (
             localization.get("move-right-alt"),
             [
-                &mut mapping.keyboard1.movement_alt.right,
-                &mut mapping.keyboard2.movement_alt.right,
-                &mut mapping.gamepad.movement_alt.right,
+                &mut keyboard1.movement_alt.right,
+                &mut keyboard2.movement_alt.right,
+                &mut gamepad.movement_alt.right,
             ],
         ),
         (
             localization.get("jump"),
-            [
-                &mut mapping.keyboard1.jump,
-                &mut mapping.keyboard2.jump,
-                &mut mapping.gamepad.jump,
-            ],
+            [&mut keyboard1.jump, &mut keyboard2.jump, &mut gamepad.jump],
         ),
         (
             localization.get("grab-drop"),
-            [
-                &mut mapping.keyboard1.grab,
-                &mut mapping.keyboard2.grab,
-                &mut mapping.gamepad.grab,
-            ],
+            [&mut keyboard1.grab, &mut keyboard2.grab, &mut gamepad.grab],
         ),
         (
             localization.get("shoot"),
             [
-                &mut mapping.keyboard1.shoot,
-                &mut mapping.keyboard2.shoot,
-                &mut mapping.gamepad.shoot,
+                &mut keyboard1.shoot,
+                &mut keyboard2.shoot,
+                &mut gamepad.shoot,
             ],
         ),
         (
             localization.get("slide"),
             [
-                &mut mapping.keyboard1.slide,
-                &mut mapping.keyboard2.slide,
-                &mut mapping.gamepad.slide,
+                &mut keyboard1.slide,
+                &mut keyboard2.slide,
+                &mut gamepad.slide,
             ],
         ),
         (
             localization.get("ragdoll"),
             [
-                &mut mapping.keyboard1.ragdoll,
-                &mut mapping.keyboard2.ragdoll,
-                &mut mapping.gamepad.ragdoll,
+                &mut keyboard1.ragdoll,
+                &mut keyboard2.ragdoll,
+                &mut gamepad.ragdoll,
             ],
         ),
         (
             localization.get("pause"),
             [
-                &mut mapping.keyboard1.pause,
-                &mut mapping.keyboard2.pause,
-                &mut mapping.gamepad.pause,
+                &mut keyboard1.pause,
+                &mut keyboard2.pause,
+                &mut gamepad.pause,
             ],
         ),
         (
             localization.get("menu-confirm"),
             [
-                &mut mapping.keyboard1.menu_confirm,
-                &mut mapping.keyboard2.menu_confirm,
-                &mut mapping.gamepad.menu_confirm,
+                &mut keyboard1.menu_confirm,
+                &mut keyboard2.menu_confirm,
+                &mut gamepad.menu_confirm,
             ],
         ),
         (
             localization.get("menu-back"),
             [
-                &mut mapping.keyboard1.menu_back,
-                &mut mapping.keyboard2.menu_back,
-                &mut mapping.gamepad.menu_back,
+                &mut keyboard1.menu_back,
+                &mut keyboard2.menu_back,
+                &mut gamepad.menu_back,
             ],
         ),
         (
             localization.get("menu-start"),
             [
-                &mut mapping.keyboard1.menu_start,
-                &mut mapping.keyboard2.menu_start,
-                &mut mapping.gamepad.menu_start,
+                &mut keyboard1.menu_start,
+                &mut keyboard2.menu_start,
+                &mut gamepad.menu_start,
             ],
         ),
     ];
 
+    // Find the actions that are bound to the same input on the same device, ignoring the menu
+    // actions, which share inputs with gameplay actions on purpose.
+    let conflicts = {
+        let is_menu_row = |row: usize| row >= input_rows.len() - MENU_ACTION_COUNT;
+        let bindings = input_rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, (_, inputs))| {
+                inputs
+                    .iter()
+                    .enumerate()
+                    .map(move |(column, input)| (row, column, (**input).clone()))
+            })
+            .filter(|(_, _, input)| *input != InputKind::None)
+            .collect::<Vec<_>>();
+
+        bindings
+            .iter()
+            .filter(|(row, column, input)| {
+                bindings
+                    .iter()
+                    .any(|(other_row, other_column, other_input)| {
+                        // Both keyboard columns share the same keyboard
+                        let same_device = (*column == 2) == (*other_column == 2);
+                        (row, column) != (other_row, other_column)
+                            && same_device
+                            && is_menu_row(*row) == is_menu_row(*other_row)
+                            && input == other_input
+                    })
+            })
+            .map(|(row, column, _)| row * 3 + column)
+            .collect::<HashSet<_>>()
+    };
+
+    if !conflicts.is_empty() {
+        let [r, g, b, a] = meta.theme.colors.negative.as_rgba_u8();
+        ui.label(
+            normal_font
+                .rich(localization.get("conflicting-bindings"))
+                .color(egui::Color32::from_rgba_unmultiplied(r, g, b, a)),
+        );
+    }
+
     // Create input table
     let width = ui.available_width();
     let label_size = normal_font.size * 7.0;
@@ -229,11 +348,14 @@ pub(super) fn widget(
                         row.col(|ui| {
                             ui.set_width(ui.available_width() * 0.92);
 
-                            let button = BorderedButton::themed(
-                                &meta.theme.buttons.small,
-                                input.to_string(),
-                            )
-                            .show(ui);
+                            let mut text = egui::RichText::new(input.to_string());
+                            if conflicts.contains(&input_idx) {
+                                let [r, g, b, a] = meta.theme.colors.negative.as_rgba_u8();
+                                text =
+                                    text.color(egui::Color32::from_rgba_unmultiplied(r, g, b, a));
+                            }
+                            let button =
+                                BorderedButton::themed(&meta.theme.buttons.small, text).show(ui);
 
                             // Start an input binding if the button is clicked
                             if button.clicked() {
@@ -330,6 +452,9 @@ pub(super) fn widget(
         });
 }
 
+/// The number of menu actions, which are the last rows of the controls table.
+const MENU_ACTION_COUNT: usize = 3;
+
 /// The kind of input binding to listen for.
 enum BindingKind {
     Keyboard,
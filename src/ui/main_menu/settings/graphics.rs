@@ -15,6 +15,12 @@ pub(super) fn widget(
 
     if *should_reset {
         state.modified_settings.fullscreen = meta.default_settings.fullscreen;
+        state.modified_settings.minimap = meta.default_settings.minimap;
+        state.modified_settings.damage_numbers = meta.default_settings.damage_numbers;
+        state.modified_settings.screen_flash = meta.default_settings.screen_flash;
+        state.modified_settings.chromatic_aberration = meta.default_settings.chromatic_aberration;
+        state.modified_settings.vignette = meta.default_settings.vignette;
+        state.modified_settings.touch_controls = meta.default_settings.touch_controls.clone();
     }
 
     ui.add_space(normal_font.size / 2.0);
@@ -22,12 +28,78 @@ pub(super) fn widget(
     #[cfg(not(target_arch = "wasm32"))]
     ui.horizontal(|ui| {
         ui.add_space(normal_font.size * 3.0);
-        ui.checkbox(
+        focusable_checkbox(
+            ui,
             &mut state.modified_settings.fullscreen,
             normal_font.rich(localization.get("fullscreen")),
         );
     });
 
-    #[cfg(target_arch = "wasm32")]
-    ui.label(normal_font.rich(localization.get("no-graphics-settings-on-web")));
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        focusable_checkbox(
+            ui,
+            &mut state.modified_settings.minimap,
+            normal_font.rich(localization.get("minimap")),
+        );
+    });
+
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        focusable_checkbox(
+            ui,
+            &mut state.modified_settings.damage_numbers,
+            normal_font.rich(localization.get("damage-numbers")),
+        );
+    });
+
+    // Post-processing effects
+    for (setting, label) in [
+        (&mut state.modified_settings.screen_flash, "screen-flash"),
+        (
+            &mut state.modified_settings.chromatic_aberration,
+            "chromatic-aberration",
+        ),
+        (&mut state.modified_settings.vignette, "vignette"),
+    ] {
+        ui.horizontal(|ui| {
+            ui.add_space(normal_font.size * 3.0);
+            focusable_checkbox(ui, setting, normal_font.rich(localization.get(label)));
+        });
+    }
+
+    // Touch controls
+    let touch_controls = &mut state.modified_settings.touch_controls;
+    ui.horizontal(|ui| {
+        ui.add_space(normal_font.size * 3.0);
+        focusable_checkbox(
+            ui,
+            &mut touch_controls.enabled,
+            normal_font.rich(localization.get("touch-controls")),
+        );
+    });
+    if touch_controls.enabled {
+        ui.horizontal(|ui| {
+            ui.add_space(normal_font.size * 3.0);
+            focusable_checkbox(
+                ui,
+                &mut touch_controls.swap_sides,
+                normal_font.rich(localization.get("touch-controls-swap-sides")),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.add_space(normal_font.size * 3.0);
+            let label = localization.get("touch-controls-opacity");
+            ui.label(normal_font.rich(label.clone()));
+            ui.add(egui::Slider::new(&mut touch_controls.opacity, 0.1..=1.0).step_by(0.05))
+                .focus_ring(ui, &label);
+        });
+        ui.horizontal(|ui| {
+            ui.add_space(normal_font.size * 3.0);
+            let label = localization.get("touch-controls-size");
+            ui.label(normal_font.rich(label.clone()));
+            ui.add(egui::Slider::new(&mut touch_controls.size, 0.5..=2.0).step_by(0.1))
+                .focus_ring(ui, &label);
+        });
+    }
 }
@@ -21,6 +21,10 @@ pub(super) fn widget(
     if *should_reset {
         state.modified_settings.matchmaking_server =
             meta.default_settings.matchmaking_server.clone();
+        state.modified_settings.match_report_endpoint =
+            meta.default_settings.match_report_endpoint.clone();
+        state.modified_settings.stream_overlay = meta.default_settings.stream_overlay;
+        state.modified_settings.lag_compensation = meta.default_settings.lag_compensation;
     }
 
     ui.add_space(bigger_font.size / 2.0);
@@ -30,8 +34,34 @@ pub(super) fn widget(
 
         ui.add(
             egui::TextEdit::singleline(&mut state.modified_settings.matchmaking_server)
-                .font(normal_font)
+                .font(normal_font.id())
                 .desired_width(ui.available_width() - bigger_font.size * 2.0),
         );
     });
+    ui.horizontal(|ui| {
+        ui.label(bigger_font.rich(localization.get("match-report-endpoint")));
+
+        ui.add(
+            egui::TextEdit::singleline(&mut state.modified_settings.match_report_endpoint)
+                .font(normal_font.id())
+                .hint_text(localization.get("match-report-endpoint-hint"))
+                .desired_width(ui.available_width() - bigger_font.size * 2.0),
+        );
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    focusable_checkbox(
+        ui,
+        &mut state.modified_settings.stream_overlay,
+        normal_font.rich(localization.get_with(
+            "stream-overlay",
+            &fluent_args! {
+                "port" => crate::stream_overlay::STREAM_OVERLAY_PORT
+            },
+        )),
+    );
+    focusable_checkbox(
+        ui,
+        &mut state.modified_settings.lag_compensation,
+        normal_font.rich(localization.get("lag-compensation")),
+    );
 }
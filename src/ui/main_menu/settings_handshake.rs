@@ -0,0 +1,344 @@
+//! Agreement on the settings that have to match between the players of a network lobby.
+//!
+//! When the lobby opens, every player tells everybody else which version of the game they are on,
+//! a hash of their physics settings, the mutators they picked, and the [hashes][AssetHashes] of
+//! the core assets and asset packs they have loaded. The host picks the mutators for everybody, so
+//! clients take theirs from the host, but the rest can't be reconciled in the lobby. The match
+//! doesn't start until every player agrees, and the differences are listed in the lobby instead,
+//! so that the match doesn't desync once it is underway.
+
+use bones_framework::networking::{NetworkMatchSocket, SocketTarget};
+
+use crate::{mods::AssetHashes, prelude::*};
+
+use super::{pack_transfer::hash_bundle, player_select::PlayerSelectMessage};
+
+/// An asset pack loaded by a player.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LoadedPackInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    /// Hash of the contents of the pack.
+    pub hash: u64,
+}
+
+/// The settings of a player that have to match everybody else's.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MatchSettings {
+    /// The version of the game.
+    pub version: String,
+    /// Hash of the physics settings in the core metadata.
+    pub physics_hash: u64,
+    /// Hash of the contents of the core assets.
+    pub core_hash: u64,
+    pub mutators: Mutators,
+    /// The asset packs that are loaded, sorted by id.
+    pub packs: Vec<LoadedPackInfo>,
+}
+
+impl MatchSettings {
+    /// Get the local settings.
+    pub fn local(meta: &GameMeta, asset_hashes: &AssetHashes, mutators: Mutators) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            physics_hash: physics_hash(&meta.core.physics),
+            core_hash: asset_hashes.core,
+            mutators,
+            packs: asset_hashes
+                .packs
+                .iter()
+                .map(|pack| LoadedPackInfo {
+                    id: pack.id.clone(),
+                    name: pack.name.clone(),
+                    version: pack.version.clone(),
+                    hash: pack.hash,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Hash the physics settings from the bytes of each of their fields, in a fixed order.
+///
+/// The settings are destructured without a catch-all, so that new fields can't be left out of the
+/// hash by accident.
+fn physics_hash(physics: &PhysicsMeta) -> u64 {
+    let PhysicsMeta {
+        gravity,
+        terminal_velocity,
+        friction_lerp,
+        stop_threshold,
+        knockback_influence,
+        crush_threshold,
+        blast_launch_speed,
+        player,
+        materials,
+    } = physics;
+    let PhysicsPlayerMeta {
+        ragdoll_initial_pop,
+        ragdoll_initial_ang_vel,
+        ragdoll_twitch_vel,
+        ragdoll_twitch_delay,
+        ragdoll_additional_mass,
+    } = player;
+    let TileMaterialsMeta { ice, rubber } = materials;
+    let material_fields = |material: &TileMaterialMeta| {
+        let TileMaterialMeta {
+            player_slide,
+            body_friction,
+            bounciness,
+        } = *material;
+        [player_slide, body_friction, bounciness]
+    };
+
+    let fields = [
+        *gravity,
+        *terminal_velocity,
+        *friction_lerp,
+        *stop_threshold,
+        *knockback_influence,
+        *crush_threshold,
+        *blast_launch_speed,
+        *ragdoll_initial_pop,
+        *ragdoll_initial_ang_vel,
+        *ragdoll_twitch_vel,
+        *ragdoll_twitch_delay,
+        *ragdoll_additional_mass,
+    ]
+    .into_iter()
+    .chain(material_fields(ice))
+    .chain(material_fields(rubber))
+    .flat_map(f32::to_le_bytes)
+    .collect::<Vec<_>>();
+    hash_bundle(&fields)
+}
+
+/// A difference between the settings of the local player and those of another player.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingsMismatch {
+    /// The player is on another version of the game.
+    Version { player: u32, version: String },
+    /// The player has different physics settings.
+    Physics { player: u32 },
+    /// The player has different core assets.
+    CoreAssets { player: u32 },
+    /// The player hasn't taken on the host's mutators yet.
+    Mutators { player: u32 },
+    /// The player is missing an asset pack, has one that is missing locally, or has different
+    /// contents in one. Packs are named by their display name.
+    Pack { player: u32, pack: String },
+}
+
+/// State of the settings handshake in the current lobby.
+#[derive(Clone, Debug, Default)]
+pub struct SettingsHandshakeState {
+    /// The settings last sent to the other players.
+    sent: Option<MatchSettings>,
+    /// The settings received from each player.
+    received: [Option<MatchSettings>; MAX_PLAYERS as usize],
+}
+
+impl SettingsHandshakeState {
+    /// Send the local settings to everybody if they haven't been sent yet or have changed since.
+    pub fn update(&mut self, socket: &NetworkMatchSocket, settings: MatchSettings) {
+        if self.sent.as_ref() == Some(&settings) {
+            return;
+        }
+        socket.send_reliable(
+            SocketTarget::All,
+            &postcard::to_allocvec(&PlayerSelectMessage::MatchSettings(settings.clone())).unwrap(),
+        );
+        self.sent = Some(settings);
+    }
+
+    /// Store the settings received from a player.
+    pub fn handle_settings(&mut self, player: u32, settings: MatchSettings) {
+        if let Some(received) = self.received.get_mut(player as usize) {
+            *received = Some(settings);
+        }
+    }
+
+    /// Whether settings have been received from every other player in the lobby.
+    pub fn is_complete(&self, socket: &NetworkMatchSocket) -> bool {
+        (0..socket.player_count())
+            .filter(|x| *x != socket.player_idx())
+            .all(|x| self.received[x as usize].is_some())
+    }
+
+    /// Get the differences between the local settings and those of the other players.
+    pub fn mismatches(&self) -> Vec<SettingsMismatch> {
+        let Some(local) = &self.sent else {
+            return Vec::new();
+        };
+
+        let mut mismatches = Vec::new();
+        for (player, remote) in self.received.iter().enumerate() {
+            let Some(remote) = remote else {
+                continue;
+            };
+            let player = player as u32;
+
+            if remote.version != local.version {
+                mismatches.push(SettingsMismatch::Version {
+                    player,
+                    version: remote.version.clone(),
+                });
+                // Everything else is likely to differ between versions too
+                continue;
+            }
+            if remote.physics_hash != local.physics_hash {
+                mismatches.push(SettingsMismatch::Physics { player });
+            }
+            if remote.core_hash != local.core_hash {
+                mismatches.push(SettingsMismatch::CoreAssets { player });
+            }
+            if remote.mutators != local.mutators {
+                mismatches.push(SettingsMismatch::Mutators { player });
+            }
+            for pack in local
+                .packs
+                .iter()
+                .filter(|x| !remote.packs.contains(x))
+                .chain(remote.packs.iter().filter(|x| !local.packs.contains(x)))
+            {
+                let mismatch = SettingsMismatch::Pack {
+                    player,
+                    pack: pack.name.clone(),
+                };
+                // A pack with different contents on each side is listed once
+                if !mismatches.contains(&mismatch) {
+                    mismatches.push(mismatch);
+                }
+            }
+        }
+
+        mismatches
+    }
+
+    /// Whether every player in the lobby agrees on the settings, so that the match may start.
+    pub fn is_agreed(&self, socket: &NetworkMatchSocket) -> bool {
+        self.sent.is_some() && self.is_complete(socket) && self.mismatches().is_empty()
+    }
+
+    /// List the differences between the settings of the players.
+    pub fn show(&self, ui: &mut egui::Ui, meta: &GameMeta, localization: &Localization<GameMeta>) {
+        let mismatches = self.mismatches();
+        if mismatches.is_empty() {
+            return;
+        }
+        let text_style = meta
+            .theme
+            .font_styles
+            .normal
+            .with_color(meta.theme.panel.font_color);
+
+        ui.label(
+            text_style
+                .rich(localization.get("settings-mismatch"))
+                .color(egui::Color32::RED),
+        );
+        for mismatch in mismatches {
+            let message = match mismatch {
+                SettingsMismatch::Version { player, version } => localization.get_with(
+                    "settings-mismatch-version",
+                    &fluent_args! { "player" => player + 1, "version" => version },
+                ),
+                SettingsMismatch::Physics { player } => localization.get_with(
+                    "settings-mismatch-physics",
+                    &fluent_args! { "player" => player + 1 },
+                ),
+                SettingsMismatch::CoreAssets { player } => localization.get_with(
+                    "settings-mismatch-core-assets",
+                    &fluent_args! { "player" => player + 1 },
+                ),
+                SettingsMismatch::Mutators { player } => localization.get_with(
+                    "settings-mismatch-mutators",
+                    &fluent_args! { "player" => player + 1 },
+                ),
+                SettingsMismatch::Pack { player, pack } => localization.get_with(
+                    "settings-mismatch-pack",
+                    &fluent_args! { "player" => player + 1, "pack" => pack },
+                ),
+            };
+            ui.label(text_style.rich(message).color(egui::Color32::RED));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lists_each_mismatch_once() {
+        let local = MatchSettings {
+            version: "0.1.0".into(),
+            physics_hash: 1,
+            core_hash: 1,
+            mutators: default(),
+            packs: vec![LoadedPackInfo {
+                id: "sharks".into(),
+                name: "Sharks".into(),
+                version: "1.0.0".into(),
+                hash: 1,
+            }],
+        };
+        let mut state = SettingsHandshakeState::default();
+        state.handle_settings(1, local.clone());
+        assert!(state.mismatches().is_empty());
+
+        state.sent = Some(local.clone());
+        state.handle_settings(
+            2,
+            MatchSettings {
+                core_hash: 2,
+                mutators: Mutators {
+                    low_gravity: true,
+                    ..default()
+                },
+                packs: vec![LoadedPackInfo {
+                    id: "sharks".into(),
+                    name: "Sharks".into(),
+                    version: "1.0.0".into(),
+                    hash: 2,
+                }],
+                ..local.clone()
+            },
+        );
+        // Everything else is ignored when the version differs
+        state.handle_settings(
+            3,
+            MatchSettings {
+                version: "0.2.0".into(),
+                physics_hash: 2,
+                ..local
+            },
+        );
+        assert_eq!(
+            state.mismatches(),
+            vec![
+                SettingsMismatch::CoreAssets { player: 2 },
+                SettingsMismatch::Mutators { player: 2 },
+                SettingsMismatch::Pack {
+                    player: 2,
+                    pack: "Sharks".into()
+                },
+                SettingsMismatch::Version {
+                    player: 3,
+                    version: "0.2.0".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn physics_hash_covers_nested_settings() {
+        let physics = PhysicsMeta::default();
+        let mut icy = physics.clone();
+        icy.materials.ice.player_slide = 2.0;
+
+        assert_eq!(physics_hash(&physics), physics_hash(&physics.clone()));
+        assert_ne!(physics_hash(&physics), physics_hash(&icy));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{prelude::*, PackMeta};
+use crate::{mods::ModList, prelude::*, ui::focus::FocusExt, PackMeta};
 
 use super::main_menu::MenuPage;
 
@@ -23,6 +23,7 @@ pub fn map_select_menu(
     localization: Localization<GameMeta>,
     player_controls: Res<GlobalPlayerControls>,
     rng: ResInit<GlobalRng>,
+    mods: Res<ModList>,
 ) -> MapSelectAction {
     if player_controls.values().any(|x| x.menu_back_just_pressed) {
         return MapSelectAction::GoBack;
@@ -79,7 +80,8 @@ pub fn map_select_menu(
                                         "All Maps".to_string(),
                                     )
                                     .show(ui)
-                                    .focus_by_default(ui);
+                                    .focus_by_default(ui)
+                                    .speak_on_focus(ui, "All Maps");
 
                                     if all_maps_button.has_focus() {
                                         all_maps_button.scroll_to_me(None);
@@ -100,7 +102,8 @@ pub fn map_select_menu(
                                             &meta.theme.buttons.small,
                                             map_meta.name.to_string(),
                                         )
-                                        .show(ui);
+                                        .show(ui)
+                                        .speak_on_focus(ui, &map_meta.name);
 
                                         if button.has_focus() {
                                             button.scroll_to_me(None);
@@ -118,11 +121,14 @@ pub fn map_select_menu(
                                             asset_server.get(pack.root.typed::<PackMeta>());
                                         for map in pack_meta.maps.iter() {
                                             let map_meta = asset_server.get(*map);
+                                            let label =
+                                                mods.map_label(&map_meta.name, &localization);
                                             let button = BorderedButton::themed(
                                                 &meta.theme.buttons.small,
-                                                map_meta.name.to_string(),
+                                                label.clone(),
                                             )
-                                            .show(ui);
+                                            .show(ui)
+                                            .speak_on_focus(ui, &label);
 
                                             if button.has_focus() {
                                                 button.scroll_to_me(None);
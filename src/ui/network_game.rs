@@ -2,10 +2,14 @@ use bones_framework::networking::online::{
     OnlineMatchmaker, OnlineMatchmakerResponse, PlayerIdxAssignment,
 };
 
-use crate::prelude::*;
+use crate::{prelude::*, ui::focus::FocusExt};
 
 use super::main_menu::MenuPage;
 
+mod lan_beacon;
+
+use lan_beacon::{HostAddress, LanBeacon};
+
 /// Game id for matchmaking
 const GAME_ID: &str = "jumpy";
 
@@ -84,6 +88,9 @@ pub struct NetworkGameState {
     status: NetworkGameStatus,
     joined_players: usize,
     lan_servers: Vec<lan::ServerInfo>,
+    lan_beacon: LanBeacon,
+    /// The host address entered to connect to a LAN server directly.
+    direct_address: String,
     ping_update_timer: Timer,
     random_seed: u64,
 }
@@ -96,6 +103,8 @@ impl Default for NetworkGameState {
             service_info: default(),
             status: default(),
             lan_servers: default(),
+            lan_beacon: default(),
+            direct_address: default(),
             joined_players: default(),
             ping_update_timer: Timer::new(Duration::from_secs(1), TimerMode::Repeating),
             random_seed: DEFAULT_RANDOM_SEED as u64,
@@ -161,9 +170,11 @@ pub fn network_game_menu(
                         if matches!(state.match_kind, MatchKind::Lan(..)) {
                             lan = lan.underline();
                         }
+                        let lan_label = lan.text().to_string();
                         if BorderedButton::themed(normal_button_style, lan)
                             .show(ui)
                             .focus_by_default(ui)
+                            .speak_on_focus(ui, &lan_label)
                             .clicked()
                         {
 
@@ -184,8 +195,10 @@ pub fn network_game_menu(
                         if matches!(state.match_kind, MatchKind::Online(..)) {
                             online = online.underline();
                         }
+                        let online_label = online.text().to_string();
                         if BorderedButton::themed(normal_button_style, online)
                             .show(ui)
+                            .speak_on_focus(ui, &online_label)
                             .clicked()
                         {
                             state.match_kind = MatchKind::Online(default());
@@ -202,11 +215,13 @@ pub fn network_game_menu(
                                             if matches!(mode, LanMode::Host { .. }) {
                                                 host = host.underline();
                                             }
+                                            let host_label = host.text().to_string();
                                             if BorderedButton::themed(
                                                 &meta.theme.buttons.small,
                                                 host,
                                             )
                                             .show(ui)
+                                            .speak_on_focus(ui, &host_label)
                                             .clicked()
                                             {
                                                 *mode = LanMode::Host {
@@ -221,11 +236,13 @@ pub fn network_game_menu(
                                             if matches!(mode, LanMode::Join) {
                                                 join = join.underline();
                                             }
+                                            let join_label = join.text().to_string();
                                             if BorderedButton::themed(
                                                 &meta.theme.buttons.small,
                                                 join,
                                             )
                                             .show(ui)
+                                            .speak_on_focus(ui, &join_label)
                                             .clicked()
                                             {
                                                 *mode = LanMode::Join
@@ -251,6 +268,8 @@ pub fn network_game_menu(
                         match_kind,
                         lan_service_discovery_recv,
                         lan_servers,
+                        lan_beacon,
+                        direct_address,
                         service_info: host_info,
                         status,
                         ping_update_timer,
@@ -271,6 +290,7 @@ pub fn network_game_menu(
                                     *status = NetworkGameStatus::Idle;
                                 }
                                 lan::prepare_to_join(lan_servers, lan_service_discovery_recv, ping_update_timer);
+                                lan_beacon.discover(lan_servers);
 
                                 if *status != NetworkGameStatus::Joining {
                                     ui.label(
@@ -314,6 +334,39 @@ pub fn network_game_menu(
                                         }
                                     });
 
+                                    // Connect to a server that can't be discovered with its host address.
+                                    ui.add_space(normal_text_style.size / 2.0);
+                                    ui.label(normal_text_style.rich(localization.get("direct-connect")));
+                                    ui.add_space(normal_text_style.size / 2.0);
+                                    ui.horizontal(|ui| {
+                                        ui.add(
+                                            egui::TextEdit::singleline(direct_address)
+                                                .hint_text(localization.get("host-address"))
+                                                .font(normal_text_style.id())
+                                                .desired_width(ui.available_width() * 0.6),
+                                        );
+
+                                        let server = HostAddress::decode(direct_address)
+                                            .and_then(|x| x.to_server());
+                                        ui.scope(|ui| {
+                                            ui.set_enabled(server.is_some());
+                                            if BorderedButton::themed(
+                                                small_button_style,
+                                                localization.get("connect"),
+                                            )
+                                            .show(ui)
+                                            .clicked()
+                                            {
+                                                if let Some(server) = &server {
+                                                    match lan::join_server(server) {
+                                                        Ok(()) => *status = NetworkGameStatus::Joining,
+                                                        Err(e) => warn!("Could not connect to host: {e}"),
+                                                    }
+                                                }
+                                            }
+                                        });
+                                    });
+
                                 // If we are trying to join a match.
                                 } else {
                                     ui.label(
@@ -322,6 +375,7 @@ pub fn network_game_menu(
                                     );
 
                                     if let Some(lan_socket) = lan::wait_game_start() {
+                                        lan_beacon.close();
                                         world.resources.insert(lan_socket);
                                         *status = default();
                                        ui.ctx().set_state(MenuPage::PlayerSelect);
@@ -406,7 +460,10 @@ pub fn network_game_menu(
 
                                 // If we are hosting a match currently
                                 } else if *status == NetworkGameStatus::Hosting {
+                                    lan_beacon.advertise(service_info);
+
                                     if let Some(socket) = lan::wait_players(joined_players, service_info) {
+                                        lan_beacon.close();
                                         world.resources.insert(socket);
                                         *status = default();
                                         ui.ctx().set_state(MenuPage::PlayerSelect);
@@ -421,6 +478,7 @@ pub fn network_game_menu(
                                         .clicked()
                                         {
                                             lan::stop_server(service_info);
+                                            lan_beacon.close();
                                             *status = NetworkGameStatus::Idle;
                                         }
 
@@ -434,6 +492,27 @@ pub fn network_game_menu(
                                             ))
                                         );
                                     });
+
+                                    // Share the host address for players that can't discover the server.
+                                    let host_address = HostAddress::from_server(service_info).encode();
+                                    ui.add_space(normal_text_style.size / 2.0);
+                                    ui.horizontal(|ui| {
+                                        ui.label(normal_text_style.rich(localization.get("host-address")));
+                                        if BorderedButton::themed(
+                                            small_button_style,
+                                            localization.get("copy"),
+                                        )
+                                        .show(ui)
+                                        .clicked()
+                                        {
+                                            ui.output_mut(|x| x.copied_text = host_address.clone());
+                                        }
+                                    });
+                                    ui.add(
+                                        egui::TextEdit::singleline(&mut host_address.as_str())
+                                            .font(smaller_text_style.id())
+                                            .desired_width(ui.available_width()),
+                                    );
                                 }
                             }
                         }
@@ -579,6 +658,7 @@ pub fn network_game_menu(
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
                     if BorderedButton::themed(normal_button_style, localization.get("back"))
                         .show(ui)
+                        .speak_on_focus(ui, &localization.get("back"))
                         .clicked()
                         || player_controls.values().any(|x| x.menu_back_just_pressed)
                     {
@@ -609,6 +689,7 @@ pub fn network_game_menu(
                             }
                         }
 
+                        lan_beacon.close();
                         ui.ctx().set_state(MenuPage::Home);
                     }
                 });
@@ -0,0 +1,224 @@
+//! LAN discovery over UDP broadcast, and host addresses for connecting directly.
+//!
+//! LAN servers are normally found with mDNS, which some networks block. While hosting, a beacon with
+//! the address of the server is also broadcast to the whole subnet, so that it can be found on those
+//! networks too. The same address can be shared as a text code and entered by hand to connect to a
+//! host that can't be discovered at all.
+
+use std::{
+    net::{Ipv4Addr, UdpSocket},
+    sync::Arc,
+    time::Instant,
+};
+
+use bones_framework::networking::lan;
+
+use crate::prelude::*;
+
+/// The port that LAN beacons are broadcast on.
+const BEACON_PORT: u16 = 39640;
+
+/// How often the host broadcasts its beacon.
+const BEACON_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a server stays listed after its last beacon.
+const BEACON_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Everything needed to connect to a LAN server, without having to discover it first.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostAddress {
+    service_type: String,
+    instance_name: String,
+    hostname: String,
+    addresses: Vec<String>,
+    port: u16,
+    /// The properties of the service, which include what the host needs to authenticate us.
+    properties: Vec<(String, String)>,
+}
+
+impl HostAddress {
+    /// Get the address of a server.
+    pub fn from_server(server: &lan::ServerInfo) -> Self {
+        let service = &server.service;
+        let service_type = service.get_type().to_string();
+        let instance_name = service
+            .get_fullname()
+            .strip_suffix(&service_type)
+            .unwrap_or(service.get_fullname())
+            .trim_end_matches('.')
+            .to_string();
+
+        Self {
+            service_type,
+            instance_name,
+            hostname: service.get_hostname().to_string(),
+            addresses: service
+                .get_addresses()
+                .iter()
+                .map(|x| x.to_string())
+                .collect(),
+            port: service.get_port(),
+            properties: service
+                .get_properties()
+                .iter()
+                .map(|x| (x.key().to_string(), x.val_str().to_string()))
+                .collect(),
+        }
+    }
+
+    /// Get a server that can be joined from its address.
+    pub fn to_server(&self) -> Option<lan::ServerInfo> {
+        let properties = self.properties.iter().cloned().collect::<HashMap<_, _>>();
+        let service = mdns_sd::ServiceInfo::new(
+            &self.service_type,
+            &self.instance_name,
+            &self.hostname,
+            self.addresses.join(",").as_str(),
+            self.port,
+            properties,
+        )
+        .map_err(|e| debug!("Invalid host address: {e}"))
+        .ok()?;
+
+        Some(lan::ServerInfo {
+            service,
+            ping: None,
+        })
+    }
+
+    /// Encode the address as text that can be shared with other players.
+    pub fn encode(&self) -> String {
+        postcard::to_allocvec(self)
+            .unwrap()
+            .iter()
+            .map(|x| format!("{x:02x}"))
+            .collect()
+    }
+
+    /// Decode an address that was shared as text, ignoring any whitespace.
+    pub fn decode(text: &str) -> Option<Self> {
+        let text = text
+            .chars()
+            .filter(|x| !x.is_whitespace())
+            .collect::<String>();
+        if text.len() % 2 != 0 {
+            return None;
+        }
+        let bytes = (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+        postcard::from_bytes(&bytes).ok()
+    }
+}
+
+/// Broadcasts the beacon of the hosted server, and listens for the beacons of others.
+#[derive(Clone, Default)]
+pub struct LanBeacon {
+    socket: Option<Arc<UdpSocket>>,
+    /// Whether the socket is listening on the beacon port, as opposed to only sending from it.
+    is_listening: bool,
+    last_sent: Option<Instant>,
+    /// The servers found through beacons, with when their last beacon arrived.
+    discovered: Vec<(HostAddress, Instant)>,
+}
+
+impl LanBeacon {
+    /// Broadcast the beacon for the hosted server, if it is time to.
+    pub fn advertise(&mut self, server: &lan::ServerInfo) {
+        if self
+            .last_sent
+            .is_some_and(|x| x.elapsed() < BEACON_INTERVAL)
+        {
+            return;
+        }
+        self.last_sent = Some(Instant::now());
+
+        let Some(socket) = self.socket(false) else {
+            return;
+        };
+        let beacon = postcard::to_allocvec(&HostAddress::from_server(server)).unwrap();
+        if let Err(e) = socket.send_to(&beacon, (Ipv4Addr::BROADCAST, BEACON_PORT)) {
+            debug!("Could not broadcast LAN beacon: {e}");
+        }
+    }
+
+    /// Add the servers heard from through beacons to the list of LAN servers.
+    pub fn discover(&mut self, lan_servers: &mut Vec<lan::ServerInfo>) {
+        if let Some(socket) = self.socket(true) {
+            let mut buf = [0; 2048];
+            while let Ok((len, _)) = socket.recv_from(&mut buf) {
+                let Ok(address) = postcard::from_bytes::<HostAddress>(&buf[..len]) else {
+                    continue;
+                };
+                self.discovered.retain(|(x, _)| *x != address);
+                self.discovered.push((address, Instant::now()));
+            }
+        }
+        self.discovered
+            .retain(|(_, at)| at.elapsed() < BEACON_TIMEOUT);
+
+        for (address, _) in &self.discovered {
+            let is_listed = lan_servers.iter().any(|server| {
+                server.service.get_hostname() == address.hostname
+                    && server.service.get_port() == address.port
+            });
+            if !is_listed {
+                lan_servers.extend(address.to_server());
+            }
+        }
+    }
+
+    /// Stop broadcasting or listening.
+    pub fn close(&mut self) {
+        *self = default();
+    }
+
+    fn socket(&mut self, listen: bool) -> Option<Arc<UdpSocket>> {
+        if self.socket.is_some() && self.is_listening != listen {
+            self.socket = None;
+        }
+        if self.socket.is_none() {
+            let port = if listen { BEACON_PORT } else { 0 };
+            let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))
+                .and_then(|socket| {
+                    socket.set_nonblocking(true)?;
+                    socket.set_broadcast(true)?;
+                    Ok(socket)
+                })
+                .map_err(|e| debug!("Could not open LAN beacon socket: {e}"))
+                .ok()?;
+            self.socket = Some(Arc::new(socket));
+            self.is_listening = listen;
+        }
+        self.socket.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shared_host_address_joins_the_same_server() {
+        let address = HostAddress {
+            service_type: "_jumpy._udp.local.".into(),
+            instance_name: "Fish Fight".into(),
+            hostname: "fishy.local.".into(),
+            addresses: vec!["192.168.1.20".into()],
+            port: 43210,
+            properties: vec![("players".into(), "2".into())],
+        };
+        let server = address.to_server().unwrap();
+        assert_eq!(HostAddress::from_server(&server), address);
+
+        // Pasted addresses often get wrapped or padded
+        let encoded = address.encode();
+        let (start, end) = encoded.split_at(10);
+        assert_eq!(
+            HostAddress::decode(&format!("  {start}\n{end} ")),
+            Some(address)
+        );
+        assert_eq!(HostAddress::decode(&encoded[..encoded.len() - 1]), None);
+    }
+}
@@ -2,7 +2,13 @@ use std::ops::Deref;
 
 use crate::{core::JumpyDefaultMatchRunner, prelude::*};
 
-use super::scoring::ScoringMenuState;
+use super::{
+    main_menu::{
+        player_select::{PlayerSelectState, PlayerSlot, PlayerSlotControlSource},
+        MenuPage,
+    },
+    scoring::ScoringMenuState,
+};
 
 #[derive(Clone, Debug, Copy, Default)]
 enum PauseMenuPage {
@@ -31,13 +37,12 @@ fn pause_menu_system(
     mut pause_menu: ResMutInit<PauseMenu>,
 ) {
     let mut back_to_menu = false;
+    let mut back_to_lobby = false;
     let mut restart_game = false;
     let mut close_pause_menu = false;
     let mut close_settings_menu = false;
     let mut select_map = None;
     if let Some(session) = sessions.get_mut(SessionNames::GAME) {
-        let pause_pressed = controls.values().any(|x| x.pause_just_pressed);
-
         #[cfg(not(target_arch = "wasm32"))]
         let is_online = session
             .world
@@ -47,10 +52,61 @@ fn pause_menu_system(
         #[cfg(target_arch = "wasm32")]
         let is_online = false;
 
+        // Controllers that aren't in the match press start to drop in instead of pausing it
+        let mut drop_in = session.world.resource_mut::<DropIn>();
+        let mut match_inputs = session.world.resource_mut::<MatchInputs>();
+        let drop_in_sources = controls
+            .keys()
+            .filter(|source| drop_in.free_slot(&match_inputs, **source).is_some())
+            .copied()
+            .collect::<Vec<_>>();
+        if !pause_menu.menu_open && !is_online {
+            for source in &drop_in_sources {
+                if controls[source].menu_start_just_pressed {
+                    drop_in.join(&mut match_inputs, *source, &meta.core.players);
+                }
+            }
+        }
+        drop(drop_in);
+        drop(match_inputs);
+
+        let pause_pressed = controls
+            .iter()
+            .any(|(source, x)| x.pause_just_pressed && !drop_in_sources.contains(source));
+
         if pause_menu.menu_open {
             let page = ctx.get_state::<PauseMenuPage>();
 
             match page {
+                // Online matches keep running, so the menu is a smaller overlay that leaves the
+                // match visible
+                PauseMenuPage::Pause if is_online => {
+                    egui::Area::new("online-pause-menu")
+                        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 16.0))
+                        .order(egui::Order::Foreground)
+                        .show(&ctx, |ui| {
+                            BorderedFrame::new(&meta.theme.panel.border)
+                                .padding(meta.theme.panel.padding)
+                                .show(ui, |ui| {
+                                    ui.set_width(meta.main_menu.menu_width);
+
+                                    world.run_system(
+                                        main_pause_menu,
+                                        (
+                                            ui,
+                                            session,
+                                            PauseMenuActions {
+                                                restart_game: &mut restart_game,
+                                                back_to_menu: &mut back_to_menu,
+                                                back_to_lobby: &mut back_to_lobby,
+                                                close_pause_menu: &mut close_pause_menu,
+                                            },
+                                            is_online,
+                                        ),
+                                    );
+                                });
+                        });
+                }
                 PauseMenuPage::Pause => {
                     egui::CentralPanel::default()
                         .frame(egui::Frame::none())
@@ -75,9 +131,12 @@ fn pause_menu_system(
                                         (
                                             ui,
                                             session,
-                                            &mut restart_game,
-                                            &mut back_to_menu,
-                                            &mut close_pause_menu,
+                                            PauseMenuActions {
+                                                restart_game: &mut restart_game,
+                                                back_to_menu: &mut back_to_menu,
+                                                back_to_lobby: &mut back_to_lobby,
+                                                close_pause_menu: &mut close_pause_menu,
+                                            },
                                             is_online,
                                         ),
                                     );
@@ -125,17 +184,47 @@ fn pause_menu_system(
         sessions.end_game();
         sessions.start_menu();
         pause_menu.menu_open = false;
+    } else if back_to_lobby {
+        // Go back to player select with the same players, ready to pick another map
+        let game_world = &sessions.get(SessionNames::GAME).unwrap().world;
+        let match_inputs = game_world.resource::<MatchInputs>();
+        let lobby = PlayerSelectState {
+            slots: std::array::from_fn(|i| {
+                let player = &match_inputs.players[i];
+                let control_source = match player.control_source {
+                    _ if player.is_ai => PlayerSlotControlSource::Ai,
+                    Some(source) => PlayerSlotControlSource::User(source),
+                    None => return PlayerSlot::Empty,
+                };
+                if !player.active {
+                    return PlayerSlot::Empty;
+                }
+                PlayerSlot::Ready {
+                    control_source,
+                    selected_player: player.selected_player,
+                    selected_hat: player.selected_hat,
+                }
+            }),
+            mode: *game_world.resource::<GameMode>(),
+            mutators: *game_world.resource::<Mutators>(),
+            drop_in: game_world.resource::<DropIn>().enabled,
+            ..default()
+        };
+        drop(match_inputs);
+        sessions.end_game();
+        sessions.start_menu();
+        ctx.set_state(lobby);
+        ctx.set_state(MenuPage::PlayerSelect);
+        pause_menu.menu_open = false;
     } else if restart_game {
         sessions.restart_game(None, false);
         pause_menu.menu_open = false;
     } else if let Some(maps) = select_map {
-        let match_info = sessions
-            .get(SessionNames::GAME)
-            .unwrap()
-            .world
-            .resource::<MatchInputs>()
-            .deref()
-            .clone();
+        let game_world = &sessions.get(SessionNames::GAME).unwrap().world;
+        let match_info = game_world.resource::<MatchInputs>().deref().clone();
+        let mode = *game_world.resource::<GameMode>();
+        let mutators = *game_world.resource::<Mutators>();
+        let drop_in = *game_world.resource::<DropIn>();
         sessions.end_game();
         sessions.create_with(SessionNames::GAME, |builder| {
             builder.install_plugin(crate::core::MatchPlugin {
@@ -146,6 +235,10 @@ fn pause_menu_system(
                     ..match_info.players[i]
                 }),
                 plugins: meta.get_plugins(&assets),
+                mode,
+                mutators,
+                lag_compensation: default(),
+                drop_in,
                 session_runner: Box::<JumpyDefaultMatchRunner>::default(),
                 score: default(),
             });
@@ -162,21 +255,28 @@ fn pause_menu_system(
     }
 }
 
+/// What the main page of the pause menu asks the pause menu system to do.
+struct PauseMenuActions<'a> {
+    restart_game: &'a mut bool,
+    back_to_menu: &'a mut bool,
+    back_to_lobby: &'a mut bool,
+    close_pause_menu: &'a mut bool,
+}
+
 fn main_pause_menu(
-    mut param: In<(
-        &mut egui::Ui,
-        &mut Session,
-        &mut bool,
-        &mut bool,
-        &mut bool,
-        bool,
-    )>,
+    mut param: In<(&mut egui::Ui, &mut Session, PauseMenuActions, bool)>,
     meta: Root<GameMeta>,
     localization: Localization<GameMeta>,
     controls: Res<GlobalPlayerControls>,
     scoring_menu: Res<ScoringMenuState>,
 ) {
-    let (ui, session, restart_game, back_to_menu, close_pause_menu, is_online) = &mut *param;
+    let (ui, session, actions, is_online) = &mut *param;
+    let PauseMenuActions {
+        restart_game,
+        back_to_menu,
+        back_to_lobby,
+        close_pause_menu,
+    } = actions;
 
     // Unpause the game
     if controls.values().any(|x| x.pause_just_pressed) {
@@ -198,6 +298,15 @@ fn main_pause_menu(
                 .rich(localization.get("paused"))
                 .color(meta.theme.panel.font_color),
         );
+        if *is_online {
+            ui.label(
+                meta.theme
+                    .font_styles
+                    .smaller
+                    .rich(localization.get("match-still-running"))
+                    .color(meta.theme.panel.font_color),
+            );
+        }
 
         // Map title
         if let Some(map_meta) = session.world.get_resource::<SpawnedMapMeta>() {
@@ -224,6 +333,20 @@ fn main_pause_menu(
 
         // Local game buttons
         ui.scope(|ui| {
+            // Settings button
+            if BorderedButton::themed(&meta.theme.buttons.normal, localization.get("settings"))
+                .min_size(vec2(width, 0.0))
+                .show(ui)
+                .clicked()
+            {
+                ui.ctx().set_state(PauseMenuPage::Settings);
+            }
+
+            // The other players of online matches can't follow us to another map or the lobby
+            if *is_online {
+                return;
+            }
+
             // Map select button
             if BorderedButton::themed(
                 &meta.theme.buttons.normal,
@@ -236,15 +359,6 @@ fn main_pause_menu(
                 ui.ctx().set_state(PauseMenuPage::MapSelect);
             }
 
-            // Settings button
-            if BorderedButton::themed(&meta.theme.buttons.normal, localization.get("settings"))
-                .min_size(vec2(width, 0.0))
-                .show(ui)
-                .clicked()
-            {
-                ui.ctx().set_state(PauseMenuPage::Settings);
-            }
-
             // Restart button
             if BorderedButton::themed(&meta.theme.buttons.normal, localization.get("restart"))
                 .min_size(vec2(width, 0.0))
@@ -253,6 +367,18 @@ fn main_pause_menu(
             {
                 **restart_game = true;
             }
+
+            // Lobby button
+            if BorderedButton::themed(
+                &meta.theme.buttons.normal,
+                localization.get("quit-to-lobby"),
+            )
+            .min_size(vec2(width, 0.0))
+            .show(ui)
+            .clicked()
+            {
+                **back_to_lobby = true;
+            }
         });
 
         // Re-add edit button once map editor is back in game.
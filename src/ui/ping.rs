@@ -0,0 +1,204 @@
+//! Round trip times to the other players of network lobbies and matches.
+//!
+//! Every second, a ping with the time it was sent is sent to the other players over the reliable
+//! channel of the match socket, and they send it right back. Like chat messages, pings are taken
+//! out of the received messages with [`NetworkPing::filter_messages`] by whoever drains the socket.
+
+use std::time::Instant;
+
+use bones_framework::networking::{NetworkMatchSocket, NetworkSocket, SocketTarget, SyncingInfo};
+
+use crate::prelude::*;
+
+pub fn game_plugin(game: &mut Game) {
+    game.init_shared_resource::<NetworkPing>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session.add_system_to_stage(Update, ping_system);
+}
+
+const PING_MESSAGE_MAGIC: u8 = 112;
+
+/// How often the other players are pinged.
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How much each new round trip time counts towards the displayed one.
+const PING_SMOOTHING: f32 = 0.25;
+
+#[derive(Serialize, Deserialize)]
+struct PingMessage {
+    magic: u8,
+    kind: PingKind,
+}
+
+#[derive(Serialize, Deserialize)]
+enum PingKind {
+    /// A ping with when it was sent, in microseconds since the sender started measuring.
+    Ping(u64),
+    /// An answer to a ping, with the time that it was sent with.
+    Pong(u64),
+}
+
+/// Shared resource with the round trip times to the other players in the network lobby or match.
+#[derive(HasSchema, Clone, Debug)]
+#[schema(no_default)]
+pub struct NetworkPing {
+    /// What ping times are measured from.
+    epoch: Instant,
+    last_sent: Option<Instant>,
+    /// Pings that still have to be answered, with who sent them.
+    pending_pongs: Vec<(u32, u64)>,
+    round_trip_times: [Option<Duration>; MAX_PLAYERS as usize],
+}
+
+impl Default for NetworkPing {
+    fn default() -> Self {
+        Self {
+            epoch: Instant::now(),
+            last_sent: None,
+            pending_pongs: default(),
+            round_trip_times: default(),
+        }
+    }
+}
+
+impl NetworkPing {
+    /// Get the round trip time to a player, once it has been measured.
+    pub fn round_trip_time(&self, player: u32) -> Option<Duration> {
+        self.round_trip_times
+            .get(player as usize)
+            .copied()
+            .flatten()
+    }
+
+    /// Take the pings out of the messages received from the socket, and return the others.
+    pub fn filter_messages(&mut self, messages: Vec<(u32, Vec<u8>)>) -> Vec<(u32, Vec<u8>)> {
+        messages
+            .into_iter()
+            .filter(|(player, data)| {
+                let Ok(message) = postcard::from_bytes::<PingMessage>(data) else {
+                    return true;
+                };
+                if message.magic != PING_MESSAGE_MAGIC {
+                    return true;
+                }
+                match message.kind {
+                    PingKind::Ping(sent_at) => self.pending_pongs.push((*player, sent_at)),
+                    PingKind::Pong(sent_at) => self.record_pong(*player, sent_at),
+                }
+                false
+            })
+            .collect()
+    }
+
+    fn record_pong(&mut self, player: u32, sent_at: u64) {
+        let Some(round_trip_time) = self
+            .epoch
+            .elapsed()
+            .checked_sub(Duration::from_micros(sent_at))
+        else {
+            return;
+        };
+        let Some(slot) = self.round_trip_times.get_mut(player as usize) else {
+            return;
+        };
+        *slot = Some(match *slot {
+            Some(previous) => {
+                previous.mul_f32(1.0 - PING_SMOOTHING) + round_trip_time.mul_f32(PING_SMOOTHING)
+            }
+            None => round_trip_time,
+        });
+    }
+
+    /// Answer pings, and ping the other players if it is time to.
+    fn update(&mut self, socket: &dyn NetworkSocket) {
+        let send = |player: u32, kind: PingKind| {
+            let message = PingMessage {
+                magic: PING_MESSAGE_MAGIC,
+                kind,
+            };
+            socket.send_reliable(
+                SocketTarget::Player(player),
+                &postcard::to_allocvec(&message).unwrap(),
+            );
+        };
+
+        for (player, sent_at) in self.pending_pongs.drain(..) {
+            send(player, PingKind::Pong(sent_at));
+        }
+
+        if self.last_sent.is_some_and(|x| x.elapsed() < PING_INTERVAL) {
+            return;
+        }
+        self.last_sent = Some(Instant::now());
+        let now = self.epoch.elapsed().as_micros() as u64;
+        for player in (0..socket.player_count()).filter(|x| *x != socket.player_idx()) {
+            send(player, PingKind::Ping(now));
+        }
+    }
+}
+
+/// Get the color to show a round trip time with, which is the warning color above the high ping
+/// threshold.
+pub fn ping_color(meta: &GameMeta, round_trip_time: Duration) -> Color {
+    if round_trip_time.as_millis() > meta.network.high_ping_threshold as u128 {
+        meta.theme.colors.negative
+    } else {
+        meta.theme.colors.positive
+    }
+}
+
+/// Ping the other players of the lobby or match, and show the round trip times during matches.
+fn ping_system(
+    meta: Root<GameMeta>,
+    localization: Localization<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    mut ping: ResMut<NetworkPing>,
+) {
+    let match_socket = sessions.get(SessionNames::GAME).and_then(|session| {
+        session
+            .world
+            .get_resource::<SyncingInfo>()
+            .and_then(|x| x.socket().cloned())
+    });
+    let Some(socket) = match_socket else {
+        let lobby_socket = sessions
+            .get(SessionNames::MAIN_MENU)
+            .and_then(|session| session.world.get_resource::<NetworkMatchSocket>());
+        match lobby_socket {
+            Some(socket) => ping.update(&*socket),
+            None => *ping = default(),
+        }
+        return;
+    };
+    ping.update(&socket);
+
+    let local_player = socket.player_idx();
+    egui::Area::new("ping")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -8.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(&ctx, |ui| {
+            for player in (0..socket.player_count()).filter(|x| *x != local_player) {
+                let Some(round_trip_time) = ping.round_trip_time(player) else {
+                    continue;
+                };
+                let [r, g, b, a] = ping_color(&meta, round_trip_time).as_rgba_u8();
+                ui.label(
+                    meta.theme
+                        .font_styles
+                        .smaller
+                        .rich(localization.get_with(
+                            "player-ping",
+                            &fluent_args! {
+                                "player" => player + 1,
+                                "ping" => round_trip_time.as_millis() as u64
+                            },
+                        ))
+                        .color(egui::Color32::from_rgba_unmultiplied(r, g, b, a)),
+                );
+            }
+        });
+}
@@ -0,0 +1,217 @@
+//! Full screen effects drawn over the running match, such as the white flash of nearby
+//! explosions, the color fringes of the kill cam, and the vignette.
+//!
+//! The strength of every effect is configured in the [`PostProcessingMeta`], and each of them can
+//! be turned off in the graphics settings.
+
+use crate::prelude::*;
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session
+        .init_resource::<ScreenFlash>()
+        .add_system_to_stage(Update, vignette)
+        .add_system_to_stage(Update, kill_cam_aberration)
+        .add_system_to_stage(Update, screen_flash);
+}
+
+/// How the post-processing effects look.
+#[derive(HasSchema, Clone, Debug, Default)]
+#[repr(C)]
+pub struct PostProcessingMeta {
+    /// How opaque the white flash of an explosion right at the center of the view is, from `0.0`
+    /// to `1.0`. Explosions further away flash less, down to nothing at `flash_radius`.
+    pub flash_opacity: f32,
+    /// How far from the center of the view an explosion may be to flash the screen, in pixels.
+    pub flash_radius: f32,
+    /// How many frames the flash takes to fade out.
+    pub flash_frames: u32,
+    /// How far the color fringes of the kill cam reach in from the sides of the screen, relative
+    /// to the screen width.
+    pub aberration_width: f32,
+    /// How opaque the color fringes of the kill cam are when it starts, from `0.0` to `1.0`.
+    pub aberration_opacity: f32,
+    /// How opaque the vignette is at the edges of the screen, from `0.0` to `1.0`.
+    pub vignette_opacity: f32,
+    /// How far the vignette reaches in from the edges of the screen, relative to the screen size.
+    pub vignette_size: f32,
+}
+
+/// The flash of the latest nearby explosion.
+#[derive(HasSchema, Clone, Debug, Default)]
+struct ScreenFlash {
+    /// How opaque the flash was when it started.
+    opacity: f32,
+    /// How many frames ago the flash started.
+    age: u32,
+}
+
+/// Get the painter for the layer that the effects are drawn on, below the rest of the HUD.
+fn effects_painter(ctx: &egui::Context) -> egui::Painter {
+    ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("post_processing"),
+    ))
+}
+
+/// Get the center of the view of the first camera in the game world.
+fn camera_center(world: &World) -> Option<Vec2> {
+    let entities = world.resources.get::<Entities>()?;
+    let cameras = world.components.get::<Camera>().borrow();
+    let transforms = world.components.get::<Transform>().borrow();
+
+    entities
+        .iter_with((&*cameras, &*transforms))
+        .next()
+        .map(|(_, (_, transform))| transform.translation.xy())
+}
+
+/// Darken the edges of the screen.
+fn vignette(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+) {
+    let meta = &meta.post_processing;
+    if !storage.get::<Settings>().is_some_and(|x| x.vignette) || meta.vignette_opacity <= 0.0 {
+        return;
+    }
+    if sessions.get(SessionNames::GAME).is_none() {
+        return;
+    }
+
+    let outer = ctx.screen_rect();
+    let inner = outer.shrink2(outer.size() * meta.vignette_size.clamp(0.0, 0.5));
+    let edge = egui::Color32::from_black_alpha((meta.vignette_opacity.min(1.0) * 255.0) as u8);
+
+    // A ring of quads that fades from transparent on the inside to dark on the outside
+    let mut mesh = egui::Mesh::default();
+    let corners = |rect: egui::Rect| {
+        [
+            rect.left_top(),
+            rect.right_top(),
+            rect.right_bottom(),
+            rect.left_bottom(),
+        ]
+    };
+    for (outer, inner) in corners(outer).into_iter().zip(corners(inner)) {
+        mesh.colored_vertex(outer, edge);
+        mesh.colored_vertex(inner, egui::Color32::TRANSPARENT);
+    }
+    for i in 0..4 {
+        let (outer, inner) = (i * 2, i * 2 + 1);
+        let (next_outer, next_inner) = ((i * 2 + 2) % 8, (i * 2 + 3) % 8);
+        mesh.add_triangle(outer, next_outer, inner);
+        mesh.add_triangle(inner, next_outer, next_inner);
+    }
+
+    effects_painter(&ctx).add(mesh);
+}
+
+/// Fringe the sides of the screen with red and blue while the slow motion kill cam plays.
+fn kill_cam_aberration(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+) {
+    let meta = &meta.post_processing;
+    if !storage
+        .get::<Settings>()
+        .is_some_and(|x| x.chromatic_aberration)
+    {
+        return;
+    }
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        return;
+    };
+    let Some(kill_cam) = game_session.world.resources.get::<KillCamState>() else {
+        return;
+    };
+    let Some(timer) = kill_cam.timer.as_ref().filter(|timer| !timer.finished()) else {
+        return;
+    };
+
+    let strength = meta.aberration_opacity.min(1.0) * (1.0 - timer.percent());
+    let screen = ctx.screen_rect();
+    let width = screen.width() * meta.aberration_width;
+    let painter = effects_painter(&ctx);
+    let mut fringe = |rect: egui::Rect, color: egui::Color32, outer_left: bool| {
+        let edge = color.gamma_multiply(strength);
+        let (left, right) = if outer_left {
+            (edge, egui::Color32::TRANSPARENT)
+        } else {
+            (egui::Color32::TRANSPARENT, edge)
+        };
+        let mut mesh = egui::Mesh::default();
+        mesh.colored_vertex(rect.left_top(), left);
+        mesh.colored_vertex(rect.right_top(), right);
+        mesh.colored_vertex(rect.right_bottom(), right);
+        mesh.colored_vertex(rect.left_bottom(), left);
+        mesh.add_triangle(0, 1, 2);
+        mesh.add_triangle(0, 2, 3);
+        painter.add(mesh);
+    };
+
+    fringe(
+        egui::Rect::from_min_max(
+            screen.left_top(),
+            egui::pos2(screen.left() + width, screen.bottom()),
+        ),
+        egui::Color32::RED,
+        true,
+    );
+    fringe(
+        egui::Rect::from_min_max(
+            egui::pos2(screen.right() - width, screen.top()),
+            screen.right_bottom(),
+        ),
+        egui::Color32::from_rgb(0, 160, 255),
+        false,
+    );
+}
+
+/// Flash the screen white when an explosion goes off close to the center of the view.
+fn screen_flash(
+    meta: Root<GameMeta>,
+    ctx: Res<EguiCtx>,
+    sessions: Res<Sessions>,
+    storage: Res<Storage>,
+    mut flash: ResMut<ScreenFlash>,
+) {
+    let meta = &meta.post_processing;
+    let Some(game_session) = sessions.get(SessionNames::GAME) else {
+        *flash = default();
+        return;
+    };
+    let world = &game_session.world;
+    let (Some(damage_events), Some(center)) =
+        (world.resources.get::<DamageEvents>(), camera_center(world))
+    else {
+        return;
+    };
+
+    flash.age = flash.age.saturating_add(1);
+    let flash_radius = meta.flash_radius.max(1.0);
+    for event in damage_events.events.iter().filter(|event| event.age == 0) {
+        let closeness = 1.0 - (event.pos - center).length() / flash_radius;
+        let opacity = meta.flash_opacity.min(1.0) * closeness;
+        let current = flash.opacity * (1.0 - flash.age as f32 / meta.flash_frames.max(1) as f32);
+        if opacity > current {
+            *flash = ScreenFlash { opacity, age: 0 };
+        }
+    }
+
+    if !storage.get::<Settings>().is_some_and(|x| x.screen_flash)
+        || is_reduced_motion(&storage)
+        || flash.age >= meta.flash_frames
+    {
+        return;
+    }
+    let fade = 1.0 - flash.age as f32 / meta.flash_frames as f32;
+    effects_painter(&ctx).rect_filled(
+        ctx.screen_rect(),
+        0.0,
+        egui::Color32::WHITE.gamma_multiply(flash.opacity * fade),
+    );
+}
@@ -34,7 +34,8 @@ impl ScoringMenuState {
 struct PlayerScoreInfo {
     pub entity: Entity,
     pub player_idx: PlayerIdx,
-    pub score: u32,
+    /// The player's score, which may be negative from penalties.
+    pub score: i32,
 }
 
 const SCORING_MESSAGE_MAGIC: u8 = 183;
@@ -74,6 +75,8 @@ fn scoring_menu_system(
     ctx: Res<EguiCtx>,
     mut state: ResMut<ScoringMenuState>,
     controls: Res<GlobalPlayerControls>,
+    #[cfg(not(target_arch = "wasm32"))] mut chat: ResMut<super::chat::ChatState>,
+    #[cfg(not(target_arch = "wasm32"))] mut ping: ResMut<super::ping::NetworkPing>,
     world: &World,
 ) {
     if !state.active {
@@ -113,7 +116,7 @@ fn scoring_menu_system(
         // contains player with highest score. If players are tied, tied players are all included.
         let mut winning_players = Vec::<PlayerIdx>::default();
 
-        let win_threshold = meta.core.config.winning_score_threshold;
+        let win_threshold = meta.core.config.winning_score_threshold as i32;
         let mut highest_score = 0;
 
         for score_info in player_score_info.iter() {
@@ -162,7 +165,7 @@ fn scoring_menu_system(
 
         #[cfg(not(target_arch = "wasm32"))]
         if let Some(socket) = network_socket.as_ref() {
-            handle_scoring_messages(socket, &mut state);
+            handle_scoring_messages(socket, &mut state, &mut chat, &mut ping);
         }
 
         // Check if all non-ai players are ready
@@ -384,7 +387,11 @@ fn player_score_panel(
                             localization.get("score"),
                             player_score_info.score,
                         ))
-                        .color(meta.theme.panel.font_color),
+                        .color(if player_score_info.score < 0 {
+                            meta.theme.colors.negative
+                        } else {
+                            meta.theme.panel.font_color
+                        }),
                 );
 
                 if !player_input.is_ai {
@@ -417,9 +424,12 @@ fn player_score_panel(
 fn handle_scoring_messages(
     network_socket: &(impl NetworkSocket + ?Sized),
     state: &mut ScoringMenuState,
+    chat: &mut super::chat::ChatState,
+    ping: &mut super::ping::NetworkPing,
 ) {
     // TODO handle disconnects
-    let datas: Vec<(u32, Vec<u8>)> = network_socket.recv_reliable();
+    let datas: Vec<(u32, Vec<u8>)> =
+        ping.filter_messages(chat.filter_messages(network_socket.recv_reliable()));
     let local_player_idx = network_socket.player_idx();
     for (_, data) in datas {
         match postcard::from_bytes::<ScoringMessage>(&data) {
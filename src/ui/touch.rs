@@ -0,0 +1,236 @@
+//! On-screen controls for touch devices.
+//!
+//! Once the screen has been touched, a virtual joystick and action buttons are drawn over the game.
+//! Touching them controls the same player as the first keyboard, so touch players can join matches
+//! and play them like any keyboard player. The layout and opacity can be changed in the settings.
+
+use crate::prelude::*;
+
+pub fn game_plugin(game: &mut Game) {
+    game.init_shared_resource::<TouchControls>();
+}
+
+pub fn session_plugin(session: &mut SessionBuilder) {
+    session.add_system_to_stage(Update, touch_controls);
+}
+
+/// The radius of the joystick at the default size, in points.
+const JOYSTICK_RADIUS: f32 = 60.0;
+/// The radius of the action buttons at the default size, in points.
+const BUTTON_RADIUS: f32 = 32.0;
+/// The distance between the controls and the edges of the screen, at the default size.
+const MARGIN: f32 = 24.0;
+
+/// The inputs given through the touch controls.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TouchInput {
+    pub move_direction: Vec2,
+    pub jump: bool,
+    pub shoot: bool,
+    pub grab: bool,
+    pub slide: bool,
+    pub pause: bool,
+}
+
+/// Shared resource with the state of the touch controls.
+#[derive(HasSchema, Clone, Debug, Default)]
+pub struct TouchControls {
+    /// The touches that are on the screen, and where they are.
+    touches: HashMap<egui::TouchId, egui::Pos2>,
+    /// The touch that is moving the joystick.
+    joystick_touch: Option<egui::TouchId>,
+    /// Whether the screen has been touched, after which the controls are shown.
+    is_touch_device: bool,
+    /// The inputs from the current touches.
+    pub input: TouchInput,
+}
+
+#[derive(Clone, Copy)]
+enum TouchButton {
+    Jump,
+    Shoot,
+    Grab,
+    Slide,
+    Pause,
+}
+
+/// Where the touch controls are on the screen.
+struct TouchLayout {
+    joystick_center: egui::Pos2,
+    joystick_radius: f32,
+    buttons: [(TouchButton, egui::Pos2, f32); 5],
+}
+
+impl TouchLayout {
+    fn new(screen: egui::Rect, settings: &Settings) -> Self {
+        let size = settings.touch_controls.size.max(0.25);
+        let margin = MARGIN * size;
+        let joystick_radius = JOYSTICK_RADIUS * size;
+        let button_radius = BUTTON_RADIUS * size;
+
+        // Positions for the right-handed layout, mirrored when the sides are swapped
+        let mirror = |pos: egui::Pos2| {
+            if settings.touch_controls.swap_sides {
+                egui::pos2(screen.right() - (pos.x - screen.left()), pos.y)
+            } else {
+                pos
+            }
+        };
+        let joystick_center = mirror(egui::pos2(
+            screen.left() + margin + joystick_radius,
+            screen.bottom() - margin - joystick_radius,
+        ));
+        let button_pos = |column: f32, row: f32| {
+            mirror(egui::pos2(
+                screen.right() - margin - button_radius * (1.0 + column * 2.4),
+                screen.bottom() - margin - button_radius * (1.0 + row * 2.4),
+            ))
+        };
+
+        Self {
+            joystick_center,
+            joystick_radius,
+            buttons: [
+                (TouchButton::Jump, button_pos(0.0, 0.0), button_radius),
+                (TouchButton::Shoot, button_pos(1.0, 0.5), button_radius),
+                (TouchButton::Grab, button_pos(0.0, 1.0), button_radius),
+                (TouchButton::Slide, button_pos(2.0, 0.0), button_radius),
+                (
+                    TouchButton::Pause,
+                    egui::pos2(
+                        screen.center().x,
+                        screen.top() + margin + button_radius / 2.0,
+                    ),
+                    button_radius / 2.0,
+                ),
+            ],
+        }
+    }
+}
+
+impl TouchControls {
+    /// Track the touches that happened since the last frame, and work out the inputs from them.
+    fn update(&mut self, events: &[egui::Event], layout: &TouchLayout) {
+        for event in events {
+            let egui::Event::Touch { id, phase, pos, .. } = event else {
+                continue;
+            };
+            self.is_touch_device = true;
+            match phase {
+                egui::TouchPhase::Start => {
+                    self.touches.insert(*id, *pos);
+                    let grabs_joystick =
+                        pos.distance(layout.joystick_center) < layout.joystick_radius * 1.5;
+                    if grabs_joystick && self.joystick_touch.is_none() {
+                        self.joystick_touch = Some(*id);
+                    }
+                }
+                egui::TouchPhase::Move => {
+                    self.touches.insert(*id, *pos);
+                }
+                egui::TouchPhase::End | egui::TouchPhase::Cancel => {
+                    self.touches.remove(id);
+                    if self.joystick_touch == Some(*id) {
+                        self.joystick_touch = None;
+                    }
+                }
+            }
+        }
+
+        let mut input = TouchInput::default();
+        if let Some(pos) = self.joystick_touch.and_then(|x| self.touches.get(&x)) {
+            let offset = (*pos - layout.joystick_center) / layout.joystick_radius;
+            // Screen positions are y-down
+            input.move_direction = vec2(offset.x, -offset.y).clamp_length_max(1.0);
+        }
+        for (id, pos) in &self.touches {
+            if Some(*id) == self.joystick_touch {
+                continue;
+            }
+            for (button, center, radius) in &layout.buttons {
+                if pos.distance(*center) > *radius {
+                    continue;
+                }
+                match button {
+                    TouchButton::Jump => input.jump = true,
+                    TouchButton::Shoot => input.shoot = true,
+                    TouchButton::Grab => input.grab = true,
+                    TouchButton::Slide => input.slide = true,
+                    TouchButton::Pause => input.pause = true,
+                }
+            }
+        }
+        self.input = input;
+    }
+}
+
+/// Read the touches on the screen, and draw the touch controls.
+fn touch_controls(
+    ctx: Res<EguiCtx>,
+    storage: Res<Storage>,
+    localization: Localization<GameMeta>,
+    mut touch: ResMut<TouchControls>,
+) {
+    let settings = storage.get::<Settings>().cloned().unwrap_or_default();
+    if !settings.touch_controls.enabled {
+        *touch = default();
+        return;
+    }
+
+    let layout = TouchLayout::new(ctx.screen_rect(), &settings);
+    let events = ctx.input(|i| i.events.clone());
+    touch.update(&events, &layout);
+    if !touch.is_touch_device {
+        return;
+    }
+
+    let opacity = settings.touch_controls.opacity.clamp(0.0, 1.0);
+    let fill = |pressed: bool| {
+        egui::Color32::from_white_alpha(((if pressed { 160.0 } else { 70.0 }) * opacity) as u8)
+    };
+    let stroke = egui::Stroke::new(
+        2.0,
+        egui::Color32::from_white_alpha((200.0 * opacity) as u8),
+    );
+    let text_color = egui::Color32::from_black_alpha((220.0 * opacity) as u8);
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("touch-controls"),
+    ));
+
+    // Joystick
+    let input = touch.input;
+    painter.circle(
+        layout.joystick_center,
+        layout.joystick_radius,
+        fill(false),
+        stroke,
+    );
+    let knob_offset =
+        egui::vec2(input.move_direction.x, -input.move_direction.y) * layout.joystick_radius * 0.6;
+    painter.circle(
+        layout.joystick_center + knob_offset,
+        layout.joystick_radius * 0.4,
+        fill(input.move_direction != Vec2::ZERO),
+        stroke,
+    );
+
+    // Buttons
+    for (button, center, radius) in &layout.buttons {
+        let (pressed, label) = match button {
+            TouchButton::Jump => (input.jump, "touch-jump"),
+            TouchButton::Shoot => (input.shoot, "touch-shoot"),
+            TouchButton::Grab => (input.grab, "touch-grab"),
+            TouchButton::Slide => (input.slide, "touch-slide"),
+            TouchButton::Pause => (input.pause, "touch-pause"),
+        };
+        painter.circle(*center, *radius, fill(pressed), stroke);
+        painter.text(
+            *center,
+            egui::Align2::CENTER_CENTER,
+            localization.get(label),
+            egui::FontId::proportional(*radius * 0.5),
+            text_color,
+        );
+    }
+}